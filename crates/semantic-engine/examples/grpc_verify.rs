@@ -52,6 +52,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             namespace: "test_verification".to_string(),
             strategy: ReasoningStrategy::Rdfs as i32,
             materialize: false,
+            background: false,
+            rules: String::new(),
         })
         .await?;
     println!("Reasoning Result: {:?}", reasoning_response.into_inner());
@@ -66,6 +68,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             graph_depth: 1,
             mode: SearchMode::Hybrid as i32,
             limit: 10,
+            min_score: 0.0,
+            rerank: false,
+            pagerank_weight: 0.0,
         })
         .await?;
 
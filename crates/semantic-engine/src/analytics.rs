@@ -0,0 +1,606 @@
+//! Graph analytics over a namespace's topology mirror.
+
+use crate::topology::{is_resource, GraphTopology};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+
+/// Per-node results of the latest analytics runs, keyed by node in N-Triples form
+#[derive(Debug, Default)]
+pub struct NodeAnalytics {
+    pub pagerank: HashMap<String, f64>,
+    /// Community ID of each resource
+    pub communities: HashMap<String, u32>,
+    pub centrality: HashMap<String, Centrality>,
+}
+
+/// How `pagerank` iterates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRankOptions {
+    /// Probability of following an edge rather than jumping to a random node
+    pub damping: f64,
+    pub max_iterations: usize,
+    /// Stop once the scores change by less than this in total
+    pub tolerance: f64,
+}
+
+impl Default for PageRankOptions {
+    fn default() -> Self {
+        Self {
+            damping: 0.85,
+            max_iterations: 100,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// Scores from `pagerank`
+#[derive(Debug, Clone, Default)]
+pub struct PageRank {
+    pub scores: HashMap<String, f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Resource IDs and nodes, with the distinct resource IDs each is linked to either way
+type ResourceGraph<'a> = (Vec<(u32, &'a str)>, HashMap<u32, Vec<u32>>);
+
+/// Resources with their IDs, and the distinct resources each is linked to either way
+fn resource_graph(topology: &GraphTopology) -> ResourceGraph<'_> {
+    let resources: Vec<(u32, &str)> = topology.nodes().filter(|(_, n)| is_resource(n)).collect();
+    let mut included = vec![false; topology.id_bound()];
+    for &(id, _) in &resources {
+        included[id as usize] = true;
+    }
+    let neighbors = resources
+        .iter()
+        .map(|&(id, _)| {
+            let mut linked: Vec<u32> = topology
+                .successors(id)
+                .chain(topology.predecessors(id))
+                .filter(|&n| n != id && included[n as usize])
+                .collect();
+            linked.sort_unstable();
+            linked.dedup();
+            (id, linked)
+        })
+        .collect();
+    (resources, neighbors)
+}
+
+/// PageRank over the edges between resources, with scores summing to 1. Nodes
+/// without outgoing edges spread their score over every node.
+pub fn pagerank(topology: &GraphTopology, options: &PageRankOptions) -> PageRank {
+    let resources: Vec<(u32, &str)> = topology.nodes().filter(|(_, n)| is_resource(n)).collect();
+    if resources.is_empty() {
+        return PageRank {
+            converged: true,
+            ..Default::default()
+        };
+    }
+    let mut included = vec![false; topology.id_bound()];
+    for &(id, _) in &resources {
+        included[id as usize] = true;
+    }
+    let targets: HashMap<u32, Vec<u32>> = resources
+        .iter()
+        .map(|&(id, _)| {
+            let targets = topology
+                .successors(id)
+                .filter(|&t| included[t as usize])
+                .collect();
+            (id, targets)
+        })
+        .collect();
+
+    let n = resources.len() as f64;
+    let d = options.damping;
+    let mut rank = vec![0.0; topology.id_bound()];
+    for &(id, _) in &resources {
+        rank[id as usize] = 1.0 / n;
+    }
+
+    let mut result = PageRank::default();
+    for iteration in 1..=options.max_iterations {
+        let dangling: f64 = resources
+            .iter()
+            .filter(|(id, _)| targets[id].is_empty())
+            .map(|&(id, _)| rank[id as usize])
+            .sum();
+        let base = (1.0 - d) / n + d * dangling / n;
+        let mut next = vec![0.0; topology.id_bound()];
+        for &(id, _) in &resources {
+            next[id as usize] += base;
+            let out = &targets[&id];
+            if !out.is_empty() {
+                let share = d * rank[id as usize] / out.len() as f64;
+                for &t in out {
+                    next[t as usize] += share;
+                }
+            }
+        }
+
+        let delta: f64 = resources
+            .iter()
+            .map(|&(id, _)| (next[id as usize] - rank[id as usize]).abs())
+            .sum();
+        rank = next;
+        result.iterations = iteration;
+        if delta < options.tolerance {
+            result.converged = true;
+            break;
+        }
+    }
+
+    result.scores = resources
+        .into_iter()
+        .map(|(id, node)| (node.to_string(), rank[id as usize]))
+        .collect();
+    result
+}
+
+/// One edge of a path, as stated in the graph (terms in N-Triples form)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathEdge {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+}
+
+/// A path between two nodes from `shortest_path`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphPath {
+    /// From the start node to the end node, both included
+    pub nodes: Vec<String>,
+    /// `edges[i]` links `nodes[i]` and `nodes[i + 1]`, in either direction
+    pub edges: Vec<PathEdge>,
+}
+
+/// An edge by node IDs: (subject, predicate, object)
+type Step<'a> = (u32, &'a str, u32);
+
+/// Shortest path of at most `max_depth` edges between two nodes (in N-Triples
+/// form), following edges either way, through resources only. With
+/// `predicate_filter`, only predicates whose IRI contains it are followed.
+/// Searches from both ends at once, always growing the smaller frontier.
+pub fn shortest_path(
+    topology: &GraphTopology,
+    from: &str,
+    to: &str,
+    max_depth: usize,
+    predicate_filter: Option<&str>,
+) -> Option<GraphPath> {
+    let (from, to) = (topology.node_id(from)?, topology.node_id(to)?);
+    if from == to {
+        return Some(GraphPath {
+            nodes: vec![topology.node(from)?.to_string()],
+            edges: Vec::new(),
+        });
+    }
+    let steps = |id: u32| -> Vec<(u32, Step)> {
+        topology
+            .out_edges(id)
+            .map(|(p, o)| (o, (id, p, o)))
+            .chain(topology.in_edges(id).map(|(p, s)| (s, (s, p, id))))
+            .filter(|(_, (_, p, _))| predicate_filter.is_none_or(|f| p.contains(f)))
+            .filter(|&(n, _)| n == to || n == from || topology.node(n).is_some_and(is_resource))
+            .collect()
+    };
+
+    // Each side maps the nodes it reached to the edge it reached them by
+    let mut forward: HashMap<u32, Option<Step>> = HashMap::from([(from, None)]);
+    let mut backward: HashMap<u32, Option<Step>> = HashMap::from([(to, None)]);
+    let mut forward_frontier = vec![from];
+    let mut backward_frontier = vec![to];
+    for _ in 0..max_depth {
+        let (frontier, reached, other) = if forward_frontier.len() <= backward_frontier.len() {
+            (&mut forward_frontier, &mut forward, &backward)
+        } else {
+            (&mut backward_frontier, &mut backward, &forward)
+        };
+        let mut next = Vec::new();
+        let mut meeting = None;
+        'layer: for &node in frontier.iter() {
+            for (n, step) in steps(node) {
+                if reached.contains_key(&n) {
+                    continue;
+                }
+                reached.insert(n, Some(step));
+                if other.contains_key(&n) {
+                    meeting = Some(n);
+                    break 'layer;
+                }
+                next.push(n);
+            }
+        }
+        if let Some(meeting) = meeting {
+            return join_path(topology, meeting, &forward, &backward);
+        }
+        if next.is_empty() {
+            return None;
+        }
+        *frontier = next;
+    }
+    None
+}
+
+/// Walk both searches back from the node where they met
+fn join_path<'a>(
+    topology: &GraphTopology,
+    meeting: u32,
+    forward: &HashMap<u32, Option<Step<'a>>>,
+    backward: &HashMap<u32, Option<Step<'a>>>,
+) -> Option<GraphPath> {
+    let walk = |reached: &HashMap<u32, Option<Step<'a>>>| -> Vec<(Step<'a>, u32)> {
+        let mut node = meeting;
+        let mut walked = Vec::new();
+        while let Some(Some(step)) = reached.get(&node) {
+            let previous = if step.2 == node { step.0 } else { step.2 };
+            walked.push((*step, previous));
+            node = previous;
+        }
+        walked
+    };
+    let mut head = walk(forward);
+    head.reverse();
+    let tail = walk(backward);
+
+    let name = |id: u32| topology.node(id).map(str::to_string);
+    let mut path = GraphPath::default();
+    for &(_, previous) in &head {
+        path.nodes.push(name(previous)?);
+    }
+    path.nodes.push(name(meeting)?);
+    for &(_, next) in &tail {
+        path.nodes.push(name(next)?);
+    }
+    for &((s, p, o), _) in head.iter().chain(&tail) {
+        path.edges.push(PathEdge {
+            subject: name(s)?,
+            predicate: p.to_string(),
+            object: name(o)?,
+        });
+    }
+    Some(path)
+}
+
+/// How `communities` groups resources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommunityAlgorithm {
+    /// Resources linked by any chain of edges, either way
+    ConnectedComponents,
+    /// Each resource repeatedly joins the community most of its neighbors are in
+    LabelPropagation,
+}
+
+impl CommunityAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConnectedComponents => "connected_components",
+            Self::LabelPropagation => "label_propagation",
+        }
+    }
+}
+
+impl std::str::FromStr for CommunityAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "connected_components" => Ok(Self::ConnectedComponents),
+            "label_propagation" => Ok(Self::LabelPropagation),
+            _ => anyhow::bail!("Unknown community algorithm: {}", s),
+        }
+    }
+}
+
+/// Resources grouped by `communities`
+#[derive(Debug, Clone, Default)]
+pub struct Communities {
+    /// Community ID of each resource; IDs rank communities by size, from 0
+    pub assignments: HashMap<String, u32>,
+    /// Members of each community by ID, most connected first
+    pub members: Vec<Vec<String>>,
+    pub iterations: usize,
+}
+
+/// Seed for label propagation's tie-breaking and centrality's sampling, so runs
+/// over the same graph agree
+const ANALYTICS_SEED: u64 = 0x5eed;
+
+/// Group the resources of a graph into communities, following edges either way
+pub fn communities(
+    topology: &GraphTopology,
+    algorithm: CommunityAlgorithm,
+    max_iterations: usize,
+) -> Communities {
+    let (resources, neighbors) = resource_graph(topology);
+    let mut label: HashMap<u32, u32> = HashMap::new();
+    let mut iterations = 0;
+    match algorithm {
+        CommunityAlgorithm::ConnectedComponents => {
+            for &(id, _) in &resources {
+                if label.contains_key(&id) {
+                    continue;
+                }
+                label.insert(id, id);
+                let mut queue = vec![id];
+                while let Some(node) = queue.pop() {
+                    for &n in &neighbors[&node] {
+                        if let std::collections::hash_map::Entry::Vacant(entry) = label.entry(n) {
+                            entry.insert(id);
+                            queue.push(n);
+                        }
+                    }
+                }
+            }
+            iterations = 1;
+        }
+        CommunityAlgorithm::LabelPropagation => {
+            label = resources.iter().map(|&(id, _)| (id, id)).collect();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(ANALYTICS_SEED);
+            let mut order: Vec<u32> = resources.iter().map(|&(id, _)| id).collect();
+            for iteration in 1..=max_iterations {
+                iterations = iteration;
+                order.shuffle(&mut rng);
+                let mut changed = false;
+                for &id in &order {
+                    let mut counts: HashMap<u32, usize> = HashMap::new();
+                    for n in &neighbors[&id] {
+                        *counts.entry(label[n]).or_default() += 1;
+                    }
+                    let Some(&most) = counts.values().max() else {
+                        continue;
+                    };
+                    // Stay put on a tie, otherwise pick one of the most common at random
+                    if counts.get(&label[&id]) == Some(&most) {
+                        continue;
+                    }
+                    let mut best: Vec<u32> = counts
+                        .into_iter()
+                        .filter(|&(_, c)| c == most)
+                        .map(|(l, _)| l)
+                        .collect();
+                    best.sort_unstable();
+                    label.insert(id, best[rng.random_range(0..best.len())]);
+                    changed = true;
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<u32, Vec<&str>> = HashMap::new();
+    for &(id, node) in &resources {
+        groups.entry(label[&id]).or_default().push(node);
+    }
+    let mut members: Vec<Vec<String>> = groups
+        .into_values()
+        .map(|mut group| {
+            group.sort_by(|a, b| topology.degree(b).cmp(&topology.degree(a)).then(a.cmp(b)));
+            group.into_iter().map(str::to_string).collect()
+        })
+        .collect();
+    members.sort_by(|a: &Vec<String>, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    let assignments = members
+        .iter()
+        .enumerate()
+        .flat_map(|(id, group)| group.iter().map(move |node| (node.clone(), id as u32)))
+        .collect();
+    Communities {
+        assignments,
+        members,
+        iterations,
+    }
+}
+
+/// How central a resource is, from `centrality`; each measure is in [0, 1]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Centrality {
+    /// Resources linked to it, as a fraction of all other resources
+    pub degree: f64,
+    /// Share of the shortest paths between other resources that pass through it
+    pub betweenness: f64,
+    /// Harmonic closeness: the mean of 1 / distance to every other resource
+    pub closeness: f64,
+}
+
+/// Degree, betweenness and closeness centrality of every resource, following edges
+/// either way. Betweenness and closeness are estimated from the shortest paths out
+/// of `samples` resources picked at random, and exact when `samples` covers them all.
+pub fn centrality(topology: &GraphTopology, samples: usize) -> HashMap<String, Centrality> {
+    let (resources, neighbors) = resource_graph(topology);
+    let n = resources.len();
+    let index: HashMap<u32, usize> = resources
+        .iter()
+        .enumerate()
+        .map(|(i, &(id, _))| (id, i))
+        .collect();
+    let adjacency: Vec<Vec<usize>> = resources
+        .iter()
+        .map(|(id, _)| neighbors[id].iter().map(|n| index[n]).collect())
+        .collect();
+
+    let mut sources: Vec<usize> = (0..n).collect();
+    if samples < n {
+        sources.shuffle(&mut rand::rngs::StdRng::seed_from_u64(ANALYTICS_SEED));
+        sources.truncate(samples.max(1));
+    }
+    let mut betweenness = vec![0.0; n];
+    let mut harmonic = vec![0.0; n];
+    for &source in &sources {
+        // Brandes: count shortest paths breadth-first, then credit them back
+        let mut order = Vec::with_capacity(n);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0; n];
+        let mut distance = vec![usize::MAX; n];
+        paths[source] = 1.0;
+        distance[source] = 0;
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in &adjacency[v] {
+                if distance[w] == usize::MAX {
+                    distance[w] = distance[v] + 1;
+                    queue.push_back(w);
+                }
+                if distance[w] == distance[v] + 1 {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+        let mut dependency = vec![0.0; n];
+        for &w in order.iter().rev() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != source {
+                betweenness[w] += dependency[w];
+                harmonic[w] += 1.0 / distance[w] as f64;
+            }
+        }
+    }
+
+    let scale = n as f64 / sources.len().max(1) as f64;
+    let others = n.saturating_sub(1).max(1) as f64;
+    // Each pair of other resources is counted from both ends
+    let pairs = (n.saturating_sub(1) * n.saturating_sub(2)).max(1) as f64;
+    resources
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, node))| {
+            let measures = Centrality {
+                degree: adjacency[i].len() as f64 / others,
+                betweenness: (betweenness[i] * scale / pairs).min(1.0),
+                closeness: (harmonic[i] * scale / others).min(1.0),
+            };
+            (node.to_string(), measures)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagerank_favors_linked_nodes() {
+        let mut topology = GraphTopology::new();
+        for source in ["<a>", "<b>", "<c>"] {
+            topology.add_edge(source, "<cites>", "<hub>");
+        }
+        topology.add_edge("<hub>", "<cites>", "<a>");
+        topology.add_edge("<hub>", "<label>", "\"Hub\"");
+        topology.add_edge("<hub>", "<asserts>", "<< <a> <cites> <hub> >>");
+
+        let result = pagerank(&topology, &PageRankOptions::default());
+        assert!(result.converged);
+        assert_eq!(result.scores.len(), 4);
+        assert!(!result.scores.contains_key("\"Hub\""));
+        assert!(!result.scores.contains_key("<< <a> <cites> <hub> >>"));
+        let total: f64 = result.scores.values().sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        let score = |node: &str| result.scores[node];
+        assert!(score("<hub>") > score("<a>"));
+        assert!(score("<a>") > score("<b>"));
+        assert!((score("<b>") - score("<c>")).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shortest_path_follows_edges_both_ways() {
+        let mut topology = GraphTopology::new();
+        topology.add_edge("<a>", "<knows>", "<b>");
+        topology.add_edge("<c>", "<knows>", "<b>");
+        topology.add_edge("<c>", "<worksAt>", "<d>");
+        topology.add_edge("<a>", "<name>", "\"A\"");
+        topology.add_edge("<d>", "<name>", "\"A\"");
+        topology.add_edge("<a>", "<likes>", "<x>");
+        topology.add_edge("<x>", "<likes>", "<y>");
+        topology.add_edge("<y>", "<likes>", "<z>");
+        topology.add_edge("<z>", "<likes>", "<d>");
+
+        let path = shortest_path(&topology, "<a>", "<d>", 6, None).unwrap();
+        assert_eq!(path.nodes, vec!["<a>", "<b>", "<c>", "<d>"]);
+        assert_eq!(
+            path.edges[1],
+            PathEdge {
+                subject: "<c>".into(),
+                predicate: "<knows>".into(),
+                object: "<b>".into(),
+            }
+        );
+        // Literals do not connect resources
+        assert!(shortest_path(&topology, "<a>", "<d>", 2, None).is_none());
+        let liked = shortest_path(&topology, "<a>", "<d>", 6, Some("likes")).unwrap();
+        assert_eq!(liked.nodes.len(), 5);
+        assert_eq!(liked.edges.len(), 4);
+        assert_eq!(liked.edges[3].object, "<d>");
+        assert!(shortest_path(&topology, "<a>", "<d>", 3, Some("likes")).is_none());
+        assert_eq!(
+            shortest_path(&topology, "<b>", "<b>", 1, None)
+                .unwrap()
+                .nodes,
+            vec!["<b>"]
+        );
+        assert!(shortest_path(&topology, "<a>", "<missing>", 6, None).is_none());
+    }
+
+    #[test]
+    fn test_communities_split_loosely_linked_clusters() {
+        let mut topology = GraphTopology::new();
+        for cluster in ["a", "b"] {
+            let nodes: Vec<String> = (0..4).map(|i| format!("<{}{}>", cluster, i)).collect();
+            for (i, s) in nodes.iter().enumerate() {
+                for o in &nodes[i + 1..] {
+                    topology.add_edge(s, "<knows>", o);
+                }
+            }
+        }
+        topology.add_edge("<a0>", "<knows>", "<b0>");
+        topology.add_edge("<loner>", "<name>", "\"Loner\"");
+
+        let components = communities(&topology, CommunityAlgorithm::ConnectedComponents, 0);
+        assert_eq!(components.members.len(), 2);
+        assert_eq!(components.members[0].len(), 8);
+        assert_eq!(components.members[0][0], "<a0>");
+        assert_eq!(components.assignments["<loner>"], 1);
+        assert!(!components.assignments.contains_key("\"Loner\""));
+
+        let propagated = communities(&topology, CommunityAlgorithm::LabelPropagation, 20);
+        assert_eq!(propagated.members.len(), 3);
+        let community = |node: &str| propagated.assignments[node];
+        assert_eq!(community("<a1>"), community("<a3>"));
+        assert_eq!(community("<b1>"), community("<b3>"));
+        assert_ne!(community("<a1>"), community("<b1>"));
+        assert_eq!(community("<loner>"), 2);
+    }
+
+    #[test]
+    fn test_centrality_of_a_star() {
+        let mut topology = GraphTopology::new();
+        for leaf in ["<a>", "<b>", "<c>", "<d>"] {
+            topology.add_edge("<hub>", "<links>", leaf);
+        }
+        topology.add_edge("<a>", "<links>", "<hub>");
+
+        let exact = centrality(&topology, usize::MAX);
+        let hub = exact["<hub>"];
+        assert_eq!(hub.degree, 1.0);
+        assert!((hub.betweenness - 1.0).abs() < 1e-9);
+        assert!((hub.closeness - 1.0).abs() < 1e-9);
+        let leaf = exact["<b>"];
+        assert_eq!(leaf.degree, 0.25);
+        assert_eq!(leaf.betweenness, 0.0);
+        // One neighbor at distance 1, three at distance 2
+        assert!((leaf.closeness - 2.5 / 4.0).abs() < 1e-9);
+
+        let sampled = centrality(&topology, 2);
+        assert_eq!(sampled.len(), 5);
+        assert!(sampled
+            .values()
+            .all(|c| c.betweenness <= sampled["<hub>"].betweenness));
+    }
+}
@@ -1,7 +1,36 @@
+use crate::reasoner::RuleStats;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::RwLock;
+use std::time::Duration;
+
+/// Queries slower than this are flagged unless `SYNAPSE_SLOW_QUERY_MS` says otherwise
+const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// Longest query text kept in a record
+const MAX_RECORDED_QUERY_LEN: usize = 4096;
+
+tokio::task_local! {
+    /// Set while running work whose call was already recorded, such as an MCP
+    /// tool call forwarded to the gRPC handlers
+    static RECORDED: ();
+}
+
+/// Run `work` without recording its mutations again
+pub async fn already_recorded<F: Future>(work: F) -> F::Output {
+    RECORDED.scope((), work).await
+}
+
+/// How a caller appears in the audit trail: `token:<fingerprint>`, or `fallback`
+/// ("anonymous", "mcp-session") without a token
+pub fn actor(token: Option<&str>, fallback: &str) -> String {
+    match token {
+        Some(token) => format!("token:{}", crate::auth::token_fingerprint(token)),
+        None => fallback.to_string(),
+    }
+}
 
 /// Record of an inference operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,12 +42,47 @@ pub struct InferenceRecord {
     pub inferred_triples: usize,
     pub duplicates_skipped: usize,
     pub sample_inferences: Vec<(String, String, String)>,
+    /// Per-rule breakdown of the inferred triples
+    #[serde(default)]
+    pub rule_stats: Vec<RuleStats>,
+}
+
+/// A write, delete or reasoning call and who made it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MutationRecord {
+    pub timestamp: DateTime<Utc>,
+    pub namespace: String,
+    pub actor: String,
+    /// RPC or MCP tool name
+    pub action: String,
+}
+
+/// An executed SPARQL query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRecord {
+    pub timestamp: DateTime<Utc>,
+    pub namespace: String,
+    pub actor: String,
+    /// Query text, cut to `MAX_RECORDED_QUERY_LEN` bytes
+    pub query: String,
+    pub duration_ms: u64,
+    /// Solutions returned
+    pub results: usize,
+    /// Took at least the slow-query threshold
+    pub slow: bool,
 }
 
-/// Audit trail for tracking inference operations
+/// Audit trail for tracking inference operations and mutations
 pub struct InferenceAudit {
     /// Namespace -> inference records
     records: RwLock<HashMap<String, Vec<InferenceRecord>>>,
+    /// Namespace -> mutation records
+    mutations: RwLock<HashMap<String, Vec<MutationRecord>>>,
+    /// Namespace -> query records, and the slow ones kept apart so fast queries
+    /// never push them out
+    queries: RwLock<HashMap<String, Vec<QueryRecord>>>,
+    slow_queries: RwLock<HashMap<String, Vec<QueryRecord>>>,
+    slow_query_threshold: RwLock<Duration>,
     /// Maximum records per namespace
     max_records: usize,
 }
@@ -33,6 +97,16 @@ impl InferenceAudit {
     pub fn new() -> Self {
         Self {
             records: RwLock::new(HashMap::new()),
+            mutations: RwLock::new(HashMap::new()),
+            queries: RwLock::new(HashMap::new()),
+            slow_queries: RwLock::new(HashMap::new()),
+            slow_query_threshold: RwLock::new(
+                std::env::var("SYNAPSE_SLOW_QUERY_MS")
+                    .ok()
+                    .and_then(|ms| ms.parse().ok())
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD),
+            ),
             max_records: 100,
         }
     }
@@ -55,10 +129,45 @@ impl InferenceAudit {
             inferred_triples: inferred,
             duplicates_skipped: skipped,
             sample_inferences: samples.into_iter().take(10).collect(),
+            rule_stats: Vec::new(),
         };
 
+        self.push(record);
+    }
+
+    /// Log an inference operation along with per-rule statistics
+    pub fn log_with_rules(
+        &self,
+        namespace: &str,
+        strategy: &str,
+        input: usize,
+        inferred: usize,
+        rule_stats: Vec<RuleStats>,
+    ) {
+        let samples = rule_stats
+            .iter()
+            .flat_map(|r| r.samples.iter().cloned())
+            .take(10)
+            .collect();
+
+        let record = InferenceRecord {
+            timestamp: Utc::now(),
+            namespace: namespace.to_string(),
+            strategy: strategy.to_string(),
+            input_triples: input,
+            inferred_triples: inferred,
+            duplicates_skipped: 0,
+            sample_inferences: samples,
+            rule_stats,
+        };
+
+        self.push(record);
+    }
+
+    fn push(&self, record: InferenceRecord) {
+        let namespace = record.namespace.clone();
         let mut records = self.records.write().unwrap();
-        let ns_records = records.entry(namespace.to_string()).or_default();
+        let ns_records = records.entry(namespace).or_default();
 
         ns_records.push(record);
 
@@ -68,6 +177,109 @@ impl InferenceAudit {
         }
     }
 
+    /// Attribute a mutating call to `actor`, unless the call was already recorded
+    /// further out (see `already_recorded`)
+    pub fn record_mutation(&self, namespace: &str, actor: &str, action: &str) {
+        if RECORDED.try_with(|_| ()).is_ok() {
+            return;
+        }
+        let record = MutationRecord {
+            timestamp: Utc::now(),
+            namespace: namespace.to_string(),
+            actor: actor.to_string(),
+            action: action.to_string(),
+        };
+        tracing::info!(
+            target: "audit",
+            namespace = %record.namespace,
+            actor = %record.actor,
+            action = %record.action,
+            "Mutation"
+        );
+
+        let mut mutations = self.mutations.write().unwrap();
+        let ns_records = mutations.entry(record.namespace.clone()).or_default();
+        ns_records.push(record);
+        if ns_records.len() > self.max_records {
+            ns_records.remove(0);
+        }
+    }
+
+    pub fn slow_query_threshold(&self) -> Duration {
+        *self.slow_query_threshold.read().unwrap()
+    }
+
+    pub fn set_slow_query_threshold(&self, threshold: Duration) {
+        *self.slow_query_threshold.write().unwrap() = threshold;
+    }
+
+    /// Record an executed SPARQL query; one at or over the slow-query threshold is
+    /// also logged. Returns whether it was slow.
+    pub fn record_query(
+        &self,
+        namespace: &str,
+        actor: &str,
+        query: &str,
+        duration: Duration,
+        results: usize,
+    ) -> bool {
+        let mut end = query.len().min(MAX_RECORDED_QUERY_LEN);
+        while !query.is_char_boundary(end) {
+            end -= 1;
+        }
+        let record = QueryRecord {
+            timestamp: Utc::now(),
+            namespace: namespace.to_string(),
+            actor: actor.to_string(),
+            query: query[..end].to_string(),
+            duration_ms: duration.as_millis() as u64,
+            results,
+            slow: duration >= self.slow_query_threshold(),
+        };
+        if record.slow {
+            tracing::warn!(
+                target: "audit",
+                namespace = %record.namespace,
+                actor = %record.actor,
+                duration_ms = record.duration_ms,
+                results = record.results,
+                "Slow SPARQL query: {}",
+                record.query.split_whitespace().collect::<Vec<_>>().join(" ")
+            );
+            self.push_query(&self.slow_queries, record.clone());
+        }
+        let slow = record.slow;
+        self.push_query(&self.queries, record);
+        slow
+    }
+
+    fn push_query(&self, log: &RwLock<HashMap<String, Vec<QueryRecord>>>, record: QueryRecord) {
+        let mut log = log.write().unwrap();
+        let ns_records = log.entry(record.namespace.clone()).or_default();
+        ns_records.push(record);
+        if ns_records.len() > self.max_records {
+            ns_records.remove(0);
+        }
+    }
+
+    /// Recent queries of a namespace, oldest first
+    pub fn get_queries(&self, namespace: &str) -> Vec<QueryRecord> {
+        let queries = self.queries.read().unwrap();
+        queries.get(namespace).cloned().unwrap_or_default()
+    }
+
+    /// Recent slow queries of a namespace, oldest first
+    pub fn get_slow_queries(&self, namespace: &str) -> Vec<QueryRecord> {
+        let slow_queries = self.slow_queries.read().unwrap();
+        slow_queries.get(namespace).cloned().unwrap_or_default()
+    }
+
+    /// Recent mutations of a namespace, oldest first
+    pub fn get_mutations(&self, namespace: &str) -> Vec<MutationRecord> {
+        let mutations = self.mutations.read().unwrap();
+        mutations.get(namespace).cloned().unwrap_or_default()
+    }
+
     /// Get inference history for a namespace
     pub fn get_history(&self, namespace: &str) -> Vec<InferenceRecord> {
         let records = self.records.read().unwrap();
@@ -1,13 +1,29 @@
-use std::collections::HashMap;
-use std::sync::RwLock;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::jwt::JwtValidator;
+use crate::lockout::AuthLockout;
 
 /// Namespace access control
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
 pub struct NamespacePermission {
     pub read: bool,
     pub write: bool,
     pub delete: bool,
     pub reason: bool,
+    /// SPARQL queries
+    pub query: bool,
+    /// Vector, graph and hybrid search
+    pub search: bool,
+    /// Whole-namespace dumps (export, streaming every triple)
+    pub export: bool,
+    /// Maintenance: compaction, backups, metrics, token management. Only ever
+    /// granted explicitly.
+    pub admin: bool,
 }
 
 impl Default for NamespacePermission {
@@ -17,16 +33,208 @@ impl Default for NamespacePermission {
             write: true,
             delete: true,
             reason: true,
+            query: true,
+            search: true,
+            export: true,
+            admin: false,
+        }
+    }
+}
+
+impl NamespacePermission {
+    /// Every permission, including `admin`
+    pub fn all() -> Self {
+        Self {
+            admin: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Name graph patterns use for a namespace's default graph
+pub const DEFAULT_GRAPH: &str = "default";
+
+/// Graphs a token may read and write (or delete from) within its namespaces, as
+/// patterns: a graph IRI, `prefix*`, `*` or `default`. `None` leaves that side
+/// unrestricted.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct GraphAccess {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write: Option<Vec<String>>,
+}
+
+impl GraphAccess {
+    pub fn is_unrestricted(&self) -> bool {
+        self.read.is_none() && self.write.is_none()
+    }
+
+    pub fn can_read(&self, graph: &str) -> bool {
+        graph_allowed(&self.read, graph)
+    }
+
+    pub fn can_write(&self, graph: &str) -> bool {
+        graph_allowed(&self.write, graph)
+    }
+
+    /// Whether reads may see every graph, as dumps, search and traversal do
+    pub fn can_read_all(&self) -> bool {
+        self.read
+            .as_ref()
+            .is_none_or(|patterns| patterns.iter().any(|p| p == "*"))
+    }
+
+    /// Whether writes may touch any graph, including ones not known up front
+    pub fn can_write_all(&self) -> bool {
+        self.write
+            .as_ref()
+            .is_none_or(|patterns| patterns.iter().any(|p| p == "*"))
+    }
+}
+
+fn graph_allowed(patterns: &Option<Vec<String>>, graph: &str) -> bool {
+    patterns
+        .as_ref()
+        .is_none_or(|patterns| patterns.iter().any(|p| namespace_matches(p, graph)))
+}
+
+/// What a registered token grants, and until when
+#[derive(Debug, Clone)]
+struct TokenEntry {
+    namespaces: Vec<String>,
+    permissions: NamespacePermission,
+    graphs: GraphAccess,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// What callers without a token may do
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnonymousPolicy {
+    /// Namespace patterns open to anonymous callers
+    pub namespaces: Vec<String>,
+    pub permissions: NamespacePermission,
+}
+
+impl AnonymousPolicy {
+    /// Everything but `admin` in the "default" namespace, for local use
+    pub fn full() -> Self {
+        Self {
+            namespaces: vec!["default".to_string()],
+            permissions: NamespacePermission::default(),
+        }
+    }
+
+    /// Everything in every namespace, for commands run on the storage directly
+    pub fn unrestricted() -> Self {
+        Self {
+            namespaces: vec!["*".to_string()],
+            permissions: NamespacePermission::all(),
+        }
+    }
+
+    /// Reads, queries, searches and exports in the "default" namespace
+    pub fn read_only() -> Self {
+        Self {
+            namespaces: vec!["default".to_string()],
+            permissions: NamespacePermission {
+                write: false,
+                delete: false,
+                reason: false,
+                admin: false,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// No anonymous access at all
+    pub fn none() -> Self {
+        Self {
+            namespaces: Vec::new(),
+            permissions: NamespacePermission::default(),
+        }
+    }
+
+    /// Policy from `SYNAPSE_ANONYMOUS`: `full`, `read-only`, `none`, or a token entry
+    /// (`{"namespaces": [...], "permissions": {...}}`, namespaces defaulting to
+    /// "default"). `None` when unset; an invalid value allows nothing.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("SYNAPSE_ANONYMOUS").ok()?;
+        let policy = Self::parse(&value).unwrap_or_else(|| {
+            tracing::warn!(
+                "Invalid SYNAPSE_ANONYMOUS '{}', anonymous access disabled",
+                value
+            );
+            Self::none()
+        });
+        Some(policy)
+    }
+
+    /// Parse a `SYNAPSE_ANONYMOUS` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "full" => return Some(Self::full()),
+            "read-only" | "readonly" | "read" => return Some(Self::read_only()),
+            "none" | "off" => return Some(Self::none()),
+            _ => {}
         }
+        let json: serde_json::Value = serde_json::from_str(value).ok()?;
+        let entry = parse_token_entry(&json).filter(|_| json.is_object())?;
+        Some(Self {
+            namespaces: if json.get("namespaces").is_some() {
+                entry.namespaces
+            } else {
+                vec!["default".to_string()]
+            },
+            permissions: entry.permissions,
+        })
     }
 }
 
 /// Auth layer for namespace-based access control
 pub struct NamespaceAuth {
-    /// Token -> (namespace patterns, permissions)
-    tokens: RwLock<HashMap<String, (Vec<String>, NamespacePermission)>>,
-    /// Allow unauthenticated access to "default" namespace
-    pub allow_anonymous_default: bool,
+    tokens: RwLock<HashMap<String, TokenEntry>>,
+    /// What unauthenticated callers may do
+    anonymous: RwLock<AnonymousPolicy>,
+    /// Tokens that came from the token file, replaced on every reload
+    file_tokens: Mutex<HashSet<String>>,
+    /// Keeps the token file watched for as long as the auth layer lives
+    file_watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    /// Tokens created at runtime, saved to `managed_path`
+    managed_tokens: Mutex<HashSet<String>>,
+    managed_path: Mutex<Option<PathBuf>>,
+    /// Validates bearer tokens that are JWTs from an OIDC issuer
+    jwt: RwLock<Option<Arc<JwtValidator>>>,
+    /// Failed authentication attempts, for locking out credential guessing
+    pub lockout: AuthLockout,
+}
+
+/// A registered token as shown to operators; the secret itself is never listed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenInfo {
+    pub fingerprint: String,
+    pub namespaces: Vec<String>,
+    pub permissions: NamespacePermission,
+    /// "api" (created at runtime), "file" (token file) or "env"
+    pub source: &'static str,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Prefix of the principals client certificates authenticate as: a token entry
+/// named `cert:<identity>` grants its permissions to certificates with that SAN or CN
+pub const CERTIFICATE_PREFIX: &str = "cert:";
+
+/// Whether a token names a certificate principal, which callers may not present as
+/// a bearer token
+pub fn is_certificate_principal(token: &str) -> bool {
+    token.starts_with(CERTIFICATE_PREFIX)
+}
+
+/// Short identifier of a token that does not reveal it
+pub fn token_fingerprint(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(token.as_bytes()))[..16].to_string()
 }
 
 impl Default for NamespaceAuth {
@@ -39,7 +247,13 @@ impl NamespaceAuth {
     pub fn new() -> Self {
         Self {
             tokens: RwLock::new(HashMap::new()),
-            allow_anonymous_default: true,
+            anonymous: RwLock::new(AnonymousPolicy::full()),
+            file_tokens: Mutex::new(HashSet::new()),
+            file_watcher: Mutex::new(None),
+            managed_tokens: Mutex::new(HashSet::new()),
+            managed_path: Mutex::new(None),
+            jwt: RwLock::new(None),
+            lockout: AuthLockout::default(),
         }
     }
 
@@ -51,7 +265,57 @@ impl NamespaceAuth {
         permissions: NamespacePermission,
     ) {
         let mut tokens = self.tokens.write().unwrap();
-        tokens.insert(token.to_string(), (namespaces, permissions));
+        tokens.insert(
+            token.to_string(),
+            TokenEntry {
+                namespaces,
+                permissions,
+                graphs: GraphAccess::default(),
+                expires_at: None,
+            },
+        );
+    }
+
+    /// Make a token stop working at `expires_at` (`None` never expires). Returns
+    /// false for an unknown token.
+    pub fn set_expiry(&self, token: &str, expires_at: Option<DateTime<Utc>>) -> Result<bool> {
+        match self.tokens.write().unwrap().get_mut(token) {
+            Some(entry) => entry.expires_at = expires_at,
+            None => return Ok(false),
+        }
+        if self.managed_tokens.lock().unwrap().contains(token) {
+            self.save_managed()?;
+        }
+        Ok(true)
+    }
+
+    /// Limit the graphs a token may read and write. Returns false for an unknown token.
+    pub fn set_graph_access(&self, token: &str, graphs: GraphAccess) -> Result<bool> {
+        match self.tokens.write().unwrap().get_mut(token) {
+            Some(entry) => entry.graphs = graphs,
+            None => return Ok(false),
+        }
+        if self.managed_tokens.lock().unwrap().contains(token) {
+            self.save_managed()?;
+        }
+        Ok(true)
+    }
+
+    /// The graphs a token may use, once `check` has let it into a namespace;
+    /// anonymous and JWT callers are not limited to graphs
+    pub fn graph_access(&self, token: Option<&str>) -> GraphAccess {
+        token
+            .and_then(|t| self.tokens.read().unwrap().get(t).map(|e| e.graphs.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Replace what callers without a token may do
+    pub fn set_anonymous_policy(&self, policy: AnonymousPolicy) {
+        *self.anonymous.write().unwrap() = policy;
+    }
+
+    pub fn anonymous_policy(&self) -> AnonymousPolicy {
+        self.anonymous.read().unwrap().clone()
     }
 
     /// Check if token has permission for namespace and operation
@@ -61,39 +325,45 @@ impl NamespaceAuth {
         namespace: &str,
         operation: &str,
     ) -> Result<(), String> {
-        // Anonymous access to default namespace
-        if token.is_none() && namespace == "default" && self.allow_anonymous_default {
-            return Ok(());
-        }
-
-        let token = token.ok_or("Authentication required")?;
+        let Some(token) = token else {
+            let anonymous = self.anonymous.read().unwrap();
+            let allowed = anonymous
+                .namespaces
+                .iter()
+                .any(|p| namespace_matches(p, namespace))
+                && check_operation(&anonymous.permissions, operation).is_ok();
+            return if allowed {
+                Ok(())
+            } else {
+                Err("Authentication required".to_string())
+            };
+        };
         let tokens = self.tokens.read().unwrap();
 
-        let (patterns, perms) = tokens.get(token).ok_or("Invalid token")?;
+        let jwt_entry;
+        let entry = match tokens.get(token) {
+            Some(entry) => entry,
+            None => {
+                jwt_entry = self.jwt_entry(token)?;
+                &jwt_entry
+            }
+        };
+        if let Some(expires_at) = entry.expires_at.filter(|t| *t <= Utc::now()) {
+            return Err(format!("Token expired at {}", expires_at.to_rfc3339()));
+        }
+        let perms = &entry.permissions;
 
         // Check namespace pattern match
-        let ns_match = patterns.iter().any(|p| {
-            if p == "*" {
-                true
-            } else if p.ends_with('*') {
-                namespace.starts_with(&p[..p.len() - 1])
-            } else {
-                p == namespace
-            }
-        });
+        let ns_match = entry
+            .namespaces
+            .iter()
+            .any(|p| namespace_matches(p, namespace));
 
         if !ns_match {
             return Err(format!("Token not authorized for namespace: {}", namespace));
         }
 
-        // Check operation permission
-        match operation {
-            "read" if !perms.read => Err("Read permission denied".to_string()),
-            "write" if !perms.write => Err("Write permission denied".to_string()),
-            "delete" if !perms.delete => Err("Delete permission denied".to_string()),
-            "reason" if !perms.reason => Err("Reasoning permission denied".to_string()),
-            _ => Ok(()),
-        }
+        check_operation(perms, operation)
     }
 
     /// Load tokens from environment variable (JSON format)
@@ -102,31 +372,616 @@ impl NamespaceAuth {
             // Try parsing as complex object first: {"token": {"namespaces": [...], "permissions": {...}}}
             if let Ok(map) = serde_json::from_str::<HashMap<String, serde_json::Value>>(&json) {
                 for (token, value) in map {
-                    if let Ok(namespaces) = serde_json::from_value::<Vec<String>>(value.clone()) {
-                        // Legacy format: value is list of namespaces
-                        self.register_token(&token, namespaces, NamespacePermission::default());
-                    } else if let Some(obj) = value.as_object() {
-                        // Complex format
-                        let namespaces = obj
-                            .get("namespaces")
-                            .and_then(|v| serde_json::from_value::<Vec<String>>(v.clone()).ok())
-                            .unwrap_or_default();
-
-                        let permissions = if let Some(p) = obj.get("permissions") {
-                            NamespacePermission {
-                                read: p.get("read").and_then(|v| v.as_bool()).unwrap_or(true),
-                                write: p.get("write").and_then(|v| v.as_bool()).unwrap_or(true),
-                                delete: p.get("delete").and_then(|v| v.as_bool()).unwrap_or(true),
-                                reason: p.get("reason").and_then(|v| v.as_bool()).unwrap_or(true),
-                            }
-                        } else {
-                            NamespacePermission::default()
-                        };
-
-                        self.register_token(&token, namespaces, permissions);
+                    if let Some(entry) = parse_token_entry(&value) {
+                        self.tokens.write().unwrap().insert(token, entry);
                     }
                 }
             }
         }
     }
+
+    /// Load a token file (TOML, YAML or JSON, picked by extension) holding a
+    /// `tokens` table in the `SYNAPSE_AUTH_TOKENS` format. Tokens from a previous
+    /// load of the file are replaced as a whole; other tokens are kept. Returns the
+    /// number of tokens loaded.
+    pub fn load_from_file(&self, path: &Path) -> Result<usize> {
+        let text = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&text)?,
+            Some("yaml" | "yml") => serde_yaml::from_str(&text)?,
+            Some("json") => serde_json::from_str(&text)?,
+            _ => anyhow::bail!("Unsupported token file format: {}", path.display()),
+        };
+        let entries = value
+            .get("tokens")
+            .and_then(|t| t.as_object())
+            .ok_or_else(|| anyhow::anyhow!("Token file has no [tokens] table"))?;
+
+        let mut loaded = HashMap::new();
+        for (token, value) in entries {
+            let entry = parse_token_entry(value).ok_or_else(|| {
+                anyhow::anyhow!("Invalid entry for token {}", token_fingerprint(token))
+            })?;
+            loaded.insert(token.clone(), entry);
+        }
+
+        // Swap under one lock so requests never see a half-loaded file
+        let mut tokens = self.tokens.write().unwrap();
+        let mut file_tokens = self.file_tokens.lock().unwrap();
+        for token in file_tokens.drain() {
+            tokens.remove(&token);
+        }
+        let count = loaded.len();
+        for (token, entry) in loaded {
+            file_tokens.insert(token.clone());
+            tokens.insert(token, entry);
+        }
+        Ok(count)
+    }
+
+    /// Load `path` and reload it whenever it changes. A file that fails to parse
+    /// keeps the previously loaded tokens.
+    pub fn watch_file(self: &Arc<Self>, path: impl Into<PathBuf>) -> Result<()> {
+        let path = path.into();
+        self.load_from_file(&path)?;
+        let path = path.canonicalize()?;
+        let dir = path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Token file has no parent directory"))?
+            .to_path_buf();
+
+        // Watch the directory: editors often replace the file instead of writing it
+        let auth = Arc::downgrade(self);
+        let file = path.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                let Ok(event) = res else { return };
+                if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    || !event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == file.file_name())
+                {
+                    return;
+                }
+                let Some(auth) = auth.upgrade() else { return };
+                if let Err(e) = auth.load_from_file(&file) {
+                    tracing::warn!(
+                        "Keeping previous tokens, failed to reload {}: {}",
+                        file.display(),
+                        e
+                    );
+                }
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        *self.file_watcher.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
+
+    /// Keep runtime-created tokens in `path` (JSON, in the token file format),
+    /// loading the ones saved there by an earlier run. Returns how many were loaded.
+    pub fn persist_to(&self, path: impl Into<PathBuf>) -> Result<usize> {
+        let path = path.into();
+        let mut loaded = 0;
+        if path.exists() {
+            let value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            let entries = value.get("tokens").and_then(|t| t.as_object());
+            let mut tokens = self.tokens.write().unwrap();
+            let mut managed = self.managed_tokens.lock().unwrap();
+            for (token, value) in entries.into_iter().flatten() {
+                if let Some(entry) = parse_token_entry(value) {
+                    tokens.insert(token.clone(), entry);
+                    managed.insert(token.clone());
+                    loaded += 1;
+                }
+            }
+        }
+        *self.managed_path.lock().unwrap() = Some(path);
+        Ok(loaded)
+    }
+
+    /// Create and register a random token, saving it with the other runtime tokens
+    pub fn create_token(
+        &self,
+        namespaces: Vec<String>,
+        permissions: NamespacePermission,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<String> {
+        let token = format!("syn_{}", uuid::Uuid::new_v4().simple());
+        self.tokens.write().unwrap().insert(
+            token.clone(),
+            TokenEntry {
+                namespaces,
+                permissions,
+                graphs: GraphAccess::default(),
+                expires_at,
+            },
+        );
+        self.managed_tokens.lock().unwrap().insert(token.clone());
+        self.save_managed()?;
+        Ok(token)
+    }
+
+    /// Issue a replacement for a token (given itself or its fingerprint) with the
+    /// same grants and `expires_at`. The old token keeps working for `grace`, or
+    /// until its own expiry if that comes first. Returns `None` for an unknown token.
+    pub fn rotate_token(
+        &self,
+        token_or_fingerprint: &str,
+        grace: chrono::Duration,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<(String, DateTime<Utc>)>> {
+        let Some((old, entry)) = self.find(token_or_fingerprint) else {
+            return Ok(None);
+        };
+        let retired_at = Utc::now() + grace;
+        let retired_at = entry.expires_at.map_or(retired_at, |t| t.min(retired_at));
+        let token = self.create_token(entry.namespaces, entry.permissions, expires_at)?;
+        if !entry.graphs.is_unrestricted() {
+            self.set_graph_access(&token, entry.graphs)?;
+        }
+        self.set_expiry(&old, Some(retired_at))?;
+        Ok(Some((token, retired_at)))
+    }
+
+    /// Accept JWT bearer tokens validated by `validator`, besides registered tokens
+    pub fn set_jwt_validator(&self, validator: Arc<JwtValidator>) {
+        *self.jwt.write().unwrap() = Some(validator);
+    }
+
+    /// Whether `token` is a registered, unexpired token or a valid JWT
+    pub fn authenticate(&self, token: &str) -> Result<(), String> {
+        let expires_at = match self.tokens.read().unwrap().get(token) {
+            Some(entry) => entry.expires_at,
+            None => self.jwt_entry(token)?.expires_at,
+        };
+        match expires_at.filter(|t| *t <= Utc::now()) {
+            Some(expires_at) => Err(format!("Token expired at {}", expires_at.to_rfc3339())),
+            None => Ok(()),
+        }
+    }
+
+    /// Authenticate a caller from `peer` (if known) presenting `token`, refusing
+    /// peers and tokens locked out after repeated failures and counting new failures
+    pub fn authenticate_attempt(
+        &self,
+        token: Option<&str>,
+        peer: Option<std::net::IpAddr>,
+    ) -> Result<(), String> {
+        let token_key = token.map(|t| format!("token:{}", token_fingerprint(t)));
+        let keys: Vec<String> = peer
+            .map(|ip| format!("peer:{}", ip))
+            .into_iter()
+            .chain(token_key.clone())
+            .collect();
+        self.lockout.check(&keys)?;
+        let (Some(token), Some(token_key)) = (token, token_key) else {
+            return Ok(());
+        };
+        match self.authenticate(token) {
+            Ok(()) => {
+                self.lockout.record_success(&token_key);
+                Ok(())
+            }
+            Err(e) => {
+                self.lockout.record_failure(&keys, &e);
+                Err(e)
+            }
+        }
+    }
+
+    /// The grant of an unregistered token that is a valid JWT
+    fn jwt_entry(&self, token: &str) -> Result<TokenEntry, String> {
+        let jwt = self.jwt.read().unwrap();
+        let validator = jwt
+            .as_ref()
+            .filter(|_| JwtValidator::looks_like_jwt(token))
+            .ok_or("Invalid token")?;
+        let grant = validator.validate(token)?;
+        Ok(TokenEntry {
+            namespaces: grant.namespaces,
+            permissions: grant.permissions,
+            graphs: GraphAccess::default(),
+            expires_at: grant.expires_at,
+        })
+    }
+
+    /// The principal of the first certificate identity with a `cert:` entry
+    pub fn certificate_principal(&self, identities: &[String]) -> Option<String> {
+        let tokens = self.tokens.read().unwrap();
+        identities
+            .iter()
+            .map(|id| format!("{}{}", CERTIFICATE_PREFIX, id))
+            .find(|principal| tokens.contains_key(principal))
+    }
+
+    /// A token and its entry, looked up by the token or its fingerprint
+    fn find(&self, token_or_fingerprint: &str) -> Option<(String, TokenEntry)> {
+        self.tokens
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(t, _)| {
+                t.as_str() == token_or_fingerprint || token_fingerprint(t) == token_or_fingerprint
+            })
+            .map(|(t, entry)| (t.clone(), entry.clone()))
+    }
+
+    /// Remove a token, given itself or its fingerprint. Tokens from the environment
+    /// or the token file come back on restart or reload; runtime tokens are gone for good.
+    pub fn revoke_token(&self, token_or_fingerprint: &str) -> Result<bool> {
+        let Some((token, _)) = self.find(token_or_fingerprint) else {
+            return Ok(false);
+        };
+        self.tokens.write().unwrap().remove(&token);
+        self.file_tokens.lock().unwrap().remove(&token);
+        if self.managed_tokens.lock().unwrap().remove(&token) {
+            self.save_managed()?;
+        }
+        Ok(true)
+    }
+
+    /// Every registered token, sorted by fingerprint
+    pub fn list_tokens(&self) -> Vec<TokenInfo> {
+        let tokens = self.tokens.read().unwrap();
+        let file_tokens = self.file_tokens.lock().unwrap();
+        let managed = self.managed_tokens.lock().unwrap();
+        let mut infos: Vec<TokenInfo> = tokens
+            .iter()
+            .map(|(token, entry)| TokenInfo {
+                fingerprint: token_fingerprint(token),
+                namespaces: entry.namespaces.clone(),
+                permissions: entry.permissions.clone(),
+                expires_at: entry.expires_at,
+                source: if managed.contains(token) {
+                    "api"
+                } else if file_tokens.contains(token) {
+                    "file"
+                } else {
+                    "env"
+                },
+            })
+            .collect();
+        infos.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+        infos
+    }
+
+    /// Write the runtime tokens to the persistence file, if one is set
+    fn save_managed(&self) -> Result<()> {
+        let Some(path) = self.managed_path.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let entries: serde_json::Map<String, serde_json::Value> = {
+            let tokens = self.tokens.read().unwrap();
+            let managed = self.managed_tokens.lock().unwrap();
+            managed
+                .iter()
+                .filter_map(|token| {
+                    let entry = tokens.get(token)?;
+                    let mut value = serde_json::json!({
+                        "namespaces": entry.namespaces,
+                        "permissions": entry.permissions,
+                    });
+                    if !entry.graphs.is_unrestricted() {
+                        value["graphs"] = serde_json::json!(entry.graphs);
+                    }
+                    if let Some(expires_at) = entry.expires_at {
+                        value["expires_at"] = expires_at.to_rfc3339().into();
+                    }
+                    Some((token.clone(), value))
+                })
+                .collect()
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Replace the file in one step so a crash never leaves half of it. It holds
+        // live bearer tokens, so only the owner may read it.
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&tmp);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        std::io::Write::write_all(
+            &mut file,
+            serde_json::to_string_pretty(&serde_json::json!({ "tokens": entries }))?.as_bytes(),
+        )?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// Check operation permission; query, search and export refine read, so they also need it
+fn check_operation(perms: &NamespacePermission, operation: &str) -> Result<(), String> {
+    match operation {
+        "query" | "search" | "export" if !perms.read => Err("Read permission denied".to_string()),
+        "read" if !perms.read => Err("Read permission denied".to_string()),
+        "write" if !perms.write => Err("Write permission denied".to_string()),
+        "delete" if !perms.delete => Err("Delete permission denied".to_string()),
+        "reason" if !perms.reason => Err("Reasoning permission denied".to_string()),
+        "query" if !perms.query => Err("Query permission denied".to_string()),
+        "search" if !perms.search => Err("Search permission denied".to_string()),
+        "export" if !perms.export => Err("Export permission denied".to_string()),
+        "admin" if !perms.admin => Err("Admin permission denied".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `namespace` matches a pattern: `*`, a `prefix*` or an exact name
+pub fn namespace_matches(pattern: &str, namespace: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => namespace.starts_with(prefix),
+        None => pattern == namespace,
+    }
+}
+
+/// Parse one token's value: either a list of namespace patterns (all permissions)
+/// or `{"namespaces": [...], "permissions": {...}, "graphs": {"read": [...],
+/// "write": [...]}, "expires_at": "<RFC 3339>"}`
+fn parse_token_entry(value: &serde_json::Value) -> Option<TokenEntry> {
+    if let Ok(namespaces) = serde_json::from_value::<Vec<String>>(value.clone()) {
+        // Legacy format: value is list of namespaces
+        return Some(TokenEntry {
+            namespaces,
+            permissions: NamespacePermission::default(),
+            graphs: GraphAccess::default(),
+            expires_at: None,
+        });
+    }
+    let obj = value.as_object()?;
+    let namespaces = obj
+        .get("namespaces")
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v.clone()).ok())
+        .unwrap_or_default();
+
+    let permissions = if let Some(p) = obj.get("permissions") {
+        let flag =
+            |name: &str, default: bool| p.get(name).and_then(|v| v.as_bool()).unwrap_or(default);
+        let read = flag("read", true);
+        let write = flag("write", true);
+        // Finer scopes default to the permission they refine
+        NamespacePermission {
+            read,
+            write,
+            delete: flag("delete", true),
+            reason: flag("reason", true),
+            query: flag("query", read),
+            search: flag("search", read),
+            export: flag("export", read),
+            admin: flag("admin", false),
+        }
+    } else {
+        NamespacePermission::default()
+    };
+    let patterns = |side: &str| match obj.get("graphs").and_then(|g| g.get(side)) {
+        Some(v) => serde_json::from_value::<Vec<String>>(v.clone()).map(Some),
+        None => Ok(None),
+    };
+    let graphs = GraphAccess {
+        read: patterns("read").ok()?,
+        write: patterns("write").ok()?,
+    };
+    let expires_at = match obj.get("expires_at").and_then(|v| v.as_str()) {
+        Some(t) => Some(DateTime::parse_from_rfc3339(t).ok()?.with_timezone(&Utc)),
+        None => None,
+    };
+    Some(TokenEntry {
+        namespaces,
+        permissions,
+        graphs,
+        expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_load_yaml_token_file() {
+        let dir = std::env::temp_dir().join("synapse_test_auth_yaml");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tokens.yaml");
+        std::fs::write(
+            &path,
+            "tokens:\n  alice:\n    namespaces: [\"research-*\"]\n    permissions:\n      write: false\n  bob: [\"*\"]\n",
+        )
+        .unwrap();
+
+        let auth = NamespaceAuth::new();
+        assert_eq!(auth.load_from_file(&path).unwrap(), 2);
+        assert!(auth.check(Some("alice"), "research-ai", "read").is_ok());
+        assert!(auth.check(Some("alice"), "research-ai", "write").is_err());
+        assert!(auth.check(Some("alice"), "research-ai", "admin").is_err());
+        assert!(auth.check(Some("alice"), "books", "read").is_err());
+        assert!(auth.check(Some("bob"), "books", "delete").is_ok());
+
+        // Errors name a bad entry by fingerprint, never by its secret
+        std::fs::write(&path, "tokens:\n  s3cret-value: 42\n").unwrap();
+        let err = auth.load_from_file(&path).unwrap_err().to_string();
+        assert!(!err.contains("s3cret-value"), "{}", err);
+        assert!(err.contains(&token_fingerprint("s3cret-value")), "{}", err);
+    }
+
+    #[test]
+    fn test_runtime_tokens_persist_and_revoke() {
+        let dir = std::env::temp_dir().join("synapse_test_auth_managed");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("tokens.json");
+
+        let auth = NamespaceAuth::new();
+        assert_eq!(auth.persist_to(&path).unwrap(), 0);
+        let permissions = NamespacePermission {
+            delete: false,
+            ..Default::default()
+        };
+        let token = auth
+            .create_token(vec!["books".into()], permissions.clone(), None)
+            .unwrap();
+        assert!(auth.check(Some(&token), "books", "write").is_ok());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let restarted = NamespaceAuth::new();
+        assert_eq!(restarted.persist_to(&path).unwrap(), 1);
+        let listed = restarted.list_tokens();
+        assert_eq!(
+            listed,
+            vec![TokenInfo {
+                fingerprint: token_fingerprint(&token),
+                namespaces: vec!["books".into()],
+                permissions,
+                source: "api",
+                expires_at: None,
+            }]
+        );
+
+        assert!(restarted.revoke_token(&listed[0].fingerprint).unwrap());
+        assert!(!restarted.revoke_token(&token).unwrap());
+        assert!(restarted.check(Some(&token), "books", "read").is_err());
+        assert_eq!(NamespaceAuth::new().persist_to(&path).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_expired_and_rotated_tokens() {
+        let auth = NamespaceAuth::new();
+        auth.register_token("old", vec!["books".into()], NamespacePermission::default());
+        let past = Utc::now() - chrono::Duration::seconds(1);
+        assert!(auth.set_expiry("old", Some(past)).unwrap());
+        let err = auth.check(Some("old"), "books", "read").unwrap_err();
+        assert!(err.starts_with("Token expired at"), "{}", err);
+
+        assert!(auth.set_expiry("old", None).unwrap());
+        let (new, retired_at) = auth
+            .rotate_token(&token_fingerprint("old"), chrono::Duration::hours(1), None)
+            .unwrap()
+            .unwrap();
+        // Both work during the grace period
+        assert!(auth.check(Some("old"), "books", "write").is_ok());
+        assert!(auth.check(Some(&new), "books", "write").is_ok());
+        assert!(retired_at > Utc::now());
+
+        // A rotation never extends an expiry that comes sooner
+        assert!(auth.set_expiry("old", Some(past)).unwrap());
+        let (_, retired_at) = auth
+            .rotate_token("old", chrono::Duration::hours(1), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(retired_at, past);
+        assert!(auth
+            .rotate_token("missing", chrono::Duration::zero(), None)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_anonymous_policy() {
+        let auth = NamespaceAuth::new();
+        assert!(auth.check(None, "default", "write").is_ok());
+        assert!(auth.check(None, "books", "read").is_err());
+
+        auth.set_anonymous_policy(AnonymousPolicy::parse("read-only").unwrap());
+        assert!(auth.check(None, "default", "query").is_ok());
+        assert_eq!(
+            auth.check(None, "default", "write"),
+            Err("Authentication required".to_string())
+        );
+
+        let policy = AnonymousPolicy::parse(
+            r#"{"namespaces": ["public-*"], "permissions": {"write": false}}"#,
+        )
+        .unwrap();
+        auth.set_anonymous_policy(policy);
+        assert!(auth.check(None, "public-docs", "search").is_ok());
+        assert!(auth.check(None, "public-docs", "write").is_err());
+        assert!(auth.check(None, "default", "read").is_err());
+
+        auth.set_anonymous_policy(AnonymousPolicy::parse("none").unwrap());
+        assert!(auth.check(None, "default", "read").is_err());
+        assert!(AnonymousPolicy::parse("sometimes").is_none());
+        assert_eq!(
+            AnonymousPolicy::parse(r#"{"permissions": {"delete": false}}"#)
+                .unwrap()
+                .namespaces,
+            vec!["default".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_graph_access_from_token_entry() {
+        let entry = parse_token_entry(&serde_json::json!({
+            "namespaces": ["books"],
+            "graphs": {"write": ["urn:batch:*", "http://example.org/agent-1"]},
+        }))
+        .unwrap();
+        let graphs = entry.graphs;
+        assert!(graphs.can_read(DEFAULT_GRAPH));
+        assert!(graphs.can_write("urn:batch:42"));
+        assert!(graphs.can_write("http://example.org/agent-1"));
+        assert!(!graphs.can_write(DEFAULT_GRAPH));
+        assert!(!graphs.can_write_all());
+        assert!(parse_token_entry(&serde_json::json!({"graphs": {"read": "default"}})).is_none());
+
+        let auth = NamespaceAuth::new();
+        auth.register_token(
+            "agent",
+            vec!["books".into()],
+            NamespacePermission::default(),
+        );
+        assert!(auth.graph_access(Some("agent")).is_unrestricted());
+        assert!(auth.set_graph_access("agent", graphs.clone()).unwrap());
+        let (rotated, _) = auth
+            .rotate_token("agent", chrono::Duration::zero(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(auth.graph_access(Some(&rotated)), graphs);
+        assert!(auth.graph_access(None).is_unrestricted());
+    }
+
+    #[tokio::test]
+    async fn test_token_file_reloads_on_change() {
+        let dir = std::env::temp_dir().join("synapse_test_auth_reload");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tokens.toml");
+        std::fs::write(&path, "[tokens.alice]\nnamespaces = [\"books\"]\n").unwrap();
+
+        let auth = Arc::new(NamespaceAuth::new());
+        auth.register_token("env", vec!["*".into()], NamespacePermission::default());
+        auth.watch_file(&path).unwrap();
+        assert!(auth.check(Some("alice"), "books", "write").is_ok());
+
+        std::fs::write(
+            &path,
+            "[tokens.carol]\nnamespaces = [\"books\"]\n\n[tokens.carol.permissions]\nwrite = false\n",
+        )
+        .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while auth.check(Some("alice"), "books", "read").is_ok() {
+            assert!(Instant::now() < deadline, "token file was not reloaded");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(auth.check(Some("carol"), "books", "read").is_ok());
+        assert!(auth.check(Some("carol"), "books", "write").is_err());
+        // Tokens registered elsewhere survive reloads
+        assert!(auth.check(Some("env"), "books", "write").is_ok());
+
+        // A broken file keeps the last good tokens
+        std::fs::write(&path, "[tokens.carol\n").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(auth.check(Some("carol"), "books", "read").is_ok());
+    }
 }
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use oxigraph::model::{Quad, Subject, Term};
+use tokio::sync::broadcast;
+
+/// Events buffered for each subscriber before it starts lagging
+const CHANGE_FEED_CAPACITY: usize = 1024;
+
+/// What happened in a namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Insert,
+    Delete,
+    /// Triples materialized by the reasoner (one event per run)
+    Reasoning,
+    NamespaceDeleted,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Insert => "insert",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Reasoning => "reasoning",
+            ChangeKind::NamespaceDeleted => "namespace_deleted",
+        }
+    }
+}
+
+/// A change published on a `ChangeFeed`; `quad` is set for inserts and deletes
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub namespace: String,
+    pub quad: Option<Quad>,
+    pub detail: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Change {
+    pub fn new(kind: ChangeKind, namespace: &str, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            namespace: namespace.to_string(),
+            quad: None,
+            detail: detail.into(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    pub fn triple(kind: ChangeKind, namespace: &str, quad: Quad) -> Self {
+        Self {
+            quad: Some(quad),
+            ..Self::new(kind, namespace, "")
+        }
+    }
+
+    /// Whether a triple change matches a pattern (`None` matches anything).
+    /// Changes without a triple match every pattern.
+    pub fn matches(
+        &self,
+        subject: Option<&Subject>,
+        predicate: Option<&oxigraph::model::NamedNode>,
+        object: Option<&Term>,
+    ) -> bool {
+        let Some(quad) = &self.quad else {
+            return true;
+        };
+        subject.is_none_or(|s| *s == quad.subject)
+            && predicate.is_none_or(|p| *p == quad.predicate)
+            && object.is_none_or(|o| *o == quad.object)
+    }
+}
+
+/// Broadcast channel of changes, shared by every store of an engine
+#[derive(Clone)]
+pub struct ChangeFeed {
+    sender: broadcast::Sender<Change>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChangeFeed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANGE_FEED_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.sender.subscribe()
+    }
+
+    /// Number of subscribers still listening
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Publish the change built by `change`, which is only called when someone is listening
+    pub fn publish(&self, change: impl FnOnce() -> Change) {
+        if self.sender.receiver_count() > 0 {
+            let _ = self.sender.send(change());
+        }
+    }
+}
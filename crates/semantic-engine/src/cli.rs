@@ -0,0 +1,303 @@
+//! Command line of the `synapse` binary. `serve` and `mcp` start a server; the
+//! other commands open the storage themselves, do one thing and exit, so they need
+//! neither a running server nor a gRPC client. A namespace is opened by one process
+//! at a time, so run them against a storage path no server is using.
+
+use crate::jobs::JobState;
+use crate::server::proto::semantic_engine_server::SemanticEngine;
+use crate::server::proto::{
+    BackupRequest, EmptyRequest, ExportNamespaceRequest, IngestDirectoryRequest, IngestFileRequest,
+    SparqlRequest,
+};
+use crate::server::MySemanticEngine;
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+use futures::StreamExt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tonic::{Request, Status};
+
+/// How often `backup` checks whether its job finished
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Parser)]
+#[command(name = "synapse", version, about = "Neuro-symbolic semantic engine")]
+pub struct Cli {
+    /// Configuration file (default: `SYNAPSE_CONFIG`, else `./synapse.toml` if present)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Same as `synapse mcp`, for existing MCP client configurations
+    #[arg(long, hide = true)]
+    pub mcp: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// The command to run; `serve` when none is given
+    pub fn command(&self) -> Command {
+        match &self.command {
+            Some(command) => command.clone(),
+            None if self.mcp => Command::Mcp,
+            None => Command::Serve,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum Command {
+    /// Run the gRPC server (the default)
+    Serve,
+    /// Serve the Model Context Protocol over stdin and stdout
+    Mcp,
+    /// Ingest a file, or every supported file under a directory
+    Ingest {
+        path: PathBuf,
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+        /// Only files matching this pattern, relative to a directory, e.g. `**/*.md`
+        #[arg(long)]
+        glob: Option<String>,
+    },
+    /// Run a SPARQL query and print its results as JSON
+    Query {
+        sparql: String,
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+    },
+    /// Write out a namespace
+    Export {
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+        /// `nquads`, `trig`, `turtle`, `ntriples`, `graphml` or `gexf`
+        #[arg(short, long, default_value = "nquads")]
+        format: String,
+        /// File to write; standard output when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Drop stale vectors and URI mappings from a namespace
+    Compact {
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+    },
+    /// Copy a namespace to `<destination>/<namespace>-<timestamp>`
+    Backup {
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+        /// Defaults to `<storage path>/backups`
+        #[arg(short, long)]
+        destination: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Whether the command runs until it is stopped
+    pub fn is_server(&self) -> bool {
+        matches!(self, Command::Serve | Command::Mcp)
+    }
+}
+
+fn failed(status: Status) -> anyhow::Error {
+    anyhow::anyhow!("{}", status.message())
+}
+
+/// Run a one-off command, writing its results to `out`. Requests go in without a
+/// token, so `engine` should allow anonymous access, as with
+/// [`AnonymousPolicy::unrestricted`](crate::auth::AnonymousPolicy::unrestricted).
+pub async fn run(engine: &MySemanticEngine, command: Command, out: &mut dyn Write) -> Result<()> {
+    match command {
+        Command::Serve | Command::Mcp => {
+            bail!("{:?} runs a server, not a one-off command", command)
+        }
+        Command::Ingest {
+            path,
+            namespace,
+            glob,
+        } if path.is_dir() => {
+            let mut stream = engine
+                .ingest_directory(Request::new(IngestDirectoryRequest {
+                    path: path.display().to_string(),
+                    glob: glob.unwrap_or_default(),
+                    namespace,
+                }))
+                .await
+                .map_err(failed)?
+                .into_inner();
+            let mut last = None;
+            while let Some(progress) = stream.next().await {
+                let progress = progress.map_err(failed)?;
+                if let Some(file) = progress.file.as_ref().filter(|f| !f.error.is_empty()) {
+                    writeln!(out, "Failed to ingest {}: {}", file.path, file.error)?;
+                }
+                last = Some(progress);
+            }
+            let Some(last) = last.filter(|p| p.done) else {
+                bail!("Directory ingestion ended unexpectedly");
+            };
+            writeln!(
+                out,
+                "Ingested {} of {} files from {}: {} triples added",
+                last.files_processed - last.files_failed,
+                last.files_total,
+                path.display(),
+                last.triples_added
+            )?;
+            if last.files_failed > 0 {
+                bail!("{} files failed to ingest", last.files_failed);
+            }
+        }
+        Command::Ingest {
+            path,
+            namespace,
+            glob,
+        } => {
+            if glob.is_some() {
+                bail!("--glob only applies to directories");
+            }
+            let report = engine
+                .ingest_file(Request::new(IngestFileRequest {
+                    file_path: path.display().to_string(),
+                    namespace,
+                    ..Default::default()
+                }))
+                .await
+                .map_err(failed)?
+                .into_inner();
+            writeln!(
+                out,
+                "Ingested {}: {} triples added, {} duplicates, {} malformed, {} vectors in {} ms",
+                path.display(),
+                report.edges_added,
+                report.duplicates_skipped,
+                report.malformed,
+                report.vectors_created,
+                report.elapsed_ms
+            )?;
+        }
+        Command::Query { sparql, namespace } => {
+            let response = engine
+                .query_sparql(Request::new(SparqlRequest {
+                    query: sparql,
+                    namespace,
+                }))
+                .await
+                .map_err(failed)?
+                .into_inner();
+            writeln!(out, "{}", response.results_json)?;
+        }
+        Command::Export {
+            namespace,
+            format,
+            output,
+        } => {
+            let mut stream = engine
+                .export_namespace(Request::new(ExportNamespaceRequest {
+                    namespace,
+                    format,
+                    chunk_size: 0,
+                }))
+                .await
+                .map_err(failed)?
+                .into_inner();
+            let mut file = match &output {
+                Some(path) => Some(std::io::BufWriter::new(std::fs::File::create(path)?)),
+                None => None,
+            };
+            let mut count = None;
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(failed)?;
+                match file.as_mut() {
+                    Some(file) => file.write_all(&chunk.data)?,
+                    None => out.write_all(&chunk.data)?,
+                }
+                if chunk.done {
+                    count = Some(chunk.count);
+                }
+            }
+            let Some(count) = count else {
+                bail!("Export ended unexpectedly");
+            };
+            if let (Some(mut file), Some(path)) = (file, output) {
+                file.flush()?;
+                writeln!(out, "Exported {} quads to {}", count, path.display())?;
+            }
+        }
+        Command::Compact { namespace } => {
+            let report = engine
+                .compact_namespace(Request::new(EmptyRequest { namespace }))
+                .await
+                .map_err(failed)?
+                .into_inner();
+            writeln!(
+                out,
+                "Compacted {}: {} vectors and {} URI mappings removed, {} -> {} bytes in {} ms",
+                report.namespace,
+                report.vectors_removed,
+                report.uri_mappings_removed,
+                report.disk_bytes_before,
+                report.disk_bytes_after,
+                report.elapsed_ms
+            )?;
+        }
+        Command::Backup {
+            namespace,
+            destination,
+        } => {
+            let response = engine
+                .backup_namespace(Request::new(BackupRequest {
+                    namespace,
+                    destination: destination
+                        .map(|d| d.display().to_string())
+                        .unwrap_or_default(),
+                }))
+                .await
+                .map_err(failed)?
+                .into_inner();
+            let status = loop {
+                match engine.jobs.get(&response.job_id) {
+                    Some(status) if status.state == JobState::Running => {}
+                    Some(status) => break status,
+                    None => bail!("Backup job {} disappeared", response.job_id),
+                }
+                tokio::time::sleep(JOB_POLL_INTERVAL).await;
+            };
+            if status.state == JobState::Failed {
+                bail!("{}", status.message);
+            }
+            writeln!(out, "{}", status.message)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_parse() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args).map(|cli| cli.command());
+        assert_eq!(parse(&["synapse"]).unwrap(), Command::Serve);
+        assert_eq!(parse(&["synapse", "--mcp"]).unwrap(), Command::Mcp);
+        assert_eq!(
+            parse(&["synapse", "query", "ASK {}", "-n", "work"]).unwrap(),
+            Command::Query {
+                sparql: "ASK {}".to_string(),
+                namespace: "work".to_string()
+            }
+        );
+        assert_eq!(
+            parse(&["synapse", "backup", "--config", "synapse.toml"]).unwrap(),
+            Command::Backup {
+                namespace: "default".to_string(),
+                destination: None
+            }
+        );
+        assert!(parse(&["synapse", "ingest"]).is_err());
+        assert!(parse(&["synapse", "frobnicate"]).is_err());
+    }
+}
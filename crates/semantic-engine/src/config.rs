@@ -0,0 +1,565 @@
+//! `synapse.toml`: the settings otherwise given as environment variables, grouped
+//! into typed sections. The file is read once at startup and each value is exported
+//! as the variable it stands for, unless that variable is already set, so the
+//! environment still overrides the file.
+//!
+//! ```toml
+//! [storage]
+//! path = "/var/lib/synapse"
+//! max_open_namespaces = 64
+//!
+//! [embeddings]
+//! provider = "remote"
+//! api_url = "http://localhost:11434/api/embeddings"
+//!
+//! [maintenance]
+//! schedule = "3600@1-5"
+//! skip = ["archive"]
+//!
+//! [watch]
+//! notes = "/home/me/notes"
+//! ```
+
+use crate::auth::AnonymousPolicy;
+use crate::lockout::LockoutPolicy;
+use crate::logging::LogFormat;
+use crate::properties::ColumnPolicy;
+use crate::quota::QuotaLimits;
+use crate::ratelimit::Quota;
+use crate::schedule::SnapshotSchedule;
+use crate::server::TransportConfig;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Read when neither `--config` nor `SYNAPSE_CONFIG` names a file, if it exists
+pub const DEFAULT_CONFIG_FILE: &str = "synapse.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SynapseConfig {
+    pub storage: StorageConfig,
+    pub server: ServerConfig,
+    pub embeddings: EmbeddingsConfig,
+    pub auth: AuthConfig,
+    pub tls: TlsConfig,
+    pub jwt: JwtConfig,
+    pub mcp: McpConfig,
+    pub maintenance: MaintenanceConfig,
+    pub logging: LoggingConfig,
+    /// Folders to watch, by namespace (`SYNAPSE_WATCH`)
+    pub watch: BTreeMap<String, PathBuf>,
+    /// `SYNAPSE_QUOTAS`
+    pub quotas: Option<QuotasConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// `GRAPH_STORAGE_PATH`
+    pub path: Option<PathBuf>,
+    /// `SYNAPSE_OBJECT_STORE`
+    pub object_store: Option<String>,
+    /// `SYNAPSE_MAX_OPEN_NAMESPACES`
+    pub max_open_namespaces: Option<usize>,
+    /// `SYNAPSE_SNAPSHOT_SCHEDULE`
+    pub snapshot_schedule: Option<String>,
+    /// `SYNAPSE_PROPERTY_DENSITY`
+    pub property_density: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// `SYNAPSE_LISTEN_ADDR`
+    pub listen_addr: Option<String>,
+    /// `SYNAPSE_SHUTDOWN_TIMEOUT_SECS`
+    pub shutdown_timeout_secs: Option<u64>,
+    /// `SYNAPSE_SLOW_QUERY_MS`
+    pub slow_query_ms: Option<u64>,
+    /// `SYNAPSE_GRPC_COMPRESSION`
+    pub grpc_compression: Option<String>,
+    /// `SYNAPSE_GRPC_MAX_MESSAGE_BYTES`
+    pub max_message_bytes: Option<usize>,
+    /// `SYNAPSE_GRPC_MAX_DECODING_BYTES`
+    pub max_decoding_bytes: Option<usize>,
+    /// `SYNAPSE_GRPC_MAX_ENCODING_BYTES`
+    pub max_encoding_bytes: Option<usize>,
+    /// `SYNAPSE_RATE_LIMIT_PER_TOKEN`
+    pub rate_limit_per_token: Option<String>,
+    /// `SYNAPSE_RATE_LIMIT_PER_NAMESPACE`
+    pub rate_limit_per_namespace: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmbeddingsConfig {
+    /// `local`, `remote` (`EMBEDDING_PROVIDER`) or `mock` (`MOCK_EMBEDDINGS`)
+    pub provider: Option<String>,
+    /// `EMBEDDING_API_URL`
+    pub api_url: Option<String>,
+    /// `EMBEDDING_MODEL`
+    pub model: Option<String>,
+    /// `EMBEDDING_API_KEY`
+    pub api_key: Option<String>,
+    /// `VECTOR_DIMENSIONS`
+    pub dimensions: Option<usize>,
+    /// `FASTEMBED_CACHE_PATH`
+    pub cache_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// `SYNAPSE_ADMIN_TOKEN`
+    pub admin_token: Option<String>,
+    /// `SYNAPSE_MCP_TOKEN`
+    pub mcp_token: Option<String>,
+    /// Token entries, as in `SYNAPSE_AUTH_TOKENS`
+    pub tokens: Option<BTreeMap<String, serde_json::Value>>,
+    /// `SYNAPSE_AUTH_TOKENS_FILE`
+    pub tokens_file: Option<PathBuf>,
+    /// `SYNAPSE_ANONYMOUS`
+    pub anonymous: Option<String>,
+    /// `SYNAPSE_AUTH_LOCKOUT`
+    pub lockout: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// `SYNAPSE_TLS_CERT`
+    pub cert: Option<PathBuf>,
+    /// `SYNAPSE_TLS_KEY`
+    pub key: Option<PathBuf>,
+    /// `SYNAPSE_TLS_CLIENT_CA`
+    pub client_ca: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    /// `SYNAPSE_JWT_ISSUER`
+    pub issuer: Option<String>,
+    /// `SYNAPSE_JWT_AUDIENCE`
+    pub audience: Option<String>,
+    /// `SYNAPSE_JWT_JWKS_URL`
+    pub jwks_url: Option<String>,
+    /// `SYNAPSE_JWT_NAMESPACES_CLAIM`
+    pub namespaces_claim: Option<String>,
+    /// `SYNAPSE_JWT_ROLES_CLAIM`
+    pub roles_claim: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct McpConfig {
+    /// `SYNAPSE_MCP_MAX_CONCURRENCY`
+    pub max_concurrency: Option<usize>,
+    /// `SYNAPSE_MCP_MAX_OUTPUT`
+    pub max_output: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaintenanceConfig {
+    /// `SYNAPSE_MAINTENANCE_SCHEDULE`
+    pub schedule: Option<String>,
+    /// `SYNAPSE_MAINTENANCE_STALE_RATIO`
+    pub stale_ratio: Option<f64>,
+    /// `SYNAPSE_MAINTENANCE_SKIP`
+    pub skip: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// `RUST_LOG`
+    pub filter: Option<String>,
+    /// `SYNAPSE_LOG_FORMAT`
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotasConfig {
+    pub namespaces: BTreeMap<String, QuotaLimits>,
+    pub tokens: BTreeMap<String, QuotaLimits>,
+}
+
+/// An invalid value, named by its key
+fn invalid(key: &str, message: impl std::fmt::Display) -> anyhow::Error {
+    anyhow::anyhow!("`{}`: {}", key, message)
+}
+
+fn check_schedule(key: &str, spec: &Option<String>) -> Result<()> {
+    match spec.as_deref() {
+        Some(spec) if spec.trim() != "off" && SnapshotSchedule::parse(spec).is_none() => Err(
+            invalid(key, "expected `off` or `interval_secs[@start-end,...]`"),
+        ),
+        _ => Ok(()),
+    }
+}
+
+impl SynapseConfig {
+    /// The file to read: `explicit` (from `--config`), else `SYNAPSE_CONFIG`, else
+    /// `synapse.toml` in the working directory if there is one
+    pub fn locate(explicit: Option<&Path>) -> Option<PathBuf> {
+        if let Some(path) = explicit {
+            return Some(path.to_path_buf());
+        }
+        if let Ok(path) = std::env::var("SYNAPSE_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.exists())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the values the types alone do not
+    pub fn validate(&self) -> Result<()> {
+        let storage = &self.storage;
+        if storage.max_open_namespaces == Some(0) {
+            bail!(invalid("storage.max_open_namespaces", "must be at least 1"));
+        }
+        check_schedule("storage.snapshot_schedule", &storage.snapshot_schedule)?;
+        if let Some(spec) = &storage.property_density {
+            if ColumnPolicy::parse(spec).is_none() {
+                bail!(invalid(
+                    "storage.property_density",
+                    "expected `promote_at:demote_at` with 0 <= demote_at <= promote_at <= 1"
+                ));
+            }
+        }
+
+        let server = &self.server;
+        if let Some(addr) = &server.listen_addr {
+            addr.parse::<std::net::SocketAddr>()
+                .map_err(|e| invalid("server.listen_addr", e))?;
+        }
+        if let Some(spec) = &server.grpc_compression {
+            TransportConfig::parse_compression(spec)
+                .map_err(|e| invalid("server.grpc_compression", e))?;
+        }
+        for (key, bytes) in [
+            ("server.max_message_bytes", server.max_message_bytes),
+            ("server.max_decoding_bytes", server.max_decoding_bytes),
+            ("server.max_encoding_bytes", server.max_encoding_bytes),
+        ] {
+            if bytes == Some(0) {
+                bail!(invalid(key, "must be at least 1"));
+            }
+        }
+        for (key, spec) in [
+            ("server.rate_limit_per_token", &server.rate_limit_per_token),
+            (
+                "server.rate_limit_per_namespace",
+                &server.rate_limit_per_namespace,
+            ),
+        ] {
+            if spec
+                .as_deref()
+                .is_some_and(|spec| Quota::parse(spec).is_none())
+            {
+                bail!(invalid(key, "expected `rate` or `rate:burst`"));
+            }
+        }
+
+        let embeddings = &self.embeddings;
+        if let Some(provider) = &embeddings.provider {
+            if !["local", "remote", "mock"].contains(&provider.as_str()) {
+                bail!(invalid(
+                    "embeddings.provider",
+                    "expected `local`, `remote` or `mock`"
+                ));
+            }
+        }
+        if embeddings.dimensions == Some(0) {
+            bail!(invalid("embeddings.dimensions", "must be at least 1"));
+        }
+
+        let auth = &self.auth;
+        if auth
+            .anonymous
+            .as_deref()
+            .is_some_and(|value| AnonymousPolicy::parse(value).is_none())
+        {
+            bail!(invalid(
+                "auth.anonymous",
+                "expected `full`, `read-only`, `none` or a token entry"
+            ));
+        }
+        if let Some(spec) = &auth.lockout {
+            if spec.trim() != "off" && LockoutPolicy::parse(spec).is_none() {
+                bail!(invalid(
+                    "auth.lockout",
+                    "expected `off` or `failures:base_secs:max_secs`"
+                ));
+            }
+        }
+        if self.tls.cert.is_some() != self.tls.key.is_some() {
+            bail!(invalid("tls", "`cert` and `key` go together"));
+        }
+        if self.tls.client_ca.is_some() && self.tls.cert.is_none() {
+            bail!(invalid("tls.client_ca", "needs `cert` and `key`"));
+        }
+        let jwt = &self.jwt;
+        if jwt.issuer.is_none()
+            && (jwt.audience.is_some()
+                || jwt.jwks_url.is_some()
+                || jwt.namespaces_claim.is_some()
+                || jwt.roles_claim.is_some())
+        {
+            bail!(invalid(
+                "jwt.issuer",
+                "required by the other `jwt` settings"
+            ));
+        }
+        if self.mcp.max_concurrency == Some(0) {
+            bail!(invalid("mcp.max_concurrency", "must be at least 1"));
+        }
+
+        check_schedule("maintenance.schedule", &self.maintenance.schedule)?;
+        if let Some(ratio) = self.maintenance.stale_ratio {
+            if !(0.0..=1.0).contains(&ratio) {
+                bail!(invalid(
+                    "maintenance.stale_ratio",
+                    "must be between 0 and 1"
+                ));
+            }
+        }
+
+        if let Some(filter) = &self.logging.filter {
+            tracing_subscriber::EnvFilter::try_new(filter)
+                .map_err(|e| invalid("logging.filter", e))?;
+        }
+        if self
+            .logging
+            .format
+            .as_deref()
+            .is_some_and(|format| LogFormat::parse(format).is_none())
+        {
+            bail!(invalid("logging.format", "expected `text` or `json`"));
+        }
+        for (namespace, path) in &self.watch {
+            if namespace.contains([',', '=']) || path.to_string_lossy().contains(',') {
+                bail!(invalid(
+                    &format!("watch.{}", namespace),
+                    "namespace and path may not contain `,` or `=`"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The environment variables this config stands for, with their values
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        let mut set = |name: &'static str, value: Option<String>| {
+            if let Some(value) = value {
+                vars.push((name, value));
+            }
+        };
+        let path = |p: &Option<PathBuf>| p.as_ref().map(|p| p.display().to_string());
+        let number = |n: Option<usize>| n.map(|n| n.to_string());
+
+        let storage = &self.storage;
+        set("GRAPH_STORAGE_PATH", path(&storage.path));
+        set("SYNAPSE_OBJECT_STORE", storage.object_store.clone());
+        set(
+            "SYNAPSE_MAX_OPEN_NAMESPACES",
+            number(storage.max_open_namespaces),
+        );
+        set(
+            "SYNAPSE_SNAPSHOT_SCHEDULE",
+            storage.snapshot_schedule.clone(),
+        );
+        set("SYNAPSE_PROPERTY_DENSITY", storage.property_density.clone());
+
+        let server = &self.server;
+        set("SYNAPSE_LISTEN_ADDR", server.listen_addr.clone());
+        set(
+            "SYNAPSE_SHUTDOWN_TIMEOUT_SECS",
+            server.shutdown_timeout_secs.map(|n| n.to_string()),
+        );
+        set(
+            "SYNAPSE_SLOW_QUERY_MS",
+            server.slow_query_ms.map(|n| n.to_string()),
+        );
+        set("SYNAPSE_GRPC_COMPRESSION", server.grpc_compression.clone());
+        set(
+            "SYNAPSE_GRPC_MAX_MESSAGE_BYTES",
+            number(server.max_message_bytes),
+        );
+        set(
+            "SYNAPSE_GRPC_MAX_DECODING_BYTES",
+            number(server.max_decoding_bytes),
+        );
+        set(
+            "SYNAPSE_GRPC_MAX_ENCODING_BYTES",
+            number(server.max_encoding_bytes),
+        );
+        set(
+            "SYNAPSE_RATE_LIMIT_PER_TOKEN",
+            server.rate_limit_per_token.clone(),
+        );
+        set(
+            "SYNAPSE_RATE_LIMIT_PER_NAMESPACE",
+            server.rate_limit_per_namespace.clone(),
+        );
+
+        let embeddings = &self.embeddings;
+        match embeddings.provider.as_deref() {
+            Some("mock") => set("MOCK_EMBEDDINGS", Some("true".to_string())),
+            provider => set("EMBEDDING_PROVIDER", provider.map(String::from)),
+        }
+        set("EMBEDDING_API_URL", embeddings.api_url.clone());
+        set("EMBEDDING_MODEL", embeddings.model.clone());
+        set("EMBEDDING_API_KEY", embeddings.api_key.clone());
+        set("VECTOR_DIMENSIONS", number(embeddings.dimensions));
+        set("FASTEMBED_CACHE_PATH", path(&embeddings.cache_path));
+
+        let auth = &self.auth;
+        set("SYNAPSE_ADMIN_TOKEN", auth.admin_token.clone());
+        set("SYNAPSE_MCP_TOKEN", auth.mcp_token.clone());
+        set(
+            "SYNAPSE_AUTH_TOKENS",
+            auth.tokens
+                .as_ref()
+                .map(|tokens| serde_json::json!(tokens).to_string()),
+        );
+        set("SYNAPSE_AUTH_TOKENS_FILE", path(&auth.tokens_file));
+        set("SYNAPSE_ANONYMOUS", auth.anonymous.clone());
+        set("SYNAPSE_AUTH_LOCKOUT", auth.lockout.clone());
+
+        set("SYNAPSE_TLS_CERT", path(&self.tls.cert));
+        set("SYNAPSE_TLS_KEY", path(&self.tls.key));
+        set("SYNAPSE_TLS_CLIENT_CA", path(&self.tls.client_ca));
+
+        let jwt = &self.jwt;
+        set("SYNAPSE_JWT_ISSUER", jwt.issuer.clone());
+        set("SYNAPSE_JWT_AUDIENCE", jwt.audience.clone());
+        set("SYNAPSE_JWT_JWKS_URL", jwt.jwks_url.clone());
+        set("SYNAPSE_JWT_NAMESPACES_CLAIM", jwt.namespaces_claim.clone());
+        set("SYNAPSE_JWT_ROLES_CLAIM", jwt.roles_claim.clone());
+
+        set(
+            "SYNAPSE_MCP_MAX_CONCURRENCY",
+            number(self.mcp.max_concurrency),
+        );
+        set("SYNAPSE_MCP_MAX_OUTPUT", number(self.mcp.max_output));
+
+        let maintenance = &self.maintenance;
+        set("SYNAPSE_MAINTENANCE_SCHEDULE", maintenance.schedule.clone());
+        set(
+            "SYNAPSE_MAINTENANCE_STALE_RATIO",
+            maintenance.stale_ratio.map(|r| r.to_string()),
+        );
+        set(
+            "SYNAPSE_MAINTENANCE_SKIP",
+            maintenance.skip.as_ref().map(|skip| skip.join(",")),
+        );
+
+        set("RUST_LOG", self.logging.filter.clone());
+        set("SYNAPSE_LOG_FORMAT", self.logging.format.clone());
+
+        if !self.watch.is_empty() {
+            let specs: Vec<String> = self
+                .watch
+                .iter()
+                .map(|(namespace, path)| format!("{}={}", namespace, path.display()))
+                .collect();
+            set("SYNAPSE_WATCH", Some(specs.join(",")));
+        }
+        set(
+            "SYNAPSE_QUOTAS",
+            self.quotas
+                .as_ref()
+                .map(|quotas| serde_json::json!(quotas).to_string()),
+        );
+        vars
+    }
+
+    /// Export every value whose variable is not already set. Call before any
+    /// threads start. Returns the variables that were set.
+    pub fn apply_env(&self) -> Vec<&'static str> {
+        self.env_vars()
+            .into_iter()
+            .filter(|(name, _)| std::env::var_os(name).is_none())
+            .map(|(name, value)| {
+                std::env::set_var(name, value);
+                name
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections_map_to_environment_variables() {
+        let config = SynapseConfig::parse(
+            r#"
+            [storage]
+            path = "/srv/graphs"
+            max_open_namespaces = 8
+
+            [embeddings]
+            provider = "mock"
+            dimensions = 128
+
+            [maintenance]
+            schedule = "3600@1-5"
+            skip = ["archive", "tenant-*"]
+
+            [watch]
+            notes = "/home/me/notes"
+
+            [quotas.namespaces."tenant-*"]
+            max_triples = 1000
+            "#,
+        )
+        .unwrap();
+        let vars: BTreeMap<&str, String> = config.env_vars().into_iter().collect();
+        assert_eq!(vars["GRAPH_STORAGE_PATH"], "/srv/graphs");
+        assert_eq!(vars["SYNAPSE_MAX_OPEN_NAMESPACES"], "8");
+        assert_eq!(vars["MOCK_EMBEDDINGS"], "true");
+        assert!(!vars.contains_key("EMBEDDING_PROVIDER"));
+        assert_eq!(vars["VECTOR_DIMENSIONS"], "128");
+        assert_eq!(vars["SYNAPSE_MAINTENANCE_SKIP"], "archive,tenant-*");
+        assert_eq!(vars["SYNAPSE_WATCH"], "notes=/home/me/notes");
+        let quotas: serde_json::Value = serde_json::from_str(&vars["SYNAPSE_QUOTAS"]).unwrap();
+        assert_eq!(quotas["namespaces"]["tenant-*"]["max_triples"], 1000);
+        assert_eq!(vars.len(), 8);
+    }
+
+    #[test]
+    fn test_errors_name_the_offending_key() {
+        let error = |content: &str| format!("{:#}", SynapseConfig::parse(content).unwrap_err());
+        assert!(
+            error("[storage]\nmax_open_namespaces = 0").contains("`storage.max_open_namespaces`")
+        );
+        assert!(error("[maintenance]\nstale_ratio = 2.0").contains("`maintenance.stale_ratio`"));
+        assert!(error("[server]\nlisten_addr = \"nowhere\"").contains("`server.listen_addr`"));
+        assert!(error("[embeddings]\nprovider = \"gpu\"").contains("`embeddings.provider`"));
+        assert!(error("[auth]\nlockout = \"5\"").contains("`auth.lockout`"));
+        assert!(error("[logging]\nformat = \"xml\"").contains("`logging.format`"));
+        // Unknown and mistyped keys are named by the parser
+        assert!(error("[storage]\npaht = \"/srv\"").contains("paht"));
+        assert!(error("[storage]\nmax_open_namespaces = \"many\"").contains("max_open_namespaces"));
+        assert!(SynapseConfig::parse("").is_ok());
+    }
+}
@@ -144,7 +144,7 @@ impl NeighborExplorer {
 
             if !visited.contains(&target_uri) {
                 visited.insert(target_uri.clone());
-                let target_id = self.store.get_or_create_id(&target_uri);
+                let target_id = self.store.term_id(&target_uri);
 
                 let mut score = base_score;
                 if options.scoring_strategy == ScoringStrategy::Degree {
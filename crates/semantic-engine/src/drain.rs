@@ -0,0 +1,170 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tonic::codegen::{http, Body};
+use tower::{Layer, Service};
+
+/// Counts work that should finish before the process exits: in-flight RPCs
+/// (including their response streams) and background jobs
+#[derive(Default)]
+pub struct Drain {
+    active: AtomicUsize,
+    idle: Notify,
+}
+
+/// Marks one unit of work as active until dropped
+pub struct DrainGuard {
+    drain: Arc<Drain>,
+}
+
+impl Drop for DrainGuard {
+    fn drop(&mut self) {
+        if self.drain.active.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.drain.idle.notify_waiters();
+        }
+    }
+}
+
+impl Drain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(self: &Arc<Self>) -> DrainGuard {
+        self.active.fetch_add(1, Ordering::AcqRel);
+        DrainGuard {
+            drain: self.clone(),
+        }
+    }
+
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Wait until nothing is active, for at most `deadline`. Returns whether
+    /// everything finished in time.
+    pub async fn wait_idle(&self, deadline: Duration) -> bool {
+        let idle = async {
+            loop {
+                // Register before checking so a guard dropped in between still wakes us
+                let notified = self.idle.notified();
+                if self.active() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(deadline, idle).await.is_ok()
+    }
+}
+
+/// Tower layer keeping each gRPC call active in a `Drain` until its response
+/// body has been sent
+#[derive(Clone)]
+pub struct DrainLayer {
+    drain: Arc<Drain>,
+}
+
+impl DrainLayer {
+    pub fn new(drain: Arc<Drain>) -> Self {
+        Self { drain }
+    }
+}
+
+impl<S> Layer<S> for DrainLayer {
+    type Service = DrainService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DrainService {
+            inner,
+            drain: self.drain.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DrainService<S> {
+    inner: S,
+    drain: Arc<Drain>,
+}
+
+impl<S, B, ResBody> Service<http::Request<B>> for DrainService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<DrainBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let guard = self.drain.track();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            Ok(response.map(|inner| DrainBody {
+                inner,
+                _guard: guard,
+            }))
+        })
+    }
+}
+
+/// Response body holding its call's `DrainGuard` until the body is dropped
+pub struct DrainBody<B> {
+    inner: B,
+    _guard: DrainGuard,
+}
+
+impl<B: Body + Unpin> Body for DrainBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_idle_waits_for_guards() {
+        let drain = Arc::new(Drain::new());
+        assert!(drain.wait_idle(Duration::from_millis(10)).await);
+
+        let guard = drain.track();
+        assert_eq!(drain.active(), 1);
+        assert!(!drain.wait_idle(Duration::from_millis(20)).await);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+        assert!(drain.wait_idle(Duration::from_secs(5)).await);
+        assert_eq!(drain.active(), 0);
+    }
+}
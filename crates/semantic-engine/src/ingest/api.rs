@@ -0,0 +1,479 @@
+use super::csv_mapping::encode_segment;
+use anyhow::Result;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// A paginated JSON REST endpoint synced into the graph.
+///
+/// ```json
+/// {
+///   "url": "https://api.example.com/v1/issues?state=open",
+///   "auth_token": "…",
+///   "headers": { "Accept": "application/json" },
+///   "pagination": { "type": "cursor", "cursor_path": "$.meta.next_cursor", "param": "cursor" },
+///   "mapping": {
+///     "items": "$.data[*]",
+///     "subject": "https://example.com/issue/{id}",
+///     "class": "http://schema.org/CreativeWork",
+///     "predicates": {
+///       "$.title": "http://schema.org/name",
+///       "$.labels[*].name": "http://schema.org/keywords",
+///       "$.author.html_url": "http://schema.org/author"
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiSource {
+    pub url: String,
+    /// Extra request headers
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Sent as `Authorization: Bearer <token>`
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    #[serde(default)]
+    pub pagination: Pagination,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+    pub mapping: ApiMapping,
+}
+
+fn default_max_pages() -> usize {
+    100
+}
+
+/// How to request the page after the current one
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Pagination {
+    /// A single request
+    #[default]
+    None,
+    /// Read the next cursor from the response and send it as a query parameter
+    Cursor { cursor_path: String, param: String },
+    /// Increment a page-number query parameter
+    Page {
+        param: String,
+        #[serde(default = "default_first_page")]
+        start: u64,
+    },
+    /// Advance an offset query parameter by the number of items received
+    Offset { param: String },
+    /// Follow a next-page URL found in the response (`$.links.next`)
+    NextUrl { path: String },
+}
+
+fn default_first_page() -> u64 {
+    1
+}
+
+/// Maps each item of a response to a subject and its properties
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiMapping {
+    /// JSONPath selecting the items of a page (`$.data[*]`); defaults to the root,
+    /// or its elements when the root is an array
+    #[serde(default = "default_items")]
+    pub items: String,
+    /// Subject URI template; `{path}` is replaced by the item's value at that JSONPath
+    pub subject: String,
+    /// rdf:type for every item
+    #[serde(default)]
+    pub class: Option<String>,
+    /// Item-relative JSONPath -> predicate URI. Strings that are URIs become nodes,
+    /// numbers and booleans become typed literals, arrays yield one triple per value.
+    #[serde(default)]
+    pub predicates: BTreeMap<String, String>,
+}
+
+fn default_items() -> String {
+    "$".to_string()
+}
+
+impl ApiSource {
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// URL of the page after `current`, or `None` when the listing is exhausted.
+    /// `page` is the 0-based index of `current`, `items` the number of items it held.
+    pub fn next_url(&self, current: &Url, body: &Value, page: usize, items: usize) -> Option<Url> {
+        if page + 1 >= self.max_pages {
+            return None;
+        }
+        match &self.pagination {
+            Pagination::None => None,
+            Pagination::Cursor { cursor_path, param } => {
+                let cursor = json_path(body, cursor_path)
+                    .into_iter()
+                    .find_map(scalar_text)
+                    .filter(|c| !c.is_empty())?;
+                // A cursor that does not move would loop forever
+                if query_param(current, param).as_deref() == Some(cursor.as_str()) {
+                    return None;
+                }
+                Some(with_query_param(current, param, &cursor))
+            }
+            Pagination::Page { param, start } => {
+                if items == 0 {
+                    return None;
+                }
+                let number = query_param(current, param)
+                    .and_then(|p| p.parse::<u64>().ok())
+                    .unwrap_or(*start);
+                Some(with_query_param(current, param, &(number + 1).to_string()))
+            }
+            Pagination::Offset { param } => {
+                if items == 0 {
+                    return None;
+                }
+                let offset = query_param(current, param)
+                    .and_then(|p| p.parse::<usize>().ok())
+                    .unwrap_or(0);
+                Some(with_query_param(
+                    current,
+                    param,
+                    &(offset + items).to_string(),
+                ))
+            }
+            Pagination::NextUrl { path } => {
+                let next = json_path(body, path).into_iter().find_map(scalar_text)?;
+                let next = current.join(&next).ok()?;
+                (next != *current).then_some(next)
+            }
+        }
+    }
+
+    /// URL of the first page; page-number pagination starts at `start`
+    pub fn first_url(&self) -> Result<Url> {
+        let url = Url::parse(&self.url)?;
+        Ok(match &self.pagination {
+            Pagination::Page { param, start } if query_param(&url, param).is_none() => {
+                with_query_param(&url, param, &start.to_string())
+            }
+            _ => url,
+        })
+    }
+
+    /// Fetch one page as JSON
+    pub async fn fetch(&self, client: &reqwest::Client, url: &Url) -> Result<Value> {
+        let mut request = client.get(url.clone());
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "HTTP error: {} ({})",
+                response.status(),
+                url
+            ));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+impl ApiMapping {
+    /// The items of one response page
+    pub fn items<'a>(&self, body: &'a Value) -> Vec<&'a Value> {
+        match json_path(body, &self.items).as_slice() {
+            [Value::Array(items)] if self.items.trim() == "$" => items.iter().collect(),
+            selected => selected.to_vec(),
+        }
+    }
+
+    /// Triples for one item, or `None` when the subject template cannot be filled
+    pub fn map_item(&self, item: &Value) -> Option<Vec<(String, String, String)>> {
+        let subject = self.subject_for(item)?;
+        let mut triples = Vec::new();
+        if let Some(class) = &self.class {
+            triples.push((subject.clone(), RDF_TYPE.to_string(), class.clone()));
+        }
+        for (path, predicate) in &self.predicates {
+            for value in json_path(item, path) {
+                for object in objects(value) {
+                    triples.push((subject.clone(), predicate.clone(), object));
+                }
+            }
+        }
+        Some(triples)
+    }
+
+    fn subject_for(&self, item: &Value) -> Option<String> {
+        let mut subject = String::new();
+        let mut rest = self.subject.as_str();
+        while let Some(start) = rest.find('{') {
+            let len = rest[start..].find('}')?;
+            let path = &rest[start + 1..start + len];
+            let value = json_path(item, path)
+                .into_iter()
+                .find_map(scalar_text)
+                .filter(|v| !v.is_empty())?;
+            subject.push_str(&rest[..start]);
+            subject.push_str(&encode_segment(&value));
+            rest = &rest[start + len + 1..];
+        }
+        subject.push_str(rest);
+        Some(subject)
+    }
+}
+
+/// Object terms for a JSON value, in the form `SynapseStore::ingest_triples` expects
+fn objects(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(values) => values.iter().flat_map(objects).collect(),
+        Value::Bool(b) => vec![format!("\"{}\"^^<{}boolean>", b, XSD)],
+        Value::Number(n) if n.is_i64() || n.is_u64() => {
+            vec![format!("\"{}\"^^<{}integer>", n, XSD)]
+        }
+        Value::Number(n) => vec![format!("\"{}\"^^<{}double>", n, XSD)],
+        Value::String(s) if s.starts_with("http://") || s.starts_with("https://") => {
+            vec![s.clone()]
+        }
+        Value::String(s) if s.starts_with("urn:") && !s.contains(char::is_whitespace) => {
+            vec![s.clone()]
+        }
+        Value::String(s) if !s.is_empty() => vec![format!("\"{}\"", s.replace('"', "'"))],
+        _ => Vec::new(),
+    }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.into_owned())
+}
+
+fn with_query_param(url: &Url, name: &str, value: &str) -> Url {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != name)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let mut next = url.clone();
+    next.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(name, value);
+    next
+}
+
+/// Evaluate a JSONPath subset: `$`, `.key`, `['key']`, `[n]`, `[*]` and `.*`.
+/// The leading `$` is optional, so item-relative paths can be written as `author.id`.
+pub fn json_path<'a>(root: &'a Value, path: &str) -> Vec<&'a Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut current = vec![root];
+    for step in path_steps(path) {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&'a Value> {
+                match (&step, value) {
+                    (Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (Step::Index(i), Value::Array(items)) => items.get(*i).into_iter().collect(),
+                    (Step::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    (Step::Wildcard, Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    current
+}
+
+enum Step {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+fn path_steps(path: &str) -> Vec<Step> {
+    let mut steps = Vec::new();
+    let mut chars = path.chars().peekable();
+    let mut key = String::new();
+    let flush = |key: &mut String, steps: &mut Vec<Step>| {
+        let key = std::mem::take(key);
+        if key == "*" {
+            steps.push(Step::Wildcard);
+        } else if !key.is_empty() {
+            steps.push(Step::Key(key));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => flush(&mut key, &mut steps),
+            '[' => {
+                flush(&mut key, &mut steps);
+                let mut inner = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    inner.push(c);
+                }
+                let inner = inner.trim();
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                steps.push(match (quoted, inner.parse::<usize>()) {
+                    (Some(name), _) => Step::Key(name.to_string()),
+                    (None, Ok(i)) => Step::Index(i),
+                    _ => Step::Wildcard,
+                });
+            }
+            c => key.push(c),
+        }
+    }
+    flush(&mut key, &mut steps);
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_path() {
+        let doc = json!({
+            "data": [
+                { "id": 1, "tags": [{ "name": "a" }, { "name": "b" }] },
+                { "id": 2, "tags": [] }
+            ],
+            "meta": { "next-cursor": "abc" }
+        });
+        assert_eq!(json_path(&doc, "$.data[*]").len(), 2);
+        assert_eq!(json_path(&doc, "$.data[1].id"), vec![&json!(2)]);
+        assert_eq!(
+            json_path(&doc, "data[*].tags[*].name"),
+            vec![&json!("a"), &json!("b")]
+        );
+        assert_eq!(
+            json_path(&doc, "$.meta['next-cursor']"),
+            vec![&json!("abc")]
+        );
+        assert!(json_path(&doc, "$.missing.key").is_empty());
+    }
+
+    #[test]
+    fn test_map_item() {
+        let source = ApiSource::parse(
+            r#"{
+              "url": "https://api.example.com/issues",
+              "mapping": {
+                "items": "$.data[*]",
+                "subject": "https://example.com/issue/{id}",
+                "class": "http://schema.org/CreativeWork",
+                "predicates": {
+                  "$.title": "http://schema.org/name",
+                  "$.comments": "http://schema.org/commentCount",
+                  "$.labels[*]": "http://schema.org/keywords",
+                  "$.author.url": "http://schema.org/author"
+                }
+              }
+            }"#,
+        )
+        .unwrap();
+        let body = json!({ "data": [
+            { "id": 7, "title": "Crash", "comments": 3, "labels": ["bug", "p1"],
+              "author": { "url": "https://example.com/u/ann" } },
+            { "title": "no id" }
+        ]});
+
+        let items = source.mapping.items(&body);
+        assert_eq!(items.len(), 2);
+        let triples = source.mapping.map_item(items[0]).unwrap();
+        let subject = "https://example.com/issue/7";
+        let has = |p: &str, o: &str| {
+            triples
+                .iter()
+                .any(|t| t.0 == subject && t.1 == p && t.2 == o)
+        };
+        assert!(has(RDF_TYPE, "http://schema.org/CreativeWork"));
+        assert!(has("http://schema.org/name", "\"Crash\""));
+        assert!(has(
+            "http://schema.org/commentCount",
+            "\"3\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+        ));
+        assert!(has("http://schema.org/keywords", "\"p1\""));
+        assert!(has("http://schema.org/author", "https://example.com/u/ann"));
+        assert!(source.mapping.map_item(items[1]).is_none());
+    }
+
+    #[test]
+    fn test_next_url() {
+        let source = |pagination: Value| {
+            ApiSource::parse(
+                &json!({
+                    "url": "https://api.example.com/items?limit=2",
+                    "pagination": pagination,
+                    "max_pages": 3,
+                    "mapping": { "subject": "urn:item:{id}" }
+                })
+                .to_string(),
+            )
+            .unwrap()
+        };
+        let body = json!({ "next": "c2", "links": { "next": "/items?page=9" } });
+
+        let cursor =
+            source(json!({ "type": "cursor", "cursor_path": "$.next", "param": "cursor" }));
+        let first = cursor.first_url().unwrap();
+        let second = cursor.next_url(&first, &body, 0, 2).unwrap();
+        assert_eq!(
+            second.as_str(),
+            "https://api.example.com/items?limit=2&cursor=c2"
+        );
+        assert!(
+            cursor.next_url(&second, &body, 1, 2).is_none(),
+            "cursor did not move"
+        );
+
+        let page = source(json!({ "type": "page", "param": "page" }));
+        let first = page.first_url().unwrap();
+        assert_eq!(
+            first.as_str(),
+            "https://api.example.com/items?limit=2&page=1"
+        );
+        let second = page.next_url(&first, &body, 0, 2).unwrap();
+        assert_eq!(
+            second.as_str(),
+            "https://api.example.com/items?limit=2&page=2"
+        );
+        assert!(page.next_url(&second, &body, 1, 0).is_none(), "empty page");
+        assert!(page.next_url(&second, &body, 2, 2).is_none(), "max_pages");
+
+        let offset = source(json!({ "type": "offset", "param": "offset" }));
+        let next = offset
+            .next_url(&offset.first_url().unwrap(), &body, 0, 2)
+            .unwrap();
+        assert_eq!(
+            next.as_str(),
+            "https://api.example.com/items?limit=2&offset=2"
+        );
+
+        let link = source(json!({ "type": "next_url", "path": "$.links.next" }));
+        let next = link
+            .next_url(&link.first_url().unwrap(), &body, 0, 2)
+            .unwrap();
+        assert_eq!(next.as_str(), "https://api.example.com/items?page=9");
+    }
+}
@@ -0,0 +1,670 @@
+use super::extractor::ExtractedTriple;
+use regex::Regex;
+use std::collections::HashMap;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+const CODE: &str = "http://synapse.os/code/";
+
+/// Source languages understood by the code extractor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    TypeScript,
+}
+
+impl Language {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "rs" => Some(Language::Rust),
+            "py" => Some(Language::Python),
+            "ts" | "tsx" => Some(Language::TypeScript),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::TypeScript => "typescript",
+        }
+    }
+
+    fn line_comment(&self) -> &'static str {
+        match self {
+            Language::Python => "#",
+            _ => "//",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    /// Struct, enum, trait, class or interface
+    Type,
+}
+
+impl SymbolKind {
+    fn class(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "Function",
+            SymbolKind::Method => "Method",
+            SymbolKind::Type => "Type",
+        }
+    }
+}
+
+/// A definition found in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// Qualified name (`Type.method` for methods)
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Enclosing type for methods
+    pub container: Option<String>,
+    /// 1-based first and last line of the definition
+    pub line: usize,
+    pub end_line: usize,
+    pub doc: Option<String>,
+}
+
+impl Symbol {
+    fn simple_name(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+}
+
+/// Structure of a source file: imports, definitions and intra-file calls
+#[derive(Debug, Clone, Default)]
+pub struct SourceOutline {
+    pub imports: Vec<String>,
+    pub symbols: Vec<Symbol>,
+    /// (caller, callee) qualified names; only calls to functions defined in the same file
+    pub calls: Vec<(String, String)>,
+}
+
+/// Line-oriented structural extractor for `.rs`, `.py` and `.ts` files. It relies on
+/// common formatting (one definition per line, braces or indentation for bodies)
+/// rather than a full parser.
+pub struct CodeExtractor {
+    language: Language,
+    definitions: Vec<(Regex, SymbolKind)>,
+    /// Blocks whose functions are methods (`impl X`, `class X`), capturing the type name
+    containers: Vec<Regex>,
+    imports: Vec<Regex>,
+    call: Regex,
+}
+
+impl CodeExtractor {
+    pub fn new(language: Language) -> Self {
+        let re = |s: &str| Regex::new(s).unwrap();
+        let (definitions, containers, imports) = match language {
+            Language::Rust => (
+                vec![
+                    (
+                        re(
+                            r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:extern\s+"[^"]*"\s+)?fn\s+([A-Za-z_]\w*)"#,
+                        ),
+                        SymbolKind::Function,
+                    ),
+                    (
+                        re(
+                            r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:struct|enum|trait|union)\s+([A-Za-z_]\w*)",
+                        ),
+                        SymbolKind::Type,
+                    ),
+                ],
+                vec![
+                    re(
+                        r"^\s*(?:unsafe\s+)?impl\b(?:<[^>]*>)?\s+(?:[\w:<>, ]+\s+for\s+)?([A-Za-z_]\w*)",
+                    ),
+                    re(r"^\s*(?:pub(?:\([^)]*\))?\s+)?trait\s+([A-Za-z_]\w*)"),
+                ],
+                vec![re(r"^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([\w:]+)")],
+            ),
+            Language::Python => (
+                vec![
+                    (
+                        re(r"^\s*(?:async\s+)?def\s+([A-Za-z_]\w*)"),
+                        SymbolKind::Function,
+                    ),
+                    (re(r"^\s*class\s+([A-Za-z_]\w*)"), SymbolKind::Type),
+                ],
+                vec![re(r"^\s*class\s+([A-Za-z_]\w*)")],
+                vec![
+                    re(r"^\s*from\s+([\w.]+)\s+import\b"),
+                    re(r"^\s*import\s+([\w.]+(?:\s*,\s*[\w.]+)*)"),
+                ],
+            ),
+            Language::TypeScript => (
+                vec![
+                    (
+                        re(
+                            r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*([A-Za-z_$][\w$]*)",
+                        ),
+                        SymbolKind::Function,
+                    ),
+                    (
+                        re(
+                            r"^\s*(?:export\s+)?(?:const|let)\s+([A-Za-z_$][\w$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:\([^)]*\)|[A-Za-z_$][\w$]*)\s*(?::[^=]+)?=>",
+                        ),
+                        SymbolKind::Function,
+                    ),
+                    (
+                        re(
+                            r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?(?:class|interface|enum)\s+([A-Za-z_$][\w$]*)",
+                        ),
+                        SymbolKind::Type,
+                    ),
+                    (
+                        re(
+                            r"^\s+(?:(?:public|private|protected|static|readonly|async|override)\s+)*([A-Za-z_$][\w$]*)\s*(?:<[^>]*>)?\([^)]*\)\s*(?::[^{;]+)?\{",
+                        ),
+                        SymbolKind::Method,
+                    ),
+                ],
+                vec![re(
+                    r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+([A-Za-z_$][\w$]*)",
+                )],
+                vec![
+                    re(r#"^\s*import\b.*?\bfrom\s+['"]([^'"]+)['"]"#),
+                    re(r#"^\s*import\s+['"]([^'"]+)['"]"#),
+                    re(r#"require\(\s*['"]([^'"]+)['"]\s*\)"#),
+                ],
+            ),
+        };
+
+        Self {
+            language,
+            definitions,
+            containers,
+            imports,
+            call: re(r"\b([A-Za-z_$][\w$]*)\s*\("),
+        }
+    }
+
+    pub fn outline(&self, source: &str) -> SourceOutline {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut outline = SourceOutline::default();
+
+        // (type name, first line index, last line index)
+        let mut containers: Vec<(String, usize, usize)> = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            for re in &self.containers {
+                if let Some(c) = re.captures(line) {
+                    containers.push((c[1].to_string(), i, self.block_end(&lines, i)));
+                    break;
+                }
+            }
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            self.collect_imports(line, &mut outline.imports);
+
+            let Some((name, mut kind)) = self
+                .definitions
+                .iter()
+                .find_map(|(re, kind)| re.captures(line).map(|c| (c[1].to_string(), *kind)))
+            else {
+                continue;
+            };
+            // TypeScript method regex also matches control flow like `if (x) {`
+            if kind == SymbolKind::Method && is_keyword(&name) {
+                continue;
+            }
+
+            let container = containers
+                .iter()
+                .filter(|(_, start, end)| *start < i && i <= *end)
+                .max_by_key(|(_, start, _)| *start)
+                .map(|(name, _, _)| name.clone());
+            let name = match (&container, kind) {
+                (Some(container), SymbolKind::Function | SymbolKind::Method) => {
+                    kind = SymbolKind::Method;
+                    format!("{}.{}", container, name)
+                }
+                (None, SymbolKind::Method) => continue,
+                _ => name,
+            };
+
+            outline.symbols.push(Symbol {
+                name,
+                kind,
+                container: container.filter(|_| kind == SymbolKind::Method),
+                line: i + 1,
+                end_line: self.block_end(&lines, i) + 1,
+                doc: self.doc_comment(&lines, i),
+            });
+        }
+
+        outline.imports.dedup();
+        outline.calls = self.calls(&lines, &outline.symbols);
+        outline
+    }
+
+    fn collect_imports(&self, line: &str, imports: &mut Vec<String>) {
+        for re in &self.imports {
+            let Some(c) = re.captures(line) else {
+                continue;
+            };
+            match self.language {
+                Language::Rust => imports.push(rust_module(&c[1])),
+                Language::Python => {
+                    imports.extend(c[1].split(',').map(|m| m.trim().to_string()));
+                }
+                Language::TypeScript => imports.push(c[1].to_string()),
+            }
+            return;
+        }
+    }
+
+    /// Index of the last line of the block opened at line `start`
+    fn block_end(&self, lines: &[&str], start: usize) -> usize {
+        if self.language == Language::Python {
+            let indent = indentation(lines[start]);
+            let mut end = start;
+            let mut in_header = !header_complete(lines[start]);
+            for (j, line) in lines.iter().enumerate().skip(start + 1) {
+                if in_header {
+                    end = j;
+                    in_header = !header_complete(line);
+                    continue;
+                }
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if indentation(line) <= indent {
+                    break;
+                }
+                end = j;
+            }
+            return end;
+        }
+
+        let mut depth = 0i32;
+        let mut opened = false;
+        for (j, line) in lines.iter().enumerate().skip(start) {
+            let code = strip_comment(line, self.language.line_comment());
+            for c in code.chars() {
+                match c {
+                    '{' => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' => depth -= 1,
+                    // A declaration without a body (`fn f();` in a trait)
+                    ';' if !opened => return j,
+                    _ => {}
+                }
+            }
+            if opened && depth <= 0 {
+                return j;
+            }
+        }
+        lines.len().saturating_sub(1)
+    }
+
+    fn doc_comment(&self, lines: &[&str], def: usize) -> Option<String> {
+        let doc = match self.language {
+            Language::Rust => {
+                let mut doc = Vec::new();
+                for line in lines[..def].iter().rev() {
+                    let trimmed = line.trim();
+                    if let Some(text) = trimmed.strip_prefix("///") {
+                        doc.push(text.trim());
+                    } else if !trimmed.starts_with("#[") {
+                        break;
+                    }
+                }
+                doc.reverse();
+                doc.join("\n")
+            }
+            Language::TypeScript => {
+                let mut j = def;
+                while j > 0 && lines[j - 1].trim().starts_with('@') {
+                    j -= 1;
+                }
+                if j == 0 || !lines[j - 1].trim().ends_with("*/") {
+                    return None;
+                }
+                let end = j - 1;
+                let start = (0..=end)
+                    .rev()
+                    .find(|&k| lines[k].trim().starts_with("/**"))?;
+                lines[start..=end]
+                    .iter()
+                    .map(|l| {
+                        l.trim()
+                            .trim_start_matches("/**")
+                            .trim_end_matches("*/")
+                            .trim_start_matches('*')
+                            .trim()
+                    })
+                    .filter(|l| !l.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Language::Python => {
+                let mut header_end = def;
+                while header_end < lines.len() && !header_complete(lines[header_end]) {
+                    header_end += 1;
+                }
+                let first = lines
+                    .iter()
+                    .skip(header_end + 1)
+                    .find(|l| !l.trim().is_empty())?
+                    .trim();
+                let quote = ["\"\"\"", "'''"]
+                    .into_iter()
+                    .find(|q| first.starts_with(q))?;
+                let body = &first[3..];
+                if let Some(end) = body.find(quote) {
+                    body[..end].trim().to_string()
+                } else {
+                    let mut doc = vec![body.trim()];
+                    let start = lines.iter().position(|l| l.trim() == first)?;
+                    for line in &lines[start + 1..] {
+                        if let Some(end) = line.find(quote) {
+                            doc.push(line[..end].trim());
+                            break;
+                        }
+                        doc.push(line.trim());
+                    }
+                    doc.join("\n").trim().to_string()
+                }
+            }
+        };
+        (!doc.is_empty()).then_some(doc)
+    }
+
+    /// Calls from each function body to other functions defined in the file
+    fn calls(&self, lines: &[&str], symbols: &[Symbol]) -> Vec<(String, String)> {
+        let mut by_name: HashMap<&str, Vec<&Symbol>> = HashMap::new();
+        for symbol in symbols.iter().filter(|s| s.kind != SymbolKind::Type) {
+            by_name
+                .entry(symbol.simple_name())
+                .or_default()
+                .push(symbol);
+        }
+
+        let mut calls = Vec::new();
+        for caller in symbols.iter().filter(|s| s.kind != SymbolKind::Type) {
+            // The definition line itself holds the signature, not calls (unless one-liner)
+            let first = if caller.end_line > caller.line {
+                caller.line
+            } else {
+                caller.line - 1
+            };
+            for line in &lines[first..caller.end_line] {
+                let code = strip_comment(line, self.language.line_comment());
+                for c in self.call.captures_iter(code) {
+                    let Some(candidates) = by_name.get(&c[1]) else {
+                        continue;
+                    };
+                    // Prefer a method of the caller's own type, then a free function
+                    let callee = candidates
+                        .iter()
+                        .find(|s| caller.container.is_some() && s.container == caller.container)
+                        .or_else(|| candidates.iter().find(|s| s.container.is_none()))
+                        .or_else(|| (candidates.len() == 1).then(|| &candidates[0]));
+                    if let Some(callee) = callee {
+                        let edge = (caller.name.clone(), callee.name.clone());
+                        if !calls.contains(&edge) {
+                            calls.push(edge);
+                        }
+                    }
+                }
+            }
+        }
+        calls
+    }
+
+    /// Triples for a file: `defines`, `calls`, `imports`, plus types, labels and lines
+    pub fn extract(&self, file_uri: &str, outline: &SourceOutline) -> Vec<ExtractedTriple> {
+        let mut triples = vec![
+            ExtractedTriple {
+                subject: file_uri.to_string(),
+                predicate: RDF_TYPE.to_string(),
+                object: format!("{}File", CODE),
+            },
+            ExtractedTriple {
+                subject: file_uri.to_string(),
+                predicate: format!("{}language", CODE),
+                object: format!("\"{}\"", self.language.name()),
+            },
+        ];
+        triples.extend(self.extract_structure(file_uri, file_uri, outline));
+        triples
+    }
+
+    /// Structural triples where `owner` defines and imports, and symbol URIs are
+    /// built on `symbol_base` (a file, or a notebook shared by its cells)
+    pub fn extract_structure(
+        &self,
+        owner: &str,
+        symbol_base: &str,
+        outline: &SourceOutline,
+    ) -> Vec<ExtractedTriple> {
+        let triple = |s: &str, p: &str, o: String| ExtractedTriple {
+            subject: s.to_string(),
+            predicate: p.to_string(),
+            object: o,
+        };
+        let mut triples = Vec::new();
+
+        for module in &outline.imports {
+            let module_uri = module_uri(self.language, module);
+            triples.push(triple(
+                owner,
+                &format!("{}imports", CODE),
+                module_uri.clone(),
+            ));
+            triples.push(triple(&module_uri, RDF_TYPE, format!("{}Module", CODE)));
+            triples.push(triple(&module_uri, RDFS_LABEL, format!("\"{}\"", module)));
+        }
+
+        for symbol in &outline.symbols {
+            let uri = symbol_uri(symbol_base, &symbol.name);
+            triples.push(triple(owner, &format!("{}defines", CODE), uri.clone()));
+            triples.push(triple(
+                &uri,
+                RDF_TYPE,
+                format!("{}{}", CODE, symbol.kind.class()),
+            ));
+            triples.push(triple(&uri, RDFS_LABEL, format!("\"{}\"", symbol.name)));
+            triples.push(triple(
+                &uri,
+                &format!("{}line", CODE),
+                format!(
+                    "\"{}\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                    symbol.line
+                ),
+            ));
+            if let Some(container) = &symbol.container {
+                if outline.symbols.iter().any(|s| &s.name == container) {
+                    triples.push(triple(
+                        &uri,
+                        &format!("{}memberOf", CODE),
+                        symbol_uri(symbol_base, container),
+                    ));
+                }
+            }
+        }
+
+        for (caller, callee) in &outline.calls {
+            triples.push(triple(
+                &symbol_uri(symbol_base, caller),
+                &format!("{}calls", CODE),
+                symbol_uri(symbol_base, callee),
+            ));
+        }
+        triples
+    }
+}
+
+pub fn symbol_uri(file_uri: &str, name: &str) -> String {
+    format!("{}#{}", file_uri, name)
+}
+
+fn module_uri(language: Language, module: &str) -> String {
+    format!(
+        "urn:module:{}:{}",
+        language.name(),
+        module.replace(['/', ' '], ":")
+    )
+}
+
+/// `use a::b::{C, d}` / `use a::b::Item` -> `a::b`; lowercase leaves are modules
+fn rust_module(path: &str) -> String {
+    let path = path.trim_end_matches("::");
+    match path.rsplit_once("::") {
+        Some((parent, leaf)) if leaf.starts_with(|c: char| c.is_uppercase()) => parent.to_string(),
+        _ => path.to_string(),
+    }
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Python `def`/`class` headers may span lines; they end with `:`
+fn header_complete(line: &str) -> bool {
+    strip_comment(line, "#").trim_end().ends_with(':')
+}
+
+fn strip_comment<'a>(line: &'a str, marker: &str) -> &'a str {
+    line.find(marker).map_or(line, |i| &line[..i])
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "if" | "for" | "while" | "switch" | "catch" | "function" | "return" | "else"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_outline() {
+        let source = r#"use std::collections::HashMap;
+use crate::store::{SynapseStore, IngestTriple};
+
+/// Adds two numbers
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+pub struct Calc;
+
+impl Calc {
+    /// Sums a slice
+    pub fn sum(&self, xs: &[i32]) -> i32 {
+        xs.iter().fold(0, |acc, x| add(acc, *x))
+    }
+
+    fn total(&self) -> i32 {
+        self.sum(&[1, 2])
+    }
+}
+"#;
+        let outline = CodeExtractor::new(Language::Rust).outline(source);
+        assert_eq!(outline.imports, vec!["std::collections", "crate::store"]);
+        let names: Vec<&str> = outline.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["add", "Calc", "Calc.sum", "Calc.total"]);
+        assert_eq!(outline.symbols[0].doc.as_deref(), Some("Adds two numbers"));
+        assert_eq!(outline.symbols[0].end_line, 7);
+        assert_eq!(outline.symbols[2].kind, SymbolKind::Method);
+        assert_eq!(
+            outline.calls,
+            vec![
+                ("Calc.sum".to_string(), "add".to_string()),
+                ("Calc.total".to_string(), "Calc.sum".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_outline() {
+        let source = r#"import os, sys
+from pathlib import Path
+
+def load(path):
+    """Read a file."""
+    return helper(path)
+
+def helper(path):
+    return open(path).read()
+
+class Loader:
+    def run(self,
+            path):
+        """Run the loader.
+
+        Returns text.
+        """
+        return load(path)
+"#;
+        let outline = CodeExtractor::new(Language::Python).outline(source);
+        assert_eq!(outline.imports, vec!["os", "sys", "pathlib"]);
+        let names: Vec<&str> = outline.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["load", "helper", "Loader", "Loader.run"]);
+        assert_eq!(outline.symbols[0].doc.as_deref(), Some("Read a file."));
+        assert_eq!(
+            outline.symbols[3].doc.as_deref(),
+            Some("Run the loader.\n\nReturns text.")
+        );
+        assert_eq!(
+            outline.calls,
+            vec![
+                ("load".to_string(), "helper".to_string()),
+                ("Loader.run".to_string(), "load".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_outline() {
+        let source = r#"import { readFile } from "fs/promises";
+import "./polyfill";
+
+/**
+ * Formats a name.
+ */
+export function format(name: string): string {
+  return name.trim();
+}
+
+export const greet = (name: string) => {
+  return `Hello ${format(name)}`;
+};
+
+export class Greeter {
+  hello(name: string): string {
+    if (name) {
+      return greet(name);
+    }
+    return "";
+  }
+}
+"#;
+        let outline = CodeExtractor::new(Language::TypeScript).outline(source);
+        assert_eq!(outline.imports, vec!["fs/promises", "./polyfill"]);
+        let names: Vec<&str> = outline.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["format", "greet", "Greeter", "Greeter.hello"]);
+        assert_eq!(outline.symbols[0].doc.as_deref(), Some("Formats a name."));
+        assert_eq!(
+            outline.calls,
+            vec![
+                ("greet".to_string(), "format".to_string()),
+                ("Greeter.hello".to_string(), "greet".to_string()),
+            ]
+        );
+    }
+}
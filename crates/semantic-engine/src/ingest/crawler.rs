@@ -0,0 +1,247 @@
+use super::readability::{self, Section};
+use anyhow::Result;
+use regex::Regex;
+use reqwest::Url;
+use std::collections::{HashSet, VecDeque};
+use tokio_util::sync::CancellationToken;
+
+/// A fetched web page
+#[derive(Debug, Clone)]
+pub struct CrawledPage {
+    pub url: String,
+    /// Article title (see `readability::extract`)
+    pub title: String,
+    /// Main content only, without navigation and other page chrome
+    pub text: String,
+    /// Main content split at headings
+    pub sections: Vec<Section>,
+    /// Same-domain links found on the page
+    pub links: Vec<String>,
+    pub depth: usize,
+}
+
+/// Breadth-first crawler restricted to the starting URL's host
+pub struct Crawler {
+    client: reqwest::Client,
+    pub max_depth: usize,
+    pub max_pages: usize,
+    progress: Option<super::ProgressFn>,
+    cancel: Option<CancellationToken>,
+}
+
+impl Crawler {
+    pub fn new(max_depth: usize, max_pages: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            max_depth,
+            max_pages: max_pages.max(1),
+            progress: None,
+            cancel: None,
+        }
+    }
+
+    /// Stop crawling (with an error) once `cancel` fires
+    pub fn with_cancellation(mut self, cancel: Option<CancellationToken>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Report each fetched page (out of `max_pages`) to `progress`
+    pub fn with_progress(mut self, progress: Option<super::ProgressFn>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Crawl from `start`. A URL ending in `sitemap.xml` seeds the crawl with its
+    /// `<loc>` entries instead of following links.
+    pub async fn crawl(&self, start: &str) -> Result<Vec<CrawledPage>> {
+        let start = Url::parse(start)?;
+        let mut queue: VecDeque<(Url, usize)> = VecDeque::new();
+        let mut seen: HashSet<String> = HashSet::new();
+
+        if start.path().ends_with("sitemap.xml") {
+            let xml = self.fetch(&start).await?.1;
+            for loc in sitemap_locations(&xml) {
+                if let Ok(url) = Url::parse(&loc) {
+                    if same_host(&start, &url) && seen.insert(normalize(&url)) {
+                        queue.push_back((url, 0));
+                    }
+                }
+            }
+        } else {
+            seen.insert(normalize(&start));
+            queue.push_back((start.clone(), 0));
+        }
+
+        let mut pages = Vec::new();
+        while let Some((url, depth)) = queue.pop_front() {
+            if pages.len() >= self.max_pages {
+                break;
+            }
+            if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                return Err(anyhow::anyhow!("Crawl cancelled"));
+            }
+
+            let (content_type, html) = match self.fetch(&url).await {
+                Ok(r) => r,
+                // The starting page must load; later pages are best-effort
+                Err(e) if pages.is_empty() && queue.is_empty() => return Err(e),
+                Err(e) => {
+                    tracing::warn!("Skipping {}: {}", url, e);
+                    continue;
+                }
+            };
+            if !content_type.is_empty() && !content_type.contains("html") {
+                continue;
+            }
+
+            // Deduplicate while keeping document order, so crawl order is stable
+            let mut on_page = HashSet::new();
+            let links: Vec<String> = extract_links(&url, &html)
+                .into_iter()
+                .filter(|link| same_host(&start, link))
+                .map(|link| normalize(&link))
+                .filter(|link| on_page.insert(link.clone()))
+                .collect();
+
+            if depth < self.max_depth {
+                for link in &links {
+                    if seen.insert(link.clone()) {
+                        if let Ok(next) = Url::parse(link) {
+                            queue.push_back((next, depth + 1));
+                        }
+                    }
+                }
+            }
+
+            if let Some(progress) = &self.progress {
+                progress(
+                    (pages.len() + 1) as f32,
+                    self.max_pages as f32,
+                    &format!("Fetched {}", url),
+                );
+            }
+            let article = readability::extract(&html);
+            pages.push(CrawledPage {
+                url: normalize(&url),
+                text: article.text(),
+                title: article.title,
+                sections: article.sections,
+                links,
+                depth,
+            });
+        }
+
+        Ok(pages)
+    }
+
+    async fn fetch(&self, url: &Url) -> Result<(String, String)> {
+        let response = self.client.get(url.clone()).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_lowercase();
+        Ok((content_type, response.text().await?))
+    }
+}
+
+/// Strip scripts, styles and tags, collapsing whitespace
+pub fn html_to_text(html: &str) -> String {
+    let script_re = Regex::new(r"(?s)<script.*?>.*?</script>").unwrap();
+    let style_re = Regex::new(r"(?s)<style.*?>.*?</style>").unwrap();
+    let tag_re = Regex::new(r"<[^>]*>").unwrap();
+
+    let no_script = script_re.replace_all(html, " ");
+    let no_style = style_re.replace_all(&no_script, " ");
+    let text_content = tag_re.replace_all(&no_style, " ");
+
+    text_content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(super) fn extract_title(html: &str) -> String {
+    let title_re = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    title_re
+        .captures(html)
+        .map(|c| c[1].split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
+
+/// Absolute http(s) links from `href` attributes
+fn extract_links(base: &Url, html: &str) -> Vec<Url> {
+    let href_re = Regex::new(r#"(?i)<a\s[^>]*href\s*=\s*["']([^"'#][^"']*)["']"#).unwrap();
+    href_re
+        .captures_iter(html)
+        .filter_map(|c| base.join(c[1].trim()).ok())
+        .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+        .collect()
+}
+
+fn sitemap_locations(xml: &str) -> Vec<String> {
+    let loc_re = Regex::new(r"(?is)<loc>\s*(.*?)\s*</loc>").unwrap();
+    loc_re
+        .captures_iter(xml)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+fn same_host(a: &Url, b: &Url) -> bool {
+    a.host_str() == b.host_str()
+}
+
+/// Drop fragments so `page#a` and `page#b` are crawled once
+fn normalize(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_links_and_title() {
+        let base = Url::parse("https://example.org/docs/index.html").unwrap();
+        let html = r##"<html><head><title> Docs
+            Home </title></head><body>
+            <a href="intro.html">Intro</a>
+            <a class="x" href="/about#team">About</a>
+            <a href="#top">Top</a>
+            <a href="mailto:hi@example.org">Mail</a>
+            <a href="https://other.org/">Other</a>
+            </body></html>"##;
+
+        assert_eq!(extract_title(html), "Docs Home");
+
+        let links: Vec<String> = extract_links(&base, html)
+            .iter()
+            .filter(|u| same_host(&base, u))
+            .map(normalize)
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                "https://example.org/docs/intro.html".to_string(),
+                "https://example.org/about".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sitemap_locations() {
+        let xml = r#"<urlset><url><loc> https://example.org/a </loc></url>
+            <url><loc>https://example.org/b</loc></url></urlset>"#;
+        assert_eq!(
+            sitemap_locations(xml),
+            vec!["https://example.org/a", "https://example.org/b"]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// Delimiters tried when sniffing, in order of preference on ties
+const CANDIDATE_DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Number of lines inspected when sniffing the delimiter
+const SNIFF_LINES: usize = 20;
+
+/// Delimiter and quoting options for CSV-like files. Unset fields are sniffed or
+/// fall back to RFC 4180 defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: Option<u8>,
+    pub quote: Option<u8>,
+    /// Escape character; when set, doubled quotes are no longer treated as escapes
+    pub escape: Option<u8>,
+}
+
+impl CsvDialect {
+    /// Build from request parameters. Accepts single characters or the names
+    /// `tab`, `comma`, `semicolon` and `pipe` (and `\t`).
+    pub fn from_params(delimiter: &str, quote: &str, escape: &str) -> Result<Self> {
+        Ok(Self {
+            delimiter: parse_char(delimiter, "delimiter")?,
+            quote: parse_char(quote, "quote")?,
+            escape: parse_char(escape, "escape")?,
+        })
+    }
+
+    /// Reader over `content` (the text of `path`), sniffing the delimiter from the
+    /// first lines unless it is set explicitly or implied by a `.tsv` extension
+    pub fn reader<'a>(&self, path: &Path, content: &'a str) -> Result<csv::Reader<&'a [u8]>> {
+        let delimiter = match self.delimiter {
+            Some(d) => d,
+            None if path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("tsv")) =>
+            {
+                b'\t'
+            }
+            None => {
+                let mut end = content.len().min(64 * 1024);
+                while !content.is_char_boundary(end) {
+                    end -= 1;
+                }
+                sniff_delimiter(&content[..end], self.quote.unwrap_or(b'"'))
+            }
+        };
+
+        let mut builder = csv::ReaderBuilder::new();
+        builder.delimiter(delimiter);
+        if let Some(quote) = self.quote {
+            builder.quote(quote);
+        }
+        if let Some(escape) = self.escape {
+            builder.escape(Some(escape)).double_quote(false);
+        }
+        Ok(builder.from_reader(content.as_bytes()))
+    }
+}
+
+fn parse_char(value: &str, name: &str) -> Result<Option<u8>> {
+    let c = match value {
+        "" => return Ok(None),
+        "tab" | "\\t" => b'\t',
+        "comma" => b',',
+        "semicolon" => b';',
+        "pipe" => b'|',
+        v if v.len() == 1 && v.is_ascii() => v.as_bytes()[0],
+        v => {
+            return Err(anyhow::anyhow!(
+                "Invalid {} '{}': expected a single ASCII character",
+                name,
+                v
+            ))
+        }
+    };
+    Ok(Some(c))
+}
+
+/// Pick the candidate delimiter that appears most consistently (same non-zero count
+/// outside quotes) across the sample's lines. Defaults to a comma.
+pub fn sniff_delimiter(sample: &str, quote: u8) -> u8 {
+    let lines: Vec<&str> = sample
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    if lines.is_empty() {
+        return b',';
+    }
+
+    let mut best = (b',', 0usize, 0usize); // (delimiter, consistent lines, count per line)
+    for &candidate in &CANDIDATE_DELIMITERS {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_unquoted(line, candidate, quote))
+            .collect();
+        let first = counts[0];
+        if first == 0 {
+            continue;
+        }
+        let consistent = counts.iter().filter(|&&c| c == first).count();
+        if consistent > best.1 || (consistent == best.1 && first > best.2) {
+            best = (candidate, consistent, first);
+        }
+    }
+    best.0
+}
+
+fn count_unquoted(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for &b in line.as_bytes() {
+        if b == quote {
+            in_quotes = !in_quotes;
+        } else if b == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiter() {
+        assert_eq!(sniff_delimiter("a,b,c\n1,2,3\n", b'"'), b',');
+        assert_eq!(sniff_delimiter("a\tb\tc\n1\t2\t3\n", b'"'), b'\t');
+        // Commas inside quotes and decimals with commas don't win over semicolons
+        assert_eq!(
+            sniff_delimiter("name;price\n\"Smith, J\";1,5\nDoe;2,25\n", b'"'),
+            b';'
+        );
+        assert_eq!(sniff_delimiter("single column\nvalue\n", b'"'), b',');
+    }
+
+    #[test]
+    fn test_from_params() {
+        let dialect = CsvDialect::from_params("tab", "'", "\\").unwrap();
+        assert_eq!(dialect.delimiter, Some(b'\t'));
+        assert_eq!(dialect.quote, Some(b'\''));
+        assert_eq!(dialect.escape, Some(b'\\'));
+        assert!(CsvDialect::from_params(";;", "", "").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Declarative mapping from CSV rows to triples, loaded from JSON or YAML.
+///
+/// ```json
+/// {
+///   "subject": "http://example.org/person/{id}",
+///   "skip": ["internal_notes"],
+///   "columns": {
+///     "name": { "predicate": "http://xmlns.com/foaf/0.1/name" },
+///     "age": { "predicate": "http://xmlns.com/foaf/0.1/age", "datatype": "xsd:integer" },
+///     "employer": { "predicate": "http://schema.org/worksFor", "datatype": "iri" }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CsvMapping {
+    /// Subject URI template; `{column}` is replaced by the row's value
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Columns that never produce triples
+    #[serde(default)]
+    pub skip: Vec<String>,
+    #[serde(default)]
+    pub columns: HashMap<String, ColumnMapping>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ColumnMapping {
+    /// Predicate URI (defaults to `urn:csv:prop:<header>`)
+    #[serde(default)]
+    pub predicate: Option<String>,
+    /// XSD datatype (`xsd:integer`, `date`, full URI) or `iri` to emit a URI object
+    #[serde(default)]
+    pub datatype: Option<String>,
+    #[serde(default)]
+    pub skip: bool,
+}
+
+impl CsvMapping {
+    /// Parse a mapping given as JSON or YAML text
+    pub fn parse(text: &str) -> Result<Self> {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('{') {
+            Ok(serde_json::from_str(trimmed)?)
+        } else {
+            Ok(serde_yaml::from_str(text)?)
+        }
+    }
+
+    /// Look for a sidecar mapping next to the CSV (`data.csv` -> `data.mapping.json|yaml|yml`)
+    pub fn find_sidecar(csv_path: &Path) -> Result<Option<Self>> {
+        for ext in ["mapping.json", "mapping.yaml", "mapping.yml"] {
+            let candidate: PathBuf = csv_path.with_extension(ext);
+            if candidate.is_file() {
+                return Self::parse(&std::fs::read_to_string(candidate)?).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Columns referenced by the subject template
+    pub(crate) fn template_columns(&self) -> HashSet<String> {
+        let mut columns = HashSet::new();
+        if let Some(template) = &self.subject {
+            let mut rest = template.as_str();
+            while let Some(start) = rest.find('{') {
+                let Some(len) = rest[start..].find('}') else {
+                    break;
+                };
+                columns.insert(rest[start + 1..start + len].to_string());
+                rest = &rest[start + len + 1..];
+            }
+        }
+        columns
+    }
+
+    /// Subject URI of a row, or `None` when a template column is missing or empty
+    pub fn subject_for(
+        &self,
+        headers: &csv::StringRecord,
+        record: &csv::StringRecord,
+        default_subject: impl Fn(&str) -> String,
+    ) -> Option<String> {
+        match &self.subject {
+            Some(template) => {
+                let mut subject = template.clone();
+                for column in &self.template_columns() {
+                    let index = headers.iter().position(|h| h == column)?;
+                    let value = record.get(index)?;
+                    if value.is_empty() {
+                        return None;
+                    }
+                    subject = subject.replace(&format!("{{{}}}", column), &encode_segment(value));
+                }
+                Some(subject)
+            }
+            None => Some(default_subject(record.get(0)?)),
+        }
+    }
+
+    /// Map one CSV row to (subject, predicate, object) triples. `default_subject` is
+    /// used when no template is configured, in which case column 0 is the row ID.
+    pub fn map_row(
+        &self,
+        headers: &csv::StringRecord,
+        record: &csv::StringRecord,
+        default_subject: impl Fn(&str) -> String,
+    ) -> Option<Vec<(String, String, String)>> {
+        let template_columns = self.template_columns();
+        let subject = self.subject_for(headers, record, default_subject)?;
+
+        let mut triples = Vec::new();
+        for (j, field) in record.iter().enumerate() {
+            let Some(header) = headers.get(j) else {
+                continue;
+            };
+            let column = self.columns.get(header);
+            let implicit_id = match &self.subject {
+                Some(_) => template_columns.contains(header) && column.is_none(),
+                None => j == 0,
+            };
+            if field.is_empty()
+                || implicit_id
+                || self.skip.iter().any(|s| s == header)
+                || column.is_some_and(|c| c.skip)
+            {
+                continue;
+            }
+
+            let predicate = column
+                .and_then(|c| c.predicate.clone())
+                .unwrap_or_else(|| format!("urn:csv:prop:{}", header));
+            let object = match column.and_then(|c| c.datatype.as_deref()) {
+                Some("iri") | Some("uri") => field.to_string(),
+                Some(datatype) => format!("\"{}\"^^<{}>", field, expand_datatype(datatype)),
+                None => format!("\"{}\"", field),
+            };
+            triples.push((subject.clone(), predicate, object));
+        }
+        Some(triples)
+    }
+}
+
+fn expand_datatype(datatype: &str) -> String {
+    if let Some(local) = datatype.strip_prefix("xsd:") {
+        format!("{}{}", XSD, local)
+    } else if datatype.contains(':') {
+        datatype.to_string()
+    } else {
+        format!("{}{}", XSD, datatype)
+    }
+}
+
+/// Keep template substitutions URI-safe
+pub(crate) fn encode_segment(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() || "-._~".contains(c) => c.to_string(),
+            ' ' => "_".to_string(),
+            c => {
+                let mut buf = [0u8; 4];
+                c.encode_utf8(&mut buf)
+                    .bytes()
+                    .map(|b| format!("%{:02X}", b))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_row_with_template_and_datatypes() {
+        let mapping = CsvMapping::parse(
+            r#"
+subject: "http://example.org/person/{id}"
+skip: [notes]
+columns:
+  name: { predicate: "http://xmlns.com/foaf/0.1/name" }
+  age: { predicate: "http://xmlns.com/foaf/0.1/age", datatype: "xsd:integer" }
+  employer: { predicate: "http://schema.org/worksFor", datatype: iri }
+"#,
+        )
+        .unwrap();
+
+        let headers = csv::StringRecord::from(vec!["id", "name", "age", "employer", "notes"]);
+        let record = csv::StringRecord::from(vec![
+            "a 1",
+            "Alice",
+            "42",
+            "http://example.org/acme",
+            "secret",
+        ]);
+
+        let triples = mapping
+            .map_row(&headers, &record, |id| format!("urn:csv:x:{}", id))
+            .unwrap();
+        assert_eq!(
+            triples,
+            vec![
+                (
+                    "http://example.org/person/a_1".to_string(),
+                    "http://xmlns.com/foaf/0.1/name".to_string(),
+                    "\"Alice\"".to_string()
+                ),
+                (
+                    "http://example.org/person/a_1".to_string(),
+                    "http://xmlns.com/foaf/0.1/age".to_string(),
+                    "\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string()
+                ),
+                (
+                    "http://example.org/person/a_1".to_string(),
+                    "http://schema.org/worksFor".to_string(),
+                    "http://example.org/acme".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Text encoding detection for ingested files.
+//!
+//! Files are read as bytes and transcoded to UTF-8: a byte order mark wins, valid
+//! UTF-8 is kept as is, and anything else goes to chardetng, which guesses legacy
+//! encodings (Windows-1252, Shift_JIS, GBK, KOI8-R, ISO-8859-x, ...) from byte
+//! statistics. Callers can force an encoding.
+
+use anyhow::Result;
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use std::path::Path;
+
+/// Look up an encoding by its WHATWG label (`latin1`, `windows-1252`, `utf-16le`, …)
+pub fn parse_label(label: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| anyhow::anyhow!("Unknown encoding '{}'", label))
+}
+
+/// Guess the encoding of `bytes`
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, false)
+}
+
+/// Decode `bytes` with `encoding`, or the detected one. A byte order mark is stripped.
+pub fn decode(bytes: &[u8], encoding: Option<&'static Encoding>) -> String {
+    let encoding = encoding.unwrap_or_else(|| detect(bytes));
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// Read a text file as UTF-8, transcoding it if needed
+pub fn read_to_string(path: &Path, encoding: Option<&'static Encoding>) -> Result<String> {
+    Ok(decode(&std::fs::read(path)?, encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{KOI8_R, KOI8_U, SHIFT_JIS, WINDOWS_1252};
+
+    #[test]
+    fn test_detects_legacy_encodings() {
+        // "Zoë;café" in Windows-1252
+        let latin = b"Zo\xeb;caf\xe9";
+        assert_eq!(detect(latin), WINDOWS_1252);
+        assert_eq!(decode(latin, None), "Zoë;café");
+
+        assert_eq!(decode("Zoë".as_bytes(), None), "Zoë");
+        assert_eq!(decode(b"\xef\xbb\xbfid", None), "id");
+        assert_eq!(decode(b"\xff\xfei\x00d\x00", None), "id");
+    }
+
+    #[test]
+    fn test_detects_non_latin_legacy_encodings() {
+        let japanese = "東京の天気は晴れです。明日は雨が降るでしょう。データを読み込みました。";
+        let (bytes, _, _) = SHIFT_JIS.encode(japanese);
+        assert_eq!(detect(&bytes), SHIFT_JIS);
+        assert_eq!(decode(&bytes, None), japanese);
+
+        let russian = "Привет, мир! Это проверка кодировки для старых файлов.";
+        let (bytes, _, _) = KOI8_R.encode(russian);
+        // KOI8-U extends KOI8-R and decodes Russian text the same way
+        assert!([KOI8_R, KOI8_U].contains(&detect(&bytes)));
+        assert_eq!(decode(&bytes, None), russian);
+    }
+
+    #[test]
+    fn test_override() {
+        let encoding = parse_label("latin1").unwrap();
+        assert_eq!(decode(b"caf\xe9", Some(encoding)), "café");
+        assert!(parse_label("klingon").is_err());
+    }
+}
@@ -1,63 +1,367 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExtractedTriple {
     pub subject: String,
     pub predicate: String,
     pub object: String,
 }
 
+impl From<ExtractedTriple> for (String, String, String) {
+    fn from(t: ExtractedTriple) -> Self {
+        (t.subject, t.predicate, t.object)
+    }
+}
+
+/// Structural triples for a Markdown document (see `MarkdownExtractor`)
 pub fn extract_metadata(content: &str, source_path: &str) -> Vec<ExtractedTriple> {
-    let mut triples = Vec::new();
-    let mut current_header = String::new();
-    let _filename = std::path::Path::new(source_path)
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        if let Some(header) = trimmed.strip_prefix('#') {
-            current_header = header.trim_start_matches('#').trim().to_string();
-            // Link file to header
+    super::markdown::MarkdownExtractor::new(std::path::Path::new(source_path))
+        .extract(content)
+        .triples
+}
+
+/// Parses RDF data files (Turtle, N-Triples, N-Quads, TriG, N3, RDF/XML) into triples
+/// in the store's term syntax: literals keep their datatype or language tag and blank
+/// nodes stay blank (`_:id`, relabelled per file so separate files never share one).
+/// Graph names in quad formats are dropped; the batch lands in its own named graph.
+pub struct RdfExtractor {
+    format: oxigraph::io::RdfFormat,
+    base_uri: Option<String>,
+}
+
+impl RdfExtractor {
+    pub fn new(format: oxigraph::io::RdfFormat) -> Self {
+        Self {
+            format,
+            base_uri: None,
+        }
+    }
+
+    /// Pick the format from a file extension (`owl` is read as RDF/XML)
+    pub fn for_extension(extension: &str) -> Option<Self> {
+        use oxigraph::io::RdfFormat;
+
+        let format = match extension.to_lowercase().as_str() {
+            "owl" => RdfFormat::RdfXml,
+            ext @ ("ttl" | "nt" | "nq" | "trig" | "n3" | "rdf" | "xml") => {
+                RdfFormat::from_extension(ext)?
+            }
+            _ => return None,
+        };
+        Some(Self::new(format))
+    }
+
+    /// Resolve relative IRIs against `base_uri`
+    pub fn with_base_uri(mut self, base_uri: &str) -> Self {
+        self.base_uri = Some(base_uri.to_string());
+        self
+    }
+
+    pub fn extract(&self, reader: impl std::io::Read) -> anyhow::Result<Vec<ExtractedTriple>> {
+        use oxigraph::io::RdfParser;
+        use oxigraph::model::{vocab::xsd, Subject, Term};
+
+        let mut parser = RdfParser::from_format(self.format).rename_blank_nodes();
+        if let Some(base) = &self.base_uri {
+            parser = parser
+                .with_base_iri(base.as_str())
+                .map_err(|e| anyhow::anyhow!("Invalid base IRI {}: {}", base, e))?;
+        }
+
+        let mut triples = Vec::new();
+        for quad in parser.for_reader(reader) {
+            let quad = quad.map_err(|e| anyhow::anyhow!("RDF parse error: {}", e))?;
+            let subject = match quad.subject {
+                Subject::NamedNode(n) => n.into_string(),
+                Subject::BlankNode(b) => b.to_string(),
+                #[allow(unreachable_patterns)]
+                _ => continue,
+            };
+            let object = match quad.object {
+                Term::NamedNode(n) => n.into_string(),
+                Term::BlankNode(b) => b.to_string(),
+                Term::Literal(l) => match l.language() {
+                    Some(language) => format!("\"{}\"@{}", l.value(), language),
+                    None if l.datatype() == xsd::STRING => format!("\"{}\"", l.value()),
+                    None => format!("\"{}\"^^<{}>", l.value(), l.datatype().as_str()),
+                },
+                #[allow(unreachable_patterns)]
+                _ => continue,
+            };
+            triples.push(ExtractedTriple {
+                subject,
+                predicate: quad.predicate.into_string(),
+                object,
+            });
+        }
+        Ok(triples)
+    }
+}
+
+/// Converts JSON documents into triples. Object keys become predicates and nested
+/// objects become derived nodes (`<parent>/<key>`). JSON-LD is parsed as RDF.
+pub struct JsonExtractor {
+    base_uri: String,
+}
+
+impl JsonExtractor {
+    pub fn new(base_uri: &str) -> Self {
+        Self {
+            base_uri: base_uri.to_string(),
+        }
+    }
+
+    /// Extract triples from a JSON or JSON-LD document
+    pub fn extract(&self, content: &str) -> anyhow::Result<Vec<ExtractedTriple>> {
+        let value: serde_json::Value = serde_json::from_str(content)?;
+        if Self::is_jsonld(&value) {
+            return self.extract_jsonld(content);
+        }
+
+        let mut triples = Vec::new();
+        self.extract_value(&self.base_uri, &value, &mut triples);
+        Ok(triples)
+    }
+
+    /// A document is treated as JSON-LD if it declares a context or graph
+    pub fn is_jsonld(value: &serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.contains_key("@context") || map.contains_key("@graph")
+            }
+            serde_json::Value::Array(items) => items.iter().any(Self::is_jsonld),
+            _ => false,
+        }
+    }
+
+    /// Parse a JSON-LD document natively with oxigraph
+    pub fn extract_jsonld(&self, content: &str) -> anyhow::Result<Vec<ExtractedTriple>> {
+        use oxigraph::io::{JsonLdProfileSet, RdfFormat, RdfParser};
+        use oxigraph::model::{Subject, Term};
+
+        let parser = RdfParser::from_format(RdfFormat::JsonLd {
+            profile: JsonLdProfileSet::empty(),
+        })
+        .with_base_iri(self.base_uri.as_str())
+        .map_err(|e| anyhow::anyhow!("Invalid base IRI {}: {}", self.base_uri, e))?;
+
+        let mut triples = Vec::new();
+        for quad in parser.for_slice(content.as_bytes()) {
+            let quad = quad.map_err(|e| anyhow::anyhow!("JSON-LD parse error: {}", e))?;
+            let subject = match quad.subject {
+                Subject::NamedNode(n) => n.into_string(),
+                Subject::BlankNode(b) => self.blank_node_uri(b.as_str()),
+                #[allow(unreachable_patterns)]
+                _ => continue,
+            };
+            let object = match quad.object {
+                Term::NamedNode(n) => n.into_string(),
+                Term::BlankNode(b) => self.blank_node_uri(b.as_str()),
+                Term::Literal(l) => format!("\"{}\"", l.value()),
+                #[allow(unreachable_patterns)]
+                _ => continue,
+            };
             triples.push(ExtractedTriple {
-                subject: format!("file://{}", source_path),
-                predicate: "http://synapse.os/contains_section".to_string(),
-                object: current_header.clone(),
+                subject,
+                predicate: quad.predicate.into_string(),
+                object,
             });
-        } else if let Some(item) = trimmed
-            .strip_prefix("- ")
-            .or_else(|| trimmed.strip_prefix("* "))
-        {
-            if !current_header.is_empty() {
+        }
+        Ok(triples)
+    }
+
+    fn blank_node_uri(&self, id: &str) -> String {
+        format!("{}/_{}", self.base_uri, id)
+    }
+
+    fn extract_value(
+        &self,
+        subject: &str,
+        value: &serde_json::Value,
+        triples: &mut Vec<ExtractedTriple>,
+    ) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    let predicate = format!("http://synapse.os/property/{}", key.replace(' ', "_"));
+                    let child_uri = format!("{}/{}", subject, Self::path_segment(key));
+                    self.extract_field(subject, &predicate, &child_uri, child, triples);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let child_uri = format!("{}/{}", subject, i);
+                    self.extract_field(
+                        subject,
+                        "http://synapse.os/has_list_item",
+                        &child_uri,
+                        item,
+                        triples,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Link `subject` to a field value: scalars become literals, containers become derived nodes
+    fn extract_field(
+        &self,
+        subject: &str,
+        predicate: &str,
+        child_uri: &str,
+        value: &serde_json::Value,
+        triples: &mut Vec<ExtractedTriple>,
+    ) {
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Object(_) => {
                 triples.push(ExtractedTriple {
-                    subject: current_header.clone(),
-                    predicate: "http://synapse.os/has_list_item".to_string(),
-                    object: item.trim().to_string(),
+                    subject: subject.to_string(),
+                    predicate: predicate.to_string(),
+                    object: child_uri.to_string(),
                 });
+                self.extract_value(child_uri, value, triples);
             }
-        } else if trimmed.contains(':') {
-            let parts: Vec<&str> = trimmed.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim();
-                let value = parts[1].trim();
-                if !key.is_empty() && !value.is_empty() {
-                    let subject = if current_header.is_empty() {
-                        format!("file://{}", source_path)
-                    } else {
-                        current_header.clone()
-                    };
+            serde_json::Value::Array(items) => {
+                // Arrays repeat the predicate once per element
+                for (i, item) in items.iter().enumerate() {
+                    let item_uri = format!("{}/{}", child_uri, i);
+                    self.extract_field(subject, predicate, &item_uri, item, triples);
+                }
+            }
+            serde_json::Value::String(s) => triples.push(ExtractedTriple {
+                subject: subject.to_string(),
+                predicate: predicate.to_string(),
+                object: format!("\"{}\"", s),
+            }),
+            other => triples.push(ExtractedTriple {
+                subject: subject.to_string(),
+                predicate: predicate.to_string(),
+                object: format!("\"{}\"", other),
+            }),
+        }
+    }
 
-                    triples.push(ExtractedTriple {
-                        subject,
-                        predicate: format!("http://synapse.os/property/{}", key.replace(" ", "_")),
-                        object: value.to_string(),
-                    });
+    fn path_segment(key: &str) -> String {
+        key.chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
                 }
+            })
+            .collect()
+    }
+}
+
+/// Flattens YAML/TOML configuration into triples on a single subject per document,
+/// using the dotted key path as predicate (`server.port`, `replicas.0.host`).
+pub struct ConfigExtractor {
+    base_uri: String,
+}
+
+impl ConfigExtractor {
+    pub fn new(base_uri: &str) -> Self {
+        Self {
+            base_uri: base_uri.to_string(),
+        }
+    }
+
+    /// Extract triples from a YAML file; multi-document streams get one subject per document
+    pub fn extract_yaml(&self, content: &str) -> anyhow::Result<Vec<ExtractedTriple>> {
+        use serde::Deserialize;
+
+        let mut documents = Vec::new();
+        for document in serde_yaml::Deserializer::from_str(content) {
+            let value = serde_json::Value::deserialize(document)?;
+            if !value.is_null() {
+                documents.push(value);
             }
         }
+
+        let mut triples = Vec::new();
+        let multi = documents.len() > 1;
+        for (i, document) in documents.iter().enumerate() {
+            let subject = if multi {
+                format!("{}/{}", self.base_uri, i)
+            } else {
+                self.base_uri.clone()
+            };
+            Self::flatten(&subject, "", document, &mut triples);
+        }
+        Ok(triples)
+    }
+
+    /// Extract triples from a TOML file
+    pub fn extract_toml(&self, content: &str) -> anyhow::Result<Vec<ExtractedTriple>> {
+        let value: toml::Value = toml::from_str(content)?;
+        let mut triples = Vec::new();
+        Self::flatten(&self.base_uri, "", &Self::toml_to_json(value), &mut triples);
+        Ok(triples)
     }
 
-    triples
+    fn toml_to_json(value: toml::Value) -> serde_json::Value {
+        match value {
+            toml::Value::String(s) => serde_json::Value::String(s),
+            toml::Value::Integer(i) => serde_json::Value::from(i),
+            toml::Value::Float(f) => serde_json::Value::from(f),
+            toml::Value::Boolean(b) => serde_json::Value::Bool(b),
+            toml::Value::Datetime(d) => serde_json::Value::String(d.to_string()),
+            toml::Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(Self::toml_to_json).collect())
+            }
+            toml::Value::Table(table) => serde_json::Value::Object(
+                table
+                    .into_iter()
+                    .map(|(k, v)| (k, Self::toml_to_json(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn flatten(
+        subject: &str,
+        path: &str,
+        value: &serde_json::Value,
+        triples: &mut Vec<ExtractedTriple>,
+    ) {
+        let join = |segment: &str| {
+            if path.is_empty() {
+                segment.to_string()
+            } else {
+                format!("{}.{}", path, segment)
+            }
+        };
+
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    Self::flatten(subject, &join(&key.replace(' ', "_")), child, triples);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if item.is_object() || item.is_array() {
+                        Self::flatten(subject, &join(&i.to_string()), item, triples);
+                    } else {
+                        // Scalar lists repeat the predicate once per element
+                        Self::flatten(subject, path, item, triples);
+                    }
+                }
+            }
+            scalar if !path.is_empty() => {
+                let object = match scalar {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                triples.push(ExtractedTriple {
+                    subject: subject.to_string(),
+                    predicate: format!("http://synapse.os/property/{}", path),
+                    object: format!("\"{}\"", object),
+                });
+            }
+            _ => {}
+        }
+    }
 }
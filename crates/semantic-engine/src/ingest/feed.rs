@@ -0,0 +1,227 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// A single RSS `<item>` or Atom `<entry>`
+#[derive(Debug, Clone, Default)]
+pub struct FeedItem {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub published: Option<DateTime<Utc>>,
+    /// Full content if present, otherwise the summary/description (may contain HTML)
+    pub content: String,
+}
+
+impl FeedItem {
+    /// Stable URI for the item node: link, then guid/id
+    pub fn uri(&self) -> Option<String> {
+        [&self.link, &self.id]
+            .into_iter()
+            .find(|s| s.starts_with("http") || s.starts_with("urn:"))
+            .cloned()
+    }
+}
+
+/// A parsed RSS 2.0 or Atom feed
+#[derive(Debug, Clone, Default)]
+pub struct Feed {
+    pub title: String,
+    pub items: Vec<FeedItem>,
+}
+
+/// Parse an RSS 2.0 or Atom document
+pub fn parse_feed(xml: &str) -> Result<Feed> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut feed = Feed::default();
+    let mut current: Option<FeedItem> = None;
+    // Local name of the element whose text we are reading
+    let mut field = String::new();
+    let mut has_full_content = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let name = local_name(&e);
+                match name.as_str() {
+                    "item" | "entry" => {
+                        current = Some(FeedItem::default());
+                        has_full_content = false;
+                    }
+                    "link" => {
+                        if let Some(item) = current.as_mut() {
+                            if let Some(href) = atom_link_href(&e) {
+                                item.link = href;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                field = name;
+            }
+            // Atom links are usually self-closing
+            Event::Empty(e) if local_name(&e) == "link" => {
+                if let (Some(item), Some(href)) = (current.as_mut(), atom_link_href(&e)) {
+                    item.link = href;
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape()?.into_owned();
+                apply_text(
+                    &mut feed,
+                    current.as_mut(),
+                    &field,
+                    text,
+                    &mut has_full_content,
+                );
+            }
+            Event::CData(c) => {
+                let text = String::from_utf8_lossy(&c.into_inner()).into_owned();
+                apply_text(
+                    &mut feed,
+                    current.as_mut(),
+                    &field,
+                    text,
+                    &mut has_full_content,
+                );
+            }
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                if name == "item" || name == "entry" {
+                    if let Some(item) = current.take() {
+                        feed.items.push(item);
+                    }
+                }
+                field.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(feed)
+}
+
+fn apply_text(
+    feed: &mut Feed,
+    item: Option<&mut FeedItem>,
+    field: &str,
+    text: String,
+    has_full_content: &mut bool,
+) {
+    let Some(item) = item else {
+        if field == "title" && feed.title.is_empty() {
+            feed.title = text;
+        }
+        return;
+    };
+
+    match field {
+        "title" => item.title = text,
+        "link" if item.link.is_empty() => item.link = text,
+        "guid" | "id" => item.id = text,
+        // Prefer the original publication date over later updates
+        "pubDate" | "published" | "updated" | "date"
+            if item.published.is_none() || field != "updated" =>
+        {
+            if let Some(date) = parse_date(&text) {
+                item.published = Some(date);
+            }
+        }
+        "encoded" | "content" => {
+            item.content = text;
+            *has_full_content = true;
+        }
+        "description" | "summary" if !*has_full_content => item.content = text,
+        _ => {}
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// `href` of an Atom `<link>` pointing at the article itself
+fn atom_link_href(e: &BytesStart) -> Option<String> {
+    let mut href = None;
+    let mut rel = None;
+    for attr in e.attributes().flatten() {
+        let value = attr.unescape_value().ok()?.into_owned();
+        match attr.key.local_name().as_ref() {
+            b"href" => href = Some(value),
+            b"rel" => rel = Some(value),
+            _ => {}
+        }
+    }
+    match rel.as_deref() {
+        None | Some("alternate") => href,
+        _ => None,
+    }
+}
+
+/// Parse RFC 2822 (RSS) or RFC 3339 (Atom) timestamps
+pub fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    DateTime::parse_from_rfc2822(s)
+        .or_else(|_| DateTime::parse_from_rfc3339(s))
+        .map(|d| d.with_timezone(&Utc))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss() {
+        let xml = r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel>
+              <title>Example Blog</title>
+              <item>
+                <title>First post</title>
+                <link>https://example.org/first</link>
+                <pubDate>Tue, 10 Jun 2025 04:00:00 GMT</pubDate>
+                <description><![CDATA[<p>Hello <b>world</b></p>]]></description>
+              </item>
+            </channel></rss>"#;
+
+        let feed = parse_feed(xml).unwrap();
+        assert_eq!(feed.title, "Example Blog");
+        assert_eq!(feed.items.len(), 1);
+        let item = &feed.items[0];
+        assert_eq!(item.title, "First post");
+        assert_eq!(item.uri().as_deref(), Some("https://example.org/first"));
+        assert!(item.published.is_some());
+        assert!(item.content.contains("Hello"));
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Atom Feed</title>
+              <entry>
+                <title>Entry</title>
+                <link rel="alternate" href="https://example.org/entry"/>
+                <link rel="edit" href="https://example.org/edit"/>
+                <id>urn:uuid:1234</id>
+                <updated>2025-06-11T10:00:00Z</updated>
+                <published>2025-06-10T10:00:00Z</published>
+                <summary>Short</summary>
+                <content type="html">Long content</content>
+              </entry>
+            </feed>"#;
+
+        let feed = parse_feed(xml).unwrap();
+        let item = &feed.items[0];
+        assert_eq!(item.link, "https://example.org/entry");
+        assert_eq!(item.id, "urn:uuid:1234");
+        assert_eq!(item.content, "Long content");
+        assert_eq!(
+            item.published,
+            Some("2025-06-10T10:00:00Z".parse::<DateTime<Utc>>().unwrap())
+        );
+    }
+}
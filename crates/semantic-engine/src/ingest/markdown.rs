@@ -0,0 +1,613 @@
+use super::extractor::ExtractedTriple;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+const SKOS_ALT_LABEL: &str = "http://www.w3.org/2004/02/skos/core#altLabel";
+pub const MD: &str = "http://synapse.os/markdown/";
+
+/// A heading and the text under it (up to the next heading). The text before the
+/// first heading is a section whose URI is the document itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub uri: String,
+    pub title: String,
+    pub level: usize,
+    pub text: String,
+}
+
+/// Triples and sections extracted from one Markdown document
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownDocument {
+    pub uri: String,
+    pub triples: Vec<ExtractedTriple>,
+    pub sections: Vec<Section>,
+}
+
+/// Models a Markdown document as a heading tree: the document `hasSection` its
+/// top-level headings, each heading `hasSection` its subheadings, and list items,
+/// paragraphs and `key: value` lines attach to the nearest heading. YAML front
+/// matter becomes typed triples on the document node, and `[[WikiLinks]]` and
+/// `#tags` become `linksTo`/`taggedWith` edges between notes and tag nodes.
+pub struct MarkdownExtractor {
+    document_uri: String,
+    name: String,
+    path: String,
+}
+
+impl MarkdownExtractor {
+    pub fn new(path: &Path) -> Self {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        Self {
+            document_uri: Self::document_uri(&name),
+            name,
+            path: path.to_string_lossy().to_string(),
+        }
+    }
+
+    /// URI of the note called `name` (its file stem)
+    pub fn document_uri(name: &str) -> String {
+        format!("urn:note:{}", slugify(name))
+    }
+
+    pub fn extract(&self, content: &str) -> MarkdownDocument {
+        let doc = self.document_uri.as_str();
+        let mut out = MarkdownDocument {
+            uri: doc.to_string(),
+            ..Default::default()
+        };
+        let mut triples = Vec::new();
+        let mut push = |s: &str, p: String, o: String| {
+            triples.push(ExtractedTriple {
+                subject: s.to_string(),
+                predicate: p,
+                object: o,
+            })
+        };
+
+        push(doc, RDF_TYPE.to_string(), format!("{}Document", MD));
+        push(doc, format!("{}path", MD), literal(&self.path));
+
+        let (front_matter, content) = split_front_matter(content);
+        let mut front_title = None;
+        if let Some(front_matter) = &front_matter {
+            front_title = front_matter_triples(doc, front_matter, &mut push);
+        }
+
+        // Open headings as (level, uri); the document is the implicit root
+        let mut stack: Vec<(usize, String)> = Vec::new();
+        let mut used: HashMap<String, usize> = HashMap::new();
+        let mut current = Section {
+            uri: doc.to_string(),
+            title: String::new(),
+            level: 0,
+            text: String::new(),
+        };
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut title: Option<String> = None;
+        let mut fence: Option<&str> = None;
+
+        // `[[Note]]`, `[[Note|label]]`, `[[Note#Heading]]` and `![[embed]]`
+        let wikilink_re =
+            Regex::new(r"\[\[([^\]|#\n]*)(?:#[^\]|\n]*)?(?:\|[^\]\n]*)?\]\]").unwrap();
+        // `#tag` at a word start; must contain a non-digit (`#1` is not a tag)
+        let tag_re = Regex::new(r"(?:^|\s)#([\p{L}\p{N}_/-]*[\p{L}_/-][\p{L}\p{N}_/-]*)").unwrap();
+        let inline_code_re = Regex::new(r"`[^`]*`").unwrap();
+        let mut links = HashSet::new();
+        let mut tags = HashSet::new();
+
+        let flush_paragraph =
+            |paragraph: &mut Vec<&str>,
+             subject: &str,
+             push: &mut dyn FnMut(&str, String, String)| {
+                if !paragraph.is_empty() {
+                    push(
+                        subject,
+                        format!("{}hasParagraph", MD),
+                        literal(&paragraph.join(" ")),
+                    );
+                    paragraph.clear();
+                }
+            };
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+
+            // Code fences: kept in the section text, never parsed as structure
+            if let Some(marker) = fence {
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+                current.text.push_str(line);
+                current.text.push('\n');
+                continue;
+            }
+            if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+                flush_paragraph(&mut paragraph, &current.uri, &mut push);
+                fence = Some(marker);
+                current.text.push_str(line);
+                current.text.push('\n');
+                continue;
+            }
+
+            if let Some((level, heading)) = heading(trimmed) {
+                flush_paragraph(&mut paragraph, &current.uri, &mut push);
+                out.sections.push(std::mem::replace(
+                    &mut current,
+                    Section {
+                        uri: String::new(),
+                        title: heading.to_string(),
+                        level,
+                        text: String::new(),
+                    },
+                ));
+
+                while stack.last().is_some_and(|(l, _)| *l >= level) {
+                    stack.pop();
+                }
+                let parent = stack.last().map_or(doc, |(_, uri)| uri.as_str());
+                let mut uri = if parent == doc {
+                    format!("{}#{}", doc, slugify(heading))
+                } else {
+                    format!("{}/{}", parent, slugify(heading))
+                };
+                let seen = used.entry(uri.clone()).or_insert(0);
+                *seen += 1;
+                if *seen > 1 {
+                    uri = format!("{}-{}", uri, seen);
+                }
+
+                push(parent, format!("{}hasSection", MD), uri.clone());
+                push(&uri, RDF_TYPE.to_string(), format!("{}Section", MD));
+                push(&uri, RDFS_LABEL.to_string(), literal(heading));
+                push(
+                    &uri,
+                    format!("{}level", MD),
+                    format!("\"{}\"^^<{}integer>", level, XSD),
+                );
+                if level == 1 && title.is_none() {
+                    title = Some(heading.to_string());
+                }
+
+                stack.push((level, uri.clone()));
+                current.uri = uri;
+                continue;
+            }
+
+            current.text.push_str(line);
+            current.text.push('\n');
+
+            let prose = inline_code_re.replace_all(trimmed, " ");
+            for c in wikilink_re.captures_iter(&prose) {
+                // Notes are identified by file name: `[[folder/Note.md]]` -> `Note`.
+                // `[[#Heading]]` points into the current note and is skipped.
+                let target = c[1].trim();
+                let target = target.rsplit('/').next().unwrap_or(target);
+                let target = target.strip_suffix(".md").unwrap_or(target);
+                if !target.is_empty() && links.insert(Self::document_uri(target)) {
+                    push(doc, format!("{}linksTo", MD), Self::document_uri(target));
+                }
+            }
+            for c in tag_re.captures_iter(&prose) {
+                let tag = c[1].trim_end_matches(['/', '-']);
+                if !tag.is_empty() && tags.insert(tag_uri(tag)) {
+                    push_tag(doc, tag, &mut push);
+                }
+            }
+
+            if trimmed.is_empty() {
+                flush_paragraph(&mut paragraph, &current.uri, &mut push);
+            } else if let Some(item) = list_item(trimmed) {
+                flush_paragraph(&mut paragraph, &current.uri, &mut push);
+                push(&current.uri, format!("{}hasListItem", MD), literal(item));
+            } else if let Some((key, value)) = property(trimmed).filter(|_| paragraph.is_empty()) {
+                push(
+                    &current.uri,
+                    format!("http://synapse.os/property/{}", key.replace(' ', "_")),
+                    literal(value),
+                );
+            } else {
+                paragraph.push(trimmed);
+            }
+        }
+        flush_paragraph(&mut paragraph, &current.uri, &mut push);
+        out.sections.push(current);
+
+        let title = front_title.or(title).unwrap_or_else(|| self.name.clone());
+        push(doc, RDFS_LABEL.to_string(), literal(&title));
+        out.sections[0].title = title;
+
+        out.sections
+            .retain(|s| s.level > 0 || !s.text.trim().is_empty());
+        out.triples = triples;
+        out
+    }
+}
+
+/// `## Title` -> (2, "Title"). `#tag` (no space) is not a heading.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim();
+    (!title.is_empty()).then_some((level, title))
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|m| line.strip_prefix(m)) {
+        return Some(item.trim());
+    }
+    // Ordered lists: `1. item`
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(item) = line[digits..].strip_prefix(". ") {
+            return Some(item.trim());
+        }
+    }
+    None
+}
+
+/// `key: value` lines with a short key, e.g. `status: draft`
+fn property(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let (key, value) = (key.trim(), value.trim());
+    let valid_key = !key.is_empty()
+        && key.len() <= 40
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '_' || c == '-');
+    (valid_key && !value.is_empty() && !value.starts_with("//")).then_some((key, value))
+}
+
+/// Split a leading `---` YAML block from the body. Malformed YAML is left in the body.
+fn split_front_matter(content: &str) -> (Option<serde_yaml::Mapping>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return match serde_yaml::from_str::<serde_yaml::Value>(yaml) {
+                Ok(serde_yaml::Value::Mapping(mapping)) => (Some(mapping), body),
+                Ok(serde_yaml::Value::Null) => (None, body),
+                _ => (None, content),
+            };
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Triples describing `doc` from a JSON metadata object, mapped the same way as
+/// front matter (`title`, `tags`, `date`, ... get typed predicates)
+pub fn metadata_triples(
+    doc: &str,
+    metadata: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<ExtractedTriple> {
+    let mut triples = Vec::new();
+    if let Ok(serde_yaml::Value::Mapping(mapping)) = serde_yaml::to_value(metadata) {
+        front_matter_triples(doc, &mapping, &mut |s: &str, p: String, o: String| {
+            triples.push(ExtractedTriple {
+                subject: s.to_string(),
+                predicate: p,
+                object: o,
+            })
+        });
+    }
+    triples
+}
+
+/// Typed triples for front matter keys. `title`, `tags`, `date` and `aliases` map to
+/// Dublin Core, tag nodes and SKOS; other keys become `property/<key>`. Returns the title.
+fn front_matter_triples(
+    doc: &str,
+    front_matter: &serde_yaml::Mapping,
+    push: &mut dyn FnMut(&str, String, String),
+) -> Option<String> {
+    let mut title = None;
+    for (key, value) in front_matter {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        match key.to_lowercase().as_str() {
+            "title" => {
+                if let Some(text) = value.as_str().filter(|t| !t.trim().is_empty()) {
+                    push(doc, format!("{}title", DCTERMS), literal(text.trim()));
+                    title = Some(text.trim().to_string());
+                }
+            }
+            "tags" | "tag" => {
+                for tag in string_list(value) {
+                    let tag = tag.trim_start_matches('#');
+                    if !tag.is_empty() {
+                        push_tag(doc, tag, push);
+                    }
+                }
+            }
+            "aliases" | "alias" => {
+                for alias in string_list(value) {
+                    push(doc, SKOS_ALT_LABEL.to_string(), literal(&alias));
+                }
+            }
+            "date" | "created" | "updated" | "modified" => {
+                let predicate = match key.to_lowercase().as_str() {
+                    "date" => format!("{}date", DCTERMS),
+                    "created" => format!("{}created", DCTERMS),
+                    _ => format!("{}modified", DCTERMS),
+                };
+                for object in typed_values(value) {
+                    push(doc, predicate.clone(), object);
+                }
+            }
+            _ => {
+                let predicate = format!("http://synapse.os/property/{}", key.replace(' ', "_"));
+                for object in typed_values(value) {
+                    push(doc, predicate.clone(), object);
+                }
+            }
+        }
+    }
+    title
+}
+
+/// A YAML list of strings, or a comma/space separated string
+fn string_list(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .filter_map(scalar_string)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        serde_yaml::Value::String(s) => s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+        other => scalar_string(other).into_iter().collect(),
+    }
+}
+
+fn scalar_string(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.trim().to_string()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Literal objects with XSD datatypes for numbers, booleans and dates
+fn typed_values(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items.iter().flat_map(typed_values).collect(),
+        serde_yaml::Value::Bool(b) => vec![format!("\"{}\"^^<{}boolean>", b, XSD)],
+        serde_yaml::Value::Number(n) if n.is_i64() || n.is_u64() => {
+            vec![format!("\"{}\"^^<{}integer>", n, XSD)]
+        }
+        serde_yaml::Value::Number(n) => vec![format!("\"{}\"^^<{}double>", n, XSD)],
+        serde_yaml::Value::String(s) => {
+            let s = s.trim();
+            let object = if chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() {
+                format!("\"{}\"^^<{}date>", s, XSD)
+            } else if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+                format!("\"{}\"^^<{}dateTime>", dt.to_rfc3339(), XSD)
+            } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+            {
+                format!("\"{}\"^^<{}dateTime>", dt.format("%Y-%m-%dT%H:%M:%S"), XSD)
+            } else {
+                literal(s)
+            };
+            vec![object]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn push_tag(doc: &str, tag: &str, push: &mut dyn FnMut(&str, String, String)) {
+    let uri = tag_uri(tag);
+    push(doc, format!("{}taggedWith", MD), uri.clone());
+    push(&uri, RDF_TYPE.to_string(), format!("{}Tag", MD));
+    push(&uri, RDFS_LABEL.to_string(), literal(tag));
+}
+
+/// Tag node URI; nested tags (`project/alpha`) keep their slashes
+pub fn tag_uri(tag: &str) -> String {
+    let slug: Vec<String> = tag
+        .split('/')
+        .map(slugify)
+        .filter(|s| !s.is_empty())
+        .collect();
+    format!("urn:tag:{}", slug.join("/"))
+}
+
+fn literal(text: &str) -> String {
+    format!("\"{}\"", text)
+}
+
+/// Lowercase, alphanumerics kept, everything else collapsed to single dashes
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has(doc: &MarkdownDocument, s: &str, p: &str, o: &str) -> bool {
+        doc.triples
+            .iter()
+            .any(|t| t.subject == s && t.predicate == p && t.object == o)
+    }
+
+    #[test]
+    fn test_heading_hierarchy() {
+        let content = "Intro text.\n\n# Guide\n\nWelcome to the guide.\nIt has two lines.\n\n## Install\n- run cargo\nstatus: draft\n\n```sh\n# not a heading\n```\n\n## Usage\n1. start\n\n# Guide\nAgain.\n";
+        let doc = MarkdownExtractor::new(Path::new("/notes/My Notes.md")).extract(content);
+
+        assert_eq!(doc.uri, "urn:note:my-notes");
+        let guide = "urn:note:my-notes#guide";
+        let install = "urn:note:my-notes#guide/install";
+        assert!(has(&doc, &doc.uri, &format!("{}hasSection", MD), guide));
+        assert!(has(&doc, guide, &format!("{}hasSection", MD), install));
+        assert!(has(
+            &doc,
+            guide,
+            &format!("{}hasSection", MD),
+            "urn:note:my-notes#guide/usage"
+        ));
+        assert!(has(
+            &doc,
+            &doc.uri,
+            &format!("{}hasSection", MD),
+            "urn:note:my-notes#guide-2"
+        ));
+        assert!(has(
+            &doc,
+            install,
+            &format!("{}hasListItem", MD),
+            "\"run cargo\""
+        ));
+        assert!(has(
+            &doc,
+            install,
+            "http://synapse.os/property/status",
+            "\"draft\""
+        ));
+        assert!(has(
+            &doc,
+            guide,
+            &format!("{}hasParagraph", MD),
+            "\"Welcome to the guide. It has two lines.\""
+        ));
+        assert!(has(&doc, &doc.uri, RDFS_LABEL, "\"Guide\""));
+
+        let titles: Vec<&str> = doc.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Guide", "Guide", "Install", "Usage", "Guide"]);
+        assert_eq!(doc.sections[0].uri, doc.uri);
+        assert!(doc.sections[2].text.contains("# not a heading"));
+    }
+
+    #[test]
+    fn test_front_matter() {
+        let content = "---\ntitle: Weekly Review\ntags: [review, project/alpha]\naliases:\n  - Review\ndate: 2025-06-10\nrating: 4\npublished: true\n---\n# Heading\nBody\n";
+        let doc = MarkdownExtractor::new(Path::new("2025-06-10.md")).extract(content);
+        let uri = doc.uri.as_str();
+
+        assert!(has(
+            &doc,
+            uri,
+            &format!("{}title", DCTERMS),
+            "\"Weekly Review\""
+        ));
+        assert!(has(&doc, uri, RDFS_LABEL, "\"Weekly Review\""));
+        assert!(has(
+            &doc,
+            uri,
+            &format!("{}taggedWith", MD),
+            "urn:tag:review"
+        ));
+        assert!(has(
+            &doc,
+            uri,
+            &format!("{}taggedWith", MD),
+            "urn:tag:project/alpha"
+        ));
+        assert!(has(&doc, uri, SKOS_ALT_LABEL, "\"Review\""));
+        assert!(has(
+            &doc,
+            uri,
+            &format!("{}date", DCTERMS),
+            "\"2025-06-10\"^^<http://www.w3.org/2001/XMLSchema#date>"
+        ));
+        assert!(has(
+            &doc,
+            uri,
+            "http://synapse.os/property/rating",
+            "\"4\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+        ));
+        assert!(has(
+            &doc,
+            uri,
+            "http://synapse.os/property/published",
+            "\"true\"^^<http://www.w3.org/2001/XMLSchema#boolean>"
+        ));
+        // Front matter is not part of the body
+        assert!(!doc
+            .triples
+            .iter()
+            .any(|t| t.object.contains("Weekly Review") && t.predicate.starts_with(MD)));
+        assert_eq!(doc.sections[0].title, "Heading");
+    }
+
+    #[test]
+    fn test_wikilinks_and_tags() {
+        let content = "# Daily\nMet about [[Project Alpha]] and [[People/Ada Lovelace|Ada]].\nSee ![[diagram.png]] and [[Project Alpha#Goals]].\nTopics: #research #project/alpha, issue #42, `#not-a-tag` and a [link](http://x.org/#frag).\n```\n[[Ignored]] #ignored\n```\n";
+        let doc = MarkdownExtractor::new(Path::new("daily.md")).extract(content);
+        let uri = doc.uri.as_str();
+        let links: Vec<&str> = doc
+            .triples
+            .iter()
+            .filter(|t| t.subject == uri && t.predicate == format!("{}linksTo", MD))
+            .map(|t| t.object.as_str())
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                "urn:note:project-alpha",
+                "urn:note:ada-lovelace",
+                "urn:note:diagram-png"
+            ]
+        );
+
+        let tags: Vec<&str> = doc
+            .triples
+            .iter()
+            .filter(|t| t.subject == uri && t.predicate == format!("{}taggedWith", MD))
+            .map(|t| t.object.as_str())
+            .collect();
+        assert_eq!(tags, vec!["urn:tag:research", "urn:tag:project/alpha"]);
+        assert!(has(
+            &doc,
+            "urn:tag:project/alpha",
+            RDFS_LABEL,
+            "\"project/alpha\""
+        ));
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("Getting Started: Linux & macOS!"),
+            "getting-started-linux-macos"
+        );
+        assert_eq!(slugify("Ünïcode Title"), "ünïcode-title");
+    }
+}
@@ -1,111 +1,1179 @@
+pub mod api;
+pub mod code;
+pub mod crawler;
+pub mod csv_dialect;
+pub mod csv_mapping;
+pub mod encoding;
 pub mod extractor;
+pub mod feed;
+pub mod markdown;
+#[cfg(feature = "ner")]
+pub mod ner;
+pub mod notebook;
 pub mod ontology;
+pub mod pipeline;
 pub mod processor;
-use crate::store::{IngestTriple, SynapseStore};
+pub mod readability;
+#[cfg(feature = "sql")]
+pub mod sql;
+use crate::store::{IngestReport, SourceSpan, SynapseStore};
 use anyhow::Result;
 use std::path::Path;
+use tokio_util::sync::CancellationToken;
+
+/// Absolute path and hex SHA-256 of a source file, for provenance
+pub(crate) fn fingerprint(path: &Path) -> Result<(String, String)> {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(std::fs::read(path)?);
+    let absolute = std::fs::canonicalize(path)?;
+    Ok((
+        absolute.to_string_lossy().to_string(),
+        format!("{:x}", digest),
+    ))
+}
+
+/// Progress callback: `(progress, total, message)`, with `progress` increasing
+pub type ProgressFn = std::sync::Arc<dyn Fn(f32, f32, &str) + Send + Sync>;
+
+fn unlocated<T: Into<(String, String, String)>>(triples: Vec<T>) -> Vec<pipeline::LocatedTriple> {
+    triples.into_iter().map(|t| (t.into(), None)).collect()
+}
+
+/// Outcome of ingesting a feed
+#[derive(Debug, Clone, Default)]
+pub struct FeedIngestSummary {
+    pub items_ingested: u32,
+    pub triples_added: u32,
+    pub chunks_indexed: u32,
+    /// Newest item date seen, to pass as `since` on the next poll
+    pub latest: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Outcome of syncing a JSON REST endpoint
+#[derive(Debug, Clone, Default)]
+pub struct ApiIngestSummary {
+    pub pages: u32,
+    pub items: u32,
+    pub triples_added: u32,
+}
+
+/// Outcome of ingesting crawled web pages
+#[derive(Debug, Clone, Default)]
+pub struct WebIngestSummary {
+    pub pages: u32,
+    pub chunks_indexed: u32,
+    pub triples_added: u32,
+}
+
+/// How `IngestionEngine::ingest_text` chunks and records a text
+#[derive(Debug, Clone)]
+pub struct TextIngestOptions {
+    /// Copied into every chunk's vector metadata and, with `create_triples`, stated
+    /// about the document node the way Markdown front matter is
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// Maximum characters per chunk; chunks overlap by 15%
+    pub chunk_size: usize,
+    /// Record the document node, its metadata and `hasPart` links to its chunks
+    pub create_triples: bool,
+    /// Statements extracted from the text (e.g. by the client's LLM via MCP
+    /// sampling), stored with `llm_extraction` provenance
+    pub triples: Vec<(String, String, String)>,
+}
+
+impl Default for TextIngestOptions {
+    fn default() -> Self {
+        Self {
+            metadata: serde_json::Map::new(),
+            chunk_size: 1000,
+            create_triples: true,
+            triples: Vec::new(),
+        }
+    }
+}
+
+/// File extensions handled by `IngestionEngine::ingest_file`
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "csv", "tsv", "json", "jsonld", "yaml", "yml", "toml", "owl", "ttl", "rdf",
+    "xml", "rs", "py", "ts", "tsx", "ipynb",
+];
+
+/// Result of ingesting one file during a directory walk
+#[derive(Debug, Clone)]
+pub struct FileIngestReport {
+    pub path: std::path::PathBuf,
+    pub triples_added: u32,
+    pub error: Option<String>,
+}
+
+/// Aggregate result of a directory ingestion
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryIngestReport {
+    pub files_total: u32,
+    pub files_ingested: u32,
+    pub files_failed: u32,
+    pub triples_added: u32,
+    pub files: Vec<FileIngestReport>,
+}
 
 pub struct IngestionEngine {
     store: std::sync::Arc<SynapseStore>,
+    /// Forced text encoding for files; detected per file when unset
+    encoding: Option<&'static encoding_rs::Encoding>,
+    progress: Option<ProgressFn>,
+    cancel: Option<CancellationToken>,
 }
 
 impl IngestionEngine {
     pub fn new(store: std::sync::Arc<SynapseStore>) -> Self {
-        Self { store }
+        Self {
+            store,
+            encoding: None,
+            progress: None,
+            cancel: None,
+        }
+    }
+
+    /// Abort with an error once `cancel` fires (checked between triples, pages and chunks)
+    pub fn with_cancellation(mut self, cancel: Option<CancellationToken>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(anyhow::anyhow!("Ingestion cancelled"));
+        }
+        Ok(())
+    }
+
+    /// Report progress of long ingestions (files, crawled pages) to `progress`
+    pub fn with_progress(mut self, progress: Option<ProgressFn>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn report_progress(&self, progress: f32, total: f32, message: &str) {
+        if let Some(report) = &self.progress {
+            report(progress, total, message);
+        }
+    }
+
+    /// Read text files with `encoding` instead of detecting it
+    pub fn with_encoding(mut self, encoding: Option<&'static encoding_rs::Encoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Read a text file as UTF-8, transcoding legacy encodings
+    fn read_text(&self, path: &Path) -> Result<String> {
+        encoding::read_to_string(path, self.encoding)
     }
 
-    pub async fn ingest_file(&self, path: &Path, namespace: &str) -> Result<u32> {
+    pub async fn ingest_file(&self, path: &Path, namespace: &str) -> Result<IngestReport> {
+        let started = std::time::Instant::now();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.report_progress(0.0, 2.0, &format!("Extracting {}", name));
         let extension = path
             .extension()
             .and_then(|e| e.to_str())
             .unwrap_or("")
             .to_lowercase();
 
-        match extension.as_str() {
+        let mut report = match extension.as_str() {
             "md" | "markdown" => self.ingest_markdown(path, namespace).await,
-            "csv" => self.ingest_csv(path, namespace).await,
-            "owl" | "ttl" | "rdf" | "xml" => {
-                let count = ontology::OntologyLoader::load_file(&self.store, path).await?;
-                Ok(count as u32)
+            "csv" | "tsv" => self.ingest_csv(path, namespace).await,
+            "json" | "jsonld" => self.ingest_json(path, namespace).await,
+            "yaml" | "yml" | "toml" => self.ingest_config(path, namespace).await,
+            "rs" | "py" | "ts" | "tsx" => self.ingest_code(path, namespace).await,
+            "ipynb" => self.ingest_notebook(path, namespace).await,
+            "owl" | "ttl" | "nt" | "nq" | "trig" | "n3" | "rdf" | "xml" => {
+                self.ingest_rdf(path, namespace).await
             }
             _ => Err(anyhow::anyhow!("Unsupported file type: {}", extension)),
+        }?;
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        self.report_progress(
+            2.0,
+            2.0,
+            &format!("Ingested {} triples from {}", report.added, name),
+        );
+        Ok(report)
+    }
+
+    /// Fetch an RSS/Atom feed and ingest items published after `since`
+    pub async fn ingest_feed(
+        &self,
+        url: &str,
+        namespace: &str,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<FeedIngestSummary> {
+        let response = reqwest::get(url).await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
         }
+        let xml = response.text().await?;
+        self.ingest_feed_content(url, &xml, namespace, since).await
     }
 
-    async fn ingest_markdown(&self, path: &Path, namespace: &str) -> Result<u32> {
-        let content = std::fs::read_to_string(path)?;
-        let triples = extractor::extract_metadata(&content, path.to_str().unwrap());
+    /// Ingest an already fetched RSS/Atom document
+    pub async fn ingest_feed_content(
+        &self,
+        url: &str,
+        xml: &str,
+        namespace: &str,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<FeedIngestSummary> {
+        let parsed = feed::parse_feed(xml)?;
+        let mut summary = FeedIngestSummary::default();
+        let triple = |s: &str, p: &str, o: String| (s.to_string(), p.to_string(), o);
+
+        let mut triples = Vec::new();
+        if !parsed.title.is_empty() {
+            triples.push(triple(
+                url,
+                "http://purl.org/dc/terms/title",
+                format!("\"{}\"", parsed.title),
+            ));
+        }
+
+        let processor = processor::Processor::new(processor::ProcessorConfig::default());
+        for item in parsed.items {
+            // Items without a date are always ingested; re-ingesting is idempotent
+            if let (Some(since), Some(published)) = (since, item.published) {
+                if published <= since {
+                    continue;
+                }
+            }
+            let Some(item_uri) = item.uri() else {
+                continue;
+            };
+
+            summary.items_ingested += 1;
+            if let Some(published) = item.published {
+                summary.latest = Some(summary.latest.map_or(published, |l| l.max(published)));
+            }
+
+            triples.push(triple(url, "http://synapse.os/has_item", item_uri.clone()));
+            triples.push(triple(
+                &item_uri,
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
+                "http://schema.org/Article".to_string(),
+            ));
+            if !item.title.is_empty() {
+                triples.push(triple(
+                    &item_uri,
+                    "http://purl.org/dc/terms/title",
+                    format!("\"{}\"", item.title),
+                ));
+            }
+            if !item.link.is_empty() {
+                triples.push(triple(
+                    &item_uri,
+                    "http://schema.org/url",
+                    format!("\"{}\"", item.link),
+                ));
+            }
+            if let Some(published) = item.published {
+                triples.push(triple(
+                    &item_uri,
+                    "http://purl.org/dc/terms/date",
+                    format!("\"{}\"", published.to_rfc3339()),
+                ));
+            }
 
-        let ingest_triples: Vec<IngestTriple> = triples
+            if item.content.is_empty() {
+                continue;
+            }
+            let chunks: Vec<(String, String)> = processor
+                .process_html(&item.content)?
+                .into_iter()
+                .enumerate()
+                .map(|(i, chunk)| (format!("{}#chunk-{}", item_uri, i), chunk))
+                .collect();
+            summary.triples_added += self.ingest_entities(&item_uri, &chunks, url).await?.added;
+
+            // Article content goes to the vector store for RAG
+            if let Some(ref vs) = self.store.vector_store {
+                for (chunk_uri, chunk) in &chunks {
+                    let metadata = serde_json::json!({
+                        "uri": item_uri,
+                        "chunk_uri": chunk_uri,
+                        "source_url": url,
+                        "type": "feed_item",
+                        "namespace": namespace
+                    });
+                    match vs.add(chunk_uri, chunk, metadata).await {
+                        Ok(_) => summary.chunks_indexed += 1,
+                        Err(e) => tracing::warn!("Failed to index chunk {}: {}", chunk_uri, e),
+                    }
+                }
+            }
+        }
+
+        summary.triples_added += self.commit(triples, url, "feed_extractor").await?.added;
+        Ok(summary)
+    }
+
+    /// Sync a paginated JSON REST endpoint, following its pagination until exhausted
+    pub async fn ingest_api(
+        &self,
+        source: &api::ApiSource,
+        namespace: &str,
+    ) -> Result<ApiIngestSummary> {
+        let client = reqwest::Client::new();
+        let mut pages = Vec::new();
+        let mut next = Some(source.first_url()?);
+        while let Some(url) = next {
+            let body = source.fetch(&client, &url).await?;
+            let items = source.mapping.items(&body).len();
+            next = source.next_url(&url, &body, pages.len(), items);
+            pages.push(body);
+        }
+        self.ingest_api_pages(source, &pages, namespace).await
+    }
+
+    /// Map already fetched response pages to triples
+    pub async fn ingest_api_pages(
+        &self,
+        source: &api::ApiSource,
+        pages: &[serde_json::Value],
+        _namespace: &str,
+    ) -> Result<ApiIngestSummary> {
+        let mut summary = ApiIngestSummary::default();
+        let mut triples = Vec::new();
+        for body in pages {
+            summary.pages += 1;
+            for item in source.mapping.items(body) {
+                let Some(mapped) = source.mapping.map_item(item) else {
+                    continue;
+                };
+                summary.items += 1;
+                triples.extend(mapped);
+            }
+        }
+
+        summary.triples_added = self.commit(triples, &source.url, "api_sync").await?.added;
+        Ok(summary)
+    }
+
+    /// Mirror the mapped tables of a Postgres or SQLite database
+    #[cfg(feature = "sql")]
+    pub async fn ingest_sql(
+        &self,
+        url: &str,
+        mapping: &sql::SqlMapping,
+        _namespace: &str,
+    ) -> Result<sql::SqlIngestSummary> {
+        let connector = sql::SqlConnector::connect(url).await?;
+        let mut summary = sql::SqlIngestSummary::default();
+
+        // Never record credentials in provenance
+        let source = match reqwest::Url::parse(url) {
+            Ok(mut parsed) => {
+                let _ = parsed.set_password(None);
+                parsed.to_string()
+            }
+            Err(_) => url.to_string(),
+        };
+        let mut triples = Vec::new();
+        for (table, table_mapping) in &mapping.tables {
+            let (rows, mapped) = connector
+                .map_table(&mapping.base, table, table_mapping)
+                .await?;
+            summary.tables += 1;
+            summary.rows += rows;
+            triples.extend(mapped);
+        }
+
+        summary.triples_added = self.commit(triples, &source, "sql_mapping").await?.added;
+        Ok(summary)
+    }
+
+    /// Index each crawled page's article as a document with one section per heading,
+    /// and record the link structure between pages. Chunks are embedded with the page
+    /// URL, title and section heading, and linked to their section with `isPartOf`.
+    pub async fn ingest_web_pages(
+        &self,
+        pages: &[crawler::CrawledPage],
+        namespace: &str,
+    ) -> Result<WebIngestSummary> {
+        const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+        const DC: &str = "http://purl.org/dc/terms/";
+        let md = markdown::MD;
+
+        let mut summary = WebIngestSummary::default();
+        let crawled: std::collections::HashSet<&str> =
+            pages.iter().map(|p| p.url.as_str()).collect();
+
+        let source = pages.first().map(|p| p.url.clone()).unwrap_or_default();
+        let triple = |s: &str, p: &str, o: String| (s.to_string(), p.to_string(), o);
+
+        let mut triples = Vec::new();
+        let processor = super::processor::TextProcessor::new();
+        for page in pages {
+            self.check_cancelled()?;
+            summary.pages += 1;
+            self.report_progress(
+                summary.pages as f32,
+                pages.len() as f32,
+                &format!("Indexing {}", page.url),
+            );
+            triples.push(triple(
+                &page.url,
+                RDF_TYPE,
+                "http://schema.org/WebPage".to_string(),
+            ));
+            if !page.title.is_empty() {
+                triples.push(triple(
+                    &page.url,
+                    &format!("{}title", DC),
+                    format!("\"{}\"", page.title),
+                ));
+            }
+            // Only link pages that were actually crawled
+            for link in page.links.iter().filter(|l| crawled.contains(l.as_str())) {
+                if link != &page.url {
+                    triples.push(triple(
+                        &page.url,
+                        "http://schema.org/relatedLink",
+                        link.clone(),
+                    ));
+                }
+            }
+
+            // (chunk URI, section URI, section heading, text)
+            let mut chunks: Vec<(String, String, &str, String)> = Vec::new();
+            for (i, section) in page.sections.iter().enumerate() {
+                let section_uri = format!("{}#section-{}", page.url, i);
+                triples.push(triple(
+                    &page.url,
+                    &format!("{}hasSection", md),
+                    section_uri.clone(),
+                ));
+                triples.push(triple(&section_uri, RDF_TYPE, format!("{}Section", md)));
+                if !section.heading.is_empty() {
+                    triples.push(triple(
+                        &section_uri,
+                        "http://www.w3.org/2000/01/rdf-schema#label",
+                        format!("\"{}\"", section.heading),
+                    ));
+                }
+                for chunk in processor.chunk_text(&section.text, 1000, 150) {
+                    let chunk_uri = format!("{}#chunk-{}", page.url, chunks.len());
+                    triples.push(triple(
+                        &chunk_uri,
+                        &format!("{}isPartOf", DC),
+                        section_uri.clone(),
+                    ));
+                    let text = if section.heading.is_empty() {
+                        chunk
+                    } else {
+                        format!("{}\n\n{}", section.heading, chunk)
+                    };
+                    chunks.push((chunk_uri, section_uri.clone(), &section.heading, text));
+                }
+            }
+
+            let tagged: Vec<(String, String)> = chunks
+                .iter()
+                .map(|(uri, _, _, text)| (uri.clone(), text.clone()))
+                .collect();
+            summary.triples_added += self
+                .ingest_entities(&page.url, &tagged, &page.url)
+                .await?
+                .added;
+
+            if let Some(ref vs) = self.store.vector_store {
+                for (chunk_uri, section_uri, heading, text) in &chunks {
+                    self.check_cancelled()?;
+                    let metadata = serde_json::json!({
+                        "uri": section_uri,
+                        "document": page.url,
+                        "chunk_uri": chunk_uri,
+                        "title": page.title,
+                        "section": heading,
+                        "source_url": page.url,
+                        "type": "web_chunk",
+                        "namespace": namespace
+                    });
+                    match vs.add(chunk_uri, text, metadata).await {
+                        Ok(_) => summary.chunks_indexed += 1,
+                        Err(e) => tracing::warn!("Failed to add chunk {}: {}", chunk_uri, e),
+                    }
+                }
+            }
+        }
+
+        summary.triples_added += self.commit(triples, &source, "web_crawler").await?.added;
+        Ok(summary)
+    }
+
+    /// Chunk a text under `uri`, tag entities in it and index the chunks. Texts longer
+    /// than one chunk get `<uri>#chunk-<i>` chunk URIs.
+    pub async fn ingest_text(
+        &self,
+        uri: &str,
+        content: &str,
+        options: &TextIngestOptions,
+        namespace: &str,
+    ) -> Result<IngestReport> {
+        const DC: &str = "http://purl.org/dc/terms/";
+        if options.chunk_size == 0 {
+            return Err(anyhow::anyhow!("chunk_size must be positive"));
+        }
+
+        let processor = super::processor::TextProcessor::new();
+        let parts = processor.chunk_text(content, options.chunk_size, options.chunk_size * 3 / 20);
+        let single = parts.len() == 1;
+        let chunks: Vec<(String, String)> = parts
             .into_iter()
-            .map(|t| IngestTriple {
-                subject: t.subject,
-                predicate: t.predicate,
-                object: t.object,
-                provenance: Some(crate::store::Provenance {
-                    source: path.to_string_lossy().to_string(),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                    method: "markdown_extractor".to_string(),
-                }),
+            .enumerate()
+            .map(|(i, chunk)| {
+                let chunk_uri = if single {
+                    uri.to_string()
+                } else {
+                    format!("{}#chunk-{}", uri, i)
+                };
+                (chunk_uri, chunk)
             })
             .collect();
 
-        let (added, _) = self.store.ingest_triples(ingest_triples).await?;
+        let mut report = IngestReport::default();
+        if options.create_triples {
+            let mut triples: Vec<(String, String, String)> = vec![(
+                uri.to_string(),
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type".to_string(),
+                "http://schema.org/TextDigitalDocument".to_string(),
+            )];
+            triples.extend(
+                markdown::metadata_triples(uri, &options.metadata)
+                    .into_iter()
+                    .map(Into::into),
+            );
+            for (chunk_uri, _) in chunks.iter().filter(|(c, _)| c != uri) {
+                triples.push((uri.to_string(), format!("{}hasPart", DC), chunk_uri.clone()));
+            }
+            report.merge(&self.commit(triples, uri, "text_ingest").await?);
+        }
+        if !options.triples.is_empty() {
+            report.merge(
+                &self
+                    .commit(options.triples.clone(), uri, "llm_extraction")
+                    .await?,
+            );
+        }
+        report.merge(&self.ingest_entities(uri, &chunks, uri).await?);
 
-        // Also ingest content into vector store for RAG
         if let Some(ref vs) = self.store.vector_store {
-            let processor = super::processor::TextProcessor::new();
-            let chunks = processor.chunk_text(&content, 1000, 150);
-            for (i, chunk) in chunks.iter().enumerate() {
-                let chunk_uri = format!("{}#chunk-{}", path.to_string_lossy(), i);
+            for (chunk_uri, chunk) in &chunks {
+                self.check_cancelled()?;
+                let mut metadata = options.metadata.clone();
+                metadata.extend([
+                    ("uri".to_string(), serde_json::json!(uri)),
+                    ("chunk_uri".to_string(), serde_json::json!(chunk_uri)),
+                    ("type".to_string(), serde_json::json!("text_chunk")),
+                    ("namespace".to_string(), serde_json::json!(namespace)),
+                ]);
+                match vs.add(chunk_uri, chunk, metadata.into()).await {
+                    Ok(_) => report.vectors_created += 1,
+                    Err(e) => tracing::warn!("Failed to add chunk {}: {}", chunk_uri, e),
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Tag named entities in a document's chunks and link them with `mentions`/`about`
+    /// triples. A no-op unless built with the `ner` feature and `SYNAPSE_NER_MODEL`
+    /// points at a model.
+    pub async fn ingest_entities(
+        &self,
+        document: &str,
+        chunks: &[(String, String)],
+        source: &str,
+    ) -> Result<IngestReport> {
+        #[cfg(feature = "ner")]
+        {
+            let Some(model) = ner::NerModel::shared() else {
+                return Ok(IngestReport::default());
+            };
+            // Inference is CPU heavy
+            let chunks = chunks.to_vec();
+            let tagged = tokio::task::spawn_blocking(move || {
+                chunks
+                    .into_iter()
+                    .map(|(uri, text)| Ok((uri, model.tag(&text)?)))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .await??;
+            let triples = ner::link_entities(document, &tagged);
+            if triples.is_empty() {
+                return Ok(IngestReport::default());
+            }
+            self.commit(triples, source, "entity_tagger").await
+        }
+        #[cfg(not(feature = "ner"))]
+        {
+            let _ = (document, chunks, source);
+            Ok(IngestReport::default())
+        }
+    }
+
+    /// Whether `ingest_file` can handle this path
+    pub fn is_supported(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+    }
+
+    /// Ingest every supported file under `root` (recursively) whose path relative to
+    /// `root` matches `pattern` (e.g. `**/*.md`). Failures are recorded per file and
+    /// do not stop the walk; `on_progress` is called after each file.
+    pub async fn ingest_directory<F>(
+        &self,
+        root: &Path,
+        pattern: Option<&str>,
+        namespace: &str,
+        mut on_progress: F,
+    ) -> Result<DirectoryIngestReport>
+    where
+        F: FnMut(&DirectoryIngestReport),
+    {
+        if !root.is_dir() {
+            return Err(anyhow::anyhow!("Not a directory: {}", root.display()));
+        }
+        let pattern = pattern
+            .filter(|p| !p.is_empty())
+            .map(glob::Pattern::new)
+            .transpose()?;
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+
+        let files: Vec<std::path::PathBuf> = Self::walk(root)?
+            .into_iter()
+            .filter(|p| Self::is_supported(p))
+            .filter(|p| match &pattern {
+                Some(pattern) => p
+                    .strip_prefix(root)
+                    .is_ok_and(|rel| pattern.matches_path_with(rel, options)),
+                None => true,
+            })
+            .collect();
+
+        let mut report = DirectoryIngestReport {
+            files_total: files.len() as u32,
+            ..Default::default()
+        };
+
+        for path in files {
+            self.check_cancelled()?;
+            let file_report = match self.ingest_file(&path, namespace).await {
+                Ok(file) => {
+                    report.files_ingested += 1;
+                    report.triples_added += file.added;
+                    FileIngestReport {
+                        path,
+                        triples_added: file.added,
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    report.files_failed += 1;
+                    FileIngestReport {
+                        path,
+                        triples_added: 0,
+                        error: Some(e.to_string()),
+                    }
+                }
+            };
+            report.files.push(file_report);
+            on_progress(&report);
+        }
+
+        Ok(report)
+    }
+
+    /// All files under `dir`, sorted, skipping hidden entries
+    fn walk(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+        let mut files = Vec::new();
+        let mut entries: Vec<_> = std::fs::read_dir(dir)?.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                files.extend(Self::walk(&path)?);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    async fn ingest_markdown(&self, path: &Path, namespace: &str) -> Result<IngestReport> {
+        let content = self.read_text(path)?;
+        let document = markdown::MarkdownExtractor::new(path).extract(&content);
+        let source = path.to_string_lossy().to_string();
+        let mut report = self
+            .commit_file(path, unlocated(document.triples), "markdown_extractor")
+            .await?;
+
+        // One chunk per section; long sections are split further
+        let processor = super::processor::TextProcessor::new();
+        let mut chunks: Vec<(String, String)> = Vec::new();
+        let mut chunk_sections = Vec::new();
+        for section in &document.sections {
+            let text = format!("{}\n\n{}", section.title, section.text.trim());
+            let parts = processor.chunk_text(&text, 1000, 150);
+            let single = parts.len() == 1;
+            for (i, part) in parts.into_iter().enumerate() {
+                let chunk_uri = if single {
+                    section.uri.clone()
+                } else {
+                    format!("{}:chunk-{}", section.uri, i)
+                };
+                chunks.push((chunk_uri, part));
+                chunk_sections.push(section);
+            }
+        }
+        let entities = self
+            .ingest_entities(&document.uri, &chunks, &source)
+            .await?;
+        report.merge(&entities);
+
+        // Also ingest section text into vector store for RAG
+        if let Some(ref vs) = self.store.vector_store {
+            for ((chunk_uri, chunk), section) in chunks.iter().zip(chunk_sections) {
+                self.check_cancelled()?;
+                let metadata = serde_json::json!({
+                    "uri": section.uri,
+                    "document": document.uri,
+                    "chunk_uri": chunk_uri,
+                    "section": section.title,
+                    "source_path": path.to_string_lossy(),
+                    "type": "markdown_section",
+                    "namespace": namespace
+                });
+                match vs.add(chunk_uri, chunk, metadata).await {
+                    Ok(_) => report.vectors_created += 1,
+                    Err(e) => tracing::warn!("Failed to index section {}: {}", chunk_uri, e),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn ingest_json(&self, path: &Path, _namespace: &str) -> Result<IngestReport> {
+        let content = self.read_text(path)?;
+        let filename = path.file_name().unwrap().to_string_lossy();
+        let base_uri = format!("urn:json:{}", filename.replace(' ', "_"));
+        let triples = extractor::JsonExtractor::new(&base_uri).extract(&content)?;
+        self.commit_file(path, unlocated(triples), "json_extractor")
+            .await
+    }
+
+    /// RDF data files, parsed as-is (literals and blank nodes preserved)
+    async fn ingest_rdf(&self, path: &Path, _namespace: &str) -> Result<IngestReport> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let filename = path.file_name().unwrap().to_string_lossy();
+        // Relative IRIs resolve under the file name
+        let base_uri = format!("http://synapse.os/rdf/{}", filename.replace(' ', "_"));
+        let extractor = extractor::RdfExtractor::for_extension(extension)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported RDF file: {}", path.display()))?
+            .with_base_uri(&base_uri);
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let triples = extractor.extract(file)?;
+        self.commit_file(path, unlocated(triples), "rdf_extractor")
+            .await
+    }
+
+    async fn ingest_config(&self, path: &Path, _namespace: &str) -> Result<IngestReport> {
+        let content = self.read_text(path)?;
+        let filename = path.file_name().unwrap().to_string_lossy();
+        let extractor =
+            extractor::ConfigExtractor::new(&format!("urn:config:{}", filename.replace(' ', "_")));
+        let is_toml = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+        let triples = if is_toml {
+            extractor.extract_toml(&content)?
+        } else {
+            extractor.extract_yaml(&content)?
+        };
+        self.commit_file(path, unlocated(triples), "config_extractor")
+            .await
+    }
+
+    /// Structural triples for a source file, with docstrings indexed for RAG
+    async fn ingest_code(&self, path: &Path, namespace: &str) -> Result<IngestReport> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let language = code::Language::from_extension(extension)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported source file: {}", path.display()))?;
+        let content = self.read_text(path)?;
+        let file_uri = format!("urn:code:{}", path.to_string_lossy().replace(' ', "%20"));
+
+        let extractor = code::CodeExtractor::new(language);
+        let outline = extractor.outline(&content);
+
+        // Triples about a definition point at its lines
+        let spans: std::collections::HashMap<String, SourceSpan> = outline
+            .symbols
+            .iter()
+            .map(|symbol| {
+                let span = SourceSpan {
+                    line_start: symbol.line as u64,
+                    line_end: symbol.end_line as u64,
+                    ..Default::default()
+                };
+                (code::symbol_uri(&file_uri, &symbol.name), span)
+            })
+            .collect();
+        let triples = extractor
+            .extract(&file_uri, &outline)
+            .into_iter()
+            .map(|t| {
+                let span = spans.get(&t.subject).cloned();
+                (t.into(), span)
+            })
+            .collect();
+        let mut report = self.commit_file(path, triples, "code_extractor").await?;
+
+        if let Some(ref vs) = self.store.vector_store {
+            for symbol in &outline.symbols {
+                let Some(doc) = &symbol.doc else {
+                    continue;
+                };
+                let symbol_uri = code::symbol_uri(&file_uri, &symbol.name);
+                let metadata = serde_json::json!({
+                    "uri": symbol_uri,
+                    "source_path": path.to_string_lossy(),
+                    "symbol": symbol.name,
+                    "line": symbol.line,
+                    "language": language.name(),
+                    "type": "code_doc",
+                    "namespace": namespace
+                });
+                let text = format!("{}: {}", symbol.name, doc);
+                match vs.add(&symbol_uri, &text, metadata).await {
+                    Ok(_) => report.vectors_created += 1,
+                    Err(e) => tracing::warn!("Failed to index docstring {}: {}", symbol_uri, e),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Notebook cells linked in order by `next` edges. Markdown cells are indexed as
+    /// document chunks; code cells get the same structural triples as source files.
+    async fn ingest_notebook(&self, path: &Path, namespace: &str) -> Result<IngestReport> {
+        const NB: &str = "http://synapse.os/notebook/";
+        let parsed = notebook::Notebook::parse(&self.read_text(path)?)?;
+        let notebook_uri = format!(
+            "urn:notebook:{}",
+            path.to_string_lossy().replace(' ', "%20")
+        );
+        let cell_uri = |i: usize| format!("{}#cell-{}", notebook_uri, i);
+
+        let source = path.to_string_lossy().to_string();
+        let triple = |s: &str, p: &str, o: String| (s.to_string(), p.to_string(), o);
+
+        let mut triples = vec![
+            triple(
+                &notebook_uri,
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
+                format!("{}Notebook", NB),
+            ),
+            triple(
+                &notebook_uri,
+                &format!("{}language", NB),
+                format!("\"{}\"", parsed.language),
+            ),
+        ];
+        if !parsed.cells.is_empty() {
+            triples.push(triple(
+                &notebook_uri,
+                &format!("{}firstCell", NB),
+                cell_uri(0),
+            ));
+        }
+
+        let extractor = parsed.code_extractor();
+        let outlines = match &extractor {
+            Some(extractor) => parsed.outline_cells(extractor),
+            None => vec![None; parsed.cells.len()],
+        };
+
+        let mut markdown_chunks = Vec::new();
+        for (i, cell) in parsed.cells.iter().enumerate() {
+            let uri = cell_uri(i);
+            triples.push(triple(
+                &notebook_uri,
+                &format!("{}hasCell", NB),
+                uri.clone(),
+            ));
+            triples.push(triple(
+                &uri,
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
+                format!("{}{}", NB, cell.kind.class()),
+            ));
+            triples.push(triple(
+                &uri,
+                &format!("{}position", NB),
+                format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#integer>", i),
+            ));
+            if i + 1 < parsed.cells.len() {
+                triples.push(triple(&uri, &format!("{}next", NB), cell_uri(i + 1)));
+            }
+
+            if cell.kind == notebook::CellKind::Markdown && !cell.source.trim().is_empty() {
+                markdown_chunks.push((uri.clone(), cell.source.clone()));
+            }
+            if let (Some(extractor), Some(outline)) = (&extractor, &outlines[i]) {
+                triples.extend(
+                    extractor
+                        .extract_structure(&uri, &notebook_uri, outline)
+                        .into_iter()
+                        .map(Into::<(String, String, String)>::into),
+                );
+            }
+        }
+
+        let mut report = self
+            .commit_file(path, unlocated(triples), "notebook_extractor")
+            .await?;
+        let entities = self
+            .ingest_entities(&notebook_uri, &markdown_chunks, &source)
+            .await?;
+        report.merge(&entities);
+
+        if let Some(ref vs) = self.store.vector_store {
+            for (chunk_uri, text) in &markdown_chunks {
                 let metadata = serde_json::json!({
-                    "uri": path.to_string_lossy(),
+                    "uri": notebook_uri,
                     "chunk_uri": chunk_uri,
-                    "type": "markdown_chunk",
+                    "type": "notebook_markdown",
                     "namespace": namespace
                 });
-                if let Err(e) = vs.add(&chunk_uri, chunk, metadata).await {
-                    eprintln!("Failed to index chunk {}: {}", i, e);
+                match vs.add(chunk_uri, text, metadata).await {
+                    Ok(_) => report.vectors_created += 1,
+                    Err(e) => tracing::warn!("Failed to index cell {}: {}", chunk_uri, e),
+                }
+            }
+            for outline in outlines.iter().flatten() {
+                for symbol in &outline.symbols {
+                    let Some(doc) = &symbol.doc else {
+                        continue;
+                    };
+                    let symbol_uri = code::symbol_uri(&notebook_uri, &symbol.name);
+                    let metadata = serde_json::json!({
+                        "uri": symbol_uri,
+                        "source_path": path.to_string_lossy(),
+                        "symbol": symbol.name,
+                        "type": "code_doc",
+                        "namespace": namespace
+                    });
+                    let text = format!("{}: {}", symbol.name, doc);
+                    match vs.add(&symbol_uri, &text, metadata).await {
+                        Ok(_) => report.vectors_created += 1,
+                        Err(e) => tracing::warn!("Failed to index docstring {}: {}", symbol_uri, e),
+                    }
                 }
             }
         }
 
-        Ok(added)
+        Ok(report)
+    }
+
+    async fn ingest_csv(&self, path: &Path, namespace: &str) -> Result<IngestReport> {
+        let mapping = csv_mapping::CsvMapping::find_sidecar(path)?;
+        self.ingest_csv_with_options(
+            path,
+            namespace,
+            mapping.as_ref(),
+            &csv_dialect::CsvDialect::default(),
+        )
+        .await
     }
 
-    async fn ingest_csv(&self, path: &Path, _namespace: &str) -> Result<u32> {
-        let mut reader = csv::Reader::from_path(path)?;
+    /// Ingest a CSV/TSV file with an explicit `mapping` and `dialect`. Unset dialect
+    /// options are sniffed from the file.
+    pub async fn ingest_csv_with_options(
+        &self,
+        path: &Path,
+        _namespace: &str,
+        mapping: Option<&csv_mapping::CsvMapping>,
+        dialect: &csv_dialect::CsvDialect,
+    ) -> Result<IngestReport> {
+        let content = self.read_text(path)?;
+        let mut reader = dialect.reader(path, &content)?;
         let headers = reader.headers()?.clone();
 
-        let mut triples = Vec::new();
+        // Each row's triples, and where the row starts in the file
+        let mut rows: Vec<Vec<(String, String, String)>> = Vec::new();
+        let mut starts: Vec<csv::Position> = Vec::new();
         let filename = path.file_name().unwrap().to_string_lossy();
+        // Rows that fail to parse, or have no subject, are skipped and reported
+        let mut malformed = 0;
 
         for result in reader.records() {
-            let record = result?;
+            let Ok(record) = result else {
+                malformed += 1;
+                continue;
+            };
+            let position = record
+                .position()
+                .cloned()
+                .unwrap_or_else(csv::Position::new);
+            if let Some(mapping) = mapping {
+                match mapping.map_row(&headers, &record, |id| {
+                    format!("urn:csv:{}:{}", filename, id)
+                }) {
+                    Some(triples) => {
+                        rows.push(triples);
+                        starts.push(position);
+                    }
+                    None => malformed += 1,
+                }
+                continue;
+            }
+
             // Assume first column is ID/Subject
-            if let Some(subject) = record.get(0) {
-                let subject_uri = format!("urn:csv:{}:{}", filename, subject); // basic namespacing
-
-                for (j, field) in record.iter().enumerate().skip(1) {
-                    if let Some(header) = headers.get(j) {
-                        if !field.is_empty() {
-                            triples.push(IngestTriple {
-                                subject: subject_uri.clone(),
-                                predicate: format!("urn:csv:prop:{}", header),
-                                object: field.to_string(),
-                                provenance: Some(crate::store::Provenance {
-                                    source: path.to_string_lossy().to_string(),
-                                    timestamp: chrono::Utc::now().to_rfc3339(),
-                                    method: "csv_extractor".to_string(),
-                                }),
-                            });
+            match record.get(0).filter(|s| !s.is_empty()) {
+                Some(subject) => {
+                    let subject_uri = format!("urn:csv:{}:{}", filename, subject); // basic namespacing
+                    let mut triples = Vec::new();
+
+                    for (j, field) in record.iter().enumerate().skip(1) {
+                        if let Some(header) = headers.get(j) {
+                            if !field.is_empty() {
+                                triples.push((
+                                    subject_uri.clone(),
+                                    format!("urn:csv:prop:{}", header),
+                                    field.to_string(),
+                                ));
+                            }
                         }
                     }
+                    rows.push(triples);
+                    starts.push(position);
                 }
+                None => malformed += 1,
             }
         }
 
-        let (added, _) = self.store.ingest_triples(triples).await?;
-        Ok(added)
+        // A row ends where the next one starts (the last one at end of file)
+        let file_len = content.len() as u64;
+        let mut triples = Vec::new();
+        for (i, row) in rows.into_iter().enumerate() {
+            let start = &starts[i];
+            let (line_end, byte_end) = match starts.get(i + 1) {
+                Some(next) => (next.line().saturating_sub(1).max(start.line()), next.byte()),
+                None => (start.line(), file_len),
+            };
+            let span = SourceSpan {
+                line_start: start.line(),
+                line_end,
+                byte_start: Some(start.byte()),
+                byte_end: Some(byte_end),
+            };
+            triples.extend(row.into_iter().map(|t| (t, Some(span.clone()))));
+        }
+
+        let method = if mapping.is_some() {
+            "csv_mapping"
+        } else {
+            "csv_extractor"
+        };
+        let mut report = self.commit_file(path, triples, method).await?;
+        report.malformed += malformed;
+        Ok(report)
+    }
+
+    /// Run extracted triples through the namespace pipeline (normalize, provenance,
+    /// custom transforms, validate) and store what survives
+    async fn commit(
+        &self,
+        triples: Vec<(String, String, String)>,
+        source: &str,
+        method: &str,
+    ) -> Result<IngestReport> {
+        let ctx = self.context(source, method, None);
+        self.run_pipeline(triples.into_iter().map(|t| (t, None)).collect(), ctx)
+            .await
+    }
+
+    /// `commit` for triples extracted from a file: provenance records the file's path
+    /// and SHA-256, plus each triple's location when the extractor tracks it
+    async fn commit_file(
+        &self,
+        path: &Path,
+        triples: Vec<pipeline::LocatedTriple>,
+        method: &str,
+    ) -> Result<IngestReport> {
+        let ctx = self.context(&path.to_string_lossy(), method, Some(fingerprint(path)?));
+        self.report_progress(1.0, 2.0, &format!("Storing {} triples", triples.len()));
+        self.run_pipeline(triples, ctx).await
+    }
+
+    fn context(
+        &self,
+        source: &str,
+        method: &str,
+        file: Option<(String, String)>,
+    ) -> pipeline::IngestContext {
+        let (path, sha256) = file.unzip();
+        pipeline::IngestContext {
+            namespace: self.store.namespace.clone(),
+            source: source.to_string(),
+            method: method.to_string(),
+            path,
+            sha256,
+        }
+    }
+
+    async fn run_pipeline(
+        &self,
+        triples: Vec<pipeline::LocatedTriple>,
+        ctx: pipeline::IngestContext,
+    ) -> Result<IngestReport> {
+        let started = std::time::Instant::now();
+        let output = self.store.pipeline.run_located(triples, &ctx)?;
+        if let Some(first) = output.rejected.first() {
+            tracing::warn!(
+                namespace = %ctx.namespace,
+                "Pipeline rejected {} triples from {} (e.g. {:?}: {})",
+                output.rejected.len(),
+                ctx.source,
+                first.triple,
+                first.reason
+            );
+        }
+        let mut report = self
+            .store
+            .ingest_triples_cancellable(output.triples, self.cancel.as_ref())
+            .await?;
+        report.malformed += output.rejected.len() as u32;
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        Ok(report)
+    }
+
+    /// Add a custom transform to this namespace's pipeline, after the configured ones
+    pub fn register_transform(&self, transform: std::sync::Arc<dyn pipeline::Transform>) {
+        self.store.pipeline.register(transform);
+    }
+
+    /// Replace this namespace's configured transforms and persist them
+    pub fn configure_pipeline(&self, config: pipeline::PipelineConfig) -> Result<()> {
+        self.store
+            .pipeline
+            .configure(&self.store.storage_path, config)
     }
 }
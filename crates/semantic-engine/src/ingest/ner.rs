@@ -0,0 +1,424 @@
+//! Local named-entity recognition with a token-classification model.
+//!
+//! Runs an ONNX export of a BERT-style NER model (such as `dslim/bert-base-NER`)
+//! through ONNX Runtime, from the directory in `SYNAPSE_NER_MODEL`. Enabled with the
+//! `ner` feature.
+
+use anyhow::{Context, Result};
+use ort::session::{Session, SessionInputValue};
+use ort::value::Tensor;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use tokenizers::{Tokenizer, TruncationParams};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+pub const MENTIONS: &str = "http://schema.org/mentions";
+pub const ABOUT: &str = "http://schema.org/about";
+const IS_PART_OF: &str = "http://purl.org/dc/terms/isPartOf";
+
+/// Entity namespace for nodes created by the tagger
+pub const ENTITY_BASE: &str = "http://synapse.os/entity/";
+
+/// A document is `about` entities mentioned at least this many times
+const ABOUT_MIN_MENTIONS: usize = 2;
+
+/// Longest token window the model is run on; longer texts are split into
+/// overlapping windows
+const MAX_TOKENS: usize = 512;
+const WINDOW_OVERLAP: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EntityLabel {
+    Person,
+    Organization,
+    Place,
+    /// Any other entity class the model knows (`MISC`, ...)
+    Other,
+}
+
+impl EntityLabel {
+    /// The class of a model label without its `B-`/`I-` prefix
+    fn from_class(class: &str) -> Self {
+        match class {
+            "PER" | "PERSON" => EntityLabel::Person,
+            "ORG" | "ORGANIZATION" => EntityLabel::Organization,
+            "LOC" | "GPE" | "LOCATION" => EntityLabel::Place,
+            _ => EntityLabel::Other,
+        }
+    }
+
+    pub fn schema_type(&self) -> &'static str {
+        match self {
+            EntityLabel::Person => "http://schema.org/Person",
+            EntityLabel::Organization => "http://schema.org/Organization",
+            EntityLabel::Place => "http://schema.org/Place",
+            EntityLabel::Other => "http://schema.org/Thing",
+        }
+    }
+}
+
+/// An entity found in a text, with byte offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entity {
+    pub text: String,
+    pub label: EntityLabel,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Entity {
+    /// Node URI shared by every mention of the same name
+    pub fn uri(&self) -> String {
+        let slug: Vec<String> = self
+            .text
+            .split_whitespace()
+            .map(|w| {
+                w.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect::<String>()
+            })
+            .filter(|w| !w.is_empty())
+            .collect();
+        format!("{}{}", ENTITY_BASE, slug.join("_"))
+    }
+}
+
+/// An ONNX token-classification model with its tokenizer and labels
+pub struct NerModel {
+    session: Session,
+    tokenizer: Tokenizer,
+    /// Label of each output class, e.g. `B-PER`
+    labels: Vec<String>,
+}
+
+impl NerModel {
+    /// Load `model.onnx`, `tokenizer.json` and `config.json` (for `id2label`) from `dir`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let session = Session::builder()?
+            .commit_from_file(dir.join("model.onnx"))
+            .context("Failed to load model.onnx")?;
+        let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer.json: {}", e))?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                stride: WINDOW_OVERLAP,
+                ..Default::default()
+            }))
+            .map_err(|e| anyhow::anyhow!("Invalid tokenizer settings: {}", e))?;
+
+        let config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("config.json"))?)?;
+        let id2label = config
+            .get("id2label")
+            .and_then(|v| v.as_object())
+            .context("config.json has no id2label")?;
+        let mut labels = vec![String::from("O"); id2label.len()];
+        for (id, label) in id2label {
+            let id: usize = id.parse().context("Invalid id2label key")?;
+            let slot = labels.get_mut(id).context("id2label is not contiguous")?;
+            *slot = label.as_str().unwrap_or("O").to_string();
+        }
+        Ok(Self {
+            session,
+            tokenizer,
+            labels,
+        })
+    }
+
+    /// The model configured by `SYNAPSE_NER_MODEL`, loaded once; `None` (with a
+    /// warning) when unset or unusable
+    pub fn shared() -> Option<Arc<NerModel>> {
+        static MODEL: OnceLock<Option<Arc<NerModel>>> = OnceLock::new();
+        MODEL
+            .get_or_init(|| {
+                let Ok(dir) = std::env::var("SYNAPSE_NER_MODEL") else {
+                    tracing::warn!("SYNAPSE_NER_MODEL is not set; entity tagging is off");
+                    return None;
+                };
+                match NerModel::load(Path::new(&dir)) {
+                    Ok(model) => Some(Arc::new(model)),
+                    Err(e) => {
+                        tracing::warn!("Failed to load NER model from {}: {:#}", dir, e);
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+
+    /// Tag the entities in `text`, in order of appearance
+    pub fn tag(&self, text: &str) -> Result<Vec<Entity>> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow::anyhow!("Failed to tokenize: {}", e))?;
+        let mut entities: Vec<Entity> = Vec::new();
+        for window in std::iter::once(&encoding).chain(encoding.get_overflowing()) {
+            let tags = self.classify(window)?;
+            let tokens: Vec<((usize, usize), &str)> = window
+                .get_offsets()
+                .iter()
+                .copied()
+                .zip(tags.iter().map(String::as_str))
+                .collect();
+            for entity in entities_from_tokens(text, &tokens) {
+                // Windows overlap, so later ones repeat some entities
+                if !entities.iter().any(|e| e.start == entity.start) {
+                    entities.push(entity);
+                }
+            }
+        }
+        entities.sort_by_key(|e| e.start);
+        Ok(entities)
+    }
+
+    /// The predicted label of each token in a window
+    fn classify(&self, window: &tokenizers::Encoding) -> Result<Vec<String>> {
+        let len = window.len();
+        let column = |values: &[u32]| -> Result<SessionInputValue<'static>> {
+            let values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
+            Ok(Tensor::from_array(([1, len], values))?.into_dyn().into())
+        };
+        let mut inputs: Vec<(Cow<str>, SessionInputValue)> = Vec::new();
+        for input in &self.session.inputs {
+            let values = match input.name.as_str() {
+                "input_ids" => window.get_ids(),
+                "attention_mask" => window.get_attention_mask(),
+                "token_type_ids" => window.get_type_ids(),
+                other => anyhow::bail!("Unsupported NER model input: {}", other),
+            };
+            inputs.push((Cow::Owned(input.name.clone()), column(values)?));
+        }
+        let outputs = self.session.run(inputs)?;
+        let (shape, logits) = outputs[0].try_extract_raw_tensor::<f32>()?;
+        let classes = shape.last().copied().unwrap_or(0) as usize;
+        anyhow::ensure!(
+            classes == self.labels.len() && logits.len() == len * classes,
+            "NER model output does not match its labels"
+        );
+        Ok(logits
+            .chunks(classes)
+            .map(|scores| {
+                let best = scores
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .map_or(0, |(i, _)| i);
+                self.labels[best].clone()
+            })
+            .collect())
+    }
+}
+
+/// Group tokens tagged in the BIO scheme (`B-PER`, `I-PER`, `O`) into entities.
+/// `tokens` are byte offsets into `text` with their labels; special tokens have
+/// empty offsets. Word pieces right after an entity's end continue it.
+pub fn entities_from_tokens(text: &str, tokens: &[((usize, usize), &str)]) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    let mut current: Option<(usize, usize, EntityLabel)> = None;
+    let mut flush = |current: &mut Option<(usize, usize, EntityLabel)>| {
+        if let Some((start, end, label)) = current.take() {
+            let name = &text[start..end];
+            if name.chars().any(char::is_alphanumeric) {
+                entities.push(Entity {
+                    text: name.to_string(),
+                    label,
+                    start,
+                    end,
+                });
+            }
+        }
+    };
+
+    for &((start, end), tag) in tokens {
+        if start == end {
+            continue;
+        }
+        let (begins, class) = match tag.split_once('-') {
+            Some(("B", class)) => (true, class),
+            Some(("I", class)) => (false, class),
+            _ if tag == "O" => {
+                flush(&mut current);
+                continue;
+            }
+            _ => (false, tag),
+        };
+        let label = EntityLabel::from_class(class);
+        match &mut current {
+            Some((_, current_end, current_label))
+                if *current_label == label && (!begins || *current_end == start) =>
+            {
+                *current_end = end;
+            }
+            _ => {
+                flush(&mut current);
+                current = Some((start, end, label));
+            }
+        }
+    }
+    flush(&mut current);
+    entities
+}
+
+/// `mentions`/`about` triples linking a document's chunks to the entities tagged in
+/// them.
+///
+/// Each chunk `mentions` its entities and is `isPartOf` the document; the document is
+/// `about` entities mentioned at least twice. Entity nodes get a type and a label.
+pub fn link_entities(
+    document: &str,
+    tagged: &[(String, Vec<Entity>)],
+) -> Vec<(String, String, String)> {
+    let mut triples = Vec::new();
+    // URI -> (entity, mentions); BTreeMap keeps output deterministic
+    let mut seen: BTreeMap<String, (Entity, usize)> = BTreeMap::new();
+
+    for (chunk_uri, entities) in tagged {
+        let mut in_chunk = HashSet::new();
+        for entity in entities {
+            let uri = entity.uri();
+            seen.entry(uri.clone()).or_insert((entity.clone(), 0)).1 += 1;
+            if in_chunk.insert(uri.clone()) {
+                triples.push((chunk_uri.clone(), MENTIONS.to_string(), uri));
+            }
+        }
+        if !in_chunk.is_empty() && chunk_uri != document {
+            triples.push((
+                chunk_uri.clone(),
+                IS_PART_OF.to_string(),
+                document.to_string(),
+            ));
+        }
+    }
+
+    for (uri, (entity, mentions)) in seen {
+        triples.push((
+            uri.clone(),
+            RDF_TYPE.to_string(),
+            entity.label.schema_type().to_string(),
+        ));
+        triples.push((
+            uri.clone(),
+            RDFS_LABEL.to_string(),
+            format!("\"{}\"", entity.text),
+        ));
+        if mentions >= ABOUT_MIN_MENTIONS {
+            triples.push((document.to_string(), ABOUT.to_string(), uri));
+        }
+    }
+    triples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whitespace-separated words of `text` with byte offsets, labelled by `tags`
+    fn words<'a>(text: &str, tags: &[&'a str]) -> Vec<((usize, usize), &'a str)> {
+        let mut offsets = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(i),
+                (true, Some(s)) => {
+                    offsets.push((s, i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(offsets.len(), tags.len());
+        offsets.into_iter().zip(tags.iter().copied()).collect()
+    }
+
+    fn names(entities: Vec<Entity>) -> Vec<(String, EntityLabel)> {
+        entities.into_iter().map(|e| (e.text, e.label)).collect()
+    }
+
+    #[test]
+    fn test_bio_tags_group_into_entities() {
+        let text = "Jane Goodall met Acme Corp staff in Nairobi and Lisbon";
+        let tags = [
+            "B-PER", "I-PER", "O", "B-ORG", "I-ORG", "O", "O", "B-LOC", "O", "B-LOC",
+        ];
+        assert_eq!(
+            names(entities_from_tokens(text, &words(text, &tags))),
+            vec![
+                ("Jane Goodall".to_string(), EntityLabel::Person),
+                ("Acme Corp".to_string(), EntityLabel::Organization),
+                ("Nairobi".to_string(), EntityLabel::Place),
+                ("Lisbon".to_string(), EntityLabel::Place),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_word_pieces_and_special_tokens() {
+        // [CLS] Go ##od ##all visited Lis ##bon [SEP]
+        let text = "Goodall visited Lisbon";
+        let tokens = [
+            ((0, 0), "O"),
+            ((0, 2), "B-PER"),
+            ((2, 4), "B-PER"),
+            ((4, 7), "I-PER"),
+            ((8, 15), "O"),
+            ((16, 19), "B-LOC"),
+            ((19, 22), "B-LOC"),
+            ((0, 0), "O"),
+        ];
+        assert_eq!(
+            names(entities_from_tokens(text, &tokens)),
+            vec![
+                ("Goodall".to_string(), EntityLabel::Person),
+                ("Lisbon".to_string(), EntityLabel::Place),
+            ]
+        );
+
+        // A new B- tag after a space starts a second entity of the same class
+        let text = "Paris Berlin";
+        assert_eq!(
+            entities_from_tokens(text, &words(text, &["B-LOC", "B-LOC"])).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_link_entities() {
+        let entity = |text: &str, label| Entity {
+            text: text.to_string(),
+            label,
+            start: 0,
+            end: text.len(),
+        };
+        let tagged = vec![
+            (
+                "urn:doc#chunk-0".to_string(),
+                vec![entity("Ada Lovelace", EntityLabel::Person)],
+            ),
+            (
+                "urn:doc#chunk-1".to_string(),
+                vec![
+                    entity("Ada Lovelace", EntityLabel::Person),
+                    entity("Charles Babbage", EntityLabel::Person),
+                ],
+            ),
+        ];
+        let triples = link_entities("urn:doc", &tagged);
+        let ada = format!("{}ada_lovelace", ENTITY_BASE);
+        let babbage = format!("{}charles_babbage", ENTITY_BASE);
+
+        let has =
+            |s: &str, p: &str, o: &str| triples.iter().any(|t| t.0 == s && t.1 == p && t.2 == o);
+        assert!(has("urn:doc#chunk-0", MENTIONS, &ada));
+        assert!(has("urn:doc#chunk-1", MENTIONS, &babbage));
+        assert!(has("urn:doc", ABOUT, &ada));
+        assert!(!has("urn:doc", ABOUT, &babbage));
+        assert!(has(&ada, RDFS_LABEL, "\"Ada Lovelace\""));
+        assert!(has(&ada, RDF_TYPE, "http://schema.org/Person"));
+    }
+}
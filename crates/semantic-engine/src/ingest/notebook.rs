@@ -0,0 +1,172 @@
+use super::code::{CodeExtractor, Language, SourceOutline};
+use anyhow::Result;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Markdown,
+    Code,
+    Raw,
+}
+
+impl CellKind {
+    pub fn class(&self) -> &'static str {
+        match self {
+            CellKind::Markdown => "MarkdownCell",
+            CellKind::Code => "CodeCell",
+            CellKind::Raw => "RawCell",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub kind: CellKind,
+    pub source: String,
+}
+
+/// A Jupyter notebook (nbformat 4): cells in order plus the kernel language
+#[derive(Debug, Clone)]
+pub struct Notebook {
+    pub language: String,
+    pub cells: Vec<Cell>,
+}
+
+impl Notebook {
+    pub fn parse(json: &str) -> Result<Self> {
+        let root: Value = serde_json::from_str(json)?;
+        let cells = root
+            .get("cells")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow::anyhow!("Not a notebook: missing 'cells'"))?;
+
+        let metadata = root.get("metadata");
+        let language = metadata
+            .and_then(|m| m.pointer("/kernelspec/language"))
+            .or_else(|| metadata.and_then(|m| m.pointer("/language_info/name")))
+            .and_then(Value::as_str)
+            .unwrap_or("python")
+            .to_lowercase();
+
+        let cells = cells
+            .iter()
+            .map(|cell| {
+                let kind = match cell.get("cell_type").and_then(Value::as_str) {
+                    Some("markdown") => CellKind::Markdown,
+                    Some("code") => CellKind::Code,
+                    _ => CellKind::Raw,
+                };
+                // `source` is a string or a list of lines (with their newlines)
+                let source = match cell.get("source") {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+                    _ => String::new(),
+                };
+                Cell { kind, source }
+            })
+            .collect();
+
+        Ok(Self { language, cells })
+    }
+
+    /// Extractor for the kernel language, if it is one the code extractor understands
+    pub fn code_extractor(&self) -> Option<CodeExtractor> {
+        let extension = match self.language.as_str() {
+            "python" | "python3" => "py",
+            "rust" => "rs",
+            "typescript" => "ts",
+            _ => return None,
+        };
+        Language::from_extension(extension).map(CodeExtractor::new)
+    }
+
+    /// Outline each code cell. Definitions and calls are resolved across the whole
+    /// notebook (a function defined in one cell and called in a later one), then split
+    /// back per cell with cell-relative line numbers. Indexed like `cells`; non-code
+    /// cells get `None`.
+    pub fn outline_cells(&self, extractor: &CodeExtractor) -> Vec<Option<SourceOutline>> {
+        // Concatenate code cells, blanking IPython magics and shell escapes
+        let mut combined = String::new();
+        let mut ranges = Vec::new(); // (cell index, first line, line count)
+        let mut line = 0;
+        for (i, cell) in self.cells.iter().enumerate() {
+            if cell.kind != CellKind::Code {
+                continue;
+            }
+            let lines: Vec<&str> = cell.source.lines().collect();
+            for l in &lines {
+                let trimmed = l.trim_start();
+                if !(trimmed.starts_with('%') || trimmed.starts_with('!')) {
+                    combined.push_str(l);
+                }
+                combined.push('\n');
+            }
+            ranges.push((i, line, lines.len()));
+            line += lines.len();
+        }
+        let whole = extractor.outline(&combined);
+
+        let mut outlines = vec![None; self.cells.len()];
+        for (i, first, count) in ranges {
+            // 1-based lines belonging to this cell
+            let in_cell = |l: usize| l > first && l <= first + count;
+            let symbols: Vec<_> = whole
+                .symbols
+                .iter()
+                .filter(|s| in_cell(s.line))
+                .cloned()
+                .map(|mut s| {
+                    s.line -= first;
+                    s.end_line = s.end_line.saturating_sub(first).min(count);
+                    s
+                })
+                .collect();
+            let calls = whole
+                .calls
+                .iter()
+                .filter(|(caller, _)| symbols.iter().any(|s| &s.name == caller))
+                .cloned()
+                .collect();
+            outlines[i] = Some(SourceOutline {
+                imports: extractor.outline(&self.cells[i].source).imports,
+                symbols,
+                calls,
+            });
+        }
+        outlines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_outline_cells() {
+        let json = r##"{
+          "metadata": { "kernelspec": { "language": "python", "name": "python3" } },
+          "nbformat": 4,
+          "cells": [
+            { "cell_type": "markdown", "source": ["# Analysis\n", "Loads data."] },
+            { "cell_type": "code", "source": ["%matplotlib inline\n", "import pandas as pd\n", "def load(p):\n", "    return pd.read_csv(p)\n"] },
+            { "cell_type": "code", "source": "def run():\n    return load('x.csv')\n" }
+          ]
+        }"##;
+        let notebook = Notebook::parse(json).unwrap();
+        assert_eq!(notebook.cells.len(), 3);
+        assert_eq!(notebook.cells[0].kind, CellKind::Markdown);
+        assert_eq!(notebook.cells[0].source, "# Analysis\nLoads data.");
+
+        let outlines = notebook.outline_cells(&notebook.code_extractor().unwrap());
+        assert!(outlines[0].is_none());
+        let first = outlines[1].as_ref().unwrap();
+        assert_eq!(first.imports, vec!["pandas"]);
+        assert_eq!(first.symbols[0].name, "load");
+        assert_eq!(first.symbols[0].line, 3);
+
+        let second = outlines[2].as_ref().unwrap();
+        assert_eq!(second.symbols[0].name, "run");
+        assert_eq!(second.symbols[0].line, 1);
+        assert_eq!(second.calls, vec![("run".to_string(), "load".to_string())]);
+    }
+}
@@ -1,7 +1,7 @@
+use super::extractor::RdfExtractor;
 use crate::store::{IngestTriple, Provenance, SynapseStore};
 use anyhow::Result;
 use oxigraph::io::RdfFormat;
-use oxigraph::io::RdfParser;
 use std::fs;
 use std::path::Path;
 
@@ -12,27 +12,36 @@ impl OntologyLoader {
         let mut total_triples = 0;
 
         if !dir_path.exists() {
-            eprintln!("Ontology directory not found: {:?}", dir_path);
+            tracing::warn!("Ontology directory not found: {:?}", dir_path);
             return Ok(0);
         }
 
         let entries = fs::read_dir(dir_path)?;
 
+        #[allow(clippy::manual_flatten)]
         for entry in entries {
             if let Ok(entry) = entry {
                 let path = entry.path();
                 if path.is_file() {
                     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                         let ext = ext.to_lowercase();
-                        if matches!(ext.as_str(), "owl" | "ttl" | "rdf" | "xml") {
-                            eprintln!("Loading ontology: {:?}", path.file_name().unwrap());
+                        if matches!(ext.as_str(), "owl" | "ttl" | "nt" | "rdf" | "xml") {
+                            tracing::info!("Loading ontology: {:?}", path.file_name().unwrap());
                             match Self::load_file(store, &path).await {
                                 Ok(count) => {
                                     total_triples += count;
-                                    eprintln!("  Loaded {} triples", count);
+                                    tracing::info!(
+                                        "Loaded {} triples from {}",
+                                        count,
+                                        path.display()
+                                    );
                                 }
                                 Err(e) => {
-                                    eprintln!("  Failed to load ontology {:?}: {}", path.display(), e);
+                                    tracing::warn!(
+                                        "Failed to load ontology {:?}: {}",
+                                        path.display(),
+                                        e
+                                    );
                                 }
                             }
                         }
@@ -47,34 +56,29 @@ impl OntologyLoader {
         let file = fs::File::open(path)?;
         let reader = std::io::BufReader::new(file);
 
-        // Determine format based on extension
-        let format = if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            match ext.to_lowercase().as_str() {
-                "ttl" => RdfFormat::Turtle,
-                "rdf" | "owl" | "xml" => RdfFormat::RdfXml,
-                _ => RdfFormat::Turtle, // Default fallback
-            }
-        } else {
-            RdfFormat::Turtle
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("ttl");
+        let extractor =
+            RdfExtractor::for_extension(extension).unwrap_or(RdfExtractor::new(RdfFormat::Turtle));
+        let (absolute, sha256) = super::fingerprint(path)?;
+        let provenance = Provenance {
+            source: path.file_name().unwrap().to_string_lossy().to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            method: "ontology_loader".to_string(),
+            path: Some(absolute),
+            sha256: Some(sha256),
+            span: None,
         };
 
-        let mut ingest_triples = Vec::new();
-        let parser = RdfParser::from_format(format);
-
-        for triple_result in parser.for_reader(reader) {
-            let triple = triple_result.map_err(|e| anyhow::anyhow!("Parse error: {}", e))?;
-
-            ingest_triples.push(IngestTriple {
-                subject: triple.subject.to_string(),
-                predicate: triple.predicate.to_string(),
-                object: triple.object.to_string(),
-                provenance: Some(Provenance {
-                    source: path.file_name().unwrap().to_string_lossy().to_string(),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                    method: "ontology_loader".to_string(),
-                }),
-            });
-        }
+        let ingest_triples: Vec<IngestTriple> = extractor
+            .extract(reader)?
+            .into_iter()
+            .map(|t| IngestTriple {
+                subject: t.subject,
+                predicate: t.predicate,
+                object: t.object,
+                provenance: Some(provenance.clone()),
+            })
+            .collect();
 
         let count = ingest_triples.len();
         if count > 0 {
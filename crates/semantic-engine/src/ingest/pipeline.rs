@@ -0,0 +1,399 @@
+//! Ingestion pipeline.
+//!
+//! Every `IngestionEngine` source runs the same stages after extraction:
+//! normalize URIs → enrich provenance → namespace transforms → validate → store.
+//! Transforms are configured per namespace (persisted as `pipeline.json` in the
+//! namespace directory) or registered in code.
+
+use crate::store::{IngestTriple, Provenance, SourceSpan};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+const CONFIG_FILE: &str = "pipeline.json";
+
+/// What a stage sees about the ingestion it is part of
+#[derive(Debug, Clone)]
+pub struct IngestContext {
+    pub namespace: String,
+    pub source: String,
+    pub method: String,
+    /// Absolute path and SHA-256 of the source file, for file-based ingestion
+    pub path: Option<String>,
+    pub sha256: Option<String>,
+}
+
+/// An extracted triple and where in its source file it came from
+pub type LocatedTriple = ((String, String, String), Option<SourceSpan>);
+
+/// A custom pipeline stage, run after provenance is attached and before validation
+pub trait Transform: Send + Sync {
+    fn name(&self) -> &str;
+    fn apply(&self, triples: Vec<IngestTriple>, ctx: &IngestContext) -> Result<Vec<IngestTriple>>;
+}
+
+/// Which triple positions a URI transform touches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Position {
+    Subject,
+    Predicate,
+    Object,
+}
+
+/// Declarative transforms, storable in `pipeline.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformConfig {
+    /// Rewrite URIs matching `pattern` (`$1` etc. refer to capture groups). Literals
+    /// are never rewritten; `positions` defaults to subject, predicate and object.
+    RegexRewrite {
+        pattern: String,
+        replacement: String,
+        #[serde(default)]
+        positions: Vec<Position>,
+    },
+    /// Drop triples whose predicate matches `pattern`
+    DropPredicate { pattern: String },
+}
+
+impl TransformConfig {
+    pub fn build(&self) -> Result<Arc<dyn Transform>> {
+        Ok(match self {
+            TransformConfig::RegexRewrite {
+                pattern,
+                replacement,
+                positions,
+            } => Arc::new(RegexRewrite {
+                name: format!("regex_rewrite {}", pattern),
+                pattern: Regex::new(pattern)?,
+                replacement: replacement.clone(),
+                positions: if positions.is_empty() {
+                    vec![Position::Subject, Position::Predicate, Position::Object]
+                } else {
+                    positions.clone()
+                },
+            }),
+            TransformConfig::DropPredicate { pattern } => Arc::new(DropPredicate {
+                name: format!("drop_predicate {}", pattern),
+                pattern: Regex::new(pattern)?,
+            }),
+        })
+    }
+}
+
+/// The transforms configured for a namespace, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    #[serde(default)]
+    pub transforms: Vec<TransformConfig>,
+}
+
+pub struct RegexRewrite {
+    name: String,
+    pattern: Regex,
+    replacement: String,
+    positions: Vec<Position>,
+}
+
+impl Transform for RegexRewrite {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(
+        &self,
+        mut triples: Vec<IngestTriple>,
+        _ctx: &IngestContext,
+    ) -> Result<Vec<IngestTriple>> {
+        let rewrite = |term: &mut String| {
+            if !is_literal(term) {
+                if let std::borrow::Cow::Owned(rewritten) =
+                    self.pattern.replace(term, self.replacement.as_str())
+                {
+                    *term = rewritten;
+                }
+            }
+        };
+        for triple in &mut triples {
+            for position in &self.positions {
+                match position {
+                    Position::Subject => rewrite(&mut triple.subject),
+                    Position::Predicate => rewrite(&mut triple.predicate),
+                    Position::Object => rewrite(&mut triple.object),
+                }
+            }
+        }
+        Ok(triples)
+    }
+}
+
+pub struct DropPredicate {
+    name: String,
+    pattern: Regex,
+}
+
+impl Transform for DropPredicate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(
+        &self,
+        mut triples: Vec<IngestTriple>,
+        _ctx: &IngestContext,
+    ) -> Result<Vec<IngestTriple>> {
+        triples.retain(|t| !self.pattern.is_match(&t.predicate));
+        Ok(triples)
+    }
+}
+
+/// A triple the validate stage refused, with the reason
+#[derive(Debug, Clone)]
+pub struct Rejected {
+    pub triple: (String, String, String),
+    pub reason: String,
+}
+
+/// Triples ready to store, and those dropped by validation
+#[derive(Default)]
+pub struct PipelineOutput {
+    pub triples: Vec<IngestTriple>,
+    pub rejected: Vec<Rejected>,
+}
+
+/// Per-namespace pipeline state, held by the namespace's `SynapseStore`
+#[derive(Default)]
+pub struct NamespacePipeline {
+    config: RwLock<PipelineConfig>,
+    configured: RwLock<Vec<Arc<dyn Transform>>>,
+    registered: RwLock<Vec<Arc<dyn Transform>>>,
+}
+
+impl NamespacePipeline {
+    /// Load `pipeline.json` from a namespace directory, if present
+    pub fn load(dir: &Path) -> Result<Self> {
+        let pipeline = Self::default();
+        let path = dir.join(CONFIG_FILE);
+        if path.exists() {
+            let config: PipelineConfig = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            pipeline.apply_config(config)?;
+        }
+        Ok(pipeline)
+    }
+
+    pub fn config(&self) -> PipelineConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the configured transforms and persist them to `dir`
+    pub fn configure(&self, dir: &Path, config: PipelineConfig) -> Result<()> {
+        self.apply_config(config.clone())?;
+        std::fs::write(
+            dir.join(CONFIG_FILE),
+            serde_json::to_string_pretty(&config)?,
+        )?;
+        Ok(())
+    }
+
+    fn apply_config(&self, config: PipelineConfig) -> Result<()> {
+        let built = config
+            .transforms
+            .iter()
+            .map(TransformConfig::build)
+            .collect::<Result<Vec<_>>>()?;
+        *self.configured.write().unwrap() = built;
+        *self.config.write().unwrap() = config;
+        Ok(())
+    }
+
+    /// Add a transform implemented in code; runs after the configured ones
+    pub fn register(&self, transform: Arc<dyn Transform>) {
+        self.registered.write().unwrap().push(transform);
+    }
+
+    /// Names of the active custom stages, in order
+    pub fn stage_names(&self) -> Vec<String> {
+        self.transforms()
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect()
+    }
+
+    fn transforms(&self) -> Vec<Arc<dyn Transform>> {
+        let mut transforms = self.configured.read().unwrap().clone();
+        transforms.extend(self.registered.read().unwrap().iter().cloned());
+        transforms
+    }
+
+    /// Run extracted triples through every stage up to (not including) storage
+    pub fn run(
+        &self,
+        triples: Vec<(String, String, String)>,
+        ctx: &IngestContext,
+    ) -> Result<PipelineOutput> {
+        self.run_located(triples.into_iter().map(|t| (t, None)).collect(), ctx)
+    }
+
+    /// Like `run`, recording each triple's source location in its provenance
+    pub fn run_located(
+        &self,
+        triples: Vec<LocatedTriple>,
+        ctx: &IngestContext,
+    ) -> Result<PipelineOutput> {
+        // Enrich provenance: one record per run (and per source location), so each
+        // batch lands in its own named graph
+        let provenance = Provenance {
+            source: ctx.source.clone(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            method: ctx.method.clone(),
+            path: ctx.path.clone(),
+            sha256: ctx.sha256.clone(),
+            span: None,
+        };
+        let mut triples: Vec<IngestTriple> = triples
+            .into_iter()
+            .map(|((s, p, o), span)| IngestTriple {
+                subject: normalize_uri(s),
+                predicate: normalize_uri(p),
+                object: normalize_uri(o),
+                provenance: Some(Provenance {
+                    span,
+                    ..provenance.clone()
+                }),
+            })
+            .collect();
+
+        for transform in self.transforms() {
+            triples = transform
+                .apply(triples, ctx)
+                .map_err(|e| anyhow::anyhow!("Transform '{}' failed: {}", transform.name(), e))?;
+        }
+
+        let mut output = PipelineOutput::default();
+        for triple in triples {
+            match validate(&triple) {
+                Ok(()) => output.triples.push(triple),
+                Err(reason) => output.rejected.push(Rejected {
+                    triple: (triple.subject, triple.predicate, triple.object),
+                    reason,
+                }),
+            }
+        }
+        Ok(output)
+    }
+}
+
+fn is_literal(term: &str) -> bool {
+    term.starts_with('"')
+}
+
+/// Trim URIs, drop `<…>` brackets and percent-encode inner spaces. Literals pass through.
+pub fn normalize_uri(term: String) -> String {
+    if is_literal(&term) {
+        return term;
+    }
+    let trimmed = term.trim();
+    let unbracketed = trimmed
+        .strip_prefix('<')
+        .and_then(|t| t.strip_suffix('>'))
+        .unwrap_or(trimmed);
+    if unbracketed.len() == term.len() && !unbracketed.contains(' ') {
+        return term;
+    }
+    unbracketed.replace(' ', "%20")
+}
+
+fn validate(triple: &IngestTriple) -> std::result::Result<(), String> {
+    if triple.subject.is_empty() || triple.predicate.is_empty() || triple.object.is_empty() {
+        return Err("empty term".to_string());
+    }
+    if is_literal(&triple.subject) {
+        return Err("literal subject".to_string());
+    }
+    if is_literal(&triple.predicate) {
+        return Err("literal predicate".to_string());
+    }
+    if triple.subject.contains(char::is_whitespace)
+        || triple.predicate.contains(char::is_whitespace)
+    {
+        return Err("whitespace in URI".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> IngestContext {
+        IngestContext {
+            namespace: "test".to_string(),
+            source: "unit".to_string(),
+            method: "test".to_string(),
+            path: None,
+            sha256: None,
+        }
+    }
+
+    fn triple(s: &str, p: &str, o: &str) -> (String, String, String) {
+        (s.to_string(), p.to_string(), o.to_string())
+    }
+
+    #[test]
+    fn test_stages_in_order() {
+        let pipeline = NamespacePipeline::default();
+        pipeline
+            .apply_config(PipelineConfig {
+                transforms: vec![
+                    TransformConfig::RegexRewrite {
+                        pattern: "^http://old\\.example/(.*)$".to_string(),
+                        replacement: "https://new.example/$1".to_string(),
+                        positions: vec![],
+                    },
+                    TransformConfig::DropPredicate {
+                        pattern: "internal".to_string(),
+                    },
+                ],
+            })
+            .unwrap();
+
+        let output = pipeline
+            .run(
+                vec![
+                    triple(
+                        " <http://old.example/a> ",
+                        "http://p",
+                        "\"http://old.example/x\"",
+                    ),
+                    triple("http://old.example/a", "http://internal/note", "\"x\""),
+                    triple("\"literal\"", "http://p", "http://o"),
+                    triple("http://s", "http://p", ""),
+                ],
+                &ctx(),
+            )
+            .unwrap();
+
+        assert_eq!(output.triples.len(), 1);
+        let kept = &output.triples[0];
+        assert_eq!(kept.subject, "https://new.example/a");
+        // Literals are never rewritten
+        assert_eq!(kept.object, "\"http://old.example/x\"");
+        assert_eq!(kept.provenance.as_ref().unwrap().method, "test");
+
+        let reasons: Vec<&str> = output.rejected.iter().map(|r| r.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["literal subject", "empty term"]);
+    }
+
+    #[test]
+    fn test_invalid_regex_is_rejected() {
+        let config = PipelineConfig {
+            transforms: vec![TransformConfig::DropPredicate {
+                pattern: "(".to_string(),
+            }],
+        };
+        assert!(NamespacePipeline::default().apply_config(config).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Article extraction for crawled HTML: picks the main content, drops page chrome
+//! (navigation, headers, footers, sidebars, forms) and splits it at headings.
+
+use super::crawler::{extract_title, html_to_text};
+use regex::Regex;
+
+/// The readable part of a page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Article {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+/// Text under one `h1`–`h3` heading. Text before the first heading has an empty
+/// heading and level 0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    pub heading: String,
+    pub level: u8,
+    pub text: String,
+}
+
+impl Article {
+    /// All section text, headings included, as one string
+    pub fn text(&self) -> String {
+        self.sections
+            .iter()
+            .flat_map(|s| [s.heading.as_str(), s.text.as_str()])
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+pub fn extract(html: &str) -> Article {
+    let chrome_re = Regex::new(
+        r"(?is)<(script|style|noscript|template|svg|nav|header|footer|aside|form)\b.*?</(script|style|noscript|template|svg|nav|header|footer|aside|form)>",
+    )
+    .unwrap();
+    let comment_re = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    let cleaned = comment_re.replace_all(html, " ");
+    let cleaned = chrome_re.replace_all(&cleaned, " ");
+
+    let main = main_content(&cleaned);
+    let sections = split_sections(main);
+    let title = meta_title(html)
+        .or_else(|| {
+            sections
+                .iter()
+                .find(|s| s.level == 1)
+                .map(|s| s.heading.clone())
+        })
+        .unwrap_or_else(|| extract_title(html));
+
+    Article { title, sections }
+}
+
+/// The first `<article>`, `<main>` or `role="main"` element, else the body
+fn main_content(html: &str) -> &str {
+    for pattern in [
+        r"(?is)<article\b[^>]*>(.*)</article>",
+        r"(?is)<main\b[^>]*>(.*)</main>",
+        r#"(?is)<div\b[^>]*role\s*=\s*["']main["'][^>]*>(.*)</div>"#,
+        r"(?is)<body\b[^>]*>(.*)</body>",
+    ] {
+        let re = Regex::new(pattern).unwrap();
+        if let Some(m) = re.captures(html).and_then(|c| c.get(1)) {
+            if !html_to_text(m.as_str()).is_empty() {
+                return m.as_str();
+            }
+        }
+    }
+    html
+}
+
+fn split_sections(html: &str) -> Vec<Section> {
+    let heading_re = Regex::new(r"(?is)<h([1-3])\b[^>]*>(.*?)</h[1-3]>").unwrap();
+    let mut sections = Vec::new();
+    let mut current = Section::default();
+    let mut last = 0;
+
+    for caps in heading_re.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        current.text = html_to_text(&html[last..whole.start()]);
+        sections.push(std::mem::take(&mut current));
+        current.heading = html_to_text(&caps[2]);
+        current.level = caps[1].parse().unwrap_or(1);
+        last = whole.end();
+    }
+    current.text = html_to_text(&html[last..]);
+    sections.push(current);
+
+    sections.retain(|s| !s.heading.is_empty() || !s.text.is_empty());
+    sections
+}
+
+/// `og:title`, which sites set to the article title without the site name
+fn meta_title(html: &str) -> Option<String> {
+    let og_re = Regex::new(
+        r#"(?is)<meta\b[^>]*property\s*=\s*["']og:title["'][^>]*content\s*=\s*["']([^"']*)["']"#,
+    )
+    .unwrap();
+    og_re
+        .captures(html)
+        .map(|c| html_to_text(&c[1]))
+        .filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extracts_article_sections() {
+        let html = r#"<html><head><title>Rust Tips | Blog</title></head><body>
+            <header><a href="/">Home</a></header>
+            <nav><ul><li>Archive</li><li>About</li></ul></nav>
+            <article>
+              <h1>Rust Tips</h1>
+              <p>Some intro.</p>
+              <h2>Ownership</h2><p>Values have <em>one</em> owner.</p>
+              <!-- ad slot -->
+              <h2>Borrowing</h2><p>References borrow.</p>
+            </article>
+            <aside>Related posts</aside>
+            <footer>Copyright</footer>
+            </body></html>"#;
+
+        let article = extract(html);
+        assert_eq!(article.title, "Rust Tips");
+        let headings: Vec<&str> = article
+            .sections
+            .iter()
+            .map(|s| s.heading.as_str())
+            .collect();
+        assert_eq!(headings, vec!["Rust Tips", "Ownership", "Borrowing"]);
+        assert_eq!(article.sections[1].text, "Values have one owner.");
+        assert_eq!(article.sections[1].level, 2);
+
+        let text = article.text();
+        assert!(!text.contains("Archive") && !text.contains("Copyright"));
+        assert!(!text.contains("Related posts") && !text.contains("ad slot"));
+    }
+
+    #[test]
+    fn test_falls_back_to_body_and_title() {
+        let html = "<html><head><title> Plain  page </title></head>\
+                    <body><p>Just text.</p></body></html>";
+        let article = extract(html);
+        assert_eq!(article.title, "Plain page");
+        assert_eq!(
+            article.sections,
+            vec![Section {
+                heading: String::new(),
+                level: 0,
+                text: "Just text.".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Relational connector: mirrors Postgres/SQLite tables into the graph.
+//!
+//! An R2RML-lite mapping turns each table into a class and each column into a
+//! predicate. Enabled with the `sql` feature.
+
+use super::csv_mapping::CsvMapping;
+use anyhow::Result;
+use futures::TryStreamExt;
+use serde::Deserialize;
+use sqlx::any::{AnyPoolOptions, AnyRow};
+use sqlx::{AnyPool, Column, Row};
+use std::collections::BTreeMap;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Table → class, column → predicate mapping, loaded from JSON or YAML.
+///
+/// ```yaml
+/// base: "http://example.org/"
+/// tables:
+///   users:
+///     class: "http://schema.org/Person"
+///     subject: "http://example.org/user/{id}"
+///     skip: [password_hash]
+///     columns:
+///       name: { predicate: "http://schema.org/name" }
+///       team_id: { predicate: "http://schema.org/memberOf", datatype: iri }
+///   teams:
+///     sql: "SELECT id, name FROM teams WHERE archived = false"
+/// ```
+///
+/// Unmapped columns get `<base><table>#<column>`; without a subject template the first
+/// column is the key and rows become `<base><table>/<key>`. Only integer, float,
+/// boolean and text columns are read; cast other types in a custom `sql`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SqlMapping {
+    #[serde(default = "default_base")]
+    pub base: String,
+    pub tables: BTreeMap<String, TableMapping>,
+}
+
+fn default_base() -> String {
+    "http://synapse.os/sql/".to_string()
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableMapping {
+    /// rdf:type for every row
+    #[serde(default)]
+    pub class: Option<String>,
+    /// Query to run instead of `SELECT * FROM <table>`
+    #[serde(default)]
+    pub sql: Option<String>,
+    /// Subject template, skipped columns and per-column predicates/datatypes
+    #[serde(flatten)]
+    pub rows: CsvMapping,
+}
+
+impl SqlMapping {
+    /// Parse a mapping given as JSON or YAML text
+    pub fn parse(text: &str) -> Result<Self> {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('{') {
+            Ok(serde_json::from_str(trimmed)?)
+        } else {
+            Ok(serde_yaml::from_str(text)?)
+        }
+    }
+}
+
+/// Outcome of mirroring a database
+#[derive(Debug, Clone, Default)]
+pub struct SqlIngestSummary {
+    pub tables: u32,
+    pub rows: u32,
+    pub triples_added: u32,
+}
+
+/// A connection pool to a Postgres (`postgres://…`) or SQLite (`sqlite://…`) database
+pub struct SqlConnector {
+    pool: AnyPool,
+}
+
+impl SqlConnector {
+    pub async fn connect(url: &str) -> Result<Self> {
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new()
+            .max_connections(2)
+            .connect(url)
+            .await?;
+        Ok(Self { pool })
+    }
+
+    /// Read a table and map its rows. Returns the row count and the triples.
+    pub async fn map_table(
+        &self,
+        base: &str,
+        table: &str,
+        mapping: &TableMapping,
+    ) -> Result<(u32, Vec<(String, String, String)>)> {
+        let query = match &mapping.sql {
+            Some(sql) => sql.clone(),
+            None => format!("SELECT * FROM {}", quote_table(table)?),
+        };
+
+        let mut rows = sqlx::query(&query).fetch(&self.pool);
+        let mut count = 0;
+        let mut triples = Vec::new();
+        while let Some(row) = rows.try_next().await? {
+            let (headers, cells) = read_row(&row);
+            let effective = with_defaults(base, table, &mapping.rows, &headers, &cells);
+            let headers = csv::StringRecord::from(headers);
+            let record = csv::StringRecord::from(
+                cells
+                    .into_iter()
+                    .map(|c| c.map(|(text, _)| text).unwrap_or_default())
+                    .collect::<Vec<_>>(),
+            );
+
+            let default_subject = |key: &str| format!("{}{}/{}", base, table, key);
+            let Some(subject) = effective.subject_for(&headers, &record, default_subject) else {
+                continue;
+            };
+            count += 1;
+            if let Some(class) = &mapping.class {
+                triples.push((subject, RDF_TYPE.to_string(), class.clone()));
+            }
+            triples.extend(
+                effective
+                    .map_row(&headers, &record, default_subject)
+                    .unwrap_or_default(),
+            );
+        }
+        Ok((count, triples))
+    }
+}
+
+/// Column names and, per column, its text and inferred XSD type (`None` for NULL)
+type Cell = Option<(String, Option<&'static str>)>;
+
+fn read_row(row: &AnyRow) -> (Vec<String>, Vec<Cell>) {
+    let headers = row.columns().iter().map(|c| c.name().to_string()).collect();
+    let cells = (0..row.len())
+        .map(|i| {
+            if let Ok(v) = row.try_get::<Option<i64>, _>(i) {
+                v.map(|v| (v.to_string(), Some("integer")))
+            } else if let Ok(v) = row.try_get::<Option<f64>, _>(i) {
+                v.map(|v| (v.to_string(), Some("double")))
+            } else if let Ok(v) = row.try_get::<Option<bool>, _>(i) {
+                v.map(|v| (v.to_string(), Some("boolean")))
+            } else {
+                row.try_get::<Option<String>, _>(i)
+                    .ok()
+                    .flatten()
+                    .map(|v| (v, None))
+            }
+        })
+        .collect();
+    (headers, cells)
+}
+
+/// Fill in predicates for unmapped columns and datatypes inferred from the SQL values
+fn with_defaults(
+    base: &str,
+    table: &str,
+    mapping: &CsvMapping,
+    headers: &[String],
+    cells: &[Cell],
+) -> CsvMapping {
+    let mut effective = mapping.clone();
+    let template_columns = mapping.template_columns();
+    for (j, (header, cell)) in headers.iter().zip(cells).enumerate() {
+        // Key columns stay implicit unless mapped explicitly
+        let is_key = match &mapping.subject {
+            Some(_) => template_columns.contains(header),
+            None => j == 0,
+        };
+        if is_key && !mapping.columns.contains_key(header) {
+            continue;
+        }
+        let column = effective.columns.entry(header.clone()).or_default();
+        if column.predicate.is_none() {
+            column.predicate = Some(format!("{}{}#{}", base, table, header));
+        }
+        if column.datatype.is_none() {
+            column.datatype = cell
+                .as_ref()
+                .and_then(|(_, datatype)| *datatype)
+                .map(str::to_string);
+        }
+    }
+    effective
+}
+
+/// Quote a (possibly schema-qualified) table name, rejecting anything but identifiers
+fn quote_table(table: &str) -> Result<String> {
+    let parts: Vec<&str> = table.split('.').collect();
+    let valid = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    if !parts.iter().all(|p| valid(p)) {
+        return Err(anyhow::anyhow!("Invalid table name: {}", table));
+    }
+    Ok(parts
+        .iter()
+        .map(|p| format!("\"{}\"", p))
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_table() {
+        assert_eq!(quote_table("public.users").unwrap(), "\"public\".\"users\"");
+        assert!(quote_table("users; DROP TABLE x").is_err());
+    }
+
+    #[test]
+    fn test_defaults_keep_key_implicit() {
+        let mapping = SqlMapping::parse(
+            r#"
+tables:
+  users:
+    columns:
+      name: { predicate: "http://schema.org/name" }
+"#,
+        )
+        .unwrap();
+        let headers = vec!["id".to_string(), "name".to_string(), "age".to_string()];
+        let cells = vec![
+            Some(("1".to_string(), Some("integer"))),
+            Some(("Ann".to_string(), None)),
+            Some(("41".to_string(), Some("integer"))),
+        ];
+        let effective = with_defaults(
+            &mapping.base,
+            "users",
+            &mapping.tables["users"].rows,
+            &headers,
+            &cells,
+        );
+        assert!(!effective.columns.contains_key("id"));
+        assert_eq!(effective.columns["name"].datatype, None);
+        let age = &effective.columns["age"];
+        assert_eq!(
+            age.predicate.as_deref(),
+            Some("http://synapse.os/sql/users#age")
+        );
+        assert_eq!(age.datatype.as_deref(), Some("integer"));
+    }
+}
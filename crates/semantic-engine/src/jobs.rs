@@ -0,0 +1,179 @@
+use crate::reasoner::RuleStats;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Lifecycle state of a background job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Running => "running",
+            JobState::Completed => "completed",
+            JobState::Failed => "failed",
+        }
+    }
+}
+
+/// Snapshot of a background job's progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub kind: String,
+    pub namespace: String,
+    pub state: JobState,
+    /// Estimated completion (0.0 - 100.0)
+    pub percent_complete: f32,
+    pub triples_inferred: usize,
+    pub message: String,
+    /// Per-rule breakdown, filled in when a reasoning job finishes
+    #[serde(default)]
+    pub rule_stats: Vec<RuleStats>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Registry of background jobs (reasoning, etc.) keyed by job ID
+pub struct JobRegistry {
+    jobs: RwLock<HashMap<String, JobStatus>>,
+    /// Cancellation handles of running jobs
+    cancellations: RwLock<HashMap<String, CancellationToken>>,
+    /// Maximum finished jobs kept around for status queries
+    max_finished: usize,
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            cancellations: RwLock::new(HashMap::new()),
+            max_finished: 100,
+        }
+    }
+
+    /// Register a new running job and return its ID
+    pub fn start(&self, kind: &str, namespace: &str) -> String {
+        let id = Uuid::new_v4().to_string();
+        let status = JobStatus {
+            id: id.clone(),
+            kind: kind.to_string(),
+            namespace: namespace.to_string(),
+            state: JobState::Running,
+            percent_complete: 0.0,
+            triples_inferred: 0,
+            message: String::new(),
+            rule_stats: Vec::new(),
+            started_at: Utc::now(),
+            finished_at: None,
+        };
+
+        let mut jobs = self.jobs.write().unwrap();
+        jobs.insert(id.clone(), status);
+        Self::trim(&mut jobs, self.max_finished);
+        self.cancellations
+            .write()
+            .unwrap()
+            .insert(id.clone(), CancellationToken::new());
+        id
+    }
+
+    /// Token the job's worker should watch; `None` once the job has finished
+    pub fn cancellation(&self, id: &str) -> Option<CancellationToken> {
+        self.cancellations.read().unwrap().get(id).cloned()
+    }
+
+    /// Ask a running job to stop. Returns false if it is unknown or already finished.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.cancellations.read().unwrap().get(id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Update progress of a running job
+    pub fn update(&self, id: &str, percent_complete: f32, triples_inferred: usize) {
+        let mut jobs = self.jobs.write().unwrap();
+        if let Some(job) = jobs.get_mut(id) {
+            if job.state == JobState::Running {
+                job.percent_complete = percent_complete.clamp(0.0, 100.0);
+                job.triples_inferred = triples_inferred;
+            }
+        }
+    }
+
+    /// Attach per-rule statistics to a job
+    pub fn set_rule_stats(&self, id: &str, rule_stats: Vec<RuleStats>) {
+        let mut jobs = self.jobs.write().unwrap();
+        if let Some(job) = jobs.get_mut(id) {
+            job.rule_stats = rule_stats;
+        }
+    }
+
+    /// Mark a job as successfully finished
+    pub fn complete(&self, id: &str, triples_inferred: usize, message: String) {
+        self.finish(id, JobState::Completed, Some(triples_inferred), message);
+    }
+
+    /// Mark a job as failed
+    pub fn fail(&self, id: &str, error: String) {
+        self.finish(id, JobState::Failed, None, error);
+    }
+
+    fn finish(&self, id: &str, state: JobState, triples_inferred: Option<usize>, message: String) {
+        self.cancellations.write().unwrap().remove(id);
+        let mut jobs = self.jobs.write().unwrap();
+        if let Some(job) = jobs.get_mut(id) {
+            job.state = state;
+            if state == JobState::Completed {
+                job.percent_complete = 100.0;
+            }
+            if let Some(count) = triples_inferred {
+                job.triples_inferred = count;
+            }
+            job.message = message;
+            job.finished_at = Some(Utc::now());
+        }
+    }
+
+    /// Get the current status of a job
+    pub fn get(&self, id: &str) -> Option<JobStatus> {
+        self.jobs.read().unwrap().get(id).cloned()
+    }
+
+    /// Drop the oldest finished jobs beyond the retention limit
+    fn trim(jobs: &mut HashMap<String, JobStatus>, max_finished: usize) {
+        let mut finished: Vec<(String, DateTime<Utc>)> = jobs
+            .values()
+            .filter_map(|j| j.finished_at.map(|t| (j.id.clone(), t)))
+            .collect();
+
+        if finished.len() <= max_finished {
+            return;
+        }
+
+        finished.sort_by_key(|(_, t)| *t);
+        let excess = finished.len() - max_finished;
+        for (id, _) in finished.into_iter().take(excess) {
+            jobs.remove(&id);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use crate::auth::NamespacePermission;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// How often the signing keys are fetched again, picking up key rotations
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Least time between the early fetches triggered by tokens signed with an unknown key
+const JWKS_UNKNOWN_KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Signature algorithms accepted from the identity provider; shared-secret (HS*)
+/// tokens are never accepted since the JWKS is public
+const ALLOWED_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// What a valid JWT grants
+#[derive(Debug, Clone, PartialEq)]
+pub struct JwtGrant {
+    pub namespaces: Vec<String>,
+    pub permissions: NamespacePermission,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Validates bearer JWTs against an OIDC issuer's signing keys and maps their
+/// claims to namespace permissions
+pub struct JwtValidator {
+    /// Compared exactly with the `iss` claim
+    issuer: String,
+    audience: Option<String>,
+    /// Signing keys location; discovered from the issuer when unset
+    jwks_url: Option<String>,
+    /// Claim listing namespace patterns
+    pub namespaces_claim: String,
+    /// Claim listing roles (see `permissions_for_roles`)
+    pub roles_claim: String,
+    keys: RwLock<JwkSet>,
+    unknown_key_refreshed_at: Mutex<Option<Instant>>,
+}
+
+impl JwtValidator {
+    pub fn new(issuer: &str, audience: Option<String>, jwks_url: Option<String>) -> Self {
+        Self {
+            issuer: issuer.to_string(),
+            audience,
+            jwks_url,
+            namespaces_claim: "namespaces".to_string(),
+            roles_claim: "roles".to_string(),
+            keys: RwLock::new(JwkSet { keys: Vec::new() }),
+            unknown_key_refreshed_at: Mutex::new(None),
+        }
+    }
+
+    /// Configuration from `SYNAPSE_JWT_ISSUER` (required), `SYNAPSE_JWT_AUDIENCE`,
+    /// `SYNAPSE_JWT_JWKS_URL`, `SYNAPSE_JWT_NAMESPACES_CLAIM` and `SYNAPSE_JWT_ROLES_CLAIM`
+    pub fn from_env() -> Option<Self> {
+        let issuer = std::env::var("SYNAPSE_JWT_ISSUER").ok()?;
+        let mut validator = Self::new(
+            &issuer,
+            std::env::var("SYNAPSE_JWT_AUDIENCE").ok(),
+            std::env::var("SYNAPSE_JWT_JWKS_URL").ok(),
+        );
+        if let Ok(claim) = std::env::var("SYNAPSE_JWT_NAMESPACES_CLAIM") {
+            validator.namespaces_claim = claim;
+        }
+        if let Ok(claim) = std::env::var("SYNAPSE_JWT_ROLES_CLAIM") {
+            validator.roles_claim = claim;
+        }
+        Some(validator)
+    }
+
+    /// Replace the signing keys
+    pub fn set_keys(&self, keys: JwkSet) {
+        *self.keys.write().unwrap() = keys;
+    }
+
+    /// Fetch the signing keys, from `jwks_url` or the issuer's OpenID configuration.
+    /// Returns the number of keys.
+    pub async fn refresh(&self) -> Result<usize> {
+        let client = reqwest::Client::new();
+        let jwks_url = match &self.jwks_url {
+            Some(url) => url.clone(),
+            None => {
+                let discovery: serde_json::Value = client
+                    .get(format!(
+                        "{}/.well-known/openid-configuration",
+                        self.issuer.trim_end_matches('/')
+                    ))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                discovery
+                    .get("jwks_uri")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow::anyhow!("OpenID configuration has no jwks_uri"))?
+                    .to_string()
+            }
+        };
+        let keys: JwkSet = client
+            .get(jwks_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let count = keys.keys.len();
+        self.set_keys(keys);
+        Ok(count)
+    }
+
+    /// Fetch the keys now and then periodically, in the background
+    pub fn spawn_refresh(self: &Arc<Self>) {
+        let validator = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = validator.refresh().await {
+                    tracing::warn!("Failed to fetch JWT signing keys: {}", e);
+                }
+                tokio::time::sleep(JWKS_REFRESH_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Whether a token signed with an unknown key may trigger a fetch now, at most
+    /// once per `JWKS_UNKNOWN_KEY_REFRESH_INTERVAL`
+    fn claim_unknown_key_refresh(&self) -> bool {
+        let mut refreshed_at = self.unknown_key_refreshed_at.lock().unwrap();
+        if refreshed_at.is_some_and(|at| at.elapsed() < JWKS_UNKNOWN_KEY_REFRESH_INTERVAL) {
+            return false;
+        }
+        *refreshed_at = Some(Instant::now());
+        true
+    }
+
+    /// Fetch the keys in the background, so a rotated-in key is picked up before
+    /// the next periodic refresh
+    fn refresh_for_unknown_key(self: &Arc<Self>) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        if !self.claim_unknown_key_refresh() {
+            return;
+        }
+        let validator = self.clone();
+        handle.spawn(async move {
+            if let Err(e) = validator.refresh().await {
+                tracing::warn!("Failed to fetch JWT signing keys: {}", e);
+            }
+        });
+    }
+
+    /// Whether a bearer token has the shape of a JWT
+    pub fn looks_like_jwt(token: &str) -> bool {
+        token.split('.').count() == 3
+    }
+
+    /// Check a JWT's signature, issuer, audience and expiry, and map its claims
+    pub fn validate(self: &Arc<Self>, token: &str) -> Result<JwtGrant, String> {
+        let header =
+            jsonwebtoken::decode_header(token).map_err(|e| format!("Invalid token: {}", e))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(format!(
+                "Invalid token: {:?} signatures are not accepted",
+                header.alg
+            ));
+        }
+        let key = {
+            let keys = self.keys.read().unwrap();
+            let jwk = match &header.kid {
+                Some(kid) => keys.find(kid),
+                None if keys.keys.len() == 1 => keys.keys.first(),
+                None => None,
+            };
+            match jwk {
+                Some(jwk) => {
+                    DecodingKey::from_jwk(jwk).map_err(|e| format!("Invalid token: {}", e))?
+                }
+                None => {
+                    drop(keys);
+                    self.refresh_for_unknown_key();
+                    return Err("Invalid token: unknown signing key".to_string());
+                }
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = jsonwebtoken::decode::<serde_json::Value>(token, &key, &validation)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => "Token expired".to_string(),
+                _ => format!("Invalid token: {}", e),
+            })?
+            .claims;
+
+        let strings = |name: &str| -> Vec<String> {
+            match claims.get(name) {
+                Some(serde_json::Value::Array(items)) => items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+                // Space-separated, like OAuth scopes
+                Some(serde_json::Value::String(s)) => {
+                    s.split_whitespace().map(str::to_string).collect()
+                }
+                _ => Vec::new(),
+            }
+        };
+        Ok(JwtGrant {
+            namespaces: strings(&self.namespaces_claim),
+            permissions: permissions_for_roles(&strings(&self.roles_claim)),
+            expires_at: claims
+                .get("exp")
+                .and_then(|v| v.as_i64())
+                .and_then(|exp| DateTime::from_timestamp(exp, 0)),
+        })
+    }
+}
+
+/// Permissions granted by JWT roles: `admin` grants everything, `writer` reading,
+/// writing and reasoning, `reader` the read scopes; a permission name (`delete`,
+/// `search`, ...) grants just that permission
+pub fn permissions_for_roles(roles: &[String]) -> NamespacePermission {
+    let mut p = NamespacePermission {
+        read: false,
+        write: false,
+        delete: false,
+        reason: false,
+        query: false,
+        search: false,
+        export: false,
+        admin: false,
+    };
+    for role in roles {
+        match role.as_str() {
+            "admin" => p = NamespacePermission::all(),
+            "writer" => {
+                (p.read, p.query, p.search, p.export) = (true, true, true, true);
+                (p.write, p.reason) = (true, true);
+            }
+            "reader" => (p.read, p.query, p.search, p.export) = (true, true, true, true),
+            "read" => p.read = true,
+            "write" => p.write = true,
+            "delete" => p.delete = true,
+            "reason" => p.reason = true,
+            "query" => p.query = true,
+            "search" => p.search = true,
+            "export" => p.export = true,
+            _ => {}
+        }
+    }
+    p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::NamespaceAuth;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn sign(claims: serde_json::Value) -> String {
+        let key = std::fs::read("tests/fixtures/jwt/private.pem").unwrap();
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("test-key".into());
+        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_rsa_pem(&key).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_jwt_claims_grant_namespace_permissions() {
+        // Issuers such as Auth0 end theirs with a slash
+        let validator = JwtValidator::new("https://idp.test/", Some("synapse".into()), None);
+        let jwks = std::fs::read_to_string("tests/fixtures/jwt/jwks.json").unwrap();
+        validator.set_keys(serde_json::from_str(&jwks).unwrap());
+        let auth = NamespaceAuth::new();
+        auth.set_jwt_validator(Arc::new(validator));
+
+        let exp = Utc::now().timestamp() + 3600;
+        let token = sign(serde_json::json!({
+            "iss": "https://idp.test/",
+            "aud": "synapse",
+            "exp": exp,
+            "namespaces": ["research-*"],
+            "roles": "reader delete",
+        }));
+        assert!(auth.check(Some(&token), "research-ai", "search").is_ok());
+        assert!(auth.check(Some(&token), "research-ai", "delete").is_ok());
+        assert_eq!(
+            auth.check(Some(&token), "research-ai", "write"),
+            Err("Write permission denied".to_string())
+        );
+        assert!(auth.check(Some(&token), "books", "read").is_err());
+
+        let expired = sign(serde_json::json!({
+            "iss": "https://idp.test/",
+            "aud": "synapse",
+            "exp": Utc::now().timestamp() - 3600,
+            "namespaces": ["*"],
+            "roles": ["admin"],
+        }));
+        assert_eq!(
+            auth.check(Some(&expired), "books", "read"),
+            Err("Token expired".to_string())
+        );
+
+        for issuer in ["https://other.test/", "https://idp.test"] {
+            let foreign = sign(serde_json::json!({
+                "iss": issuer,
+                "aud": "synapse",
+                "exp": exp,
+                "namespaces": ["*"],
+                "roles": ["admin"],
+            }));
+            let err = auth.check(Some(&foreign), "books", "read").unwrap_err();
+            assert!(err.starts_with("Invalid token"), "{}", err);
+        }
+        assert!(auth
+            .check(Some("a.b.c"), "books", "read")
+            .unwrap_err()
+            .starts_with("Invalid token"));
+    }
+
+    #[test]
+    fn test_unknown_keys_trigger_rate_limited_refreshes() {
+        let validator = JwtValidator::new("https://idp.test/", None, None);
+        assert!(validator.claim_unknown_key_refresh());
+        assert!(!validator.claim_unknown_key_refresh());
+        *validator.unknown_key_refreshed_at.lock().unwrap() =
+            Some(Instant::now() - JWKS_UNKNOWN_KEY_REFRESH_INTERVAL);
+        assert!(validator.claim_unknown_key_refresh());
+    }
+}
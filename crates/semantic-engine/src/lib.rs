@@ -1,13 +1,40 @@
+pub mod analytics;
 pub mod audit;
 pub mod auth;
+pub mod changes;
+pub mod cli;
+pub mod config;
 pub mod disambiguation;
+pub mod drain;
 pub mod ingest;
+pub mod jobs;
+pub mod jwt;
+pub mod lockout;
+pub mod logging;
+pub mod lru;
+pub mod maintenance;
+pub mod mcp;
 pub mod mcp_stdio;
 pub mod mcp_types;
+pub mod memory;
+pub mod metrics;
+pub mod network;
 pub mod persistence;
 pub mod processor;
+pub mod properties;
+pub mod quota;
+pub mod ratelimit;
 pub mod reasoner;
+#[cfg(feature = "object-store")]
+pub mod remote;
+pub mod sampling;
 pub mod scenarios;
+pub mod schedule;
 pub mod server;
+pub mod snapshot;
 pub mod store;
+pub mod tls;
+pub mod topology;
 pub mod vector_store;
+pub mod visualize;
+pub mod watcher;
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// When repeated authentication failures lock a peer or token out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LockoutPolicy {
+    /// Failures allowed before the first lockout
+    pub max_failures: u32,
+    /// First lockout; each further failure doubles it
+    pub base: Duration,
+    /// Longest lockout; failures older than this are forgotten
+    pub max: Duration,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+impl LockoutPolicy {
+    /// Parse `failures:base_secs:max_secs`, e.g. `5:1:900`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut parts = spec.trim().split(':').map(|p| p.trim().parse::<u64>().ok());
+        let (Some(Some(failures)), Some(Some(base)), Some(Some(max)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        (failures > 0 && base > 0 && max >= base).then(|| Self {
+            max_failures: failures as u32,
+            base: Duration::from_secs(base),
+            max: Duration::from_secs(max),
+        })
+    }
+
+    /// Policy from `SYNAPSE_AUTH_LOCKOUT` (`failures:base_secs:max_secs`, or `off`);
+    /// the default when unset or invalid
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("SYNAPSE_AUTH_LOCKOUT") {
+            Ok(spec) if spec.trim() == "off" => None,
+            Ok(spec) => Some(Self::parse(&spec).unwrap_or_else(|| {
+                tracing::warn!("Ignoring invalid SYNAPSE_AUTH_LOCKOUT: {}", spec);
+                Self::default()
+            })),
+            Err(_) => Some(Self::default()),
+        }
+    }
+
+    /// Lockout after the `failures`-th failure in a row, if any
+    fn lockout(&self, failures: u32) -> Option<Duration> {
+        let over = failures.checked_sub(self.max_failures)?;
+        Some(self.base.saturating_mul(1 << over.min(20)).min(self.max))
+    }
+}
+
+struct Failures {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Failed authentication attempts per key (`peer:<ip>`, `token:<fingerprint>`),
+/// with exponential lockout once a key fails too often
+pub struct AuthLockout {
+    policy: RwLock<Option<LockoutPolicy>>,
+    failures: Mutex<HashMap<String, Failures>>,
+    failures_total: AtomicU64,
+    lockouts_total: AtomicU64,
+}
+
+impl Default for AuthLockout {
+    fn default() -> Self {
+        Self::new(Some(LockoutPolicy::default()))
+    }
+}
+
+impl AuthLockout {
+    pub fn new(policy: Option<LockoutPolicy>) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            failures: Mutex::new(HashMap::new()),
+            failures_total: AtomicU64::new(0),
+            lockouts_total: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the policy; `None` stops locking anyone out
+    pub fn set_policy(&self, policy: Option<LockoutPolicy>) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    /// Refuse keys that are locked out
+    pub fn check(&self, keys: &[String]) -> Result<(), String> {
+        if self.policy.read().unwrap().is_none() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let failures = self.failures.lock().unwrap();
+        let remaining = keys
+            .iter()
+            .filter_map(|key| failures.get(key)?.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+            .max();
+        match remaining {
+            Some(remaining) => Err(format!(
+                "Too many failed authentication attempts; retry in {} s",
+                remaining.as_secs().max(1)
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Count a failed attempt against each key, logging it as an audit event
+    pub fn record_failure(&self, keys: &[String], reason: &str) {
+        self.failures_total.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(
+            target: "audit",
+            keys = %keys.join(","),
+            reason,
+            "Authentication failure"
+        );
+        let Some(policy) = *self.policy.read().unwrap() else {
+            return;
+        };
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        // Forget keys that have been quiet for longer than the longest lockout
+        failures.retain(|_, f| now.duration_since(f.last) < policy.max);
+        for key in keys {
+            let entry = failures.entry(key.clone()).or_insert(Failures {
+                count: 0,
+                last: now,
+                locked_until: None,
+            });
+            entry.count += 1;
+            entry.last = now;
+            if let Some(lockout) = policy.lockout(entry.count) {
+                entry.locked_until = Some(now + lockout);
+                self.lockouts_total.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    "Locking out {} for {} s after {} failed authentication attempts",
+                    key,
+                    lockout.as_secs(),
+                    entry.count
+                );
+            }
+        }
+    }
+
+    /// Clear the failures of a key that authenticated
+    pub fn record_success(&self, key: &str) {
+        self.failures.lock().unwrap().remove(key);
+    }
+
+    /// Counters in the Prometheus text format
+    pub fn render(&self) -> String {
+        let now = Instant::now();
+        let locked = self
+            .failures
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|f| f.locked_until.is_some_and(|until| until > now))
+            .count();
+        format!(
+            "# HELP synapse_auth_failures_total Failed authentication attempts\n\
+             # TYPE synapse_auth_failures_total counter\n\
+             synapse_auth_failures_total {}\n\
+             # HELP synapse_auth_lockouts_total Peers or tokens locked out after repeated failures\n\
+             # TYPE synapse_auth_lockouts_total counter\n\
+             synapse_auth_lockouts_total {}\n\
+             # HELP synapse_auth_locked_out Peers and tokens currently locked out\n\
+             # TYPE synapse_auth_locked_out gauge\n\
+             synapse_auth_locked_out {}\n",
+            self.failures_total.load(Ordering::Relaxed),
+            self.lockouts_total.load(Ordering::Relaxed),
+            locked
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_grows_exponentially() {
+        let policy = LockoutPolicy::parse("3:2:10").unwrap();
+        assert_eq!(policy.lockout(2), None);
+        assert_eq!(policy.lockout(3), Some(Duration::from_secs(2)));
+        assert_eq!(policy.lockout(4), Some(Duration::from_secs(4)));
+        assert_eq!(policy.lockout(6), Some(Duration::from_secs(10)));
+        assert_eq!(policy.lockout(200), Some(Duration::from_secs(10)));
+        assert!(LockoutPolicy::parse("3:20:10").is_none());
+        assert!(LockoutPolicy::parse("3:2").is_none());
+
+        let lockout = AuthLockout::new(Some(policy));
+        let keys = vec!["peer:10.0.0.1".to_string(), "token:abc".to_string()];
+        lockout.record_failure(&keys, "Invalid token");
+        lockout.record_failure(&keys, "Invalid token");
+        assert!(lockout.check(&keys).is_ok());
+        lockout.record_failure(&keys, "Invalid token");
+        assert!(lockout.check(&keys[..1]).is_err());
+        // A token that authenticates is cleared; the peer stays locked out
+        lockout.record_success("token:abc");
+        assert!(lockout.check(&keys[1..]).is_ok());
+        assert!(lockout.check(&keys).is_err());
+        assert!(lockout.render().contains("synapse_auth_lockouts_total 2\n"));
+
+        lockout.set_policy(None);
+        assert!(lockout.check(&keys).is_ok());
+    }
+}
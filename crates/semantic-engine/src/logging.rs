@@ -0,0 +1,66 @@
+//! Log output for the `synapse` binary. Levels and per-module filters come from
+//! `RUST_LOG` (default `info`), the format from `SYNAPSE_LOG_FORMAT`. Logs always
+//! go to stderr: in MCP mode stdout carries nothing but JSON-RPC.
+
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when `RUST_LOG` is unset or invalid
+const DEFAULT_FILTER: &str = "info";
+
+/// How log lines are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, for log shippers
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "text" | "" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Install the global subscriber. Does nothing if one is already installed.
+pub fn init() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let requested = std::env::var("SYNAPSE_LOG_FORMAT").ok();
+    let format = requested
+        .as_deref()
+        .map_or(Some(LogFormat::Text), LogFormat::parse);
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    let installed = match format.unwrap_or_default() {
+        LogFormat::Text => builder.with_ansi(false).try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+
+    if installed.is_ok() && format.is_none() {
+        tracing::warn!(
+            "Ignoring invalid SYNAPSE_LOG_FORMAT: {}",
+            requested.unwrap_or_default()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse(" JSON "), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse("text"), Some(LogFormat::Text));
+        assert_eq!(LogFormat::parse("xml"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Recency of open namespace stores, so the engine can close the least recently
+//! used ones once more than `SYNAPSE_MAX_OPEN_NAMESPACES` are open.
+
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// When each open namespace was last used, by a logical clock
+pub struct StoreLru {
+    capacity: Option<usize>,
+    clock: AtomicU64,
+    last_used: DashMap<String, u64>,
+    evicting: Mutex<()>,
+    eviction_scheduled: AtomicBool,
+    open_close: DashMap<String, Arc<tokio::sync::Mutex<()>>>,
+}
+
+impl Default for StoreLru {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl StoreLru {
+    /// `None` keeps every namespace open
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            clock: AtomicU64::new(0),
+            last_used: DashMap::new(),
+            evicting: Mutex::new(()),
+            eviction_scheduled: AtomicBool::new(false),
+            open_close: DashMap::new(),
+        }
+    }
+
+    /// Cap from `SYNAPSE_MAX_OPEN_NAMESPACES`; unlimited when unset, `0` or invalid
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("SYNAPSE_MAX_OPEN_NAMESPACES")
+            .ok()
+            .and_then(|value| match value.trim().parse::<usize>() {
+                Ok(n) => Some(n).filter(|n| *n > 0),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid SYNAPSE_MAX_OPEN_NAMESPACES: {}", value);
+                    None
+                }
+            });
+        Self::new(capacity)
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Record a use of a namespace
+    pub fn touch(&self, namespace: &str) {
+        let now = self.clock.fetch_add(1, Ordering::Relaxed);
+        self.last_used.insert(namespace.to_string(), now);
+    }
+
+    pub fn forget(&self, namespace: &str) {
+        self.last_used.remove(namespace);
+    }
+
+    /// Held while closing stores, so concurrent evictions do not both close one
+    /// store each to get under the cap
+    pub fn evicting(&self) -> MutexGuard<'_, ()> {
+        self.evicting.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Claim the background eviction; false while one is already scheduled or
+    /// running. Release it with `eviction_finished`.
+    pub fn schedule_eviction(&self) -> bool {
+        self.eviction_scheduled
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    pub fn eviction_finished(&self) {
+        self.eviction_scheduled.store(false, Ordering::Release);
+    }
+
+    /// Serializes opening a namespace with closing it, so a store is never
+    /// opened twice or reopened while the handle being closed still holds its
+    /// files. Async so openers wait without blocking a runtime worker.
+    pub fn open_close_lock(&self, namespace: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.open_close
+            .entry(namespace.to_string())
+            .or_default()
+            .value()
+            .clone()
+    }
+
+    /// Namespaces to close, least recently used first, for `open` stores to fit
+    /// the cap. Callers skip the ones still in use.
+    pub fn candidates(&self, open: usize) -> Vec<String> {
+        match self.capacity {
+            Some(capacity) if open > capacity => {
+                let mut namespaces: Vec<(u64, String)> = self
+                    .last_used
+                    .iter()
+                    .map(|entry| (*entry.value(), entry.key().clone()))
+                    .collect();
+                namespaces.sort();
+                namespaces
+                    .into_iter()
+                    .map(|(_, namespace)| namespace)
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_are_least_recently_used_first() {
+        let lru = StoreLru::new(Some(2));
+        lru.touch("a");
+        lru.touch("b");
+        lru.touch("c");
+        lru.touch("a");
+        assert!(lru.candidates(2).is_empty());
+        assert_eq!(lru.candidates(3), vec!["b", "c", "a"]);
+        lru.forget("b");
+        assert_eq!(lru.candidates(3), vec!["c", "a"]);
+        assert!(StoreLru::default().candidates(100).is_empty());
+    }
+
+    #[test]
+    fn test_only_one_eviction_is_scheduled_at_a_time() {
+        let lru = StoreLru::new(Some(1));
+        assert!(lru.schedule_eviction());
+        assert!(!lru.schedule_eviction());
+        lru.eviction_finished();
+        assert!(lru.schedule_eviction());
+        assert!(Arc::ptr_eq(
+            &lru.open_close_lock("a"),
+            &lru.open_close_lock("a")
+        ));
+    }
+}
@@ -1,33 +1,132 @@
+use clap::Parser;
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
+use synapse_core::auth::AnonymousPolicy;
+use synapse_core::cli::{self, Cli, Command};
+use synapse_core::config::SynapseConfig;
+use synapse_core::drain::DrainLayer;
+use synapse_core::maintenance::MaintenancePolicy;
+use synapse_core::metrics::MetricsLayer;
+use synapse_core::schedule::SnapshotSchedule;
 use synapse_core::server::{
-    proto::semantic_engine_server::SemanticEngineServer, run_mcp_stdio, MySemanticEngine,
+    certificate_interceptor, run_mcp_stdio, MySemanticEngine, TransportConfig,
 };
+use synapse_core::watcher::parse_watch_specs;
+use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Server;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    let is_mcp = args.contains(&"--mcp".to_string());
+/// gRPC address unless `SYNAPSE_LISTEN_ADDR` is set
+const DEFAULT_LISTEN_ADDR: &str = "[::1]:50051";
 
+/// How long in-flight requests and jobs may run after a shutdown signal
+const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_secs(30);
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let command = cli.command();
+
+    // The file only fills in variables that are unset, so the environment wins.
+    // Exported while the process is still single-threaded.
+    let config = match SynapseConfig::locate(cli.config.as_deref()) {
+        Some(path) => {
+            let applied = SynapseConfig::load(&path)?.apply_env();
+            Some((path, applied.len()))
+        }
+        None => None,
+    };
+    // Before anything logs; everything goes to stderr, so MCP's stdout stays clean
+    synapse_core::logging::init();
+    if let Some((path, applied)) = config {
+        tracing::info!(
+            "Loaded configuration from {} ({} settings not overridden by the environment)",
+            path.display(),
+            applied
+        );
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(command))
+}
+
+async fn run(command: Command) -> Result<(), Box<dyn std::error::Error>> {
     // Get storage path from env or default
     let storage_path = env::var("GRAPH_STORAGE_PATH").unwrap_or_else(|_| "data/graphs".to_string());
 
     let engine = MySemanticEngine::new(&storage_path);
 
+    // Fetch namespaces this machine has no copy of before anything opens them
+    #[cfg(feature = "object-store")]
+    match engine.restore_from_remote().await {
+        Ok(restored) if !restored.is_empty() => {
+            tracing::info!(
+                "Restored namespaces from object store: {}",
+                restored.join(", ")
+            )
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to restore from object store: {}", e),
+    }
+
+    if !command.is_server() {
+        // Whoever can run this can read and write the storage files anyway
+        engine
+            .auth
+            .set_anonymous_policy(AnonymousPolicy::unrestricted());
+        let result = cli::run(&engine, command, &mut std::io::stdout().lock()).await;
+        // Flush whatever the command changed
+        engine.shutdown().await;
+        return Ok(result?);
+    }
+
+    if let Some(schedule) = SnapshotSchedule::from_env() {
+        engine.spawn_snapshot_scheduler(schedule);
+    }
+    if let Some(policy) = MaintenancePolicy::from_env() {
+        engine.spawn_maintenance_scheduler(policy);
+    }
+
     // Ensure 'core' scenario is installed on startup (backgrounded for MCP performance)
     let engine_init = engine.clone();
     tokio::spawn(async move {
         match engine_init.install_scenario("core", "default").await {
-            Ok(msg) => eprintln!("{}", msg),
-            Err(e) => eprintln!("Failed to load core scenario: {}", e),
+            Ok(msg) => tracing::info!("{}", msg),
+            Err(e) => tracing::warn!("Failed to load core scenario: {}", e),
         }
     });
 
-    if is_mcp {
+    // Watch folders configured as SYNAPSE_WATCH="namespace=/path,other=/path2"
+    if let Ok(spec) = env::var("SYNAPSE_WATCH") {
+        for config in parse_watch_specs(&spec) {
+            let path = config.path.display().to_string();
+            match engine.start_watch(config).await {
+                Ok(()) => tracing::info!("Watching {} for new files", path),
+                Err(e) => tracing::warn!("Failed to watch {}: {}", path, e.message()),
+            }
+        }
+    }
+
+    let drain_deadline = env::var("SYNAPSE_SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_DRAIN_DEADLINE);
+
+    if command == Command::Mcp {
         // MCP mode: no stdout messages, only JSON-RPC
-        eprintln!("Synapse-MCP starting (stdio mode)...");
-        run_mcp_stdio(Arc::new(engine)).await?;
+        tracing::info!("Synapse-MCP starting (stdio mode)...");
+        let engine = Arc::new(engine);
+        tokio::select! {
+            // Flushes every namespace itself once stdin closes
+            result = run_mcp_stdio(engine.clone()) => result?,
+            result = shutdown_signal() => {
+                result?;
+                tracing::info!("Shutting down Synapse...");
+                engine.graceful_shutdown(drain_deadline).await;
+            }
+        }
     } else {
         println!(
             r#"
@@ -39,25 +138,85 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
      \/ \/         \/     \/|__|       \/     \/
 "#
         );
-        let addr = "[::1]:50051".parse()?;
-        println!("🚀 Synapse (ex-Grafoso) listening on {}", addr);
-        println!("Storage Path: {}", storage_path);
-
-        let engine_clone = engine.clone();
-
-        Server::builder()
-            .add_service(SemanticEngineServer::with_interceptor(
-                engine,
-                synapse_core::server::auth_interceptor,
-            ))
-            .serve_with_shutdown(addr, async move {
-                if tokio::signal::ctrl_c().await.is_ok() {
-                    println!("\nShutting down Synapse...");
+        let addr: std::net::SocketAddr = env::var("SYNAPSE_LISTEN_ADDR")
+            .unwrap_or_else(|_| DEFAULT_LISTEN_ADDR.to_string())
+            .parse()?;
+        tracing::info!("🚀 Synapse (ex-Grafoso) listening on {}", addr);
+        tracing::info!("Storage Path: {}", storage_path);
+        // Reachable from the network: anonymous callers get nothing unless configured
+        if !addr.ip().is_loopback() && env::var("SYNAPSE_ANONYMOUS").is_err() {
+            engine.auth.set_anonymous_policy(AnonymousPolicy::none());
+            tracing::info!("Anonymous access disabled (set SYNAPSE_ANONYMOUS to allow it)");
+        }
+
+        let transport = TransportConfig::from_env();
+        tracing::info!(
+            "Max message size: {} bytes in, {} bytes out",
+            transport.max_decoding_message_size,
+            transport.max_encoding_message_size
+        );
+
+        let mut builder = Server::builder();
+        if let Some(tls) = synapse_core::tls::server_tls_from_env()? {
+            tracing::info!(
+                "TLS enabled{}",
+                if env::var("SYNAPSE_TLS_CLIENT_CA").is_ok() {
+                    ", client certificates required"
+                } else {
+                    ""
                 }
-                engine_clone.shutdown().await;
-            })
-            .await?;
+            );
+            builder = builder.tls_config(tls)?;
+        }
+
+        let (stop_accepting, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            builder
+                .layer(DrainLayer::new(engine.drain.clone()))
+                .layer(MetricsLayer::new(engine.metrics.clone()))
+                .add_service(InterceptedService::new(
+                    transport.service(engine.clone()),
+                    certificate_interceptor(engine.auth.clone()),
+                ))
+                .serve_with_shutdown(addr, async {
+                    let _ = stopped.await;
+                }),
+        );
+
+        let outcome: anyhow::Result<()> = tokio::select! {
+            result = shutdown_signal() => result.map_err(Into::into),
+            // The server only ends on its own when it fails
+            result = server => match result {
+                Ok(served) => served.map_err(Into::into),
+                Err(e) => Err(e.into()),
+            },
+        };
+        if let Err(e) = &outcome {
+            tracing::error!("Server failed: {}", e);
+        }
+        tracing::info!("Shutting down Synapse...");
+        // Stop accepting connections, let in-flight calls finish, then flush, even
+        // when the server failed
+        let _ = stop_accepting.send(());
+        engine.graceful_shutdown(drain_deadline).await;
+        outcome?;
     }
 
     Ok(())
 }
+
+/// Resolves on Ctrl-C, or on SIGTERM where there is one, which is how service
+/// managers and container runtimes stop the process
+async fn shutdown_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
@@ -0,0 +1,145 @@
+//! Periodic upkeep of open namespaces: compaction once enough of a vector index
+//! is stale, an optimize pass over the graph storage, and a rebuild of the
+//! topology mirror that degree scoring reads.
+
+use crate::auth::namespace_matches;
+use crate::schedule::SnapshotSchedule;
+use rand::Rng;
+use std::time::Duration;
+
+/// Hourly unless `SYNAPSE_MAINTENANCE_SCHEDULE` says otherwise
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Share of stale embeddings at which a vector index is compacted
+const DEFAULT_STALE_RATIO: f64 = 0.2;
+
+/// Each wait is the interval give or take this share of it, so instances started
+/// together do not all compact at once
+const JITTER: f64 = 0.1;
+
+/// When maintenance runs, and on what
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenancePolicy {
+    pub schedule: SnapshotSchedule,
+    pub stale_ratio: f64,
+    /// Namespace patterns (`name`, `prefix*` or `*`) left alone
+    pub skip: Vec<String>,
+}
+
+impl Default for MaintenancePolicy {
+    fn default() -> Self {
+        Self {
+            schedule: SnapshotSchedule {
+                interval: DEFAULT_INTERVAL,
+                windows: Vec::new(),
+            },
+            stale_ratio: DEFAULT_STALE_RATIO,
+            skip: Vec::new(),
+        }
+    }
+}
+
+impl MaintenancePolicy {
+    /// Policy from `SYNAPSE_MAINTENANCE_SCHEDULE` (as `SYNAPSE_SNAPSHOT_SCHEDULE`,
+    /// `off` disables), `SYNAPSE_MAINTENANCE_STALE_RATIO` and
+    /// `SYNAPSE_MAINTENANCE_SKIP` (comma-separated namespace patterns)
+    pub fn from_env() -> Option<Self> {
+        let default = Self::default();
+        let schedule =
+            SnapshotSchedule::from_env_var("SYNAPSE_MAINTENANCE_SCHEDULE", default.schedule)?;
+        let stale_ratio = match std::env::var("SYNAPSE_MAINTENANCE_STALE_RATIO") {
+            Ok(value) => Self::parse_ratio(&value).unwrap_or_else(|| {
+                tracing::warn!(
+                    "Ignoring invalid SYNAPSE_MAINTENANCE_STALE_RATIO: {}",
+                    value
+                );
+                default.stale_ratio
+            }),
+            Err(_) => default.stale_ratio,
+        };
+        let skip = std::env::var("SYNAPSE_MAINTENANCE_SKIP")
+            .map(|value| Self::parse_skip(&value))
+            .unwrap_or_default();
+        Some(Self {
+            schedule,
+            stale_ratio,
+            skip,
+        })
+    }
+
+    fn parse_ratio(value: &str) -> Option<f64> {
+        value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|r| (0.0..=1.0).contains(r))
+    }
+
+    fn parse_skip(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Whether `namespace` opted out of maintenance
+    pub fn skips(&self, namespace: &str) -> bool {
+        self.skip
+            .iter()
+            .any(|pattern| namespace_matches(pattern, namespace))
+    }
+
+    /// Whether a vector index with `active` live and `stale` dropped embeddings is
+    /// worth compacting
+    pub fn needs_compaction(&self, active: usize, stale: usize) -> bool {
+        let total = active + stale;
+        stale > 0 && stale as f64 >= self.stale_ratio * total as f64
+    }
+
+    /// How long to wait before the next run
+    pub fn next_delay(&self) -> Duration {
+        let factor = rand::rng().random_range(1.0 - JITTER..=1.0 + JITTER);
+        self.schedule.interval.mul_f64(factor)
+    }
+}
+
+/// What one maintenance pass did to a namespace
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    /// Stale embeddings dropped; `None` when the index was not compacted
+    pub vectors_removed: Option<usize>,
+    pub elapsed_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_parsing_and_decisions() {
+        assert_eq!(MaintenancePolicy::parse_ratio("0.5"), Some(0.5));
+        assert_eq!(MaintenancePolicy::parse_ratio("1.5"), None);
+        assert_eq!(MaintenancePolicy::parse_ratio("x"), None);
+
+        let policy = MaintenancePolicy {
+            skip: MaintenancePolicy::parse_skip(" archive , tenant-*,"),
+            ..Default::default()
+        };
+        assert_eq!(policy.skip, vec!["archive", "tenant-*"]);
+        assert!(policy.skips("archive"));
+        assert!(policy.skips("tenant-a"));
+        assert!(!policy.skips("default"));
+
+        assert!(!policy.needs_compaction(100, 0));
+        assert!(!policy.needs_compaction(100, 10));
+        assert!(policy.needs_compaction(80, 20));
+        assert!(policy.needs_compaction(0, 1));
+
+        for _ in 0..100 {
+            let delay = policy.next_delay();
+            assert!(delay >= Duration::from_secs(3240) && delay <= Duration::from_secs(3960));
+        }
+    }
+}
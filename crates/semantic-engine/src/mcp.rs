@@ -0,0 +1,126 @@
+//! In-process MCP adapter: the agent-facing knowledge graph tools as plain async
+//! methods over `MySemanticEngine`, for embedders that don't need the stdio transport.
+
+use crate::ingest::{IngestionEngine, TextIngestOptions};
+use crate::mcp_types::{IngestToolResult, SearchResultItem, SearchToolResult};
+use crate::server::proto::semantic_engine_server::SemanticEngine;
+use crate::server::proto::{HybridSearchRequest, SearchMode};
+use crate::server::{AuthToken, MySemanticEngine};
+use anyhow::Result;
+use std::sync::Arc;
+use tonic::{Request, Status};
+
+pub struct McpServer {
+    engine: Arc<MySemanticEngine>,
+    namespace: String,
+    token: Option<String>,
+}
+
+impl McpServer {
+    /// An adapter over the `default` namespace, called anonymously
+    pub fn new(engine: Arc<MySemanticEngine>) -> Self {
+        Self {
+            engine,
+            namespace: "default".to_string(),
+            token: None,
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = namespace.to_string();
+        self
+    }
+
+    /// Token checked against the engine's `NamespaceAuth` on every call
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Hybrid (vector + graph) search, returning at most `top_k` results
+    pub async fn query_knowledge_graph(&self, query: &str, top_k: u32) -> Result<SearchToolResult> {
+        let request = self.request(HybridSearchRequest {
+            query: query.to_string(),
+            namespace: self.namespace.clone(),
+            vector_k: top_k.max(1),
+            graph_depth: 1,
+            mode: SearchMode::Hybrid as i32,
+            limit: top_k,
+            min_score: 0.0,
+            rerank: false,
+            pagerank_weight: 0.0,
+        });
+        let results = self
+            .engine
+            .hybrid_search(request)
+            .await
+            .map_err(status_error)?
+            .into_inner()
+            .results;
+
+        Ok(SearchToolResult {
+            results: results
+                .into_iter()
+                .map(|r| SearchResultItem {
+                    node_id: r.node_id,
+                    score: r.score,
+                    content: r.content,
+                    uri: r.uri,
+                })
+                .collect(),
+        })
+    }
+
+    /// Record a piece of text as an observation node (`urn:observation:<uuid>`) with
+    /// its source, and index it for search
+    pub async fn add_observation(&self, text: &str, source: &str) -> Result<IngestToolResult> {
+        self.engine
+            .auth
+            .check(self.token.as_deref(), &self.namespace, "write")
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.engine
+            .rate_limiter
+            .check(self.token.as_deref(), &self.namespace)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let store = self
+            .engine
+            .get_store(&self.namespace)
+            .await
+            .map_err(status_error)?;
+
+        let uri = format!("urn:observation:{}", uuid::Uuid::new_v4());
+        let mut options = TextIngestOptions::default();
+        options
+            .metadata
+            .insert("source".to_string(), serde_json::json!(source));
+        let report = IngestionEngine::new(store)
+            .ingest_text(&uri, text, &options, &self.namespace)
+            .await?;
+
+        Ok(IngestToolResult {
+            nodes_added: 1,
+            edges_added: report.added,
+            message: format!(
+                "Recorded observation {} ({} chunks indexed)",
+                uri, report.vectors_created
+            ),
+            report: None,
+        })
+    }
+
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(token) = &self.token {
+            request.extensions_mut().insert(AuthToken(token.clone()));
+        }
+        request
+    }
+}
+
+fn status_error(status: Status) -> anyhow::Error {
+    anyhow::anyhow!(status.message().to_string())
+}
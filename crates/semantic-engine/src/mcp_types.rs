@@ -19,6 +19,13 @@ pub struct McpResponse {
     pub error: Option<McpError>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct McpNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct McpError {
     pub code: i32,
@@ -62,6 +69,17 @@ pub struct IngestToolResult {
     pub nodes_added: u32,
     pub edges_added: u32,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<IngestReportItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IngestReportItem {
+    pub added: u32,
+    pub duplicates_skipped: u32,
+    pub malformed: u32,
+    pub vectors_created: u32,
+    pub elapsed_ms: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -77,11 +95,21 @@ pub struct SearchToolResult {
     pub results: Vec<SearchResultItem>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AskGraphToolResult {
+    pub question: String,
+    pub sparql: String,
+    /// Query solutions, omitted when the query was not executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<serde_json::Value>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NeighborItem {
     pub direction: String,
     pub predicate: String,
     pub target: String,
+    pub depth: u32,
     pub score: f32,
 }
 
@@ -100,6 +128,135 @@ pub struct TripleItem {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TriplesToolResult {
     pub triples: Vec<TripleItem>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FileFailureItem {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DirectoryToolResult {
+    pub files_total: u32,
+    pub files_ingested: u32,
+    pub files_failed: u32,
+    pub triples_added: u32,
+    pub failures: Vec<FileFailureItem>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FeedToolResult {
+    pub items_ingested: u32,
+    pub triples_added: u32,
+    pub chunks_indexed: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApiSyncToolResult {
+    pub pages: u32,
+    pub items: u32,
+    pub triples_added: u32,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SqlToolResult {
+    pub tables: u32,
+    pub rows: u32,
+    pub triples_added: u32,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PipelineToolResult {
+    pub namespace: String,
+    /// Custom stages in run order, between provenance enrichment and validation
+    pub stages: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NamespaceItem {
+    pub name: String,
+    /// Stored quads, including provenance metadata
+    pub triples: usize,
+    pub vectors: usize,
+    /// RFC 3339; absent for a namespace that was never written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NamespacesToolResult {
+    pub namespaces: Vec<NamespaceItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VisualizeToolResult {
+    pub format: String,
+    pub nodes: usize,
+    pub edges: usize,
+    /// True when the node limit cut the traversal short
+    pub truncated: bool,
+    pub diagram: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportToolResult {
+    pub format: String,
+    pub triples: usize,
+    pub bytes: usize,
+    /// Set when the export was written to a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The serialized graph, when returned inline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProvenanceToolResult {
+    pub subject: String,
+    pub records: Vec<ProvenanceItem>,
+}
+
+/// One ingestion batch that contributed facts about the subject
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProvenanceItem {
+    pub graph: String,
+    pub source: String,
+    pub timestamp: String,
+    pub extractor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<crate::store::SourceSpan>,
+    pub facts: Vec<TripleItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WatchItem {
+    pub namespace: String,
+    pub path: String,
+    pub exclude: Vec<String>,
+    pub debounce_ms: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WatchToolResult {
+    pub success: bool,
+    pub message: String,
+    pub watches: Vec<WatchItem>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -107,6 +264,36 @@ pub struct ReasoningToolResult {
     pub success: bool,
     pub triples_inferred: u32,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rule_stats: Vec<RuleStatsItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RuleStatsItem {
+    pub rule: String,
+    pub triples_inferred: u32,
+    pub duration_ms: u64,
+    pub samples: Vec<TripleItem>,
+}
+
+/// `apply_reasoning` with `preview`: nothing is stored
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReasoningPreviewToolResult {
+    pub triples_inferred: u32,
+    pub sample: Vec<TripleItem>,
+    pub rule_stats: Vec<RuleStatsItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReasoningStatusToolResult {
+    pub job_id: String,
+    pub namespace: String,
+    pub state: String,
+    pub percent_complete: f32,
+    pub triples_inferred: u32,
+    pub message: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -122,6 +309,15 @@ pub struct StatsToolResult {
     pub total_embeddings: usize,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PathToolResult {
+    pub found: bool,
+    /// From start to end, as plain IRIs (blank nodes as `_:id`)
+    pub nodes: Vec<String>,
+    /// `edges[i]` links `nodes[i]` and `nodes[i + 1]`, as stated in the graph
+    pub edges: Vec<TripleItem>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DegreeResult {
     pub uri: String,
@@ -0,0 +1,87 @@
+//! Rough heap accounting for a namespace's in-memory structures, so operators can
+//! tell which namespace holds the RAM. Sizes count allocated capacity plus a
+//! per-entry estimate for map overhead; they are not allocator measurements.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+
+/// Estimated heap bytes of one namespace, by structure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Adjacency lists of the topology mirror
+    pub topology: usize,
+    /// Property columns and their value indexes
+    pub properties: usize,
+    /// Embeddings, the HNSW index and vector metadata
+    pub vectors: usize,
+    /// Node ID <-> URI maps
+    pub uri_mappings: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize {
+        self.topology + self.properties + self.vectors + self.uri_mappings
+    }
+
+    /// `(component, bytes)` pairs, as labelled in metrics
+    pub fn components(&self) -> [(&'static str, usize); 4] {
+        [
+            ("topology", self.topology),
+            ("properties", self.properties),
+            ("vectors", self.vectors),
+            ("uri_mappings", self.uri_mappings),
+        ]
+    }
+}
+
+pub fn string_bytes(s: &String) -> usize {
+    s.capacity()
+}
+
+pub fn vec_bytes<T>(v: &Vec<T>) -> usize {
+    v.capacity() * size_of::<T>()
+}
+
+/// Table slots plus one control byte each; keys and values that own heap memory
+/// are counted by the caller
+pub fn map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+/// Entries plus about half again for node slack
+pub fn btree_bytes<K, V>(map: &BTreeMap<K, V>) -> usize {
+    map.len() * size_of::<(K, V)>() * 3 / 2
+}
+
+/// Node ID <-> URI maps, with each URI counted once per map
+pub fn uri_map_bytes(uri_to_id: &HashMap<String, u32>, id_to_uri: &HashMap<u32, String>) -> usize {
+    map_bytes(uri_to_id)
+        + uri_to_id.keys().map(string_bytes).sum::<usize>()
+        + map_bytes(id_to_uri)
+        + id_to_uri.values().map(string_bytes).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_maps_grow_with_their_strings() {
+        let mut uri_to_id = HashMap::new();
+        let mut id_to_uri = HashMap::new();
+        let empty = uri_map_bytes(&uri_to_id, &id_to_uri);
+        let uri = "http://example.org/".repeat(10);
+        uri_to_id.insert(uri.clone(), 1);
+        id_to_uri.insert(1, uri.clone());
+        assert!(uri_map_bytes(&uri_to_id, &id_to_uri) >= empty + 2 * uri.len());
+
+        let usage = MemoryUsage {
+            topology: 1,
+            properties: 2,
+            vectors: 3,
+            uri_mappings: 4,
+        };
+        assert_eq!(usage.total(), 10);
+        assert_eq!(usage.components()[2], ("vectors", 3));
+    }
+}
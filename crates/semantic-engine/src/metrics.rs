@@ -0,0 +1,243 @@
+use crate::memory::MemoryUsage;
+use crate::store::DiskUsage;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tonic::codegen::http;
+use tower::{Layer, Service};
+
+/// Upper bounds (in seconds) of the latency histogram buckets
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+tokio::task_local! {
+    /// Namespace of the RPC being measured, filled in by the handler
+    static NAMESPACE: Arc<Mutex<Option<String>>>;
+}
+
+/// Label the RPC running on this task with its namespace (a no-op outside `MetricsLayer`)
+pub fn record_namespace(namespace: &str) {
+    let _ = NAMESPACE.try_with(|slot| {
+        slot.lock()
+            .unwrap()
+            .get_or_insert_with(|| namespace.to_string());
+    });
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestKey {
+    method: String,
+    namespace: String,
+    status: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Observations at or below each of `LATENCY_BUCKETS`
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Call counts and latencies of gRPC requests by method, namespace and status
+#[derive(Default)]
+pub struct RequestMetrics {
+    requests: Mutex<BTreeMap<RequestKey, Histogram>>,
+}
+
+impl RequestMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&self, method: &str, namespace: &str, status: tonic::Code, elapsed: Duration) {
+        let key = RequestKey {
+            method: method.to_string(),
+            namespace: namespace.to_string(),
+            status: format!("{:?}", status),
+        };
+        self.requests
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Number of calls recorded for a method, over all namespaces and statuses
+    pub fn calls(&self, method: &str) -> u64 {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.method == method)
+            .map(|(_, histogram)| histogram.count)
+            .sum()
+    }
+
+    /// All metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let requests = self.requests.lock().unwrap();
+        let mut out = String::from(
+            "# HELP synapse_grpc_requests_total gRPC requests handled\n\
+             # TYPE synapse_grpc_requests_total counter\n",
+        );
+        for (key, histogram) in requests.iter() {
+            out.push_str(&format!(
+                "synapse_grpc_requests_total{{{}}} {}\n",
+                key.labels(),
+                histogram.count
+            ));
+        }
+        out.push_str(
+            "# HELP synapse_grpc_request_duration_seconds Time until the response (or first stream message) is ready\n\
+             # TYPE synapse_grpc_request_duration_seconds histogram\n",
+        );
+        for (key, histogram) in requests.iter() {
+            let labels = key.labels();
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                out.push_str(&format!(
+                    "synapse_grpc_request_duration_seconds_bucket{{{},le=\"{}\"}} {}\n",
+                    labels, bound, count
+                ));
+            }
+            out.push_str(&format!(
+                "synapse_grpc_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}\n\
+                 synapse_grpc_request_duration_seconds_sum{{{}}} {}\n\
+                 synapse_grpc_request_duration_seconds_count{{{}}} {}\n",
+                labels, histogram.count, labels, histogram.sum, labels, histogram.count
+            ));
+        }
+        out
+    }
+}
+
+/// Memory and disk gauges of the open namespaces, by component
+pub fn render_namespace_usage(namespaces: &[(String, MemoryUsage, DiskUsage)]) -> String {
+    let mut out = String::from(
+        "# HELP synapse_namespace_memory_bytes Estimated heap bytes of an open namespace\n\
+         # TYPE synapse_namespace_memory_bytes gauge\n",
+    );
+    for (namespace, memory, _) in namespaces {
+        for (component, bytes) in memory.components() {
+            out.push_str(&format!(
+                "synapse_namespace_memory_bytes{{namespace=\"{}\",component=\"{}\"}} {}\n",
+                namespace, component, bytes
+            ));
+        }
+    }
+    out.push_str(
+        "# HELP synapse_namespace_disk_bytes Bytes on disk of an open namespace\n\
+         # TYPE synapse_namespace_disk_bytes gauge\n",
+    );
+    for (namespace, _, disk) in namespaces {
+        for (component, bytes) in disk.components() {
+            out.push_str(&format!(
+                "synapse_namespace_disk_bytes{{namespace=\"{}\",component=\"{}\"}} {}\n",
+                namespace, component, bytes
+            ));
+        }
+    }
+    out
+}
+
+impl RequestKey {
+    fn labels(&self) -> String {
+        format!(
+            "method=\"{}\",namespace=\"{}\",status=\"{}\"",
+            self.method, self.namespace, self.status
+        )
+    }
+}
+
+/// Tower layer recording every gRPC call in `RequestMetrics`
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Arc<RequestMetrics>,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: Arc<RequestMetrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<RequestMetrics>,
+}
+
+impl<S, B, ResBody> Service<http::Request<B>> for MetricsService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // "/semantic_engine.SemanticEngine/IngestTriples" -> "IngestTriples"
+        let method = request
+            .uri()
+            .path()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let metrics = self.metrics.clone();
+        let slot = Arc::new(Mutex::new(None));
+        let response = NAMESPACE.scope(slot.clone(), self.inner.call(request));
+
+        Box::pin(async move {
+            let started = Instant::now();
+            let result = response.await;
+            // Failed calls are trailers-only responses carrying grpc-status in the
+            // headers; successful ones send it later in the trailers
+            let status = match &result {
+                Ok(response) => response
+                    .headers()
+                    .get("grpc-status")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<i32>().ok())
+                    .map_or(tonic::Code::Ok, tonic::Code::from_i32),
+                Err(_) => tonic::Code::Unknown,
+            };
+            let namespace = slot.lock().unwrap().take().unwrap_or_default();
+            metrics.observe(&method, &namespace, status, started.elapsed());
+            result
+        })
+    }
+}
@@ -0,0 +1,380 @@
+//! A namespace's topology as GraphML or GEXF, so it can be opened in Gephi or yEd.
+//! Resources become nodes, literal-valued facts become node attributes and every
+//! other fact a directed edge typed by its predicate.
+
+use crate::properties::{PropertyStore, PropertyValue};
+use crate::store::local_name;
+use crate::topology::{is_resource, GraphTopology};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+const RDFS_LABEL: &str = "<http://www.w3.org/2000/01/rdf-schema#label>";
+
+/// Graph file formats `write_network` can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkFormat {
+    GraphMl,
+    Gexf,
+}
+
+impl NetworkFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "graphml" => Some(Self::GraphMl),
+            "gexf" => Some(Self::Gexf),
+            _ => None,
+        }
+    }
+}
+
+/// A node attribute, one per literal-valued property
+struct Attribute<'a> {
+    property: &'a str,
+    name: String,
+    kind: &'static str,
+}
+
+/// Nodes, attributes and edges gathered from the mirrors, in a stable order
+struct Network<'a> {
+    nodes: Vec<&'a str>,
+    attributes: Vec<Attribute<'a>>,
+    /// `(attribute index, value)` per node; the first value when a node has several
+    values: HashMap<&'a str, Vec<(usize, &'a PropertyValue)>>,
+    edges: Vec<(&'a str, &'a str, &'a str)>,
+}
+
+impl<'a> Network<'a> {
+    fn collect(topology: &'a GraphTopology, properties: &'a PropertyStore) -> Self {
+        let mut nodes: Vec<&str> = topology
+            .nodes()
+            .map(|(_, node)| node)
+            .filter(|node| is_resource(node))
+            .collect();
+        nodes.sort_unstable();
+
+        let mut names: Vec<&str> = properties.property_names().collect();
+        names.sort_unstable();
+        let mut local_names: HashMap<&str, usize> = HashMap::new();
+        for property in &names {
+            *local_names.entry(local_name(strip(property))).or_default() += 1;
+        }
+        let mut attributes: Vec<Attribute> = names
+            .iter()
+            .map(|&property| {
+                let short = local_name(strip(property));
+                Attribute {
+                    property,
+                    // Full IRIs only where local names would clash
+                    name: if local_names[short] > 1 {
+                        strip(property).to_string()
+                    } else {
+                        short.to_string()
+                    },
+                    kind: "",
+                }
+            })
+            .collect();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+
+        let mut values: HashMap<&str, Vec<(usize, &PropertyValue)>> = HashMap::new();
+        for (node, property, value) in properties.values() {
+            let i = index[property];
+            let kind = value_kind(value);
+            attributes[i].kind = match attributes[i].kind {
+                "" => kind,
+                current if current == kind => kind,
+                "long" | "double" if matches!(kind, "long" | "double") => "double",
+                _ => "string",
+            };
+            let node_values = values.entry(node).or_default();
+            if !node_values.iter().any(|(a, _)| *a == i) {
+                node_values.push((i, value));
+            }
+        }
+        for node_values in values.values_mut() {
+            node_values.sort_by_key(|(i, _)| *i);
+        }
+
+        let mut seen = HashSet::new();
+        let mut edges: Vec<(&str, &str, &str)> = topology
+            .edges()
+            .filter(|(_, _, object)| is_resource(object))
+            // The same fact stated in several graphs is one edge
+            .filter(|edge| seen.insert(*edge))
+            .collect();
+        edges.sort_unstable();
+
+        Self {
+            nodes,
+            attributes,
+            values,
+            edges,
+        }
+    }
+
+    fn label(&self, node: &'a str) -> String {
+        self.values
+            .get(node)
+            .into_iter()
+            .flatten()
+            .find(|(i, _)| self.attributes[*i].property == RDFS_LABEL)
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_else(|| local_name(strip(node)).to_string())
+    }
+
+    fn to_graphml<W: Write>(&self, out: &mut W) -> Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        writeln!(
+            out,
+            r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+        )?;
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            writeln!(
+                out,
+                r#"  <key id="p{}" for="node" attr.name="{}" attr.type="{}"/>"#,
+                i,
+                escape(&attribute.name),
+                attribute.kind
+            )?;
+        }
+        writeln!(
+            out,
+            r#"  <key id="edge_label" for="edge" attr.name="label" attr.type="string"/>"#
+        )?;
+        writeln!(
+            out,
+            r#"  <key id="predicate" for="edge" attr.name="predicate" attr.type="string"/>"#
+        )?;
+        writeln!(out, r#"  <graph id="G" edgedefault="directed">"#)?;
+        for &node in &self.nodes {
+            writeln!(out, r#"    <node id="{}">"#, escape(strip(node)))?;
+            writeln!(
+                out,
+                r#"      <data key="label">{}</data>"#,
+                escape(&self.label(node))
+            )?;
+            for (i, value) in self.values.get(node).into_iter().flatten() {
+                writeln!(
+                    out,
+                    r#"      <data key="p{}">{}</data>"#,
+                    i,
+                    escape(&value.to_string())
+                )?;
+            }
+            writeln!(out, "    </node>")?;
+        }
+        for (i, (subject, predicate, object)) in self.edges.iter().enumerate() {
+            writeln!(
+                out,
+                r#"    <edge id="e{}" source="{}" target="{}">"#,
+                i,
+                escape(strip(subject)),
+                escape(strip(object))
+            )?;
+            writeln!(
+                out,
+                r#"      <data key="edge_label">{}</data>"#,
+                escape(local_name(strip(predicate)))
+            )?;
+            writeln!(
+                out,
+                r#"      <data key="predicate">{}</data>"#,
+                escape(strip(predicate))
+            )?;
+            writeln!(out, "    </edge>")?;
+        }
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</graphml>")?;
+        Ok(())
+    }
+
+    fn to_gexf<W: Write>(&self, out: &mut W) -> Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+        writeln!(out, r#"  <graph mode="static" defaultedgetype="directed">"#)?;
+        writeln!(out, r#"    <attributes class="node">"#)?;
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            writeln!(
+                out,
+                r#"      <attribute id="p{}" title="{}" type="{}"/>"#,
+                i,
+                escape(&attribute.name),
+                attribute.kind
+            )?;
+        }
+        writeln!(out, "    </attributes>")?;
+        writeln!(out, r#"    <attributes class="edge">"#)?;
+        writeln!(
+            out,
+            r#"      <attribute id="predicate" title="predicate" type="string"/>"#
+        )?;
+        writeln!(out, "    </attributes>")?;
+        writeln!(out, "    <nodes>")?;
+        for &node in &self.nodes {
+            writeln!(
+                out,
+                r#"      <node id="{}" label="{}">"#,
+                escape(strip(node)),
+                escape(&self.label(node))
+            )?;
+            writeln!(out, "        <attvalues>")?;
+            for (i, value) in self.values.get(node).into_iter().flatten() {
+                writeln!(
+                    out,
+                    r#"          <attvalue for="p{}" value="{}"/>"#,
+                    i,
+                    escape(&value.to_string())
+                )?;
+            }
+            writeln!(out, "        </attvalues>")?;
+            writeln!(out, "      </node>")?;
+        }
+        writeln!(out, "    </nodes>")?;
+        writeln!(out, "    <edges>")?;
+        for (i, (subject, predicate, object)) in self.edges.iter().enumerate() {
+            writeln!(
+                out,
+                r#"      <edge id="e{}" source="{}" target="{}" label="{}" kind="{}">"#,
+                i,
+                escape(strip(subject)),
+                escape(strip(object)),
+                escape(local_name(strip(predicate))),
+                escape(local_name(strip(predicate)))
+            )?;
+            writeln!(
+                out,
+                r#"        <attvalues><attvalue for="predicate" value="{}"/></attvalues>"#,
+                escape(strip(predicate))
+            )?;
+            writeln!(out, "      </edge>")?;
+        }
+        writeln!(out, "    </edges>")?;
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</gexf>")?;
+        Ok(())
+    }
+}
+
+/// Write the mirrors as a graph file. Returns the number of nodes and edges written.
+pub fn write_network<W: Write>(
+    topology: &GraphTopology,
+    properties: &PropertyStore,
+    format: NetworkFormat,
+    writer: W,
+) -> Result<(usize, usize)> {
+    let network = Network::collect(topology, properties);
+    let mut out = std::io::BufWriter::new(writer);
+    match format {
+        NetworkFormat::GraphMl => network.to_graphml(&mut out)?,
+        NetworkFormat::Gexf => network.to_gexf(&mut out)?,
+    }
+    out.flush()?;
+    Ok((network.nodes.len(), network.edges.len()))
+}
+
+/// An IRI without its angle brackets
+fn strip(node: &str) -> &str {
+    node.strip_prefix('<')
+        .and_then(|n| n.strip_suffix('>'))
+        .unwrap_or(node)
+}
+
+fn value_kind(value: &PropertyValue) -> &'static str {
+    match value {
+        PropertyValue::Integer(_) => "long",
+        PropertyValue::Float(_) => "double",
+        PropertyValue::Boolean(_) => "boolean",
+        PropertyValue::Text(_) => "string",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (GraphTopology, PropertyStore) {
+        let mut topology = GraphTopology::new();
+        let mut properties = PropertyStore::new();
+        topology.add_edge(
+            "<http://ex.org/alice>",
+            "<http://ex.org/knows>",
+            "<http://ex.org/bob>",
+        );
+        // Stated again in another graph
+        topology.add_edge(
+            "<http://ex.org/alice>",
+            "<http://ex.org/knows>",
+            "<http://ex.org/bob>",
+        );
+        topology.add_edge("<http://ex.org/alice>", RDFS_LABEL, "\"Alice & co\"");
+        topology.add_edge("<http://ex.org/alice>", "<http://ex.org/age>", "\"41\"");
+        topology.add_edge("<http://ex.org/bob>", "<http://ex.org/age>", "\"38.5\"");
+        properties.add(
+            "<http://ex.org/alice>",
+            RDFS_LABEL,
+            PropertyValue::Text("Alice & co".into()),
+        );
+        properties.add(
+            "<http://ex.org/alice>",
+            "<http://ex.org/age>",
+            PropertyValue::Integer(41),
+        );
+        properties.add(
+            "<http://ex.org/bob>",
+            "<http://ex.org/age>",
+            PropertyValue::Float(38.5),
+        );
+        (topology, properties)
+    }
+
+    #[test]
+    fn test_graphml_nodes_attributes_and_edges() {
+        let (topology, properties) = sample();
+        let mut out = Vec::new();
+        let counts =
+            write_network(&topology, &properties, NetworkFormat::GraphMl, &mut out).unwrap();
+        assert_eq!(counts, (2, 1));
+        let xml = String::from_utf8(out).unwrap();
+        // Integers and floats in one column widen to double
+        assert!(xml.contains(r#"attr.name="age" attr.type="double""#));
+        assert!(xml.contains(r#"<data key="label">Alice &amp; co</data>"#));
+        assert!(xml.contains(r#"<data key="label">bob</data>"#));
+        assert!(xml.contains(r#"source="http://ex.org/alice" target="http://ex.org/bob""#));
+        assert!(xml.contains(r#"<data key="edge_label">knows</data>"#));
+        assert!(!xml.contains("&quot;41"));
+    }
+
+    #[test]
+    fn test_gexf_declares_attributes() {
+        let (topology, properties) = sample();
+        let mut out = Vec::new();
+        write_network(&topology, &properties, NetworkFormat::Gexf, &mut out).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+        assert!(xml.contains(r#"<node id="http://ex.org/alice" label="Alice &amp; co">"#));
+        assert!(xml.contains(r#"title="age" type="double""#));
+        assert!(xml.contains(r#"label="knows" kind="knows""#));
+        assert_eq!(xml.matches("<edge ").count(), 1);
+    }
+}
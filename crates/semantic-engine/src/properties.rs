@@ -0,0 +1,906 @@
+//! Typed literal attributes of a namespace's nodes, kept one column per property
+//! so filtering on a property only touches that property's values.
+
+use crate::memory::{btree_bytes, map_bytes, string_bytes, vec_bytes};
+use oxigraph::model::vocab::xsd;
+use oxigraph::model::{LiteralRef, NamedNodeRef};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, RangeBounds};
+
+/// Datatypes whose literals become `PropertyValue::Integer`
+const INTEGER_TYPES: [NamedNodeRef<'static>; 13] = [
+    xsd::INTEGER,
+    xsd::INT,
+    xsd::LONG,
+    xsd::SHORT,
+    xsd::BYTE,
+    xsd::NON_NEGATIVE_INTEGER,
+    xsd::NON_POSITIVE_INTEGER,
+    xsd::POSITIVE_INTEGER,
+    xsd::NEGATIVE_INTEGER,
+    xsd::UNSIGNED_INT,
+    xsd::UNSIGNED_LONG,
+    xsd::UNSIGNED_SHORT,
+    xsd::UNSIGNED_BYTE,
+];
+
+/// A property value, typed from its literal's datatype
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyValue {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Text(String),
+}
+
+impl PropertyValue {
+    /// Numeric and boolean literals become typed values when they parse; anything
+    /// else, including language-tagged strings, is kept as text
+    pub fn from_literal(literal: LiteralRef<'_>) -> Self {
+        let value = literal.value();
+        let datatype = literal.datatype();
+        if INTEGER_TYPES.contains(&datatype) {
+            if let Ok(i) = value.parse() {
+                return Self::Integer(i);
+            }
+        } else if datatype == xsd::DECIMAL || datatype == xsd::DOUBLE || datatype == xsd::FLOAT {
+            if let Ok(f) = value.parse() {
+                return Self::Float(f);
+            }
+        } else if datatype == xsd::BOOLEAN {
+            match value {
+                "true" | "1" => return Self::Boolean(true),
+                "false" | "0" => return Self::Boolean(false),
+                _ => {}
+            }
+        }
+        Self::Text(value.to_string())
+    }
+
+    /// Integers and floats as `f64`, for numeric comparisons
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(i) => write!(f, "{}", i),
+            Self::Float(x) => write!(f, "{}", x),
+            Self::Boolean(b) => write!(f, "{}", b),
+            Self::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+/// How a property's values are indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexKind {
+    /// Equality lookups
+    Hash,
+    /// Equality lookups and numeric ranges
+    BTree,
+}
+
+/// A number ordered and hashed by its bits, so it can key an index
+#[derive(Debug, Clone, Copy)]
+struct Number(f64);
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// What a value is looked up by: integers and floats compare as numbers, so
+/// `5` finds `5.0`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum IndexKey {
+    Boolean(bool),
+    Number(Number),
+    Text(String),
+}
+
+impl IndexKey {
+    fn of(value: &PropertyValue) -> Self {
+        match value {
+            PropertyValue::Boolean(b) => Self::Boolean(*b),
+            PropertyValue::Text(s) => Self::Text(s.clone()),
+            // Adding 0.0 turns -0.0 into 0.0, so both are one key
+            number => Self::Number(Number(number.as_f64().unwrap_or_default() + 0.0)),
+        }
+    }
+
+    /// Key of a numeric range bound, and whether it is inclusive; an unbounded
+    /// end becomes the inclusive `unbounded`
+    fn number(bound: Bound<&f64>, unbounded: f64) -> (Self, bool) {
+        let key = |x: f64| Self::Number(Number(x + 0.0));
+        match bound {
+            Bound::Included(&x) => (key(x), true),
+            Bound::Excluded(&x) => (key(x), false),
+            Bound::Unbounded => (key(unbounded), true),
+        }
+    }
+}
+
+/// Node IDs by value, once per stored value
+#[derive(Debug)]
+enum ValueIndex {
+    Hash(HashMap<IndexKey, Vec<u32>>),
+    BTree(BTreeMap<IndexKey, Vec<u32>>),
+}
+
+impl ValueIndex {
+    fn new(kind: IndexKind) -> Self {
+        match kind {
+            IndexKind::Hash => Self::Hash(HashMap::new()),
+            IndexKind::BTree => Self::BTree(BTreeMap::new()),
+        }
+    }
+
+    fn kind(&self) -> IndexKind {
+        match self {
+            Self::Hash(_) => IndexKind::Hash,
+            Self::BTree(_) => IndexKind::BTree,
+        }
+    }
+
+    fn insert(&mut self, key: IndexKey, node: u32) {
+        match self {
+            Self::Hash(index) => index.entry(key).or_default().push(node),
+            Self::BTree(index) => index.entry(key).or_default().push(node),
+        }
+    }
+
+    fn remove(&mut self, key: &IndexKey, node: u32) {
+        let nodes = match self {
+            Self::Hash(index) => index.get_mut(key),
+            Self::BTree(index) => index.get_mut(key),
+        };
+        let Some(nodes) = nodes else {
+            return;
+        };
+        if let Some(i) = nodes.iter().position(|&n| n == node) {
+            nodes.swap_remove(i);
+        }
+        if nodes.is_empty() {
+            match self {
+                Self::Hash(index) => index.remove(key),
+                Self::BTree(index) => index.remove(key),
+            };
+        }
+    }
+
+    fn heap_bytes(&self) -> usize {
+        let entry = |(key, nodes): (&IndexKey, &Vec<u32>)| {
+            vec_bytes(nodes)
+                + match key {
+                    IndexKey::Text(text) => string_bytes(text),
+                    _ => 0,
+                }
+        };
+        match self {
+            Self::Hash(index) => map_bytes(index) + index.iter().map(entry).sum::<usize>(),
+            Self::BTree(index) => btree_bytes(index) + index.iter().map(entry).sum::<usize>(),
+        }
+    }
+
+    fn get(&self, key: &IndexKey) -> &[u32] {
+        let nodes = match self {
+            Self::Hash(index) => index.get(key),
+            Self::BTree(index) => index.get(key),
+        };
+        nodes.map_or(&[], Vec::as_slice)
+    }
+}
+
+/// How a column lays out its values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnLayout {
+    /// Values keyed by node ID, costing memory only for nodes with the property
+    Sparse,
+    /// A slot per node ID, cheaper per value once most nodes have the property
+    Dense,
+}
+
+/// Node densities at which `PropertyStore` switches a column's layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnPolicy {
+    /// Share of nodes with a value at which a sparse column becomes dense
+    pub promote_at: f64,
+    /// Share below which a dense column goes back to sparse; lower than
+    /// `promote_at` so a column near the threshold doesn't flip back and forth
+    pub demote_at: f64,
+}
+
+impl Default for ColumnPolicy {
+    fn default() -> Self {
+        Self {
+            promote_at: 0.5,
+            demote_at: 0.2,
+        }
+    }
+}
+
+impl ColumnPolicy {
+    /// Parse `promote_at:demote_at`, such as `0.5:0.2`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (promote, demote) = spec.trim().split_once(':')?;
+        let promote_at: f64 = promote.trim().parse().ok()?;
+        let demote_at: f64 = demote.trim().parse().ok()?;
+        ((0.0..=1.0).contains(&promote_at) && (0.0..=promote_at).contains(&demote_at)).then_some(
+            Self {
+                promote_at,
+                demote_at,
+            },
+        )
+    }
+
+    /// Policy from `SYNAPSE_PROPERTY_DENSITY`; the default when unset or invalid
+    pub fn from_env() -> Self {
+        match std::env::var("SYNAPSE_PROPERTY_DENSITY") {
+            Ok(spec) => Self::parse(&spec).unwrap_or_else(|| {
+                tracing::warn!("Ignoring invalid SYNAPSE_PROPERTY_DENSITY: {}", spec);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// Below this many nodes every column stays sparse unless set by hand
+const MIN_DENSE_NODES: usize = 64;
+
+#[derive(Debug)]
+enum Cells {
+    Sparse(HashMap<u32, Vec<PropertyValue>>),
+    /// Empty for nodes without the property
+    Dense(Vec<Vec<PropertyValue>>),
+}
+
+impl Default for Cells {
+    fn default() -> Self {
+        Self::Sparse(HashMap::new())
+    }
+}
+
+/// The values of one property, by node ID. RDF allows several values per node,
+/// though most nodes have one.
+#[derive(Debug, Default)]
+struct Column {
+    cells: Cells,
+    /// Nodes with at least one value
+    covered: usize,
+    /// Layout set by hand, which the policy leaves alone
+    pinned: bool,
+    index: Option<ValueIndex>,
+}
+
+impl Column {
+    fn heap_bytes(&self) -> usize {
+        let values = |values: &Vec<PropertyValue>| {
+            vec_bytes(values)
+                + values
+                    .iter()
+                    .map(|value| match value {
+                        PropertyValue::Text(text) => string_bytes(text),
+                        _ => 0,
+                    })
+                    .sum::<usize>()
+        };
+        let cells = match &self.cells {
+            Cells::Sparse(cells) => map_bytes(cells) + cells.values().map(values).sum::<usize>(),
+            Cells::Dense(cells) => vec_bytes(cells) + cells.iter().map(values).sum::<usize>(),
+        };
+        cells + self.index.as_ref().map_or(0, ValueIndex::heap_bytes)
+    }
+
+    fn get(&self, node: u32) -> &[PropertyValue] {
+        let values = match &self.cells {
+            Cells::Sparse(cells) => cells.get(&node),
+            Cells::Dense(cells) => cells.get(node as usize),
+        };
+        values.map_or(&[], Vec::as_slice)
+    }
+
+    /// Nodes with values, with their values
+    fn cells(&self) -> Box<dyn Iterator<Item = (u32, &[PropertyValue])> + '_> {
+        match &self.cells {
+            Cells::Sparse(cells) => {
+                Box::new(cells.iter().map(|(&n, values)| (n, values.as_slice())))
+            }
+            Cells::Dense(cells) => Box::new(
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, values)| !values.is_empty())
+                    .map(|(n, values)| (n as u32, values.as_slice())),
+            ),
+        }
+    }
+
+    fn push(&mut self, node: u32, value: PropertyValue) {
+        if let Some(index) = &mut self.index {
+            index.insert(IndexKey::of(&value), node);
+        }
+        let values = match &mut self.cells {
+            Cells::Sparse(cells) => cells.entry(node).or_default(),
+            Cells::Dense(cells) => {
+                if cells.len() <= node as usize {
+                    cells.resize_with(node as usize + 1, Vec::new);
+                }
+                &mut cells[node as usize]
+            }
+        };
+        if values.is_empty() {
+            self.covered += 1;
+        }
+        values.push(value);
+    }
+
+    fn remove(&mut self, node: u32, value: &PropertyValue) -> bool {
+        let values = match &mut self.cells {
+            Cells::Sparse(cells) => cells.get_mut(&node),
+            Cells::Dense(cells) => cells.get_mut(node as usize),
+        };
+        let Some(values) = values else {
+            return false;
+        };
+        let Some(i) = values.iter().position(|v| v == value) else {
+            return false;
+        };
+        values.swap_remove(i);
+        if values.is_empty() {
+            // Free the slot's allocation too
+            *values = Vec::new();
+            self.covered -= 1;
+            if let Cells::Sparse(cells) = &mut self.cells {
+                cells.remove(&node);
+            }
+        }
+        if let Some(index) = &mut self.index {
+            index.remove(&IndexKey::of(value), node);
+        }
+        true
+    }
+
+    fn layout(&self) -> ColumnLayout {
+        match self.cells {
+            Cells::Sparse(_) => ColumnLayout::Sparse,
+            Cells::Dense(_) => ColumnLayout::Dense,
+        }
+    }
+
+    fn set_layout(&mut self, layout: ColumnLayout) {
+        self.cells = match (std::mem::take(&mut self.cells), layout) {
+            (Cells::Sparse(cells), ColumnLayout::Dense) => {
+                let len = cells.keys().max().map_or(0, |&n| n as usize + 1);
+                let mut dense = vec![Vec::new(); len];
+                for (n, values) in cells {
+                    dense[n as usize] = values;
+                }
+                Cells::Dense(dense)
+            }
+            (Cells::Dense(cells), ColumnLayout::Sparse) => Cells::Sparse(
+                cells
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, values)| !values.is_empty())
+                    .map(|(n, values)| (n as u32, values))
+                    .collect(),
+            ),
+            (cells, _) => cells,
+        };
+    }
+}
+
+/// Literal-valued facts of a namespace as typed property columns. Nodes and
+/// properties are keyed in N-Triples form, as in `GraphTopology`; the same fact
+/// stated in two graphs is stored twice.
+///
+/// Columns start sparse and are promoted to dense, and demoted again, as their
+/// share of nodes crosses the `ColumnPolicy` thresholds. A write only checks the
+/// column it touched; `rebalance` checks them all.
+#[derive(Debug, Default)]
+pub struct PropertyStore {
+    node_ids: HashMap<String, u32>,
+    nodes: Vec<String>,
+    property_ids: HashMap<String, u32>,
+    properties: Vec<String>,
+    /// One column per property ID
+    columns: Vec<Column>,
+    values: usize,
+    policy: ColumnPolicy,
+}
+
+impl PropertyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_policy(policy: ColumnPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    fn intern_node(&mut self, node: &str) -> u32 {
+        if let Some(&id) = self.node_ids.get(node) {
+            return id;
+        }
+        let id = self.nodes.len() as u32;
+        self.node_ids.insert(node.to_string(), id);
+        self.nodes.push(node.to_string());
+        id
+    }
+
+    fn intern_property(&mut self, property: &str) -> u32 {
+        if let Some(&id) = self.property_ids.get(property) {
+            return id;
+        }
+        let id = self.properties.len() as u32;
+        self.property_ids.insert(property.to_string(), id);
+        self.properties.push(property.to_string());
+        self.columns.push(Column::default());
+        id
+    }
+
+    fn column(&self, property: &str) -> Option<&Column> {
+        let id = *self.property_ids.get(property)?;
+        Some(&self.columns[id as usize])
+    }
+
+    /// Record one value of a node's property
+    pub fn add(&mut self, node: &str, property: &str, value: PropertyValue) {
+        let n = self.intern_node(node);
+        let p = self.intern_property(property);
+        self.columns[p as usize].push(n, value);
+        self.values += 1;
+        self.apply_policy(p);
+    }
+
+    /// Remove one value of a node's property, returning whether it was there
+    pub fn remove(&mut self, node: &str, property: &str, value: &PropertyValue) -> bool {
+        let (Some(&n), Some(&p)) = (self.node_ids.get(node), self.property_ids.get(property))
+        else {
+            return false;
+        };
+        if !self.columns[p as usize].remove(n, value) {
+            return false;
+        }
+        self.values -= 1;
+        self.apply_policy(p);
+        true
+    }
+
+    /// Values of a node's property
+    pub fn get(&self, node: &str, property: &str) -> &[PropertyValue] {
+        match (self.node_ids.get(node), self.column(property)) {
+            (Some(&n), Some(column)) => column.get(n),
+            _ => &[],
+        }
+    }
+
+    /// `(property, value)` pairs of a node, ordered by property
+    pub fn properties_of(&self, node: &str) -> Vec<(&str, &PropertyValue)> {
+        let Some(&n) = self.node_ids.get(node) else {
+            return Vec::new();
+        };
+        let mut found: Vec<(&str, &PropertyValue)> = self
+            .columns
+            .iter()
+            .zip(&self.properties)
+            .flat_map(|(column, property)| {
+                column.get(n).iter().map(move |v| (property.as_str(), v))
+            })
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(b.0));
+        found
+    }
+
+    /// Nodes with a value of `property` that satisfies `filter`, scanning only that column
+    pub fn nodes_where(
+        &self,
+        property: &str,
+        filter: impl Fn(&PropertyValue) -> bool,
+    ) -> Vec<&str> {
+        let Some(column) = self.column(property) else {
+            return Vec::new();
+        };
+        let mut found: Vec<&str> = column
+            .cells()
+            .filter(|(_, values)| values.iter().any(&filter))
+            .map(|(n, _)| self.nodes[n as usize].as_str())
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Index a property's values, replacing any index it had. The property need
+    /// not have values yet.
+    pub fn create_index(&mut self, property: &str, kind: IndexKind) {
+        let p = self.intern_property(property);
+        let column = &mut self.columns[p as usize];
+        let mut index = ValueIndex::new(kind);
+        for (n, values) in column.cells() {
+            for value in values {
+                index.insert(IndexKey::of(value), n);
+            }
+        }
+        column.index = Some(index);
+    }
+
+    /// Drop a property's index, returning whether it had one
+    pub fn drop_index(&mut self, property: &str) -> bool {
+        let Some(&p) = self.property_ids.get(property) else {
+            return false;
+        };
+        self.columns[p as usize].index.take().is_some()
+    }
+
+    /// Indexed properties with the kind of their index, ordered by property
+    pub fn indexes(&self) -> Vec<(&str, IndexKind)> {
+        let mut indexes: Vec<(&str, IndexKind)> = self
+            .columns
+            .iter()
+            .zip(&self.properties)
+            .filter_map(|(column, property)| {
+                Some((property.as_str(), column.index.as_ref()?.kind()))
+            })
+            .collect();
+        indexes.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        indexes
+    }
+
+    fn node_names(&self, mut ids: Vec<u32>) -> Vec<&str> {
+        ids.sort_unstable();
+        ids.dedup();
+        let mut found: Vec<&str> = ids
+            .into_iter()
+            .map(|n| self.nodes[n as usize].as_str())
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Nodes with `value` for `property`, where integers and floats compare as
+    /// numbers. Uses the property's index if it has one.
+    pub fn nodes_with_value(&self, property: &str, value: &PropertyValue) -> Vec<&str> {
+        let Some(column) = self.column(property) else {
+            return Vec::new();
+        };
+        let key = IndexKey::of(value);
+        match &column.index {
+            Some(index) => self.node_names(index.get(&key).to_vec()),
+            None => self.nodes_where(property, |v| IndexKey::of(v) == key),
+        }
+    }
+
+    /// Nodes with a numeric value of `property` in `range`. Uses the property's
+    /// index if it is a BTree.
+    pub fn nodes_in_range(&self, property: &str, range: impl RangeBounds<f64>) -> Vec<&str> {
+        let Some(column) = self.column(property) else {
+            return Vec::new();
+        };
+        let Some(ValueIndex::BTree(index)) = &column.index else {
+            return self.nodes_where(property, |v| v.as_f64().is_some_and(|x| range.contains(&x)));
+        };
+        let (low, low_included) = IndexKey::number(range.start_bound(), f64::NEG_INFINITY);
+        let (high, high_included) = IndexKey::number(range.end_bound(), f64::INFINITY);
+        // BTreeMap::range panics on an empty or inverted range
+        if low > high || (low == high && !(low_included && high_included)) {
+            return Vec::new();
+        }
+        let bound = |key, included| {
+            if included {
+                Bound::Included(key)
+            } else {
+                Bound::Excluded(key)
+            }
+        };
+        let ids = index
+            .range((bound(low, low_included), bound(high, high_included)))
+            .flat_map(|(_, nodes)| nodes.iter().copied())
+            .collect();
+        self.node_names(ids)
+    }
+
+    /// Properties with at least one value
+    pub fn property_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.columns
+            .iter()
+            .zip(&self.properties)
+            .filter(|(column, _)| column.covered > 0)
+            .map(|(_, property)| property.as_str())
+    }
+
+    /// Estimated heap bytes, names counted in both the ID maps and the name lists
+    pub fn heap_bytes(&self) -> usize {
+        let names = |ids: &HashMap<String, u32>| {
+            map_bytes(ids) + 2 * ids.keys().map(string_bytes).sum::<usize>()
+        };
+        names(&self.node_ids)
+            + vec_bytes(&self.nodes)
+            + names(&self.property_ids)
+            + vec_bytes(&self.properties)
+            + vec_bytes(&self.columns)
+            + self.columns.iter().map(Column::heap_bytes).sum::<usize>()
+    }
+
+    /// Every value as `(node, property, value)`
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str, &PropertyValue)> + '_ {
+        self.columns
+            .iter()
+            .zip(&self.properties)
+            .flat_map(move |(column, property)| {
+                column.cells().flat_map(move |(n, values)| {
+                    values
+                        .iter()
+                        .map(move |v| (self.nodes[n as usize].as_str(), property.as_str(), v))
+                })
+            })
+    }
+
+    pub fn value_count(&self) -> usize {
+        self.values
+    }
+
+    /// Switch a column's layout if its density crossed the policy's thresholds,
+    /// returning whether it did
+    fn apply_policy(&mut self, p: u32) -> bool {
+        let nodes = self.nodes.len();
+        let column = &mut self.columns[p as usize];
+        if column.pinned || nodes < MIN_DENSE_NODES {
+            return false;
+        }
+        let density = column.covered as f64 / nodes as f64;
+        let layout = match column.layout() {
+            ColumnLayout::Sparse if density >= self.policy.promote_at => ColumnLayout::Dense,
+            ColumnLayout::Dense if density < self.policy.demote_at => ColumnLayout::Sparse,
+            _ => return false,
+        };
+        column.set_layout(layout);
+        true
+    }
+
+    /// Apply the policy to every column, returning how many changed layout
+    pub fn rebalance(&mut self) -> usize {
+        (0..self.columns.len() as u32)
+            .filter(|&p| self.apply_policy(p))
+            .count()
+    }
+
+    pub fn policy(&self) -> ColumnPolicy {
+        self.policy
+    }
+
+    /// Replace the policy and rebalance every column under it
+    pub fn set_policy(&mut self, policy: ColumnPolicy) {
+        self.policy = policy;
+        self.rebalance();
+    }
+
+    /// Fix a property's layout, or with `None` hand it back to the policy
+    pub fn set_layout(&mut self, property: &str, layout: Option<ColumnLayout>) {
+        let p = self.intern_property(property);
+        let column = &mut self.columns[p as usize];
+        column.pinned = layout.is_some();
+        match layout {
+            Some(layout) => column.set_layout(layout),
+            None => {
+                self.apply_policy(p);
+            }
+        }
+    }
+
+    /// Properties with their column layout and whether it was set by hand,
+    /// ordered by property
+    pub fn layouts(&self) -> Vec<(&str, ColumnLayout, bool)> {
+        let mut layouts: Vec<(&str, ColumnLayout, bool)> = self
+            .columns
+            .iter()
+            .zip(&self.properties)
+            .map(|(column, property)| (property.as_str(), column.layout(), column.pinned))
+            .collect();
+        layouts.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        layouts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::Literal;
+
+    #[test]
+    fn test_values_are_typed_from_literals() {
+        let typed = |value: &str, datatype| {
+            PropertyValue::from_literal(Literal::new_typed_literal(value, datatype).as_ref())
+        };
+        assert_eq!(typed("42", xsd::INTEGER), PropertyValue::Integer(42));
+        assert_eq!(typed("2.5", xsd::DOUBLE), PropertyValue::Float(2.5));
+        assert_eq!(typed("true", xsd::BOOLEAN), PropertyValue::Boolean(true));
+        assert_eq!(
+            typed("many", xsd::INTEGER),
+            PropertyValue::Text("many".into())
+        );
+        let simple = Literal::new_simple_literal("42");
+        assert_eq!(
+            PropertyValue::from_literal(simple.as_ref()),
+            PropertyValue::Text("42".into())
+        );
+    }
+
+    #[test]
+    fn test_columns_filter_by_property() {
+        let mut store = PropertyStore::new();
+        store.add("<a>", "<age>", PropertyValue::Integer(30));
+        store.add("<b>", "<age>", PropertyValue::Integer(45));
+        store.add("<a>", "<name>", PropertyValue::Text("A".into()));
+        store.add("<a>", "<name>", PropertyValue::Text("Alpha".into()));
+
+        assert_eq!(store.value_count(), 4);
+        assert_eq!(store.get("<a>", "<name>").len(), 2);
+        assert_eq!(
+            store.nodes_where("<age>", |v| v.as_f64().is_some_and(|age| age > 40.0)),
+            vec!["<b>"]
+        );
+        assert!(store.nodes_where("<missing>", |_| true).is_empty());
+        assert_eq!(
+            store.properties_of("<a>").first(),
+            Some(&("<age>", &PropertyValue::Integer(30)))
+        );
+
+        assert!(store.remove("<a>", "<name>", &PropertyValue::Text("A".into())));
+        assert!(!store.remove("<a>", "<name>", &PropertyValue::Text("A".into())));
+        assert!(store.remove("<b>", "<age>", &PropertyValue::Integer(45)));
+        assert_eq!(store.get("<b>", "<age>"), &[]);
+        assert_eq!(store.value_count(), 2);
+        assert_eq!(
+            store.property_names().collect::<Vec<_>>(),
+            vec!["<age>", "<name>"]
+        );
+    }
+
+    #[test]
+    fn test_indexed_lookups_match_scans() {
+        let mut store = PropertyStore::new();
+        let statuses = ["active", "retired", "active", "pending"];
+        for (i, status) in statuses.iter().enumerate() {
+            let node = format!("<n{}>", i);
+            store.add(&node, "<status>", PropertyValue::Text(status.to_string()));
+            store.add(&node, "<score>", PropertyValue::Integer(i as i64 * 10));
+        }
+        store.add("<n4>", "<score>", PropertyValue::Float(15.5));
+
+        let active = PropertyValue::Text("active".into());
+        fn lookups(store: &PropertyStore, active: &PropertyValue) -> [Vec<String>; 5] {
+            [
+                store.nodes_with_value("<status>", active),
+                store.nodes_with_value("<score>", &PropertyValue::Float(20.0)),
+                store.nodes_in_range("<score>", 10.0..20.0),
+                store.nodes_in_range("<score>", ..=10.0),
+                store.nodes_in_range("<score>", 25.0..),
+            ]
+            .map(|nodes| nodes.into_iter().map(String::from).collect())
+        }
+        let scanned = lookups(&store, &active);
+        assert_eq!(scanned[0], vec!["<n0>", "<n2>"]);
+        assert_eq!(scanned[1], vec!["<n2>"]);
+        assert_eq!(scanned[2], vec!["<n1>", "<n4>"]);
+        assert_eq!(scanned[3], vec!["<n0>", "<n1>"]);
+        assert_eq!(scanned[4], vec!["<n3>"]);
+
+        store.create_index("<status>", IndexKind::Hash);
+        store.create_index("<score>", IndexKind::BTree);
+        assert_eq!(
+            store.indexes(),
+            vec![("<score>", IndexKind::BTree), ("<status>", IndexKind::Hash)]
+        );
+        assert_eq!(lookups(&store, &active), scanned);
+        assert!(store.nodes_in_range("<score>", 20.0..20.0).is_empty());
+
+        // Indexes follow later writes
+        store.remove("<n0>", "<status>", &active);
+        store.add("<n3>", "<status>", active.clone());
+        assert_eq!(
+            store.nodes_with_value("<status>", &active),
+            vec!["<n2>", "<n3>"]
+        );
+        assert!(store.drop_index("<status>"));
+        assert!(!store.drop_index("<status>"));
+        assert_eq!(
+            store.nodes_with_value("<status>", &active),
+            vec!["<n2>", "<n3>"]
+        );
+    }
+
+    #[test]
+    fn test_columns_follow_their_density() {
+        let mut store = PropertyStore::new();
+        let layout = |store: &PropertyStore, property: &str| {
+            store
+                .layouts()
+                .into_iter()
+                .find(|(p, _, _)| *p == property)
+                .map(|(_, layout, _)| layout)
+        };
+        for i in 0..100 {
+            store.add(&format!("<n{}>", i), "<name>", PropertyValue::Integer(i));
+            if i % 10 == 0 {
+                store.add(&format!("<n{}>", i), "<rare>", PropertyValue::Boolean(true));
+            }
+        }
+        assert_eq!(layout(&store, "<name>"), Some(ColumnLayout::Dense));
+        assert_eq!(layout(&store, "<rare>"), Some(ColumnLayout::Sparse));
+        assert_eq!(store.get("<n42>", "<name>"), &[PropertyValue::Integer(42)]);
+        assert_eq!(
+            store.nodes_in_range("<name>", 97.0..),
+            vec!["<n97>", "<n98>", "<n99>"]
+        );
+
+        // Dropping below the demotion threshold goes back to sparse
+        for i in 15..100 {
+            store.remove(&format!("<n{}>", i), "<name>", &PropertyValue::Integer(i));
+        }
+        assert_eq!(layout(&store, "<name>"), Some(ColumnLayout::Sparse));
+        assert_eq!(store.nodes_where("<name>", |_| true).len(), 15);
+
+        // A layout set by hand sticks until handed back
+        store.set_layout("<rare>", Some(ColumnLayout::Dense));
+        store.add("<n1>", "<rare>", PropertyValue::Boolean(false));
+        assert_eq!(layout(&store, "<rare>"), Some(ColumnLayout::Dense));
+        assert_eq!(
+            store.get("<n1>", "<rare>"),
+            &[PropertyValue::Boolean(false)]
+        );
+        store.set_layout("<rare>", None);
+        assert_eq!(layout(&store, "<rare>"), Some(ColumnLayout::Sparse));
+
+        store.set_policy(ColumnPolicy {
+            promote_at: 0.1,
+            demote_at: 0.05,
+        });
+        assert_eq!(layout(&store, "<rare>"), Some(ColumnLayout::Dense));
+        assert_eq!(ColumnPolicy::parse("0.2:0.5"), None);
+        assert_eq!(ColumnPolicy::parse(" 0.6 : 0.1 ").unwrap().demote_at, 0.1);
+    }
+}
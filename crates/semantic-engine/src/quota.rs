@@ -0,0 +1,175 @@
+use crate::auth::namespace_matches;
+use crate::store::SynapseStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Size caps for one namespace; `None` is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaLimits {
+    pub max_triples: Option<u64>,
+    pub max_vectors: Option<u64>,
+    pub max_disk_bytes: Option<u64>,
+}
+
+impl QuotaLimits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The stricter of two limits for each resource
+    pub fn tighten(self, other: Self) -> Self {
+        let min = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            max_triples: min(self.max_triples, other.max_triples),
+            max_vectors: min(self.max_vectors, other.max_vectors),
+            max_disk_bytes: min(self.max_disk_bytes, other.max_disk_bytes),
+        }
+    }
+
+    /// Refuse an ingest of `incoming` triples (0 when unknown up front) into `store`
+    /// once it would exceed a limit
+    pub fn check(&self, store: &SynapseStore, incoming: usize) -> Result<(), String> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+        let stats = store.stats().map_err(|e| e.to_string())?;
+        let exceeded = |what: &str, used: u64, limit: u64| {
+            format!(
+                "Quota exceeded for namespace '{}': {} {} (limit {})",
+                store.namespace, used, what, limit
+            )
+        };
+        if let Some(limit) = self.max_triples {
+            let used = (stats.triples + incoming) as u64;
+            if used > limit || (incoming == 0 && used >= limit) {
+                return Err(exceeded("triples", used, limit));
+            }
+        }
+        if let Some(limit) = self.max_vectors {
+            if stats.vectors as u64 >= limit {
+                return Err(exceeded("vectors", stats.vectors as u64, limit));
+            }
+        }
+        if let Some(limit) = self.max_disk_bytes {
+            if stats.disk_bytes >= limit {
+                return Err(exceeded("bytes on disk", stats.disk_bytes, limit));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QuotaConfig {
+    #[serde(default)]
+    namespaces: HashMap<String, QuotaLimits>,
+    #[serde(default)]
+    tokens: HashMap<String, QuotaLimits>,
+}
+
+/// Quotas per namespace pattern and per auth token; a write is held to the
+/// tightest limit that applies to it
+#[derive(Default)]
+pub struct NamespaceQuotas {
+    namespaces: RwLock<Vec<(String, QuotaLimits)>>,
+    tokens: RwLock<HashMap<String, QuotaLimits>>,
+}
+
+impl NamespaceQuotas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quotas from `SYNAPSE_QUOTAS`, e.g.
+    /// `{"namespaces": {"tenant-*": {"max_triples": 100000}}, "tokens": {"trial": {"max_vectors": 1000}}}`
+    pub fn from_env() -> Self {
+        let quotas = Self::new();
+        if let Ok(json) = std::env::var("SYNAPSE_QUOTAS") {
+            match serde_json::from_str::<QuotaConfig>(&json) {
+                Ok(config) => {
+                    for (pattern, limits) in config.namespaces {
+                        quotas.set_namespace_quota(&pattern, limits);
+                    }
+                    for (token, limits) in config.tokens {
+                        quotas.set_token_quota(&token, limits);
+                    }
+                }
+                Err(e) => tracing::warn!("Ignoring invalid SYNAPSE_QUOTAS: {}", e),
+            }
+        }
+        quotas
+    }
+
+    /// Limit namespaces matching `pattern` (`*`, `prefix*` or a name)
+    pub fn set_namespace_quota(&self, pattern: &str, limits: QuotaLimits) {
+        let mut namespaces = self.namespaces.write().unwrap();
+        namespaces.retain(|(p, _)| p != pattern);
+        namespaces.push((pattern.to_string(), limits));
+    }
+
+    /// Limit every namespace written with `token`
+    pub fn set_token_quota(&self, token: &str, limits: QuotaLimits) {
+        self.tokens
+            .write()
+            .unwrap()
+            .insert(token.to_string(), limits);
+    }
+
+    /// Limits for a write by `token` to `namespace`
+    pub fn limits_for(&self, token: Option<&str>, namespace: &str) -> QuotaLimits {
+        let mut limits = self
+            .namespaces
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(pattern, _)| namespace_matches(pattern, namespace))
+            .fold(QuotaLimits::default(), |acc, (_, l)| acc.tighten(*l));
+        if let Some(l) = token.and_then(|t| self.tokens.read().unwrap().get(t).copied()) {
+            limits = limits.tighten(l);
+        }
+        limits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_combine_namespace_and_token_quotas() {
+        let quotas = NamespaceQuotas::new();
+        quotas.set_namespace_quota(
+            "tenant-*",
+            QuotaLimits {
+                max_triples: Some(100),
+                max_vectors: Some(50),
+                ..Default::default()
+            },
+        );
+        quotas.set_token_quota(
+            "trial",
+            QuotaLimits {
+                max_triples: Some(10),
+                max_disk_bytes: Some(1 << 20),
+                ..Default::default()
+            },
+        );
+
+        assert!(quotas.limits_for(None, "other").is_unlimited());
+        assert_eq!(quotas.limits_for(None, "tenant-a").max_triples, Some(100));
+        assert_eq!(
+            quotas.limits_for(Some("trial"), "tenant-a"),
+            QuotaLimits {
+                max_triples: Some(10),
+                max_vectors: Some(50),
+                max_disk_bytes: Some(1 << 20),
+            }
+        );
+    }
+}
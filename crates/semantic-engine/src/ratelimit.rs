@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    /// Set while running work whose request was already charged, such as an MCP
+    /// tool call forwarded to the gRPC handlers
+    static CHARGED: ();
+}
+
+/// Run `work` without charging the rate limits again
+pub async fn already_charged<F: Future>(work: F) -> F::Output {
+    CHARGED.scope((), work).await
+}
+
+/// Sustained request rate and burst size of a token bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub per_second: f64,
+    pub burst: f64,
+}
+
+impl Quota {
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        Self {
+            per_second,
+            burst: f64::from(burst.max(1)),
+        }
+    }
+
+    /// Parse `rate` or `rate:burst` (requests per second); the burst defaults to the rate
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (rate, burst) = match spec.trim().split_once(':') {
+            Some((rate, burst)) => (rate.trim().parse().ok()?, burst.trim().parse().ok()?),
+            None => {
+                let rate: f64 = spec.trim().parse().ok()?;
+                (rate, rate.ceil() as u32)
+            }
+        };
+        (rate > 0.0).then(|| Self::new(rate, burst))
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, quota: Quota, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * quota.per_second).min(quota.burst);
+        self.updated = now;
+    }
+
+    /// Time until a whole token is available
+    fn wait(&self, quota: Quota) -> Duration {
+        Duration::from_secs_f64(((1.0 - self.tokens) / quota.per_second).max(0.0))
+    }
+}
+
+/// Token buckets per auth token and per namespace; a request spends one token
+/// from each, and is refused when either is empty
+pub struct RateLimiter {
+    per_token: Option<Quota>,
+    per_namespace: Option<Quota>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(per_token: Option<Quota>, per_namespace: Option<Quota>) -> Self {
+        Self {
+            per_token,
+            per_namespace,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limits from `SYNAPSE_RATE_LIMIT_PER_TOKEN` and `SYNAPSE_RATE_LIMIT_PER_NAMESPACE`
+    /// (`rate` or `rate:burst`); unset means unlimited
+    pub fn from_env() -> Self {
+        let quota = |name: &str| {
+            let spec = std::env::var(name).ok()?;
+            let quota = Quota::parse(&spec);
+            if quota.is_none() {
+                tracing::warn!("Ignoring invalid {}: {}", name, spec);
+            }
+            quota
+        };
+        Self::new(
+            quota("SYNAPSE_RATE_LIMIT_PER_TOKEN"),
+            quota("SYNAPSE_RATE_LIMIT_PER_NAMESPACE"),
+        )
+    }
+
+    /// Spend one request for `token` (anonymous callers share a bucket) in `namespace`
+    pub fn check(&self, token: Option<&str>, namespace: &str) -> Result<(), String> {
+        if CHARGED.try_with(|_| ()).is_ok() {
+            return Ok(());
+        }
+        let limits: Vec<(String, Quota)> = [
+            self.per_token
+                .map(|q| (format!("token:{}", token.unwrap_or("")), q)),
+            self.per_namespace
+                .map(|q| (format!("namespace:{}", namespace), q)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if limits.is_empty() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        for (key, quota) in &limits {
+            let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+                tokens: quota.burst,
+                updated: now,
+            });
+            bucket.refill(*quota, now);
+            if bucket.tokens < 1.0 {
+                let scope = if key.starts_with("token:") {
+                    "this token".to_string()
+                } else {
+                    format!("namespace '{}'", namespace)
+                };
+                return Err(format!(
+                    "Rate limit exceeded for {}; retry in {} ms",
+                    scope,
+                    bucket.wait(*quota).as_millis().max(1)
+                ));
+            }
+        }
+        for (key, _) in &limits {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_parse() {
+        assert_eq!(Quota::parse("5"), Some(Quota::new(5.0, 5)));
+        assert_eq!(Quota::parse("0.5:10"), Some(Quota::new(0.5, 10)));
+        assert_eq!(Quota::parse("0"), None);
+        assert_eq!(Quota::parse("fast"), None);
+    }
+
+    #[test]
+    fn test_buckets_are_per_token_and_namespace() {
+        let limiter = RateLimiter::new(Some(Quota::new(0.001, 2)), Some(Quota::new(0.001, 3)));
+        assert!(limiter.check(Some("a"), "books").is_ok());
+        assert!(limiter.check(Some("a"), "books").is_ok());
+        // Token "a" is out, but "b" still has its own bucket
+        assert!(limiter
+            .check(Some("a"), "films")
+            .unwrap_err()
+            .contains("this token"));
+        assert!(limiter.check(Some("b"), "books").is_ok());
+        // ...until the namespace bucket runs dry
+        assert!(limiter
+            .check(Some("b"), "books")
+            .unwrap_err()
+            .contains("namespace 'books'"));
+        assert!(limiter.check(Some("b"), "films").is_ok());
+    }
+}
@@ -1,6 +1,18 @@
 use anyhow::Result;
-use oxigraph::model::{GraphName, NamedNode, Quad, Subject, Term};
+use oxigraph::model::{GraphName, GraphNameRef, NamedNode, Quad, Subject, Term};
 use oxigraph::store::Store;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_SUBCLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+const OWL_TRANSITIVE_PROPERTY: &str = "http://www.w3.org/2002/07/owl#TransitiveProperty";
+const OWL_SYMMETRIC_PROPERTY: &str = "http://www.w3.org/2002/07/owl#SymmetricProperty";
+const OWL_INVERSE_OF: &str = "http://www.w3.org/2002/07/owl#inverseOf";
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum ReasoningStrategy {
@@ -9,210 +21,598 @@ pub enum ReasoningStrategy {
     OWLRL,
 }
 
+/// How many candidate triples are processed between progress callbacks
+const PROGRESS_INTERVAL: usize = 1000;
+
+/// An inferred (subject, predicate, object) triple
+pub type InferredTriple = (String, String, String);
+
+/// Maximum sample inferences kept per rule
+const RULE_SAMPLE_SIZE: usize = 10;
+
+/// Individual inference rules applied by the reasoner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    SubClassTransitivity,
+    TransitiveProperty,
+    SymmetricProperty,
+    InverseOf,
+}
+
+impl Rule {
+    /// All rules, in evaluation order
+    pub const ALL: [Rule; 4] = [
+        Rule::SubClassTransitivity,
+        Rule::TransitiveProperty,
+        Rule::SymmetricProperty,
+        Rule::InverseOf,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::SubClassTransitivity => "subclass",
+            Rule::TransitiveProperty => "transitive",
+            Rule::SymmetricProperty => "symmetric",
+            Rule::InverseOf => "inverse",
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown rule '{}' (expected one of: subclass, transitive, symmetric, inverse)",
+                    s
+                )
+            })
+    }
+}
+
+/// Set of rules enabled for a reasoning run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: HashSet<Rule>,
+}
+
+impl RuleSet {
+    /// Rules implied by a strategy
+    pub fn for_strategy(strategy: &ReasoningStrategy) -> Self {
+        let rules: &[Rule] = match strategy {
+            ReasoningStrategy::None => &[],
+            ReasoningStrategy::RDFS => &[Rule::SubClassTransitivity],
+            ReasoningStrategy::OWLRL => &[
+                Rule::TransitiveProperty,
+                Rule::SymmetricProperty,
+                Rule::InverseOf,
+            ],
+        };
+        Self {
+            rules: rules.iter().copied().collect(),
+        }
+    }
+
+    pub fn contains(&self, rule: Rule) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Enabled rules, in evaluation order
+    pub fn iter(&self) -> impl Iterator<Item = Rule> + '_ {
+        Rule::ALL.into_iter().filter(|rule| self.contains(*rule))
+    }
+}
+
+impl std::fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.iter().map(|rule| rule.name()).collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = anyhow::Error;
+
+    /// Parse a comma-separated list of rule names, e.g. "subclass,inverse"
+    fn from_str(s: &str) -> Result<Self> {
+        let rules = s
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(Rule::from_str)
+            .collect::<Result<HashSet<_>>>()?;
+        Ok(Self { rules })
+    }
+}
+
+/// Work done by a single rule during a reasoning run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleStats {
+    pub rule: String,
+    pub triples_inferred: usize,
+    pub duration_ms: u64,
+    pub samples: Vec<InferredTriple>,
+}
+
+impl RuleStats {
+    fn new(rule: Rule) -> Self {
+        Self {
+            rule: rule.name().to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, triple: &(String, String, String)) {
+        self.triples_inferred += 1;
+        if self.samples.len() < RULE_SAMPLE_SIZE {
+            self.samples.push(triple.clone());
+        }
+    }
+}
+
+/// Outcome of a materialization run
+#[derive(Debug, Clone, Default)]
+pub struct ReasoningReport {
+    pub triples_inferred: usize,
+    pub rules: Vec<RuleStats>,
+}
+
+/// What materializing would add, without changing the store
+#[derive(Debug, Clone, Default)]
+pub struct ReasoningPreview {
+    pub triples_inferred: usize,
+    /// Up to the requested number of the triples that would be added
+    pub sample: Vec<InferredTriple>,
+    /// Triples the sample was taken from
+    pub total: usize,
+    pub rules: Vec<RuleStats>,
+}
+
+/// Candidate triples produced by one rule in one pass
+struct RuleOutput {
+    rule: Rule,
+    triples: Vec<(String, String, String)>,
+    elapsed: Duration,
+}
+
+/// Progress of a materialization run, reported during each fixed-point pass
+#[derive(Debug, Clone, Default)]
+pub struct ReasoningProgress {
+    /// Current fixed-point pass (1-based)
+    pub iteration: usize,
+    /// Candidate triples processed in the current pass
+    pub processed: usize,
+    /// Candidate triples produced by the current pass
+    pub total: usize,
+    /// New triples persisted so far (across all passes)
+    pub triples_inferred: usize,
+}
+
+impl ReasoningProgress {
+    /// Estimated completion. Each pass covers half of the remaining range, since the
+    /// number of passes needed to reach the fixed point is not known in advance.
+    pub fn percent_complete(&self) -> f32 {
+        let pass_fraction = if self.total == 0 {
+            1.0
+        } else {
+            self.processed as f32 / self.total as f32
+        };
+        let done_before = 1.0 - 0.5f32.powi(self.iteration.saturating_sub(1) as i32);
+        let pass_span = 0.5f32.powi(self.iteration as i32);
+        ((done_before + pass_span * pass_fraction) * 100.0).min(99.0)
+    }
+}
+
 pub struct SynapseReasoner {
     pub strategy: ReasoningStrategy,
+    /// Rules actually evaluated; defaults to those implied by the strategy
+    pub rules: RuleSet,
+    cancel: Option<CancellationToken>,
 }
 
 impl SynapseReasoner {
     pub fn new(strategy: ReasoningStrategy) -> Self {
-        Self { strategy }
+        let rules = RuleSet::for_strategy(&strategy);
+        Self {
+            strategy,
+            rules,
+            cancel: None,
+        }
+    }
+
+    /// Stop materializing (between progress reports) once `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(anyhow::anyhow!("Reasoning cancelled"));
+        }
+        Ok(())
+    }
+
+    /// Evaluate exactly the given rules instead of those implied by the strategy
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Label for audit records, e.g. "OWLRL" or "OWLRL[subclass,inverse]" when rules were overridden
+    pub fn label(&self) -> String {
+        if self.rules == RuleSet::for_strategy(&self.strategy) {
+            format!("{:?}", self.strategy)
+        } else {
+            format!("{:?}[{}]", self.strategy, self.rules)
+        }
     }
 
     /// Apply reasoning to a store and return inferred triples (without inserting)
     pub fn apply(&self, store: &Store) -> Result<Vec<(String, String, String)>> {
-        let mut inferred = Vec::new();
+        Ok(self
+            .evaluate(store)?
+            .into_iter()
+            .flat_map(|output| output.triples)
+            .collect())
+    }
 
-        match self.strategy {
-            ReasoningStrategy::None => {}
-            ReasoningStrategy::RDFS => {
-                // RDFS: SubClassOf Transitivity
-                // If A subClassOf B, and B subClassOf C -> A subClassOf C
-                let subclass_prop =
-                    NamedNode::new("http://www.w3.org/2000/01/rdf-schema#subClassOf")?;
+    /// Like `apply`, but also report how many triples each rule produced and how long it took
+    pub fn apply_with_stats(&self, store: &Store) -> Result<(Vec<InferredTriple>, Vec<RuleStats>)> {
+        let mut inferred = Vec::new();
+        let mut stats = Vec::new();
 
-                for q1 in store
-                    .quads_for_pattern(None, Some(subclass_prop.as_ref()), None, None)
-                    .flatten()
-                {
-                    if let Subject::NamedNode(a) = q1.subject {
-                        if let Term::NamedNode(b) = q1.object {
-                            for q2 in store
-                                .quads_for_pattern(
-                                    Some(b.as_ref().into()),
-                                    Some(subclass_prop.as_ref()),
-                                    None,
-                                    None,
-                                )
-                                .flatten()
-                            {
-                                if let Term::NamedNode(c) = q2.object {
-                                    inferred.push((
-                                        a.as_str().to_string(),
-                                        subclass_prop.as_str().to_string(),
-                                        c.as_str().to_string(),
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                }
+        for output in self.evaluate(store)? {
+            let mut rule_stats = RuleStats::new(output.rule);
+            rule_stats.duration_ms = output.elapsed.as_millis() as u64;
+            for triple in &output.triples {
+                rule_stats.record(triple);
             }
-            ReasoningStrategy::OWLRL => {
-                // OWL-RL: TransitiveProperty
-                // If p is TransitiveProperty, and x p y, y p z -> x p z
-                let type_prop = NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type")?;
-                let transitive_class =
-                    NamedNode::new("http://www.w3.org/2002/07/owl#TransitiveProperty")?;
-
-                // Find all transitive properties
-                for q in store
-                    .quads_for_pattern(
-                        None,
-                        Some(type_prop.as_ref()),
-                        Some(transitive_class.as_ref().into()),
-                        None,
-                    )
-                    .flatten()
-                {
-                    if let Subject::NamedNode(p_node) = q.subject {
-                        let p_ref = p_node.as_ref();
-
-                        // Naive transitive: x p y ("xy")
-                        for xy_quad in store
-                            .quads_for_pattern(None, Some(p_ref), None, None)
-                            .flatten()
-                        {
-                            if let Subject::NamedNode(x) = xy_quad.subject {
-                                if let Term::NamedNode(y) = xy_quad.object {
-                                    // Find y p z ("yz")
-                                    for yz_quad in store
-                                        .quads_for_pattern(
-                                            Some(y.as_ref().into()),
-                                            Some(p_ref),
-                                            None,
-                                            None,
-                                        )
-                                        .flatten()
-                                    {
-                                        if let Term::NamedNode(z) = yz_quad.object {
-                                            inferred.push((
-                                                x.as_str().to_string(),
-                                                p_node.as_str().to_string(),
-                                                z.as_str().to_string(),
-                                            ));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+            stats.push(rule_stats);
+            inferred.extend(output.triples);
+        }
+
+        Ok((inferred, stats))
+    }
+
+    /// Run every enabled rule once, timing each
+    fn evaluate(&self, store: &Store) -> Result<Vec<RuleOutput>> {
+        // Each rule instance only touches the triples of its own property,
+        // so the work is independent and can be spread across the pool.
+        self.rules
+            .iter()
+            .map(|rule| match rule {
+                Rule::SubClassTransitivity => {
+                    Self::timed(rule, || Self::subclass_transitivity(store))
                 }
+                Rule::TransitiveProperty => Self::timed(rule, || {
+                    Ok(Self::properties_of_type(store, OWL_TRANSITIVE_PROPERTY)?
+                        .par_iter()
+                        .flat_map_iter(|p| Self::transitive_property(store, p))
+                        .collect())
+                }),
+                Rule::SymmetricProperty => Self::timed(rule, || {
+                    Ok(Self::properties_of_type(store, OWL_SYMMETRIC_PROPERTY)?
+                        .par_iter()
+                        .flat_map_iter(|p| Self::symmetric_property(store, p))
+                        .collect())
+                }),
+                Rule::InverseOf => Self::timed(rule, || {
+                    Ok(Self::inverse_pairs(store)?
+                        .par_iter()
+                        .flat_map_iter(|(p1, p2)| Self::inverse_of(store, p1, p2))
+                        .collect())
+                }),
+            })
+            .collect()
+    }
 
-                // OWL-RL: SymmetricProperty
-                // If p is SymmetricProperty, and x p y -> y p x
-                let symmetric_class =
-                    NamedNode::new("http://www.w3.org/2002/07/owl#SymmetricProperty")?;
+    fn timed<F>(rule: Rule, f: F) -> Result<RuleOutput>
+    where
+        F: FnOnce() -> Result<Vec<(String, String, String)>>,
+    {
+        let start = Instant::now();
+        let triples = f()?;
+        Ok(RuleOutput {
+            rule,
+            triples,
+            elapsed: start.elapsed(),
+        })
+    }
 
-                for q in store
+    /// RDFS: SubClassOf Transitivity
+    /// If A subClassOf B, and B subClassOf C -> A subClassOf C
+    fn subclass_transitivity(store: &Store) -> Result<Vec<(String, String, String)>> {
+        let subclass_prop = NamedNode::new(RDFS_SUBCLASS_OF)?;
+
+        let edges: Vec<(NamedNode, NamedNode)> = store
+            .quads_for_pattern(None, Some(subclass_prop.as_ref()), None, None)
+            .flatten()
+            .filter_map(|q| match (q.subject, q.object) {
+                (Subject::NamedNode(a), Term::NamedNode(b)) => Some((a, b)),
+                _ => None,
+            })
+            .collect();
+
+        Ok(edges
+            .par_iter()
+            .flat_map_iter(|(a, b)| {
+                store
                     .quads_for_pattern(
+                        Some(b.as_ref().into()),
+                        Some(subclass_prop.as_ref()),
                         None,
-                        Some(type_prop.as_ref()),
-                        Some(symmetric_class.as_ref().into()),
                         None,
                     )
                     .flatten()
-                {
-                    if let Subject::NamedNode(p_node) = q.subject {
-                        let p_ref = p_node.as_ref();
-
-                        for e in store
-                            .quads_for_pattern(None, Some(p_ref), None, None)
-                            .flatten()
-                        {
-                            // Infer: y p x
-                            if let Subject::NamedNode(s_node) = e.subject {
-                                if let Term::NamedNode(obj_node) = e.object {
-                                    inferred.push((
-                                        obj_node.as_str().to_string(),
-                                        p_node.as_str().to_string(),
-                                        s_node.as_str().to_string(),
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                }
+                    .filter_map(|q2| match q2.object {
+                        Term::NamedNode(c) => Some((
+                            a.as_str().to_string(),
+                            RDFS_SUBCLASS_OF.to_string(),
+                            c.as_str().to_string(),
+                        )),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect())
+    }
 
-                // OWL-RL: inverseOf
-                // If p1 inverseOf p2, and x p1 y -> y p2 x
-                let inverse_prop = NamedNode::new("http://www.w3.org/2002/07/owl#inverseOf")?;
+    /// All properties declared with `rdf:type <class>`
+    fn properties_of_type(store: &Store, class: &str) -> Result<Vec<NamedNode>> {
+        let type_prop = NamedNode::new(RDF_TYPE)?;
+        let class_node = NamedNode::new(class)?;
+
+        Ok(store
+            .quads_for_pattern(
+                None,
+                Some(type_prop.as_ref()),
+                Some(class_node.as_ref().into()),
+                None,
+            )
+            .flatten()
+            .filter_map(|q| match q.subject {
+                Subject::NamedNode(p) => Some(p),
+                _ => None,
+            })
+            .collect())
+    }
 
-                for q in store
-                    .quads_for_pattern(None, Some(inverse_prop.as_ref()), None, None)
-                    .flatten()
-                {
-                    if let Subject::NamedNode(p1_node) = q.subject {
-                        let p1_ref = p1_node.as_ref();
-                        if let Term::NamedNode(p2_node) = q.object {
-                            // p1 inverseOf p2. For every x p1 y, infer y p2 x
-                            for e in store
-                                .quads_for_pattern(None, Some(p1_ref), None, None)
-                                .flatten()
-                            {
-                                if let Subject::NamedNode(x) = e.subject {
-                                    if let Term::NamedNode(y) = e.object {
-                                        inferred.push((
-                                            y.as_str().to_string(),
-                                            p2_node.as_str().to_string(),
-                                            x.as_str().to_string(),
-                                        ));
-                                    }
-                                }
-                            }
+    /// All `p1 owl:inverseOf p2` declarations
+    fn inverse_pairs(store: &Store) -> Result<Vec<(NamedNode, NamedNode)>> {
+        let inverse_prop = NamedNode::new(OWL_INVERSE_OF)?;
+
+        Ok(store
+            .quads_for_pattern(None, Some(inverse_prop.as_ref()), None, None)
+            .flatten()
+            .filter_map(|q| match (q.subject, q.object) {
+                (Subject::NamedNode(p1), Term::NamedNode(p2)) => Some((p1, p2)),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// OWL-RL: TransitiveProperty
+    /// If p is TransitiveProperty, and x p y, y p z -> x p z
+    fn transitive_property(store: &Store, p_node: &NamedNode) -> Vec<(String, String, String)> {
+        let p_ref = p_node.as_ref();
+        let mut inferred = Vec::new();
+
+        // Naive transitive: x p y ("xy")
+        for xy_quad in store
+            .quads_for_pattern(None, Some(p_ref), None, None)
+            .flatten()
+        {
+            if let Subject::NamedNode(x) = xy_quad.subject {
+                if let Term::NamedNode(y) = xy_quad.object {
+                    // Find y p z ("yz")
+                    for yz_quad in store
+                        .quads_for_pattern(Some(y.as_ref().into()), Some(p_ref), None, None)
+                        .flatten()
+                    {
+                        if let Term::NamedNode(z) = yz_quad.object {
+                            inferred.push((
+                                x.as_str().to_string(),
+                                p_node.as_str().to_string(),
+                                z.as_str().to_string(),
+                            ));
                         }
                     }
                 }
             }
         }
 
-        Ok(inferred)
+        inferred
+    }
+
+    /// OWL-RL: SymmetricProperty
+    /// If p is SymmetricProperty, and x p y -> y p x
+    fn symmetric_property(store: &Store, p_node: &NamedNode) -> Vec<(String, String, String)> {
+        store
+            .quads_for_pattern(None, Some(p_node.as_ref()), None, None)
+            .flatten()
+            .filter_map(|e| match (e.subject, e.object) {
+                (Subject::NamedNode(s_node), Term::NamedNode(obj_node)) => Some((
+                    obj_node.as_str().to_string(),
+                    p_node.as_str().to_string(),
+                    s_node.as_str().to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// OWL-RL: inverseOf
+    /// If p1 inverseOf p2, and x p1 y -> y p2 x
+    fn inverse_of(
+        store: &Store,
+        p1_node: &NamedNode,
+        p2_node: &NamedNode,
+    ) -> Vec<(String, String, String)> {
+        store
+            .quads_for_pattern(None, Some(p1_node.as_ref()), None, None)
+            .flatten()
+            .filter_map(|e| match (e.subject, e.object) {
+                (Subject::NamedNode(x), Term::NamedNode(y)) => Some((
+                    y.as_str().to_string(),
+                    p2_node.as_str().to_string(),
+                    x.as_str().to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
     }
 
     /// Apply reasoning and persist inferred triples
     pub fn materialize(&self, store: &Store) -> Result<usize> {
+        Ok(self.materialize_with_stats(store)?.triples_inferred)
+    }
+
+    /// Apply reasoning and persist inferred triples, with per-rule statistics
+    pub fn materialize_with_stats(&self, store: &Store) -> Result<ReasoningReport> {
+        self.materialize_with_progress(store, |_| {})
+    }
+
+    /// Run materialization to its fixed point on an in-memory copy of the store, so
+    /// counts and samples are exactly what `materialize` would add
+    pub fn preview(&self, store: &Store, sample_size: usize) -> Result<ReasoningPreview> {
+        self.preview_page(store, 0, sample_size)
+    }
+
+    /// Like `preview`, with the sample being the page at `offset` of all the triples
+    /// that would be added, sorted
+    pub fn preview_page(
+        &self,
+        store: &Store,
+        offset: usize,
+        limit: usize,
+    ) -> Result<ReasoningPreview> {
+        let scratch = Store::new()?;
+        scratch.extend(store.iter().collect::<std::result::Result<Vec<_>, _>>()?)?;
+        let report = self.materialize_with_stats(&scratch)?;
+
+        let mut added = Vec::new();
+        for quad in scratch.quads_for_pattern(None, None, None, Some(GraphNameRef::DefaultGraph)) {
+            let quad = quad?;
+            if store.contains(&quad)? {
+                continue;
+            }
+            if let (Subject::NamedNode(s), Term::NamedNode(o)) = (&quad.subject, &quad.object) {
+                added.push((
+                    s.as_str().to_string(),
+                    quad.predicate.as_str().to_string(),
+                    o.as_str().to_string(),
+                ));
+            }
+        }
+        added.sort();
+
+        Ok(ReasoningPreview {
+            triples_inferred: report.triples_inferred,
+            total: added.len(),
+            sample: added.into_iter().skip(offset).take(limit).collect(),
+            rules: report.rules,
+        })
+    }
+
+    /// Apply reasoning and persist inferred triples, reporting progress as candidates are processed
+    pub fn materialize_with_progress<F>(
+        &self,
+        store: &Store,
+        mut on_progress: F,
+    ) -> Result<ReasoningReport>
+    where
+        F: FnMut(&ReasoningProgress),
+    {
         let mut total_inferred = 0;
+        let mut iteration = 0;
+        let mut rules: Vec<RuleStats> = Vec::new();
 
         // Fixed-point iteration loop
         loop {
-            let inferred = self.apply(store)?;
-            if inferred.is_empty() {
+            iteration += 1;
+            self.check_cancelled()?;
+            let outputs = self.evaluate(store)?;
+            let total: usize = outputs.iter().map(|o| o.triples.len()).sum();
+            if total == 0 {
                 break;
             }
 
-            let mut new_triples = 0;
-            for (s, p, o) in inferred {
-                let s_node = NamedNode::new(s)?;
-                let p_node = NamedNode::new(p)?;
-                let o_node = NamedNode::new(o)?;
-
-                let quad = Quad::new(s_node, p_node, o_node, GraphName::DefaultGraph);
-
-                // Only count if actually new
-                // Note: store.contains checks exact match including graph name.
-                // We insert into DefaultGraph.
-                if !store.contains(&quad)? {
-                    store.insert(&quad)?;
-                    new_triples += 1;
+            let mut processed = 0;
+            let mut seen = HashSet::new();
+            let mut batch = Vec::new();
+            for output in outputs {
+                let idx = match rules.iter().position(|r| r.rule == output.rule.name()) {
+                    Some(idx) => idx,
+                    None => {
+                        rules.push(RuleStats::new(output.rule));
+                        rules.len() - 1
+                    }
+                };
+                rules[idx].duration_ms += output.elapsed.as_millis() as u64;
+
+                for triple in output.triples {
+                    if processed % PROGRESS_INTERVAL == 0 {
+                        // Nothing from this pass is written yet, so stopping here is clean
+                        self.check_cancelled()?;
+                        on_progress(&ReasoningProgress {
+                            iteration,
+                            processed,
+                            total,
+                            triples_inferred: total_inferred + batch.len(),
+                        });
+                    }
+                    processed += 1;
+
+                    let s_node = NamedNode::new(triple.0.as_str())?;
+                    let p_node = NamedNode::new(triple.1.as_str())?;
+                    let o_node = NamedNode::new(triple.2.as_str())?;
+
+                    let quad = Quad::new(s_node, p_node, o_node, GraphName::DefaultGraph);
+
+                    // Only count if actually new
+                    // Note: store.contains checks exact match including graph name.
+                    // We insert into DefaultGraph.
+                    if !seen.contains(&quad) && !store.contains(&quad)? {
+                        seen.insert(quad.clone());
+                        batch.push(quad);
+                        // Credited to the first rule that produced the triple
+                        rules[idx].record(&triple);
+                    }
                 }
             }
 
+            // Insert the whole pass in one bulk write
+            let new_triples = batch.len();
+            if new_triples > 0 {
+                store.extend(batch)?;
+            }
+
+            total_inferred += new_triples;
+            on_progress(&ReasoningProgress {
+                iteration,
+                processed: total,
+                total,
+                triples_inferred: total_inferred,
+            });
+
             if new_triples == 0 {
                 break;
             }
-            total_inferred += new_triples;
         }
 
-        Ok(total_inferred)
+        Ok(ReasoningReport {
+            triples_inferred: total_inferred,
+            rules,
+        })
     }
 }
 
@@ -299,4 +699,201 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_owl_symmetric_and_inverse_materialize() -> Result<()> {
+        let store = Store::new()?;
+        let reasoner = SynapseReasoner::new(ReasoningStrategy::OWLRL);
+
+        let type_prop = NamedNode::new(RDF_TYPE)?;
+        let knows = NamedNode::new("http://example.org/knows")?;
+        let parent_of = NamedNode::new("http://example.org/parentOf")?;
+        let child_of = NamedNode::new("http://example.org/childOf")?;
+        let alice = NamedNode::new("http://example.org/alice")?;
+        let bob = NamedNode::new("http://example.org/bob")?;
+
+        for quad in [
+            Quad::new(
+                knows.clone(),
+                type_prop,
+                NamedNode::new(OWL_SYMMETRIC_PROPERTY)?,
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                parent_of.clone(),
+                NamedNode::new(OWL_INVERSE_OF)?,
+                child_of.clone(),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                alice.clone(),
+                knows.clone(),
+                bob.clone(),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                alice.clone(),
+                parent_of,
+                bob.clone(),
+                GraphName::DefaultGraph,
+            ),
+        ] {
+            store.insert(&quad)?;
+        }
+
+        let count = reasoner.materialize(&store)?;
+        assert_eq!(count, 2);
+        assert!(store.contains(&Quad::new(
+            bob.clone(),
+            knows,
+            alice.clone(),
+            GraphName::DefaultGraph
+        ))?);
+        assert!(store.contains(&Quad::new(bob, child_of, alice, GraphName::DefaultGraph))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_materialize_reports_progress() -> Result<()> {
+        let store = Store::new()?;
+        let reasoner = SynapseReasoner::new(ReasoningStrategy::RDFS);
+        let sub_class_of = NamedNode::new("http://www.w3.org/2000/01/rdf-schema#subClassOf")?;
+
+        // Chain A0 -> A1 -> ... -> A4 requires several fixed-point passes
+        for i in 0..4 {
+            store.insert(&Quad::new(
+                NamedNode::new(format!("http://example.org/A{}", i))?,
+                sub_class_of.clone(),
+                NamedNode::new(format!("http://example.org/A{}", i + 1))?,
+                GraphName::DefaultGraph,
+            ))?;
+        }
+
+        let mut reports = Vec::new();
+        let count = reasoner
+            .materialize_with_progress(&store, |p| reports.push(p.clone()))?
+            .triples_inferred;
+
+        assert!(count > 0);
+        assert!(!reports.is_empty());
+        let last = reports.last().unwrap();
+        assert_eq!(last.triples_inferred, count);
+        assert!(last.percent_complete() <= 99.0);
+        // Progress estimates never go backwards
+        for pair in reports.windows(2) {
+            assert!(pair[1].percent_complete() >= pair[0].percent_complete());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_materialize_reports_rule_stats() -> Result<()> {
+        let store = Store::new()?;
+        let reasoner = SynapseReasoner::new(ReasoningStrategy::OWLRL);
+
+        let knows = NamedNode::new("http://example.org/knows")?;
+        let alice = NamedNode::new("http://example.org/alice")?;
+        let bob = NamedNode::new("http://example.org/bob")?;
+
+        store.insert(&Quad::new(
+            knows.clone(),
+            NamedNode::new(RDF_TYPE)?,
+            NamedNode::new(OWL_SYMMETRIC_PROPERTY)?,
+            GraphName::DefaultGraph,
+        ))?;
+        store.insert(&Quad::new(
+            alice.clone(),
+            knows.clone(),
+            bob.clone(),
+            GraphName::DefaultGraph,
+        ))?;
+
+        let report = reasoner.materialize_with_stats(&store)?;
+        assert_eq!(report.triples_inferred, 1);
+
+        let symmetric = report
+            .rules
+            .iter()
+            .find(|r| r.rule == "symmetric")
+            .expect("symmetric rule stats");
+        assert_eq!(symmetric.triples_inferred, 1);
+        assert_eq!(
+            symmetric.samples,
+            vec![(
+                bob.as_str().to_string(),
+                knows.as_str().to_string(),
+                alice.as_str().to_string()
+            )]
+        );
+        assert!(report
+            .rules
+            .iter()
+            .filter(|r| r.rule != "symmetric")
+            .all(|r| r.triples_inferred == 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rule_set_selection() -> Result<()> {
+        let rules: RuleSet = " subclass, Inverse ,".parse()?;
+        assert!(rules.contains(Rule::SubClassTransitivity));
+        assert!(rules.contains(Rule::InverseOf));
+        assert!(!rules.contains(Rule::SymmetricProperty));
+        assert_eq!(rules.to_string(), "subclass,inverse");
+        assert!("subclass,bogus".parse::<RuleSet>().is_err());
+
+        let store = Store::new()?;
+        let knows = NamedNode::new("http://example.org/knows")?;
+        store.insert(&Quad::new(
+            knows.clone(),
+            NamedNode::new(RDF_TYPE)?,
+            NamedNode::new(OWL_SYMMETRIC_PROPERTY)?,
+            GraphName::DefaultGraph,
+        ))?;
+        store.insert(&Quad::new(
+            NamedNode::new("http://example.org/alice")?,
+            knows,
+            NamedNode::new("http://example.org/bob")?,
+            GraphName::DefaultGraph,
+        ))?;
+
+        // OWL-RL with the symmetric rule switched off infers nothing here
+        let reasoner = SynapseReasoner::new(ReasoningStrategy::OWLRL).with_rules(rules);
+        assert!(reasoner.apply(&store)?.is_empty());
+        assert_eq!(reasoner.label(), "OWLRL[subclass,inverse]");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview_leaves_store_unchanged() -> Result<()> {
+        let store = Store::new()?;
+        let sub_class_of = NamedNode::new(RDFS_SUBCLASS_OF)?;
+        for (a, b) in [("A", "B"), ("B", "C"), ("C", "D")] {
+            store.insert(&Quad::new(
+                NamedNode::new(format!("http://example.org/{}", a))?,
+                sub_class_of.clone(),
+                NamedNode::new(format!("http://example.org/{}", b))?,
+                GraphName::DefaultGraph,
+            ))?;
+        }
+
+        let reasoner = SynapseReasoner::new(ReasoningStrategy::RDFS);
+        let preview = reasoner.preview(&store, 2)?;
+        // A-C, B-D, A-D, the last only reached in the second pass
+        assert_eq!(preview.triples_inferred, 3);
+        assert_eq!(preview.sample.len(), 2);
+        assert_eq!(preview.rules[0].triples_inferred, 3);
+        assert_eq!(store.len()?, 3);
+
+        let page = reasoner.preview_page(&store, 2, 10)?;
+        assert_eq!(page.sample.len(), 1);
+        assert!(!preview.sample.contains(&page.sample[0]));
+
+        assert_eq!(reasoner.materialize(&store)?, 3);
+        Ok(())
+    }
 }
@@ -0,0 +1,184 @@
+//! Copies of namespaces and backups in an object store (S3, GCS), so deployments
+//! without durable local disks can get their data back after losing a machine.
+
+use anyhow::{Context, Result};
+use futures::TryStreamExt;
+use object_store::buffered::BufWriter;
+use object_store::path::Path as ObjectPath;
+use object_store::prefix::PrefixStore;
+use object_store::ObjectStore;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+/// Where namespace copies live, one directory per namespace
+pub const NAMESPACES_PREFIX: &str = "namespaces";
+
+/// Where backups live, one directory per backup
+pub const BACKUPS_PREFIX: &str = "backups";
+
+/// An object store rooted at a bucket prefix
+pub struct RemoteStorage {
+    store: Arc<dyn ObjectStore>,
+    url: String,
+}
+
+impl RemoteStorage {
+    /// Backend for `s3://bucket/prefix`, `gs://bucket/prefix`, `file:///dir` or
+    /// `memory://`. Credentials and regions come from the usual `AWS_*` and
+    /// `GOOGLE_*` variables.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .with_context(|| format!("Not an object store URL: {}", url))?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let store: Arc<dyn ObjectStore> = match scheme {
+            "s3" => Arc::new(
+                object_store::aws::AmazonS3Builder::from_env()
+                    .with_url(format!("s3://{}", bucket))
+                    .build()?,
+            ),
+            "gs" => Arc::new(
+                object_store::gcp::GoogleCloudStorageBuilder::from_env()
+                    .with_url(format!("gs://{}", bucket))
+                    .build()?,
+            ),
+            "file" => {
+                std::fs::create_dir_all(rest)?;
+                return Ok(Self {
+                    store: Arc::new(object_store::local::LocalFileSystem::new_with_prefix(rest)?),
+                    url: url.to_string(),
+                });
+            }
+            "memory" => Arc::new(object_store::memory::InMemory::new()),
+            _ => anyhow::bail!("Unsupported object store scheme: {}", scheme),
+        };
+        let store = match prefix.trim_matches('/') {
+            "" => store,
+            prefix => Arc::new(PrefixStore::new(store, prefix)),
+        };
+        Ok(Self {
+            store,
+            url: url.to_string(),
+        })
+    }
+
+    /// Backend from `SYNAPSE_OBJECT_STORE`, if set
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var("SYNAPSE_OBJECT_STORE") {
+            Ok(url) if !url.trim().is_empty() => Ok(Some(Self::from_url(url.trim())?)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Make `prefix` a copy of the files under `dir`: upload each one and delete
+    /// objects with no local file. Returns the bytes uploaded.
+    pub async fn mirror_dir(&self, dir: &Path, prefix: &str) -> Result<u64> {
+        let mut uploaded = 0;
+        let mut names = HashSet::new();
+        for (name, path) in files_under(dir)? {
+            let mut file = tokio::fs::File::open(&path).await?;
+            let location = ObjectPath::from(format!("{}/{}", prefix, name));
+            let mut writer = BufWriter::new(self.store.clone(), location.clone());
+            uploaded += tokio::io::copy(&mut file, &mut writer).await?;
+            writer.shutdown().await?;
+            names.insert(location);
+        }
+        let stale: Vec<ObjectPath> = self
+            .store
+            .list(Some(&ObjectPath::from(prefix)))
+            .map_ok(|meta| meta.location)
+            .try_filter(|location| std::future::ready(!names.contains(location)))
+            .try_collect()
+            .await?;
+        for location in stale {
+            self.store.delete(&location).await?;
+        }
+        Ok(uploaded)
+    }
+
+    /// Download every object under `prefix` into `dir`, returning the bytes downloaded
+    pub async fn download_dir(&self, prefix: &str, dir: &Path) -> Result<u64> {
+        let prefix = ObjectPath::from(prefix);
+        let objects: Vec<_> = self.store.list(Some(&prefix)).try_collect().await?;
+        let mut downloaded = 0;
+        for meta in objects {
+            let Some(parts) = meta.location.prefix_match(&prefix) else {
+                continue;
+            };
+            let target = parts.fold(dir.to_path_buf(), |path, part| path.join(part.as_ref()));
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut file = tokio::fs::File::create(&target).await?;
+            let mut stream = self.store.get(&meta.location).await?.into_stream();
+            while let Some(chunk) = stream.try_next().await? {
+                file.write_all(&chunk).await?;
+                downloaded += chunk.len() as u64;
+            }
+            file.flush().await?;
+        }
+        Ok(downloaded)
+    }
+
+    /// Names of the directories directly under `prefix` holding at least one object
+    pub async fn list_dirs(&self, prefix: &str) -> Result<Vec<String>> {
+        let prefix = ObjectPath::from(prefix);
+        let objects: Vec<_> = self.store.list(Some(&prefix)).try_collect().await?;
+        let mut names: Vec<String> = objects
+            .iter()
+            .filter_map(|meta| {
+                let mut parts = meta.location.prefix_match(&prefix)?;
+                let dir = parts.next()?;
+                // Objects directly under `prefix` are not directories
+                parts.next()?;
+                Some(dir.as_ref().to_string())
+            })
+            .collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// Delete every object under `prefix`, returning how many there were
+    pub async fn delete_dir(&self, prefix: &str) -> Result<usize> {
+        let locations: Vec<ObjectPath> = self
+            .store
+            .list(Some(&ObjectPath::from(prefix)))
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await?;
+        for location in &locations {
+            self.store.delete(location).await?;
+        }
+        Ok(locations.len())
+    }
+}
+
+/// Files under `dir` with their `/`-separated paths relative to it
+fn files_under(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((name, path));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
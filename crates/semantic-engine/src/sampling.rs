@@ -0,0 +1,172 @@
+//! Prompts for MCP sampling (`sampling/createMessage`), where the client's LLM
+//! writes SPARQL or extracts triples for us, and parsers for its replies.
+
+use crate::store::SynapseStore;
+use anyhow::Result;
+use regex::Regex;
+
+/// Predicates and classes used in a namespace, most frequent first
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    pub predicates: Vec<String>,
+    pub classes: Vec<String>,
+}
+
+impl Vocabulary {
+    pub fn of(store: &SynapseStore) -> Result<Self> {
+        let column = |query: &str, variable: &str| -> Result<Vec<String>> {
+            let rows: Vec<serde_json::Value> = serde_json::from_str(&store.query_sparql(query)?)?;
+            Ok(rows
+                .iter()
+                .filter_map(|row| row[variable].as_str().map(String::from))
+                .collect())
+        };
+        Ok(Self {
+            predicates: column(
+                "SELECT ?p (COUNT(*) AS ?n) WHERE { { ?s ?p ?o } UNION { GRAPH ?g { ?s ?p ?o } } } \
+                 GROUP BY ?p ORDER BY DESC(?n) LIMIT 50",
+                "?p",
+            )?,
+            classes: column(
+                "SELECT ?c (COUNT(*) AS ?n) WHERE { { ?s a ?c } UNION { GRAPH ?g { ?s a ?c } } } \
+                 GROUP BY ?c ORDER BY DESC(?n) LIMIT 30",
+                "?c",
+            )?,
+        })
+    }
+}
+
+pub const SPARQL_SYSTEM_PROMPT: &str = "You translate questions into SPARQL 1.1 queries \
+    over an RDF knowledge graph. Reply with a single read-only query (SELECT, ASK, CONSTRUCT \
+    or DESCRIBE) and nothing else.";
+
+pub fn sparql_prompt(question: &str, vocabulary: &Vocabulary) -> String {
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            "(none)".to_string()
+        } else {
+            items.join("\n")
+        }
+    };
+    format!(
+        "Ingested triples are stored in named graphs (one per batch), so wrap triple \
+         patterns in `GRAPH ?g {{ ... }}`; triples without provenance are in the default \
+         graph, so use `{{ ... }} UNION {{ GRAPH ?g {{ ... }} }}` when unsure.\n\nPredicates in use:\n{}\n\nClasses in use:\n{}\n\n\
+         Question: {}",
+        list(&vocabulary.predicates),
+        list(&vocabulary.classes),
+        question
+    )
+}
+
+/// The query in an LLM reply, without Markdown fences or surrounding prose
+pub fn extract_sparql(reply: &str) -> Option<String> {
+    let body = fenced(reply).unwrap_or(reply);
+    let keyword_re = Regex::new(r"(?i)\b(PREFIX|BASE|SELECT|ASK|CONSTRUCT|DESCRIBE)\b").unwrap();
+    let start = keyword_re.find(body)?.start();
+    Some(body[start..].trim().to_string())
+}
+
+pub const TRIPLES_SYSTEM_PROMPT: &str = "You extract facts from text as RDF-style triples. \
+    Reply with a JSON array only.";
+
+pub fn triples_prompt(text: &str) -> String {
+    format!(
+        "Extract the factual statements in the text below as a JSON array of objects with \
+         \"subject\", \"predicate\" and \"object\" strings. Use short camelCase predicates \
+         (e.g. \"worksFor\"). Set \"literal\": true when the object is a value such as a \
+         date, number or quote rather than an entity.\n\nText:\n{}",
+        text
+    )
+}
+
+/// Triples from an LLM reply to `triples_prompt`. Literal objects are quoted; other
+/// terms are left for the store to resolve into URIs. Malformed entries are skipped.
+pub fn parse_triples(reply: &str) -> Vec<(String, String, String)> {
+    let body = fenced(reply).unwrap_or(reply);
+    let (Some(start), Some(end)) = (body.find('['), body.rfind(']')) else {
+        return Vec::new();
+    };
+    let Ok(serde_json::Value::Array(items)) = serde_json::from_str(&body[start..=end]) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let term = |key: &str| {
+                item[key]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+            };
+            let (subject, predicate, object) =
+                (term("subject")?, term("predicate")?, term("object")?);
+            let object = if item["literal"].as_bool().unwrap_or(false) {
+                format!("\"{}\"", object.replace('"', "'"))
+            } else {
+                object.replace(' ', "_")
+            };
+            Some((
+                subject.replace(' ', "_"),
+                predicate.replace(' ', "_"),
+                object,
+            ))
+        })
+        .collect()
+}
+
+/// The contents of the first Markdown code fence, if any
+fn fenced(reply: &str) -> Option<&str> {
+    let start = reply.find("```")?;
+    let after = &reply[start + 3..];
+    // Skip the info string (e.g. "sparql" or "json")
+    let body = &after[after.find('\n')? + 1..];
+    Some(&body[..body.find("```").unwrap_or(body.len())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_sparql_from_fenced_reply() {
+        let reply = "Here you go:\n```sparql\nSELECT ?s WHERE { GRAPH ?g { ?s ?p ?o } }\n```\n";
+        assert_eq!(
+            extract_sparql(reply).unwrap(),
+            "SELECT ?s WHERE { GRAPH ?g { ?s ?p ?o } }"
+        );
+        assert_eq!(
+            extract_sparql("ASK { ?s ?p ?o }").unwrap(),
+            "ASK { ?s ?p ?o }"
+        );
+        assert!(extract_sparql("Based on the task, I don't know").is_none());
+    }
+
+    #[test]
+    fn test_parse_triples() {
+        let reply = r#"```json
+[
+  {"subject": "Ada Lovelace", "predicate": "bornIn", "object": "London"},
+  {"subject": "Ada Lovelace", "predicate": "birthYear", "object": "1815", "literal": true},
+  {"subject": "", "predicate": "x", "object": "y"}
+]
+```"#;
+        assert_eq!(
+            parse_triples(reply),
+            vec![
+                (
+                    "Ada_Lovelace".to_string(),
+                    "bornIn".to_string(),
+                    "London".to_string()
+                ),
+                (
+                    "Ada_Lovelace".to_string(),
+                    "birthYear".to_string(),
+                    "\"1815\"".to_string()
+                ),
+            ]
+        );
+        assert!(parse_triples("no facts").is_empty());
+    }
+}
@@ -106,7 +106,7 @@ impl ScenarioManager {
     async fn install_from_local_path(&self, source: &Path, dest: &Path) -> Result<PathBuf> {
         // Prevent self-copy
         if source.canonicalize()? == dest.canonicalize().unwrap_or(dest.to_path_buf()) {
-            eprintln!("Source and destination are the same, skipping copy.");
+            tracing::debug!("Source and destination are the same, skipping copy.");
             return Ok(dest.to_path_buf());
         }
 
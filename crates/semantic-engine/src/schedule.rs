@@ -0,0 +1,111 @@
+use chrono::Timelike;
+use std::time::Duration;
+
+/// How often a background task (snapshots, maintenance) runs, and optionally
+/// only during some hours (UTC)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotSchedule {
+    pub interval: Duration,
+    /// `[start, end)` hours; a window with `start > end` wraps past midnight.
+    /// Empty means any time.
+    pub windows: Vec<(u32, u32)>,
+}
+
+impl Default for SnapshotSchedule {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            windows: Vec::new(),
+        }
+    }
+}
+
+impl SnapshotSchedule {
+    /// Parse `interval_secs[@start-end,...]`, e.g. `300` or `900@22-6,13-14`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (interval, windows) = match spec.trim().split_once('@') {
+            Some((interval, windows)) => (interval, Some(windows)),
+            None => (spec.trim(), None),
+        };
+        let interval = interval.trim().parse::<u64>().ok().filter(|s| *s > 0)?;
+        let windows = match windows {
+            Some(windows) => windows
+                .split(',')
+                .map(|window| {
+                    let (start, end) = window.split_once('-')?;
+                    let start = start.trim().parse::<u32>().ok().filter(|h| *h < 24)?;
+                    let end = end.trim().parse::<u32>().ok().filter(|h| *h <= 24)?;
+                    (start != end).then_some((start, end))
+                })
+                .collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Some(Self {
+            interval: Duration::from_secs(interval),
+            windows,
+        })
+    }
+
+    /// Schedule from `SYNAPSE_SNAPSHOT_SCHEDULE` (`interval_secs[@start-end,...]`,
+    /// or `off`); the default when unset or invalid
+    pub fn from_env() -> Option<Self> {
+        Self::from_env_var("SYNAPSE_SNAPSHOT_SCHEDULE", Self::default())
+    }
+
+    /// Schedule from the variable `name`, in the same format; `default` when
+    /// unset or invalid
+    pub fn from_env_var(name: &str, default: Self) -> Option<Self> {
+        match std::env::var(name) {
+            Ok(spec) if spec.trim() == "off" => None,
+            Ok(spec) => Some(Self::parse(&spec).unwrap_or_else(|| {
+                tracing::warn!("Ignoring invalid {}: {}", name, spec);
+                default
+            })),
+            Err(_) => Some(default),
+        }
+    }
+
+    /// Whether the task may run during this hour of the day
+    pub fn allows_hour(&self, hour: u32) -> bool {
+        self.windows.is_empty()
+            || self.windows.iter().any(|&(start, end)| {
+                if start < end {
+                    (start..end).contains(&hour)
+                } else {
+                    hour >= start || hour < end
+                }
+            })
+    }
+
+    /// Whether the task may run now
+    pub fn allows_now(&self) -> bool {
+        self.allows_hour(chrono::Utc::now().hour())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_schedule() {
+        assert_eq!(
+            SnapshotSchedule::parse("300"),
+            Some(SnapshotSchedule::default())
+        );
+        let schedule = SnapshotSchedule::parse("900@22-6, 13-14").unwrap();
+        assert_eq!(schedule.interval, Duration::from_secs(900));
+        assert_eq!(schedule.windows, vec![(22, 6), (13, 14)]);
+        for spec in ["", "0", "x", "60@", "60@5", "60@5-5", "60@24-2", "60@1-25"] {
+            assert_eq!(SnapshotSchedule::parse(spec), None, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_windows_wrap_past_midnight() {
+        let schedule = SnapshotSchedule::parse("60@22-6,13-14").unwrap();
+        let allowed: Vec<u32> = (0..24).filter(|h| schedule.allows_hour(*h)).collect();
+        assert_eq!(allowed, vec![0, 1, 2, 3, 4, 5, 13, 22, 23]);
+        assert!((0..24).all(|h| SnapshotSchedule::default().allows_hour(h)));
+    }
+}
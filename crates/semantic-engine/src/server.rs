@@ -1,48 +1,638 @@
 use dashmap::DashMap;
 use std::sync::Arc;
+use tonic::codec::CompressionEncoding;
 use tonic::{Request, Response, Status};
 
 pub mod proto {
     tonic::include_proto!("semantic_engine");
 }
 
-use proto::semantic_engine_server::SemanticEngine;
+use proto::semantic_engine_server::{SemanticEngine, SemanticEngineServer};
 use proto::*;
 
-use crate::ingest::IngestionEngine;
-use crate::reasoner::{ReasoningStrategy as InternalStrategy, SynapseReasoner};
+use crate::ingest::csv_dialect::CsvDialect;
+use crate::ingest::csv_mapping::CsvMapping;
+use crate::ingest::{DirectoryIngestReport, IngestionEngine, ProgressFn};
+use crate::reasoner::{ReasoningStrategy as InternalStrategy, RuleSet, RuleStats, SynapseReasoner};
 use crate::scenarios::ScenarioManager;
 use crate::server::proto::{ReasoningStrategy, SearchMode};
-use crate::store::{IngestTriple, SynapseStore};
+use crate::store::{
+    graph_label, DiskUsage, IngestReport, IngestTriple, SynapseStore, BATCH_GRAPH_PREFIX,
+};
 use std::path::Path;
+use tokio_util::sync::CancellationToken;
 
+use crate::analytics::{CommunityAlgorithm, PageRankOptions};
 use crate::audit::InferenceAudit;
-use crate::auth::NamespaceAuth;
+use crate::auth::{
+    is_certificate_principal, AnonymousPolicy, GraphAccess, NamespaceAuth, NamespacePermission,
+    TokenInfo as AuthTokenInfo, DEFAULT_GRAPH,
+};
+use crate::changes::{Change, ChangeFeed, ChangeKind};
+use crate::drain::Drain;
+use crate::jobs::JobRegistry;
+use crate::lru::StoreLru;
+use crate::maintenance::{MaintenancePolicy, MaintenanceReport};
+use crate::memory::MemoryUsage;
+use crate::metrics::{record_namespace, render_namespace_usage, RequestMetrics};
+use crate::network::NetworkFormat;
+use crate::quota::NamespaceQuotas;
+use crate::ratelimit::RateLimiter;
+use crate::schedule::SnapshotSchedule;
+use crate::watcher::{FolderWatcher, WatchConfig};
 
 #[derive(Clone)]
 pub struct AuthToken(pub String);
 
-#[allow(clippy::result_large_err)]
-pub fn auth_interceptor(mut req: Request<()>) -> Result<Request<()>, Status> {
-    if let Some(token) = req
-        .metadata()
+/// Bearer token of a request's `authorization` header
+fn bearer_token(metadata: &tonic::metadata::MetadataMap) -> Option<String> {
+    metadata
         .get("authorization")
         .and_then(|t| t.to_str().ok())
         .map(|s| s.trim_start_matches("Bearer ").to_string())
-    {
+}
+
+#[allow(clippy::result_large_err)]
+pub fn auth_interceptor(mut req: Request<()>) -> Result<Request<()>, Status> {
+    if let Some(token) = bearer_token(req.metadata()) {
+        if is_certificate_principal(&token) {
+            return Err(Status::unauthenticated(
+                "Certificate identities cannot be used as bearer tokens",
+            ));
+        }
         req.extensions_mut().insert(AuthToken(token));
     }
     Ok(req)
 }
 
+/// `auth_interceptor` that also authenticates clients by their TLS certificate:
+/// without a bearer token, the first identity (SAN or CN) of the client certificate
+/// with a `cert:<identity>` entry in `auth` becomes the caller's principal. Bearer
+/// tokens are verified up front, and peers or tokens that keep failing are locked out.
+#[allow(clippy::result_large_err)]
+pub fn certificate_interceptor(
+    auth: Arc<NamespaceAuth>,
+) -> impl FnMut(Request<()>) -> Result<Request<()>, Status> + Clone {
+    move |req: Request<()>| {
+        let mut req = auth_interceptor(req)?;
+        let peer = req.remote_addr().map(|addr| addr.ip());
+        let bearer = req.extensions().get::<AuthToken>().map(|t| t.0.clone());
+        auth.authenticate_attempt(bearer.as_deref(), peer)
+            .map_err(Status::unauthenticated)?;
+        if bearer.is_none() {
+            let identities = req
+                .peer_certs()
+                .and_then(|certs| {
+                    certs
+                        .first()
+                        .map(|c| crate::tls::certificate_identities(c.get_ref()))
+                })
+                .unwrap_or_default();
+            if let Some(principal) = auth.certificate_principal(&identities) {
+                req.extensions_mut().insert(AuthToken(principal));
+            }
+        }
+        Ok(req)
+    }
+}
+
+/// Largest gRPC message accepted or sent unless `SYNAPSE_GRPC_MAX_MESSAGE_BYTES` says
+/// otherwise (tonic's own default is 4 MiB)
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Message size limits and compression of the gRPC service
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransportConfig {
+    pub max_decoding_message_size: usize,
+    pub max_encoding_message_size: usize,
+    /// Encodings accepted from clients and used for replies when a client accepts them
+    pub compression: Vec<CompressionEncoding>,
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            max_decoding_message_size: DEFAULT_MAX_MESSAGE_BYTES,
+            max_encoding_message_size: DEFAULT_MAX_MESSAGE_BYTES,
+            compression: vec![CompressionEncoding::Gzip, CompressionEncoding::Zstd],
+        }
+    }
+}
+
+impl TransportConfig {
+    /// Read `SYNAPSE_GRPC_MAX_MESSAGE_BYTES` (both directions),
+    /// `SYNAPSE_GRPC_MAX_DECODING_BYTES`, `SYNAPSE_GRPC_MAX_ENCODING_BYTES` and
+    /// `SYNAPSE_GRPC_COMPRESSION` ("gzip,zstd", the default, or "none")
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let bytes = |name: &str| {
+            let value = std::env::var(name).ok()?;
+            let parsed = value.trim().parse::<usize>().ok().filter(|n| *n > 0);
+            if parsed.is_none() {
+                tracing::warn!("Ignoring invalid {}: {}", name, value);
+            }
+            parsed
+        };
+        if let Some(n) = bytes("SYNAPSE_GRPC_MAX_MESSAGE_BYTES") {
+            config.max_decoding_message_size = n;
+            config.max_encoding_message_size = n;
+        }
+        if let Some(n) = bytes("SYNAPSE_GRPC_MAX_DECODING_BYTES") {
+            config.max_decoding_message_size = n;
+        }
+        if let Some(n) = bytes("SYNAPSE_GRPC_MAX_ENCODING_BYTES") {
+            config.max_encoding_message_size = n;
+        }
+        if let Ok(spec) = std::env::var("SYNAPSE_GRPC_COMPRESSION") {
+            match Self::parse_compression(&spec) {
+                Ok(compression) => config.compression = compression,
+                Err(e) => tracing::warn!("Ignoring SYNAPSE_GRPC_COMPRESSION: {}", e),
+            }
+        }
+        config
+    }
+
+    /// Parse a comma-separated list of "gzip" and "zstd"; "none" or an empty list
+    /// disables compression
+    pub fn parse_compression(spec: &str) -> Result<Vec<CompressionEncoding>, String> {
+        let mut encodings = Vec::new();
+        for name in spec.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let encoding = match name.to_lowercase().as_str() {
+                "gzip" => CompressionEncoding::Gzip,
+                "zstd" => CompressionEncoding::Zstd,
+                "none" => continue,
+                other => return Err(format!("unknown encoding '{}'", other)),
+            };
+            if !encodings.contains(&encoding) {
+                encodings.push(encoding);
+            }
+        }
+        Ok(encodings)
+    }
+
+    /// The gRPC service for `engine` with these limits and encodings
+    pub fn service(&self, engine: MySemanticEngine) -> SemanticEngineServer<MySemanticEngine> {
+        let mut service = SemanticEngineServer::new(engine)
+            .max_decoding_message_size(self.max_decoding_message_size)
+            .max_encoding_message_size(self.max_encoding_message_size);
+        for encoding in &self.compression {
+            service = service
+                .accept_compressed(*encoding)
+                .send_compressed(*encoding);
+        }
+        service
+    }
+}
+
 fn get_token<T>(req: &Request<T>) -> Option<String> {
     if let Some(token) = req.extensions().get::<AuthToken>() {
         return Some(token.0.clone());
     }
-    req.metadata()
-        .get("authorization")
-        .and_then(|t| t.to_str().ok())
-        .map(|s| s.trim_start_matches("Bearer ").to_string())
+    // Certificate principals only come from the TLS handshake
+    bearer_token(req.metadata()).map(|t| {
+        if is_certificate_principal(&t) {
+            String::new()
+        } else {
+            t
+        }
+    })
+}
+
+/// Status metadata naming the kind of a refusal; quota refusals carry `quota-exceeded`,
+/// telling them apart from rate limiting (both are `RESOURCE_EXHAUSTED`)
+pub const ERROR_KIND_METADATA: &str = "synapse-error";
+
+fn quota_exceeded(message: String) -> Status {
+    let mut status = Status::resource_exhausted(message);
+    status
+        .metadata_mut()
+        .insert(ERROR_KIND_METADATA, "quota-exceeded".parse().unwrap());
+    status
+}
+
+/// Default size of an `ExportNamespace` chunk
+const DEFAULT_EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks an export may run ahead of its client
+const EXPORT_CHUNK_BUFFER: usize = 8;
+
+/// Sends what is written to an `ExportNamespace` stream, `chunk_size` bytes at a time.
+/// The remainder is left in `buffer` for the final chunk.
+struct ChunkWriter {
+    tx: tokio::sync::mpsc::Sender<Result<ExportChunk, Status>>,
+    buffer: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl std::io::Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while self.buffer.len() >= self.chunk_size {
+            let rest = self.buffer.split_off(self.chunk_size);
+            let chunk = ExportChunk {
+                data: std::mem::replace(&mut self.buffer, rest),
+                ..Default::default()
+            };
+            if self.tx.blocking_send(Ok(chunk)).is_err() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "Export client disconnected",
+                ));
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A search hit with the labels, matched triple and provenance of its resource
+fn search_result(
+    store: &SynapseStore,
+    query: &str,
+    idx: usize,
+    uri: String,
+    score: f32,
+) -> anyhow::Result<SearchResult> {
+    let details = store.search_details(query, &uri)?;
+    let content = match (details.labels.first(), &details.triple) {
+        (Some(label), _) => label.clone(),
+        (None, Some((s, p, o))) => format!("{} {} {}", s, p, o),
+        (None, None) => uri.clone(),
+    };
+    Ok(SearchResult {
+        node_id: idx as u32,
+        score,
+        content,
+        uri,
+        labels: details.labels,
+        matched_triple: details.triple.map(|(subject, predicate, object)| Triple {
+            subject,
+            predicate,
+            object,
+            provenance: None,
+            embedding: vec![],
+        }),
+        graph: details.graph,
+        source: details.source,
+    })
+}
+
+/// Items per page when a `PageRequest` leaves the size at 0, and the most allowed
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Offset and size of the page a `PageRequest` asks for (the first page when unset).
+/// Page tokens are offsets into the listing.
+#[allow(clippy::result_large_err)]
+fn page_window(page: Option<&PageRequest>) -> Result<(usize, usize), Status> {
+    let page = page.cloned().unwrap_or_default();
+    let offset = if page.page_token.is_empty() {
+        0
+    } else {
+        page.page_token.parse().map_err(|_| {
+            Status::invalid_argument(format!("Invalid page token: {}", page.page_token))
+        })?
+    };
+    let size = match page.page_size as usize {
+        0 => DEFAULT_PAGE_SIZE,
+        n => n.min(MAX_PAGE_SIZE),
+    };
+    Ok((offset, size))
+}
+
+/// The `PageResponse` for `len` items listed from `offset`, with `more` after them
+fn page_response(offset: usize, len: usize, more: bool, total: Option<usize>) -> PageResponse {
+    PageResponse {
+        next_page_token: if more {
+            (offset + len).to_string()
+        } else {
+            String::new()
+        },
+        total_size: total.unwrap_or(0) as u64,
+    }
+}
+
+/// Chunks (and parsed batches) an `ImportNamespace` may hold in flight
+const IMPORT_CHUNK_BUFFER: usize = 8;
+
+/// Quads loaded into the store at a time during an import
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Reads the data of an `ImportNamespace` stream as one continuous input; the
+/// stream ending is the end of input
+struct ChunkReader {
+    rx: tokio::sync::mpsc::Receiver<Vec<u8>>,
+    current: std::io::Cursor<Vec<u8>>,
+}
+
+impl std::io::Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.current.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            match self.rx.blocking_recv() {
+                Some(data) => self.current = std::io::Cursor::new(data),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Longest path `FindPath` considers when the request does not say
+pub const DEFAULT_PATH_DEPTH: usize = 6;
+
+/// Label propagation rounds `GetCommunities` runs when the request does not say
+const DEFAULT_LABEL_PROPAGATION_ROUNDS: usize = 20;
+
+/// Sources `ComputeAnalytics` samples for centrality when the request does not say
+const DEFAULT_CENTRALITY_SAMPLES: usize = 256;
+
+/// Events buffered ahead of a `SubscribeChanges` client
+const CHANGE_STREAM_BUFFER: usize = 256;
+
+/// Catch the topology mirror up with inferences the reasoner wrote straight to the graph
+fn refresh_topology(store: &SynapseStore) {
+    if let Err(e) = store.rebuild_topology() {
+        tracing::warn!(
+            "Failed to rebuild topology of namespace '{}': {}",
+            store.namespace,
+            e
+        );
+    }
+}
+
+fn publish_materialized(store: &SynapseStore, strategy: &str, count: usize) {
+    if count > 0 {
+        refresh_topology(store);
+        store.changes.publish(|| {
+            Change::new(
+                ChangeKind::Reasoning,
+                &store.namespace,
+                format!("Materialized {} triples ({})", count, strategy),
+            )
+        });
+    }
+}
+
+fn token_permissions(p: &NamespacePermission) -> TokenPermissions {
+    TokenPermissions {
+        read: p.read,
+        write: p.write,
+        delete: p.delete,
+        reason: p.reason,
+        query: p.query,
+        search: p.search,
+        export: p.export,
+        admin: p.admin,
+    }
+}
+
+fn namespace_permission(p: &TokenPermissions) -> NamespacePermission {
+    NamespacePermission {
+        read: p.read,
+        write: p.write,
+        delete: p.delete,
+        reason: p.reason,
+        query: p.query,
+        search: p.search,
+        export: p.export,
+        admin: p.admin,
+    }
+}
+
+fn token_info(info: &AuthTokenInfo) -> TokenInfo {
+    TokenInfo {
+        fingerprint: info.fingerprint.clone(),
+        namespaces: info.namespaces.clone(),
+        permissions: Some(token_permissions(&info.permissions)),
+        source: info.source.to_string(),
+        expires_at: info.expires_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+    }
+}
+
+/// Longest time a rotated token may stay valid next to its replacement
+const MAX_ROTATION_GRACE_SECS: u64 = 365 * 24 * 3600;
+
+/// Parse an optional RFC 3339 expiry; empty means none
+#[allow(clippy::result_large_err)]
+fn parse_expiry(value: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>, Status> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| Some(t.with_timezone(&chrono::Utc)))
+        .map_err(|e| Status::invalid_argument(format!("Invalid 'expires_at': {}", e)))
+}
+
+fn change_event(change: &Change) -> ChangeEvent {
+    let (subject, predicate, object, graph) = match &change.quad {
+        Some(quad) => {
+            let triple = stored_triple(quad);
+            let graph = match &quad.graph_name {
+                oxigraph::model::GraphName::NamedNode(node) => node.as_str().to_string(),
+                _ => String::new(),
+            };
+            (triple.subject, triple.predicate, triple.object, graph)
+        }
+        None => Default::default(),
+    };
+    ChangeEvent {
+        kind: change.kind.as_str().to_string(),
+        namespace: change.namespace.clone(),
+        subject,
+        predicate,
+        object,
+        graph,
+        detail: change.detail.clone(),
+        timestamp: change.timestamp.to_rfc3339(),
+    }
+}
+
+/// Triples buffered ahead of a `GetAllTriples` client
+const TRIPLE_STREAM_BUFFER: usize = 256;
+
+/// A stored quad as a `Triple` message, with `<>` stripped from IRIs
+fn stored_triple(quad: &oxigraph::model::Quad) -> Triple {
+    let clean = |term: String| match term.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+        Some(iri) => iri.to_string(),
+        None => term,
+    };
+    Triple {
+        subject: clean(quad.subject.to_string()),
+        predicate: clean(quad.predicate.to_string()),
+        object: clean(quad.object.to_string()),
+        provenance: Some(Provenance {
+            source: "oxigraph".to_string(),
+            timestamp: "".to_string(),
+            method: "storage".to_string(),
+        }),
+        embedding: vec![],
+    }
+}
+
+fn watch_info(config: &WatchConfig) -> WatchInfo {
+    WatchInfo {
+        namespace: config.namespace.clone(),
+        path: config.path.to_string_lossy().to_string(),
+        exclude: config.exclude.clone(),
+        debounce_ms: config.debounce_ms.min(u32::MAX as u64) as u32,
+    }
+}
+
+/// Acknowledgements an `IngestStream` may run ahead of its client
+const INGEST_ACK_BUFFER: usize = 16;
+
+/// Refuse a write to `graph` (an IRI or `DEFAULT_GRAPH`) outside the token's graphs
+#[allow(clippy::result_large_err)]
+pub(crate) fn check_graph_write(access: &GraphAccess, graph: &str) -> Result<(), Status> {
+    if access.can_write(graph) {
+        Ok(())
+    } else {
+        Err(graph_write_denied(graph))
+    }
+}
+
+fn graph_write_denied(graph: &str) -> Status {
+    Status::permission_denied(format!("Token not authorized to write graph: {}", graph))
+}
+
+/// How a batch graph ingestion is about to create is checked against graph
+/// patterns: only a pattern covering every batch graph (`urn:batch:*`) allows it
+pub(crate) fn new_batch_graph() -> String {
+    format!("{}*", BATCH_GRAPH_PREFIX)
+}
+
+/// Refuse triples that would land in a graph the token may not write
+#[allow(clippy::result_large_err)]
+fn check_ingest_graphs(access: &GraphAccess, triples: &[IngestTriple]) -> Result<(), Status> {
+    let (batched, unbatched): (Vec<_>, Vec<_>) = triples
+        .iter()
+        .partition(|t| SynapseStore::uses_batch_graph(t.provenance.as_ref()));
+    if !batched.is_empty() {
+        check_graph_write(access, &new_batch_graph())?;
+    }
+    if !unbatched.is_empty() {
+        check_graph_write(access, DEFAULT_GRAPH)?;
+    }
+    Ok(())
+}
+
+/// The graphs a SPARQL update writes, by the names graph patterns use; `None` when
+/// they are not known up front (a variable graph in a template, CLEAR or DROP of
+/// NAMED or ALL)
+fn update_target_graphs(update: &spargebra::Update) -> Option<Vec<String>> {
+    use spargebra::algebra::GraphTarget;
+    use spargebra::term::{GraphName, GraphNamePattern};
+
+    let name = |graph: &GraphName| match graph {
+        GraphName::NamedNode(node) => node.as_str().to_string(),
+        GraphName::DefaultGraph => DEFAULT_GRAPH.to_string(),
+    };
+    let pattern = |graph: &GraphNamePattern| match graph {
+        GraphNamePattern::NamedNode(node) => Some(node.as_str().to_string()),
+        GraphNamePattern::DefaultGraph => Some(DEFAULT_GRAPH.to_string()),
+        GraphNamePattern::Variable(_) => None,
+    };
+    let target = |graph: &GraphTarget| match graph {
+        GraphTarget::NamedNode(node) => Some(node.as_str().to_string()),
+        GraphTarget::DefaultGraph => Some(DEFAULT_GRAPH.to_string()),
+        GraphTarget::NamedGraphs | GraphTarget::AllGraphs => None,
+    };
+
+    let mut graphs = Vec::new();
+    for operation in &update.operations {
+        match operation {
+            spargebra::GraphUpdateOperation::InsertData { data } => {
+                graphs.extend(data.iter().map(|q| name(&q.graph_name)))
+            }
+            spargebra::GraphUpdateOperation::DeleteData { data } => {
+                graphs.extend(data.iter().map(|q| name(&q.graph_name)))
+            }
+            spargebra::GraphUpdateOperation::DeleteInsert { delete, insert, .. } => {
+                for graph in delete.iter().map(|q| &q.graph_name) {
+                    graphs.push(pattern(graph)?);
+                }
+                for graph in insert.iter().map(|q| &q.graph_name) {
+                    graphs.push(pattern(graph)?);
+                }
+            }
+            spargebra::GraphUpdateOperation::Load { destination, .. } => {
+                graphs.push(name(destination))
+            }
+            spargebra::GraphUpdateOperation::Clear { graph, .. }
+            | spargebra::GraphUpdateOperation::Drop { graph, .. } => graphs.push(target(graph)?),
+            spargebra::GraphUpdateOperation::Create { graph, .. } => {
+                graphs.push(graph.as_str().to_string())
+            }
+        }
+    }
+    graphs.sort();
+    graphs.dedup();
+    Some(graphs)
+}
+
+fn ingest_triple(t: Triple) -> IngestTriple {
+    IngestTriple {
+        subject: t.subject,
+        predicate: t.predicate,
+        object: t.object,
+        provenance: t.provenance.map(|p| crate::store::Provenance {
+            source: p.source,
+            timestamp: p.timestamp,
+            method: p.method,
+            ..Default::default()
+        }),
+    }
+}
+
+fn ingest_response(report: &IngestReport) -> IngestResponse {
+    IngestResponse {
+        nodes_added: report.added,
+        edges_added: report.added,
+        duplicates_skipped: report.duplicates,
+        malformed: report.malformed,
+        vectors_created: report.vectors_created,
+        elapsed_ms: report.elapsed_ms,
+    }
+}
+
+fn directory_progress(
+    report: &DirectoryIngestReport,
+    file: Option<FileIngestResult>,
+    done: bool,
+) -> IngestDirectoryProgress {
+    IngestDirectoryProgress {
+        files_total: report.files_total,
+        files_processed: report.files.len() as u32,
+        files_failed: report.files_failed,
+        triples_added: report.triples_added,
+        file,
+        done,
+    }
+}
+
+fn rule_statistics(stats: &RuleStats) -> RuleStatistics {
+    RuleStatistics {
+        rule: stats.rule.clone(),
+        triples_inferred: stats.triples_inferred as u32,
+        duration_ms: stats.duration_ms,
+        samples: stats
+            .samples
+            .iter()
+            .map(|(s, p, o)| Triple {
+                subject: s.clone(),
+                predicate: p.clone(),
+                object: o.clone(),
+                provenance: None,
+                embedding: vec![],
+            })
+            .collect(),
+    }
 }
 
 #[derive(Clone)]
@@ -51,13 +641,51 @@ pub struct MySemanticEngine {
     pub stores: Arc<DashMap<String, Arc<SynapseStore>>>,
     pub auth: Arc<NamespaceAuth>,
     pub audit: Arc<InferenceAudit>,
+    pub jobs: Arc<JobRegistry>,
     pub scenario_manager: Arc<ScenarioManager>,
+    pub watcher: Arc<FolderWatcher>,
+    /// Changes in every namespace, for `SubscribeChanges`
+    pub changes: ChangeFeed,
+    /// Filled by a `MetricsLayer` around the gRPC server
+    pub metrics: Arc<RequestMetrics>,
+    /// Shared by the gRPC and MCP paths
+    pub rate_limiter: Arc<RateLimiter>,
+    /// In-flight RPCs (tracked by a `DrainLayer`) and background jobs
+    pub drain: Arc<Drain>,
+    /// Size caps checked before ingesting
+    pub quotas: Arc<NamespaceQuotas>,
+    /// Recency of `stores`, for closing idle namespaces past `SYNAPSE_MAX_OPEN_NAMESPACES`
+    pub store_lru: Arc<StoreLru>,
+    /// Copies of namespaces and backups kept in S3/GCS (`SYNAPSE_OBJECT_STORE`)
+    #[cfg(feature = "object-store")]
+    pub remote: Option<Arc<crate::remote::RemoteStorage>>,
 }
 
 impl MySemanticEngine {
     pub fn new(storage_path: &str) -> Self {
         let auth = Arc::new(NamespaceAuth::new());
         auth.load_from_env();
+        if let Ok(path) = std::env::var("SYNAPSE_AUTH_TOKENS_FILE") {
+            if let Err(e) = auth.watch_file(&path) {
+                tracing::warn!("Ignoring SYNAPSE_AUTH_TOKENS_FILE {}: {}", path, e);
+            }
+        }
+        if let Some(validator) = crate::jwt::JwtValidator::from_env() {
+            let validator = Arc::new(validator);
+            // Keys are fetched in the background; without a runtime (tests) they stay empty
+            if tokio::runtime::Handle::try_current().is_ok() {
+                validator.spawn_refresh();
+            }
+            auth.set_jwt_validator(validator);
+        }
+        if let Some(policy) = AnonymousPolicy::from_env() {
+            auth.set_anonymous_policy(policy);
+        }
+        auth.lockout
+            .set_policy(crate::lockout::LockoutPolicy::from_env());
+        if let Err(e) = auth.persist_to(Path::new(storage_path).join("tokens.json")) {
+            tracing::warn!("Failed to load saved tokens: {}", e);
+        }
         let scenario_manager = Arc::new(ScenarioManager::new(std::path::Path::new(".")));
 
         Self {
@@ -65,10 +693,43 @@ impl MySemanticEngine {
             stores: Arc::new(DashMap::new()),
             auth,
             audit: Arc::new(InferenceAudit::new()),
+            jobs: Arc::new(JobRegistry::new()),
             scenario_manager,
+            watcher: Arc::new(FolderWatcher::new()),
+            changes: ChangeFeed::new(),
+            metrics: Arc::new(RequestMetrics::new()),
+            rate_limiter: Arc::new(RateLimiter::from_env()),
+            drain: Arc::new(Drain::new()),
+            quotas: Arc::new(NamespaceQuotas::from_env()),
+            store_lru: Arc::new(StoreLru::from_env()),
+            #[cfg(feature = "object-store")]
+            remote: match crate::remote::RemoteStorage::from_env() {
+                Ok(remote) => remote.map(Arc::new),
+                Err(e) => {
+                    tracing::warn!("Ignoring SYNAPSE_OBJECT_STORE: {}", e);
+                    None
+                }
+            },
         }
     }
 
+    /// Start watching a folder for a namespace
+    #[allow(clippy::result_large_err)]
+    pub async fn start_watch(&self, config: WatchConfig) -> Result<(), Status> {
+        let store = self.get_store(&config.namespace).await?;
+        self.watcher
+            .watch(config, store)
+            .map_err(|e| Status::invalid_argument(e.to_string()))
+    }
+
+    fn watch_infos(&self, namespace: &str) -> Vec<WatchInfo> {
+        self.watcher
+            .list(Some(namespace))
+            .iter()
+            .map(watch_info)
+            .collect()
+    }
+
     pub async fn install_scenario(&self, name: &str, namespace: &str) -> Result<String, String> {
         let path = self
             .scenario_manager
@@ -78,6 +739,7 @@ impl MySemanticEngine {
 
         let store = self
             .get_store(namespace)
+            .await
             .map_err(|e| e.message().to_string())?;
 
         // Load Ontologies
@@ -100,8 +762,8 @@ impl MySemanticEngine {
                     if p.is_file() {
                         // Use ingestion engine
                         let engine = IngestionEngine::new(store.clone());
-                        if let Ok(count) = engine.ingest_file(&p, namespace).await {
-                            triples_loaded += count as usize;
+                        if let Ok(report) = engine.ingest_file(&p, namespace).await {
+                            triples_loaded += report.added as usize;
                             data_files_loaded += 1;
                         }
                     }
@@ -144,98 +806,283 @@ impl MySemanticEngine {
         ))
     }
 
-    pub async fn shutdown(&self) {
-        eprintln!("Shutting down... flushing {} stores", self.stores.len());
-        for entry in self.stores.iter() {
-            let store = entry.value();
-            if let Err(e) = store.flush() {
-                eprintln!("Failed to flush store '{}': {}", entry.key(), e);
+    /// Wait up to `deadline` for in-flight RPCs and background jobs, then flush
+    /// every store
+    pub async fn graceful_shutdown(&self, deadline: std::time::Duration) {
+        let active = self.drain.active();
+        if active > 0 {
+            tracing::info!(
+                "Draining {} in-flight requests and jobs (up to {:?})...",
+                active,
+                deadline
+            );
+        }
+        if !self.drain.wait_idle(deadline).await {
+            tracing::warn!(
+                "{} requests or jobs still running after {:?}; flushing anyway",
+                self.drain.active(),
+                deadline
+            );
+        }
+        self.shutdown().await;
+    }
+
+    /// Flush every open namespace with unsaved changes: mirror snapshots, URI
+    /// mappings and vectors. Returns how many namespaces were flushed.
+    pub async fn checkpoint_all(&self) -> usize {
+        let stores: Vec<(String, Arc<SynapseStore>)> = self
+            .stores
+            .iter()
+            .filter(|entry| entry.value().has_unsaved_changes())
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let mut flushed = 0;
+        for (namespace, store) in stores {
+            match tokio::task::spawn_blocking(move || store.flush()).await {
+                Ok(Ok(())) => flushed += 1,
+                Ok(Err(e)) => tracing::warn!("Failed to snapshot namespace '{}': {}", namespace, e),
+                Err(e) => tracing::warn!("Snapshot of namespace '{}' panicked: {}", namespace, e),
             }
         }
-        eprintln!("Shutdown complete.");
+        flushed
     }
 
-    #[allow(clippy::result_large_err)]
-    pub fn get_store(&self, namespace: &str) -> Result<Arc<SynapseStore>, Status> {
-        // Use entry API to ensure atomicity
-        let store = self.stores.entry(namespace.to_string()).or_insert_with(|| {
-            let s =
-                SynapseStore::open(namespace, &self.storage_path).expect("Failed to open store");
-            Arc::new(s)
+    /// Run `checkpoint_all` in the background on a schedule, so open namespaces are
+    /// saved even when writes stay below the save thresholds
+    pub fn spawn_snapshot_scheduler(&self, schedule: SnapshotSchedule) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(schedule.interval).await;
+                if !schedule.allows_now() {
+                    continue;
+                }
+                let _guard = engine.drain.track();
+                engine.checkpoint_all().await;
+            }
         });
-
-        Ok(store.value().clone())
     }
-}
 
-#[tonic::async_trait]
-impl SemanticEngine for MySemanticEngine {
-    async fn ingest_triples(
+    /// Run one maintenance pass over the open namespaces the policy does not skip,
+    /// one at a time so at most one namespace is busy with it. Closed namespaces
+    /// are not opened for it.
+    pub async fn run_maintenance(
         &self,
-        request: Request<IngestRequest>,
-    ) -> Result<Response<IngestResponse>, Status> {
-        // Auth check (Write permission)
-        let token = get_token(&request);
-        let req = request.into_inner();
-        let namespace = if req.namespace.is_empty() {
-            "default"
-        } else {
-            &req.namespace
+        policy: &MaintenancePolicy,
+    ) -> Vec<(String, MaintenanceReport)> {
+        let stores: Vec<(String, Arc<SynapseStore>)> = self
+            .stores
+            .iter()
+            .filter(|entry| !policy.skips(entry.key()))
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let mut reports = Vec::new();
+        for (namespace, store) in stores {
+            let policy = policy.clone();
+            match tokio::task::spawn_blocking(move || store.maintain(&policy)).await {
+                Ok(Ok(report)) => {
+                    if let Some(removed) = report.vectors_removed {
+                        tracing::info!(
+                            "Maintenance of '{}': compacted, {} stale vectors removed in {} ms",
+                            namespace,
+                            removed,
+                            report.elapsed_ms
+                        );
+                    }
+                    reports.push((namespace, report));
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!("Maintenance of namespace '{}' failed: {}", namespace, e)
+                }
+                Err(e) => {
+                    tracing::warn!("Maintenance of namespace '{}' panicked: {}", namespace, e)
+                }
+            }
+        }
+        reports
+    }
+
+    /// Run `run_maintenance` in the background, at jittered intervals
+    pub fn spawn_maintenance_scheduler(&self, policy: MaintenancePolicy) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(policy.next_delay()).await;
+                if !policy.schedule.allows_now() {
+                    continue;
+                }
+                let _guard = engine.drain.track();
+                engine.run_maintenance(&policy).await;
+            }
+        });
+    }
+
+    /// Flush every open namespace before exit: URI mappings, mirrors, vectors below
+    /// their auto-save threshold and, in memory-only builds, the graph. Namespaces
+    /// flush in parallel; one that fails is logged and does not stop the others.
+    /// Returns how many namespaces were flushed.
+    pub async fn shutdown(&self) -> usize {
+        let stores: Vec<(String, Arc<SynapseStore>)> = self
+            .stores
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        tracing::info!("Shutting down... flushing {} stores", stores.len());
+        let total = stores.len();
+        let results =
+            futures::future::join_all(stores.into_iter().map(|(namespace, store)| async move {
+                let result = tokio::task::spawn_blocking(move || store.flush()).await;
+                (namespace, result)
+            }))
+            .await;
+        let mut flushed = 0;
+        for (namespace, result) in results {
+            match result {
+                Ok(Ok(())) => flushed += 1,
+                Ok(Err(e)) => tracing::error!("Failed to flush store '{}': {}", namespace, e),
+                Err(e) => tracing::error!("Flushing store '{}' panicked: {}", namespace, e),
+            }
+        }
+        #[cfg(feature = "object-store")]
+        if self.remote.is_some() {
+            let namespaces: Vec<String> = self.stores.iter().map(|e| e.key().clone()).collect();
+            for namespace in namespaces {
+                if let Err(e) = self.sync_namespace_to_remote(&namespace).await {
+                    tracing::error!("Failed to upload store '{}': {}", namespace, e);
+                }
+            }
+        }
+        tracing::info!(
+            "Shutdown complete: flushed {} of {} stores.",
+            flushed,
+            total
+        );
+        flushed
+    }
+
+    /// Upload a consistent copy of a namespace to the object store, replacing the
+    /// previous one. Returns the bytes uploaded.
+    #[cfg(feature = "object-store")]
+    pub async fn sync_namespace_to_remote(&self, namespace: &str) -> anyhow::Result<u64> {
+        let Some(remote) = self.remote.clone() else {
+            anyhow::bail!("No object store configured");
         };
+        let store = self
+            .get_store(namespace)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message().to_string()))?;
+        let staging = Path::new(&self.storage_path)
+            .join(".remote-sync")
+            .join(format!("{}-{}", namespace, uuid::Uuid::new_v4()));
+        let copy = staging.clone();
+        tokio::task::spawn_blocking(move || store.backup(&copy)).await??;
+        let uploaded = remote
+            .mirror_dir(
+                &staging,
+                &format!("{}/{}", crate::remote::NAMESPACES_PREFIX, namespace),
+            )
+            .await;
+        let _ = std::fs::remove_dir_all(&staging);
+        uploaded
+    }
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "write") {
-            return Err(Status::permission_denied(e));
+    /// Download namespaces that are in the object store but not on local disk.
+    /// Call before any store is opened. Returns the restored namespaces.
+    #[cfg(feature = "object-store")]
+    pub async fn restore_from_remote(&self) -> anyhow::Result<Vec<String>> {
+        let Some(remote) = self.remote.clone() else {
+            return Ok(Vec::new());
+        };
+        let mut restored = Vec::new();
+        for namespace in remote.list_dirs(crate::remote::NAMESPACES_PREFIX).await? {
+            let path = Path::new(&self.storage_path).join(&namespace);
+            if path.exists() || self.stores.contains_key(&namespace) {
+                continue;
+            }
+            remote
+                .download_dir(
+                    &format!("{}/{}", crate::remote::NAMESPACES_PREFIX, namespace),
+                    &path,
+                )
+                .await?;
+            restored.push(namespace);
         }
+        Ok(restored)
+    }
+
+    /// Run reasoning for a namespace on a blocking worker and track it as a job.
+    /// Returns the job ID; progress is available through `self.jobs`.
+    #[allow(clippy::result_large_err)]
+    pub async fn spawn_reasoning_job(
+        &self,
+        namespace: &str,
+        reasoner: SynapseReasoner,
+        materialize: bool,
+    ) -> Result<String, Status> {
+        let store = self.get_store(namespace).await?;
+        let job_id = self.jobs.start("reasoning", namespace);
+
+        let jobs = self.jobs.clone();
+        let audit = self.audit.clone();
+        let namespace = namespace.to_string();
+        let id = job_id.clone();
+        let reasoner = match self.jobs.cancellation(&id) {
+            Some(cancel) => reasoner.with_cancellation(cancel),
+            None => reasoner,
+        };
+        let guard = self.drain.track();
 
-        let store = self.get_store(namespace)?;
+        tokio::task::spawn_blocking(move || {
+            let _guard = guard;
+            let strategy_name = reasoner.label();
+            let start_triples = store.store.len().unwrap_or(0);
 
-        // Log provenance for audit
-        let timestamp = chrono::Utc::now().to_rfc3339();
-        let triple_count = req.triples.len();
-        let mut sources: Vec<String> = Vec::new();
+            let result = if materialize {
+                reasoner
+                    .materialize_with_progress(&store.store, |progress| {
+                        jobs.update(&id, progress.percent_complete(), progress.triples_inferred);
+                    })
+                    .map(|report| (report.triples_inferred, report.rules))
+            } else {
+                reasoner
+                    .apply_with_stats(&store.store)
+                    .map(|(triples, rules)| (triples.len(), rules))
+            };
 
-        let triples: Vec<IngestTriple> = req
-            .triples
-            .into_iter()
-            .map(|t| {
-                // Capture provenance sources for logging
-                if let Some(ref prov) = t.provenance {
-                    if !prov.source.is_empty() && !sources.contains(&prov.source) {
-                        sources.push(prov.source.clone());
+            match result {
+                Ok((count, rules)) => {
+                    let verb = if materialize { "Materialized" } else { "Found" };
+                    if materialize {
+                        publish_materialized(&store, &strategy_name, count);
                     }
+                    jobs.set_rule_stats(&id, rules.clone());
+                    jobs.complete(
+                        &id,
+                        count,
+                        format!("{} {} triples in namespace '{}'", verb, count, namespace),
+                    );
+                    audit.log_with_rules(&namespace, &strategy_name, start_triples, count, rules);
                 }
-                IngestTriple {
-                    subject: t.subject,
-                    predicate: t.predicate,
-                    object: t.object,
-                    provenance: t.provenance.map(|p| crate::store::Provenance {
-                        source: p.source,
-                        timestamp: p.timestamp,
-                        method: p.method,
-                    }),
+                Err(e) => {
+                    // A cancelled run keeps the passes it finished
+                    if materialize {
+                        refresh_topology(&store);
+                    }
+                    jobs.fail(&id, e.to_string())
                 }
-            })
-            .collect();
-
-        match store.ingest_triples(triples).await {
-            Ok((added, _)) => {
-                // Log ingestion for audit trail
-                eprintln!(
-                    "INGEST [{timestamp}] namespace={namespace} triples={triple_count} added={added} sources={:?}",
-                    sources
-                );
-                Ok(Response::new(IngestResponse {
-                    nodes_added: added,
-                    edges_added: added,
-                }))
             }
-            Err(e) => Err(Status::internal(e.to_string())),
-        }
+        });
+
+        Ok(job_id)
     }
 
-    async fn ingest_file(
+    /// The `IngestFile` RPC, reporting progress to `progress` and stopping once
+    /// `cancel` fires (used by MCP clients)
+    pub async fn ingest_file_tracked(
         &self,
         request: Request<IngestFileRequest>,
+        progress: Option<ProgressFn>,
+        cancel: Option<CancellationToken>,
     ) -> Result<Response<IngestResponse>, Status> {
         // Auth check (Write permission) - previously missing? or just implicit?
         // Note: The original code didn't check auth for ingest_file!
@@ -248,289 +1095,1486 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "write") {
-            return Err(Status::permission_denied(e));
-        }
-        let store = self.get_store(namespace)?;
-
-        let engine = IngestionEngine::new(store);
+        self.check_access(token.as_deref(), namespace, "write")?;
+        self.record_mutation(token.as_deref(), namespace, "IngestFile");
+        // The ingestion pipeline records provenance, so every file gets a batch graph
+        check_graph_write(
+            &self.auth.graph_access(token.as_deref()),
+            &new_batch_graph(),
+        )?;
+        let store = self.get_store(namespace).await?;
+        self.check_quota(token.as_deref(), &store, 0)?;
+
+        let encoding = match req.encoding.trim() {
+            "" => None,
+            label => Some(
+                crate::ingest::encoding::parse_label(label)
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            ),
+        };
+        let engine = IngestionEngine::new(store)
+            .with_encoding(encoding)
+            .with_progress(progress)
+            .with_cancellation(cancel);
         let path = Path::new(&req.file_path);
 
-        match engine.ingest_file(path, namespace).await {
-            Ok(count) => Ok(Response::new(IngestResponse {
-                nodes_added: count,
-                edges_added: count,
+        let has_csv_options = !req.mapping.trim().is_empty()
+            || !req.delimiter.is_empty()
+            || !req.quote.is_empty()
+            || !req.escape.is_empty();
+
+        let result = if !has_csv_options {
+            engine.ingest_file(path, namespace).await
+        } else {
+            let is_csv = path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("csv") || e.eq_ignore_ascii_case("tsv"));
+            if !is_csv {
+                return Err(Status::invalid_argument(
+                    "Mapping and dialect options can only be used with CSV files",
+                ));
+            }
+            let mapping = if req.mapping.trim().is_empty() {
+                CsvMapping::find_sidecar(path).map_err(|e| Status::internal(e.to_string()))?
+            } else {
+                Some(
+                    CsvMapping::parse(&req.mapping)
+                        .map_err(|e| Status::invalid_argument(format!("Invalid mapping: {}", e)))?,
+                )
+            };
+            let dialect = CsvDialect::from_params(&req.delimiter, &req.quote, &req.escape)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            engine
+                .ingest_csv_with_options(path, namespace, mapping.as_ref(), &dialect)
+                .await
+        };
+
+        match result {
+            Ok(report) => Ok(Response::new(ingest_response(&report))),
+            Err(e) => Err(Status::internal(e.to_string())),
+        }
+    }
+
+    /// Namespaces on disk plus any opened but not yet persisted, sorted by name
+    pub fn namespace_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(&self.storage_path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        names.extend(self.stores.iter().map(|entry| entry.key().clone()));
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// `NamespaceAuth::check` followed by the rate limits, also labelling the current
+    /// RPC's metrics with the namespace
+    #[allow(clippy::result_large_err)]
+    pub fn check_access(
+        &self,
+        token: Option<&str>,
+        namespace: &str,
+        operation: &str,
+    ) -> Result<(), Status> {
+        record_namespace(namespace);
+        self.auth
+            .check(token, namespace, operation)
+            .map_err(Status::permission_denied)?;
+        self.rate_limiter
+            .check(token, namespace)
+            .map_err(Status::resource_exhausted)
+    }
+
+    /// Refuse reads that span every graph of a namespace (dumps, search, traversal,
+    /// analytics) to a token that may only read some graphs. SPARQL queries are
+    /// narrowed to the readable graphs instead.
+    #[allow(clippy::result_large_err)]
+    pub fn check_read_all_graphs(&self, token: Option<&str>) -> Result<(), Status> {
+        if self.auth.graph_access(token).can_read_all() {
+            Ok(())
+        } else {
+            Err(Status::permission_denied(
+                "Token may only read some graphs; use QuerySparql",
+            ))
+        }
+    }
+
+    /// Attribute a mutating RPC to its caller in the audit trail
+    pub fn record_mutation(&self, token: Option<&str>, namespace: &str, action: &str) {
+        self.audit
+            .record_mutation(namespace, &crate::audit::actor(token, "anonymous"), action);
+    }
+
+    /// Run a SPARQL query as `token`, over the graphs it may read, and record it in
+    /// the audit trail (as `fallback` without a token)
+    pub fn audited_query(
+        &self,
+        token: Option<&str>,
+        fallback: &str,
+        store: &SynapseStore,
+        query: &str,
+    ) -> anyhow::Result<String> {
+        let graphs = self.auth.graph_access(token);
+        let readable = |graph: &str| graphs.can_read(graph);
+        let started = std::time::Instant::now();
+        let (json, results) = store.query_sparql_counted(
+            query,
+            graphs
+                .read
+                .is_some()
+                .then_some(&readable as &dyn Fn(&str) -> bool),
+        )?;
+        self.audit.record_query(
+            &store.namespace,
+            &crate::audit::actor(token, fallback),
+            query,
+            started.elapsed(),
+            results,
+        );
+        Ok(json)
+    }
+
+    /// Refuse a write of `incoming` triples (0 when unknown) that would take the
+    /// namespace past its quota
+    #[allow(clippy::result_large_err)]
+    pub fn check_quota(
+        &self,
+        token: Option<&str>,
+        store: &SynapseStore,
+        incoming: usize,
+    ) -> Result<(), Status> {
+        self.quotas
+            .limits_for(token, &store.namespace)
+            .check(store, incoming)
+            .map_err(quota_exceeded)
+    }
+
+    pub async fn get_store(&self, namespace: &str) -> Result<Arc<SynapseStore>, Status> {
+        record_namespace(namespace);
+        let cached = self.stores.get(namespace).map(|e| e.value().clone());
+        let store = match cached {
+            Some(store) => store,
+            None => self.open_store(namespace).await?,
+        };
+        self.store_lru.touch(namespace);
+
+        if self
+            .store_lru
+            .capacity()
+            .is_some_and(|capacity| self.stores.len() > capacity)
+            && self.store_lru.schedule_eviction()
+        {
+            let engine = self.clone();
+            tokio::task::spawn_blocking(move || {
+                engine.evict_idle_stores();
+                engine.store_lru.eviction_finished();
+            });
+        }
+        Ok(store)
+    }
+
+    /// Open a namespace on the blocking pool. Only the namespace's open/close
+    /// lock is held meanwhile, so a namespace is opened once and never while an
+    /// eviction is still closing it.
+    async fn open_store(&self, namespace: &str) -> Result<Arc<SynapseStore>, Status> {
+        let lock = self.store_lru.open_close_lock(namespace);
+        let _opening = lock.lock().await;
+        if let Some(entry) = self.stores.get(namespace) {
+            return Ok(entry.value().clone());
+        }
+        let name = namespace.to_string();
+        let storage_path = self.storage_path.clone();
+        let changes = self.changes.clone();
+        let opened = tokio::task::spawn_blocking(move || {
+            SynapseStore::open(&name, &storage_path).map(|mut store| {
+                store.changes = changes;
+                Arc::new(store)
+            })
+        })
+        .await
+        .map_err(|e| Status::internal(format!("Store open task failed: {}", e)))?;
+        let store = opened.map_err(|e| {
+            Status::unavailable(format!("Failed to open namespace '{}': {}", namespace, e))
+        })?;
+        self.stores.insert(namespace.to_string(), store.clone());
+        Ok(store)
+    }
+
+    /// Flush and close the least recently used namespaces until no more than
+    /// `SYNAPSE_MAX_OPEN_NAMESPACES` are open. Stores still held elsewhere (by a
+    /// request, job or folder watch) stay open. Returns the closed namespaces.
+    pub fn evict_idle_stores(&self) -> Vec<String> {
+        let mut closed = Vec::new();
+        let Some(capacity) = self.store_lru.capacity() else {
+            return closed;
+        };
+        let _evicting = self.store_lru.evicting();
+        for namespace in self.store_lru.candidates(self.stores.len()) {
+            if self.stores.len() <= capacity {
+                break;
+            }
+            let Some(store) = self.stores.get(&namespace).map(|e| e.value().clone()) else {
+                self.store_lru.forget(&namespace);
+                continue;
+            };
+            // Held by the map and by us only
+            if Arc::strong_count(&store) > 2 {
+                continue;
+            }
+            if let Err(e) = store.flush() {
+                tracing::warn!(
+                    "Failed to flush namespace '{}' before closing it: {}",
+                    namespace,
+                    e
+                );
+                continue;
+            }
+            drop(store);
+            // Unless someone opened or wrote to it during the flush. The last
+            // handle is dropped before anyone may open the namespace again; a
+            // namespace being opened right now is in use, so it is skipped.
+            let lock = self.store_lru.open_close_lock(&namespace);
+            let Ok(closing) = lock.try_lock() else {
+                continue;
+            };
+            let removed = self.stores.remove_if(&namespace, |_, store| {
+                Arc::strong_count(store) == 1 && !store.has_unsaved_changes()
+            });
+            let was_removed = removed.is_some();
+            drop(removed);
+            drop(closing);
+            if was_removed {
+                self.store_lru.forget(&namespace);
+                closed.push(namespace);
+            }
+        }
+        closed
+    }
+}
+
+#[tonic::async_trait]
+impl SemanticEngine for MySemanticEngine {
+    async fn ingest_triples(
+        &self,
+        request: Request<IngestRequest>,
+    ) -> Result<Response<IngestResponse>, Status> {
+        // Auth check (Write permission)
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "write")?;
+        self.record_mutation(token.as_deref(), namespace, "IngestTriples");
+
+        let store = self.get_store(namespace).await?;
+        self.check_quota(token.as_deref(), &store, req.triples.len())?;
+
+        // Log provenance for audit
+        let triple_count = req.triples.len();
+        let mut sources: Vec<String> = Vec::new();
+
+        let triples: Vec<IngestTriple> = req
+            .triples
+            .into_iter()
+            .map(|t| {
+                // Capture provenance sources for logging
+                if let Some(ref prov) = t.provenance {
+                    if !prov.source.is_empty() && !sources.contains(&prov.source) {
+                        sources.push(prov.source.clone());
+                    }
+                }
+                ingest_triple(t)
+            })
+            .collect();
+        check_ingest_graphs(&self.auth.graph_access(token.as_deref()), &triples)?;
+
+        match store.ingest_triples(triples).await {
+            Ok(report) => {
+                // Log ingestion for audit trail
+                tracing::info!(
+                    target: "audit",
+                    %namespace,
+                    triples = triple_count,
+                    added = report.added,
+                    duplicates = report.duplicates,
+                    malformed = report.malformed,
+                    ?sources,
+                    "Ingest"
+                );
+                Ok(Response::new(ingest_response(&report)))
+            }
+            Err(e) => Err(Status::internal(e.to_string())),
+        }
+    }
+
+    type IngestStreamStream =
+        std::pin::Pin<Box<dyn futures::Stream<Item = Result<IngestStreamAck, Status>> + Send>>;
+
+    async fn ingest_stream(
+        &self,
+        request: Request<tonic::Streaming<IngestRequest>>,
+    ) -> Result<Response<Self::IngestStreamStream>, Status> {
+        let token = get_token(&request);
+        let mut chunks = request.into_inner();
+
+        // The first chunk names the namespace for the whole stream
+        let Some(first) = chunks.message().await? else {
+            return Err(Status::invalid_argument("Empty ingest stream"));
+        };
+        let namespace = if first.namespace.is_empty() {
+            "default".to_string()
+        } else {
+            first.namespace.clone()
+        };
+        self.check_access(token.as_deref(), &namespace, "write")?;
+        self.record_mutation(token.as_deref(), &namespace, "IngestStream");
+        let store = self.get_store(&namespace).await?;
+        let quota = self.quotas.limits_for(token.as_deref(), &namespace);
+        let graphs = self.auth.graph_access(token.as_deref());
+
+        // Chunks are read one at a time and acknowledgements are bounded, so a
+        // client that outpaces the store (or stops reading acks) is held back
+        let (tx, rx) = tokio::sync::mpsc::channel(INGEST_ACK_BUFFER);
+        tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            let mut ack = IngestStreamAck::default();
+            let mut totals = IngestReport::default();
+            let mut next = Some(first);
+            while let Some(chunk) = next.take() {
+                if !chunk.namespace.is_empty() && chunk.namespace != namespace {
+                    let _ = tx
+                        .send(Err(Status::invalid_argument(format!(
+                            "Chunk for namespace '{}' in a stream for '{}'",
+                            chunk.namespace, namespace
+                        ))))
+                        .await;
+                    return;
+                }
+                if let Err(e) = quota.check(&store, chunk.triples.len()) {
+                    let _ = tx.send(Err(quota_exceeded(e))).await;
+                    return;
+                }
+                ack.chunks_received += 1;
+                ack.triples_received += chunk.triples.len() as u64;
+                let triples: Vec<IngestTriple> =
+                    chunk.triples.into_iter().map(ingest_triple).collect();
+                if let Err(status) = check_ingest_graphs(&graphs, &triples) {
+                    let _ = tx.send(Err(status)).await;
+                    return;
+                }
+                match store.ingest_triples(triples).await {
+                    Ok(report) => totals.merge(&report),
+                    Err(e) => {
+                        let _ = tx.send(Err(Status::internal(e.to_string()))).await;
+                        return;
+                    }
+                }
+                ack.totals = Some(ingest_response(&totals));
+                if tx.send(Ok(ack.clone())).await.is_err() {
+                    return;
+                }
+                next = match chunks.message().await {
+                    Ok(next) => next,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                };
+            }
+
+            totals.elapsed_ms = started.elapsed().as_millis() as u64;
+            tracing::info!(
+                target: "audit",
+                %namespace,
+                chunks = ack.chunks_received,
+                triples = ack.triples_received,
+                added = totals.added,
+                duplicates = totals.duplicates,
+                malformed = totals.malformed,
+                "Ingest stream"
+            );
+            ack.totals = Some(ingest_response(&totals));
+            ack.done = true;
+            let _ = tx.send(Ok(ack)).await;
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        )))
+    }
+
+    async fn ingest_file(
+        &self,
+        request: Request<IngestFileRequest>,
+    ) -> Result<Response<IngestResponse>, Status> {
+        self.ingest_file_tracked(request, None, None).await
+    }
+
+    type IngestDirectoryStream = std::pin::Pin<
+        Box<dyn futures::Stream<Item = Result<IngestDirectoryProgress, Status>> + Send>,
+    >;
+
+    async fn ingest_directory(
+        &self,
+        request: Request<IngestDirectoryRequest>,
+    ) -> Result<Response<Self::IngestDirectoryStream>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default".to_string()
+        } else {
+            req.namespace.clone()
+        };
+
+        self.check_access(token.as_deref(), &namespace, "write")?;
+        self.record_mutation(token.as_deref(), &namespace, "IngestDirectory");
+        check_graph_write(
+            &self.auth.graph_access(token.as_deref()),
+            &new_batch_graph(),
+        )?;
+        let store = self.get_store(&namespace).await?;
+        self.check_quota(token.as_deref(), &store, 0)?;
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            // Stop walking once the client stops listening
+            let cancel = CancellationToken::new();
+            let engine = IngestionEngine::new(store).with_cancellation(Some(cancel.clone()));
+            let progress_tx = tx.clone();
+            let result = engine
+                .ingest_directory(
+                    Path::new(&req.path),
+                    Some(req.glob.as_str()),
+                    &namespace,
+                    |report| {
+                        let file = report.files.last().map(|f| FileIngestResult {
+                            path: f.path.to_string_lossy().to_string(),
+                            triples_added: f.triples_added,
+                            error: f.error.clone().unwrap_or_default(),
+                        });
+                        let progress = directory_progress(report, file, false);
+                        if progress_tx.send(Ok(progress)).is_err() {
+                            cancel.cancel();
+                        }
+                    },
+                )
+                .await;
+
+            let last = match result {
+                Ok(report) => Ok(directory_progress(&report, None, true)),
+                Err(e) => Err(Status::invalid_argument(e.to_string())),
+            };
+            let _ = tx.send(last);
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx),
+        )))
+    }
+
+    async fn watch_folder(
+        &self,
+        request: Request<WatchFolderRequest>,
+    ) -> Result<Response<WatchFolderResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "write")?;
+        self.record_mutation(token.as_deref(), namespace, "WatchFolder");
+        // Watched files are ingested into new batch graphs
+        check_graph_write(
+            &self.auth.graph_access(token.as_deref()),
+            &new_batch_graph(),
+        )?;
+
+        let mut config = WatchConfig::new(namespace, &req.path);
+        config.exclude = req.exclude.clone();
+        if req.debounce_ms > 0 {
+            config.debounce_ms = req.debounce_ms as u64;
+        }
+        self.start_watch(config).await?;
+
+        Ok(Response::new(WatchFolderResponse {
+            success: true,
+            message: format!("Watching {} for namespace '{}'", req.path, namespace),
+            watches: self.watch_infos(namespace),
+        }))
+    }
+
+    async fn unwatch_folder(
+        &self,
+        request: Request<WatchFolderRequest>,
+    ) -> Result<Response<WatchFolderResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "write")?;
+        self.record_mutation(token.as_deref(), namespace, "UnwatchFolder");
+
+        let removed = self.watcher.unwatch(namespace, Path::new(&req.path));
+        Ok(Response::new(WatchFolderResponse {
+            success: removed,
+            message: if removed {
+                format!("Stopped watching {}", req.path)
+            } else {
+                format!("{} is not watched in namespace '{}'", req.path, namespace)
+            },
+            watches: self.watch_infos(namespace),
+        }))
+    }
+
+    async fn compute_analytics(
+        &self,
+        request: Request<AnalyticsRequest>,
+    ) -> Result<Response<AnalyticsResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "reason")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let algorithm = if req.algorithm.is_empty() {
+            "pagerank"
+        } else {
+            req.algorithm.as_str()
+        };
+        if !matches!(algorithm, "pagerank" | "centrality") {
+            return Err(Status::invalid_argument(format!(
+                "Unknown analytics algorithm: {}",
+                algorithm
+            )));
+        }
+        let mut options = PageRankOptions::default();
+        if req.damping != 0.0 {
+            if !(0.0..1.0).contains(&req.damping) {
+                return Err(Status::invalid_argument("damping must be in [0, 1)"));
+            }
+            options.damping = req.damping as f64;
+        }
+        if req.max_iterations > 0 {
+            options.max_iterations = req.max_iterations as usize;
+        }
+        let samples = if req.samples == 0 {
+            DEFAULT_CENTRALITY_SAMPLES
+        } else {
+            req.samples as usize
+        };
+        let top_k = if req.top_k == 0 {
+            10
+        } else {
+            req.top_k as usize
+        };
+
+        let store = self.get_store(namespace).await?;
+        let started = std::time::Instant::now();
+        let (mut top, iterations, converged) = match algorithm {
+            "centrality" => {
+                let result = tokio::task::spawn_blocking(move || store.compute_centrality(samples))
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?;
+                let sampled = samples.min(result.len());
+                let scores = result
+                    .into_iter()
+                    .map(|(uri, measures)| NodeScore {
+                        uri,
+                        score: measures.betweenness,
+                        degree_centrality: measures.degree,
+                        betweenness: measures.betweenness,
+                        closeness: measures.closeness,
+                    })
+                    .collect::<Vec<_>>();
+                let exact = sampled == scores.len();
+                (scores, sampled as u32, exact)
+            }
+            _ => {
+                let result = tokio::task::spawn_blocking(move || store.compute_pagerank(&options))
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?;
+                let scores = result
+                    .scores
+                    .into_iter()
+                    .map(|(uri, score)| NodeScore {
+                        uri,
+                        score,
+                        ..Default::default()
+                    })
+                    .collect::<Vec<_>>();
+                (scores, result.iterations as u32, result.converged)
+            }
+        };
+        let nodes = top.len() as u64;
+
+        if !req.uri.is_empty() {
+            let key = crate::store::node_key(&req.uri);
+            top.retain(|node| node.uri == key);
+            if top.is_empty() {
+                return Err(Status::not_found(format!(
+                    "Resource not found: {}",
+                    req.uri
+                )));
+            }
+        }
+        top.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.uri.cmp(&b.uri)));
+        top.truncate(top_k);
+        Ok(Response::new(AnalyticsResponse {
+            algorithm: algorithm.to_string(),
+            nodes,
+            iterations,
+            converged,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            top,
+        }))
+    }
+
+    async fn find_path(
+        &self,
+        request: Request<PathRequest>,
+    ) -> Result<Response<PathResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "read")?;
+        self.check_read_all_graphs(token.as_deref())?;
+        if req.from.is_empty() || req.to.is_empty() {
+            return Err(Status::invalid_argument(
+                "Both 'from' and 'to' are required",
+            ));
+        }
+        let max_depth = if req.max_depth == 0 {
+            DEFAULT_PATH_DEPTH
+        } else {
+            req.max_depth as usize
+        };
+        let predicate_filter = Some(req.predicate_filter.as_str()).filter(|f| !f.is_empty());
+
+        let store = self.get_store(namespace).await?;
+        let path = store.shortest_path(&req.from, &req.to, max_depth, predicate_filter);
+        Ok(Response::new(match path {
+            Some(path) => PathResponse {
+                found: true,
+                nodes: path.nodes,
+                edges: path
+                    .edges
+                    .into_iter()
+                    .map(|e| Triple {
+                        subject: e.subject,
+                        predicate: e.predicate,
+                        object: e.object,
+                        provenance: None,
+                        embedding: vec![],
+                    })
+                    .collect(),
+            },
+            None => PathResponse::default(),
+        }))
+    }
+
+    async fn get_communities(
+        &self,
+        request: Request<CommunitiesRequest>,
+    ) -> Result<Response<CommunitiesResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "reason")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let algorithm: CommunityAlgorithm = if req.algorithm.is_empty() {
+            CommunityAlgorithm::LabelPropagation
+        } else {
+            req.algorithm
+                .parse()
+                .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?
+        };
+        let max_iterations = if req.max_iterations == 0 {
+            DEFAULT_LABEL_PROPAGATION_ROUNDS
+        } else {
+            req.max_iterations as usize
+        };
+        let max_members = if req.max_members == 0 {
+            10
+        } else {
+            req.max_members as usize
+        };
+
+        let store = self.get_store(namespace).await?;
+        let worker = store.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            worker.compute_communities(algorithm, max_iterations)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        let only = if req.uri.is_empty() {
+            None
+        } else {
+            let id = store
+                .community_of(&req.uri)
+                .ok_or_else(|| Status::not_found(format!("Resource not found: {}", req.uri)))?;
+            Some(id as usize)
+        };
+        let communities = result
+            .members
+            .iter()
+            .enumerate()
+            .filter(|(id, members)| {
+                only.is_none_or(|only| only == *id) && members.len() >= req.min_size as usize
+            })
+            .map(|(id, members)| Community {
+                id: id as u32,
+                size: members.len() as u64,
+                members: members.iter().take(max_members).cloned().collect(),
+            })
+            .collect();
+        Ok(Response::new(CommunitiesResponse {
+            algorithm: algorithm.name().to_string(),
+            community_count: result.members.len() as u64,
+            iterations: result.iterations as u32,
+            communities,
+        }))
+    }
+
+    async fn ingest_feed(
+        &self,
+        request: Request<IngestFeedRequest>,
+    ) -> Result<Response<IngestFeedResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "write")?;
+        self.record_mutation(token.as_deref(), namespace, "IngestFeed");
+        check_graph_write(
+            &self.auth.graph_access(token.as_deref()),
+            &new_batch_graph(),
+        )?;
+
+        let since = if req.since.is_empty() {
+            None
+        } else {
+            Some(
+                chrono::DateTime::parse_from_rfc3339(&req.since)
+                    .map_err(|e| Status::invalid_argument(format!("Invalid 'since': {}", e)))?
+                    .with_timezone(&chrono::Utc),
+            )
+        };
+
+        let store = self.get_store(namespace).await?;
+        self.check_quota(token.as_deref(), &store, 0)?;
+        let engine = IngestionEngine::new(store);
+
+        match engine.ingest_feed(&req.url, namespace, since).await {
+            Ok(summary) => Ok(Response::new(IngestFeedResponse {
+                items_ingested: summary.items_ingested,
+                triples_added: summary.triples_added,
+                chunks_indexed: summary.chunks_indexed,
+                latest: summary.latest.map(|d| d.to_rfc3339()).unwrap_or_default(),
             })),
             Err(e) => Err(Status::internal(e.to_string())),
         }
     }
 
-    async fn get_neighbors(
+    async fn get_neighbors(
+        &self,
+        request: Request<NodeRequest>,
+    ) -> Result<Response<NeighborResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "read")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(namespace).await?;
+
+        let options = crate::store::TraversalOptions {
+            direction: if req.direction.is_empty() {
+                "outgoing".to_string()
+            } else {
+                req.direction
+            },
+            edge_filter: Some(req.edge_filter).filter(|f| !f.is_empty()),
+            node_type_filter: Some(req.node_type_filter).filter(|t| !t.is_empty()),
+            depth: if req.depth == 0 {
+                1
+            } else {
+                req.depth as usize
+            },
+            limit_per_layer: if req.limit_per_layer == 0 {
+                usize::MAX
+            } else {
+                req.limit_per_layer as usize
+            },
+            scoring: req.scoring_strategy,
+        };
+
+        let page = req.page.clone();
+        let neighbors: Vec<Neighbor> = match store.get_uri(req.node_id) {
+            Some(start) => store
+                .neighbors(&start, &options)
+                .into_iter()
+                .map(|n| Neighbor {
+                    node_id: n.node_id,
+                    edge_type: n.edge_type,
+                    uri: n.uri,
+                    direction: n.direction,
+                    depth: n.depth,
+                    score: n.score,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let total = neighbors.len();
+        let Some(page) = page else {
+            return Ok(Response::new(NeighborResponse {
+                neighbors,
+                page: Some(page_response(0, total, false, Some(total))),
+            }));
+        };
+        let (offset, size) = page_window(Some(&page))?;
+        let neighbors: Vec<Neighbor> = neighbors.into_iter().skip(offset).take(size).collect();
+        Ok(Response::new(NeighborResponse {
+            page: Some(page_response(
+                offset,
+                neighbors.len(),
+                offset + neighbors.len() < total,
+                Some(total),
+            )),
+            neighbors,
+        }))
+    }
+
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "search")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(namespace).await?;
+
+        match store.hybrid_search(&req.query, req.limit as usize, 0).await {
+            Ok(results) => {
+                let grpc_results = results
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, (uri, score))| search_result(&store, &req.query, idx, uri, score))
+                    .collect::<anyhow::Result<_>>()
+                    .map_err(|e| Status::internal(e.to_string()))?;
+                Ok(Response::new(SearchResponse {
+                    results: grpc_results,
+                }))
+            }
+            Err(e) => Err(Status::internal(e.to_string())),
+        }
+    }
+
+    async fn resolve_id(
+        &self,
+        request: Request<ResolveRequest>,
+    ) -> Result<Response<ResolveResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "read")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(namespace).await?;
+
+        // Look up the URIs in our mapping
+        let uri_to_id = store.uri_to_id.read().unwrap();
+        let lookup = |content: &str| uri_to_id.get(&store.ensure_uri(content)).copied();
+        let results = req
+            .contents
+            .iter()
+            .map(|content| {
+                let node_id = lookup(content);
+                ResolvedId {
+                    content: content.clone(),
+                    node_id: node_id.unwrap_or(0),
+                    found: node_id.is_some(),
+                }
+            })
+            .collect();
+        let node_id = if req.content.is_empty() {
+            None
+        } else {
+            lookup(&req.content)
+        };
+        Ok(Response::new(ResolveResponse {
+            node_id: node_id.unwrap_or(0),
+            found: node_id.is_some(),
+            results,
+        }))
+    }
+
+    async fn resolve_uri(
+        &self,
+        request: Request<ResolveUriRequest>,
+    ) -> Result<Response<ResolveUriResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "read")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(namespace).await?;
+        let id_to_uri = store.id_to_uri.read().unwrap();
+        let results = req
+            .node_ids
+            .iter()
+            .map(|&node_id| match id_to_uri.get(&node_id) {
+                Some(uri) => ResolvedUri {
+                    node_id,
+                    uri: uri.clone(),
+                    found: true,
+                },
+                None => ResolvedUri {
+                    node_id,
+                    uri: String::new(),
+                    found: false,
+                },
+            })
+            .collect();
+        Ok(Response::new(ResolveUriResponse { results }))
+    }
+
+    type ExportNamespaceStream =
+        std::pin::Pin<Box<dyn futures::Stream<Item = Result<ExportChunk, Status>> + Send>>;
+
+    async fn export_namespace(
+        &self,
+        request: Request<ExportNamespaceRequest>,
+    ) -> Result<Response<Self::ExportNamespaceStream>, Status> {
+        use oxigraph::io::RdfFormat;
+
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "export")?;
+        self.check_read_all_graphs(token.as_deref())?;
+        let network = NetworkFormat::parse(&req.format);
+        let format = match req.format.to_lowercase().as_str() {
+            // Unused; the network format is written instead
+            _ if network.is_some() => RdfFormat::NQuads,
+            "" | "nquads" | "nq" => RdfFormat::NQuads,
+            "trig" => RdfFormat::TriG,
+            "turtle" | "ttl" => RdfFormat::Turtle,
+            "ntriples" | "nt" => RdfFormat::NTriples,
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "Unsupported export format: {}",
+                    other
+                )))
+            }
+        };
+        let chunk_size = match req.chunk_size {
+            0 => DEFAULT_EXPORT_CHUNK_SIZE,
+            n => n as usize,
+        };
+        let store = self.get_store(namespace).await?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(EXPORT_CHUNK_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let mut writer = ChunkWriter {
+                tx: tx.clone(),
+                buffer: Vec::with_capacity(chunk_size),
+                chunk_size,
+            };
+            let result = if let Some(network) = network {
+                store
+                    .export_graphml(network, &mut writer)
+                    .map(|(_, edges)| edges)
+            } else if format.supports_datasets() {
+                store.export_dataset(format, &mut writer)
+            } else {
+                store.export_graph(None, format, &mut writer)
+            };
+            let last = result
+                .map(|count| ExportChunk {
+                    data: std::mem::take(&mut writer.buffer),
+                    done: true,
+                    count: count as u64,
+                })
+                .map_err(|e| Status::internal(format!("Export failed: {}", e)));
+            let _ = tx.blocking_send(last);
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        )))
+    }
+
+    async fn import_namespace(
+        &self,
+        request: Request<tonic::Streaming<ImportChunk>>,
+    ) -> Result<Response<IngestResponse>, Status> {
+        use oxigraph::io::{RdfFormat, RdfParser};
+
+        let token = get_token(&request);
+        let mut chunks = request.into_inner();
+        let Some(first) = chunks.message().await? else {
+            return Err(Status::invalid_argument("Empty import stream"));
+        };
+        let namespace = if first.namespace.is_empty() {
+            "default".to_string()
+        } else {
+            first.namespace.clone()
+        };
+        self.check_access(token.as_deref(), &namespace, "write")?;
+        self.record_mutation(token.as_deref(), &namespace, "ImportNamespace");
+        let format = match first.format.to_lowercase().as_str() {
+            "" | "nquads" | "nq" => RdfFormat::NQuads,
+            "trig" => RdfFormat::TriG,
+            "turtle" | "ttl" => RdfFormat::Turtle,
+            "ntriples" | "nt" => RdfFormat::NTriples,
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "Unsupported import format: {}",
+                    other
+                )))
+            }
+        };
+        let index_vectors = first.index_vectors;
+        let graphs = self.auth.graph_access(token.as_deref());
+        let store = self.get_store(&namespace).await?;
+        self.check_quota(token.as_deref(), &store, 0)?;
+
+        // The parser runs on a blocking thread, reading chunks as they arrive and
+        // handing back batches of quads
+        let (data_tx, data_rx) = tokio::sync::mpsc::channel::<Vec<u8>>(IMPORT_CHUNK_BUFFER);
+        let (batch_tx, mut batch_rx) = tokio::sync::mpsc::channel(IMPORT_CHUNK_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let reader = ChunkReader {
+                rx: data_rx,
+                current: std::io::Cursor::new(Vec::new()),
+            };
+            let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+            for quad in RdfParser::from_format(format)
+                .rename_blank_nodes()
+                .for_reader(reader)
+            {
+                match quad {
+                    Ok(quad) => batch.push(quad),
+                    Err(e) => {
+                        let _ = batch_tx.blocking_send(Err(Status::invalid_argument(format!(
+                            "Import parse error: {}",
+                            e
+                        ))));
+                        return;
+                    }
+                }
+                if batch.len() == IMPORT_BATCH_SIZE
+                    && batch_tx
+                        .blocking_send(Ok(std::mem::take(&mut batch)))
+                        .is_err()
+                {
+                    return;
+                }
+            }
+            let _ = batch_tx.blocking_send(Ok(batch));
+        });
+        let pump = tokio::spawn(async move {
+            let mut next = Some(first);
+            while let Some(chunk) = next.take() {
+                if data_tx.send(chunk.data).await.is_err() {
+                    break;
+                }
+                next = chunks.message().await?;
+            }
+            Ok::<(), Status>(())
+        });
+
+        let mut report = IngestReport::default();
+        let mut failure = None;
+        while let Some(batch) = batch_rx.recv().await {
+            let loaded = match batch {
+                Ok(quads) => match quads
+                    .iter()
+                    .map(|q| graph_label(&q.graph_name))
+                    .find(|graph| !graphs.can_write(graph))
+                {
+                    None => store
+                        .import_quads(quads, index_vectors)
+                        .await
+                        .map_err(|e| Status::internal(e.to_string())),
+                    Some(graph) => Err(graph_write_denied(&graph)),
+                },
+                Err(status) => Err(status),
+            };
+            match loaded {
+                Ok(batch_report) => report.merge(&batch_report),
+                Err(status) => {
+                    failure = Some(status);
+                    break;
+                }
+            }
+        }
+        // A broken client stream explains a parse error on truncated input
+        match pump.await {
+            Ok(Err(status)) => return Err(status),
+            Err(e) => return Err(Status::internal(e.to_string())),
+            Ok(Ok(())) => {}
+        }
+        if let Some(status) = failure {
+            return Err(status);
+        }
+        tracing::info!(
+            target: "audit",
+            %namespace,
+            added = report.added,
+            duplicates = report.duplicates,
+            vectors = report.vectors_created,
+            "Import"
+        );
+        Ok(Response::new(ingest_response(&report)))
+    }
+
+    async fn get_namespace_stats(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<NamespaceStats>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "read")?;
+        let store = self.get_store(namespace).await?;
+        let stats = store.stats().map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(NamespaceStats {
+            namespace: namespace.to_string(),
+            triple_count: stats.triples as u64,
+            graph_count: stats.graphs as u64,
+            vector_count: stats.vectors as u64,
+            stale_vector_count: stats.stale_vectors as u64,
+            uri_mapping_count: stats.uri_mappings as u64,
+            disk_bytes: stats.disk_bytes,
+            last_modified: stats
+                .last_modified
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339())
+                .unwrap_or_default(),
+            memory_bytes: stats.memory.total() as u64,
+            topology_memory_bytes: stats.memory.topology as u64,
+            property_memory_bytes: stats.memory.properties as u64,
+            vector_memory_bytes: stats.memory.vectors as u64,
+            uri_mapping_memory_bytes: stats.memory.uri_mappings as u64,
+            graph_disk_bytes: stats.disk.graph,
+            vector_disk_bytes: stats.disk.vectors,
+            mirror_disk_bytes: stats.disk.mirrors,
+            uri_mapping_disk_bytes: stats.disk.uri_mappings,
+        }))
+    }
+
+    async fn compact_namespace(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<CompactionResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default".to_string()
+        } else {
+            req.namespace
+        };
+
+        self.check_access(token.as_deref(), &namespace, "admin")?;
+        self.record_mutation(token.as_deref(), &namespace, "CompactNamespace");
+        let store = self.get_store(&namespace).await?;
+        let report = tokio::task::spawn_blocking(move || store.compact())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| Status::internal(e.to_string()))?;
+        tracing::info!(
+            "Compacted {}: {} vectors, {} mappings removed, {} -> {} bytes in {} ms",
+            namespace,
+            report.vectors_removed,
+            report.uri_mappings_removed,
+            report.disk_bytes_before,
+            report.disk_bytes_after,
+            report.elapsed_ms
+        );
+
+        Ok(Response::new(CompactionResponse {
+            namespace,
+            vectors_removed: report.vectors_removed as u64,
+            uri_mappings_removed: report.uri_mappings_removed as u64,
+            disk_bytes_before: report.disk_bytes_before,
+            disk_bytes_after: report.disk_bytes_after,
+            elapsed_ms: report.elapsed_ms,
+        }))
+    }
+
+    async fn get_metrics(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<MetricsResponse>, Status> {
+        let token = get_token(&request);
+        // Metrics cover every namespace, so only tokens for all of them may read them
+        if let Err(e) = self.auth.check(token.as_deref(), "*", "admin") {
+            return Err(Status::permission_denied(e));
+        }
+        let mut usage: Vec<(String, MemoryUsage, DiskUsage)> = self
+            .stores
+            .iter()
+            .map(|entry| {
+                let store = entry.value();
+                (
+                    entry.key().clone(),
+                    store.memory_usage(),
+                    store.disk_usage(),
+                )
+            })
+            .collect();
+        usage.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(Response::new(MetricsResponse {
+            prometheus: self.metrics.render()
+                + &self.auth.lockout.render()
+                + &render_namespace_usage(&usage),
+        }))
+    }
+
+    async fn create_token(
+        &self,
+        request: Request<CreateTokenRequest>,
+    ) -> Result<Response<CreateTokenResponse>, Status> {
+        let token = get_token(&request);
+        self.auth
+            .check(token.as_deref(), "*", "admin")
+            .map_err(Status::permission_denied)?;
+        self.record_mutation(token.as_deref(), "*", "CreateToken");
+        let req = request.into_inner();
+        if req.namespaces.is_empty() {
+            return Err(Status::invalid_argument(
+                "A token needs at least one namespace",
+            ));
+        }
+
+        let permissions = req
+            .permissions
+            .as_ref()
+            .map(namespace_permission)
+            .unwrap_or_default();
+        let created = self
+            .auth
+            .create_token(req.namespaces, permissions, parse_expiry(&req.expires_at)?)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(CreateTokenResponse {
+            fingerprint: crate::auth::token_fingerprint(&created),
+            token: created,
+        }))
+    }
+
+    async fn revoke_token(
+        &self,
+        request: Request<RevokeTokenRequest>,
+    ) -> Result<Response<RevokeTokenResponse>, Status> {
+        let token = get_token(&request);
+        self.auth
+            .check(token.as_deref(), "*", "admin")
+            .map_err(Status::permission_denied)?;
+        self.record_mutation(token.as_deref(), "*", "RevokeToken");
+        let req = request.into_inner();
+
+        let revoked = self
+            .auth
+            .revoke_token(&req.token)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(RevokeTokenResponse { revoked }))
+    }
+
+    async fn rotate_token(
+        &self,
+        request: Request<RotateTokenRequest>,
+    ) -> Result<Response<RotateTokenResponse>, Status> {
+        let token = get_token(&request);
+        self.auth
+            .check(token.as_deref(), "*", "admin")
+            .map_err(Status::permission_denied)?;
+        self.record_mutation(token.as_deref(), "*", "RotateToken");
+        let req = request.into_inner();
+
+        if req.grace_seconds > MAX_ROTATION_GRACE_SECS {
+            return Err(Status::invalid_argument(format!(
+                "'grace_seconds' is limited to {}",
+                MAX_ROTATION_GRACE_SECS
+            )));
+        }
+        let grace = chrono::Duration::seconds(req.grace_seconds as i64);
+        let rotated = self
+            .auth
+            .rotate_token(&req.token, grace, parse_expiry(&req.expires_at)?)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(match rotated {
+            Some((created, retired_at)) => RotateTokenResponse {
+                found: true,
+                fingerprint: crate::auth::token_fingerprint(&created),
+                token: created,
+                previous_expires_at: retired_at.to_rfc3339(),
+            },
+            None => RotateTokenResponse::default(),
+        }))
+    }
+
+    async fn list_tokens(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<ListTokensResponse>, Status> {
+        let token = get_token(&request);
+        self.auth
+            .check(token.as_deref(), "*", "admin")
+            .map_err(Status::permission_denied)?;
+        Ok(Response::new(ListTokensResponse {
+            tokens: self.auth.list_tokens().iter().map(token_info).collect(),
+        }))
+    }
+
+    type SubscribeChangesStream =
+        std::pin::Pin<Box<dyn futures::Stream<Item = Result<ChangeEvent, Status>> + Send>>;
+
+    async fn subscribe_changes(
         &self,
-        request: Request<NodeRequest>,
-    ) -> Result<Response<NeighborResponse>, Status> {
+        request: Request<SubscribeChangesRequest>,
+    ) -> Result<Response<Self::SubscribeChangesStream>, Status> {
         let token = get_token(&request);
         let req = request.into_inner();
         let namespace = if req.namespace.is_empty() {
-            "default"
-        } else {
-            &req.namespace
-        };
-
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "read") {
-            return Err(Status::permission_denied(e));
-        }
-
-        let store = self.get_store(namespace)?;
-
-        let direction = if req.direction.is_empty() {
-            "outgoing"
+            "default".to_string()
         } else {
-            &req.direction
+            req.namespace.clone()
         };
-        let edge_filter = if req.edge_filter.is_empty() {
-            None
-        } else {
-            Some(req.edge_filter.as_str())
-        };
-        let node_type_filter = if req.node_type_filter.is_empty() {
-            None
-        } else {
-            Some(req.node_type_filter.as_str())
-        };
-        let max_depth = if req.depth == 0 {
-            1
-        } else {
-            req.depth as usize
-        };
-        let limit_per_layer = if req.limit_per_layer == 0 {
-            usize::MAX
-        } else {
-            req.limit_per_layer as usize
-        };
-
-        let mut neighbors = Vec::new();
-        let mut visited = std::collections::HashSet::new();
-        let mut current_frontier = Vec::new();
-
-        // Start with the initial node
-        if let Some(start_uri) = store.get_uri(req.node_id) {
-            current_frontier.push(start_uri.clone());
-            visited.insert(start_uri);
-        }
-
-        // BFS traversal up to max_depth
-        for current_depth in 1..=max_depth {
-            let mut next_frontier = Vec::new();
-            let mut layer_count = 0;
-            let base_score = 1.0 / current_depth as f32; // Path scoring: closer = higher
-
-            for uri in &current_frontier {
-                if layer_count >= limit_per_layer {
-                    break;
-                }
-
-                // Query outgoing edges (URI as subject)
-                if direction == "outgoing" || direction == "both" {
-                    if let Ok(subj) = oxigraph::model::NamedNodeRef::new(uri) {
-                        for quad in
-                            store
-                                .store
-                                .quads_for_pattern(Some(subj.into()), None, None, None)
-                        {
-                            if layer_count >= limit_per_layer {
-                                break;
-                            }
-                            if let Ok(q) = quad {
-                                let pred = q.predicate.to_string();
-                                // Apply edge filter if specified
-                                if let Some(filter) = edge_filter {
-                                    if !pred.contains(filter) {
-                                        continue;
-                                    }
-                                }
-                                let obj_term = q.object;
-                                let obj_uri = obj_term.to_string();
-
-                                // Node Type Filter Logic
-                                if let Some(type_filter) = node_type_filter {
-                                    let passed =
-                                        if let oxigraph::model::Term::NamedNode(ref n) = obj_term {
-                                            let rdf_type = oxigraph::model::NamedNodeRef::new(
-                                                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
-                                            )
-                                            .unwrap();
-                                            if let Ok(target_type) =
-                                                oxigraph::model::NamedNodeRef::new(type_filter)
-                                            {
-                                                store
-                                                    .store
-                                                    .quads_for_pattern(
-                                                        Some(n.into()),
-                                                        Some(rdf_type),
-                                                        Some(target_type.into()),
-                                                        None,
-                                                    )
-                                                    .next()
-                                                    .is_some()
-                                            } else {
-                                                false
-                                            }
-                                        } else {
-                                            false
-                                        };
-                                    if !passed {
-                                        continue;
-                                    }
-                                }
-
-                                let clean_uri = match &obj_term {
-                                    oxigraph::model::Term::NamedNode(n) => n.as_str(),
-                                    _ => &obj_uri,
-                                };
-
-                                // Always add to neighbors if not already in neighbors list to avoid duplicates there
-                                // But we must allow revisiting nodes for graph expansion if we want to find paths?
-                                // BFS typically avoids cycles by checking visited.
-
-                                // NOTE: visited set prevents processing same node twice in BFS.
-                                // If we reach a node that was already visited in a previous layer (or this layer), skip it.
-                                if !visited.contains(&obj_uri) {
-                                    visited.insert(obj_uri.clone());
-                                    let obj_id = store.get_or_create_id(&obj_uri);
-
-                                    let mut neighbor_score = base_score;
-                                    if req.scoring_strategy == "degree" {
-                                        let degree = store.get_degree(clean_uri);
-                                        neighbor_score /= (degree as f32 + 1.0).ln().max(0.1);
-                                    }
-
-                                    neighbors.push(Neighbor {
-                                        node_id: obj_id,
-                                        edge_type: pred,
-                                        uri: obj_uri.clone(), // This is the N-Triples formatted string for display
-                                        direction: "outgoing".to_string(),
-                                        depth: current_depth as u32,
-                                        score: neighbor_score,
-                                    });
-                                    // Use clean_uri for next frontier to ensure we query with raw URI, not <uri>
-                                    next_frontier.push(clean_uri.to_string());
-                                    layer_count += 1;
-                                }
-                            }
-                        }
-                    }
-                }
 
-                // Query incoming edges (URI as object)
-                if direction == "incoming" || direction == "both" {
-                    if let Ok(obj) = oxigraph::model::NamedNodeRef::new(uri) {
-                        for quad in
-                            store
-                                .store
-                                .quads_for_pattern(None, None, Some(obj.into()), None)
+        self.check_access(token.as_deref(), &namespace, "read")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(&namespace).await?;
+        let (subject, predicate, object) = store
+            .triple_pattern(&req.subject, &req.predicate, &req.object)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let kinds = req.kinds;
+
+        let mut changes = self.changes.subscribe();
+        let (tx, rx) = tokio::sync::mpsc::channel(CHANGE_STREAM_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    received = changes.recv() => received,
+                    // Stop as soon as the client goes away, even if nothing it
+                    // watches ever changes again
+                    _ = tx.closed() => break,
+                };
+                let event = match received {
+                    Ok(change) => {
+                        if change.namespace != namespace
+                            || !(kinds.is_empty()
+                                || kinds.iter().any(|k| k == change.kind.as_str()))
+                            || !change.matches(
+                                subject.as_ref(),
+                                predicate.as_ref(),
+                                object.as_ref(),
+                            )
                         {
-                            if layer_count >= limit_per_layer {
-                                break;
-                            }
-                            if let Ok(q) = quad {
-                                let pred = q.predicate.to_string();
-                                // Apply edge filter if specified
-                                if let Some(filter) = edge_filter {
-                                    if !pred.contains(filter) {
-                                        continue;
-                                    }
-                                }
-                                let subj_term = q.subject;
-                                let subj_uri = subj_term.to_string();
-
-                                // Node Type Filter Logic
-                                if let Some(type_filter) = node_type_filter {
-                                    let passed = if let oxigraph::model::Subject::NamedNode(ref n) =
-                                        subj_term
-                                    {
-                                        let rdf_type = oxigraph::model::NamedNodeRef::new(
-                                            "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
-                                        )
-                                        .unwrap();
-                                        if let Ok(target_type) =
-                                            oxigraph::model::NamedNodeRef::new(type_filter)
-                                        {
-                                            store
-                                                .store
-                                                .quads_for_pattern(
-                                                    Some(n.into()),
-                                                    Some(rdf_type),
-                                                    Some(target_type.into()),
-                                                    None,
-                                                )
-                                                .next()
-                                                .is_some()
-                                        } else {
-                                            false
-                                        }
-                                    } else {
-                                        false
-                                    };
-                                    if !passed {
-                                        continue;
-                                    }
-                                }
-
-                                let clean_uri = match &subj_term {
-                                    oxigraph::model::Subject::NamedNode(n) => n.as_str(),
-                                    _ => &subj_uri,
-                                };
-
-                                if !visited.contains(&subj_uri) {
-                                    visited.insert(subj_uri.clone());
-                                    let subj_id = store.get_or_create_id(&subj_uri);
-
-                                    let mut neighbor_score = base_score;
-                                    if req.scoring_strategy == "degree" {
-                                        let degree = store.get_degree(clean_uri);
-                                        // Penalize super nodes
-                                        neighbor_score /= (degree as f32 + 1.0).ln().max(0.1);
-                                    }
-
-                                    neighbors.push(Neighbor {
-                                        node_id: subj_id,
-                                        edge_type: pred,
-                                        uri: subj_uri.clone(),
-                                        direction: "incoming".to_string(),
-                                        depth: current_depth as u32,
-                                        score: neighbor_score,
-                                    });
-                                    // Use clean_uri for next frontier
-                                    next_frontier.push(clean_uri.to_string());
-                                    layer_count += 1;
-                                }
-                            }
+                            continue;
                         }
+                        change_event(&change)
                     }
+                    // A slow subscriber is told how much it missed rather than cut off
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => ChangeEvent {
+                        kind: "lagged".to_string(),
+                        namespace: namespace.clone(),
+                        detail: format!("{} events dropped", missed),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                        ..Default::default()
+                    },
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                if tx.send(Ok(event)).await.is_err() {
+                    break;
                 }
             }
-
-            current_frontier = next_frontier;
-            if current_frontier.is_empty() {
-                break;
-            }
-        }
-
-        // Sort by score (highest first)
-        neighbors.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        Ok(Response::new(NeighborResponse { neighbors }))
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        )))
     }
 
-    async fn search(
+    type GetAllTriplesStream =
+        std::pin::Pin<Box<dyn futures::Stream<Item = Result<Triple, Status>> + Send>>;
+
+    async fn get_all_triples(
         &self,
-        request: Request<SearchRequest>,
-    ) -> Result<Response<SearchResponse>, Status> {
+        request: Request<TriplesRequest>,
+    ) -> Result<Response<Self::GetAllTriplesStream>, Status> {
         let token = get_token(&request);
         let req = request.into_inner();
         let namespace = if req.namespace.is_empty() {
@@ -539,36 +2583,92 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "read") {
-            return Err(Status::permission_denied(e));
-        }
+        self.check_access(token.as_deref(), namespace, "export")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(namespace).await?;
+        let (subject, predicate, object) = store
+            .triple_pattern(&req.subject, &req.predicate, &req.object)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        // The bounded channel holds the scan back while the client is slow to read
+        let (tx, rx) = tokio::sync::mpsc::channel(TRIPLE_STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let quads = store.store.quads_for_pattern(
+                subject.as_ref().map(Into::into),
+                predicate.as_ref().map(Into::into),
+                object.as_ref().map(Into::into),
+                None,
+            );
+            for quad in quads {
+                let triple = quad
+                    .map(|q| stored_triple(&q))
+                    .map_err(|e| Status::internal(e.to_string()));
+                if tx.blocking_send(triple).is_err() {
+                    // The client went away
+                    break;
+                }
+            }
+        });
 
-        let store = self.get_store(namespace)?;
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        )))
+    }
 
-        match store.hybrid_search(&req.query, req.limit as usize, 0).await {
-            Ok(results) => {
-                let grpc_results = results
-                    .into_iter()
-                    .enumerate()
-                    .map(|(idx, (uri, score))| SearchResult {
-                        node_id: idx as u32,
-                        score,
-                        content: uri.clone(),
-                        uri,
-                    })
-                    .collect();
-                Ok(Response::new(SearchResponse {
-                    results: grpc_results,
-                }))
-            }
-            Err(e) => Err(Status::internal(e.to_string())),
-        }
+    async fn list_triples(
+        &self,
+        request: Request<ListTriplesRequest>,
+    ) -> Result<Response<TriplesPage>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let pattern = req.pattern.unwrap_or_default();
+        let namespace = if pattern.namespace.is_empty() {
+            "default"
+        } else {
+            &pattern.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "read")?;
+        self.check_read_all_graphs(token.as_deref())?;
+
+        let store = self.get_store(namespace).await?;
+        let (subject, predicate, object) = store
+            .triple_pattern(&pattern.subject, &pattern.predicate, &pattern.object)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let (offset, size) = page_window(req.page.as_ref())?;
+
+        // One extra triple tells whether another page follows
+        let mut triples = tokio::task::spawn_blocking(move || {
+            store
+                .store
+                .quads_for_pattern(
+                    subject.as_ref().map(Into::into),
+                    predicate.as_ref().map(Into::into),
+                    object.as_ref().map(Into::into),
+                    None,
+                )
+                .skip(offset)
+                .take(size + 1)
+                .map(|quad| quad.map(|q| stored_triple(&q)))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::internal(e.to_string()))?;
+        let more = triples.len() > size;
+        triples.truncate(size);
+
+        Ok(Response::new(TriplesPage {
+            page: Some(page_response(offset, triples.len(), more, None)),
+            triples,
+        }))
     }
 
-    async fn resolve_id(
+    async fn query_sparql(
         &self,
-        request: Request<ResolveRequest>,
-    ) -> Result<Response<ResolveResponse>, Status> {
+        request: Request<SparqlRequest>,
+    ) -> Result<Response<SparqlResponse>, Status> {
         let token = get_token(&request);
         let req = request.into_inner();
         let namespace = if req.namespace.is_empty() {
@@ -577,33 +2677,20 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "read") {
-            return Err(Status::permission_denied(e));
-        }
-
-        let store = self.get_store(namespace)?;
+        self.check_access(token.as_deref(), namespace, "query")?;
 
-        let uri = store.ensure_uri(&req.content);
+        let store = self.get_store(namespace).await?;
 
-        // Look up the URI in our mapping
-        let uri_to_id = store.uri_to_id.read().unwrap();
-        if let Some(&node_id) = uri_to_id.get(&uri) {
-            Ok(Response::new(ResolveResponse {
-                node_id,
-                found: true,
-            }))
-        } else {
-            Ok(Response::new(ResolveResponse {
-                node_id: 0,
-                found: false,
-            }))
+        match self.audited_query(token.as_deref(), "anonymous", &store, &req.query) {
+            Ok(json) => Ok(Response::new(SparqlResponse { results_json: json })),
+            Err(e) => Err(Status::internal(e.to_string())),
         }
     }
 
-    async fn get_all_triples(
+    async fn get_query_log(
         &self,
-        request: Request<EmptyRequest>,
-    ) -> Result<Response<TriplesResponse>, Status> {
+        request: Request<QueryLogRequest>,
+    ) -> Result<Response<QueryLogResponse>, Status> {
         let token = get_token(&request);
         let req = request.into_inner();
         let namespace = if req.namespace.is_empty() {
@@ -612,56 +2699,33 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "read") {
-            return Err(Status::permission_denied(e));
-        }
-
-        let store = self.get_store(namespace)?;
-
-        let mut triples = Vec::new();
-
-        for quad in store.store.iter().map(|q| q.unwrap()) {
-            let s = quad.subject.to_string();
-            let p = quad.predicate.to_string();
-            let o = quad.object.to_string();
-
-            // Clean up NTriples formatting (<uri> -> uri)
-            let clean_s = if s.starts_with('<') && s.ends_with('>') {
-                s[1..s.len() - 1].to_string()
-            } else {
-                s
-            };
-            let clean_p = if p.starts_with('<') && p.ends_with('>') {
-                p[1..p.len() - 1].to_string()
-            } else {
-                p
-            };
-            let clean_o = if o.starts_with('<') && o.ends_with('>') {
-                o[1..o.len() - 1].to_string()
-            } else {
-                o
-            };
-
-            triples.push(Triple {
-                subject: clean_s,
-                predicate: clean_p,
-                object: clean_o,
-                provenance: Some(Provenance {
-                    source: "oxigraph".to_string(),
-                    timestamp: "".to_string(),
-                    method: "storage".to_string(),
-                }),
-                embedding: vec![],
-            });
-        }
+        self.check_access(token.as_deref(), namespace, "admin")?;
 
-        Ok(Response::new(TriplesResponse { triples }))
+        let records = if req.slow_only {
+            self.audit.get_slow_queries(namespace)
+        } else {
+            self.audit.get_queries(namespace)
+        };
+        Ok(Response::new(QueryLogResponse {
+            queries: records
+                .into_iter()
+                .map(|r| QueryLogEntry {
+                    timestamp: r.timestamp.to_rfc3339(),
+                    actor: r.actor,
+                    query: r.query,
+                    duration_ms: r.duration_ms,
+                    results: r.results as u64,
+                    slow: r.slow,
+                })
+                .collect(),
+            slow_query_threshold_ms: self.audit.slow_query_threshold().as_millis() as u64,
+        }))
     }
 
-    async fn query_sparql(
+    async fn update_sparql(
         &self,
-        request: Request<SparqlRequest>,
-    ) -> Result<Response<SparqlResponse>, Status> {
+        request: Request<SparqlUpdateRequest>,
+    ) -> Result<Response<SparqlUpdateResponse>, Status> {
         let token = get_token(&request);
         let req = request.into_inner();
         let namespace = if req.namespace.is_empty() {
@@ -670,16 +2734,58 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "read") {
-            return Err(Status::permission_denied(e));
+        self.check_access(token.as_deref(), namespace, "write")?;
+        self.record_mutation(token.as_deref(), namespace, "UpdateSparql");
+        let mut update = spargebra::Update::parse(&req.update, None)
+            .map_err(|e| Status::invalid_argument(format!("Invalid SPARQL update: {}", e)))?;
+        let graphs = self.auth.graph_access(token.as_deref());
+        match update_target_graphs(&update) {
+            Some(targets) => {
+                for graph in &targets {
+                    check_graph_write(&graphs, graph)?;
+                }
+            }
+            None if graphs.can_write_all() => {}
+            None => {
+                return Err(Status::permission_denied(
+                    "Token may only write some graphs; name each graph the update writes",
+                ))
+            }
         }
-
-        let store = self.get_store(namespace)?;
-
-        match store.query_sparql(&req.query) {
-            Ok(json) => Ok(Response::new(SparqlResponse { results_json: json })),
-            Err(e) => Err(Status::internal(e.to_string())),
+        // A WHERE clause only sees the graphs the token may read: give every
+        // DELETE/INSERT a dataset to narrow, remembering which had no USING
+        let mut implicit_datasets = Vec::new();
+        if graphs.read.is_some() {
+            for operation in &mut update.operations {
+                if let spargebra::GraphUpdateOperation::DeleteInsert { using, .. } = operation {
+                    implicit_datasets.push(using.is_none());
+                    using.get_or_insert_with(|| spargebra::algebra::QueryDataset {
+                        default: Vec::new(),
+                        named: None,
+                    });
+                }
+            }
         }
+        let mut update = oxigraph::sparql::Update::from(update);
+        let store = self.get_store(namespace).await?;
+        self.check_quota(token.as_deref(), &store, 0)?;
+        for (dataset, implicit) in update.using_datasets_mut().zip(implicit_datasets) {
+            if implicit {
+                dataset.set_default_graph(vec![oxigraph::model::GraphName::DefaultGraph]);
+            }
+            store
+                .scope_dataset(dataset, |g| graphs.can_read(g))
+                .map_err(|e| Status::internal(e.to_string()))?;
+        }
+
+        let report = tokio::task::spawn_blocking(move || store.update_sparql(update))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(SparqlUpdateResponse {
+            inserted: report.inserted as u64,
+            deleted: report.deleted as u64,
+        }))
     }
 
     async fn delete_namespace_data(
@@ -694,18 +2800,41 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "delete") {
-            return Err(Status::permission_denied(e));
+        self.check_access(token.as_deref(), namespace, "delete")?;
+        self.record_mutation(token.as_deref(), namespace, "DeleteNamespaceData");
+        if !self.auth.graph_access(token.as_deref()).can_write_all() {
+            return Err(Status::permission_denied(
+                "Token may only write some graphs, not delete the namespace",
+            ));
         }
 
         // Remove from cache
         self.stores.remove(namespace);
+        self.store_lru.forget(namespace);
+        self.changes.publish(|| {
+            Change::new(
+                ChangeKind::NamespaceDeleted,
+                namespace,
+                format!("Deleted namespace '{}'", namespace),
+            )
+        });
 
         // Delete directory
         let path = Path::new(&self.storage_path).join(namespace);
         if path.exists() {
             std::fs::remove_dir_all(path).map_err(|e| Status::internal(e.to_string()))?;
         }
+        #[cfg(feature = "object-store")]
+        if let Some(remote) = &self.remote {
+            remote
+                .delete_dir(&format!(
+                    "{}/{}",
+                    crate::remote::NAMESPACES_PREFIX,
+                    namespace
+                ))
+                .await
+                .map_err(|e| Status::internal(format!("Failed to delete remote copy: {}", e)))?;
+        }
 
         Ok(Response::new(DeleteResponse {
             success: true,
@@ -713,6 +2842,46 @@ impl SemanticEngine for MySemanticEngine {
         }))
     }
 
+    async fn delete_graph(
+        &self,
+        request: Request<DeleteGraphRequest>,
+    ) -> Result<Response<DeleteGraphResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "delete")?;
+        self.record_mutation(token.as_deref(), namespace, "DeleteGraph");
+        if oxigraph::model::NamedNodeRef::new(&req.graph).is_err() {
+            return Err(Status::invalid_argument(format!(
+                "Invalid graph URI: {}",
+                req.graph
+            )));
+        }
+        check_graph_write(&self.auth.graph_access(token.as_deref()), &req.graph)?;
+        let store = self.get_store(namespace).await?;
+
+        let graph = req.graph.clone();
+        let deletion = tokio::task::spawn_blocking(move || store.delete_graph(&graph))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| {
+                Status::not_found(format!(
+                    "Graph '{}' not found in namespace '{}'",
+                    req.graph, namespace
+                ))
+            })?;
+        Ok(Response::new(DeleteGraphResponse {
+            triples_removed: deletion.triples_removed as u64,
+            vectors_removed: deletion.vectors_removed as u64,
+        }))
+    }
+
     async fn hybrid_search(
         &self,
         request: Request<HybridSearchRequest>,
@@ -725,33 +2894,44 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "read") {
-            return Err(Status::permission_denied(e));
-        }
+        self.check_access(token.as_deref(), namespace, "search")?;
+        self.check_read_all_graphs(token.as_deref())?;
 
-        let store = self.get_store(namespace)?;
+        let store = self.get_store(namespace).await?;
 
         let vector_k = req.vector_k as usize;
         let graph_depth = req.graph_depth;
 
-        let results = match SearchMode::try_from(req.mode) {
-            Ok(SearchMode::VectorOnly) | Ok(SearchMode::Hybrid) => store
-                .hybrid_search(&req.query, vector_k, graph_depth)
-                .await
-                .map_err(|e| Status::internal(format!("Hybrid search failed: {}", e)))?,
-            _ => vec![],
-        };
+        let mut results = match SearchMode::try_from(req.mode) {
+            Ok(SearchMode::VectorOnly) => store.hybrid_search(&req.query, vector_k, 0).await,
+            Ok(SearchMode::Hybrid) => store.hybrid_search(&req.query, vector_k, graph_depth).await,
+            Ok(SearchMode::GraphOnly) => store.graph_search(&req.query, graph_depth),
+            Err(_) => return Err(Status::invalid_argument("Unknown search mode")),
+        }
+        .map_err(|e| Status::internal(format!("Hybrid search failed: {}", e)))?;
+
+        if req.rerank {
+            results = store.rerank(&req.query, results);
+        }
+        if req.pagerank_weight != 0.0 {
+            if !(0.0..=1.0).contains(&req.pagerank_weight) {
+                return Err(Status::invalid_argument(
+                    "pagerank_weight must be in [0, 1]",
+                ));
+            }
+            results = store.blend_pagerank(results, req.pagerank_weight);
+        }
+        results.retain(|(_, score)| *score >= req.min_score);
+        if req.limit > 0 {
+            results.truncate(req.limit as usize);
+        }
 
         let grpc_results = results
             .into_iter()
             .enumerate()
-            .map(|(idx, (uri, score))| SearchResult {
-                node_id: idx as u32,
-                score,
-                content: uri.clone(),
-                uri,
-            })
-            .collect();
+            .map(|(idx, (uri, score))| search_result(&store, &req.query, idx, uri, score))
+            .collect::<anyhow::Result<_>>()
+            .map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(SearchResponse {
             results: grpc_results,
@@ -771,69 +2951,285 @@ impl SemanticEngine for MySemanticEngine {
             &req.namespace
         };
 
-        if let Err(e) = self.auth.check(token.as_deref(), namespace, "reason") {
-            return Err(Status::permission_denied(e));
-        }
+        self.check_access(token.as_deref(), namespace, "reason")?;
+        self.record_mutation(token.as_deref(), namespace, "ApplyReasoning");
 
-        let store = self.get_store(namespace)?;
+        let store = self.get_store(namespace).await?;
 
         let strategy = match ReasoningStrategy::try_from(req.strategy) {
             Ok(ReasoningStrategy::Rdfs) => InternalStrategy::RDFS,
             Ok(ReasoningStrategy::Owlrl) => InternalStrategy::OWLRL,
             _ => InternalStrategy::None,
         };
-        let strategy_name = format!("{:?}", strategy);
+        let mut reasoner = SynapseReasoner::new(strategy);
+        if !req.rules.trim().is_empty() {
+            let rules = req
+                .rules
+                .parse::<RuleSet>()
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            reasoner = reasoner.with_rules(rules);
+        }
+        let strategy_name = reasoner.label();
+
+        if req.background {
+            let job_id = self
+                .spawn_reasoning_job(namespace, reasoner, req.materialize)
+                .await?;
+            return Ok(Response::new(ReasoningResponse {
+                success: true,
+                triples_inferred: 0,
+                message: format!("Reasoning job started in namespace '{}'", namespace),
+                job_id,
+                rule_stats: vec![],
+            }));
+        }
 
-        let reasoner = SynapseReasoner::new(strategy);
         let start_triples = store.store.len().unwrap_or(0);
 
-        let response = if req.materialize {
-            match reasoner.materialize(&store.store) {
-                Ok(count) => Ok(Response::new(ReasoningResponse {
-                    success: true,
-                    triples_inferred: count as u32,
-                    message: format!(
-                        "Materialized {} triples in namespace '{}'",
-                        count, namespace
-                    ),
-                })),
-                Err(e) => Err(Status::internal(e.to_string())),
-            }
-        } else {
-            match reasoner.apply(&store.store) {
-                Ok(triples) => Ok(Response::new(ReasoningResponse {
-                    success: true,
-                    triples_inferred: triples.len() as u32,
-                    message: format!(
-                        "Found {} inferred triples in namespace '{}'",
-                        triples.len(),
-                        namespace
-                    ),
-                })),
-                Err(e) => Err(Status::internal(e.to_string())),
-            }
+        let result = if req.materialize {
+            reasoner
+                .materialize_with_stats(&store.store)
+                .map(|report| (report.triples_inferred, report.rules))
+        } else {
+            reasoner
+                .apply_with_stats(&store.store)
+                .map(|(triples, rules)| (triples.len(), rules))
+        };
+
+        let (count, rules) = result.map_err(|e| Status::internal(e.to_string()))?;
+        if req.materialize {
+            publish_materialized(&store, &strategy_name, count);
+        }
+        let message = if req.materialize {
+            format!(
+                "Materialized {} triples in namespace '{}'",
+                count, namespace
+            )
+        } else {
+            format!(
+                "Found {} inferred triples in namespace '{}'",
+                count, namespace
+            )
         };
+        let rule_stats = rules.iter().map(rule_statistics).collect();
 
         // Audit Log
-        if let Ok(ref res) = response {
-            let inferred = res.get_ref().triples_inferred as usize;
-            self.audit.log(
-                namespace,
-                &strategy_name,
-                start_triples,
-                inferred,
-                0, // Duplicates skipped not easily tracked here without changing reasoner return signature
-                vec![], // Sample inferences
-            );
+        self.audit
+            .log_with_rules(namespace, &strategy_name, start_triples, count, rules);
+
+        Ok(Response::new(ReasoningResponse {
+            success: true,
+            triples_inferred: count as u32,
+            message,
+            job_id: String::new(),
+            rule_stats,
+        }))
+    }
+
+    async fn preview_reasoning(
+        &self,
+        request: Request<PreviewReasoningRequest>,
+    ) -> Result<Response<PreviewReasoningResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default"
+        } else {
+            &req.namespace
+        };
+
+        self.check_access(token.as_deref(), namespace, "reason")?;
+
+        let store = self.get_store(namespace).await?;
+        let strategy = match ReasoningStrategy::try_from(req.strategy) {
+            Ok(ReasoningStrategy::Owlrl) => InternalStrategy::OWLRL,
+            _ => InternalStrategy::RDFS,
+        };
+        let mut reasoner = SynapseReasoner::new(strategy);
+        if !req.rules.trim().is_empty() {
+            let rules = req
+                .rules
+                .parse::<RuleSet>()
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            reasoner = reasoner.with_rules(rules);
+        }
+        let (offset, limit) = page_window(req.page.as_ref())?;
+
+        let preview =
+            tokio::task::spawn_blocking(move || reasoner.preview_page(&store.store, offset, limit))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| Status::internal(e.to_string()))?;
+        let more = offset + preview.sample.len() < preview.total;
+        Ok(Response::new(PreviewReasoningResponse {
+            triples_inferred: preview.triples_inferred as u32,
+            page: Some(page_response(
+                offset,
+                preview.sample.len(),
+                more,
+                Some(preview.total),
+            )),
+            triples: preview
+                .sample
+                .into_iter()
+                .map(|(subject, predicate, object)| Triple {
+                    subject,
+                    predicate,
+                    object,
+                    provenance: None,
+                    embedding: vec![],
+                })
+                .collect(),
+            rule_stats: preview.rules.iter().map(rule_statistics).collect(),
+        }))
+    }
+
+    async fn backup_namespace(
+        &self,
+        request: Request<BackupRequest>,
+    ) -> Result<Response<BackupResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+        let namespace = if req.namespace.is_empty() {
+            "default".to_string()
+        } else {
+            req.namespace
+        };
+
+        self.check_access(token.as_deref(), &namespace, "admin")?;
+        self.record_mutation(token.as_deref(), &namespace, "BackupNamespace");
+        if req.destination.contains("://") {
+            return Err(Status::invalid_argument(
+                "Backups can only be written to a directory path",
+            ));
         }
+        let destination = if req.destination.is_empty() {
+            std::path::Path::new(&self.storage_path).join("backups")
+        } else {
+            std::path::PathBuf::from(&req.destination)
+        };
+        let target = destination.join(format!(
+            "{}-{}",
+            namespace,
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        let store = self.get_store(&namespace).await?;
+        let job_id = self.jobs.start("backup", &namespace);
+
+        let jobs = self.jobs.clone();
+        let id = job_id.clone();
+        let path = target.clone();
+        let guard = self.drain.track();
+        #[cfg(feature = "object-store")]
+        let remote = self.remote.clone();
+        tokio::spawn(async move {
+            let _guard = guard;
+            let backup = path.clone();
+            let result = match tokio::task::spawn_blocking(move || store.backup(&backup)).await {
+                Ok(result) => result,
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(bytes) => {
+                    #[allow(unused_mut)]
+                    let mut message = format!(
+                        "Backed up namespace '{}' ({} bytes) to {}",
+                        namespace,
+                        bytes,
+                        path.display()
+                    );
+                    #[cfg(feature = "object-store")]
+                    if let (Some(remote), Some(name)) = (remote, path.file_name()) {
+                        let prefix = format!(
+                            "{}/{}",
+                            crate::remote::BACKUPS_PREFIX,
+                            name.to_string_lossy()
+                        );
+                        match remote.mirror_dir(&path, &prefix).await {
+                            Ok(_) => message.push_str(&format!(" and {}/{}", remote.url(), prefix)),
+                            Err(e) => {
+                                jobs.fail(&id, format!("Backup upload failed: {}", e));
+                                return;
+                            }
+                        }
+                    }
+                    jobs.complete(&id, 0, message)
+                }
+                Err(e) => jobs.fail(&id, format!("Backup failed: {}", e)),
+            }
+        });
+
+        Ok(Response::new(BackupResponse {
+            job_id,
+            path: target.display().to_string(),
+        }))
+    }
+
+    async fn get_job_status(
+        &self,
+        request: Request<JobStatusRequest>,
+    ) -> Result<Response<JobStatusResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
 
-        response
+        let Some(job) = self.jobs.get(&req.job_id) else {
+            return Ok(Response::new(JobStatusResponse {
+                found: false,
+                job_id: req.job_id,
+                ..Default::default()
+            }));
+        };
+
+        self.check_access(token.as_deref(), &job.namespace, "read")?;
+
+        Ok(Response::new(JobStatusResponse {
+            found: true,
+            job_id: job.id,
+            kind: job.kind,
+            namespace: job.namespace,
+            state: job.state.as_str().to_string(),
+            percent_complete: job.percent_complete,
+            message: job.message,
+            started_at: job.started_at.to_rfc3339(),
+            finished_at: job.finished_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        }))
+    }
+
+    async fn get_reasoning_status(
+        &self,
+        request: Request<ReasoningStatusRequest>,
+    ) -> Result<Response<ReasoningStatusResponse>, Status> {
+        let token = get_token(&request);
+        let req = request.into_inner();
+
+        let job = match self.jobs.get(&req.job_id) {
+            Some(job) => job,
+            None => {
+                return Ok(Response::new(ReasoningStatusResponse {
+                    found: false,
+                    job_id: req.job_id,
+                    ..Default::default()
+                }))
+            }
+        };
+
+        self.check_access(token.as_deref(), &job.namespace, "read")?;
+
+        Ok(Response::new(ReasoningStatusResponse {
+            found: true,
+            job_id: job.id,
+            namespace: job.namespace,
+            state: job.state.as_str().to_string(),
+            percent_complete: job.percent_complete,
+            triples_inferred: job.triples_inferred as u32,
+            message: job.message,
+        }))
     }
 }
 
 pub async fn run_mcp_stdio(
     engine: Arc<MySemanticEngine>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let server = crate::mcp_stdio::McpStdioServer::new(engine);
+    let server = Arc::new(crate::mcp_stdio::McpStdioServer::new(engine));
     server.run().await
 }
@@ -0,0 +1,377 @@
+//! Snapshots of a namespace's topology and property mirrors, with a write-ahead
+//! log of the mutations since the last one, so reopening a namespace loads the
+//! mirrors instead of rescanning its graph.
+
+use crate::properties::{ColumnPolicy, PropertyStore, PropertyValue};
+use crate::topology::GraphTopology;
+use anyhow::Result;
+use oxigraph::model::{Quad, Term};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Leads a compressed snapshot file; anything else is a plain bincode snapshot
+/// from before snapshots were compressed
+const SNAPSHOT_MAGIC: &[u8; 8] = b"SYNSNAP1";
+
+const SNAPSHOT_COMPRESSION_LEVEL: i32 = 3;
+
+/// One change to the mirrors. Terms are in N-Triples form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Mutation {
+    AddEdge {
+        subject: String,
+        predicate: String,
+        object: String,
+    },
+    RemoveEdge {
+        subject: String,
+        predicate: String,
+        object: String,
+    },
+    AddProperty {
+        node: String,
+        property: String,
+        value: PropertyValue,
+    },
+    RemoveProperty {
+        node: String,
+        property: String,
+        value: PropertyValue,
+    },
+}
+
+impl Mutation {
+    /// What inserting (or removing) a quad changes: its edge and, for a literal
+    /// object, a property value
+    pub fn for_quad(quad: &Quad, insert: bool) -> Vec<Self> {
+        let (subject, predicate) = (quad.subject.to_string(), quad.predicate.to_string());
+        let value = match &quad.object {
+            Term::Literal(literal) => Some(PropertyValue::from_literal(literal.as_ref())),
+            _ => None,
+        };
+        let edge = if insert {
+            Self::AddEdge {
+                subject: subject.clone(),
+                predicate: predicate.clone(),
+                object: quad.object.to_string(),
+            }
+        } else {
+            Self::RemoveEdge {
+                subject: subject.clone(),
+                predicate: predicate.clone(),
+                object: quad.object.to_string(),
+            }
+        };
+        let property = value.map(|value| {
+            let (node, property) = (subject, predicate);
+            if insert {
+                Self::AddProperty {
+                    node,
+                    property,
+                    value,
+                }
+            } else {
+                Self::RemoveProperty {
+                    node,
+                    property,
+                    value,
+                }
+            }
+        });
+        std::iter::once(edge).chain(property).collect()
+    }
+
+    pub fn apply(&self, topology: &mut GraphTopology, properties: &mut PropertyStore) {
+        match self {
+            Self::AddEdge {
+                subject,
+                predicate,
+                object,
+            } => topology.add_edge(subject, predicate, object),
+            Self::RemoveEdge {
+                subject,
+                predicate,
+                object,
+            } => {
+                topology.remove_edge(subject, predicate, object);
+            }
+            Self::AddProperty {
+                node,
+                property,
+                value,
+            } => properties.add(node, property, value.clone()),
+            Self::RemoveProperty {
+                node,
+                property,
+                value,
+            } => {
+                properties.remove(node, property, value);
+            }
+        }
+    }
+}
+
+/// Full contents of the mirrors as of one write-ahead log position
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    /// Sequence number of the last log record the snapshot includes
+    pub sequence: u64,
+    pub edges: Vec<(String, String, String)>,
+    pub properties: Vec<(String, String, PropertyValue)>,
+}
+
+impl GraphSnapshot {
+    pub fn capture(topology: &GraphTopology, properties: &PropertyStore, sequence: u64) -> Self {
+        Self {
+            sequence,
+            edges: topology
+                .edges()
+                .map(|(s, p, o)| (s.to_string(), p.to_string(), o.to_string()))
+                .collect(),
+            properties: properties
+                .values()
+                .map(|(n, p, v)| (n.to_string(), p.to_string(), v.clone()))
+                .collect(),
+        }
+    }
+
+    /// Rebuild the mirrors, laying out property columns under `policy`
+    pub fn into_mirrors(self, policy: ColumnPolicy) -> (GraphTopology, PropertyStore) {
+        let mut topology = GraphTopology::new();
+        for (subject, predicate, object) in &self.edges {
+            topology.add_edge(subject, predicate, object);
+        }
+        let mut properties = PropertyStore::with_policy(policy);
+        for (node, property, value) in self.properties {
+            properties.add(&node, &property, value);
+        }
+        properties.rebalance();
+        (topology, properties)
+    }
+
+    /// Save as zstd-compressed bincode behind `SNAPSHOT_MAGIC`, atomically via a
+    /// temporary file
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            writer.write_all(SNAPSHOT_MAGIC)?;
+            let mut encoder = zstd::Encoder::new(writer, SNAPSHOT_COMPRESSION_LEVEL)?;
+            bincode::serialize_into(&mut encoder, self)?;
+            encoder.finish()?.flush()?;
+        }
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Load a compressed snapshot, or an uncompressed one written before
+    /// compression
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        if reader.fill_buf()?.starts_with(SNAPSHOT_MAGIC) {
+            reader.consume(SNAPSHOT_MAGIC.len());
+            Ok(bincode::deserialize_from(zstd::Decoder::with_buffer(
+                reader,
+            )?)?)
+        } else {
+            Ok(bincode::deserialize_from(reader)?)
+        }
+    }
+}
+
+/// Append-only log of mutation batches. Each record is a little-endian `u32`
+/// length followed by the bincode of `(sequence, mutations)`; a record cut short
+/// by a crash ends the log.
+pub struct WriteAheadLog {
+    file: File,
+    /// Sequence number of the next record
+    next_sequence: u64,
+    records: usize,
+}
+
+impl WriteAheadLog {
+    /// Open a log for appending, returning the mutations of its records after
+    /// sequence number `after` (those a snapshot does not include yet)
+    pub fn open(path: &Path, after: u64) -> Result<(Self, Vec<Mutation>)> {
+        let data = if path.exists() {
+            std::fs::read(path)?
+        } else {
+            Vec::new()
+        };
+        let mut mutations = Vec::new();
+        let mut next_sequence = after + 1;
+        let mut records = 0;
+        let mut offset = 0;
+        while let Some(header) = data.get(offset..offset + 4) {
+            let len = u32::from_le_bytes(header.try_into().unwrap()) as usize;
+            let Some(body) = data.get(offset + 4..offset + 4 + len) else {
+                break;
+            };
+            let Ok((sequence, batch)) = bincode::deserialize::<(u64, Vec<Mutation>)>(body) else {
+                break;
+            };
+            offset += 4 + len;
+            records += 1;
+            if sequence >= next_sequence {
+                next_sequence = sequence + 1;
+                mutations.extend(batch);
+            }
+        }
+        if offset < data.len() {
+            tracing::warn!(
+                "Dropping {} bytes of an incomplete record at the end of {}",
+                data.len() - offset,
+                path.display()
+            );
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        // New records go after the last complete one
+        file.set_len(offset as u64)?;
+        let log = Self {
+            file,
+            next_sequence,
+            records,
+        };
+        Ok((log, mutations))
+    }
+
+    /// Append one batch of mutations, returning its sequence number
+    pub fn append(&mut self, mutations: &[Mutation]) -> Result<u64> {
+        let sequence = self.next_sequence;
+        let body = bincode::serialize(&(sequence, mutations))?;
+        let mut record = Vec::with_capacity(4 + body.len());
+        record.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        record.extend_from_slice(&body);
+        // One write per record, so a crash cuts at most the last one short
+        self.file.write_all(&record)?;
+        self.next_sequence += 1;
+        self.records += 1;
+        Ok(sequence)
+    }
+
+    /// Sequence number of the last record appended or read
+    pub fn last_sequence(&self) -> u64 {
+        self.next_sequence - 1
+    }
+
+    /// Records in the file
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// Drop every record, once a snapshot includes them. Sequence numbers carry on.
+    pub fn truncate(&mut self) -> Result<()> {
+        self.file.set_len(0)?;
+        self.records = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{GraphName, Literal, NamedNode};
+
+    fn quad(object: Term) -> Quad {
+        Quad::new(
+            NamedNode::new_unchecked("http://ex.org/a"),
+            NamedNode::new_unchecked("http://ex.org/p"),
+            object,
+            GraphName::DefaultGraph,
+        )
+    }
+
+    #[test]
+    fn test_snapshot_and_log_replay() {
+        let dir = std::env::temp_dir().join("synapse_test_snapshot_replay");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (snapshot_path, log_path) = (dir.join("mirror.snapshot"), dir.join("mirror.wal"));
+
+        let link = quad(NamedNode::new_unchecked("http://ex.org/b").into());
+        let age = quad(Literal::from(42).into());
+        let mut topology = GraphTopology::new();
+        let mut properties = PropertyStore::new();
+        let (mut log, replay) = WriteAheadLog::open(&log_path, 0).unwrap();
+        assert!(replay.is_empty());
+        for mutations in [
+            Mutation::for_quad(&link, true),
+            Mutation::for_quad(&age, true),
+        ] {
+            for m in &mutations {
+                m.apply(&mut topology, &mut properties);
+            }
+            log.append(&mutations).unwrap();
+        }
+        GraphSnapshot::capture(&topology, &properties, log.last_sequence())
+            .save_to_file(&snapshot_path)
+            .unwrap();
+        log.truncate().unwrap();
+        let removal = Mutation::for_quad(&age, false);
+        assert_eq!(log.append(&removal).unwrap(), 3);
+        drop(log);
+
+        // A crash mid-append leaves a partial record behind
+        let mut file = OpenOptions::new().append(true).open(&log_path).unwrap();
+        file.write_all(&[200, 0, 0, 0, 1, 2]).unwrap();
+        drop(file);
+
+        let snapshot = GraphSnapshot::load_from_file(&snapshot_path).unwrap();
+        assert_eq!(snapshot.sequence, 2);
+        let (log, replay) = WriteAheadLog::open(&log_path, snapshot.sequence).unwrap();
+        assert_eq!(replay, removal);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log.last_sequence(), 3);
+
+        let (mut topology, mut properties) = snapshot.into_mirrors(ColumnPolicy::default());
+        assert_eq!(topology.edge_count(), 2);
+        assert_eq!(
+            properties.get("<http://ex.org/a>", "<http://ex.org/p>"),
+            &[PropertyValue::Integer(42)]
+        );
+        for m in &replay {
+            m.apply(&mut topology, &mut properties);
+        }
+        assert_eq!(topology.edge_count(), 1);
+        assert_eq!(properties.value_count(), 0);
+    }
+
+    #[test]
+    fn test_snapshots_are_compressed_and_read_uncompressed_ones() {
+        let dir = std::env::temp_dir().join("synapse_test_snapshot_compression");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut topology = GraphTopology::new();
+        for i in 0..1000 {
+            topology.add_edge(
+                &format!("<http://ex.org/item/{}>", i),
+                "<http://ex.org/partOf>",
+                "<http://ex.org/catalog>",
+            );
+        }
+        let snapshot = GraphSnapshot::capture(&topology, &PropertyStore::new(), 7);
+
+        let compressed = dir.join("compressed.snapshot");
+        snapshot.save_to_file(&compressed).unwrap();
+        let bytes = std::fs::read(&compressed).unwrap();
+        assert!(bytes.starts_with(SNAPSHOT_MAGIC));
+        let plain = bincode::serialize(&snapshot).unwrap();
+        assert!(bytes.len() * 5 < plain.len());
+        let loaded = GraphSnapshot::load_from_file(&compressed).unwrap();
+        assert_eq!(loaded.sequence, 7);
+        assert_eq!(loaded.edges, snapshot.edges);
+
+        let legacy = dir.join("legacy.snapshot");
+        std::fs::write(&legacy, plain).unwrap();
+        let loaded = GraphSnapshot::load_from_file(&legacy).unwrap();
+        assert_eq!(loaded.edges.len(), 1000);
+    }
+}
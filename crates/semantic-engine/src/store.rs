@@ -1,16 +1,70 @@
+use crate::analytics::{
+    Centrality, Communities, CommunityAlgorithm, GraphPath, NodeAnalytics, PageRank,
+    PageRankOptions,
+};
+use crate::changes::{Change, ChangeFeed, ChangeKind};
+use crate::ingest::pipeline::NamespacePipeline;
+use crate::maintenance::{MaintenancePolicy, MaintenanceReport};
+use crate::memory::{uri_map_bytes, MemoryUsage};
+use crate::network::{write_network, NetworkFormat};
 use crate::persistence::{load_bincode, save_bincode};
+use crate::properties::{ColumnLayout, ColumnPolicy, IndexKind, PropertyStore, PropertyValue};
+use crate::snapshot::{GraphSnapshot, Mutation, WriteAheadLog};
+use crate::topology::GraphTopology;
 use crate::vector_store::VectorStore;
 use anyhow::Result;
 use oxigraph::model::*;
 use oxigraph::store::Store;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeBounds;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 const DEFAULT_MAPPING_SAVE_THRESHOLD: usize = 1000;
+/// Unsaved URI mappings are written at most this often, unless the threshold is hit
+const MAPPING_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Prefix of the named graphs ingestion batches are stored in
+pub const BATCH_GRAPH_PREFIX: &str = "urn:batch:";
+
+/// Files a namespace keeps next to its graph storage
+const SIDECAR_FILES: &[&str] = &[
+    "uri_mappings.bin",
+    "uri_mappings.json",
+    "vectors.json",
+    "pipeline.json",
+    PROPERTY_INDEXES_FILE,
+    MIRROR_SNAPSHOT_FILE,
+    MIRROR_LOG_FILE,
+];
+
+/// Which properties a namespace indexes, by property in N-Triples form
+const PROPERTY_INDEXES_FILE: &str = "property_indexes.json";
+
+/// Triples embedded per vector store call during ingestion
+const EMBED_BATCH_SIZE: usize = 256;
+
+/// Full snapshot of the topology and property mirrors
+const MIRROR_SNAPSHOT_FILE: &str = "mirror.snapshot";
+
+/// Write-ahead log of mirror changes since the snapshot
+const MIRROR_LOG_FILE: &str = "mirror.wal";
+
+/// Log records after which the mirrors are snapshotted and the log emptied
+const MIRROR_SNAPSHOT_INTERVAL: usize = 10_000;
+
+/// Predicates whose literal values name a resource
+const LABEL_PREDICATES: &[&str] = &[
+    "http://www.w3.org/2000/01/rdf-schema#label",
+    "http://www.w3.org/2004/02/skos/core#prefLabel",
+    "http://purl.org/dc/terms/title",
+    "http://schema.org/name",
+    "http://xmlns.com/foaf/0.1/name",
+];
 
 /// Persisted URI mappings
 #[derive(Serialize, Deserialize, Default)]
@@ -19,13 +73,64 @@ struct UriMappings {
     next_id: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// `UriMappings` borrowed from the live map, so saving does not copy it
+#[derive(Serialize)]
+struct UriMappingsRef<'a> {
+    uri_to_id: &'a HashMap<String, u32>,
+    next_id: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Provenance {
     pub source: String,
     pub timestamp: String,
+    /// Extractor or tool that produced the triples
     pub method: String,
+    /// Absolute path of the source file, for file-based ingestion
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Hex SHA-256 of the source file contents
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Location within the source file, when the extractor tracks it
+    #[serde(default)]
+    pub span: Option<SourceSpan>,
 }
 
+/// Where in a source file a fact came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SourceSpan {
+    /// First and last line, 1-based and inclusive
+    pub line_start: u64,
+    pub line_end: u64,
+    /// Byte range `[start, end)` in the file's UTF-8 text (after any transcoding), if known
+    #[serde(default)]
+    pub byte_start: Option<u64>,
+    #[serde(default)]
+    pub byte_end: Option<u64>,
+}
+
+/// A batch of a subject's facts and the provenance recorded for it
+#[derive(Debug, Clone)]
+pub struct ProvenanceRecord {
+    /// Named graph holding the batch
+    pub graph: String,
+    pub provenance: Provenance,
+    /// The subject's (predicate, object) pairs in this batch
+    pub facts: Vec<(String, String)>,
+}
+
+const PROV_DERIVED_FROM: &str = "http://www.w3.org/ns/prov#wasDerivedFrom";
+const PROV_GENERATED_AT: &str = "http://www.w3.org/ns/prov#generatedAtTime";
+const PROV_GENERATED_BY: &str = "http://www.w3.org/ns/prov#wasGeneratedBy";
+const SOURCE_PATH: &str = "http://synapse.os/prov#path";
+const SOURCE_SHA256: &str = "http://synapse.os/prov#sha256";
+const SOURCE_LINE_START: &str = "http://synapse.os/prov#lineStart";
+const SOURCE_LINE_END: &str = "http://synapse.os/prov#lineEnd";
+const SOURCE_BYTE_START: &str = "http://synapse.os/prov#byteStart";
+const SOURCE_BYTE_END: &str = "http://synapse.os/prov#byteEnd";
+
+#[derive(Debug, Clone)]
 pub struct IngestTriple {
     pub subject: String,
     pub predicate: String,
@@ -33,6 +138,151 @@ pub struct IngestTriple {
     pub provenance: Option<Provenance>,
 }
 
+/// What an ingestion did, returned by `ingest_triples` and the `IngestionEngine` sources
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IngestReport {
+    /// Triples newly inserted
+    pub added: u32,
+    /// Triples that were already present
+    pub duplicates: u32,
+    /// Triples or source rows rejected as invalid
+    pub malformed: u32,
+    /// Vector store entries created (triples and text chunks)
+    pub vectors_created: u32,
+    pub elapsed_ms: u64,
+}
+
+impl IngestReport {
+    /// Add another report's counts to this one (elapsed times are summed)
+    pub fn merge(&mut self, other: &IngestReport) {
+        self.added += other.added;
+        self.duplicates += other.duplicates;
+        self.malformed += other.malformed;
+        self.vectors_created += other.vectors_created;
+        self.elapsed_ms += other.elapsed_ms;
+    }
+}
+
+/// Size and activity of one namespace, from `SynapseStore::stats`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoreStats {
+    pub triples: usize,
+    /// Named graphs (one per provenance batch, plus loaded graphs)
+    pub graphs: usize,
+    pub vectors: usize,
+    /// Embeddings left behind by removed keys, reclaimed by compaction
+    pub stale_vectors: usize,
+    pub uri_mappings: usize,
+    /// Bytes under the namespace directory
+    pub disk_bytes: u64,
+    pub disk: DiskUsage,
+    pub memory: MemoryUsage,
+    pub last_modified: Option<std::time::SystemTime>,
+}
+
+/// Bytes on disk of one namespace, by what the files hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// RocksDB files or `graph.nq`, and anything else not listed below
+    pub graph: u64,
+    pub vectors: u64,
+    /// Snapshot and write-ahead log of the topology and property mirrors
+    pub mirrors: u64,
+    pub uri_mappings: u64,
+}
+
+impl DiskUsage {
+    /// `(component, bytes)` pairs, as labelled in metrics
+    pub fn components(&self) -> [(&'static str, u64); 4] {
+        [
+            ("graph", self.graph),
+            ("vectors", self.vectors),
+            ("mirrors", self.mirrors),
+            ("uri_mappings", self.uri_mappings),
+        ]
+    }
+}
+
+/// What `SynapseStore::delete_graph` removed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphDeletion {
+    pub triples_removed: usize,
+    pub vectors_removed: usize,
+}
+
+/// What `SynapseStore::compact` reclaimed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactionReport {
+    /// Stale embeddings dropped from the vector index
+    pub vectors_removed: usize,
+    /// ID mappings of terms no longer in the graph
+    pub uri_mappings_removed: usize,
+    pub disk_bytes_before: u64,
+    pub disk_bytes_after: u64,
+    pub elapsed_ms: u64,
+}
+
+/// Quads changed by `SynapseStore::update_sparql`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SparqlUpdateReport {
+    pub inserted: usize,
+    pub deleted: usize,
+}
+
+/// What a search hit is about, from `SynapseStore::search_details`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchDetails {
+    pub labels: Vec<String>,
+    /// The resource's triple that best matches the query, as (subject, predicate, object)
+    pub triple: Option<(String, String, String)>,
+    /// Named graph of that triple; empty for the default graph
+    pub graph: String,
+    /// Provenance source of that graph's batch
+    pub source: String,
+}
+
+/// How `SynapseStore::neighbors` walks the graph
+#[derive(Debug, Clone)]
+pub struct TraversalOptions {
+    /// "outgoing", "incoming" or "both"
+    pub direction: String,
+    /// Only follow predicates whose IRI contains this string
+    pub edge_filter: Option<String>,
+    /// Only reach nodes with this `rdf:type`
+    pub node_type_filter: Option<String>,
+    pub depth: usize,
+    /// Maximum nodes collected per layer
+    pub limit_per_layer: usize,
+    /// "default" scores 1/depth; "degree" also penalizes highly connected nodes;
+    /// "centrality" penalizes nodes by betweenness from the latest `compute_centrality`
+    /// (like "degree" before the first run)
+    pub scoring: String,
+}
+
+impl Default for TraversalOptions {
+    fn default() -> Self {
+        Self {
+            direction: "outgoing".to_string(),
+            edge_filter: None,
+            node_type_filter: None,
+            depth: 1,
+            limit_per_layer: usize::MAX,
+            scoring: "default".to_string(),
+        }
+    }
+}
+
+/// A node reached by `SynapseStore::neighbors`; terms are in N-Triples form
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNeighbor {
+    pub node_id: u32,
+    pub edge_type: String,
+    pub uri: String,
+    pub direction: String,
+    pub depth: u32,
+    pub score: f32,
+}
+
 pub struct SynapseStore {
     pub store: Store,
     pub namespace: String,
@@ -43,9 +293,25 @@ pub struct SynapseStore {
     pub next_id: std::sync::atomic::AtomicU32,
     // Vector store for hybrid search
     pub vector_store: Option<Arc<VectorStore>>,
+    // Ingestion transforms configured for this namespace
+    pub pipeline: NamespacePipeline,
+    // Where inserts and deletes are announced; engines share one feed across stores
+    pub changes: ChangeFeed,
+    // Adjacency mirror of the graph, for traversals
+    pub topology: RwLock<GraphTopology>,
+    // Typed mirror of literal-valued facts, for filtering on attributes
+    pub properties: RwLock<PropertyStore>,
+    // Changes to the mirrors since their last snapshot; held while they change,
+    // so records are in the order the changes were applied
+    mirror_log: Mutex<WriteAheadLog>,
+    // Per-node results of the latest analytics runs
+    pub analytics: RwLock<NodeAnalytics>,
     // Persistence state
     dirty_count: AtomicUsize,
     save_threshold: usize,
+    mappings_saved_at: Mutex<std::time::Instant>,
+    // Last write through this handle, which may not have reached disk yet
+    modified: RwLock<Option<std::time::SystemTime>>,
 }
 
 impl SynapseStore {
@@ -64,7 +330,7 @@ impl SynapseStore {
                 let file = std::fs::File::open(&graph_path)?;
                 let reader = std::io::BufReader::new(file);
                 s.load_from_reader(oxigraph::io::RdfFormat::NQuads, reader)?;
-                eprintln!("Loaded in-memory graph from {}", graph_path.display());
+                tracing::info!("Loaded in-memory graph from {}", graph_path.display());
             }
             s
         };
@@ -95,14 +361,37 @@ impl SynapseStore {
         };
 
         // Initialize vector store (optional, can fail gracefully)
-        let vector_store = match VectorStore::new(namespace) {
+        let vector_store = match VectorStore::open(Some(path.clone())) {
             Ok(vs) => Some(Arc::new(vs)),
             Err(e) => {
-                eprintln!("WARNING: Failed to initialize vector store for namespace '{}': {}", namespace, e);
+                tracing::warn!(
+                    "Failed to initialize vector store for namespace '{}': {}",
+                    namespace,
+                    e
+                );
                 None
             }
         };
 
+        let (topology, mut properties, mirror_log) = Self::open_mirrors(&store, &path)?;
+        let indexes_path = path.join(PROPERTY_INDEXES_FILE);
+        if indexes_path.exists() {
+            let indexes: BTreeMap<String, IndexKind> =
+                serde_json::from_str(&std::fs::read_to_string(&indexes_path)?)?;
+            for (property, kind) in indexes {
+                properties.create_index(&property, kind);
+            }
+        }
+
+        let pipeline = NamespacePipeline::load(&path).unwrap_or_else(|e| {
+            tracing::warn!(
+                "Ignoring invalid pipeline config for namespace '{}': {}",
+                namespace,
+                e
+            );
+            NamespacePipeline::default()
+        });
+
         Ok(Self {
             store,
             namespace: namespace.to_string(),
@@ -111,54 +400,93 @@ impl SynapseStore {
             uri_to_id: RwLock::new(uri_to_id),
             next_id: std::sync::atomic::AtomicU32::new(next_id),
             vector_store,
+            pipeline,
+            changes: ChangeFeed::new(),
+            topology: RwLock::new(topology),
+            properties: RwLock::new(properties),
+            mirror_log: Mutex::new(mirror_log),
+            analytics: RwLock::new(NodeAnalytics::default()),
             dirty_count: AtomicUsize::new(0),
             save_threshold: DEFAULT_MAPPING_SAVE_THRESHOLD,
+            mappings_saved_at: Mutex::new(std::time::Instant::now()),
+            modified: RwLock::new(None),
         })
     }
 
     /// Save URI mappings to disk
     fn save_mappings(&self) -> Result<()> {
-        let mappings = UriMappings {
-            uri_to_id: self.uri_to_id.read().unwrap().clone(),
+        // Holding the read lock keeps new IDs out until the map is written, so the
+        // dirty count read here covers everything saved
+        let uri_to_id = self.uri_to_id.read().unwrap();
+        let current_dirty = self.dirty_count.load(Ordering::Relaxed);
+        let mappings = UriMappingsRef {
+            uri_to_id: &uri_to_id,
             next_id: self.next_id.load(std::sync::atomic::Ordering::Relaxed),
         };
-        // Capture the count before saving? No, we just care that we saved the current state.
-        // But if new items are added during save, the dirty count will increment.
-        // We need to subtract what we think we saved.
-        // Since we save the *entire* map, we effectively save *all* dirty items up to that point.
-        // So we can read the dirty count, save, then subtract.
-        let current_dirty = self.dirty_count.load(Ordering::Relaxed);
-
         save_bincode(&self.storage_path.join("uri_mappings.bin"), &mappings)?;
+        drop(uri_to_id);
 
         if current_dirty > 0 {
             let _ = self.dirty_count.fetch_sub(current_dirty, Ordering::Relaxed);
         }
+        *self.mappings_saved_at.lock().unwrap() = std::time::Instant::now();
         Ok(())
     }
 
-    /// Force save all data to disk
+    /// Save URI mappings once a write has left enough of them unsaved, or some have
+    /// waited `MAPPING_SAVE_INTERVAL`. Called after each write batch rather than per ID.
+    fn save_mappings_if_due(&self) {
+        let dirty = self.dirty_count.load(Ordering::Relaxed);
+        let due = dirty >= self.save_threshold
+            || (dirty > 0
+                && self.mappings_saved_at.lock().unwrap().elapsed() >= MAPPING_SAVE_INTERVAL);
+        if due {
+            if let Err(e) = self.save_mappings() {
+                tracing::warn!(
+                    "Failed to save URI mappings for namespace '{}': {}",
+                    self.namespace,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Force save all data to disk. Every part is written even if an earlier one
+    /// fails, so one bad file does not cost the others; the first error is returned.
     pub fn flush(&self) -> Result<()> {
-        self.save_mappings()?;
+        let mut results = vec![self.save_mappings(), self.snapshot_mirrors()];
         if let Some(ref vs) = self.vector_store {
-            vs.flush()?;
+            results.push(vs.flush());
         }
-
         #[cfg(not(feature = "rocksdb"))]
-        {
-            let graph_path = self.storage_path.join("graph.nq");
-            // Atomic write pattern: write to tmp, then rename
-            let tmp_path = self.storage_path.join("graph.nq.tmp");
-            let file = std::fs::File::create(&tmp_path)?;
-            let writer = std::io::BufWriter::new(file);
-            self.store.dump_to_writer(oxigraph::io::RdfFormat::NQuads, writer)?;
-            std::fs::rename(tmp_path, graph_path)?;
-            eprintln!("Persisted in-memory graph to disk.");
-        }
+        results.push(self.dump_graph());
+        results.into_iter().collect()
+    }
 
+    #[cfg(not(feature = "rocksdb"))]
+    fn dump_graph(&self) -> Result<()> {
+        let graph_path = self.storage_path.join("graph.nq");
+        // Atomic write pattern: write to tmp, then rename
+        let tmp_path = self.storage_path.join("graph.nq.tmp");
+        let file = std::fs::File::create(&tmp_path)?;
+        let writer = std::io::BufWriter::new(file);
+        self.store
+            .dump_to_writer(oxigraph::io::RdfFormat::NQuads, writer)?;
+        std::fs::rename(tmp_path, &graph_path)?;
+        tracing::debug!("Persisted in-memory graph to {}", graph_path.display());
         Ok(())
     }
 
+    /// Whether URI mappings, mirror changes or vectors are waiting to be written
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty_count.load(Ordering::Relaxed) > 0
+            || !self.mirror_log.lock().unwrap().is_empty()
+            || self
+                .vector_store
+                .as_ref()
+                .is_some_and(|vs| vs.has_unsaved_changes())
+    }
+
     pub fn get_or_create_id(&self, uri: &str) -> u32 {
         {
             let map = self.uri_to_id.read().unwrap();
@@ -180,24 +508,195 @@ impl SynapseStore {
         uri_map.insert(uri.to_string(), id);
         id_map.insert(id, uri.to_string());
 
-        drop(uri_map);
-        drop(id_map);
-
-        // Check if we need to auto-save mappings
-        let count = self.dirty_count.fetch_add(1, Ordering::Relaxed);
-        if count + 1 >= self.save_threshold {
-            let _ = self.save_mappings();
-        }
+        // Saved by the write that allocated it, in a batch with the others
+        self.dirty_count.fetch_add(1, Ordering::Relaxed);
 
         id
     }
 
+    /// ID of an already mapped URI; reads use this so they never allocate one
+    pub fn get_id(&self, uri: &str) -> Option<u32> {
+        self.uri_to_id.read().unwrap().get(uri).copied()
+    }
+
+    /// ID of a term in N-Triples form, by its plain IRI first; 0 when it has none
+    pub fn term_id(&self, term: &str) -> u32 {
+        let plain = term.strip_prefix('<').and_then(|iri| iri.strip_suffix('>'));
+        plain
+            .and_then(|iri| self.get_id(iri))
+            .or_else(|| self.get_id(term))
+            .unwrap_or(0)
+    }
+
     pub fn get_uri(&self, id: u32) -> Option<String> {
         self.id_to_uri.read().unwrap().get(&id).cloned()
     }
 
-    pub async fn ingest_triples(&self, triples: Vec<IngestTriple>) -> Result<(u32, u32)> {
-        let mut added = 0;
+    fn build_mirrors(
+        store: &Store,
+        policy: ColumnPolicy,
+    ) -> Result<(GraphTopology, PropertyStore)> {
+        let mut topology = GraphTopology::new();
+        let mut properties = PropertyStore::with_policy(policy);
+        for quad in store.iter() {
+            for mutation in Mutation::for_quad(&quad?, true) {
+                mutation.apply(&mut topology, &mut properties);
+            }
+        }
+        // Columns filled early were only checked against the nodes seen so far
+        properties.rebalance();
+        Ok((topology, properties))
+    }
+
+    /// Load the mirrors from their snapshot and log, or build them from the graph
+    /// when there is no snapshot or the graph has a different number of quads
+    /// (such as after a crash that lost unflushed graph writes)
+    fn open_mirrors(
+        store: &Store,
+        dir: &Path,
+    ) -> Result<(GraphTopology, PropertyStore, WriteAheadLog)> {
+        let policy = ColumnPolicy::from_env();
+        let snapshot_path = dir.join(MIRROR_SNAPSHOT_FILE);
+        let snapshot = if snapshot_path.exists() {
+            GraphSnapshot::load_from_file(&snapshot_path)
+                .map_err(|e| {
+                    tracing::warn!(
+                        "Ignoring unreadable mirror snapshot {}: {}",
+                        snapshot_path.display(),
+                        e
+                    )
+                })
+                .ok()
+        } else {
+            None
+        };
+        let sequence = snapshot.as_ref().map_or(0, |s| s.sequence);
+        let (mut log, replay) = WriteAheadLog::open(&dir.join(MIRROR_LOG_FILE), sequence)?;
+
+        if let Some(snapshot) = snapshot {
+            let (mut topology, mut properties) = snapshot.into_mirrors(policy);
+            for mutation in &replay {
+                mutation.apply(&mut topology, &mut properties);
+            }
+            if topology.edge_count() == store.len()? {
+                return Ok((topology, properties, log));
+            }
+            tracing::warn!(
+                "Mirror snapshot in {} does not match the graph; rebuilding",
+                dir.display()
+            );
+        }
+        let (topology, properties) = Self::build_mirrors(store, policy)?;
+        GraphSnapshot::capture(&topology, &properties, log.last_sequence())
+            .save_to_file(&snapshot_path)?;
+        log.truncate()?;
+        Ok((topology, properties, log))
+    }
+
+    /// Apply mutations to the mirrors and log them, snapshotting once the log is
+    /// long enough
+    fn record_mutations(&self, mutations: &[Mutation]) {
+        let mut log = self.mirror_log.lock().unwrap();
+        {
+            let mut topology = self.topology.write().unwrap();
+            let mut properties = self.properties.write().unwrap();
+            for mutation in mutations {
+                mutation.apply(&mut topology, &mut properties);
+            }
+        }
+        if let Err(e) = log.append(mutations) {
+            tracing::warn!(
+                "Failed to log mirror changes for namespace '{}': {}",
+                self.namespace,
+                e
+            );
+        }
+        if log.len() >= MIRROR_SNAPSHOT_INTERVAL {
+            if let Err(e) = self.write_snapshot(&mut log) {
+                tracing::warn!(
+                    "Failed to snapshot mirrors for namespace '{}': {}",
+                    self.namespace,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Record an inserted quad in the topology and, for literal objects, the property store
+    fn mirror_quad(&self, quad: &Quad) {
+        self.record_mutations(&Mutation::for_quad(quad, true));
+    }
+
+    fn unmirror_quad(&self, quad: &Quad) {
+        self.record_mutations(&Mutation::for_quad(quad, false));
+    }
+
+    fn write_snapshot(&self, log: &mut WriteAheadLog) -> Result<()> {
+        let snapshot = GraphSnapshot::capture(
+            &self.topology.read().unwrap(),
+            &self.properties.read().unwrap(),
+            log.last_sequence(),
+        );
+        snapshot.save_to_file(&self.storage_path.join(MIRROR_SNAPSHOT_FILE))?;
+        log.truncate()
+    }
+
+    /// Write a full snapshot of the topology and property mirrors and empty their log
+    pub fn snapshot_mirrors(&self) -> Result<()> {
+        let mut log = self.mirror_log.lock().unwrap();
+        self.write_snapshot(&mut log)
+    }
+
+    /// Rebuild the topology and property mirrors from the graph, after writes that
+    /// bypassed this store (such as materialized inferences)
+    pub fn rebuild_topology(&self) -> Result<()> {
+        let mut log = self.mirror_log.lock().unwrap();
+        let (policy, indexes, pinned) = {
+            let current = self.properties.read().unwrap();
+            let indexes: Vec<(String, IndexKind)> = current
+                .indexes()
+                .into_iter()
+                .map(|(property, kind)| (property.to_string(), kind))
+                .collect();
+            let pinned: Vec<(String, ColumnLayout)> = current
+                .layouts()
+                .into_iter()
+                .filter(|(_, _, pinned)| *pinned)
+                .map(|(property, layout, _)| (property.to_string(), layout))
+                .collect();
+            (current.policy(), indexes, pinned)
+        };
+        let (topology, mut properties) = Self::build_mirrors(&self.store, policy)?;
+        for (property, kind) in indexes {
+            properties.create_index(&property, kind);
+        }
+        for (property, layout) in pinned {
+            properties.set_layout(&property, Some(layout));
+        }
+        *self.topology.write().unwrap() = topology;
+        *self.properties.write().unwrap() = properties;
+        self.write_snapshot(&mut log)
+    }
+
+    /// Whether triples with this provenance go to a new batch graph rather than the
+    /// default graph; MCP triples stay in the default graph for easier querying
+    pub fn uses_batch_graph(provenance: Option<&Provenance>) -> bool {
+        provenance.is_some_and(|p| p.source != "mcp")
+    }
+
+    pub async fn ingest_triples(&self, triples: Vec<IngestTriple>) -> Result<IngestReport> {
+        self.ingest_triples_cancellable(triples, None).await
+    }
+
+    /// `ingest_triples` that stops with an error once `cancel` fires. Triples stored
+    /// before that point are kept.
+    pub async fn ingest_triples_cancellable(
+        &self,
+        triples: Vec<IngestTriple>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<IngestReport> {
+        let started = std::time::Instant::now();
+        let mut report = IngestReport::default();
 
         // Group by provenance to optimize batch insertion into named graphs
         let mut batches: HashMap<Option<Provenance>, Vec<(String, String, String)>> =
@@ -210,60 +709,50 @@ impl SynapseStore {
                 .push((t.subject, t.predicate, t.object));
         }
 
+        // Resolve every triple first, then insert the new ones in bulk and embed them
+        // in batches, rather than one triple at a time
+        let mut metadata_quads = Vec::new();
+        let mut quads = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut vector_keys = std::collections::HashSet::new();
+        let mut vector_items = Vec::new();
         for (prov, batch_triples) in batches {
             let graph_name = if let Some(p) = &prov {
                 let uuid = Uuid::new_v4();
-                let uri = format!("urn:batch:{}", uuid);
+                let uri = format!("{}{}", BATCH_GRAPH_PREFIX, uuid);
 
                 let batch_node = NamedNode::new_unchecked(&uri);
-                let p_derived =
-                    NamedNode::new_unchecked("http://www.w3.org/ns/prov#wasDerivedFrom");
-                let p_time = NamedNode::new_unchecked("http://www.w3.org/ns/prov#generatedAtTime");
-                let p_method = NamedNode::new_unchecked("http://www.w3.org/ns/prov#wasGeneratedBy");
-
-                let o_source = Literal::new_simple_literal(&p.source);
-                let o_time = Literal::new_simple_literal(&p.timestamp);
-                let o_method = Literal::new_simple_literal(&p.method);
-
-                self.store.insert(&Quad::new(
-                    batch_node.clone(),
-                    p_derived,
-                    o_source,
-                    GraphName::DefaultGraph,
-                ))?;
-                self.store.insert(&Quad::new(
-                    batch_node.clone(),
-                    p_time,
-                    o_time,
-                    GraphName::DefaultGraph,
-                ))?;
-                self.store.insert(&Quad::new(
-                    batch_node.clone(),
-                    p_method,
-                    o_method,
-                    GraphName::DefaultGraph,
-                ))?;
-
-                // If source is "mcp", put triples in default graph for easier querying
-                if p.source == "mcp" {
-                    GraphName::DefaultGraph
-                } else {
+                for (predicate, object) in Self::provenance_terms(p) {
+                    metadata_quads.push(Quad::new(
+                        batch_node.clone(),
+                        NamedNode::new_unchecked(predicate),
+                        object,
+                        GraphName::DefaultGraph,
+                    ));
+                }
+
+                if Self::uses_batch_graph(Some(p)) {
                     GraphName::NamedNode(batch_node)
+                } else {
+                    GraphName::DefaultGraph
                 }
             } else {
                 GraphName::DefaultGraph
             };
 
             for (s, p, o) in batch_triples {
-                let subject_uri = self.ensure_uri(&s);
+                let subject_uri = self.resource_uri(&s);
                 let predicate_uri = self.ensure_uri(&p);
 
-                let (object_term, object_key_str) = if o.starts_with('"') && o.ends_with('"') && o.len() >= 2 {
-                    let literal_val = &o[1..o.len() - 1];
-                    (Term::Literal(Literal::new_simple_literal(literal_val)), literal_val.to_string())
-                } else {
-                    let uri = self.ensure_uri(&o);
-                    (Term::NamedNode(NamedNode::new_unchecked(&uri)), uri)
+                let Some((object, object_key_str)) = self.object_term(&o) else {
+                    report.malformed += 1;
+                    continue;
+                };
+                let (Some(subject), Ok(predicate)) =
+                    (Self::resource(&subject_uri), NamedNode::new(&predicate_uri))
+                else {
+                    report.malformed += 1;
+                    continue;
                 };
 
                 // Register URIs in the ID mapping (for gRPC compatibility)
@@ -271,18 +760,19 @@ impl SynapseStore {
                 self.get_or_create_id(&predicate_uri);
                 self.get_or_create_id(&object_key_str);
 
-                let subject = Subject::NamedNode(NamedNode::new_unchecked(&subject_uri));
-                let predicate = NamedNode::new_unchecked(&predicate_uri);
-                let object = object_term;
-
+                // A fact already in the target graph, or earlier in this call, is a duplicate
                 let quad = Quad::new(subject, predicate, object, graph_name.clone());
-                let inserted = self.store.insert(&quad)?;
-                
+                if seen.contains(&quad) || self.store.contains(&quad)? {
+                    report.duplicates += 1;
+                    continue;
+                }
+                seen.insert(quad.clone());
+                quads.push(quad);
+
                 // Also index in vector store if available
                 if let Some(ref vs) = self.vector_store {
-                    // We check if it's already in the vector store by key
                     let key = format!("{}|{}|{}", subject_uri, predicate_uri, object_key_str);
-                    if vs.get_id(&key).is_none() {
+                    if vs.get_id(&key).is_none() && vector_keys.insert(key.clone()) {
                         // Create searchable content from triple
                         let content = format!("{} {} {}", s, p, o);
                         // Pass metadata including the subject URI for graph expansion later
@@ -292,22 +782,342 @@ impl SynapseStore {
                             "object": object_key_str,
                             "type": "triple"
                         });
-
-                        if let Err(e) = vs.add(&key, &content, metadata).await {
-                            // If we just inserted it into the graph but vector failed, 
-                            // we technically have an inconsistency, but for now we just log.
-                            eprintln!("Vector store insertion failed for {}: {}", key, e);
-                        }
+                        vector_items.push((key, content, metadata));
                     }
                 }
+            }
+        }
+
+        if cancel.is_some_and(|c| c.is_cancelled()) {
+            return Err(anyhow::anyhow!(
+                "Ingestion cancelled after {} triples",
+                report.added
+            ));
+        }
+        // Provenance metadata may already be there; the facts were checked above
+        let metadata_quads: Vec<Quad> = metadata_quads
+            .into_iter()
+            .filter(|quad| !self.store.contains(quad).unwrap_or(false))
+            .collect();
+        self.store
+            .extend(metadata_quads.iter().chain(&quads).cloned())?;
+        let mutations: Vec<Mutation> = metadata_quads
+            .iter()
+            .chain(&quads)
+            .flat_map(|quad| Mutation::for_quad(quad, true))
+            .collect();
+        self.record_mutations(&mutations);
+        for quad in &quads {
+            self.changes
+                .publish(|| Change::triple(ChangeKind::Insert, &self.namespace, quad.clone()));
+        }
+        report.added = quads.len() as u32;
+
+        if let Some(vs) = &self.vector_store {
+            report.vectors_created = Self::embed_batches(vs, vector_items, cancel).await? as u32;
+        }
+
+        if report.added > 0 {
+            *self.modified.write().unwrap() = Some(std::time::SystemTime::now());
+        }
+        self.save_mappings_if_due();
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        Ok(report)
+    }
+
+    /// Embed and index vector items in batches of `EMBED_BATCH_SIZE`, up to one batch
+    /// per core at a time. A failed batch is logged and skipped, as the triples are
+    /// already stored. Returns the number of vectors added.
+    async fn embed_batches(
+        vs: &Arc<VectorStore>,
+        items: Vec<(String, String, serde_json::Value)>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<usize> {
+        use futures::StreamExt;
+
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let mut batches = Vec::new();
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            batches.push(items.by_ref().take(EMBED_BATCH_SIZE).collect::<Vec<_>>());
+        }
+        let mut results = futures::stream::iter(batches)
+            .map(|batch| {
+                let vs = vs.clone();
+                let len = batch.len();
+                tokio::spawn(async move { (len, vs.add_batch(batch).await) })
+            })
+            .buffer_unordered(parallelism);
+
+        let mut created = 0;
+        while let Some(result) = results.next().await {
+            match result? {
+                (_, Ok((_, added))) => created += added,
+                // The triples are stored either way; search just won't find them by vector
+                (len, Err(e)) => {
+                    tracing::warn!("Vector store insertion failed for {} triples: {}", len, e)
+                }
+            }
+            if cancel.is_some_and(|c| c.is_cancelled()) {
+                return Err(anyhow::anyhow!(
+                    "Ingestion cancelled after embedding {} triples",
+                    created
+                ));
+            }
+        }
+        Ok(created)
+    }
+
+    /// Load quads as they are, keeping their graph names (for namespace imports).
+    /// With `index_vectors`, new triples are embedded like ingested ones.
+    pub async fn import_quads(
+        &self,
+        quads: Vec<Quad>,
+        index_vectors: bool,
+    ) -> Result<IngestReport> {
+        let started = std::time::Instant::now();
+        let mut report = IngestReport::default();
+        let mut vector_keys = std::collections::HashSet::new();
+        let mut vector_items = Vec::new();
+        for quad in quads {
+            if !self.store.insert(&quad)? {
+                report.duplicates += 1;
+                continue;
+            }
+            report.added += 1;
+            self.mirror_quad(&quad);
+
+            let subject = Self::term_value(&quad.subject.clone().into());
+            let predicate = quad.predicate.as_str().to_string();
+            let object = Self::term_value(&quad.object);
+            self.get_or_create_id(&subject);
+            self.get_or_create_id(&predicate);
+            self.get_or_create_id(&object);
+
+            if let (true, Some(vs)) = (index_vectors, &self.vector_store) {
+                let key = format!("{}|{}|{}", subject, predicate, object);
+                if vs.get_id(&key).is_none() && vector_keys.insert(key.clone()) {
+                    let content = format!("{} {} {}", subject, predicate, object);
+                    let metadata = serde_json::json!({
+                        "uri": subject,
+                        "predicate": predicate,
+                        "object": object,
+                        "type": "triple"
+                    });
+                    vector_items.push((key, content, metadata));
+                }
+            }
+            self.changes
+                .publish(|| Change::triple(ChangeKind::Insert, &self.namespace, quad));
+        }
+        if let Some(vs) = &self.vector_store {
+            report.vectors_created = Self::embed_batches(vs, vector_items, None).await? as u32;
+        }
+
+        if report.added > 0 {
+            *self.modified.write().unwrap() = Some(std::time::SystemTime::now());
+        }
+        self.save_mappings_if_due();
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        Ok(report)
+    }
+
+    /// When the namespace last changed: the latest ingest through this handle or the
+    /// newest file in its directory, whichever is later
+    pub fn last_modified(&self) -> Option<std::time::SystemTime> {
+        let on_disk = std::fs::read_dir(&self.storage_path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+            .max();
+        on_disk.max(*self.modified.read().unwrap())
+    }
+
+    pub fn stats(&self) -> Result<StoreStats> {
+        let (vectors, stale_vectors) = match &self.vector_store {
+            Some(vs) => {
+                let (active, stale, _) = vs.stats();
+                (active, stale)
+            }
+            None => (0, 0),
+        };
+        Ok(StoreStats {
+            triples: self.store.len()?,
+            graphs: self.store.named_graphs().count(),
+            vectors,
+            stale_vectors,
+            uri_mappings: self.uri_to_id.read().unwrap().len(),
+            disk_bytes: dir_size(&self.storage_path),
+            disk: self.disk_usage(),
+            memory: self.memory_usage(),
+            last_modified: self.last_modified(),
+        })
+    }
 
-                if inserted {
-                    added += 1;
+    /// Estimated heap bytes of the mirrors, vectors and URI maps
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            topology: self.topology.read().unwrap().heap_bytes(),
+            properties: self.properties.read().unwrap().heap_bytes(),
+            vectors: self.vector_store.as_ref().map_or(0, |vs| vs.heap_bytes()),
+            uri_mappings: uri_map_bytes(
+                &self.uri_to_id.read().unwrap(),
+                &self.id_to_uri.read().unwrap(),
+            ),
+        }
+    }
+
+    /// Bytes under the namespace directory, by component
+    pub fn disk_usage(&self) -> DiskUsage {
+        let size = |files: &[&str]| -> u64 {
+            files
+                .iter()
+                .filter_map(|file| std::fs::metadata(self.storage_path.join(file)).ok())
+                .map(|metadata| metadata.len())
+                .sum()
+        };
+        let vectors = size(&["vectors.json"]);
+        let mirrors = size(&[MIRROR_SNAPSHOT_FILE, MIRROR_LOG_FILE]);
+        let uri_mappings = size(&["uri_mappings.bin", "uri_mappings.json"]);
+        DiskUsage {
+            graph: dir_size(&self.storage_path).saturating_sub(vectors + mirrors + uri_mappings),
+            vectors,
+            mirrors,
+            uri_mappings,
+        }
+    }
+
+    /// Drop one named graph (such as an ingestion batch), the embeddings of its triples
+    /// and the provenance recorded for it. Returns `None` when the graph does not exist.
+    pub fn delete_graph(&self, graph: &str) -> Result<Option<GraphDeletion>> {
+        let graph = NamedNode::new(graph)?;
+        let quads = self
+            .store
+            .quads_for_pattern(None, None, None, Some(graph.as_ref().into()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if quads.is_empty() && !self.store.contains_named_graph(&graph)? {
+            return Ok(None);
+        }
+
+        let mut deletion = GraphDeletion::default();
+        for quad in quads {
+            self.store.remove(&quad)?;
+            self.unmirror_quad(&quad);
+            deletion.triples_removed += 1;
+            // Keep the embedding if another graph still states the same fact
+            let still_stated = self
+                .store
+                .quads_for_pattern(
+                    Some(quad.subject.as_ref()),
+                    Some(quad.predicate.as_ref()),
+                    Some(quad.object.as_ref()),
+                    None,
+                )
+                .next()
+                .is_some();
+            if let (false, Some(vs)) = (still_stated, &self.vector_store) {
+                let key = format!(
+                    "{}|{}|{}",
+                    Self::term_value(&quad.subject.clone().into()),
+                    quad.predicate.as_str(),
+                    Self::term_value(&quad.object)
+                );
+                if vs.remove(&key) {
+                    deletion.vectors_removed += 1;
                 }
             }
+            self.changes
+                .publish(|| Change::triple(ChangeKind::Delete, &self.namespace, quad));
+        }
+        self.store.remove_named_graph(&graph)?;
+
+        // The batch's provenance lives in the default graph, about the graph node
+        let provenance = self
+            .store
+            .quads_for_pattern(
+                Some(graph.as_ref().into()),
+                None,
+                None,
+                Some(GraphNameRef::DefaultGraph),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        for quad in provenance {
+            self.store.remove(&quad)?;
+            self.unmirror_quad(&quad);
+        }
+
+        *self.modified.write().unwrap() = Some(std::time::SystemTime::now());
+        Ok(Some(deletion))
+    }
+
+    /// One maintenance pass: a full `compact` when the policy finds enough stale
+    /// embeddings, otherwise only an optimize of the graph storage; then a rebuild
+    /// of the topology mirror
+    pub fn maintain(&self, policy: &MaintenancePolicy) -> Result<MaintenanceReport> {
+        let started = std::time::Instant::now();
+        let (active, stale, _) = self
+            .vector_store
+            .as_ref()
+            .map_or((0, 0, 0), |vs| vs.stats());
+        let vectors_removed = if policy.needs_compaction(active, stale) {
+            Some(self.compact()?.vectors_removed)
+        } else {
+            #[cfg(feature = "rocksdb")]
+            self.store.optimize()?;
+            None
+        };
+        self.rebuild_topology()?;
+        Ok(MaintenanceReport {
+            vectors_removed,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Reclaim space: optimize the graph storage, drop stale embeddings, prune ID
+    /// mappings of terms that left the graph, and persist everything
+    pub fn compact(&self) -> Result<CompactionReport> {
+        let started = std::time::Instant::now();
+        let disk_bytes_before = dir_size(&self.storage_path);
+
+        #[cfg(feature = "rocksdb")]
+        self.store.optimize()?;
+
+        let vectors_removed = match &self.vector_store {
+            Some(vs) => vs.compact()?,
+            None => 0,
+        };
+
+        // Mapping keys are either the plain value or the N-Triples form of a term
+        let mut live = std::collections::HashSet::new();
+        for quad in self.store.iter() {
+            let quad = quad?;
+            let subject: Term = quad.subject.into();
+            let predicate: Term = quad.predicate.into();
+            for term in [subject, predicate, quad.object] {
+                live.insert(Self::term_value(&term));
+                live.insert(term.to_string());
+            }
         }
+        let uri_mappings_removed = {
+            let mut uri_map = self.uri_to_id.write().unwrap();
+            let mut id_map = self.id_to_uri.write().unwrap();
+            let before = uri_map.len();
+            uri_map.retain(|uri, _| live.contains(uri));
+            id_map.retain(|_, uri| live.contains(uri));
+            before - uri_map.len()
+        };
+
+        // Rewrites the mappings (and, in memory-only builds, the graph dump)
+        self.flush()?;
 
-        Ok((added, 0))
+        Ok(CompactionReport {
+            vectors_removed,
+            uri_mappings_removed,
+            disk_bytes_before,
+            disk_bytes_after: dir_size(&self.storage_path),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
     }
 
     /// Hybrid search: vector similarity + graph expansion
@@ -346,37 +1156,274 @@ impl SynapseStore {
         Ok(results)
     }
 
-    /// Expand graph from a starting URI
-    fn expand_graph(&self, start_uri: &str, depth: u32) -> Result<Vec<String>> {
-        let mut expanded = Vec::new();
+    /// Graph-only search: resources whose URI or literal values mention the query terms,
+    /// scored by the fraction of terms matched, then expanded like `hybrid_search`
+    pub fn graph_search(&self, query: &str, graph_depth: u32) -> Result<Vec<(String, f32)>> {
+        let terms = query_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        if depth == 0 {
-            return Ok(expanded);
+        let mut matched: HashMap<String, std::collections::HashSet<&str>> = HashMap::new();
+        for quad in self.store.iter() {
+            let quad = quad?;
+            // Provenance batch nodes describe ingestion, not content
+            let Subject::NamedNode(subject) = &quad.subject else {
+                continue;
+            };
+            if subject.as_str().starts_with(BATCH_GRAPH_PREFIX) {
+                continue;
+            }
+            let mut text = local_name(subject.as_str()).to_lowercase();
+            if let Term::Literal(literal) = &quad.object {
+                text.push(' ');
+                text.push_str(&literal.value().to_lowercase());
+            }
+            let hits: Vec<&str> = terms
+                .iter()
+                .filter(|t| text.contains(t.as_str()))
+                .map(String::as_str)
+                .collect();
+            if !hits.is_empty() {
+                matched
+                    .entry(subject.as_str().to_string())
+                    .or_default()
+                    .extend(hits);
+            }
         }
 
-        // Query for all triples where start_uri is subject or object
-        let subject = NamedNodeRef::new(start_uri).ok();
+        let mut results = Vec::new();
+        for (uri, hits) in matched {
+            let score = hits.len() as f32 / terms.len() as f32;
+            if graph_depth > 0 {
+                for expanded_uri in self.expand_graph(&uri, graph_depth)? {
+                    results.push((expanded_uri, score * 0.8));
+                }
+            }
+            results.push((uri, score));
+        }
 
-        if let Some(subj) = subject {
-            for q in self
-                .store
-                .quads_for_pattern(Some(subj.into()), None, None, None)
-                .flatten()
-            {
-                expanded.push(q.object.to_string());
-
-                // Recursive expansion (simplified, depth-1)
-                if depth > 1 {
-                    let nested = self.expand_graph(&q.object.to_string(), depth - 1)?;
-                    expanded.extend(nested);
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        let mut seen = std::collections::HashSet::new();
+        results.retain(|(uri, _)| seen.insert(uri.clone()));
+        Ok(results)
+    }
+
+    /// Re-score search results by blending their score with how many query terms
+    /// appear in each resource's local name and literal values
+    pub fn rerank(&self, query: &str, results: Vec<(String, f32)>) -> Vec<(String, f32)> {
+        let terms = query_terms(query);
+        if terms.is_empty() {
+            return results;
+        }
+
+        let mut reranked: Vec<(String, f32)> = results
+            .into_iter()
+            .map(|(uri, score)| {
+                let mut text = local_name(&uri).to_lowercase();
+                if let Ok(node) = NamedNodeRef::new(&uri) {
+                    for quad in self
+                        .store
+                        .quads_for_pattern(Some(node.into()), None, None, None)
+                        .flatten()
+                    {
+                        if let Term::Literal(literal) = &quad.object {
+                            text.push(' ');
+                            text.push_str(&literal.value().to_lowercase());
+                        }
+                    }
                 }
+                let overlap = terms.iter().filter(|t| text.contains(t.as_str())).count() as f32
+                    / terms.len() as f32;
+                (uri, 0.5 * score + 0.5 * overlap)
+            })
+            .collect();
+        reranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        reranked
+    }
+
+    /// Copy the namespace into `target`, which must not exist yet, as a directory
+    /// that can be opened like any namespace. Returns the bytes written.
+    pub fn backup(&self, target: &std::path::Path) -> Result<u64> {
+        if target.exists() {
+            anyhow::bail!("Backup target already exists: {}", target.display());
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        self.flush()?;
+
+        #[cfg(feature = "rocksdb")]
+        self.store.backup(target)?;
+
+        #[cfg(not(feature = "rocksdb"))]
+        {
+            std::fs::create_dir_all(target)?;
+            std::fs::copy(self.storage_path.join("graph.nq"), target.join("graph.nq"))?;
+        }
+
+        for file in SIDECAR_FILES {
+            let source = self.storage_path.join(file);
+            if source.exists() {
+                std::fs::copy(&source, target.join(file))?;
             }
         }
+        Ok(dir_size(target))
+    }
+
+    /// Labels of a search hit and the triple about it sharing the most words with
+    /// the query, with where that triple came from
+    pub fn search_details(&self, query: &str, uri: &str) -> Result<SearchDetails> {
+        let mut details = SearchDetails::default();
+        let Some(subject) = Self::resource(uri) else {
+            return Ok(details);
+        };
+        let terms = query_terms(query);
+        let mut best: Option<(usize, Quad)> = None;
+        for quad in self
+            .store
+            .quads_for_pattern(Some(subject.as_ref()), None, None, None)
+        {
+            let quad = quad?;
+            if let (true, Term::Literal(label)) = (
+                LABEL_PREDICATES.contains(&quad.predicate.as_str()),
+                &quad.object,
+            ) {
+                if !details.labels.iter().any(|l| l == label.value()) {
+                    details.labels.push(label.value().to_string());
+                }
+            }
+            let text = format!(
+                "{} {}",
+                local_name(quad.predicate.as_str()),
+                Self::term_value(&quad.object)
+            )
+            .to_lowercase();
+            let overlap = terms.iter().filter(|t| text.contains(t.as_str())).count();
+            if best.as_ref().is_none_or(|(score, _)| overlap > *score) {
+                best = Some((overlap, quad));
+            }
+        }
+
+        if let Some((_, quad)) = best {
+            if let GraphName::NamedNode(graph) = &quad.graph_name {
+                details.graph = graph.as_str().to_string();
+                details.source = self.batch_provenance(graph.as_ref())?.source;
+            }
+            details.triple = Some((
+                Self::term_value(&quad.subject.into()),
+                quad.predicate.into_string(),
+                Self::term_value(&quad.object),
+            ));
+        }
+        Ok(details)
+    }
 
+    /// IRIs reachable from `start_uri` over up to `depth` outgoing edges, nearest
+    /// first. Each node is visited once, so cycles end the walk; literals are skipped.
+    fn expand_graph(&self, start_uri: &str, depth: u32) -> Result<Vec<String>> {
+        let mut expanded = Vec::new();
+        let Ok(start) = NamedNodeRef::new(start_uri) else {
+            return Ok(expanded);
+        };
+        let topology = self.topology.read().unwrap();
+        let Some(start) = topology.node_id(&start.to_string()) else {
+            return Ok(expanded);
+        };
+        let mut visited = std::collections::HashSet::from([start]);
+        let mut frontier = vec![start];
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for id in frontier {
+                for object in topology.successors(id) {
+                    if !visited.insert(object) {
+                        continue;
+                    }
+                    let Some(node) = topology.node(object) else {
+                        continue;
+                    };
+                    if node.starts_with('"') || node.starts_with("<<") {
+                        continue;
+                    }
+                    // Blank nodes are walked through but not returned
+                    if let Some(iri) = node.strip_prefix('<').and_then(|n| n.strip_suffix('>')) {
+                        expanded.push(iri.to_string());
+                    }
+                    next.push(object);
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
         Ok(expanded)
     }
 
     pub fn query_sparql(&self, query: &str) -> Result<String> {
+        Ok(self.query_sparql_counted(query, None)?.0)
+    }
+
+    /// `query_sparql` that also returns the number of solutions. With `readable`, the
+    /// query only sees the graphs it accepts (by IRI, or `auth::DEFAULT_GRAPH` for the
+    /// default graph), whatever dataset the query names.
+    pub fn query_sparql_counted(
+        &self,
+        query: &str,
+        readable: Option<&dyn Fn(&str) -> bool>,
+    ) -> Result<(String, usize)> {
+        let mut query = oxigraph::sparql::Query::parse(query, None)?;
+        if let Some(readable) = readable {
+            self.scope_dataset(query.dataset_mut(), readable)?;
+        }
+        self.run_query(query)
+    }
+
+    /// Narrow a query or update dataset to the graphs `readable` accepts
+    pub fn scope_dataset(
+        &self,
+        dataset: &mut oxigraph::sparql::QueryDataset,
+        readable: impl Fn(&str) -> bool,
+    ) -> Result<()> {
+        let named = |graph: &NamedOrBlankNode| graph_label(&graph.clone().into());
+
+        // `None` stands for the union of all graphs, or every named graph for FROM NAMED
+        let default: Vec<GraphName> = match dataset.default_graph_graphs() {
+            Some(graphs) => graphs
+                .iter()
+                .filter(|g| readable(&graph_label(g)))
+                .cloned()
+                .collect(),
+            None => std::iter::once(GraphName::DefaultGraph)
+                .chain(
+                    self.store
+                        .named_graphs()
+                        .filter_map(|g| g.ok())
+                        .map(GraphName::from),
+                )
+                .filter(|g| readable(&graph_label(g)))
+                .collect(),
+        };
+        let available: Vec<NamedOrBlankNode> = match dataset.available_named_graphs() {
+            Some(graphs) => graphs
+                .iter()
+                .filter(|g| readable(&named(g)))
+                .cloned()
+                .collect(),
+            None => self
+                .store
+                .named_graphs()
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|g| readable(&named(g)))
+                .collect(),
+        };
+        dataset.set_default_graph(default);
+        dataset.set_available_named_graphs(available);
+        Ok(())
+    }
+
+    fn run_query(&self, query: oxigraph::sparql::Query) -> Result<(String, usize)> {
         use oxigraph::sparql::QueryResults;
 
         let results = self.store.query(query)?;
@@ -395,26 +1442,677 @@ impl SynapseStore {
                     }
                     results_array.push(serde_json::Value::Object(mapping));
                 }
-                Ok(serde_json::to_string(&results_array)?)
+                Ok((serde_json::to_string(&results_array)?, results_array.len()))
+            }
+            _ => Ok(("[]".to_string(), 0)),
+        }
+    }
+
+    /// Run a SPARQL UPDATE. The inserted and deleted quads are found by comparing
+    /// the dataset before and after, so the namespace is copied into memory once.
+    pub fn update_sparql(&self, update: oxigraph::sparql::Update) -> Result<SparqlUpdateReport> {
+        let before = self
+            .store
+            .iter()
+            .collect::<Result<std::collections::HashSet<Quad>, _>>()?;
+        self.store.update(update)?;
+        let mut report = SparqlUpdateReport::default();
+        let mut remaining = before;
+        for quad in self.store.iter() {
+            let quad = quad?;
+            if remaining.remove(&quad) {
+                continue;
+            }
+            report.inserted += 1;
+            self.mirror_quad(&quad);
+            self.get_or_create_id(&Self::term_value(&quad.subject.clone().into()));
+            self.get_or_create_id(quad.predicate.as_str());
+            self.get_or_create_id(&Self::term_value(&quad.object));
+            self.changes
+                .publish(|| Change::triple(ChangeKind::Insert, &self.namespace, quad));
+        }
+        report.deleted = remaining.len();
+        for quad in remaining {
+            self.unmirror_quad(&quad);
+            self.changes
+                .publish(|| Change::triple(ChangeKind::Delete, &self.namespace, quad));
+        }
+
+        if report.inserted + report.deleted > 0 {
+            *self.modified.write().unwrap() = Some(std::time::SystemTime::now());
+        }
+        self.save_mappings_if_due();
+        Ok(report)
+    }
+
+    /// IRIs whose full form, local name or label starts with `prefix` (ignoring case),
+    /// sorted, for argument completion. With `predicates`, only IRIs used as
+    /// predicates; otherwise only IRIs used as subjects or objects.
+    pub fn complete(&self, prefix: &str, predicates: bool) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        let matches = |text: &str| text.to_lowercase().starts_with(&prefix);
+        let used = |node: NamedNodeRef| {
+            if predicates {
+                self.store
+                    .quads_for_pattern(None, Some(node), None, None)
+                    .next()
+                    .is_some()
+            } else {
+                self.store
+                    .quads_for_pattern(Some(node.into()), None, None, None)
+                    .next()
+                    .is_some()
+                    || self
+                        .store
+                        .quads_for_pattern(None, None, Some(node.into()), None)
+                        .next()
+                        .is_some()
+            }
+        };
+
+        let mut found: std::collections::BTreeSet<String> = self
+            .uri_to_id
+            .read()
+            .unwrap()
+            .keys()
+            .filter(|uri| matches(uri) || matches(local_name(uri)))
+            .filter(|uri| NamedNodeRef::new(uri).is_ok_and(used))
+            .cloned()
+            .collect();
+
+        if !predicates {
+            for label in LABEL_PREDICATES {
+                let label = NamedNodeRef::new_unchecked(label);
+                for quad in self
+                    .store
+                    .quads_for_pattern(None, Some(label), None, None)
+                    .flatten()
+                {
+                    if let (Subject::NamedNode(node), Term::Literal(value)) =
+                        (&quad.subject, &quad.object)
+                    {
+                        if matches(value.value()) {
+                            found.insert(node.as_str().to_string());
+                        }
+                    }
+                }
             }
-            _ => Ok("[]".to_string()),
         }
+        found.into_iter().collect()
     }
 
     pub fn get_degree(&self, uri: &str) -> usize {
-        let node = NamedNodeRef::new(uri).ok();
-        if let Some(n) = node {
-            let outgoing = self
-                .store
-                .quads_for_pattern(Some(n.into()), None, None, None)
-                .count();
-            let incoming = self
-                .store
-                .quads_for_pattern(None, None, Some(n.into()), None)
-                .count();
-            outgoing + incoming
+        match NamedNodeRef::new(uri) {
+            Ok(node) => self.topology.read().unwrap().degree(&node.to_string()),
+            Err(_) => 0,
+        }
+    }
+
+    /// Compute PageRank over the topology mirror, keeping the scores for
+    /// `pagerank_of` and search ranking until the next run
+    pub fn compute_pagerank(&self, options: &PageRankOptions) -> PageRank {
+        let result = crate::analytics::pagerank(&self.topology.read().unwrap(), options);
+        self.analytics.write().unwrap().pagerank = result.scores.clone();
+        result
+    }
+
+    /// PageRank of an IRI or N-Triples term from the latest `compute_pagerank`
+    pub fn pagerank_of(&self, uri: &str) -> Option<f64> {
+        self.analytics
+            .read()
+            .unwrap()
+            .pagerank
+            .get(&node_key(uri))
+            .copied()
+    }
+
+    /// Blend PageRank from the latest `compute_pagerank` into search scores, as
+    /// `(1 - weight) * score + weight * rank / highest_rank`
+    pub fn blend_pagerank(&self, results: Vec<(String, f32)>, weight: f32) -> Vec<(String, f32)> {
+        let analytics = self.analytics.read().unwrap();
+        let highest = analytics.pagerank.values().copied().fold(0.0, f64::max);
+        if highest <= 0.0 {
+            return results;
+        }
+        let mut blended: Vec<(String, f32)> = results
+            .into_iter()
+            .map(|(uri, score)| {
+                let rank = analytics
+                    .pagerank
+                    .get(&node_key(&uri))
+                    .copied()
+                    .unwrap_or(0.0);
+                let score = (1.0 - weight) * score + weight * (rank / highest) as f32;
+                (uri, score)
+            })
+            .collect();
+        blended.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        blended
+    }
+
+    /// Group the resources into communities over the topology mirror, keeping each
+    /// one's community ID for `community_of` until the next run
+    pub fn compute_communities(
+        &self,
+        algorithm: CommunityAlgorithm,
+        max_iterations: usize,
+    ) -> Communities {
+        let result = crate::analytics::communities(
+            &self.topology.read().unwrap(),
+            algorithm,
+            max_iterations,
+        );
+        self.analytics.write().unwrap().communities = result.assignments.clone();
+        result
+    }
+
+    /// Community ID of an IRI or N-Triples term from the latest `compute_communities`
+    pub fn community_of(&self, uri: &str) -> Option<u32> {
+        self.analytics
+            .read()
+            .unwrap()
+            .communities
+            .get(&node_key(uri))
+            .copied()
+    }
+
+    /// Typed literal properties of an IRI or N-Triples term, ordered by property
+    /// (in N-Triples form)
+    pub fn node_properties(&self, uri: &str) -> Vec<(String, PropertyValue)> {
+        self.properties
+            .read()
+            .unwrap()
+            .properties_of(&node_key(uri))
+            .into_iter()
+            .map(|(property, value)| (property.to_string(), value.clone()))
+            .collect()
+    }
+
+    /// Nodes (in N-Triples form) with a value of `property` that satisfies `filter`
+    pub fn nodes_where(
+        &self,
+        property: &str,
+        filter: impl Fn(&PropertyValue) -> bool,
+    ) -> Vec<String> {
+        self.properties
+            .read()
+            .unwrap()
+            .nodes_where(&node_key(property), filter)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Index a property's values for `nodes_with_value` and `nodes_in_range`,
+    /// replacing any index it had. Indexes are kept across restarts.
+    pub fn create_property_index(&self, property: &str, kind: IndexKind) -> Result<()> {
+        let mut properties = self.properties.write().unwrap();
+        properties.create_index(&node_key(property), kind);
+        self.save_property_indexes(&properties)
+    }
+
+    /// Drop a property's index, returning whether it had one
+    pub fn drop_property_index(&self, property: &str) -> Result<bool> {
+        let mut properties = self.properties.write().unwrap();
+        let dropped = properties.drop_index(&node_key(property));
+        if dropped {
+            self.save_property_indexes(&properties)?;
+        }
+        Ok(dropped)
+    }
+
+    fn save_property_indexes(&self, properties: &PropertyStore) -> Result<()> {
+        let indexes: BTreeMap<&str, IndexKind> = properties.indexes().into_iter().collect();
+        std::fs::write(
+            self.storage_path.join(PROPERTY_INDEXES_FILE),
+            serde_json::to_string_pretty(&indexes)?,
+        )?;
+        Ok(())
+    }
+
+    /// Nodes (in N-Triples form) with `value` for `property`, where integers and
+    /// floats compare as numbers
+    pub fn nodes_with_value(&self, property: &str, value: &PropertyValue) -> Vec<String> {
+        self.properties
+            .read()
+            .unwrap()
+            .nodes_with_value(&node_key(property), value)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Nodes (in N-Triples form) with a numeric value of `property` in `range`
+    pub fn nodes_in_range(&self, property: &str, range: impl RangeBounds<f64>) -> Vec<String> {
+        self.properties
+            .read()
+            .unwrap()
+            .nodes_in_range(&node_key(property), range)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Degree, betweenness and closeness centrality over the topology mirror, with
+    /// betweenness and closeness estimated from `samples` sources. The results are
+    /// kept for `centrality_of` and "centrality" neighbor scoring until the next run.
+    pub fn compute_centrality(&self, samples: usize) -> HashMap<String, Centrality> {
+        let result = crate::analytics::centrality(&self.topology.read().unwrap(), samples);
+        self.analytics.write().unwrap().centrality = result.clone();
+        result
+    }
+
+    /// Centrality of an IRI or N-Triples term from the latest `compute_centrality`
+    pub fn centrality_of(&self, uri: &str) -> Option<Centrality> {
+        self.analytics
+            .read()
+            .unwrap()
+            .centrality
+            .get(&node_key(uri))
+            .copied()
+    }
+
+    /// Shortest path of at most `max_depth` edges between two resources (IRIs or
+    /// N-Triples terms), following edges in either direction. With
+    /// `predicate_filter`, only predicates whose IRI contains it are followed.
+    pub fn shortest_path(
+        &self,
+        from_uri: &str,
+        to_uri: &str,
+        max_depth: usize,
+        predicate_filter: Option<&str>,
+    ) -> Option<GraphPath> {
+        let topology = self.topology.read().unwrap();
+        crate::analytics::shortest_path(
+            &topology,
+            &node_key(from_uri),
+            &node_key(to_uri),
+            max_depth,
+            predicate_filter,
+        )
+    }
+
+    /// Breadth-first traversal from `start`, each node visited once, sorted by score
+    /// (closer first)
+    pub fn neighbors(&self, start: &str, options: &TraversalOptions) -> Vec<GraphNeighbor> {
+        let rdf_type =
+            NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type")
+                .to_string();
+        let type_filter = options
+            .node_type_filter
+            .as_deref()
+            .map(|t| NamedNodeRef::new(t).ok().map(|n| n.to_string()));
+        let topology = self.topology.read().unwrap();
+        let analytics = self.analytics.read().unwrap();
+        let highest_betweenness = analytics
+            .centrality
+            .values()
+            .map(|c| c.betweenness)
+            .fold(0.0, f64::max);
+        let has_type = |node: &str| match &type_filter {
+            None => true,
+            Some(Some(target)) => {
+                node.starts_with('<')
+                    && topology
+                        .outgoing(node)
+                        .any(|(p, o)| p == rdf_type && o == target)
+            }
+            Some(None) => false,
+        };
+        let outgoing = options.direction == "outgoing" || options.direction == "both";
+        let incoming = options.direction == "incoming" || options.direction == "both";
+
+        let mut neighbors = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        visited.insert(start.to_string());
+        let mut frontier = vec![start.to_string()];
+
+        for depth in 1..=options.depth {
+            let mut next = Vec::new();
+            let mut layer_count = 0;
+            let base_score = 1.0 / depth as f32;
+
+            for uri in &frontier {
+                if layer_count >= options.limit_per_layer {
+                    break;
+                }
+                let Ok(node) = NamedNodeRef::new(uri) else {
+                    continue;
+                };
+                let key = node.to_string();
+                let out_edges = outgoing
+                    .then(|| topology.outgoing(&key))
+                    .into_iter()
+                    .flatten()
+                    .map(|(p, o)| (p.to_string(), o.to_string(), "outgoing"));
+                let in_edges = incoming
+                    .then(|| topology.incoming(&key))
+                    .into_iter()
+                    .flatten()
+                    .map(|(p, s)| (p.to_string(), s.to_string(), "incoming"));
+
+                for (edge_type, term_string, direction) in out_edges.chain(in_edges) {
+                    if layer_count >= options.limit_per_layer {
+                        break;
+                    }
+                    if let Some(filter) = &options.edge_filter {
+                        if !edge_type.contains(filter.as_str()) {
+                            continue;
+                        }
+                    }
+                    if !has_type(&term_string) {
+                        continue;
+                    }
+                    if !visited.insert(term_string.clone()) {
+                        continue;
+                    }
+                    // Expand from the raw IRI, not its `<...>` form
+                    let clean_uri = term_string
+                        .strip_prefix('<')
+                        .and_then(|iri| iri.strip_suffix('>'))
+                        .unwrap_or(&term_string)
+                        .to_string();
+                    let mut score = base_score;
+                    match options.scoring.as_str() {
+                        "centrality" if highest_betweenness > 0.0 => {
+                            // Halve the score of the node most shortest paths run through
+                            let betweenness = analytics
+                                .centrality
+                                .get(&term_string)
+                                .map_or(0.0, |c| c.betweenness);
+                            score *= 1.0 - 0.5 * (betweenness / highest_betweenness) as f32;
+                        }
+                        "degree" | "centrality" => {
+                            // Penalize super nodes
+                            score /= (topology.degree(&term_string) as f32 + 1.0).ln().max(0.1);
+                        }
+                        _ => {}
+                    }
+                    neighbors.push(GraphNeighbor {
+                        node_id: self.term_id(&term_string),
+                        edge_type,
+                        uri: term_string,
+                        direction: direction.to_string(),
+                        depth: depth as u32,
+                        score,
+                    });
+                    next.push(clean_uri);
+                    layer_count += 1;
+                }
+            }
+
+            frontier = next;
+            if frontier.is_empty() {
+                break;
+            }
+        }
+
+        neighbors.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        neighbors
+    }
+
+    /// Serialize one named graph, or the whole namespace with its graphs merged, as
+    /// triples in `format`. Returns the number of triples written.
+    pub fn export_graph<W: std::io::Write>(
+        &self,
+        graph: Option<&str>,
+        format: oxigraph::io::RdfFormat,
+        writer: W,
+    ) -> Result<usize> {
+        let graph_name = graph.map(NamedNode::new).transpose()?;
+        let mut serializer = oxigraph::io::RdfSerializer::from_format(format)
+            .with_prefix("synapse", "http://synapse.os/")?
+            .for_writer(writer);
+        // The same fact may sit in several batch graphs; write it once
+        let mut written = std::collections::HashSet::new();
+        for quad in self.store.quads_for_pattern(
+            None,
+            None,
+            None,
+            graph_name.as_ref().map(|g| g.as_ref().into()),
+        ) {
+            let triple = Triple::from(quad?);
+            if !written.contains(&triple) {
+                serializer.serialize_triple(&triple)?;
+                written.insert(triple);
+            }
+        }
+        serializer.finish()?;
+        Ok(written.len())
+    }
+
+    /// Write the topology as GraphML or GEXF for Gephi and yEd, with literal-valued
+    /// facts as node attributes. Returns the number of nodes and edges written.
+    pub fn export_graphml<W: std::io::Write>(
+        &self,
+        format: NetworkFormat,
+        writer: W,
+    ) -> Result<(usize, usize)> {
+        let topology = self.topology.read().unwrap();
+        let properties = self.properties.read().unwrap();
+        write_network(&topology, &properties, format, writer)
+    }
+
+    /// Serialize every quad with its graph name in a dataset `format` (N-Quads, TriG).
+    /// Returns the number of quads written.
+    pub fn export_dataset<W: std::io::Write>(
+        &self,
+        format: oxigraph::io::RdfFormat,
+        writer: W,
+    ) -> Result<usize> {
+        if !format.supports_datasets() {
+            return Err(anyhow::anyhow!(
+                "{} cannot hold named graphs",
+                format.name()
+            ));
+        }
+        let mut serializer = oxigraph::io::RdfSerializer::from_format(format).for_writer(writer);
+        let mut written = 0;
+        for quad in self.store.iter() {
+            serializer.serialize_quad(&quad?)?;
+            written += 1;
+        }
+        serializer.finish()?;
+        Ok(written)
+    }
+
+    /// Metadata triples describing a batch (subject is the batch node)
+    fn provenance_terms(p: &Provenance) -> Vec<(&'static str, Literal)> {
+        let mut terms = vec![
+            (PROV_DERIVED_FROM, Literal::new_simple_literal(&p.source)),
+            (PROV_GENERATED_AT, Literal::new_simple_literal(&p.timestamp)),
+            (PROV_GENERATED_BY, Literal::new_simple_literal(&p.method)),
+        ];
+        if let Some(path) = &p.path {
+            terms.push((SOURCE_PATH, Literal::new_simple_literal(path)));
+        }
+        if let Some(sha256) = &p.sha256 {
+            terms.push((SOURCE_SHA256, Literal::new_simple_literal(sha256)));
+        }
+        if let Some(span) = &p.span {
+            terms.push((SOURCE_LINE_START, Literal::from(span.line_start)));
+            terms.push((SOURCE_LINE_END, Literal::from(span.line_end)));
+            if let Some(start) = span.byte_start {
+                terms.push((SOURCE_BYTE_START, Literal::from(start)));
+            }
+            if let Some(end) = span.byte_end {
+                terms.push((SOURCE_BYTE_END, Literal::from(end)));
+            }
+        }
+        terms
+    }
+
+    /// Trace a subject's facts back to the batches (and source locations) they came from.
+    /// Facts stored in the default graph carry no provenance and are not listed.
+    pub fn get_provenance(&self, subject: &str) -> Result<Vec<ProvenanceRecord>> {
+        let uri = self.resource_uri(subject);
+        let subject =
+            Self::resource(&uri).ok_or_else(|| anyhow::anyhow!("Invalid subject: {}", subject))?;
+
+        let mut records: Vec<ProvenanceRecord> = Vec::new();
+        for quad in self
+            .store
+            .quads_for_pattern(Some(subject.as_ref()), None, None, None)
+        {
+            let quad = quad?;
+            let GraphName::NamedNode(graph) = quad.graph_name else {
+                continue;
+            };
+            let fact = (quad.predicate.into_string(), Self::term_value(&quad.object));
+            match records.iter_mut().find(|r| r.graph == graph.as_str()) {
+                Some(record) => record.facts.push(fact),
+                None => records.push(ProvenanceRecord {
+                    graph: graph.as_str().to_string(),
+                    provenance: self.batch_provenance(graph.as_ref())?,
+                    facts: vec![fact],
+                }),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Read back the metadata `ingest_triples` recorded for a batch node
+    fn batch_provenance(&self, batch: NamedNodeRef) -> Result<Provenance> {
+        let mut provenance = Provenance::default();
+        let mut span = SourceSpan::default();
+        let mut has_span = false;
+        for quad in self.store.quads_for_pattern(
+            Some(batch.into()),
+            None,
+            None,
+            Some(GraphNameRef::DefaultGraph),
+        ) {
+            let quad = quad?;
+            let Term::Literal(literal) = &quad.object else {
+                continue;
+            };
+            let value = literal.value().to_string();
+            let number = || value.parse::<u64>().ok();
+            match quad.predicate.as_str() {
+                PROV_DERIVED_FROM => provenance.source = value,
+                PROV_GENERATED_AT => provenance.timestamp = value,
+                PROV_GENERATED_BY => provenance.method = value,
+                SOURCE_PATH => provenance.path = Some(value),
+                SOURCE_SHA256 => provenance.sha256 = Some(value),
+                SOURCE_LINE_START => {
+                    span.line_start = number().unwrap_or_default();
+                    has_span = true;
+                }
+                SOURCE_LINE_END => span.line_end = number().unwrap_or_default(),
+                SOURCE_BYTE_START => span.byte_start = number(),
+                SOURCE_BYTE_END => span.byte_end = number(),
+                _ => {}
+            }
+        }
+        provenance.span = has_span.then_some(span);
+        Ok(provenance)
+    }
+
+    /// A term as the plain string used by the ingest APIs (URI, `_:id` or literal value)
+    fn term_value(term: &Term) -> String {
+        match term {
+            Term::NamedNode(node) => node.as_str().to_string(),
+            Term::Literal(literal) => literal.value().to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    /// The term for an object as the ingest APIs write it (`"literal"`, `"v"^^<dt>`,
+    /// `"v"@lang`, URI or `_:id`), with the key used in the ID mapping
+    fn object_term(&self, o: &str) -> Option<(Term, String)> {
+        if o.starts_with('"') && o.ends_with('"') && o.len() >= 2 {
+            let literal_val = &o[1..o.len() - 1];
+            Some((
+                Term::Literal(Literal::new_simple_literal(literal_val)),
+                literal_val.to_string(),
+            ))
+        } else if let Some((literal_val, datatype)) = Self::split_typed_literal(o) {
+            // "value"^^<datatype>
+            let literal =
+                Literal::new_typed_literal(literal_val, NamedNode::new_unchecked(datatype));
+            Some((Term::Literal(literal), literal_val.to_string()))
+        } else if let Some((literal_val, language)) = Self::split_language_literal(o) {
+            // "value"@lang
+            let literal = Literal::new_language_tagged_literal(literal_val, language).ok()?;
+            Some((Term::Literal(literal), literal_val.to_string()))
+        } else {
+            let uri = self.resource_uri(o);
+            match Self::resource(&uri)? {
+                Subject::NamedNode(node) => Some((Term::NamedNode(node), uri)),
+                Subject::BlankNode(node) => Some((Term::BlankNode(node), uri)),
+                _ => None,
+            }
+        }
+    }
+
+    /// A triple pattern from ingest-style terms, where an empty string matches anything
+    pub fn triple_pattern(
+        &self,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+    ) -> Result<(Option<Subject>, Option<NamedNode>, Option<Term>)> {
+        let subject = match subject {
+            "" => None,
+            s => Some(
+                Self::resource(&self.resource_uri(s))
+                    .ok_or_else(|| anyhow::anyhow!("Invalid subject: {}", s))?,
+            ),
+        };
+        let predicate = match predicate {
+            "" => None,
+            p => Some(
+                NamedNode::new(self.ensure_uri(p))
+                    .map_err(|_| anyhow::anyhow!("Invalid predicate: {}", p))?,
+            ),
+        };
+        let object = match object {
+            "" => None,
+            o => Some(
+                self.object_term(o)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid object: {}", o))?
+                    .0,
+            ),
+        };
+        Ok((subject, predicate, object))
+    }
+
+    /// Split a `"value"^^<datatype>` literal into its parts
+    fn split_typed_literal(o: &str) -> Option<(&str, &str)> {
+        let rest = o.strip_prefix('"')?;
+        let (value, datatype) = rest.rsplit_once("\"^^<")?;
+        Some((value, datatype.strip_suffix('>')?))
+    }
+
+    /// Split a `"value"@lang` literal into its parts
+    fn split_language_literal(o: &str) -> Option<(&str, &str)> {
+        let rest = o.strip_prefix('"')?;
+        let (value, language) = rest.rsplit_once("\"@")?;
+        let valid = !language.is_empty()
+            && language
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-');
+        valid.then_some((value, language))
+    }
+
+    /// Like `ensure_uri`, but `_:id` blank nodes are kept as they are
+    fn resource_uri(&self, s: &str) -> String {
+        if s.starts_with("_:") {
+            s.to_string()
         } else {
-            0
+            self.ensure_uri(s)
+        }
+    }
+
+    /// Build a subject from a URI or `_:id` blank node, if valid
+    pub(crate) fn resource(uri: &str) -> Option<Subject> {
+        match uri.strip_prefix("_:") {
+            Some(id) => BlankNode::new(id).ok().map(Subject::BlankNode),
+            None => NamedNode::new(uri).ok().map(Subject::NamedNode),
         }
     }
 
@@ -427,3 +2125,56 @@ impl SynapseStore {
         }
     }
 }
+
+/// Total size of the files under a directory
+fn dir_size(path: &std::path::Path) -> u64 {
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some(if metadata.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                metadata.len()
+            })
+        })
+        .sum()
+}
+
+/// Lowercased words of a search query, ignoring one-letter tokens
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() > 1)
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Topology key of a resource: an IRI in `<...>` form; N-Triples terms are kept as they are
+pub(crate) fn node_key(uri: &str) -> String {
+    match NamedNodeRef::new(uri) {
+        Ok(node) => node.to_string(),
+        Err(_) => uri.to_string(),
+    }
+}
+
+/// The part of a URI after its last `#` or `/`
+pub(crate) fn local_name(uri: &str) -> &str {
+    uri.rsplit(['#', '/'])
+        .find(|part| !part.is_empty())
+        .unwrap_or(uri)
+}
+
+/// A graph as graph access patterns name it: its IRI, or `auth::DEFAULT_GRAPH`
+pub(crate) fn graph_label(graph: &GraphName) -> String {
+    match graph {
+        GraphName::NamedNode(node) => node.as_str().to_string(),
+        GraphName::BlankNode(node) => node.to_string(),
+        GraphName::DefaultGraph => crate::auth::DEFAULT_GRAPH.to_string(),
+    }
+}
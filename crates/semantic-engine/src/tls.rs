@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+use x509_parser::extensions::GeneralName;
+
+/// TLS for the gRPC server: `SYNAPSE_TLS_CERT` and `SYNAPSE_TLS_KEY` (PEM) enable it,
+/// and `SYNAPSE_TLS_CLIENT_CA` additionally requires client certificates signed by
+/// that CA (mTLS). Returns `None` when TLS is not configured.
+pub fn server_tls_from_env() -> Result<Option<ServerTlsConfig>> {
+    let (Ok(cert), Ok(key)) = (
+        std::env::var("SYNAPSE_TLS_CERT"),
+        std::env::var("SYNAPSE_TLS_KEY"),
+    ) else {
+        return Ok(None);
+    };
+    let client_ca = std::env::var("SYNAPSE_TLS_CLIENT_CA").ok();
+    server_tls(
+        Path::new(&cert),
+        Path::new(&key),
+        client_ca.as_deref().map(Path::new),
+    )
+    .map(Some)
+}
+
+/// Server TLS from PEM files; with `client_ca`, clients must present a certificate it signed
+pub fn server_tls(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<ServerTlsConfig> {
+    let read = |path: &Path| {
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+    };
+    let mut config = ServerTlsConfig::new().identity(Identity::from_pem(read(cert)?, read(key)?));
+    if let Some(ca) = client_ca {
+        config = config.client_ca_root(Certificate::from_pem(read(ca)?));
+    }
+    Ok(config)
+}
+
+/// Identities a client certificate (DER) can be mapped by: its subject alternative
+/// names (URI, DNS, email), then its common name
+pub fn certificate_identities(der: &[u8]) -> Vec<String> {
+    let Ok((_, cert)) = x509_parser::parse_x509_certificate(der) else {
+        return Vec::new();
+    };
+    let mut identities = Vec::new();
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::URI(s) | GeneralName::DNSName(s) | GeneralName::RFC822Name(s) => {
+                    identities.push(s.to_string())
+                }
+                _ => {}
+            }
+        }
+    }
+    for cn in cert.subject().iter_common_name() {
+        if let Ok(cn) = cn.as_str() {
+            identities.push(cn.to_string());
+        }
+    }
+    identities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_certificate_identities() {
+        let pem = std::fs::read("tests/fixtures/tls/client.pem").unwrap();
+        let der = pem_to_der(&pem);
+        assert_eq!(
+            certificate_identities(&der),
+            vec!["spiffe://synapse.test/agent-1", "agent-1"]
+        );
+        assert!(certificate_identities(b"not a certificate").is_empty());
+    }
+
+    /// The DER body of a single-certificate PEM file
+    fn pem_to_der(pem: &[u8]) -> Vec<u8> {
+        x509_parser::pem::parse_x509_pem(pem).unwrap().1.contents
+    }
+}
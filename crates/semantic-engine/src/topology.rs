@@ -0,0 +1,364 @@
+//! In-memory adjacency lists mirroring a namespace's graph, so traversals follow
+//! edges instead of scanning quads.
+
+use crate::memory::{map_bytes, string_bytes, vec_bytes};
+use std::collections::HashMap;
+
+/// Whether a node is a resource (an IRI or blank node) rather than a literal or a
+/// quoted triple. Only resources are nodes in analytics and graph exports.
+pub fn is_resource(node: &str) -> bool {
+    (node.starts_with('<') && !node.starts_with("<<")) || node.starts_with("_:")
+}
+
+/// Adjacency lists over a namespace's quads. Nodes and predicates are kept in
+/// N-Triples form (`<iri>`, `_:b0`, `"literal"`); each quad adds one edge, so a
+/// fact stated in two graphs counts twice, as it does in `quads_for_pattern`.
+///
+/// Nodes exist while they have edges. A removed node's ID is tombstoned rather
+/// than reused, and tombstones are compacted away (renumbering the live nodes)
+/// once they make up half the IDs.
+#[derive(Debug, Default)]
+pub struct GraphTopology {
+    node_ids: HashMap<String, u32>,
+    /// `None` for tombstoned IDs
+    nodes: Vec<Option<String>>,
+    predicate_ids: HashMap<String, u32>,
+    predicates: Vec<String>,
+    /// (predicate, object) pairs per subject
+    outgoing: Vec<Vec<(u32, u32)>>,
+    /// (predicate, subject) pairs per object
+    incoming: Vec<Vec<(u32, u32)>>,
+    edges: usize,
+    tombstones: usize,
+}
+
+impl GraphTopology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn intern_node(&mut self, node: &str) -> u32 {
+        if let Some(&id) = self.node_ids.get(node) {
+            return id;
+        }
+        let id = self.nodes.len() as u32;
+        self.node_ids.insert(node.to_string(), id);
+        self.nodes.push(Some(node.to_string()));
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        id
+    }
+
+    fn intern_predicate(&mut self, predicate: &str) -> u32 {
+        if let Some(&id) = self.predicate_ids.get(predicate) {
+            return id;
+        }
+        let id = self.predicates.len() as u32;
+        self.predicate_ids.insert(predicate.to_string(), id);
+        self.predicates.push(predicate.to_string());
+        id
+    }
+
+    /// Record one quad's `subject -predicate-> object` edge
+    pub fn add_edge(&mut self, subject: &str, predicate: &str, object: &str) {
+        let s = self.intern_node(subject);
+        let p = self.intern_predicate(predicate);
+        let o = self.intern_node(object);
+        self.outgoing[s as usize].push((p, o));
+        self.incoming[o as usize].push((p, s));
+        self.edges += 1;
+    }
+
+    /// Remove one `subject -predicate-> object` edge, and its ends if that leaves
+    /// them without edges. Returns whether the edge existed.
+    pub fn remove_edge(&mut self, subject: &str, predicate: &str, object: &str) -> bool {
+        let (Some(s), Some(&p), Some(o)) = (
+            self.node_id(subject),
+            self.predicate_ids.get(predicate),
+            self.node_id(object),
+        ) else {
+            return false;
+        };
+        let Some(i) = self.outgoing[s as usize].iter().position(|&e| e == (p, o)) else {
+            return false;
+        };
+        self.outgoing[s as usize].swap_remove(i);
+        if let Some(i) = self.incoming[o as usize].iter().position(|&e| e == (p, s)) {
+            self.incoming[o as usize].swap_remove(i);
+        }
+        self.edges -= 1;
+        for id in [s, o] {
+            if self.outgoing[id as usize].is_empty() && self.incoming[id as usize].is_empty() {
+                self.tombstone(id);
+            }
+        }
+        self.compact_if_sparse();
+        true
+    }
+
+    /// Remove a node with all its edges, returning how many edges went with it
+    pub fn remove_node(&mut self, node: &str) -> usize {
+        let Some(id) = self.node_id(node) else {
+            return 0;
+        };
+        let outgoing = std::mem::take(&mut self.outgoing[id as usize]);
+        let incoming = std::mem::take(&mut self.incoming[id as usize]);
+        for &(p, o) in &outgoing {
+            self.incoming[o as usize].retain(|&e| e != (p, id));
+        }
+        for &(p, s) in &incoming {
+            self.outgoing[s as usize].retain(|&e| e != (p, id));
+        }
+        // A self-loop is in both lists but is one edge
+        let self_loops = outgoing.iter().filter(|&&(_, o)| o == id).count();
+        let removed = outgoing.len() + incoming.len() - self_loops;
+        self.edges -= removed;
+
+        self.tombstone(id);
+        let neighbors: Vec<u32> = outgoing.iter().chain(&incoming).map(|&(_, n)| n).collect();
+        for n in neighbors {
+            let isolated =
+                self.outgoing[n as usize].is_empty() && self.incoming[n as usize].is_empty();
+            if isolated && self.nodes[n as usize].is_some() {
+                self.tombstone(n);
+            }
+        }
+        self.compact_if_sparse();
+        removed
+    }
+
+    fn tombstone(&mut self, id: u32) {
+        if let Some(node) = self.nodes[id as usize].take() {
+            self.node_ids.remove(&node);
+            self.tombstones += 1;
+        }
+    }
+
+    fn compact_if_sparse(&mut self) {
+        if self.tombstones > 64 && self.tombstones * 2 > self.nodes.len() {
+            self.compact();
+        }
+    }
+
+    /// Drop tombstoned IDs, renumbering the live nodes densely
+    pub fn compact(&mut self) {
+        if self.tombstones == 0 {
+            return;
+        }
+        let mut remap = vec![u32::MAX; self.nodes.len()];
+        let mut next = 0u32;
+        for (old, node) in self.nodes.iter().enumerate() {
+            if node.is_some() {
+                remap[old] = next;
+                next += 1;
+            }
+        }
+        let renumber = |edges: Vec<(u32, u32)>| -> Vec<(u32, u32)> {
+            edges
+                .into_iter()
+                .map(|(p, n)| (p, remap[n as usize]))
+                .collect()
+        };
+
+        let nodes = std::mem::take(&mut self.nodes);
+        let outgoing = std::mem::take(&mut self.outgoing);
+        let incoming = std::mem::take(&mut self.incoming);
+        for ((node, out), inc) in nodes.into_iter().zip(outgoing).zip(incoming) {
+            if let Some(node) = node {
+                self.node_ids.insert(node.clone(), self.nodes.len() as u32);
+                self.nodes.push(Some(node));
+                self.outgoing.push(renumber(out));
+                self.incoming.push(renumber(inc));
+            }
+        }
+        self.tombstones = 0;
+    }
+
+    /// Dense ID of a node, valid until the topology is compacted or rebuilt
+    pub fn node_id(&self, node: &str) -> Option<u32> {
+        self.node_ids.get(node).copied()
+    }
+
+    pub fn node(&self, id: u32) -> Option<&str> {
+        self.nodes.get(id as usize)?.as_deref()
+    }
+
+    fn edges_of<'a>(
+        &'a self,
+        lists: &'a [Vec<(u32, u32)>],
+        node: &str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        self.node_id(node)
+            .map(|id| lists[id as usize].as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|&(p, n)| {
+                (
+                    self.predicates[p as usize].as_str(),
+                    self.nodes[n as usize].as_deref().unwrap_or_default(),
+                )
+            })
+    }
+
+    /// `(predicate, object)` pairs of a node's outgoing edges
+    pub fn outgoing(&self, node: &str) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.edges_of(&self.outgoing, node)
+    }
+
+    /// `(predicate, subject)` pairs of the edges pointing at a node
+    pub fn incoming(&self, node: &str) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.edges_of(&self.incoming, node)
+    }
+
+    /// One past the highest node ID, for arrays indexed by ID
+    pub fn id_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Live nodes with their IDs
+    pub fn nodes(&self) -> impl Iterator<Item = (u32, &str)> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, node)| Some((id as u32, node.as_deref()?)))
+    }
+
+    /// `(predicate, object ID)` pairs of a node's outgoing edges
+    pub fn out_edges(&self, id: u32) -> impl Iterator<Item = (&str, u32)> + '_ {
+        self.outgoing
+            .get(id as usize)
+            .into_iter()
+            .flatten()
+            .map(|&(p, o)| (self.predicates[p as usize].as_str(), o))
+    }
+
+    /// `(predicate, subject ID)` pairs of the edges pointing at a node
+    pub fn in_edges(&self, id: u32) -> impl Iterator<Item = (&str, u32)> + '_ {
+        self.incoming
+            .get(id as usize)
+            .into_iter()
+            .flatten()
+            .map(|&(p, s)| (self.predicates[p as usize].as_str(), s))
+    }
+
+    /// IDs of the objects of a node's outgoing edges, once per edge
+    pub fn successors(&self, id: u32) -> impl Iterator<Item = u32> + '_ {
+        self.outgoing
+            .get(id as usize)
+            .into_iter()
+            .flatten()
+            .map(|&(_, o)| o)
+    }
+
+    /// IDs of the subjects of the edges pointing at a node, once per edge
+    pub fn predecessors(&self, id: u32) -> impl Iterator<Item = u32> + '_ {
+        self.incoming
+            .get(id as usize)
+            .into_iter()
+            .flatten()
+            .map(|&(_, s)| s)
+    }
+
+    /// Every edge as `(subject, predicate, object)`, once per quad
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str, &str)> + '_ {
+        self.nodes().flat_map(move |(id, subject)| {
+            self.out_edges(id)
+                .map(move |(predicate, o)| (subject, predicate, self.node(o).unwrap_or_default()))
+        })
+    }
+
+    /// Edges into or out of a node
+    pub fn degree(&self, node: &str) -> usize {
+        self.node_id(node).map_or(0, |id| {
+            self.outgoing[id as usize].len() + self.incoming[id as usize].len()
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_ids.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    /// Estimated heap bytes, names counted in both the ID map and the name list
+    pub fn heap_bytes(&self) -> usize {
+        let names = |ids: &HashMap<String, u32>| {
+            map_bytes(ids) + 2 * ids.keys().map(string_bytes).sum::<usize>()
+        };
+        let adjacency = |lists: &Vec<Vec<(u32, u32)>>| {
+            vec_bytes(lists) + lists.iter().map(vec_bytes).sum::<usize>()
+        };
+        names(&self.node_ids)
+            + vec_bytes(&self.nodes)
+            + names(&self.predicate_ids)
+            + vec_bytes(&self.predicates)
+            + adjacency(&self.outgoing)
+            + adjacency(&self.incoming)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topology_counts_edges_per_quad() {
+        let mut topology = GraphTopology::new();
+        topology.add_edge("<a>", "<knows>", "<b>");
+        topology.add_edge("<a>", "<name>", "\"A\"");
+        topology.add_edge("<b>", "<knows>", "<a>");
+        // The same fact in a second graph
+        topology.add_edge("<a>", "<knows>", "<b>");
+
+        assert_eq!(topology.node_count(), 3);
+        assert_eq!(topology.edge_count(), 4);
+        assert_eq!(topology.degree("<a>"), 4);
+        assert_eq!(topology.degree("<b>"), 3);
+        assert_eq!(topology.degree("<missing>"), 0);
+        let out: Vec<_> = topology.outgoing("<a>").collect();
+        assert_eq!(
+            out,
+            vec![("<knows>", "<b>"), ("<name>", "\"A\""), ("<knows>", "<b>")]
+        );
+        assert_eq!(topology.outgoing("\"A\"").count(), 0);
+        let into_b: Vec<_> = topology.incoming("<b>").collect();
+        assert_eq!(into_b, vec![("<knows>", "<a>"), ("<knows>", "<a>")]);
+    }
+
+    #[test]
+    fn test_topology_removal() {
+        let mut topology = GraphTopology::new();
+        topology.add_edge("<a>", "<knows>", "<b>");
+        topology.add_edge("<a>", "<knows>", "<b>");
+        topology.add_edge("<b>", "<knows>", "<c>");
+        topology.add_edge("<c>", "<knows>", "<c>");
+
+        // One of the two copies goes; the nodes stay
+        assert!(topology.remove_edge("<a>", "<knows>", "<b>"));
+        assert!(!topology.remove_edge("<a>", "<likes>", "<b>"));
+        assert_eq!(topology.degree("<b>"), 2);
+        assert!(topology.remove_edge("<a>", "<knows>", "<b>"));
+        assert_eq!(topology.node_id("<a>"), None);
+        assert_eq!(topology.incoming("<b>").count(), 0);
+
+        assert_eq!(topology.remove_node("<c>"), 2);
+        assert_eq!(topology.node_count(), 0);
+        assert_eq!(topology.edge_count(), 0);
+
+        // Tombstones are compacted away once they dominate
+        for i in 0..100 {
+            topology.add_edge(&format!("<n{}>", i), "<knows>", "<hub>");
+        }
+        for i in 0..80 {
+            topology.remove_node(&format!("<n{}>", i));
+        }
+        assert_eq!(topology.node_count(), 21);
+        assert!(topology.nodes.len() < 100);
+        let hub = topology.node_id("<hub>").unwrap();
+        assert_eq!(topology.node(hub), Some("<hub>"));
+        assert_eq!(topology.incoming("<hub>").count(), 20);
+        assert!(topology.incoming("<hub>").all(|(_, s)| s.starts_with("<n")));
+    }
+}
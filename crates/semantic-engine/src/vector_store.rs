@@ -1,3 +1,4 @@
+use crate::memory::{map_bytes, string_bytes, vec_bytes};
 use anyhow::{anyhow, Result};
 #[cfg(feature = "local-embeddings")]
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
@@ -5,7 +6,8 @@ use hnsw::Hnsw;
 use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 
@@ -140,7 +142,7 @@ impl RemoteEmbedder {
 
 enum Embedder {
     #[cfg(feature = "local-embeddings")]
-    Local(TextEmbedding),
+    Local(Arc<TextEmbedding>),
     Remote(RemoteEmbedder),
     Mock,
 }
@@ -150,22 +152,17 @@ impl Embedder {
         match self {
             #[cfg(feature = "local-embeddings")]
             Embedder::Local(model) => {
-                // fastembed is blocking/CPU heavy, so we should spawn_blocking if we were rigorous,
-                // but for now we follow existing pattern (it seems existing code didn't spawn_blocking?)
-                // Ah, the memory mentioned "executed via tokio::task::spawn_blocking".
-                // We should preserve that if possible, but TextEmbedding is not Sync?
-                // TextEmbedding IS Sync.
-                // But fastembed::TextEmbedding::embed is synchronous.
-                // So strictly we should wrap it.
-                // But let's keep it simple:
-                Ok(model.embed(texts, None)?)
+                // fastembed is synchronous and CPU heavy
+                let model = Arc::clone(model);
+                Ok(tokio::task::spawn_blocking(move || model.embed(texts, None)).await??)
             }
             Embedder::Remote(remote) => remote.embed_batch(texts).await,
             Embedder::Mock => {
                 let mut rng = rand::rng();
                 let mut results = Vec::new();
                 for _ in 0..texts.len() {
-                    let embedding: Vec<f32> = (0..384).map(|_| rand::Rng::random(&mut rng)).collect();
+                    let embedding: Vec<f32> =
+                        (0..384).map(|_| rand::Rng::random(&mut rng)).collect();
                     results.push(embedding);
                 }
                 Ok(results)
@@ -176,24 +173,151 @@ impl Embedder {
 
 // --- VectorStore ---
 
-/// Vector store using Local FastEmbed or Remote API for embeddings
-pub struct VectorStore {
+type HnswIndex = Hnsw<Euclidian, Vec<f32>, Pcg64, 16, 32>;
+
+/// The HNSW index with its key maps and stored entries, behind one lock so they
+/// always change together
+struct VectorIndex {
     /// HNSW index for fast approximate nearest neighbor search
-    index: Arc<RwLock<Hnsw<Euclidian, Vec<f32>, Pcg64, 16, 32>>>,
+    hnsw: HnswIndex,
     /// Mapping from node ID (internal) to Key
-    id_to_key: Arc<RwLock<HashMap<usize, String>>>,
+    id_to_key: HashMap<usize, String>,
     /// Mapping from Key to node ID (internal)
-    key_to_id: Arc<RwLock<HashMap<String, usize>>>,
+    key_to_id: HashMap<String, usize>,
     /// Mapping from Key to Metadata (for fast retrieval)
-    key_to_metadata: Arc<RwLock<HashMap<String, serde_json::Value>>>,
+    key_to_metadata: HashMap<String, serde_json::Value>,
+    /// Stored embeddings for persistence
+    embeddings: Vec<VectorEntry>,
+    /// Bumped on every insert and removal
+    generation: u64,
+}
+
+impl VectorIndex {
+    /// Index the entries with the expected dimensions, dropping the others
+    fn from_entries(entries: Vec<VectorEntry>, dimensions: usize) -> Self {
+        let mut index = Self {
+            hnsw: Hnsw::new(Euclidian),
+            id_to_key: HashMap::new(),
+            key_to_id: HashMap::new(),
+            key_to_metadata: HashMap::new(),
+            embeddings: Vec::new(),
+            generation: 0,
+        };
+        let mut searcher = hnsw::Searcher::default();
+        for entry in entries {
+            if entry.embedding.len() == dimensions {
+                let id = index.hnsw.insert(entry.embedding.clone(), &mut searcher);
+                index.id_to_key.insert(id, entry.key.clone());
+                index.key_to_id.insert(entry.key.clone(), id);
+                let metadata =
+                    serde_json::from_str(&entry.metadata_json).unwrap_or(serde_json::Value::Null);
+                index.key_to_metadata.insert(entry.key.clone(), metadata);
+                index.embeddings.push(entry);
+            }
+        }
+        index
+    }
+
+    /// Insert the embeddings of `items[new_indices[i]]`, filling in `result_ids`.
+    /// Returns how many were added; keys indexed meanwhile keep their vector.
+    fn insert(
+        &mut self,
+        items: &[(String, String, serde_json::Value)],
+        new_indices: &[usize],
+        embeddings: Vec<Vec<f32>>,
+        result_ids: &mut [usize],
+    ) -> usize {
+        let mut searcher = hnsw::Searcher::default();
+        let mut added = 0;
+        for (&original_idx, embedding) in new_indices.iter().zip(embeddings) {
+            let (key, _, metadata) = &items[original_idx];
+
+            if let Some(&id) = self.key_to_id.get(key) {
+                result_ids[original_idx] = id;
+                continue;
+            }
+
+            let id = self.hnsw.insert(embedding.clone(), &mut searcher);
+            self.key_to_id.insert(key.clone(), id);
+            self.id_to_key.insert(id, key.clone());
+            self.key_to_metadata.insert(key.clone(), metadata.clone());
+
+            self.embeddings.push(VectorEntry {
+                key: key.clone(),
+                embedding,
+                metadata_json: serde_json::to_string(metadata).unwrap_or_default(),
+            });
+
+            result_ids[original_idx] = id;
+            added += 1;
+        }
+        self.generation += added as u64;
+        added
+    }
+
+    fn search(&self, query_embedding: &Vec<f32>, k: usize) -> Vec<SearchResult> {
+        let len = self.hnsw.len();
+        if len == 0 {
+            return Vec::new();
+        }
+
+        let k = k.min(len);
+        let ef = k.max(50);
+        let mut searcher = hnsw::Searcher::default();
+
+        let mut neighbors = vec![
+            space::Neighbor {
+                index: 0,
+                distance: u32::MAX
+            };
+            k
+        ];
+
+        let found_neighbors = self
+            .hnsw
+            .nearest(query_embedding, ef, &mut searcher, &mut neighbors);
+
+        found_neighbors
+            .iter()
+            .filter_map(|neighbor| {
+                self.id_to_key.get(&neighbor.index).map(|key| {
+                    let score_f32 = (neighbor.distance as f32) / 1_000_000.0;
+                    let metadata = self
+                        .key_to_metadata
+                        .get(key)
+                        .cloned()
+                        .unwrap_or(serde_json::Value::Null);
+                    let uri = metadata
+                        .get("uri")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(key)
+                        .to_string();
+
+                    SearchResult {
+                        key: key.clone(),
+                        score: 1.0 / (1.0 + score_f32),
+                        metadata,
+                        uri,
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Vector store using Local FastEmbed or Remote API for embeddings.
+///
+/// The index lock is only taken for in-memory work; async callers run index
+/// inserts, searches and saves on the blocking pool, so a slow one never stalls
+/// the executor.
+pub struct VectorStore {
+    index: Arc<RwLock<VectorIndex>>,
     /// Storage path for persistence
     storage_path: Option<PathBuf>,
     /// Embedding provider
     embedder: Arc<Embedder>,
     /// Vector dimensions
     dimensions: usize,
-    /// Stored embeddings for persistence
-    embeddings: Arc<RwLock<Vec<VectorEntry>>>,
     /// Number of unsaved changes
     dirty_count: Arc<AtomicUsize>,
     /// Threshold for auto-save
@@ -211,6 +335,28 @@ pub struct SearchResult {
     pub uri: String,
 }
 
+/// Save vectors to disk (JSON format for robust cross-version compatibility)
+fn save_vectors(path: &Path, index: &RwLock<VectorIndex>, dirty_count: &AtomicUsize) -> Result<()> {
+    std::fs::create_dir_all(path)?;
+
+    let (entries, current_dirty) = {
+        let index = index.read().unwrap();
+        (
+            index.embeddings.clone(),
+            dirty_count.load(Ordering::Relaxed),
+        )
+    };
+
+    let data = VectorData { entries };
+    let json = serde_json::to_string_pretty(&data)?;
+    std::fs::write(path.join("vectors.json"), json)?;
+
+    if current_dirty > 0 {
+        let _ = dirty_count.fetch_sub(current_dirty, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
 impl VectorStore {
     /// Create a new vector store for a namespace
     pub fn new(namespace: &str) -> Result<Self> {
@@ -218,7 +364,12 @@ impl VectorStore {
         let storage_path = std::env::var("GRAPH_STORAGE_PATH")
             .ok()
             .map(|p| PathBuf::from(p).join(namespace));
+        Self::open(storage_path)
+    }
 
+    /// Vector store persisted in `storage_path` (the namespace directory), or kept
+    /// in memory only without one
+    pub fn open(storage_path: Option<PathBuf>) -> Result<Self> {
         // Get dimensions from env or default
         let dimensions = std::env::var("VECTOR_DIMENSIONS")
             .ok()
@@ -235,126 +386,97 @@ impl VectorStore {
         let use_mock = std::env::var("MOCK_EMBEDDINGS").unwrap_or_default() == "true";
 
         let embedder = if use_mock {
-            eprintln!("VectorStore: Using MOCK Embeddings");
+            tracing::info!("VectorStore: Using MOCK Embeddings");
             Embedder::Mock
         } else if provider == "remote" || !cfg!(feature = "local-embeddings") {
-             let url = std::env::var("EMBEDDING_API_URL").unwrap_or_else(|_| DEFAULT_REMOTE_API_URL.to_string());
-             let model = std::env::var("EMBEDDING_MODEL").unwrap_or_else(|_| DEFAULT_REMOTE_MODEL.to_string());
-             let key = std::env::var("EMBEDDING_API_KEY").ok();
-
-             eprintln!("VectorStore: Using Remote Embeddings ({} model={})", url, model);
-             Embedder::Remote(RemoteEmbedder::new(url, model, key))
+            let url = std::env::var("EMBEDDING_API_URL")
+                .unwrap_or_else(|_| DEFAULT_REMOTE_API_URL.to_string());
+            let model = std::env::var("EMBEDDING_MODEL")
+                .unwrap_or_else(|_| DEFAULT_REMOTE_MODEL.to_string());
+            let key = std::env::var("EMBEDDING_API_KEY").ok();
+
+            tracing::info!(
+                "VectorStore: Using Remote Embeddings ({} model={})",
+                url,
+                model
+            );
+            Embedder::Remote(RemoteEmbedder::new(url, model, key))
         } else {
             #[cfg(feature = "local-embeddings")]
             {
                 // Initialize FastEmbed model
-                let mut model_opts =
-                    InitOptions::new(EmbeddingModel::BGESmallENV15).with_show_download_progress(true);
+                let mut model_opts = InitOptions::new(EmbeddingModel::BGESmallENV15)
+                    .with_show_download_progress(true);
 
                 if let Ok(cache_path) = std::env::var("FASTEMBED_CACHE_PATH") {
                     model_opts = model_opts.with_cache_dir(PathBuf::from(cache_path));
                 }
 
-                eprintln!("VectorStore: Using Local Embeddings (fastembed)");
+                tracing::info!("VectorStore: Using Local Embeddings (fastembed)");
                 let model = TextEmbedding::try_new(model_opts)?;
-                Embedder::Local(model)
+                Embedder::Local(Arc::new(model))
             }
             #[cfg(not(feature = "local-embeddings"))]
             {
                 // This branch should be unreachable due to the logic above,
                 // but safe fallback if logic changes
-                 let url = std::env::var("EMBEDDING_API_URL").unwrap_or_else(|_| DEFAULT_REMOTE_API_URL.to_string());
-                 let model = std::env::var("EMBEDDING_MODEL").unwrap_or_else(|_| DEFAULT_REMOTE_MODEL.to_string());
-                 Embedder::Remote(RemoteEmbedder::new(url, model, None))
+                let url = std::env::var("EMBEDDING_API_URL")
+                    .unwrap_or_else(|_| DEFAULT_REMOTE_API_URL.to_string());
+                let model = std::env::var("EMBEDDING_MODEL")
+                    .unwrap_or_else(|_| DEFAULT_REMOTE_MODEL.to_string());
+                Embedder::Remote(RemoteEmbedder::new(url, model, None))
             }
         };
 
-        // Create HNSW index
-        let mut index = Hnsw::new(Euclidian);
-        let mut id_to_key = HashMap::new();
-        let mut key_to_id = HashMap::new();
-        let mut key_to_metadata = HashMap::new();
-        let mut embeddings = Vec::new();
-
         // Try to load persisted vectors
+        let mut entries = Vec::new();
         if let Some(ref path) = storage_path {
             let vectors_json = path.join("vectors.json");
 
-            let loaded_data = if vectors_json.exists() {
-                match std::fs::read_to_string(&vectors_json) {
-                    Ok(content) => match serde_json::from_str::<VectorData>(&content) {
-                        Ok(data) => Some(data),
-                        Err(e) => {
-                            eprintln!("ERROR: Failed to parse vectors: {}", e);
-                            None
-                        }
-                    },
-                    Err(_) => None,
+            if let Ok(content) = std::fs::read_to_string(&vectors_json) {
+                match serde_json::from_str::<VectorData>(&content) {
+                    Ok(data) => entries = data.entries,
+                    Err(e) => tracing::error!("Failed to parse vectors: {}", e),
                 }
-            } else {
-                None
-            };
-
-            if let Some(data) = loaded_data {
-                let mut searcher = hnsw::Searcher::default();
-                for entry in data.entries {
-                    if entry.embedding.len() == dimensions {
-                        let id = index.insert(entry.embedding.clone(), &mut searcher);
-                        id_to_key.insert(id, entry.key.clone());
-                        key_to_id.insert(entry.key.clone(), id);
-                        
-                        let metadata = serde_json::from_str(&entry.metadata_json).unwrap_or(serde_json::Value::Null);
-                        key_to_metadata.insert(entry.key.clone(), metadata);
-                        embeddings.push(entry);
-                    }
-                }
-                eprintln!("Loaded {} vectors from disk", embeddings.len());
             }
         }
+        let loaded = !entries.is_empty();
+        let index = VectorIndex::from_entries(entries, dimensions);
+        if loaded {
+            tracing::info!("Loaded {} vectors from disk", index.embeddings.len());
+        }
 
         Ok(Self {
             index: Arc::new(RwLock::new(index)),
-            id_to_key: Arc::new(RwLock::new(id_to_key)),
-            key_to_id: Arc::new(RwLock::new(key_to_id)),
-            key_to_metadata: Arc::new(RwLock::new(key_to_metadata)),
             storage_path,
             embedder: Arc::new(embedder),
             dimensions,
-            embeddings: Arc::new(RwLock::new(embeddings)),
             dirty_count: Arc::new(AtomicUsize::new(0)),
             auto_save_threshold: DEFAULT_AUTO_SAVE_THRESHOLD,
         })
     }
 
-    /// Save vectors to disk (JSON format for robust cross-version compatibility)
     fn save_vectors(&self) -> Result<()> {
-        if let Some(ref path) = self.storage_path {
-            std::fs::create_dir_all(path)?;
-
-            let (entries, current_dirty) = {
-                let guard = self.embeddings.read().unwrap();
-                (guard.clone(), self.dirty_count.load(Ordering::Relaxed))
-            };
-
-            let data = VectorData { entries };
-            let json = serde_json::to_string_pretty(&data)?;
-            std::fs::write(path.join("vectors.json"), json)?;
-
-            if current_dirty > 0 {
-                let _ = self.dirty_count.fetch_sub(current_dirty, Ordering::Relaxed);
-            }
+        match self.storage_path {
+            Some(ref path) => save_vectors(path, &self.index, &self.dirty_count),
+            None => Ok(()),
         }
-        Ok(())
     }
 
     pub fn flush(&self) -> Result<()> {
         self.save_vectors()
     }
 
+    /// Whether vectors were added since they were last saved; always false for
+    /// stores without a storage path
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.storage_path.is_some() && self.dirty_count.load(Ordering::Relaxed) > 0
+    }
+
     pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
         let embeddings = self.embed_batch(vec![text.to_string()]).await?;
         if embeddings.is_empty() {
-             return Err(anyhow!("No embedding returned"));
+            return Err(anyhow!("No embedding returned"));
         }
         Ok(embeddings[0].clone())
     }
@@ -375,21 +497,23 @@ impl VectorStore {
         let results = self
             .add_batch(vec![(key.to_string(), content.to_string(), metadata)])
             .await?;
-        Ok(results[0])
+        Ok(results.0[0])
     }
 
+    /// Embed and index the items whose keys are not indexed yet. Returns the id of
+    /// every item, in order, and how many of them were newly inserted.
     pub async fn add_batch(
         &self,
         items: Vec<(String, String, serde_json::Value)>,
-    ) -> Result<Vec<usize>> {
+    ) -> Result<(Vec<usize>, usize)> {
         let mut new_items = Vec::new();
         let mut result_ids = vec![0; items.len()];
         let mut new_indices = Vec::new();
 
         {
-            let key_map = self.key_to_id.read().unwrap();
+            let index = self.index.read().unwrap();
             for (i, (key, content, _)) in items.iter().enumerate() {
-                if let Some(&id) = key_map.get(key) {
+                if let Some(&id) = index.key_to_id.get(key) {
                     result_ids[i] = id;
                 } else {
                     new_items.push(content.clone());
@@ -399,198 +523,151 @@ impl VectorStore {
         }
 
         if new_items.is_empty() {
-            return Ok(result_ids);
+            return Ok((result_ids, 0));
         }
 
         let embeddings = self.embed_batch(new_items).await?;
 
         // Validation: ensure we got embeddings
         if embeddings.len() != new_indices.len() {
-             eprintln!("WARNING: Requested {} embeddings, got {}. Some items may be skipped.", new_indices.len(), embeddings.len());
+            tracing::warn!(
+                "Requested {} embeddings, got {}. Some items may be skipped.",
+                new_indices.len(),
+                embeddings.len()
+            );
         }
 
-        let mut ids_to_add = Vec::new();
-        let mut searcher = hnsw::Searcher::default();
-
-        {
-            let mut index = self.index.write().unwrap();
-            let mut key_map = self.key_to_id.write().unwrap();
-            let mut id_map = self.id_to_key.write().unwrap();
-            let mut metadata_map = self.key_to_metadata.write().unwrap();
-            let mut embs = self.embeddings.write().unwrap();
-
-            for (i, embedding) in embeddings.into_iter().enumerate() {
-                if i >= new_indices.len() { break; } // Safety
-                let original_idx = new_indices[i];
-                let (key, _, metadata) = &items[original_idx];
-
-                if let Some(&id) = key_map.get(key) {
-                    result_ids[original_idx] = id;
-                    continue;
+        let index = Arc::clone(&self.index);
+        let dirty_count = Arc::clone(&self.dirty_count);
+        let storage_path = self.storage_path.clone();
+        let auto_save_threshold = self.auto_save_threshold;
+        tokio::task::spawn_blocking(move || {
+            let added =
+                index
+                    .write()
+                    .unwrap()
+                    .insert(&items, &new_indices, embeddings, &mut result_ids);
+            if added > 0 {
+                let count = dirty_count.fetch_add(added, Ordering::Relaxed);
+                if let (true, Some(path)) = (count + added >= auto_save_threshold, storage_path) {
+                    if let Err(e) = save_vectors(&path, &index, &dirty_count) {
+                        tracing::warn!("Failed to save vectors to {}: {}", path.display(), e);
+                    }
                 }
-
-                let id = index.insert(embedding.clone(), &mut searcher);
-                key_map.insert(key.clone(), id);
-                id_map.insert(id, key.clone());
-                metadata_map.insert(key.clone(), metadata.clone());
-
-                embs.push(VectorEntry {
-                    key: key.clone(),
-                    embedding,
-                    metadata_json: serde_json::to_string(metadata).unwrap_or_default(),
-                });
-
-                result_ids[original_idx] = id;
-                ids_to_add.push(id);
             }
-        }
-
-        if !ids_to_add.is_empty() {
-            let count = self
-                .dirty_count
-                .fetch_add(ids_to_add.len(), Ordering::Relaxed);
-            if count + ids_to_add.len() >= self.auto_save_threshold {
-                let _ = self.save_vectors();
-            }
-        }
-
-        Ok(result_ids)
+            Ok((result_ids, added))
+        })
+        .await?
     }
 
     pub async fn search(&self, query: &str, k: usize) -> Result<Vec<SearchResult>> {
         let query_embedding = self.embed(query).await?;
-        let mut searcher = hnsw::Searcher::default();
-
-        let index = self.index.read().unwrap();
-        let len = index.len();
-        if len == 0 {
-            return Ok(Vec::new());
-        }
-
-        let k = k.min(len);
-        let ef = k.max(50);
-
-        let mut neighbors = vec![
-            space::Neighbor {
-                index: 0,
-                distance: u32::MAX
-            };
-            k
-        ];
-
-        let found_neighbors = index.nearest(&query_embedding, ef, &mut searcher, &mut neighbors);
-
-        let id_map = self.id_to_key.read().unwrap();
-        let metadata_map = self.key_to_metadata.read().unwrap();
-
-        let results: Vec<SearchResult> = found_neighbors
-            .iter()
-            .filter_map(|neighbor| {
-                id_map.get(&neighbor.index).map(|key| {
-                    let score_f32 = (neighbor.distance as f32) / 1_000_000.0;
-                    let metadata = metadata_map
-                        .get(key)
-                        .cloned()
-                        .unwrap_or(serde_json::Value::Null);
-                    let uri = metadata
-                        .get("uri")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or(key)
-                        .to_string();
-
-                    SearchResult {
-                        key: key.clone(),
-                        score: 1.0 / (1.0 + score_f32),
-                        metadata,
-                        uri,
-                    }
-                })
-            })
-            .collect();
-
-        Ok(results)
+        let index = Arc::clone(&self.index);
+        Ok(
+            tokio::task::spawn_blocking(move || index.read().unwrap().search(&query_embedding, k))
+                .await?,
+        )
     }
 
     pub fn get_key(&self, id: usize) -> Option<String> {
-        self.id_to_key.read().unwrap().get(&id).cloned()
+        self.index.read().unwrap().id_to_key.get(&id).cloned()
     }
 
     pub fn get_id(&self, key: &str) -> Option<usize> {
-        self.key_to_id.read().unwrap().get(key).copied()
+        self.index.read().unwrap().key_to_id.get(key).copied()
     }
 
     pub fn len(&self) -> usize {
-        self.key_to_id.read().unwrap().len()
+        self.index.read().unwrap().key_to_id.len()
     }
 
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Rebuild the index without removed vectors. The rebuild runs under a read
+    /// lock, so searches continue meanwhile; it starts over if vectors were added
+    /// or removed before the swap.
     pub fn compact(&self) -> Result<usize> {
-        let embeddings = self.embeddings.read().unwrap();
-        let current_keys: std::collections::HashSet<_> =
-            self.key_to_id.read().unwrap().keys().cloned().collect();
-
-        if current_keys.is_empty() && !embeddings.is_empty() {
-            return Ok(0);
-        }
-
-        let active_entries: Vec<_> = embeddings
-            .iter()
-            .filter(|e| current_keys.contains(&e.key))
-            .cloned()
-            .collect();
-
-        let removed = embeddings.len() - active_entries.len();
-        if removed == 0 {
-            return Ok(0);
-        }
-
-        let mut new_index = hnsw::Hnsw::new(Euclidian);
-        let mut new_id_to_key = std::collections::HashMap::new();
-        let mut new_key_to_id = std::collections::HashMap::new();
-        let mut new_key_to_metadata = std::collections::HashMap::new();
-        let mut searcher = hnsw::Searcher::default();
-
-        for entry in &active_entries {
-            if entry.embedding.len() == self.dimensions {
-                let id = new_index.insert(entry.embedding.clone(), &mut searcher);
-                new_id_to_key.insert(id, entry.key.clone());
-                new_key_to_id.insert(entry.key.clone(), id);
-                let metadata = serde_json::from_str(&entry.metadata_json).unwrap_or(serde_json::Value::Null);
-                new_key_to_metadata.insert(entry.key.clone(), metadata);
+        let removed = loop {
+            let (active_entries, removed, generation) = {
+                let index = self.index.read().unwrap();
+                if index.key_to_id.is_empty() && !index.embeddings.is_empty() {
+                    return Ok(0);
+                }
+                let active_entries: Vec<_> = index
+                    .embeddings
+                    .iter()
+                    .filter(|e| index.key_to_id.contains_key(&e.key))
+                    .cloned()
+                    .collect();
+                let removed = index.embeddings.len() - active_entries.len();
+                (active_entries, removed, index.generation)
+            };
+            if removed == 0 {
+                return Ok(0);
             }
-        }
-
-        *self.index.write().unwrap() = new_index;
-        *self.id_to_key.write().unwrap() = new_id_to_key;
-        *self.key_to_id.write().unwrap() = new_key_to_id;
-        *self.key_to_metadata.write().unwrap() = new_key_to_metadata;
 
-        drop(embeddings);
-        *self.embeddings.write().unwrap() = active_entries;
+            let rebuilt = VectorIndex::from_entries(active_entries, self.dimensions);
+            let mut index = self.index.write().unwrap();
+            if index.generation == generation {
+                *index = VectorIndex {
+                    generation,
+                    ..rebuilt
+                };
+                break removed;
+            }
+        };
         let _ = self.save_vectors();
         Ok(removed)
     }
 
     pub fn remove(&self, key: &str) -> bool {
-        let mut key_map = self.key_to_id.write().unwrap();
-        let mut id_map = self.id_to_key.write().unwrap();
-        let mut metadata_map = self.key_to_metadata.write().unwrap();
-
-        if let Some(id) = key_map.remove(key) {
-            id_map.remove(&id);
-            metadata_map.remove(key);
+        let mut index = self.index.write().unwrap();
+        if let Some(id) = index.key_to_id.remove(key) {
+            index.id_to_key.remove(&id);
+            index.key_to_metadata.remove(key);
+            index.generation += 1;
             true
         } else {
             false
         }
     }
 
+    /// Estimated heap bytes of the embeddings, the HNSW index and the key maps
+    pub fn heap_bytes(&self) -> usize {
+        let index = self.index.read().unwrap();
+        let entries = vec_bytes(&index.embeddings)
+            + index
+                .embeddings
+                .iter()
+                .map(|e| {
+                    vec_bytes(&e.embedding) + string_bytes(&e.key) + string_bytes(&e.metadata_json)
+                })
+                .sum::<usize>();
+        // Each indexed point holds its own copy of the vector and up to 32
+        // neighbors on the bottom layer
+        let hnsw = index.hnsw.len()
+            * (size_of::<Vec<f32>>()
+                + self.dimensions * size_of::<f32>()
+                + 32 * size_of::<usize>());
+        let keys = map_bytes(&index.key_to_id)
+            + map_bytes(&index.id_to_key)
+            + 2 * index.key_to_id.keys().map(string_bytes).sum::<usize>();
+        // Metadata is also kept serialized in the entries; count the map itself
+        let metadata = map_bytes(&index.key_to_metadata)
+            + index
+                .key_to_metadata
+                .keys()
+                .map(string_bytes)
+                .sum::<usize>();
+        entries + hnsw + keys + metadata
+    }
+
     pub fn stats(&self) -> (usize, usize, usize) {
-        let embeddings_count = self.embeddings.read().unwrap().len();
-        let active_count = self.key_to_id.read().unwrap().len();
+        let index = self.index.read().unwrap();
+        let embeddings_count = index.embeddings.len();
+        let active_count = index.key_to_id.len();
         let stale_count = embeddings_count.saturating_sub(active_count);
         (active_count, stale_count, embeddings_count)
     }
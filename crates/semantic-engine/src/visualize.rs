@@ -0,0 +1,215 @@
+//! Bounded neighborhood traversal rendered as Mermaid or Graphviz DOT, so MCP
+//! clients can draw the surroundings of an entity.
+
+use crate::store::{local_name, SynapseStore};
+use oxigraph::model::{Subject, Term};
+use std::collections::{HashMap, HashSet};
+
+/// Text diagram formats `Subgraph::render` can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramFormat {
+    Mermaid,
+    Dot,
+}
+
+impl DiagramFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Dot),
+            _ => None,
+        }
+    }
+}
+
+/// Nodes and edges reached from a root, with edges as (from, predicate, to) node indices
+#[derive(Debug, Default)]
+pub struct Subgraph {
+    pub nodes: Vec<Term>,
+    pub edges: Vec<(usize, String, usize)>,
+    /// Whether `max_nodes` stopped the traversal early
+    pub truncated: bool,
+}
+
+impl Subgraph {
+    /// Breadth-first walk over outgoing and incoming edges, up to `depth` hops and
+    /// `max_nodes` nodes. Literals are leaves.
+    pub fn collect(store: &SynapseStore, root: &str, depth: usize, max_nodes: usize) -> Self {
+        let mut graph = Subgraph::default();
+        let Some(root) = SynapseStore::resource(root) else {
+            return graph;
+        };
+        let mut index = HashMap::new();
+        let mut seen_edges = HashSet::new();
+        graph.node(&mut index, Term::from(root.clone()));
+        let mut frontier = vec![root];
+
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for node in frontier {
+                let from = index[&Term::from(node.clone())];
+                let outgoing = store
+                    .store
+                    .quads_for_pattern(Some(node.as_ref()), None, None, None)
+                    .flatten()
+                    .map(|q| (q.predicate.as_str().to_string(), q.object, true));
+                let incoming = store
+                    .store
+                    .quads_for_pattern(None, None, Some(Term::from(node.clone()).as_ref()), None)
+                    .flatten()
+                    .map(|q| {
+                        (
+                            q.predicate.as_str().to_string(),
+                            Term::from(q.subject),
+                            false,
+                        )
+                    });
+
+                for (predicate, other, is_outgoing) in outgoing.chain(incoming) {
+                    let to = match index.get(&other) {
+                        Some(&i) => i,
+                        None if graph.nodes.len() >= max_nodes => {
+                            graph.truncated = true;
+                            continue;
+                        }
+                        None => {
+                            if let Some(resource) = expandable(&other) {
+                                next.push(resource);
+                            }
+                            graph.node(&mut index, other)
+                        }
+                    };
+                    let edge = if is_outgoing {
+                        (from, predicate, to)
+                    } else {
+                        (to, predicate, from)
+                    };
+                    if seen_edges.insert(edge.clone()) {
+                        graph.edges.push(edge);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        graph
+    }
+
+    fn node(&mut self, index: &mut HashMap<Term, usize>, term: Term) -> usize {
+        let i = self.nodes.len();
+        index.insert(term.clone(), i);
+        self.nodes.push(term);
+        i
+    }
+
+    pub fn render(&self, format: DiagramFormat) -> String {
+        match format {
+            DiagramFormat::Mermaid => self.to_mermaid(),
+            DiagramFormat::Dot => self.to_dot(),
+        }
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let escape = |s: &str| s.replace('"', "#quot;");
+        let mut out = String::from("graph LR\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = escape(&label(node));
+            if matches!(node, Term::Literal(_)) {
+                out.push_str(&format!("    n{}[/\"{}\"/]\n", i, label));
+            } else {
+                out.push_str(&format!("    n{}[\"{}\"]\n", i, label));
+            }
+        }
+        for (from, predicate, to) in &self.edges {
+            out.push_str(&format!(
+                "    n{} -->|\"{}\"| n{}\n",
+                from,
+                escape(local_name(predicate)),
+                to
+            ));
+        }
+        out
+    }
+
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut out = String::from("digraph G {\n    rankdir=LR;\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let shape = if matches!(node, Term::Literal(_)) {
+                "box"
+            } else {
+                "ellipse"
+            };
+            out.push_str(&format!(
+                "    n{} [label=\"{}\", shape={}];\n",
+                i,
+                escape(&label(node)),
+                shape
+            ));
+        }
+        for (from, predicate, to) in &self.edges {
+            out.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"];\n",
+                from,
+                to,
+                escape(local_name(predicate))
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Subjects can be expanded further; literals (and nested triples) cannot
+fn expandable(term: &Term) -> Option<Subject> {
+    match term {
+        Term::NamedNode(n) => Some(n.clone().into()),
+        Term::BlankNode(b) => Some(b.clone().into()),
+        _ => None,
+    }
+}
+
+fn label(term: &Term) -> String {
+    match term {
+        Term::NamedNode(n) => local_name(n.as_str()).to_string(),
+        Term::Literal(l) => l.value().to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    fn sample() -> Subgraph {
+        Subgraph {
+            nodes: vec![
+                NamedNode::new("http://ex.org/alice").unwrap().into(),
+                NamedNode::new("http://ex.org/bob").unwrap().into(),
+                Literal::new_simple_literal("Say \"hi\"").into(),
+            ],
+            edges: vec![
+                (0, "http://ex.org/knows".to_string(), 1),
+                (1, "http://ex.org/vocab#motto".to_string(), 2),
+            ],
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_mermaid_escapes_labels() {
+        let mermaid = sample().to_mermaid();
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("n0[\"alice\"]"));
+        assert!(mermaid.contains("n2[/\"Say #quot;hi#quot;\"/]"));
+        assert!(mermaid.contains("n1 -->|\"motto\"| n2"));
+    }
+
+    #[test]
+    fn test_dot_escapes_labels() {
+        let dot = sample().to_dot();
+        assert!(dot.contains("n2 [label=\"Say \\\"hi\\\"\", shape=box];"));
+        assert!(dot.contains("n0 -> n1 [label=\"knows\"];"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::ingest::IngestionEngine;
+use crate::store::SynapseStore;
+use anyhow::Result;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Default quiet period before a changed file is ingested
+pub const DEFAULT_DEBOUNCE_MS: u64 = 1000;
+
+/// Patterns always excluded: editor swap/backup files and partial downloads
+const DEFAULT_EXCLUDES: &[&str] = &["*~", "*.swp", "*.tmp", "*.part", "*.crdownload"];
+
+/// A folder watched for new or modified files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchConfig {
+    pub namespace: String,
+    pub path: PathBuf,
+    /// Glob patterns (relative to `path`, or bare file names) that are never ingested
+    pub exclude: Vec<String>,
+    pub debounce_ms: u64,
+}
+
+impl WatchConfig {
+    pub fn new(namespace: &str, path: impl Into<PathBuf>) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            path: path.into(),
+            exclude: Vec::new(),
+            debounce_ms: DEFAULT_DEBOUNCE_MS,
+        }
+    }
+
+    /// Whether a changed file should be ingested
+    pub fn accepts(&self, file: &Path) -> bool {
+        let Ok(relative) = file.strip_prefix(&self.path) else {
+            return false;
+        };
+        // Hidden files and anything inside hidden directories
+        if relative
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        {
+            return false;
+        }
+        if !IngestionEngine::is_supported(file) {
+            return false;
+        }
+
+        let file_name = file.file_name().unwrap_or_default();
+        !DEFAULT_EXCLUDES
+            .iter()
+            .copied()
+            .chain(self.exclude.iter().map(String::as_str))
+            .filter_map(|p| glob::Pattern::new(p).ok())
+            .any(|p| p.matches_path(relative) || p.matches_path(Path::new(file_name)))
+    }
+}
+
+/// Watches folders and ingests files dropped into them
+#[derive(Default)]
+pub struct FolderWatcher {
+    /// (namespace, canonical path) -> live OS watcher. Dropping the watcher closes the
+    /// event channel, which ends its ingestion task.
+    watches: Mutex<HashMap<(String, PathBuf), (WatchConfig, notify::RecommendedWatcher)>>,
+}
+
+impl FolderWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching `config.path`, ingesting into `store`. Replaces an existing
+    /// watch of the same folder for the namespace.
+    pub fn watch(&self, mut config: WatchConfig, store: Arc<SynapseStore>) -> Result<()> {
+        config.path = config.path.canonicalize()?;
+        if !config.path.is_dir() {
+            return Err(anyhow::anyhow!(
+                "Not a directory: {}",
+                config.path.display()
+            ));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+            })?;
+        watcher.watch(&config.path, RecursiveMode::Recursive)?;
+
+        tokio::spawn(Self::run(config.clone(), store, rx));
+
+        let key = (config.namespace.clone(), config.path.clone());
+        self.watches.lock().unwrap().insert(key, (config, watcher));
+        Ok(())
+    }
+
+    /// Stop watching a folder. Returns false if it was not watched.
+    pub fn unwatch(&self, namespace: &str, path: &Path) -> bool {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.watches
+            .lock()
+            .unwrap()
+            .remove(&(namespace.to_string(), path))
+            .is_some()
+    }
+
+    /// Active watches, optionally restricted to a namespace
+    pub fn list(&self, namespace: Option<&str>) -> Vec<WatchConfig> {
+        let mut configs: Vec<WatchConfig> = self
+            .watches
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(config, _)| config.clone())
+            .filter(|c| namespace.is_none_or(|ns| c.namespace == ns))
+            .collect();
+        configs.sort_by(|a, b| (&a.namespace, &a.path).cmp(&(&b.namespace, &b.path)));
+        configs
+    }
+
+    /// Collect change events and ingest each file once it has been quiet for the debounce period
+    async fn run(
+        config: WatchConfig,
+        store: Arc<SynapseStore>,
+        mut rx: mpsc::UnboundedReceiver<PathBuf>,
+    ) {
+        let debounce = Duration::from_millis(config.debounce_ms);
+        let engine = IngestionEngine::new(store);
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+        loop {
+            tokio::select! {
+                changed = rx.recv() => match changed {
+                    Some(path) => {
+                        if config.accepts(&path) {
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                    None => break,
+                },
+                _ = tokio::time::sleep(debounce), if !pending.is_empty() => {
+                    let now = Instant::now();
+                    let ready: Vec<PathBuf> = pending
+                        .iter()
+                        .filter(|(_, changed_at)| now.duration_since(**changed_at) >= debounce)
+                        .map(|(path, _)| path.clone())
+                        .collect();
+
+                    for path in ready {
+                        pending.remove(&path);
+                        if !path.is_file() {
+                            continue;
+                        }
+                        match engine.ingest_file(&path, &config.namespace).await {
+                            Ok(report) => tracing::info!(
+                                namespace = %config.namespace,
+                                "Watch: ingested {} triples from {}",
+                                report.added,
+                                path.display()
+                            ),
+                            Err(e) => tracing::warn!(
+                                namespace = %config.namespace,
+                                "Watch: failed to ingest {}: {}",
+                                path.display(),
+                                e
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parse `SYNAPSE_WATCH`-style specs: `namespace=/path,other=/path2`
+pub fn parse_watch_specs(spec: &str) -> Vec<WatchConfig> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let (namespace, path) = entry.split_once('=')?;
+            let (namespace, path) = (namespace.trim(), path.trim());
+            (!namespace.is_empty() && !path.is_empty()).then(|| WatchConfig::new(namespace, path))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_and_excludes() {
+        let mut config = WatchConfig::new("notes", "/data/notes");
+        config.exclude = vec!["drafts/**".to_string(), "*.csv".to_string()];
+
+        assert!(config.accepts(Path::new("/data/notes/today.md")));
+        assert!(config.accepts(Path::new("/data/notes/projects/plan.md")));
+        assert!(!config.accepts(Path::new("/data/notes/drafts/wip.md")));
+        assert!(!config.accepts(Path::new("/data/notes/sub/table.csv")));
+        assert!(!config.accepts(Path::new("/data/notes/.obsidian/app.json")));
+        assert!(!config.accepts(Path::new("/data/notes/today.md.swp")));
+        assert!(!config.accepts(Path::new("/data/notes/photo.png")));
+        assert!(!config.accepts(Path::new("/elsewhere/today.md")));
+    }
+
+    #[test]
+    fn test_parse_watch_specs() {
+        let specs = parse_watch_specs("notes=/data/notes, docs = /srv/docs,broken");
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[1].namespace, "docs");
+        assert_eq!(specs[1].path, PathBuf::from("/srv/docs"));
+    }
+}
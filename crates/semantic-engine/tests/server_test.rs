@@ -48,7 +48,7 @@ async fn test_get_neighbors_deterministic_scoring() {
     engine.ingest_triples(ingest_req).await.unwrap();
 
     // 1. Resolve ID for "http://a"
-    let store = engine.get_store("test").unwrap();
+    let store = engine.get_store("test").await.unwrap();
     let id_a = store.get_or_create_id("http://a");
 
     // 2. Query Neighbors of A with strategy "degree"
@@ -61,6 +61,7 @@ async fn test_get_neighbors_deterministic_scoring() {
         edge_filter: "".into(),
         node_type_filter: "".into(),
         limit_per_layer: 0,
+        page: None,
     };
 
     let mut req_wrapped = Request::new(req);
@@ -111,6 +112,7 @@ async fn test_get_neighbors_deterministic_scoring() {
         edge_filter: "".into(),
         node_type_filter: "".into(),
         limit_per_layer: 0,
+        page: None,
     };
 
     let mut req_depth_wrapped = Request::new(req_depth);
@@ -146,3 +148,2392 @@ async fn test_get_neighbors_deterministic_scoring() {
         "Depth 1 node should have higher score than depth 2 node"
     );
 }
+
+#[tokio::test]
+async fn test_get_all_triples_streams_pattern_matches() {
+    use futures::StreamExt;
+    use synapse_core::server::proto::TriplesRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    env::set_var("SYNAPSE_AUTH_TOKENS", "{\"test-token\": [\"*\"]}");
+    let storage_path = "/tmp/synapse_test_stream_triples";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let triples = (0..600)
+        .map(|i| Triple {
+            subject: format!("http://s{}", i),
+            predicate: if i % 3 == 0 { "http://p" } else { "http://q" }.into(),
+            object: "http://o".into(),
+            provenance: None,
+            embedding: vec![],
+        })
+        .collect();
+    let mut ingest_req = Request::new(IngestRequest {
+        namespace: "test".into(),
+        triples,
+    });
+    ingest_req
+        .metadata_mut()
+        .insert("authorization", "Bearer test-token".parse().unwrap());
+    engine.ingest_triples(ingest_req).await.unwrap();
+
+    let stream_triples = |predicate: &str| {
+        let mut req = Request::new(TriplesRequest {
+            namespace: "test".into(),
+            predicate: predicate.into(),
+            ..Default::default()
+        });
+        req.metadata_mut()
+            .insert("authorization", "Bearer test-token".parse().unwrap());
+        req
+    };
+
+    // More triples than the stream buffers at once
+    let all: Vec<Triple> = engine
+        .get_all_triples(stream_triples(""))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|t| t.unwrap())
+        .collect()
+        .await;
+    assert_eq!(all.len(), 600);
+
+    let matching: Vec<Triple> = engine
+        .get_all_triples(stream_triples("http://p"))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|t| t.unwrap())
+        .collect()
+        .await;
+    assert_eq!(matching.len(), 200);
+    assert!(matching.iter().all(|t| t.predicate == "http://p"));
+
+    let invalid = engine
+        .get_all_triples(stream_triples("http://bad iri"))
+        .await;
+    assert_eq!(invalid.err().unwrap().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_ingest_stream_acknowledges_chunks() {
+    use futures::StreamExt;
+    use synapse_core::server::proto::semantic_engine_client::SemanticEngineClient;
+    use synapse_core::server::proto::semantic_engine_server::SemanticEngineServer;
+    use synapse_core::server::proto::IngestStreamAck;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_ingest_stream";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let store = engine.get_store("default").await.unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        Some((listener.accept().await.map(|(s, _)| s), listener))
+    });
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(SemanticEngineServer::new(engine))
+            .serve_with_incoming(incoming),
+    );
+
+    let mut client = SemanticEngineClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+    // Three chunks of 100 triples; the last repeats the first chunk's triples
+    let chunks: Vec<IngestRequest> = [0, 100, 0]
+        .into_iter()
+        .map(|start| IngestRequest {
+            namespace: String::new(),
+            triples: (start..start + 100)
+                .map(|i| Triple {
+                    subject: format!("http://s{}", i),
+                    predicate: "http://p".into(),
+                    object: "http://o".into(),
+                    provenance: None,
+                    embedding: vec![],
+                })
+                .collect(),
+        })
+        .collect();
+    let acks: Vec<IngestStreamAck> = client
+        .ingest_stream(futures::stream::iter(chunks))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|ack| ack.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(acks.len(), 4);
+    assert_eq!(acks[1].chunks_received, 2);
+    assert_eq!(acks[1].totals.as_ref().unwrap().edges_added, 200);
+    let last = acks.last().unwrap();
+    assert!(last.done);
+    assert_eq!(last.triples_received, 300);
+    let totals = last.totals.as_ref().unwrap();
+    assert_eq!(totals.edges_added, 200);
+    assert_eq!(totals.duplicates_skipped, 100);
+    assert_eq!(store.store.len().unwrap(), 200);
+}
+
+#[tokio::test]
+async fn test_subscribe_changes_streams_matching_events() {
+    use futures::StreamExt;
+    use synapse_core::server::proto::{EmptyRequest, SubscribeChangesRequest};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    env::set_var("SYNAPSE_AUTH_TOKENS", "{\"test-token\": [\"*\"]}");
+    let storage_path = "/tmp/synapse_test_subscribe_changes";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    fn authorized<T>(message: T) -> Request<T> {
+        let mut req = Request::new(message);
+        req.metadata_mut()
+            .insert("authorization", "Bearer test-token".parse().unwrap());
+        req
+    }
+    let mut events = engine
+        .subscribe_changes(authorized(SubscribeChangesRequest {
+            namespace: "feed".into(),
+            predicate: "http://likes".into(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let triple = |s: &str, p: &str| Triple {
+        subject: s.into(),
+        predicate: p.into(),
+        object: "http://o".into(),
+        provenance: None,
+        embedding: vec![],
+    };
+    for namespace in ["other", "feed"] {
+        engine
+            .ingest_triples(authorized(IngestRequest {
+                namespace: namespace.into(),
+                triples: vec![
+                    triple("http://a", "http://knows"),
+                    triple("http://a", "http://likes"),
+                ],
+            }))
+            .await
+            .unwrap();
+    }
+    engine
+        .delete_namespace_data(authorized(EmptyRequest {
+            namespace: "feed".into(),
+        }))
+        .await
+        .unwrap();
+
+    let insert = events.next().await.unwrap().unwrap();
+    assert_eq!(insert.kind, "insert");
+    assert_eq!(insert.namespace, "feed");
+    assert_eq!(insert.subject, "http://a");
+    assert_eq!(insert.predicate, "http://likes");
+    let deleted = events.next().await.unwrap().unwrap();
+    assert_eq!(deleted.kind, "namespace_deleted");
+}
+
+#[tokio::test]
+async fn test_change_subscription_ends_when_client_leaves() {
+    use synapse_core::server::proto::SubscribeChangesRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_subscribe_changes_drop";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let events = engine
+        .subscribe_changes(Request::new(SubscribeChangesRequest::default()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(engine.changes.subscribers(), 1);
+
+    // Nothing is published, so only the disconnect can end the forwarding task
+    drop(events);
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while engine.changes.subscribers() > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("subscription outlived its client");
+}
+
+#[tokio::test]
+async fn test_metrics_layer_records_rpcs() {
+    use synapse_core::metrics::MetricsLayer;
+    use synapse_core::server::proto::semantic_engine_client::SemanticEngineClient;
+    use synapse_core::server::proto::semantic_engine_server::SemanticEngineServer;
+    use synapse_core::server::proto::{EmptyRequest, SparqlRequest};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    env::set_var("SYNAPSE_AUTH_TOKENS", "{\"test-token\": [\"*\"]}");
+    let storage_path = "/tmp/synapse_test_metrics";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    engine.auth.register_token(
+        "metrics-admin",
+        vec!["*".into()],
+        synapse_core::auth::NamespacePermission::all(),
+    );
+    let metrics = engine.metrics.clone();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        Some((listener.accept().await.map(|(s, _)| s), listener))
+    });
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .layer(MetricsLayer::new(metrics.clone()))
+            .add_service(SemanticEngineServer::new(engine))
+            .serve_with_incoming(incoming),
+    );
+
+    let mut client = SemanticEngineClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+    let query = |namespace: &str| SparqlRequest {
+        query: "ASK { ?s ?p ?o }".into(),
+        namespace: namespace.into(),
+    };
+    client.query_sparql(query("default")).await.unwrap();
+    // Anonymous callers can't reach other namespaces
+    let denied = client.query_sparql(query("private")).await;
+    assert_eq!(denied.err().unwrap().code(), tonic::Code::PermissionDenied);
+
+    assert_eq!(metrics.calls("QuerySparql"), 2);
+    let text = metrics.render();
+    assert!(text.contains(
+        "synapse_grpc_requests_total{method=\"QuerySparql\",namespace=\"default\",status=\"Ok\"} 1"
+    ));
+    assert!(text.contains(
+        "synapse_grpc_requests_total{method=\"QuerySparql\",namespace=\"private\",status=\"PermissionDenied\"} 1"
+    ));
+
+    let denied = client.get_metrics(EmptyRequest::default()).await;
+    assert_eq!(denied.err().unwrap().code(), tonic::Code::PermissionDenied);
+    let mut request = Request::new(EmptyRequest::default());
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer metrics-admin".parse().unwrap());
+    let exported = client.get_metrics(request).await.unwrap().into_inner();
+    assert!(exported
+        .prometheus
+        .contains("synapse_grpc_request_duration_seconds_count{method=\"QuerySparql\""));
+}
+
+#[tokio::test]
+async fn test_rate_limits_return_resource_exhausted() {
+    use std::sync::Arc;
+    use synapse_core::ratelimit::{Quota, RateLimiter};
+    use synapse_core::server::proto::SparqlRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_rate_limits";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    engine.rate_limiter = Arc::new(RateLimiter::new(Some(Quota::new(0.001, 2)), None));
+    let query = || {
+        Request::new(SparqlRequest {
+            query: "ASK { ?s ?p ?o }".into(),
+            namespace: "default".into(),
+        })
+    };
+    assert!(engine.query_sparql(query()).await.is_ok());
+    assert!(engine.query_sparql(query()).await.is_ok());
+    let limited = engine.query_sparql(query()).await.err().unwrap();
+    assert_eq!(limited.code(), tonic::Code::ResourceExhausted);
+}
+
+#[tokio::test]
+async fn test_get_namespace_stats() {
+    use synapse_core::server::proto::{EmptyRequest, Provenance};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_namespace_stats";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let triple = |s: &str, provenance: Option<Provenance>| Triple {
+        subject: s.into(),
+        predicate: "http://p".into(),
+        object: "http://o".into(),
+        provenance,
+        embedding: vec![],
+    };
+    let provenance = Provenance {
+        source: "notes.md".into(),
+        timestamp: "2024-01-01T00:00:00Z".into(),
+        method: "test".into(),
+    };
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples: vec![
+                triple("http://a", None),
+                triple("http://b", Some(provenance)),
+            ],
+        }))
+        .await
+        .unwrap();
+    engine.get_store("default").await.unwrap().flush().unwrap();
+
+    let stats = engine
+        .get_namespace_stats(Request::new(EmptyRequest::default()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(stats.namespace, "default");
+    assert_eq!(stats.graph_count, 1);
+    // Two facts plus the batch's provenance
+    assert!(stats.triple_count >= 2);
+    assert_eq!(stats.vector_count, 2);
+    assert_eq!(stats.uri_mapping_count, 4);
+    assert!(stats.disk_bytes > 0);
+    assert!(!stats.last_modified.is_empty());
+    assert!(stats.topology_memory_bytes > 0);
+    assert!(stats.property_memory_bytes > 0);
+    assert!(stats.uri_mapping_memory_bytes > 0);
+    assert_eq!(
+        stats.memory_bytes,
+        stats.topology_memory_bytes
+            + stats.property_memory_bytes
+            + stats.vector_memory_bytes
+            + stats.uri_mapping_memory_bytes
+    );
+    assert!(stats.graph_disk_bytes > 0);
+    assert!(stats.uri_mapping_disk_bytes > 0);
+    assert_eq!(
+        stats.disk_bytes,
+        stats.graph_disk_bytes
+            + stats.vector_disk_bytes
+            + stats.mirror_disk_bytes
+            + stats.uri_mapping_disk_bytes
+    );
+
+    let usage = synapse_core::metrics::render_namespace_usage(&[(
+        "default".to_string(),
+        engine.get_store("default").await.unwrap().memory_usage(),
+        engine.get_store("default").await.unwrap().disk_usage(),
+    )]);
+    assert!(usage
+        .contains("synapse_namespace_memory_bytes{namespace=\"default\",component=\"topology\"}"));
+    assert!(
+        usage.contains("synapse_namespace_disk_bytes{namespace=\"default\",component=\"graph\"}")
+    );
+}
+
+#[tokio::test]
+async fn test_export_namespace_streams_chunks() {
+    use futures::StreamExt;
+    use synapse_core::server::proto::{ExportChunk, ExportNamespaceRequest, Provenance};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_export_namespace";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let triples = (0..50)
+        .map(|i| Triple {
+            subject: format!("http://s{}", i),
+            predicate: "http://p".into(),
+            object: format!("\"value {}\"", i),
+            provenance: Some(Provenance {
+                source: "export-test".into(),
+                timestamp: "2024-01-01T00:00:00Z".into(),
+                method: "test".into(),
+            }),
+            embedding: vec![],
+        })
+        .collect();
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples,
+        }))
+        .await
+        .unwrap();
+    let total = engine
+        .get_store("default")
+        .await
+        .unwrap()
+        .store
+        .len()
+        .unwrap();
+
+    let chunks: Vec<ExportChunk> = engine
+        .export_namespace(Request::new(ExportNamespaceRequest {
+            chunk_size: 256,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|c| c.unwrap())
+        .collect()
+        .await;
+    assert!(chunks.len() > 2);
+    assert!(chunks[..chunks.len() - 1]
+        .iter()
+        .all(|c| c.data.len() == 256 && !c.done));
+    let last = chunks.last().unwrap();
+    assert!(last.done);
+    assert_eq!(last.count as usize, total);
+
+    // The dump is valid N-Quads that keeps the batch graphs
+    let dump: Vec<u8> = chunks.into_iter().flat_map(|c| c.data).collect();
+    let copy = oxigraph::store::Store::new().unwrap();
+    copy.load_from_reader(oxigraph::io::RdfFormat::NQuads, dump.as_slice())
+        .unwrap();
+    assert_eq!(copy.len().unwrap(), total);
+    assert_eq!(copy.named_graphs().count(), 1);
+}
+
+#[tokio::test]
+async fn test_import_namespace_loads_streamed_chunks() {
+    use synapse_core::server::proto::semantic_engine_client::SemanticEngineClient;
+    use synapse_core::server::proto::semantic_engine_server::SemanticEngineServer;
+    use synapse_core::server::proto::ImportChunk;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_import_namespace";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let store = engine.get_store("default").await.unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        Some((listener.accept().await.map(|(s, _)| s), listener))
+    });
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(SemanticEngineServer::new(engine))
+            .serve_with_incoming(incoming),
+    );
+    let mut client = SemanticEngineClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let dump: String = (0..40)
+        .map(|i| {
+            format!("<http://ex.org/s{i}> <http://ex.org/p> \"value {i}\" <urn:batch:imported> .\n")
+        })
+        .collect();
+    // Chunks split lines (and terms) at arbitrary points
+    let chunks: Vec<ImportChunk> = dump
+        .as_bytes()
+        .chunks(100)
+        .enumerate()
+        .map(|(i, data)| ImportChunk {
+            index_vectors: i == 0,
+            data: data.to_vec(),
+            ..Default::default()
+        })
+        .collect();
+    let report = client
+        .import_namespace(futures::stream::iter(chunks))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(report.edges_added, 40);
+    assert_eq!(report.vectors_created, 40);
+    assert_eq!(store.store.named_graphs().count(), 1);
+    assert_eq!(store.store.len().unwrap(), 40);
+
+    let bad = ImportChunk {
+        format: "turtle".into(),
+        data: b"<http://ex.org/a> <http://ex.org/p> .".to_vec(),
+        ..Default::default()
+    };
+    let status = client
+        .import_namespace(futures::stream::iter(vec![bad]))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_compact_namespace_reclaims_vectors_and_mappings() {
+    use synapse_core::auth::{AnonymousPolicy, NamespacePermission};
+    use synapse_core::server::proto::EmptyRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_compact_namespace";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    // Maintenance needs `admin`, which anonymous callers only get explicitly
+    engine.auth.set_anonymous_policy(AnonymousPolicy {
+        permissions: NamespacePermission::all(),
+        ..AnonymousPolicy::full()
+    });
+    let triple = |s: &str| Triple {
+        subject: s.into(),
+        predicate: "http://p".into(),
+        object: "http://o".into(),
+        provenance: None,
+        embedding: vec![],
+    };
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples: vec![triple("http://a"), triple("http://b")],
+        }))
+        .await
+        .unwrap();
+    let store = engine.get_store("default").await.unwrap();
+    let vs = store.vector_store.as_ref().unwrap();
+    assert!(vs.remove("http://a|http://p|http://o"));
+    store.get_or_create_id("http://gone");
+    assert_eq!(store.stats().unwrap().stale_vectors, 1);
+
+    let report = engine
+        .compact_namespace(Request::new(EmptyRequest::default()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(report.namespace, "default");
+    assert_eq!(report.vectors_removed, 1);
+    assert_eq!(report.uri_mappings_removed, 1);
+    assert!(report.disk_bytes_after > 0);
+
+    let stats = store.stats().unwrap();
+    assert_eq!(stats.stale_vectors, 0);
+    assert_eq!(stats.vectors, 1);
+    assert_eq!(stats.uri_mappings, 4);
+    assert!(store.uri_to_id.read().unwrap().contains_key("http://a"));
+
+    // Nothing left to reclaim
+    let again = engine
+        .compact_namespace(Request::new(EmptyRequest::default()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!((again.vectors_removed, again.uri_mappings_removed), (0, 0));
+}
+
+#[tokio::test]
+async fn test_update_sparql_requires_write_and_counts_quads() {
+    use std::sync::Arc;
+    use synapse_core::auth::{NamespaceAuth, NamespacePermission};
+    use synapse_core::server::proto::SparqlUpdateRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_update_sparql";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    let auth = NamespaceAuth::new();
+    auth.register_token(
+        "reader",
+        vec!["books".into()],
+        NamespacePermission {
+            write: false,
+            ..Default::default()
+        },
+    );
+    auth.register_token("writer", vec!["books".into()], Default::default());
+    engine.auth = Arc::new(auth);
+    let update = |token: &str, update: &str| {
+        let mut request = Request::new(SparqlUpdateRequest {
+            update: update.into(),
+            namespace: "books".into(),
+        });
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    };
+
+    let insert = "INSERT DATA { <http://ex.org/a> <http://ex.org/p> \"1\" . \
+                  <http://ex.org/b> <http://ex.org/p> \"2\" }";
+    let denied = engine.update_sparql(update("reader", insert)).await;
+    assert_eq!(denied.err().unwrap().code(), tonic::Code::PermissionDenied);
+
+    let report = engine
+        .update_sparql(update("writer", insert))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!((report.inserted, report.deleted), (2, 0));
+
+    let report = engine
+        .update_sparql(update(
+            "writer",
+            "DELETE { ?s <http://ex.org/p> \"1\" } INSERT { ?s <http://ex.org/p> \"3\" } \
+             WHERE { ?s <http://ex.org/p> \"1\" }",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!((report.inserted, report.deleted), (1, 1));
+    assert_eq!(
+        engine
+            .get_store("books")
+            .await
+            .unwrap()
+            .store
+            .len()
+            .unwrap(),
+        2
+    );
+
+    let invalid = engine
+        .update_sparql(update("writer", "INSERT NOTHING"))
+        .await;
+    assert_eq!(invalid.err().unwrap().code(), tonic::Code::InvalidArgument);
+
+    // Every authorized update is attributed to the writer's token
+    let writer = synapse_core::audit::actor(Some("writer"), "anonymous");
+    let mutations = engine.audit.get_mutations("books");
+    assert_eq!(mutations.len(), 3);
+    assert!(mutations
+        .iter()
+        .all(|m| m.actor == writer && m.action == "UpdateSparql"));
+}
+
+#[tokio::test]
+async fn test_resolve_ids_and_uris_in_batches() {
+    use synapse_core::server::proto::{ResolveRequest, ResolveUriRequest};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_resolve_batches";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples: vec![Triple {
+                subject: "http://ex.org/a".into(),
+                predicate: "http://ex.org/p".into(),
+                object: "http://ex.org/b".into(),
+                provenance: None,
+                embedding: vec![],
+            }],
+        }))
+        .await
+        .unwrap();
+
+    let resolved = engine
+        .resolve_id(Request::new(ResolveRequest {
+            contents: vec![
+                "http://ex.org/a".into(),
+                "http://ex.org/missing".into(),
+                "http://ex.org/b".into(),
+            ],
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!resolved.found);
+    let found: Vec<bool> = resolved.results.iter().map(|r| r.found).collect();
+    assert_eq!(found, vec![true, false, true]);
+
+    let ids = vec![
+        resolved.results[2].node_id,
+        9999,
+        resolved.results[0].node_id,
+    ];
+    let uris = engine
+        .resolve_uri(Request::new(ResolveUriRequest {
+            node_ids: ids.clone(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(uris.iter().map(|r| r.node_id).collect::<Vec<_>>(), ids);
+    assert_eq!(uris[0].uri, "http://ex.org/b");
+    assert!(!uris[1].found);
+    assert_eq!(uris[2].uri, "http://ex.org/a");
+
+    // The single-content form still answers in the top-level fields
+    let single = engine
+        .resolve_id(Request::new(ResolveRequest {
+            content: "http://ex.org/a".into(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(single.found && single.results.is_empty());
+    assert_eq!(single.node_id, uris[2].node_id);
+}
+
+#[tokio::test]
+async fn test_backup_namespace_job_writes_openable_copy() {
+    use synapse_core::auth::{AnonymousPolicy, NamespacePermission};
+    use synapse_core::server::proto::{BackupRequest, JobStatusRequest};
+    use synapse_core::store::SynapseStore;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_backup_namespace";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    // Maintenance needs `admin`, which anonymous callers only get explicitly
+    engine.auth.set_anonymous_policy(AnonymousPolicy {
+        permissions: NamespacePermission::all(),
+        ..AnonymousPolicy::full()
+    });
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples: vec![Triple {
+                subject: "http://ex.org/dune".into(),
+                predicate: "http://ex.org/author".into(),
+                object: "http://ex.org/herbert".into(),
+                provenance: None,
+                embedding: vec![],
+            }],
+        }))
+        .await
+        .unwrap();
+
+    let started = engine
+        .backup_namespace(Request::new(BackupRequest {
+            namespace: "default".into(),
+            destination: format!("{}/snapshots", storage_path),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(started
+        .path
+        .starts_with("/tmp/synapse_test_backup_namespace/snapshots/default-"));
+
+    let mut status = Default::default();
+    for _ in 0..100 {
+        status = engine
+            .get_job_status(Request::new(JobStatusRequest {
+                job_id: started.job_id.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        if status.state != "running" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(status.found);
+    assert_eq!(status.kind, "backup");
+    assert_eq!(status.state, "completed", "{}", status.message);
+    assert!(!status.finished_at.is_empty());
+
+    // The copy opens as a namespace of its own
+    let path = std::path::Path::new(&started.path);
+    let restored = SynapseStore::open(
+        path.file_name().unwrap().to_str().unwrap(),
+        path.parent().unwrap().to_str().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(restored.store.len().unwrap(), 1);
+    assert!(restored
+        .uri_to_id
+        .read()
+        .unwrap()
+        .contains_key("http://ex.org/dune"));
+
+    let rejected = engine
+        .backup_namespace(Request::new(BackupRequest {
+            namespace: "default".into(),
+            destination: "s3://bucket/backups".into(),
+        }))
+        .await;
+    assert_eq!(rejected.err().unwrap().code(), tonic::Code::InvalidArgument);
+
+    let missing = engine
+        .get_job_status(Request::new(JobStatusRequest {
+            job_id: "nope".into(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!missing.found);
+}
+
+#[tokio::test]
+async fn test_preview_reasoning_pages_without_writing() {
+    use synapse_core::server::proto::{PageRequest, PreviewReasoningRequest, ReasoningStrategy};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_preview_reasoning";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let sub_class_of = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+    let triples = ["A", "B", "C", "D"]
+        .windows(2)
+        .map(|pair| Triple {
+            subject: format!("http://ex.org/{}", pair[0]),
+            predicate: sub_class_of.into(),
+            object: format!("http://ex.org/{}", pair[1]),
+            provenance: None,
+            embedding: vec![],
+        })
+        .collect();
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples,
+        }))
+        .await
+        .unwrap();
+    let store = engine.get_store("default").await.unwrap();
+    let before = store.store.len().unwrap();
+
+    let preview = |page_token: String| {
+        Request::new(PreviewReasoningRequest {
+            strategy: ReasoningStrategy::Rdfs as i32,
+            page: Some(PageRequest {
+                page_size: 2,
+                page_token,
+            }),
+            ..Default::default()
+        })
+    };
+    let first = engine
+        .preview_reasoning(preview(String::new()))
+        .await
+        .unwrap()
+        .into_inner();
+    // A-C, B-D and A-D
+    assert_eq!(first.triples_inferred, 3);
+    assert_eq!(first.triples.len(), 2);
+    let first_page = first.page.unwrap();
+    assert_eq!(first_page.total_size, 3);
+    assert!(!first_page.next_page_token.is_empty());
+    let second = engine
+        .preview_reasoning(preview(first_page.next_page_token))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(second.triples.len(), 1);
+    assert!(second.page.unwrap().next_page_token.is_empty());
+    assert!(second.triples[0].subject.starts_with("http://ex.org/"));
+    assert!(first
+        .triples
+        .iter()
+        .all(|t| t.subject != second.triples[0].subject || t.object != second.triples[0].object));
+
+    assert_eq!(store.store.len().unwrap(), before);
+}
+
+#[tokio::test]
+async fn test_large_compressed_ingest_over_transport_config() {
+    use synapse_core::server::proto::semantic_engine_client::SemanticEngineClient;
+    use synapse_core::server::TransportConfig;
+    use tonic::codec::CompressionEncoding;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_transport_config";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    assert_eq!(
+        TransportConfig::parse_compression("zstd, gzip,zstd").unwrap(),
+        vec![CompressionEncoding::Zstd, CompressionEncoding::Gzip]
+    );
+    assert!(TransportConfig::parse_compression("none")
+        .unwrap()
+        .is_empty());
+    assert!(TransportConfig::parse_compression("brotli").is_err());
+
+    let engine = MySemanticEngine::new(storage_path);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        Some((listener.accept().await.map(|(s, _)| s), listener))
+    });
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(TransportConfig::default().service(engine))
+            .serve_with_incoming(incoming),
+    );
+    let mut client = SemanticEngineClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap()
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Zstd)
+        .max_encoding_message_size(usize::MAX);
+
+    // Well over tonic's default 4 MiB limit before compression
+    let filler = "x".repeat(5_000);
+    let triples: Vec<Triple> = (0..1_000)
+        .map(|i| Triple {
+            subject: format!("http://ex.org/doc{}", i),
+            predicate: "http://ex.org/text".into(),
+            object: format!("\"{}\"", filler),
+            provenance: None,
+            embedding: vec![],
+        })
+        .collect();
+    let report = client
+        .ingest_triples(IngestRequest {
+            namespace: "default".into(),
+            triples,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(report.edges_added, 1_000);
+}
+
+#[tokio::test]
+async fn test_drain_layer_tracks_streams_until_finished() {
+    use synapse_core::drain::DrainLayer;
+    use synapse_core::server::proto::semantic_engine_client::SemanticEngineClient;
+    use synapse_core::server::proto::semantic_engine_server::SemanticEngineServer;
+    use synapse_core::server::proto::SubscribeChangesRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_drain_layer";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let drain = engine.drain.clone();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        Some((listener.accept().await.map(|(s, _)| s), listener))
+    });
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .layer(DrainLayer::new(drain.clone()))
+            .add_service(SemanticEngineServer::new(engine))
+            .serve_with_incoming(incoming),
+    );
+    let mut client = SemanticEngineClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    // A change subscription stays open until the client goes away
+    let stream = client
+        .subscribe_changes(SubscribeChangesRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(drain.active(), 1);
+    assert!(!drain.wait_idle(std::time::Duration::from_millis(50)).await);
+
+    drop(stream);
+    assert!(drain.wait_idle(std::time::Duration::from_secs(5)).await);
+}
+
+#[tokio::test]
+async fn test_delete_graph_drops_one_batch() {
+    use synapse_core::server::proto::{DeleteGraphRequest, Provenance};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_delete_graph";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let batch = |source: &str, subject: &str| IngestRequest {
+        namespace: "default".into(),
+        triples: vec![Triple {
+            subject: subject.into(),
+            predicate: "http://ex.org/p".into(),
+            object: "http://ex.org/o".into(),
+            provenance: Some(Provenance {
+                source: source.into(),
+                timestamp: "2024-01-01T00:00:00Z".into(),
+                method: "test".into(),
+            }),
+            embedding: vec![],
+        }],
+    };
+    for (source, subject) in [
+        ("good.md", "http://ex.org/a"),
+        ("bad.md", "http://ex.org/b"),
+    ] {
+        engine
+            .ingest_triples(Request::new(batch(source, subject)))
+            .await
+            .unwrap();
+    }
+    let store = engine.get_store("default").await.unwrap();
+    let bad_graph = store
+        .get_provenance("http://ex.org/b")
+        .unwrap()
+        .remove(0)
+        .graph;
+    let before = store.store.len().unwrap();
+
+    let deleted = engine
+        .delete_graph(Request::new(DeleteGraphRequest {
+            namespace: "default".into(),
+            graph: bad_graph.clone(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(deleted.triples_removed, 1);
+    assert_eq!(deleted.vectors_removed, 1);
+    assert_eq!(store.store.named_graphs().count(), 1);
+    // The fact and its batch's provenance are gone; the other batch is untouched
+    assert!(store.get_provenance("http://ex.org/b").unwrap().is_empty());
+    assert_eq!(store.get_provenance("http://ex.org/a").unwrap().len(), 1);
+    assert!(store.store.len().unwrap() < before - 1);
+    assert_eq!(store.vector_store.as_ref().unwrap().len(), 1);
+
+    let missing = engine
+        .delete_graph(Request::new(DeleteGraphRequest {
+            namespace: "default".into(),
+            graph: bad_graph,
+        }))
+        .await;
+    assert_eq!(missing.err().unwrap().code(), tonic::Code::NotFound);
+    let invalid = engine
+        .delete_graph(Request::new(DeleteGraphRequest {
+            namespace: "default".into(),
+            graph: "not a uri".into(),
+        }))
+        .await;
+    assert_eq!(invalid.err().unwrap().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_list_rpcs_share_page_tokens() {
+    use synapse_core::server::proto::{
+        ListTriplesRequest, NodeRequest, PageRequest, ResolveRequest, TriplesRequest,
+    };
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_pagination";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let triples = (0..5)
+        .map(|i| Triple {
+            subject: "http://ex.org/hub".into(),
+            predicate: "http://ex.org/links".into(),
+            object: format!("http://ex.org/n{}", i),
+            provenance: None,
+            embedding: vec![],
+        })
+        .collect();
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples,
+        }))
+        .await
+        .unwrap();
+
+    // Walk the triples two at a time
+    let mut seen = Vec::new();
+    let mut page_token = String::new();
+    loop {
+        let page = engine
+            .list_triples(Request::new(ListTriplesRequest {
+                pattern: Some(TriplesRequest {
+                    subject: "http://ex.org/hub".into(),
+                    ..Default::default()
+                }),
+                page: Some(PageRequest {
+                    page_size: 2,
+                    page_token,
+                }),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(page.triples.len() <= 2);
+        seen.extend(page.triples.into_iter().map(|t| t.object));
+        page_token = page.page.unwrap().next_page_token;
+        if page_token.is_empty() {
+            break;
+        }
+    }
+    seen.sort();
+    seen.dedup();
+    assert_eq!(seen.len(), 5);
+
+    let hub = engine
+        .resolve_id(Request::new(ResolveRequest {
+            content: "http://ex.org/hub".into(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .node_id;
+    let neighbors = |page: Option<PageRequest>| {
+        Request::new(NodeRequest {
+            node_id: hub,
+            page,
+            ..Default::default()
+        })
+    };
+    let all = engine
+        .get_neighbors(neighbors(None))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(all.neighbors.len(), 5);
+    assert!(all.page.unwrap().next_page_token.is_empty());
+
+    let last = engine
+        .get_neighbors(neighbors(Some(PageRequest {
+            page_size: 3,
+            page_token: "3".into(),
+        })))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(last.neighbors.len(), 2);
+    let page = last.page.unwrap();
+    assert_eq!(page.total_size, 5);
+    assert!(page.next_page_token.is_empty());
+
+    let invalid = engine
+        .get_neighbors(neighbors(Some(PageRequest {
+            page_size: 3,
+            page_token: "next".into(),
+        })))
+        .await;
+    assert_eq!(invalid.err().unwrap().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_operation_scopes_are_enforced() {
+    use std::sync::Arc;
+    use synapse_core::auth::{NamespaceAuth, NamespacePermission};
+    use synapse_core::server::proto::{EmptyRequest, SearchRequest, SparqlRequest};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_operation_scopes";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    let auth = NamespaceAuth::new();
+    auth.register_token(
+        "searcher",
+        vec!["*".into()],
+        NamespacePermission {
+            query: false,
+            admin: false,
+            ..Default::default()
+        },
+    );
+    auth.register_token(
+        "blind",
+        vec!["*".into()],
+        NamespacePermission {
+            read: false,
+            ..NamespacePermission::all()
+        },
+    );
+    engine.auth = Arc::new(auth);
+    fn with_token<T>(message: T, token: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    }
+    let sparql = || SparqlRequest {
+        query: "SELECT * WHERE { ?s ?p ?o }".into(),
+        namespace: "books".into(),
+    };
+    let search = || SearchRequest {
+        query: "book".into(),
+        limit: 5,
+        namespace: "books".into(),
+    };
+
+    let denied = engine
+        .query_sparql(with_token(sparql(), "searcher"))
+        .await
+        .unwrap_err();
+    assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+    assert_eq!(denied.message(), "Query permission denied");
+    assert!(engine
+        .search(with_token(search(), "searcher"))
+        .await
+        .is_ok());
+    let denied = engine
+        .get_metrics(with_token(EmptyRequest::default(), "searcher"))
+        .await
+        .unwrap_err();
+    assert_eq!(denied.message(), "Admin permission denied");
+
+    // Finer read scopes cannot outlive a revoked read permission
+    let denied = engine
+        .search(with_token(search(), "blind"))
+        .await
+        .unwrap_err();
+    assert_eq!(denied.message(), "Read permission denied");
+    assert!(engine
+        .get_metrics(with_token(EmptyRequest::default(), "blind"))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_ingest_is_refused_past_namespace_quota() {
+    use synapse_core::quota::QuotaLimits;
+    use synapse_core::server::ERROR_KIND_METADATA;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_quota";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    engine.quotas.set_namespace_quota(
+        "default",
+        QuotaLimits {
+            max_triples: Some(3),
+            ..Default::default()
+        },
+    );
+    let ingest = |names: &[&str]| {
+        Request::new(IngestRequest {
+            triples: names
+                .iter()
+                .map(|n| Triple {
+                    subject: format!("http://ex.org/{}", n),
+                    predicate: "http://ex.org/p".into(),
+                    object: "http://ex.org/o".into(),
+                    ..Default::default()
+                })
+                .collect(),
+            namespace: "default".into(),
+        })
+    };
+
+    engine.ingest_triples(ingest(&["a", "b"])).await.unwrap();
+    let refused = engine
+        .ingest_triples(ingest(&["c", "d"]))
+        .await
+        .unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    assert_eq!(
+        refused.metadata().get(ERROR_KIND_METADATA).unwrap(),
+        "quota-exceeded"
+    );
+    assert!(refused.message().contains("4 triples (limit 3)"));
+
+    // What still fits is accepted
+    engine.ingest_triples(ingest(&["c"])).await.unwrap();
+    let store = engine.get_store("default").await.unwrap();
+    assert_eq!(store.store.len().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_token_admin_rpcs() {
+    use std::sync::Arc;
+    use synapse_core::auth::{NamespaceAuth, NamespacePermission};
+    use synapse_core::server::proto::{
+        CreateTokenRequest, EmptyRequest, RevokeTokenRequest, RotateTokenRequest, TokenPermissions,
+    };
+
+    let storage_path = "/tmp/synapse_test_token_admin";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    let auth = NamespaceAuth::new();
+    auth.persist_to(format!("{}/tokens.json", storage_path))
+        .unwrap();
+    auth.register_token("root", vec!["*".into()], NamespacePermission::all());
+    auth.register_token(
+        "operator",
+        vec!["*".into()],
+        NamespacePermission {
+            admin: false,
+            ..Default::default()
+        },
+    );
+    engine.auth = Arc::new(auth);
+    fn with_token<T>(message: T, token: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    }
+
+    let create = CreateTokenRequest {
+        namespaces: vec!["books".into()],
+        permissions: Some(TokenPermissions {
+            read: true,
+            search: true,
+            ..Default::default()
+        }),
+        expires_at: String::new(),
+    };
+    let denied = engine
+        .create_token(with_token(create.clone(), "operator"))
+        .await
+        .unwrap_err();
+    assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+
+    let created = engine
+        .create_token(with_token(create, "root"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(engine
+        .auth
+        .check(Some(&created.token), "books", "search")
+        .is_ok());
+    assert!(engine
+        .auth
+        .check(Some(&created.token), "books", "write")
+        .is_err());
+
+    // A fresh engine picks the token up from disk
+    let restarted = MySemanticEngine::new(storage_path);
+    let listed = restarted
+        .list_tokens(Request::new(EmptyRequest::default()))
+        .await;
+    assert_eq!(listed.unwrap_err().code(), tonic::Code::PermissionDenied);
+    assert!(restarted
+        .auth
+        .check(Some(&created.token), "books", "read")
+        .is_ok());
+
+    let tokens = engine
+        .list_tokens(with_token(EmptyRequest::default(), "root"))
+        .await
+        .unwrap()
+        .into_inner()
+        .tokens;
+    assert_eq!(tokens.len(), 3);
+    let api = tokens.iter().find(|t| t.source == "api").unwrap();
+    assert_eq!(api.fingerprint, created.fingerprint);
+    assert_eq!(api.namespaces, vec!["books".to_string()]);
+
+    let rotated = engine
+        .rotate_token(with_token(
+            RotateTokenRequest {
+                token: created.token.clone(),
+                grace_seconds: 0,
+                expires_at: "2999-01-01T00:00:00Z".into(),
+            },
+            "root",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(rotated.found);
+    let err = engine
+        .auth
+        .check(Some(&created.token), "books", "read")
+        .unwrap_err();
+    assert!(err.starts_with("Token expired"));
+    assert!(engine
+        .auth
+        .check(Some(&rotated.token), "books", "read")
+        .is_ok());
+    let restarted = MySemanticEngine::new(storage_path);
+    assert!(restarted
+        .auth
+        .check(Some(&created.token), "books", "read")
+        .is_err());
+    let listed = restarted.auth.list_tokens();
+    let replacement = listed
+        .iter()
+        .find(|t| t.fingerprint == rotated.fingerprint)
+        .unwrap();
+    assert_eq!(
+        replacement.expires_at.unwrap().to_rfc3339(),
+        "2999-01-01T00:00:00+00:00"
+    );
+
+    let revoked = engine
+        .revoke_token(with_token(
+            RevokeTokenRequest {
+                token: created.fingerprint.clone(),
+            },
+            "root",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(revoked.revoked);
+    assert!(engine
+        .auth
+        .check(Some(&created.token), "books", "read")
+        .is_err());
+    let restarted = MySemanticEngine::new(storage_path);
+    assert!(restarted
+        .auth
+        .check(Some(&created.token), "books", "read")
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_admin_is_never_implied_by_write() {
+    use std::sync::Arc;
+    use synapse_core::auth::NamespaceAuth;
+    use synapse_core::server::proto::{CreateTokenRequest, TokenPermissions};
+
+    let storage_path = "/tmp/synapse_test_admin_not_implied";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+    let tokens = format!("{}/tokens.json", storage_path);
+    std::fs::write(
+        &tokens,
+        r#"{"tokens": {
+            "writer": {"namespaces": ["*"], "permissions": {"write": true}},
+            "legacy": ["*"],
+            "plain": {"namespaces": ["*"]},
+            "root": {"namespaces": ["*"], "permissions": {"admin": true}}
+        }}"#,
+    )
+    .unwrap();
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    let auth = NamespaceAuth::new();
+    auth.load_from_file(std::path::Path::new(&tokens)).unwrap();
+    engine.auth = Arc::new(auth);
+    let create = |token: &str| {
+        let mut request = Request::new(CreateTokenRequest {
+            namespaces: vec!["*".into()],
+            permissions: Some(TokenPermissions {
+                read: true,
+                ..Default::default()
+            }),
+            expires_at: String::new(),
+        });
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    };
+
+    for token in ["writer", "legacy", "plain"] {
+        assert!(engine.auth.check(Some(token), "books", "write").is_ok());
+        let denied = engine.create_token(create(token)).await.unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied, "{}", token);
+    }
+    assert!(engine.create_token(create("root")).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_mtls_maps_client_certificates_to_permissions() {
+    use std::path::Path;
+    use synapse_core::auth::NamespacePermission;
+    use synapse_core::server::certificate_interceptor;
+    use synapse_core::server::proto::semantic_engine_client::SemanticEngineClient;
+    use synapse_core::server::proto::semantic_engine_server::SemanticEngineServer;
+    use synapse_core::server::proto::SparqlRequest;
+    use tonic::service::interceptor::InterceptedService;
+    use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_mtls";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let fixture = |name: &str| std::fs::read(format!("tests/fixtures/tls/{}", name)).unwrap();
+
+    let engine = MySemanticEngine::new(storage_path);
+    engine.auth.register_token(
+        "cert:spiffe://synapse.test/agent-1",
+        vec!["books".into()],
+        NamespacePermission {
+            write: false,
+            ..Default::default()
+        },
+    );
+    let tls = synapse_core::tls::server_tls(
+        Path::new("tests/fixtures/tls/server.pem"),
+        Path::new("tests/fixtures/tls/server.key"),
+        Some(Path::new("tests/fixtures/tls/ca.pem")),
+    )
+    .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        Some((listener.accept().await.map(|(s, _)| s), listener))
+    });
+    let auth = engine.auth.clone();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .tls_config(tls)
+            .unwrap()
+            .add_service(InterceptedService::new(
+                SemanticEngineServer::new(engine),
+                certificate_interceptor(auth),
+            ))
+            .serve_with_incoming(incoming),
+    );
+
+    let connect = |identity: Option<Identity>| {
+        let mut tls = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(fixture("ca.pem")))
+            .domain_name("localhost");
+        if let Some(identity) = identity {
+            tls = tls.identity(identity);
+        }
+        async move {
+            Channel::from_shared(format!("https://{}", addr))
+                .unwrap()
+                .tls_config(tls)
+                .unwrap()
+                .connect()
+                .await
+        }
+    };
+    let query = |token: Option<&str>| {
+        let mut request = Request::new(SparqlRequest {
+            query: "ASK { ?s ?p ?o }".into(),
+            namespace: "books".into(),
+        });
+        if let Some(token) = token {
+            request.metadata_mut().insert(
+                "authorization",
+                format!("Bearer {}", token).parse().unwrap(),
+            );
+        }
+        request
+    };
+
+    // Without a client certificate the handshake fails (with TLS 1.3 the client
+    // only notices on its first call)
+    if let Ok(channel) = connect(None).await {
+        let mut anonymous = SemanticEngineClient::new(channel);
+        let refused = anonymous.query_sparql(query(None)).await.unwrap_err();
+        assert_ne!(refused.code(), tonic::Code::PermissionDenied);
+    }
+
+    let channel = connect(Some(Identity::from_pem(
+        fixture("client.pem"),
+        fixture("client.key"),
+    )))
+    .await
+    .unwrap();
+    let mut client = SemanticEngineClient::new(channel);
+    client.query_sparql(query(None)).await.unwrap();
+    let denied = client
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![],
+            namespace: "books".into(),
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(denied.message(), "Write permission denied");
+
+    // Certificate principals cannot be claimed with a bearer token
+    let spoofed = client
+        .query_sparql(query(Some("cert:spiffe://synapse.test/agent-1")))
+        .await
+        .unwrap_err();
+    assert_eq!(spoofed.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_graph_acls_within_namespace() {
+    use std::sync::Arc;
+    use synapse_core::auth::{GraphAccess, NamespaceAuth, NamespacePermission};
+    use synapse_core::server::proto::{
+        DeleteGraphRequest, ExportNamespaceRequest, HybridSearchRequest, SearchRequest,
+        SparqlRequest, SparqlUpdateRequest, TriplesRequest, WatchFolderRequest,
+    };
+
+    let storage_path = "/tmp/synapse_test_graph_acls";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    let auth = NamespaceAuth::new();
+    auth.register_token("admin", vec!["*".into()], NamespacePermission::default());
+    auth.register_token(
+        "agent",
+        vec!["books".into()],
+        NamespacePermission::default(),
+    );
+    auth.set_graph_access(
+        "agent",
+        GraphAccess {
+            read: Some(vec!["default".into(), "http://example.org/agent-*".into()]),
+            write: Some(vec!["http://example.org/agent-1".into()]),
+        },
+    )
+    .unwrap();
+    engine.auth = Arc::new(auth);
+    fn with_token<T>(message: T, token: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    }
+    let update = |update: &str| SparqlUpdateRequest {
+        update: update.into(),
+        namespace: "books".into(),
+    };
+    let query = |query: &str| SparqlRequest {
+        query: query.into(),
+        namespace: "books".into(),
+    };
+
+    engine
+        .update_sparql(with_token(
+            update(
+                "INSERT DATA { <http://example.org/a> <http://example.org/p> \"public\" . \
+                 GRAPH <http://example.org/private> { <http://example.org/a> <http://example.org/p> \"secret\" } }",
+            ),
+            "admin",
+        ))
+        .await
+        .unwrap();
+
+    // Writes only reach the agent's own graph
+    let inserted = engine
+        .update_sparql(with_token(
+            update("INSERT DATA { GRAPH <http://example.org/agent-1> { <http://example.org/a> <http://example.org/p> \"mine\" } }"),
+            "agent",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(inserted.inserted, 1);
+    for denied in [
+        "INSERT DATA { <http://example.org/a> <http://example.org/p> \"x\" }",
+        "DELETE WHERE { GRAPH ?g { ?s ?p ?o } }",
+        "DROP GRAPH <http://example.org/private>",
+    ] {
+        let status = engine
+            .update_sparql(with_token(update(denied), "agent"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied, "{}", denied);
+    }
+    let status = engine
+        .ingest_triples(with_token(
+            IngestRequest {
+                triples: vec![Triple {
+                    subject: "http://example.org/b".into(),
+                    predicate: "http://example.org/p".into(),
+                    object: "http://example.org/c".into(),
+                    provenance: None,
+                    embedding: vec![],
+                }],
+                namespace: "books".into(),
+            },
+            "agent",
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    assert_eq!(
+        status.message(),
+        "Token not authorized to write graph: default"
+    );
+    // Watched files land in new batch graphs, which the agent may not write
+    let status = engine
+        .watch_folder(with_token(
+            WatchFolderRequest {
+                path: storage_path.into(),
+                namespace: "books".into(),
+                ..Default::default()
+            },
+            "agent",
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    // Queries and update WHERE clauses only see readable graphs
+    let all = "SELECT ?o WHERE { { ?s ?p ?o } UNION { GRAPH ?g { ?s ?p ?o } } }";
+    let seen = engine
+        .query_sparql(with_token(query(all), "agent"))
+        .await
+        .unwrap()
+        .into_inner()
+        .results_json;
+    assert!(seen.contains("public") && seen.contains("mine"), "{}", seen);
+    assert!(!seen.contains("secret"), "{}", seen);
+    let seen = engine
+        .query_sparql(with_token(query(all), "admin"))
+        .await
+        .unwrap()
+        .into_inner()
+        .results_json;
+    assert!(seen.contains("secret"), "{}", seen);
+    let copied = engine
+        .update_sparql(with_token(
+            update(
+                "INSERT { GRAPH <http://example.org/agent-1> { ?s <http://example.org/copy> ?o } } \
+                 WHERE { GRAPH <http://example.org/private> { ?s ?p ?o } }",
+            ),
+            "agent",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(copied.inserted, 0);
+
+    // Reads over every graph are refused rather than leaking unreadable ones
+    let triples = || TriplesRequest {
+        namespace: "books".into(),
+        ..Default::default()
+    };
+    let denied = [
+        engine
+            .get_all_triples(with_token(triples(), "agent"))
+            .await
+            .err(),
+        engine
+            .export_namespace(with_token(
+                ExportNamespaceRequest {
+                    namespace: "books".into(),
+                    ..Default::default()
+                },
+                "agent",
+            ))
+            .await
+            .err(),
+        engine
+            .search(with_token(
+                SearchRequest {
+                    query: "secret".into(),
+                    namespace: "books".into(),
+                    ..Default::default()
+                },
+                "agent",
+            ))
+            .await
+            .err(),
+        engine
+            .hybrid_search(with_token(
+                HybridSearchRequest {
+                    query: "secret".into(),
+                    namespace: "books".into(),
+                    ..Default::default()
+                },
+                "agent",
+            ))
+            .await
+            .err(),
+    ];
+    for status in denied {
+        assert_eq!(status.unwrap().code(), tonic::Code::PermissionDenied);
+    }
+    assert!(engine
+        .get_all_triples(with_token(triples(), "admin"))
+        .await
+        .is_ok());
+
+    // Graphs are deleted only where the token may write
+    let delete_graph = |graph: &str| DeleteGraphRequest {
+        namespace: "books".into(),
+        graph: graph.into(),
+    };
+    let status = engine
+        .delete_graph(with_token(
+            delete_graph("http://example.org/private"),
+            "agent",
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    let deleted = engine
+        .delete_graph(with_token(
+            delete_graph("http://example.org/agent-1"),
+            "agent",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(deleted.triples_removed, 1);
+}
+
+#[tokio::test]
+async fn test_sparql_queries_are_audited() {
+    use synapse_core::auth::{AnonymousPolicy, NamespacePermission};
+    use synapse_core::server::proto::{QueryLogRequest, SparqlRequest};
+
+    let storage_path = "/tmp/synapse_test_query_log";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+    // The query log needs `admin`, which anonymous callers only get explicitly
+    engine.auth.set_anonymous_policy(AnonymousPolicy {
+        permissions: NamespacePermission::all(),
+        ..AnonymousPolicy::full()
+    });
+    engine
+        .audit
+        .set_slow_query_threshold(std::time::Duration::from_secs(3600));
+
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![Triple {
+                subject: "http://example.org/a".into(),
+                predicate: "http://example.org/p".into(),
+                object: "http://example.org/b".into(),
+                provenance: None,
+                embedding: vec![],
+            }],
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+    let query = |query: &str| {
+        Request::new(SparqlRequest {
+            query: query.into(),
+            namespace: "default".into(),
+        })
+    };
+    engine
+        .query_sparql(query("SELECT * WHERE { ?s ?p ?o }"))
+        .await
+        .unwrap();
+    engine
+        .audit
+        .set_slow_query_threshold(std::time::Duration::ZERO);
+    engine
+        .query_sparql(query("ASK { ?s ?p ?o }"))
+        .await
+        .unwrap();
+
+    let log = |slow_only| {
+        Request::new(QueryLogRequest {
+            namespace: "default".into(),
+            slow_only,
+        })
+    };
+    let all = engine.get_query_log(log(false)).await.unwrap().into_inner();
+    assert_eq!(all.slow_query_threshold_ms, 0);
+    assert_eq!(all.queries.len(), 2);
+    assert_eq!(all.queries[0].query, "SELECT * WHERE { ?s ?p ?o }");
+    assert_eq!(all.queries[0].results, 1);
+    assert_eq!(all.queries[0].actor, "anonymous");
+    assert!(!all.queries[0].slow);
+    let slow = engine.get_query_log(log(true)).await.unwrap().into_inner();
+    assert_eq!(slow.queries.len(), 1);
+    assert_eq!(slow.queries[0].query, "ASK { ?s ?p ?o }");
+    assert!(slow.queries[0].slow);
+}
+
+#[tokio::test]
+async fn test_repeated_auth_failures_lock_out_a_token() {
+    use std::sync::Arc;
+    use synapse_core::auth::{NamespaceAuth, NamespacePermission};
+    use synapse_core::lockout::LockoutPolicy;
+    use synapse_core::server::certificate_interceptor;
+    use synapse_core::server::proto::EmptyRequest;
+
+    let storage_path = "/tmp/synapse_test_auth_lockout";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let mut engine = MySemanticEngine::new(storage_path);
+    let auth = NamespaceAuth::new();
+    auth.register_token("admin", vec!["*".into()], NamespacePermission::all());
+    auth.lockout.set_policy(LockoutPolicy::parse("2:60:600"));
+    engine.auth = Arc::new(auth);
+    let mut intercept = certificate_interceptor(engine.auth.clone());
+    let call = |token: &str| {
+        let mut request = Request::new(());
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    };
+
+    for _ in 0..2 {
+        let status = intercept(call("guess")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert_eq!(status.message(), "Invalid token");
+    }
+    let status = intercept(call("guess")).unwrap_err();
+    assert!(
+        status
+            .message()
+            .starts_with("Too many failed authentication attempts"),
+        "{}",
+        status.message()
+    );
+    assert!(intercept(call("admin")).is_ok());
+
+    let mut request = Request::new(EmptyRequest::default());
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer admin".parse().unwrap());
+    let metrics = engine.get_metrics(request).await.unwrap().into_inner();
+    assert!(metrics
+        .prometheus
+        .contains("synapse_auth_failures_total 2\n"));
+    assert!(metrics.prometheus.contains("synapse_auth_locked_out 1\n"));
+}
+
+#[tokio::test]
+async fn test_topology_mirrors_graph_changes() {
+    use synapse_core::store::{IngestTriple, SynapseStore, TraversalOptions};
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_topology";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    let triple = |s: &str, o: &str| IngestTriple {
+        subject: s.into(),
+        predicate: "http://ex.org/p".into(),
+        object: o.into(),
+        provenance: None,
+    };
+    store
+        .ingest_triples(vec![
+            triple("http://ex.org/a", "http://ex.org/b"),
+            triple("http://ex.org/b", "http://ex.org/c"),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(store.get_degree("http://ex.org/b"), 2);
+    let options = TraversalOptions {
+        depth: 2,
+        ..Default::default()
+    };
+    let reached = |store: &SynapseStore| -> Vec<String> {
+        store
+            .neighbors("http://ex.org/a", &options)
+            .into_iter()
+            .map(|n| n.uri)
+            .collect()
+    };
+    assert_eq!(
+        reached(&store),
+        vec!["<http://ex.org/b>", "<http://ex.org/c>"]
+    );
+    let incoming = TraversalOptions {
+        direction: "incoming".into(),
+        depth: 2,
+        ..Default::default()
+    };
+    let sources: Vec<String> = store
+        .neighbors("http://ex.org/c", &incoming)
+        .into_iter()
+        .map(|n| n.uri)
+        .collect();
+    assert_eq!(sources, vec!["<http://ex.org/b>", "<http://ex.org/a>"]);
+
+    let update = oxigraph::sparql::Update::parse(
+        "DELETE DATA { <http://ex.org/b> <http://ex.org/p> <http://ex.org/c> }",
+        None,
+    )
+    .unwrap();
+    store.update_sparql(update).unwrap();
+    assert_eq!(store.get_degree("http://ex.org/b"), 1);
+    assert_eq!(reached(&store), vec!["<http://ex.org/b>"]);
+
+    // Built from the graph when the namespace is opened again
+    store.flush().unwrap();
+    drop(store);
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    assert_eq!(store.get_degree("http://ex.org/a"), 1);
+    assert_eq!(reached(&store), vec!["<http://ex.org/b>"]);
+}
+
+#[tokio::test]
+async fn test_pagerank_ranks_search_results() {
+    use synapse_core::server::proto::{AnalyticsRequest, HybridSearchRequest, SearchMode};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_pagerank";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+
+    let cites = |s: &str, o: &str| Triple {
+        subject: format!("http://ex.org/paper-{}", s),
+        predicate: "http://ex.org/cites".into(),
+        object: format!("http://ex.org/paper-{}", o),
+        provenance: None,
+        embedding: vec![],
+    };
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![
+                cites("a", "hub"),
+                cites("b", "hub"),
+                cites("c", "hub"),
+                cites("hub", "a"),
+            ],
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+
+    let analytics = engine
+        .compute_analytics(Request::new(AnalyticsRequest {
+            namespace: "default".into(),
+            top_k: 2,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(analytics.algorithm, "pagerank");
+    assert_eq!(analytics.nodes, 4);
+    assert!(analytics.converged);
+    let top: Vec<&str> = analytics.top.iter().map(|n| n.uri.as_str()).collect();
+    assert_eq!(
+        top,
+        vec!["<http://ex.org/paper-hub>", "<http://ex.org/paper-a>"]
+    );
+
+    let search = |pagerank_weight: f32| {
+        Request::new(HybridSearchRequest {
+            query: "paper".into(),
+            namespace: "default".into(),
+            vector_k: 10,
+            graph_depth: 0,
+            mode: SearchMode::GraphOnly as i32,
+            limit: 0,
+            min_score: 0.0,
+            rerank: false,
+            pagerank_weight,
+        })
+    };
+    let ranked = engine
+        .hybrid_search(search(0.5))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(ranked.results[0].uri, "http://ex.org/paper-hub");
+    assert_eq!(ranked.results[1].uri, "http://ex.org/paper-a");
+    let invalid = engine.hybrid_search(search(2.0)).await;
+    assert_eq!(invalid.err().unwrap().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_centrality_analytics() {
+    use synapse_core::server::proto::AnalyticsRequest;
+
+    let storage_path = "/tmp/synapse_test_centrality";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+
+    let knows = |s: &str, o: &str| Triple {
+        subject: format!("http://ex.org/{}", s),
+        predicate: "http://ex.org/knows".into(),
+        object: format!("http://ex.org/{}", o),
+        provenance: None,
+        embedding: vec![],
+    };
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![knows("a", "hub"), knows("b", "hub"), knows("hub", "c")],
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+
+    let centrality = |uri: &str| {
+        Request::new(AnalyticsRequest {
+            namespace: "default".into(),
+            algorithm: "centrality".into(),
+            uri: uri.into(),
+            ..Default::default()
+        })
+    };
+    let all = engine
+        .compute_analytics(centrality(""))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(all.algorithm, "centrality");
+    assert_eq!(all.nodes, 4);
+    assert_eq!(all.iterations, 4);
+    assert!(all.converged);
+    let hub = &all.top[0];
+    assert_eq!(hub.uri, "<http://ex.org/hub>");
+    assert!((hub.betweenness - 1.0).abs() < 1e-9);
+    assert!((hub.degree_centrality - 1.0).abs() < 1e-9);
+    assert!((hub.closeness - 1.0).abs() < 1e-9);
+
+    let leaf = engine
+        .compute_analytics(centrality("http://ex.org/a"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(leaf.top.len(), 1);
+    assert_eq!(leaf.top[0].uri, "<http://ex.org/a>");
+    assert_eq!(leaf.top[0].betweenness, 0.0);
+    assert!(leaf.top[0].closeness < hub.closeness);
+
+    let missing = engine
+        .compute_analytics(centrality("http://ex.org/z"))
+        .await;
+    assert_eq!(missing.err().unwrap().code(), tonic::Code::NotFound);
+    let unknown = engine
+        .compute_analytics(Request::new(AnalyticsRequest {
+            algorithm: "katz".into(),
+            ..Default::default()
+        }))
+        .await;
+    assert_eq!(unknown.err().unwrap().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_get_communities() {
+    use synapse_core::server::proto::CommunitiesRequest;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_communities";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+
+    let knows = |s: &str, o: &str| Triple {
+        subject: format!("http://ex.org/{}", s),
+        predicate: "http://ex.org/knows".into(),
+        object: format!("http://ex.org/{}", o),
+        provenance: None,
+        embedding: vec![],
+    };
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![
+                knows("alice", "bob"),
+                knows("bob", "carol"),
+                knows("carol", "alice"),
+                knows("dave", "erin"),
+            ],
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+
+    let request = |algorithm: &str, uri: &str| {
+        Request::new(CommunitiesRequest {
+            namespace: "default".into(),
+            algorithm: algorithm.into(),
+            uri: uri.into(),
+            ..Default::default()
+        })
+    };
+    let all = engine
+        .get_communities(request("connected_components", ""))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(all.algorithm, "connected_components");
+    assert_eq!(all.community_count, 2);
+    assert_eq!(all.communities[0].size, 3);
+    assert_eq!(all.communities[1].size, 2);
+
+    let dave = engine
+        .get_communities(request("", "http://ex.org/dave"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(dave.algorithm, "label_propagation");
+    assert_eq!(dave.communities.len(), 1);
+    assert!(dave.communities[0]
+        .members
+        .contains(&"<http://ex.org/erin>".to_string()));
+    let store = engine.get_store("default").await.unwrap();
+    assert_eq!(
+        store.community_of("http://ex.org/dave"),
+        store.community_of("http://ex.org/erin")
+    );
+
+    let unknown = engine.get_communities(request("louvain", "")).await;
+    assert_eq!(unknown.err().unwrap().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_checkpoint_all_flushes_changed_namespaces() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_checkpoint_all";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples: vec![Triple {
+                subject: "http://a".into(),
+                predicate: "http://p".into(),
+                object: "http://b".into(),
+                provenance: None,
+                embedding: vec![],
+            }],
+        }))
+        .await
+        .unwrap();
+    let store = engine.get_store("default").await.unwrap();
+    assert!(store.has_unsaved_changes());
+
+    assert_eq!(engine.checkpoint_all().await, 1);
+    assert!(!store.has_unsaved_changes());
+    assert!(std::path::Path::new(storage_path)
+        .join("default/mirror.snapshot")
+        .exists());
+    // Nothing changed since, so nothing to do
+    assert_eq!(engine.checkpoint_all().await, 0);
+}
+
+#[tokio::test]
+async fn test_idle_namespaces_are_closed_past_the_cap() {
+    use std::sync::Arc;
+    use synapse_core::lru::StoreLru;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_store_lru";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    engine.store_lru = Arc::new(StoreLru::new(Some(2)));
+    let first = engine.get_store("first").await.unwrap();
+    first
+        .ingest_triples(vec![synapse_core::store::IngestTriple {
+            subject: "http://a".into(),
+            predicate: "http://p".into(),
+            object: "http://b".into(),
+            provenance: None,
+        }])
+        .await
+        .unwrap();
+    drop(first);
+    let held = engine.get_store("second").await.unwrap();
+    engine.get_store("third").await.unwrap();
+
+    // "first" is the least recently used and idle; "second" is still held
+    engine.evict_idle_stores();
+    assert!(!engine.stores.contains_key("first"));
+    assert!(engine.stores.contains_key("second"));
+    assert_eq!(engine.stores.len(), 2);
+    drop(held);
+
+    // Closed namespaces reopen from disk with their data
+    let reopened = engine.get_store("first").await.unwrap();
+    assert_eq!(reopened.store.len().unwrap(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_namespaces_reopen_while_being_evicted() {
+    use std::sync::Arc;
+    use synapse_core::lru::StoreLru;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_store_lru_race";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let mut engine = MySemanticEngine::new(storage_path);
+    engine.store_lru = Arc::new(StoreLru::new(Some(1)));
+    let tasks: Vec<_> = (0..8)
+        .map(|worker| {
+            let engine = engine.clone();
+            tokio::spawn(async move {
+                for i in 0..50 {
+                    let namespace = format!("ns-{}", (worker + i) % 3);
+                    engine.get_store(&namespace).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_flushes_vectors_and_mappings_below_thresholds() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_shutdown_flush";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let store = engine.get_store("default").await.unwrap();
+    store
+        .ingest_triples(vec![synapse_core::store::IngestTriple {
+            subject: "http://a".into(),
+            predicate: "http://p".into(),
+            object: "http://b".into(),
+            provenance: None,
+        }])
+        .await
+        .unwrap();
+    let id = store.get_id("http://a").unwrap();
+    // Too few writes for either auto-save
+    assert!(store.has_unsaved_changes());
+    drop(store);
+
+    assert_eq!(engine.shutdown().await, 1);
+
+    let reopened = synapse_core::store::SynapseStore::open("default", storage_path).unwrap();
+    assert_eq!(reopened.get_id("http://a"), Some(id));
+    assert_eq!(reopened.vector_store.as_ref().unwrap().len(), 1);
+    assert!(!reopened.has_unsaved_changes());
+}
+
+#[tokio::test]
+async fn test_maintenance_compacts_stale_namespaces_it_does_not_skip() {
+    use synapse_core::maintenance::MaintenancePolicy;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_maintenance";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    for namespace in ["active", "archive"] {
+        let store = engine.get_store(namespace).await.unwrap();
+        let triples = (0..10)
+            .map(|i| synapse_core::store::IngestTriple {
+                subject: format!("http://s{}", i),
+                predicate: "http://p".into(),
+                object: "http://o".into(),
+                provenance: None,
+            })
+            .collect();
+        store.ingest_triples(triples).await.unwrap();
+        let vs = store.vector_store.as_ref().unwrap();
+        assert!(vs.remove("http://s0|http://p|http://o"));
+        assert!(vs.remove("http://s1|http://p|http://o"));
+        assert_eq!(vs.stats(), (8, 2, 10));
+    }
+
+    let policy = MaintenancePolicy {
+        skip: vec!["arch*".into()],
+        ..Default::default()
+    };
+    let reports = engine.run_maintenance(&policy).await;
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].0, "active");
+    assert_eq!(reports[0].1.vectors_removed, Some(2));
+
+    let stats = |namespace: &'static str| {
+        let engine = engine.clone();
+        async move {
+            let store = engine.get_store(namespace).await.unwrap();
+            let stats = store.vector_store.as_ref().unwrap().stats();
+            stats
+        }
+    };
+    assert_eq!(stats("active").await, (8, 0, 8));
+    assert_eq!(stats("archive").await, (8, 2, 10));
+
+    // Below the ratio, the index is left as it is
+    let store = engine.get_store("active").await.unwrap();
+    assert!(store
+        .vector_store
+        .as_ref()
+        .unwrap()
+        .remove("http://s2|http://p|http://o"));
+    let reports = engine.run_maintenance(&policy).await;
+    assert_eq!(reports[0].1.vectors_removed, None);
+    assert_eq!(stats("active").await, (7, 1, 8));
+}
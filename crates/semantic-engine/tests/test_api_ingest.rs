@@ -0,0 +1,106 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::api::ApiSource;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serve two cursor-paginated pages, rejecting requests without the bearer token
+async fn serve_api() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                break;
+            };
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+
+            let (status, body) = if !request.contains("authorization: bearer secret") {
+                ("401 Unauthorized", "{}".to_string())
+            } else if request.starts_with("get /users?cursor=p2 ") {
+                (
+                    "200 OK",
+                    r#"{"data":[{"id":3,"name":"Cy","active":false}],"next":null}"#.to_string(),
+                )
+            } else {
+                (
+                    "200 OK",
+                    r#"{"data":[{"id":1,"name":"Ann","active":true,"team":"https://ex.org/team/core"},
+                                {"id":2,"name":"Bob","active":true}],"next":"p2"}"#
+                        .to_string(),
+                )
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    format!("http://{}/users", addr)
+}
+
+#[tokio::test]
+async fn test_api_sync_with_cursor_pagination() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_api_ingest";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let store = Arc::new(SynapseStore::open("test_api", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    let url = serve_api().await;
+
+    let source = ApiSource::parse(
+        &serde_json::json!({
+            "url": url,
+            "auth_token": "secret",
+            "pagination": { "type": "cursor", "cursor_path": "$.next", "param": "cursor" },
+            "mapping": {
+                "items": "$.data[*]",
+                "subject": "https://ex.org/user/{id}",
+                "class": "http://schema.org/Person",
+                "predicates": {
+                    "$.name": "http://schema.org/name",
+                    "$.active": "https://ex.org/active",
+                    "$.team": "http://schema.org/memberOf"
+                }
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let summary = engine.ingest_api(&source, "test_api").await.unwrap();
+    assert_eq!(summary.pages, 2);
+    assert_eq!(summary.items, 3);
+    assert!(summary.triples_added >= 9);
+
+    let results = store
+        .query_sparql(
+            "SELECT ?name WHERE { GRAPH ?g { <https://ex.org/user/3> <http://schema.org/name> ?name } }",
+        )
+        .unwrap();
+    assert!(
+        results.contains("Cy"),
+        "second page was not synced: {}",
+        results
+    );
+
+    let results = store
+        .query_sparql(
+            "SELECT ?team WHERE { GRAPH ?g { <https://ex.org/user/1> <http://schema.org/memberOf> ?team } }",
+        )
+        .unwrap();
+    assert!(results.contains("https://ex.org/team/core"));
+
+    // Without the token the endpoint refuses and the sync fails
+    let mut anonymous = source.clone();
+    anonymous.auth_token = None;
+    assert!(engine.ingest_api(&anonymous, "test_api").await.is_err());
+}
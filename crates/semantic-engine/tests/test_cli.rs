@@ -0,0 +1,132 @@
+use std::env;
+use synapse_core::auth::AnonymousPolicy;
+use synapse_core::cli::{self, Command};
+use synapse_core::server::MySemanticEngine;
+
+async fn run(engine: &MySemanticEngine, command: Command) -> anyhow::Result<String> {
+    let mut out = Vec::new();
+    cli::run(engine, command, &mut out).await?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[tokio::test]
+async fn test_one_off_commands_work_on_storage_directly() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_cli";
+    let _ = std::fs::remove_dir_all(root);
+    let sources = format!("{}/sources", root);
+    std::fs::create_dir_all(&sources).unwrap();
+    std::fs::write(
+        format!("{}/people.nt", sources),
+        "<http://ex.org/ann> <http://ex.org/knows> <http://ex.org/bob> .\n\
+         <http://ex.org/bob> <http://ex.org/knows> <http://ex.org/cy> .\n",
+    )
+    .unwrap();
+    std::fs::write(
+        format!("{}/more.ttl", sources),
+        "<http://ex.org/cy> <http://ex.org/knows> <http://ex.org/ann> .\n",
+    )
+    .unwrap();
+
+    let engine = MySemanticEngine::new(&format!("{}/graphs", root));
+    engine
+        .auth
+        .set_anonymous_policy(AnonymousPolicy::unrestricted());
+    let namespace = "cli".to_string();
+
+    let out = run(
+        &engine,
+        Command::Ingest {
+            path: format!("{}/people.nt", sources).into(),
+            namespace: namespace.clone(),
+            glob: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert!(out.contains("2 triples added"), "{}", out);
+
+    // A directory takes every matching file
+    let out = run(
+        &engine,
+        Command::Ingest {
+            path: sources.clone().into(),
+            namespace: namespace.clone(),
+            glob: Some("more.*".to_string()),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(out.contains("Ingested 1 of 1 files"), "{}", out);
+
+    let out = run(
+        &engine,
+        Command::Query {
+            sparql: "SELECT (COUNT(*) AS ?n) WHERE { GRAPH ?g { ?s <http://ex.org/knows> ?o } }"
+                .to_string(),
+            namespace: namespace.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    let rows: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert!(
+        rows[0]["?n"].as_str().unwrap().starts_with("\"3\""),
+        "{}",
+        out
+    );
+
+    let export = format!("{}/cli.nt", root);
+    let out = run(
+        &engine,
+        Command::Export {
+            namespace: namespace.clone(),
+            format: "ntriples".to_string(),
+            output: Some(export.clone().into()),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(out.starts_with("Exported "), "{}", out);
+    let exported = std::fs::read_to_string(&export).unwrap();
+    assert!(exported.contains("<http://ex.org/cy> <http://ex.org/knows> <http://ex.org/ann>"));
+
+    let out = run(
+        &engine,
+        Command::Compact {
+            namespace: namespace.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(out.starts_with("Compacted cli:"), "{}", out);
+
+    let out = run(
+        &engine,
+        Command::Backup {
+            namespace: namespace.clone(),
+            destination: Some(format!("{}/backups", root).into()),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(out.starts_with("Backed up namespace 'cli'"), "{}", out);
+    assert_eq!(
+        std::fs::read_dir(format!("{}/backups", root))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    let error = run(
+        &engine,
+        Command::Query {
+            sparql: "SELECT nonsense".to_string(),
+            namespace,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(!error.to_string().is_empty());
+    engine.shutdown().await;
+}
@@ -0,0 +1,44 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_python_file_structure() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_code_ingest";
+    let _ = std::fs::remove_dir_all(root);
+    std::fs::create_dir_all(root).unwrap();
+    let file = format!("{}/loader.py", root);
+    std::fs::write(
+        &file,
+        "import json\n\n\
+         def load(path):\n    \"\"\"Load a JSON file.\"\"\"\n    return parse(open(path).read())\n\n\
+         def parse(text):\n    return json.loads(text)\n",
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_code", &format!("{}/store", root)).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    let report = engine
+        .ingest_file(std::path::Path::new(&file), "test_code")
+        .await
+        .unwrap();
+    assert!(report.added > 0);
+
+    let file_uri = format!("urn:code:{}", file);
+    let calls = store
+        .query_sparql(&format!(
+            "SELECT ?g WHERE {{ GRAPH ?g {{ <{0}#load> <http://synapse.os/code/calls> <{0}#parse> }} \
+             GRAPH ?h {{ <{0}> <http://synapse.os/code/defines> <{0}#load> . \
+             <{0}> <http://synapse.os/code/imports> <urn:module:python:json> }} }}",
+            file_uri
+        ))
+        .unwrap();
+    assert!(calls.contains("urn:batch"), "{}", calls);
+
+    // Facts about a definition trace back to its lines
+    let records = store.get_provenance(&format!("{}#load", file_uri)).unwrap();
+    let span = records[0].provenance.span.as_ref().unwrap();
+    assert_eq!((span.line_start, span.line_end), (3, 5));
+}
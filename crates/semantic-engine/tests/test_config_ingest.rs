@@ -0,0 +1,53 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_yaml_and_toml_ingest() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_config_ingest";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_config", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+
+    let yaml_path = format!("{}/deploy.yaml", storage_path);
+    std::fs::write(
+        &yaml_path,
+        "kind: Deployment\nspec:\n  replicas: 3\n  containers:\n    - image: nginx\n",
+    )
+    .unwrap();
+    engine
+        .ingest_file(std::path::Path::new(&yaml_path), "test_config")
+        .await
+        .unwrap();
+
+    let toml_path = format!("{}/app.toml", storage_path);
+    std::fs::write(
+        &toml_path,
+        "[database]\nhost = \"db.internal\"\nport = 5432\n",
+    )
+    .unwrap();
+    engine
+        .ingest_file(std::path::Path::new(&toml_path), "test_config")
+        .await
+        .unwrap();
+
+    let result = store
+        .query_sparql(
+            "SELECT ?o WHERE { GRAPH ?g { \
+             <urn:config:deploy.yaml> <http://synapse.os/property/spec.containers.0.image> ?o } }",
+        )
+        .unwrap();
+    assert!(result.contains("nginx"), "got: {}", result);
+
+    let result = store
+        .query_sparql(
+            "SELECT ?o WHERE { GRAPH ?g { \
+             <urn:config:app.toml> <http://synapse.os/property/database.port> ?o } }",
+        )
+        .unwrap();
+    assert!(result.contains("5432"), "got: {}", result);
+}
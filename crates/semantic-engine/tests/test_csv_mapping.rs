@@ -0,0 +1,113 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_csv_sidecar_mapping() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_csv_mapping";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let csv_path = format!("{}/people.csv", storage_path);
+    std::fs::write(&csv_path, "id,name,age,notes\np1,Alice,42,internal\n").unwrap();
+    std::fs::write(
+        format!("{}/people.mapping.json", storage_path),
+        r#"{
+            "subject": "http://example.org/person/{id}",
+            "skip": ["notes"],
+            "columns": {
+                "name": { "predicate": "http://xmlns.com/foaf/0.1/name" },
+                "age": { "predicate": "http://xmlns.com/foaf/0.1/age", "datatype": "integer" }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_csv_mapping", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    let report = engine
+        .ingest_file(std::path::Path::new(&csv_path), "test_csv_mapping")
+        .await
+        .unwrap();
+    assert_eq!(report.added, 2);
+
+    let result = store
+        .query_sparql(
+            "SELECT ?age WHERE { GRAPH ?g { \
+             <http://example.org/person/p1> <http://xmlns.com/foaf/0.1/age> ?age \
+             FILTER(datatype(?age) = <http://www.w3.org/2001/XMLSchema#integer> && ?age > 40) } }",
+        )
+        .unwrap();
+    assert!(result.contains("42"), "got: {}", result);
+}
+
+#[tokio::test]
+async fn test_tsv_and_semicolon_ingest() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_csv_dialect";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_csv_dialect", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+
+    let tsv_path = format!("{}/cities.tsv", storage_path);
+    std::fs::write(&tsv_path, "id\tname\tcountry\nc1\tParis\tFR\n").unwrap();
+    let report = engine
+        .ingest_file(std::path::Path::new(&tsv_path), "test_csv_dialect")
+        .await
+        .unwrap();
+    assert_eq!(report.added, 2);
+
+    // Semicolon-delimited with decimal commas is sniffed, not split on commas
+    let csv_path = format!("{}/prices.csv", storage_path);
+    std::fs::write(&csv_path, "id;price;unit\np1;1,5;kg\np2;2,25;kg\n").unwrap();
+    let report = engine
+        .ingest_file(std::path::Path::new(&csv_path), "test_csv_dialect")
+        .await
+        .unwrap();
+    assert_eq!(report.added, 4);
+}
+
+#[tokio::test]
+async fn test_latin1_csv_is_transcoded() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_csv_encoding";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    // Windows-1252 export: "Zoë" and "Montréal"; the empty mapping keeps values literal
+    let csv_path = format!("{}/people.csv", storage_path);
+    std::fs::write(&csv_path, b"id,name,city\np1,\"Zo\xeb\",Montr\xe9al\n").unwrap();
+    std::fs::write(format!("{}/people.mapping.json", storage_path), "{}").unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_csv_encoding", storage_path).unwrap());
+    let report = IngestionEngine::new(store.clone())
+        .ingest_file(std::path::Path::new(&csv_path), "test_csv_encoding")
+        .await
+        .unwrap();
+    assert_eq!((report.added, report.malformed), (2, 0));
+
+    let results = store
+        .query_sparql("SELECT ?o WHERE { GRAPH ?g { ?s ?p ?o } }")
+        .unwrap();
+    assert!(
+        results.contains("Zoë") && results.contains("Montréal"),
+        "{}",
+        results
+    );
+
+    // An explicit encoding overrides detection
+    let forced = IngestionEngine::new(store.clone()).with_encoding(Some(encoding_rs::UTF_8));
+    let report = forced
+        .ingest_file(std::path::Path::new(&csv_path), "test_csv_encoding")
+        .await
+        .unwrap();
+    assert_eq!(report.added, 2);
+    let results = store
+        .query_sparql("SELECT ?o WHERE { GRAPH ?g { ?s ?p ?o } }")
+        .unwrap();
+    assert!(results.contains('\u{FFFD}'), "{}", results);
+}
@@ -0,0 +1,47 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_directory_ingest_with_glob() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_directory_ingest";
+    let _ = std::fs::remove_dir_all(root);
+    let docs = format!("{}/docs/nested", root);
+    std::fs::create_dir_all(&docs).unwrap();
+
+    std::fs::write(format!("{}/top.md", root), "# Top\n- item\n").unwrap();
+    std::fs::write(format!("{}/note.md", docs), "# Nested\nstatus: draft\n").unwrap();
+    std::fs::write(format!("{}/broken.json", docs), "{ not json").unwrap();
+    std::fs::write(format!("{}/ignored.bin", docs), "binary").unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_dir", &format!("{}/store", root)).unwrap());
+    let engine = IngestionEngine::new(store);
+
+    let mut updates = 0;
+    let report = engine
+        .ingest_directory(std::path::Path::new(root), None, "test_dir", |_| {
+            updates += 1
+        })
+        .await
+        .unwrap();
+    assert_eq!(report.files_total, 3);
+    assert_eq!(report.files_ingested, 2);
+    assert_eq!(report.files_failed, 1);
+    assert_eq!(updates, 3);
+    let failed = report.files.iter().find(|f| f.error.is_some()).unwrap();
+    assert!(failed.path.ends_with("broken.json"));
+
+    let report = engine
+        .ingest_directory(
+            std::path::Path::new(root),
+            Some("docs/**/*.md"),
+            "test_dir",
+            |_| {},
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.files_total, 1);
+    assert!(report.files[0].path.ends_with("note.md"));
+}
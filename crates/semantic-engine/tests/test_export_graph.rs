@@ -0,0 +1,113 @@
+use std::env;
+use synapse_core::store::{IngestTriple, Provenance, SynapseStore};
+
+fn triple(subject: &str, object: &str, source: &str) -> IngestTriple {
+    IngestTriple {
+        subject: subject.to_string(),
+        predicate: "http://example.org/knows".to_string(),
+        object: object.to_string(),
+        provenance: Some(Provenance {
+            source: source.to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            method: "manual".to_string(),
+            ..Default::default()
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_export_namespace_and_named_graph() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_export_graph";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let store = SynapseStore::open("test_export", storage_path).unwrap();
+
+    store
+        .ingest_triples(vec![triple(
+            "http://example.org/alice",
+            "http://example.org/bob",
+            "a",
+        )])
+        .await
+        .unwrap();
+    store
+        .ingest_triples(vec![triple("http://example.org/bob", "\"Carol\"", "b")])
+        .await
+        .unwrap();
+
+    let mut turtle = Vec::new();
+    let written = store
+        .export_graph(None, oxigraph::io::RdfFormat::Turtle, &mut turtle)
+        .unwrap();
+    let turtle = String::from_utf8(turtle).unwrap();
+    assert!(turtle.contains("<http://example.org/alice>"), "{}", turtle);
+    assert!(turtle.contains("\"Carol\""), "{}", turtle);
+    // Both facts plus the provenance metadata of their batches
+    assert!(written > 2);
+
+    // A single batch graph holds only its own fact
+    let graph = store.get_provenance("http://example.org/alice").unwrap()[0]
+        .graph
+        .clone();
+    let mut jsonld = Vec::new();
+    let written = store
+        .export_graph(
+            Some(&graph),
+            oxigraph::io::RdfFormat::JsonLd {
+                profile: oxigraph::io::JsonLdProfileSet::empty(),
+            },
+            &mut jsonld,
+        )
+        .unwrap();
+    assert_eq!(written, 1);
+    let jsonld: serde_json::Value = serde_json::from_slice(&jsonld).unwrap();
+    let text = jsonld.to_string();
+    assert!(text.contains("http://example.org/bob"), "{}", text);
+    assert!(!text.contains("Carol"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_export_graphml_and_gexf() {
+    use synapse_core::network::NetworkFormat;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_export_graphml";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let store = SynapseStore::open("test_export_graphml", storage_path).unwrap();
+
+    store
+        .ingest_triples(vec![
+            triple("http://example.org/alice", "http://example.org/bob", "a"),
+            IngestTriple {
+                subject: "http://example.org/alice".to_string(),
+                predicate: "http://example.org/age".to_string(),
+                object: "\"41\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string(),
+                provenance: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let mut graphml = Vec::new();
+    let (nodes, edges) = store
+        .export_graphml(NetworkFormat::GraphMl, &mut graphml)
+        .unwrap();
+    let graphml = String::from_utf8(graphml).unwrap();
+    assert!(nodes >= 2 && edges >= 1);
+    assert!(
+        graphml.contains(r#"attr.name="age" attr.type="long""#),
+        "{}",
+        graphml
+    );
+    assert!(graphml.contains(r#"<data key="edge_label">knows</data>"#));
+    assert!(graphml.contains(r#"<node id="http://example.org/bob">"#));
+
+    let mut gexf = Vec::new();
+    let (gexf_nodes, gexf_edges) = store
+        .export_graphml(NetworkFormat::Gexf, &mut gexf)
+        .unwrap();
+    assert_eq!((gexf_nodes, gexf_edges), (nodes, edges));
+    let gexf = String::from_utf8(gexf).unwrap();
+    assert!(gexf.contains(r#"<gexf xmlns="http://gexf.net/1.3""#));
+    assert!(gexf.contains(r#"value="41"/>"#), "{}", gexf);
+}
@@ -51,7 +51,7 @@ async fn test_node_type_filter() {
     });
     engine.ingest_triples(req).await.unwrap();
 
-    let store = engine.get_store(namespace).unwrap();
+    let store = engine.get_store(namespace).await.unwrap();
     let a_id = store.get_or_create_id("http://synapse.os/A");
 
     // Filter for Person
@@ -64,6 +64,7 @@ async fn test_node_type_filter() {
         limit_per_layer: 0,
         scoring_strategy: "default".into(),
         node_type_filter: "http://synapse.os/Person".into(), // B should match
+        page: None,
     });
 
     let resp = engine.get_neighbors(req_filter).await.unwrap().into_inner();
@@ -101,8 +102,9 @@ async fn test_auth_read_fail() {
     let namespace = "default";
 
     // Request with valid read token
-    let mut req_good = Request::new(synapse_core::server::proto::EmptyRequest {
+    let mut req_good = Request::new(synapse_core::server::proto::TriplesRequest {
         namespace: namespace.into(),
+        ..Default::default()
     });
     req_good
         .metadata_mut()
@@ -112,8 +114,9 @@ async fn test_auth_read_fail() {
     assert!(res.is_ok(), "Read should succeed with read permission");
 
     // Request with no permission
-    let mut req_bad = Request::new(synapse_core::server::proto::EmptyRequest {
+    let mut req_bad = Request::new(synapse_core::server::proto::TriplesRequest {
         namespace: namespace.into(),
+        ..Default::default()
     });
     req_bad
         .metadata_mut()
@@ -0,0 +1,61 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>Example Blog</title>
+  <item>
+    <title>Old post</title>
+    <link>https://example.org/old</link>
+    <pubDate>Mon, 02 Jun 2025 08:00:00 GMT</pubDate>
+    <description>Old content</description>
+  </item>
+  <item>
+    <title>New post</title>
+    <link>https://example.org/new</link>
+    <pubDate>Tue, 10 Jun 2025 08:00:00 GMT</pubDate>
+    <description><![CDATA[<p>Fresh <b>content</b></p>]]></description>
+  </item>
+</channel></rss>"#;
+
+#[tokio::test]
+async fn test_feed_ingest_since() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_feed_ingest";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let store = Arc::new(SynapseStore::open("test_feed", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    let url = "https://example.org/feed.xml";
+
+    let since = "2025-06-05T00:00:00Z".parse().unwrap();
+    let summary = engine
+        .ingest_feed_content(url, FEED, "test_feed", Some(since))
+        .await
+        .unwrap();
+    assert_eq!(summary.items_ingested, 1);
+    assert_eq!(
+        summary.latest.unwrap().to_rfc3339(),
+        "2025-06-10T08:00:00+00:00"
+    );
+    assert!(summary.chunks_indexed >= 1);
+
+    let result = store
+        .query_sparql(
+            "SELECT ?item ?title WHERE { GRAPH ?g { \
+             <https://example.org/feed.xml> <http://synapse.os/has_item> ?item . \
+             ?item <http://purl.org/dc/terms/title> ?title } }",
+        )
+        .unwrap();
+    assert!(result.contains("New post"), "got: {}", result);
+    assert!(!result.contains("Old post"), "got: {}", result);
+
+    // Polling again with the returned watermark ingests nothing new
+    let summary = engine
+        .ingest_feed_content(url, FEED, "test_feed", summary.latest)
+        .await
+        .unwrap();
+    assert_eq!(summary.items_ingested, 0);
+}
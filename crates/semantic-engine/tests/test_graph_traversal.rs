@@ -59,7 +59,7 @@ async fn test_graph_traversal_scoring() {
 
     // Get ID for "A"
     // Use store directly or resolve
-    let store = engine.get_store(namespace).unwrap();
+    let store = engine.get_store(namespace).await.unwrap();
     let a_id = store.get_or_create_id("http://synapse.os/A");
 
     // Test Default Strategy
@@ -72,6 +72,7 @@ async fn test_graph_traversal_scoring() {
         limit_per_layer: 0,
         scoring_strategy: "default".into(),
         node_type_filter: "".into(),
+        page: None,
     });
 
     let resp_default = engine
@@ -107,6 +108,7 @@ async fn test_graph_traversal_scoring() {
         limit_per_layer: 0,
         scoring_strategy: "degree".into(),
         node_type_filter: "".into(),
+        page: None,
     });
 
     let resp_degree = engine.get_neighbors(req_degree).await.unwrap().into_inner();
@@ -131,3 +133,62 @@ async fn test_graph_traversal_scoring() {
         "Hub should be penalized"
     );
 }
+
+#[tokio::test]
+async fn test_neighbor_reads_do_not_allocate_ids() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_traversal_ids";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = MySemanticEngine::new(storage_path);
+    let namespace = "default";
+    let triples = ["B", "C"]
+        .iter()
+        .map(|object| Triple {
+            subject: "A".into(),
+            predicate: "to".into(),
+            object: object.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples,
+            namespace: namespace.into(),
+        }))
+        .await
+        .unwrap();
+
+    let store = engine.get_store(namespace).await.unwrap();
+    let mapped = store.uri_to_id.read().unwrap().len();
+    let a_id = store.get_id("http://synapse.os/A").unwrap();
+    for scoring_strategy in ["default", "degree"] {
+        let neighbors = engine
+            .get_neighbors(Request::new(NodeRequest {
+                node_id: a_id,
+                namespace: namespace.into(),
+                direction: "outgoing".into(),
+                depth: 1,
+                scoring_strategy: scoring_strategy.into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .neighbors;
+        assert_eq!(neighbors.len(), 2);
+        // Neighbor IDs are the ones their plain IRIs were given at ingest
+        for neighbor in &neighbors {
+            let iri = neighbor.uri.trim_start_matches('<').trim_end_matches('>');
+            assert_eq!(Some(neighbor.node_id), store.get_id(iri));
+        }
+    }
+    assert_eq!(store.uri_to_id.read().unwrap().len(), mapped);
+
+    // Mappings from the ingest are written with the next flush
+    assert!(store.has_unsaved_changes());
+    store.flush().unwrap();
+    assert!(!store.has_unsaved_changes());
+    let reopened = synapse_core::store::SynapseStore::open(namespace, storage_path).unwrap();
+    assert_eq!(reopened.get_id("http://synapse.os/A"), Some(a_id));
+}
@@ -0,0 +1,80 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::pipeline::{IngestContext, PipelineConfig, Transform, TransformConfig};
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::{IngestTriple, SynapseStore};
+
+/// Tags every triple's provenance method, to check custom stages see the context
+struct MarkMethod;
+
+impl Transform for MarkMethod {
+    fn name(&self) -> &str {
+        "mark_method"
+    }
+
+    fn apply(
+        &self,
+        mut triples: Vec<IngestTriple>,
+        ctx: &IngestContext,
+    ) -> anyhow::Result<Vec<IngestTriple>> {
+        for triple in &mut triples {
+            if let Some(provenance) = triple.provenance.as_mut() {
+                provenance.method = format!("{}+marked", ctx.method);
+            }
+        }
+        Ok(triples)
+    }
+}
+
+#[tokio::test]
+async fn test_namespace_transforms() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_ingest_pipeline";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let csv_path = format!("{}/people.csv", storage_path);
+    std::fs::write(&csv_path, "id,name,secret\nann,Ann,x\n").unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_pipeline", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    engine
+        .configure_pipeline(PipelineConfig {
+            transforms: vec![
+                TransformConfig::RegexRewrite {
+                    pattern: "^urn:csv:people\\.csv:(.*)$".to_string(),
+                    replacement: "https://ex.org/person/$1".to_string(),
+                    positions: vec![],
+                },
+                TransformConfig::DropPredicate {
+                    pattern: "secret$".to_string(),
+                },
+            ],
+        })
+        .unwrap();
+    engine.register_transform(Arc::new(MarkMethod));
+
+    let report = engine
+        .ingest_file(std::path::Path::new(&csv_path), "test_pipeline")
+        .await
+        .unwrap();
+    assert_eq!(report.added, 1);
+
+    let results = store
+        .query_sparql("SELECT ?p ?o WHERE { GRAPH ?g { <https://ex.org/person/ann> ?p ?o } }")
+        .unwrap();
+    assert!(results.contains("Ann"), "{}", results);
+    assert!(!results.contains("secret"));
+
+    let results = store
+        .query_sparql("SELECT ?m WHERE { ?b <http://www.w3.org/ns/prov#wasGeneratedBy> ?m }")
+        .unwrap();
+    assert!(results.contains("csv_extractor+marked"), "{}", results);
+
+    // Configured transforms persist with the namespace; registered ones do not
+    drop(engine);
+    drop(store);
+    let reopened = SynapseStore::open("test_pipeline", storage_path).unwrap();
+    assert_eq!(reopened.pipeline.config().transforms.len(), 2);
+    assert_eq!(reopened.pipeline.stage_names().len(), 2);
+}
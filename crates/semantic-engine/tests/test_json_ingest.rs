@@ -0,0 +1,62 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_json_and_jsonld_ingest() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_json_ingest";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_json", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+
+    // Plain JSON: keys become predicates, nested objects become derived nodes
+    let json_path = format!("{}/service.json", storage_path);
+    std::fs::write(
+        &json_path,
+        r#"{"name": "billing", "owner": {"team": "payments"}, "ports": [80, 443]}"#,
+    )
+    .unwrap();
+    let report = engine
+        .ingest_file(std::path::Path::new(&json_path), "test_json")
+        .await
+        .unwrap();
+    assert!(report.added >= 5);
+
+    let result = store
+        .query_sparql(
+            "SELECT ?team WHERE { GRAPH ?g { \
+             <urn:json:service.json> <http://synapse.os/property/owner> ?owner . \
+             ?owner <http://synapse.os/property/team> ?team } }",
+        )
+        .unwrap();
+    assert!(result.contains("payments"), "got: {}", result);
+
+    // JSON-LD is parsed as RDF
+    let jsonld_path = format!("{}/person.jsonld", storage_path);
+    std::fs::write(
+        &jsonld_path,
+        r#"{
+            "@context": {"name": "http://xmlns.com/foaf/0.1/name"},
+            "@id": "http://example.org/alice",
+            "name": "Alice"
+        }"#,
+    )
+    .unwrap();
+    engine
+        .ingest_file(std::path::Path::new(&jsonld_path), "test_json")
+        .await
+        .unwrap();
+
+    let result = store
+        .query_sparql(
+            "SELECT ?name WHERE { GRAPH ?g { \
+             <http://example.org/alice> <http://xmlns.com/foaf/0.1/name> ?name \
+             FILTER(isLiteral(?name)) } }",
+        )
+        .unwrap();
+    assert!(result.contains("Alice"), "got: {}", result);
+}
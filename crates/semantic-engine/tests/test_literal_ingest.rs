@@ -26,10 +26,159 @@ async fn test_literal_ingest() {
     println!("SPARQL Result: {}", result_json);
 
     // If it was ingested as a URI, result_json will be "[]"
-    assert!(result_json.contains("Alice"), "Expected literal 'Alice' in results, got: {}", result_json);
+    assert!(
+        result_json.contains("Alice"),
+        "Expected literal 'Alice' in results, got: {}",
+        result_json
+    );
 
     // Also verify it is NOT a URI
     let query_uri = "SELECT ?o WHERE { <http://example.org/alice> <http://example.org/name> ?o . FILTER(isIRI(?o)) }";
     let result_uri = store.query_sparql(query_uri).unwrap();
-    assert_eq!(result_uri, "[]", "Expected no URI results, got: {}", result_uri);
+    assert_eq!(
+        result_uri, "[]",
+        "Expected no URI results, got: {}",
+        result_uri
+    );
+}
+
+#[tokio::test]
+async fn test_literals_populate_property_store() {
+    use synapse_core::properties::{IndexKind, PropertyValue};
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_literal_properties";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    let triple = |s: &str, p: &str, o: &str| IngestTriple {
+        subject: format!("http://example.org/{}", s),
+        predicate: format!("http://example.org/{}", p),
+        object: o.to_string(),
+        provenance: None,
+    };
+    let integer = |n: u32| format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#integer>", n);
+    store
+        .ingest_triples(vec![
+            triple("alice", "age", &integer(34)),
+            triple("bob", "age", &integer(52)),
+            triple("alice", "name", "\"Alice\""),
+            triple("alice", "knows", "http://example.org/bob"),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        store.node_properties("http://example.org/alice"),
+        vec![
+            (
+                "<http://example.org/age>".to_string(),
+                PropertyValue::Integer(34)
+            ),
+            (
+                "<http://example.org/name>".to_string(),
+                PropertyValue::Text("Alice".into())
+            ),
+        ]
+    );
+    let over_40 = |store: &SynapseStore| {
+        store.nodes_where("http://example.org/age", |v| {
+            v.as_f64().is_some_and(|age| age > 40.0)
+        })
+    };
+    assert_eq!(over_40(&store), vec!["<http://example.org/bob>"]);
+
+    let update = oxigraph::sparql::Update::parse(
+        "DELETE DATA { <http://example.org/bob> <http://example.org/age> 52 }",
+        None,
+    )
+    .unwrap();
+    store
+        .create_property_index("http://example.org/age", IndexKind::BTree)
+        .unwrap();
+    assert_eq!(over_40(&store), vec!["<http://example.org/bob>"]);
+    assert_eq!(
+        store.nodes_in_range("http://example.org/age", 30.0..40.0),
+        vec!["<http://example.org/alice>"]
+    );
+    assert_eq!(
+        store.nodes_with_value(
+            "http://example.org/name",
+            &PropertyValue::Text("Alice".into())
+        ),
+        vec!["<http://example.org/alice>"]
+    );
+    store.update_sparql(update).unwrap();
+    assert!(over_40(&store).is_empty());
+    assert!(store
+        .nodes_in_range("http://example.org/age", 40.0..)
+        .is_empty());
+
+    // Rebuilt from the graph when the namespace is opened again
+    store.flush().unwrap();
+    drop(store);
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    assert_eq!(
+        store.nodes_where("http://example.org/age", |_| true),
+        vec!["<http://example.org/alice>"]
+    );
+    assert_eq!(
+        store.properties.read().unwrap().indexes(),
+        vec![("<http://example.org/age>", IndexKind::BTree)]
+    );
+    assert!(store.drop_property_index("http://example.org/age").unwrap());
+}
+
+#[tokio::test]
+async fn test_mirrors_reload_from_snapshot_and_log() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_mirror_snapshot";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let dir = std::path::Path::new(storage_path).join("default");
+    let triple = |s: &str, o: &str| IngestTriple {
+        subject: format!("http://example.org/{}", s),
+        predicate: "http://example.org/rank".to_string(),
+        object: o.to_string(),
+        provenance: None,
+    };
+    let in_sync = |store: &SynapseStore| {
+        store.topology.read().unwrap().edge_count() == store.store.len().unwrap()
+    };
+
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    store
+        .ingest_triples(vec![
+            triple("a", "http://example.org/b"),
+            triple("a", "\"7\"^^<http://www.w3.org/2001/XMLSchema#integer>"),
+        ])
+        .await
+        .unwrap();
+    assert!(std::fs::metadata(dir.join("mirror.wal")).unwrap().len() > 0);
+    store.flush().unwrap();
+    assert!(dir.join("mirror.snapshot").exists());
+    assert_eq!(std::fs::metadata(dir.join("mirror.wal")).unwrap().len(), 0);
+
+    // Logged but never flushed: reopening replays the log, or rebuilds when the
+    // graph itself lost the write
+    store
+        .ingest_triples(vec![triple("c", "http://example.org/b")])
+        .await
+        .unwrap();
+    drop(store);
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    assert!(in_sync(&store));
+    assert_eq!(store.node_properties("http://example.org/a").len(), 1);
+    drop(store);
+
+    // An unreadable snapshot is rebuilt from the graph
+    std::fs::write(dir.join("mirror.snapshot"), b"not a snapshot").unwrap();
+    let store = SynapseStore::open("default", storage_path).unwrap();
+    assert!(in_sync(&store));
+    assert_eq!(
+        store.nodes_with_value(
+            "http://example.org/rank",
+            &synapse_core::properties::PropertyValue::Integer(7)
+        ),
+        vec!["<http://example.org/a>"]
+    );
 }
@@ -0,0 +1,122 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_markdown_sections() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_markdown_ingest";
+    let _ = std::fs::remove_dir_all(root);
+    std::fs::create_dir_all(root).unwrap();
+    let file = format!("{}/handbook.md", root);
+    std::fs::write(
+        &file,
+        "# Handbook\n\nHow we work.\n\n## Onboarding\n- get a laptop\n\n### Accounts\n- request email\n",
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_markdown", &format!("{}/store", root)).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    engine
+        .ingest_file(std::path::Path::new(&file), "test_markdown")
+        .await
+        .unwrap();
+
+    let result = store
+        .query_sparql(
+            "SELECT ?item WHERE { GRAPH ?g { \
+             <urn:note:handbook> <http://synapse.os/markdown/hasSection> ?h1 . \
+             ?h1 <http://synapse.os/markdown/hasSection> ?h2 . \
+             ?h2 <http://synapse.os/markdown/hasSection> ?h3 . \
+             ?h3 <http://synapse.os/markdown/hasListItem> ?item } }",
+        )
+        .unwrap();
+    assert!(result.contains("request email"), "{}", result);
+
+    // Each section is indexed under its own URI
+    if let Some(vs) = store.vector_store.as_ref() {
+        assert!(vs
+            .get_id("urn:note:handbook#handbook/onboarding/accounts")
+            .is_some());
+    }
+}
+
+#[tokio::test]
+async fn test_front_matter_is_queryable() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_markdown_front_matter";
+    let _ = std::fs::remove_dir_all(root);
+    std::fs::create_dir_all(root).unwrap();
+    let file = format!("{}/idea.md", root);
+    std::fs::write(
+        &file,
+        "---\ntitle: Graph Notes\ntags: [graphs, rust]\ndate: 2025-06-10\n---\nSome thoughts.\n",
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_front", &format!("{}/store", root)).unwrap());
+    IngestionEngine::new(store.clone())
+        .ingest_file(std::path::Path::new(&file), "test_front")
+        .await
+        .unwrap();
+
+    let result = store
+        .query_sparql(
+            "SELECT ?title WHERE { GRAPH ?g { \
+             ?doc <http://purl.org/dc/terms/title> ?title ; \
+                  <http://synapse.os/markdown/taggedWith> <urn:tag:rust> ; \
+                  <http://purl.org/dc/terms/date> ?date . \
+             FILTER(?date >= \"2025-01-01\"^^<http://www.w3.org/2001/XMLSchema#date>) } }",
+        )
+        .unwrap();
+    assert!(result.contains("Graph Notes"), "{}", result);
+}
+
+#[tokio::test]
+async fn test_vault_links_and_tags() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_markdown_vault";
+    let _ = std::fs::remove_dir_all(root);
+    let vault = format!("{}/vault", root);
+    std::fs::create_dir_all(format!("{}/people", vault)).unwrap();
+    std::fs::write(
+        format!("{}/Index.md", vault),
+        "Start at [[Projects]]. #home\n",
+    )
+    .unwrap();
+    std::fs::write(
+        format!("{}/Projects.md", vault),
+        "# Projects\nOwned by [[Grace Hopper|Grace]]. #work\n",
+    )
+    .unwrap();
+    std::fs::write(
+        format!("{}/people/Grace Hopper.md", vault),
+        "# Grace Hopper\n#people #work\n",
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_vault", &format!("{}/store", root)).unwrap());
+    IngestionEngine::new(store.clone())
+        .ingest_directory(std::path::Path::new(&vault), None, "test_vault", |_| {})
+        .await
+        .unwrap();
+
+    // Two hops from the index reach a note tagged #people
+    let result = store
+        .query_sparql(
+            "SELECT ?name WHERE { GRAPH ?g1 { <urn:note:index> <http://synapse.os/markdown/linksTo> ?p } \
+             GRAPH ?g2 { ?p <http://synapse.os/markdown/linksTo> ?person } \
+             GRAPH ?g3 { ?person <http://synapse.os/markdown/taggedWith> <urn:tag:people> ; \
+                                 <http://www.w3.org/2000/01/rdf-schema#label> ?name } }",
+        )
+        .unwrap();
+    assert!(result.contains("Grace Hopper"), "{}", result);
+
+    let work = store
+        .query_sparql(
+            "SELECT DISTINCT ?note WHERE { GRAPH ?g { ?note <http://synapse.os/markdown/taggedWith> <urn:tag:work> } }",
+        )
+        .unwrap();
+    assert_eq!(work.matches("urn:note:").count(), 2, "{}", work);
+}
@@ -0,0 +1,69 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::auth::NamespacePermission;
+use synapse_core::mcp::McpServer;
+use synapse_core::server::MySemanticEngine;
+
+#[tokio::test]
+async fn test_observation_is_searchable() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_mcp_adapter";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = Arc::new(MySemanticEngine::new(storage_path));
+    let mcp = McpServer::new(engine.clone());
+
+    let text = "The staging cluster runs out of disk every Monday.";
+    let added = mcp.add_observation(text, "standup").await.unwrap();
+    assert!(added.edges_added > 0, "{:?}", added);
+
+    let found = mcp.query_knowledge_graph(text, 5).await.unwrap();
+    assert!(found.results.len() <= 5);
+    let hit = found
+        .results
+        .iter()
+        .find(|r| r.uri.starts_with("urn:observation:"))
+        .expect("observation not found");
+
+    let source = engine
+        .get_store("default")
+        .await
+        .unwrap()
+        .query_sparql(&format!(
+            "SELECT ?o WHERE {{ GRAPH ?g {{ <{}> <http://synapse.os/property/source> ?o }} }}",
+            hit.uri
+        ))
+        .unwrap();
+    assert!(source.contains("standup"), "{}", source);
+}
+
+#[tokio::test]
+async fn test_namespace_requires_token() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_mcp_adapter_auth";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = Arc::new(MySemanticEngine::new(storage_path));
+    engine.auth.register_token(
+        "team",
+        vec!["team".to_string()],
+        NamespacePermission::default(),
+    );
+
+    let anonymous = McpServer::new(engine.clone()).with_namespace("team");
+    assert!(anonymous.add_observation("x", "test").await.is_err());
+    assert!(anonymous.query_knowledge_graph("x", 3).await.is_err());
+
+    let member = McpServer::new(engine.clone())
+        .with_namespace("team")
+        .with_token("team");
+    assert_eq!(member.namespace(), "team");
+    member
+        .add_observation("Deploys freeze on Fridays.", "wiki")
+        .await
+        .unwrap();
+    assert!(!member
+        .query_knowledge_graph("Deploys freeze on Fridays.", 3)
+        .await
+        .unwrap()
+        .results
+        .is_empty());
+}
@@ -2,7 +2,7 @@ use serde_json::json;
 use std::env;
 use std::sync::Arc;
 use synapse_core::mcp_stdio::McpStdioServer;
-use synapse_core::mcp_types::{DegreeResult, IngestToolResult, McpRequest};
+use synapse_core::mcp_types::{DegreeResult, IngestToolResult, McpRequest, PathToolResult};
 use synapse_core::server::MySemanticEngine;
 
 #[tokio::test]
@@ -83,3 +83,61 @@ async fn test_mcp_integration() {
         serde_json::from_str(&degree_json_str).expect("Failed to deserialize DegreeResult");
     assert_eq!(degree_result.degree, 3);
 }
+
+#[tokio::test]
+async fn test_mcp_find_path() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_mcp_path";
+    let _ = std::fs::remove_dir_all(storage_path);
+
+    let engine = Arc::new(MySemanticEngine::new(storage_path));
+    let server = McpStdioServer::new(engine);
+
+    let req_ingest = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(1)),
+        method: "tools/call".into(),
+        params: Some(json!({
+            "name": "ingest_triples",
+            "arguments": {
+                "namespace": "default",
+                "triples": [
+                    { "subject": "http://a", "predicate": "http://p", "object": "http://b" },
+                    { "subject": "http://b", "predicate": "http://p", "object": "http://c" },
+                    { "subject": "http://b", "predicate": "http://p", "object": "http://d" }
+                ]
+            }
+        })),
+    };
+    let resp_ingest = server.handle_request(req_ingest).await;
+    assert!(resp_ingest.error.is_none());
+
+    // From C to D, through B against the edge direction
+    let req_path = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(2)),
+        method: "tools/call".into(),
+        params: Some(json!({
+            "name": "find_path",
+            "arguments": {
+                "namespace": "default",
+                "from": "http://c",
+                "to": "http://d"
+            }
+        })),
+    };
+
+    let resp_path = server.handle_request(req_path).await;
+    let path_json_str = resp_path.result.as_ref().unwrap().get("content").unwrap()[0]
+        .get("text")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string();
+    let path_result: PathToolResult =
+        serde_json::from_str(&path_json_str).expect("Failed to deserialize PathToolResult");
+    assert!(path_result.found);
+    assert_eq!(path_result.nodes, vec!["http://c", "http://b", "http://d"]);
+    assert_eq!(path_result.edges[0].subject, "http://b");
+    assert_eq!(path_result.edges[0].object, "http://c");
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "ner")]
+
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+/// Run with `SYNAPSE_NER_MODEL` set to an exported model, e.g. from
+/// `optimum-cli export onnx --model dslim/bert-base-NER <dir>`
+#[tokio::test]
+#[ignore = "needs a NER model in SYNAPSE_NER_MODEL"]
+async fn test_markdown_entities_are_linked() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_ner";
+    let _ = std::fs::remove_dir_all(root);
+    std::fs::create_dir_all(root).unwrap();
+    let doc = format!("{}/notes.md", root);
+    std::fs::write(
+        &doc,
+        "# Meeting notes\n\nWe met Grace Hopper at Acme Corp.\nLater, Grace Hopper visited engineers in Lisbon.\n",
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_ner", &format!("{}/store", root)).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    engine
+        .ingest_file(std::path::Path::new(&doc), "test_ner")
+        .await
+        .unwrap();
+
+    let mentions = store
+        .query_sparql(
+            "SELECT ?chunk WHERE { GRAPH ?g { \
+             ?chunk <http://schema.org/mentions> <http://synapse.os/entity/acme_corp> } }",
+        )
+        .unwrap();
+    assert!(
+        mentions.contains("urn:note:notes#meeting-notes"),
+        "{}",
+        mentions
+    );
+
+    let about = store
+        .query_sparql(
+            "SELECT ?e ?type WHERE { GRAPH ?g { \
+             ?doc <http://schema.org/about> ?e . ?e a ?type } }",
+        )
+        .unwrap();
+    assert!(about.contains("grace_hopper"), "{}", about);
+    assert!(!about.contains("acme_corp"), "{}", about);
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_notebook_cells_are_ordered() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_notebook_ingest";
+    let _ = std::fs::remove_dir_all(root);
+    std::fs::create_dir_all(root).unwrap();
+    let file = format!("{}/analysis.ipynb", root);
+    std::fs::write(
+        &file,
+        r##"{
+          "metadata": { "language_info": { "name": "python" } },
+          "nbformat": 4,
+          "nbformat_minor": 5,
+          "cells": [
+            { "cell_type": "markdown", "metadata": {}, "source": ["# Sales analysis\n", "Quarterly numbers."] },
+            { "cell_type": "code", "metadata": {}, "outputs": [], "source": ["import csv\n", "def total(rows):\n", "    return sum(rows)\n"] },
+            { "cell_type": "code", "metadata": {}, "outputs": [], "source": ["def report():\n", "    return total([1, 2])\n"] }
+          ]
+        }"##,
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_notebook", &format!("{}/store", root)).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    engine
+        .ingest_file(std::path::Path::new(&file), "test_notebook")
+        .await
+        .unwrap();
+
+    let nb = format!("urn:notebook:{}", file);
+    let result = store
+        .query_sparql(&format!(
+            "SELECT ?g WHERE {{ GRAPH ?g {{ \
+             <{0}> <http://synapse.os/notebook/firstCell> <{0}#cell-0> . \
+             <{0}#cell-0> <http://synapse.os/notebook/next> <{0}#cell-1> . \
+             <{0}#cell-1> <http://synapse.os/notebook/next> <{0}#cell-2> . \
+             <{0}#cell-0> a <http://synapse.os/notebook/MarkdownCell> . \
+             <{0}#cell-1> <http://synapse.os/code/imports> <urn:module:python:csv> . \
+             <{0}#cell-1> <http://synapse.os/code/defines> <{0}#total> . \
+             <{0}#report> <http://synapse.os/code/calls> <{0}#total> }} }}",
+            nb
+        ))
+        .unwrap();
+    assert!(result.contains("urn:batch"), "{}", result);
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "object-store")]
+
+use std::env;
+use std::sync::Arc;
+use synapse_core::remote::RemoteStorage;
+use synapse_core::server::proto::semantic_engine_server::SemanticEngine;
+use synapse_core::server::proto::{IngestRequest, Triple};
+use synapse_core::server::MySemanticEngine;
+use tonic::Request;
+
+#[tokio::test]
+async fn test_namespaces_round_trip_through_object_store() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let bucket = "/tmp/synapse_test_object_store_bucket";
+    let first_path = "/tmp/synapse_test_object_store_a";
+    let second_path = "/tmp/synapse_test_object_store_b";
+    for path in [bucket, first_path, second_path] {
+        let _ = std::fs::remove_dir_all(path);
+    }
+    let remote = Arc::new(RemoteStorage::from_url(&format!("file://{}", bucket)).unwrap());
+
+    let mut first = MySemanticEngine::new(first_path);
+    first.remote = Some(remote.clone());
+    first
+        .ingest_triples(Request::new(IngestRequest {
+            namespace: "default".into(),
+            triples: vec![Triple {
+                subject: "http://a".into(),
+                predicate: "http://p".into(),
+                object: "http://b".into(),
+                provenance: None,
+                embedding: vec![],
+            }],
+        }))
+        .await
+        .unwrap();
+    first.shutdown().await;
+    assert_eq!(
+        remote.list_dirs("namespaces").await.unwrap(),
+        vec!["default"]
+    );
+    // The staging copy is gone and never listed as a namespace
+    assert_eq!(first.namespace_names(), vec!["default"]);
+
+    let mut second = MySemanticEngine::new(second_path);
+    second.remote = Some(remote.clone());
+    assert_eq!(second.restore_from_remote().await.unwrap(), vec!["default"]);
+    let store = second.get_store("default").await.unwrap();
+    assert_eq!(store.store.len().unwrap(), 1);
+    // Namespaces already on disk are left alone
+    assert!(second.restore_from_remote().await.unwrap().is_empty());
+
+    assert!(remote.delete_dir("namespaces/default").await.unwrap() > 0);
+    assert!(remote.list_dirs("namespaces").await.unwrap().is_empty());
+}
@@ -1,4 +1,6 @@
 use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
 use synapse_core::store::{IngestTriple, Provenance, SynapseStore};
 
 #[tokio::test]
@@ -14,6 +16,7 @@ async fn test_provenance_persistence() {
         source: "test_source".to_string(),
         timestamp: "2024-01-01T00:00:00Z".to_string(),
         method: "manual".to_string(),
+        ..Default::default()
     };
 
     let triple = IngestTriple {
@@ -23,9 +26,39 @@ async fn test_provenance_persistence() {
         provenance: Some(prov.clone()),
     };
 
-    let (nodes, _edges) = store.ingest_triples(vec![triple]).await.unwrap();
-    // In our implementation, we add edges. We don't distinguish node addition in the return value currently (always returns added, 0).
-    assert_eq!(nodes, 1);
+    let report = store.ingest_triples(vec![triple.clone()]).await.unwrap();
+    assert_eq!(report.added, 1);
+
+    // A later batch asserts the fact in its own graph; a repeat within that batch is
+    // reported as a duplicate, invalid IRIs as malformed
+    let broken = IngestTriple {
+        subject: "http://example.org/not an iri".to_string(),
+        ..triple.clone()
+    };
+    let report = store
+        .ingest_triples(vec![triple.clone(), triple.clone(), broken])
+        .await
+        .unwrap();
+    assert_eq!(
+        (report.added, report.duplicates, report.malformed),
+        (1, 1, 1)
+    );
+
+    // Facts without provenance share the default graph
+    let plain = IngestTriple {
+        provenance: None,
+        ..triple
+    };
+    assert_eq!(
+        store
+            .ingest_triples(vec![plain.clone()])
+            .await
+            .unwrap()
+            .added,
+        1
+    );
+    let report = store.ingest_triples(vec![plain]).await.unwrap();
+    assert_eq!((report.added, report.duplicates), (0, 1));
 
     // Verify triples are in a named graph
     // SPARQL: SELECT ?g WHERE { GRAPH ?g { <http://example.org/alice> <http://example.org/knows> <http://example.org/bob> } }
@@ -44,3 +77,41 @@ async fn test_provenance_persistence() {
     let result_meta = store.query_sparql(query_meta).unwrap();
     assert_ne!(result_meta, "[]");
 }
+
+#[tokio::test]
+async fn test_file_provenance_locations() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_file_provenance";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let csv_path = format!("{}/people.csv", storage_path);
+    let content = "id,name\nann,Ann\nbob,Bob\n";
+    std::fs::write(&csv_path, content).unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_file_provenance", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    engine
+        .ingest_file(std::path::Path::new(&csv_path), "test_file_provenance")
+        .await
+        .unwrap();
+
+    let records = store.get_provenance("urn:csv:people.csv:bob").unwrap();
+    assert_eq!(records.len(), 1);
+    let provenance = &records[0].provenance;
+    assert_eq!(provenance.method, "csv_extractor");
+    assert!(provenance.path.as_deref().unwrap().ends_with("people.csv"));
+    assert_eq!(provenance.sha256.as_ref().unwrap().len(), 64);
+
+    let span = provenance.span.as_ref().unwrap();
+    assert_eq!((span.line_start, span.line_end), (3, 3));
+    let (start, end) = (span.byte_start.unwrap(), span.byte_end.unwrap());
+    assert_eq!(&content[start as usize..end as usize], "bob,Bob\n");
+    assert_eq!(
+        records[0].facts,
+        vec![(
+            "urn:csv:prop:name".to_string(),
+            "http://synapse.os/Bob".to_string()
+        )]
+    );
+}
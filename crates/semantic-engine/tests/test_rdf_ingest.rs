@@ -0,0 +1,67 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_rdf_files_keep_literals_and_blank_nodes() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_rdf_ingest";
+    let _ = std::fs::remove_dir_all(storage_path);
+    std::fs::create_dir_all(storage_path).unwrap();
+
+    let ttl_path = format!("{}/people.ttl", storage_path);
+    std::fs::write(
+        &ttl_path,
+        r#"@prefix ex: <http://ex.org/> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+ex:ann ex:name "Ann"@en ;
+    ex:age "41"^^xsd:integer ;
+    ex:address [ ex:city "Lyon" ] .
+<#local> ex:note "relative" .
+"#,
+    )
+    .unwrap();
+    let nt_path = format!("{}/more.nt", storage_path);
+    std::fs::write(
+        &nt_path,
+        "<http://ex.org/bob> <http://ex.org/knows> <http://ex.org/ann> .\n",
+    )
+    .unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_rdf", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    let report = engine
+        .ingest_file(std::path::Path::new(&ttl_path), "test_rdf")
+        .await
+        .unwrap();
+    assert_eq!((report.added, report.malformed), (5, 0));
+    let report = engine
+        .ingest_file(std::path::Path::new(&nt_path), "test_rdf")
+        .await
+        .unwrap();
+    assert_eq!(report.added, 1);
+
+    let results = store
+        .query_sparql(
+            "SELECT ?city WHERE { GRAPH ?g { \
+             <http://ex.org/ann> <http://ex.org/name> \"Ann\"@en ; \
+             <http://ex.org/age> 41 ; \
+             <http://ex.org/address> ?a . ?a <http://ex.org/city> ?city \
+             FILTER(isBlank(?a)) } }",
+        )
+        .unwrap();
+    assert!(results.contains("Lyon"), "{}", results);
+
+    let results = store
+        .query_sparql(
+            "SELECT ?o WHERE { GRAPH ?g { <http://synapse.os/rdf/people.ttl#local> ?p ?o } }",
+        )
+        .unwrap();
+    assert!(results.contains("relative"), "{}", results);
+
+    let results = store
+        .query_sparql("SELECT ?s WHERE { GRAPH ?g { ?s <http://ex.org/knows> ?o } }")
+        .unwrap();
+    assert!(results.contains("http://ex.org/bob"), "{}", results);
+}
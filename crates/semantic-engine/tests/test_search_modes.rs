@@ -0,0 +1,234 @@
+use std::env;
+use synapse_core::server::proto::semantic_engine_server::SemanticEngine;
+use synapse_core::server::proto::{HybridSearchRequest, IngestRequest, SearchMode, Triple};
+use synapse_core::server::MySemanticEngine;
+use tonic::Request;
+
+fn triple(s: &str, p: &str, o: &str) -> Triple {
+    Triple {
+        subject: s.into(),
+        predicate: p.into(),
+        object: o.into(),
+        provenance: None,
+        embedding: vec![],
+    }
+}
+
+fn search(query: &str, mode: SearchMode) -> HybridSearchRequest {
+    HybridSearchRequest {
+        query: query.into(),
+        namespace: "default".into(),
+        vector_k: 10,
+        graph_depth: 0,
+        mode: mode as i32,
+        limit: 0,
+        min_score: 0.0,
+        rerank: false,
+        pagerank_weight: 0.0,
+    }
+}
+
+#[tokio::test]
+async fn test_graph_mode_min_score_and_limit() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_search_modes";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![
+                triple(
+                    "http://ex.org/ada",
+                    "http://ex.org/name",
+                    "\"Ada Lovelace\"",
+                ),
+                triple(
+                    "http://ex.org/ada",
+                    "http://ex.org/wrote",
+                    "http://ex.org/notes",
+                ),
+                triple(
+                    "http://ex.org/charles",
+                    "http://ex.org/name",
+                    "\"Charles Babbage\"",
+                ),
+                triple(
+                    "http://ex.org/ada_byron",
+                    "http://ex.org/name",
+                    "\"Ada Byron\"",
+                ),
+            ],
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+
+    // Graph mode matches literals without embeddings; full matches rank first
+    let results = engine
+        .hybrid_search(Request::new(search("ada lovelace", SearchMode::GraphOnly)))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    let uris: Vec<&str> = results.iter().map(|r| r.uri.as_str()).collect();
+    assert_eq!(uris, vec!["http://ex.org/ada", "http://ex.org/ada_byron"]);
+    assert_eq!(results[0].score, 1.0);
+
+    let mut request = search("ada lovelace", SearchMode::GraphOnly);
+    request.min_score = 0.75;
+    let results = engine
+        .hybrid_search(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(results.len(), 1);
+
+    // Expansion reaches the notes; limit keeps only the best hit
+    let mut request = search("lovelace", SearchMode::GraphOnly);
+    request.graph_depth = 1;
+    let results = engine
+        .hybrid_search(Request::new(request.clone()))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert!(results.iter().any(|r| r.uri.contains("notes")));
+    request.limit = 1;
+    let results = engine
+        .hybrid_search(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].uri, "http://ex.org/ada");
+
+    // Reranking lifts the resource whose name matches the query
+    let mut request = search("charles babbage", SearchMode::VectorOnly);
+    request.rerank = true;
+    let results = engine
+        .hybrid_search(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(results[0].uri, "http://ex.org/charles");
+}
+
+#[tokio::test]
+async fn test_search_results_carry_labels_and_provenance() {
+    use synapse_core::server::proto::Provenance;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_search_details";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+
+    let provenance = Provenance {
+        source: "biographies.md".into(),
+        timestamp: "2024-01-01T00:00:00Z".into(),
+        method: "test".into(),
+    };
+    let mut triples = vec![
+        triple(
+            "http://ex.org/ada",
+            "http://www.w3.org/2000/01/rdf-schema#label",
+            "\"Ada Lovelace\"",
+        ),
+        triple(
+            "http://ex.org/ada",
+            "http://ex.org/wrote",
+            "\"Notes on the analytical engine\"",
+        ),
+    ];
+    for t in &mut triples {
+        t.provenance = Some(provenance.clone());
+    }
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples,
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+
+    let results = engine
+        .hybrid_search(Request::new(search(
+            "analytical engine",
+            SearchMode::GraphOnly,
+        )))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    let hit = &results[0];
+    assert_eq!(hit.uri, "http://ex.org/ada");
+    assert_eq!(hit.content, "Ada Lovelace");
+    assert_eq!(hit.labels, vec!["Ada Lovelace"]);
+    let matched = hit.matched_triple.as_ref().unwrap();
+    assert_eq!(matched.predicate, "http://ex.org/wrote");
+    assert_eq!(matched.object, "Notes on the analytical engine");
+    assert!(!hit.graph.is_empty());
+    assert_eq!(hit.source, "biographies.md");
+}
+
+#[tokio::test]
+async fn test_graph_expansion_stops_at_cycles() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_search_expansion_cycles";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let engine = MySemanticEngine::new(storage_path);
+
+    engine
+        .ingest_triples(Request::new(IngestRequest {
+            triples: vec![
+                triple("http://ex.org/ada", "http://ex.org/name", "\"Ada\""),
+                triple(
+                    "http://ex.org/ada",
+                    "http://ex.org/wrote",
+                    "http://ex.org/notes",
+                ),
+                triple(
+                    "http://ex.org/notes",
+                    "http://ex.org/about",
+                    "http://ex.org/engine",
+                ),
+                triple(
+                    "http://ex.org/engine",
+                    "http://ex.org/inspired",
+                    "http://ex.org/ada",
+                ),
+                triple(
+                    "http://ex.org/notes",
+                    "http://ex.org/cites",
+                    "http://ex.org/engine",
+                ),
+            ],
+            namespace: "default".into(),
+        }))
+        .await
+        .unwrap();
+
+    let mut request = search("ada", SearchMode::GraphOnly);
+    request.graph_depth = 10;
+    let results = engine
+        .hybrid_search(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    let mut uris: Vec<&str> = results.iter().map(|r| r.uri.as_str()).collect();
+    assert_eq!(uris[0], "http://ex.org/ada");
+    uris.sort();
+    // Each resource once, as a plain IRI, and no literals
+    assert_eq!(
+        uris,
+        vec![
+            "http://ex.org/ada",
+            "http://ex.org/engine",
+            "http://ex.org/notes"
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "sql")]
+
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::sql::SqlMapping;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+async fn seed(url: &str) {
+    sqlx::any::install_default_drivers();
+    let pool = sqlx::AnyPool::connect(url).await.unwrap();
+    for statement in [
+        "CREATE TABLE teams (id INTEGER PRIMARY KEY, name TEXT)",
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER, team_id INTEGER, password_hash TEXT)",
+        "INSERT INTO teams VALUES (1, 'Core')",
+        "INSERT INTO users VALUES (1, 'Ann', 41, 1, 'x'), (2, 'Bob', NULL, 1, 'y')",
+    ] {
+        sqlx::query(statement).execute(&pool).await.unwrap();
+    }
+    pool.close().await;
+}
+
+#[tokio::test]
+async fn test_sqlite_tables_to_triples() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_sql_ingest";
+    let db_path = "/tmp/synapse_test_sql_ingest.db";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let _ = std::fs::remove_file(db_path);
+    let url = format!("sqlite://{}?mode=rwc", db_path);
+    seed(&url).await;
+
+    let store = Arc::new(SynapseStore::open("test_sql", storage_path).unwrap());
+    let engine = IngestionEngine::new(store.clone());
+    let mapping = SqlMapping::parse(
+        r#"
+base: "http://ex.org/"
+tables:
+  users:
+    class: "http://schema.org/Person"
+    subject: "http://ex.org/user/{id}"
+    skip: [password_hash]
+    columns:
+      name: { predicate: "http://schema.org/name" }
+  teams:
+    class: "http://schema.org/Organization"
+"#,
+    )
+    .unwrap();
+
+    let summary = engine.ingest_sql(&url, &mapping, "test_sql").await.unwrap();
+    assert_eq!(summary.tables, 2);
+    assert_eq!(summary.rows, 3);
+
+    let results = store
+        .query_sparql(
+            "SELECT ?name ?age WHERE { GRAPH ?g { \
+             <http://ex.org/user/1> a <http://schema.org/Person> ; \
+             <http://schema.org/name> ?name ; <http://ex.org/users#age> ?age } }",
+        )
+        .unwrap();
+    assert!(results.contains("Ann"), "{}", results);
+    assert!(results.contains("41"), "{}", results);
+
+    // Key columns stay implicit, skipped columns and NULLs produce nothing
+    let results = store
+        .query_sparql("SELECT ?p WHERE { GRAPH ?g { <http://ex.org/user/2> ?p ?o } }")
+        .unwrap();
+    assert!(results.contains("users#team_id"));
+    assert!(!results.contains("users#id\""));
+    assert!(!results.contains("password_hash"));
+    assert!(!results.contains("users#age"));
+
+    let results = store
+        .query_sparql(
+            "SELECT ?name WHERE { GRAPH ?g { <http://ex.org/teams/1> <http://ex.org/teams#name> ?name } }",
+        )
+        .unwrap();
+    assert!(results.contains("Core"));
+}
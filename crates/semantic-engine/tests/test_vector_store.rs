@@ -61,3 +61,135 @@ async fn test_vector_synchronization() {
     // Clean up
     env::remove_var("MOCK_EMBEDDINGS");
 }
+
+#[tokio::test]
+async fn test_bulk_ingest_embeds_every_new_triple_once() {
+    use synapse_core::store::Provenance;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_vector_bulk";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let store = SynapseStore::open("test_vector_bulk", storage_path).unwrap();
+
+    let provenance = |source: &str| Provenance {
+        source: source.to_string(),
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+        method: "test".to_string(),
+        ..Default::default()
+    };
+    // Enough triples for several embedding batches, spread over two provenance batches
+    let mut triples: Vec<IngestTriple> = (0..1000)
+        .map(|i| IngestTriple {
+            subject: format!("http://example.org/n{}", i),
+            predicate: "http://example.org/next".to_string(),
+            object: format!("http://example.org/n{}", i + 1),
+            provenance: Some(provenance(if i % 2 == 0 { "even" } else { "odd" })),
+        })
+        .collect();
+    // Repeated within the call, in the same provenance batch
+    triples.push(triples[0].clone());
+
+    let report = store.ingest_triples(triples).await.unwrap();
+    assert_eq!(report.added, 1000);
+    assert_eq!(report.duplicates, 1);
+    assert_eq!(report.vectors_created, 1000);
+    assert_eq!(store.vector_store.as_ref().unwrap().len(), 1000);
+    assert_eq!(
+        store
+            .topology
+            .read()
+            .unwrap()
+            .degree("<http://example.org/n1>"),
+        2
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_reingest_reports_only_new_vectors() {
+    use synapse_core::store::Provenance;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_vector_reingest";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let store =
+        std::sync::Arc::new(SynapseStore::open("test_vector_reingest", storage_path).unwrap());
+
+    let triples = |source: &str| -> Vec<IngestTriple> {
+        (0..300)
+            .map(|i| IngestTriple {
+                subject: format!("http://example.org/n{}", i),
+                predicate: "http://example.org/next".to_string(),
+                object: format!("http://example.org/n{}", i + 1),
+                provenance: Some(Provenance {
+                    source: source.to_string(),
+                    timestamp: "2024-01-01T00:00:00Z".to_string(),
+                    method: "test".to_string(),
+                    ..Default::default()
+                }),
+            })
+            .collect()
+    };
+    // Two sources at once: every fact is new to its graph, but each vector is indexed once
+    let (first, second) = tokio::join!(
+        store.ingest_triples(triples("a")),
+        store.ingest_triples(triples("b"))
+    );
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_eq!(first.added + second.added, 600);
+    assert_eq!(first.vectors_created + second.vectors_created, 300);
+
+    // The same facts under another source are stored, but need no new vectors
+    let report = store.ingest_triples(triples("c")).await.unwrap();
+    assert_eq!(report.added, 300);
+    assert_eq!(report.vectors_created, 0);
+    assert_eq!(store.vector_store.as_ref().unwrap().len(), 300);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_compaction_runs_alongside_adds_and_searches() {
+    use std::sync::Arc;
+    use synapse_core::vector_store::VectorStore;
+
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let vs = Arc::new(VectorStore::new("test_vector_compact_concurrent").unwrap());
+    let items = |range: std::ops::Range<usize>| {
+        range
+            .map(|i| {
+                (
+                    format!("k{}", i),
+                    format!("text {}", i),
+                    serde_json::json!({}),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    vs.add_batch(items(0..200)).await.unwrap();
+    for i in 0..100 {
+        assert!(vs.remove(&format!("k{}", i)));
+    }
+
+    let compaction = {
+        let vs = vs.clone();
+        tokio::task::spawn_blocking(move || vs.compact().unwrap())
+    };
+    let mut writers = Vec::new();
+    for batch in 0..4 {
+        let vs = vs.clone();
+        writers.push(tokio::spawn(async move {
+            vs.add_batch(items(200 + batch * 25..225 + batch * 25))
+                .await
+                .unwrap();
+            vs.search("text", 5).await.unwrap()
+        }));
+    }
+    for writer in writers {
+        assert!(!writer.await.unwrap().is_empty());
+    }
+    assert_eq!(compaction.await.unwrap(), 100);
+
+    // Vectors added during the rebuild survive it
+    assert_eq!(vs.len(), 200);
+    assert_eq!(vs.stats(), (200, 0, 200));
+    assert!(vs.get_id("k250").is_some());
+    assert!(vs.get_id("k50").is_none());
+}
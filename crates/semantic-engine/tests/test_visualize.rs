@@ -0,0 +1,60 @@
+use std::env;
+use synapse_core::store::{IngestTriple, SynapseStore};
+use synapse_core::visualize::{DiagramFormat, Subgraph};
+
+fn triple(s: &str, p: &str, o: &str) -> IngestTriple {
+    IngestTriple {
+        subject: s.to_string(),
+        predicate: p.to_string(),
+        object: o.to_string(),
+        provenance: None,
+    }
+}
+
+#[tokio::test]
+async fn test_bounded_neighborhood() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let storage_path = "/tmp/synapse_test_visualize";
+    let _ = std::fs::remove_dir_all(storage_path);
+    let store = SynapseStore::open("test_visualize", storage_path).unwrap();
+
+    store
+        .ingest_triples(vec![
+            triple(
+                "http://ex.org/alice",
+                "http://ex.org/knows",
+                "http://ex.org/bob",
+            ),
+            triple(
+                "http://ex.org/bob",
+                "http://ex.org/knows",
+                "http://ex.org/carol",
+            ),
+            triple(
+                "http://ex.org/dave",
+                "http://ex.org/knows",
+                "http://ex.org/alice",
+            ),
+            triple("http://ex.org/alice", "http://ex.org/name", "\"Alice\""),
+        ])
+        .await
+        .unwrap();
+
+    // One hop reaches bob, dave (incoming) and the name literal, but not carol
+    let subgraph = Subgraph::collect(&store, "http://ex.org/alice", 1, 50);
+    assert_eq!(subgraph.nodes.len(), 4);
+    assert_eq!(subgraph.edges.len(), 3);
+    let mermaid = subgraph.render(DiagramFormat::Mermaid);
+    assert!(mermaid.contains("[\"dave\"]"), "{}", mermaid);
+    assert!(!mermaid.contains("carol"), "{}", mermaid);
+
+    let subgraph = Subgraph::collect(&store, "http://ex.org/alice", 2, 50);
+    assert!(subgraph
+        .render(DiagramFormat::Dot)
+        .contains("label=\"carol\""));
+    assert!(!subgraph.truncated);
+
+    let subgraph = Subgraph::collect(&store, "http://ex.org/alice", 2, 2);
+    assert_eq!(subgraph.nodes.len(), 2);
+    assert!(subgraph.truncated);
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use synapse_core::store::SynapseStore;
+use synapse_core::watcher::{FolderWatcher, WatchConfig};
+
+fn triple_count(store: &SynapseStore) -> usize {
+    store.store.len().unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_watch_folder_ingests_new_files() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_watch_folder";
+    let _ = std::fs::remove_dir_all(root);
+    let inbox = format!("{}/inbox", root);
+    std::fs::create_dir_all(&inbox).unwrap();
+
+    let store = Arc::new(SynapseStore::open("test_watch", &format!("{}/store", root)).unwrap());
+    let watcher = FolderWatcher::new();
+    let mut config = WatchConfig::new("test_watch", &inbox);
+    config.exclude = vec!["drafts/**".to_string()];
+    config.debounce_ms = 100;
+    watcher.watch(config, store.clone()).unwrap();
+    assert_eq!(watcher.list(Some("test_watch")).len(), 1);
+
+    std::fs::create_dir_all(format!("{}/drafts", inbox)).unwrap();
+    std::fs::write(format!("{}/drafts/wip.md", inbox), "# Draft\n- skipped\n").unwrap();
+    std::fs::write(format!("{}/note.md", inbox), "# Note\n- watched item\n").unwrap();
+
+    let mut ingested = false;
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if triple_count(&store) > 0 {
+            ingested = true;
+            break;
+        }
+    }
+    assert!(ingested, "watched file was not ingested");
+
+    // Let any pending events settle, then check the excluded draft never landed
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    let result = store
+        .query_sparql("SELECT ?s ?o WHERE { GRAPH ?g { ?s ?p ?o } }")
+        .unwrap();
+    assert!(result.contains("watched item"), "{}", result);
+    assert!(
+        !result.contains("skipped"),
+        "excluded file was ingested: {}",
+        result
+    );
+
+    assert!(watcher.unwatch("test_watch", std::path::Path::new(&inbox)));
+    assert!(watcher.list(None).is_empty());
+}
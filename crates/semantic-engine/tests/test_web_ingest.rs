@@ -0,0 +1,59 @@
+use std::env;
+use std::sync::Arc;
+use synapse_core::ingest::crawler::CrawledPage;
+use synapse_core::ingest::readability;
+use synapse_core::ingest::IngestionEngine;
+use synapse_core::store::SynapseStore;
+
+#[tokio::test]
+async fn test_web_page_sections() {
+    env::set_var("MOCK_EMBEDDINGS", "true");
+    let root = "/tmp/synapse_test_web_ingest";
+    let _ = std::fs::remove_dir_all(root);
+
+    let html = r#"<html><head><title>Caching | Example Blog</title></head><body>
+        <nav><a href="/">Home</a> <a href="/archive">Archive</a></nav>
+        <article>
+          <h1>Caching</h1>
+          <h2>Eviction</h2><p>LRU drops the least recently used entry.</p>
+          <h2>Invalidation</h2><p>Expire entries when the source changes.</p>
+        </article>
+        <footer>Subscribe to our newsletter</footer>
+        </body></html>"#;
+    let article = readability::extract(html);
+    let url = "https://blog.example/caching".to_string();
+    let page = CrawledPage {
+        url: url.clone(),
+        text: article.text(),
+        title: article.title,
+        sections: article.sections,
+        links: vec![],
+        depth: 0,
+    };
+
+    let store = Arc::new(SynapseStore::open("test_web", root).unwrap());
+    let summary = IngestionEngine::new(store.clone())
+        .ingest_web_pages(&[page], "test_web")
+        .await
+        .unwrap();
+    assert_eq!(summary.pages, 1);
+
+    let result = store
+        .query_sparql(&format!(
+            "SELECT ?title ?label WHERE {{ GRAPH ?g {{ \
+             <{url}> <http://purl.org/dc/terms/title> ?title ; \
+                     <http://synapse.os/markdown/hasSection> ?s . \
+             ?s <http://www.w3.org/2000/01/rdf-schema#label> ?label . \
+             ?c <http://purl.org/dc/terms/isPartOf> ?s }} }}"
+        ))
+        .unwrap();
+    assert!(result.contains("Caching"), "{}", result);
+    assert!(result.contains("Eviction"), "{}", result);
+    assert!(result.contains("Invalidation"), "{}", result);
+    assert!(!result.contains("newsletter"), "{}", result);
+
+    if let Some(vs) = store.vector_store.as_ref() {
+        assert_eq!(summary.chunks_indexed, 2);
+        assert!(vs.get_id(&format!("{}#chunk-0", url)).is_some());
+    }
+}